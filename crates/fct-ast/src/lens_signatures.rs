@@ -315,6 +315,28 @@ impl LensSignatureRegistry {
             }],
         ));
 
+        // Template data is passed as arbitrary keyword arguments.
+        self.register(LensSignature::variadic(
+            "template".to_string(),
+            FacetType::Primitive(PrimitiveType::String),
+            FacetType::Primitive(PrimitiveType::String),
+            vec![
+                ParameterSignature {
+                    name: "escape".to_string(),
+                    param_type: FacetType::Primitive(PrimitiveType::String),
+                    required: false,
+                },
+                ParameterSignature {
+                    name: "partials".to_string(),
+                    param_type: FacetType::Map(Box::new(FacetType::Primitive(
+                        PrimitiveType::String,
+                    ))),
+                    required: false,
+                },
+            ],
+            Some(FacetType::Any),
+        ));
+
         self.register(LensSignature::new(
             "keys".to_string(),
             FacetType::Map(Box::new(FacetType::Any)),
@@ -376,6 +398,164 @@ impl LensSignatureRegistry {
             vec![],
         ));
 
+        self.register(LensSignature::new(
+            "length".to_string(),
+            FacetType::List(Box::new(FacetType::Any)),
+            FacetType::Primitive(PrimitiveType::Int),
+            vec![],
+        ));
+
+        // Logic lenses: comparisons, booleans and conditionals for `when` gates
+        let bool_type = || FacetType::Primitive(PrimitiveType::Bool);
+        let value_param = |name: &str, param_type: FacetType| ParameterSignature {
+            name: name.to_string(),
+            param_type,
+            required: true,
+        };
+        for name in ["eq", "ne"] {
+            self.register(LensSignature::new(
+                name.to_string(),
+                FacetType::Any,
+                bool_type(),
+                vec![value_param("value", FacetType::Any)],
+            ));
+        }
+        let orderable = FacetType::Union(vec![
+            FacetType::Primitive(PrimitiveType::Number),
+            FacetType::Primitive(PrimitiveType::String),
+        ]);
+        for name in ["gt", "gte", "lt", "lte"] {
+            self.register(LensSignature::new(
+                name.to_string(),
+                orderable.clone(),
+                bool_type(),
+                vec![value_param("value", orderable.clone())],
+            ));
+        }
+        self.register(LensSignature::new(
+            "contains".to_string(),
+            FacetType::Union(vec![
+                FacetType::Primitive(PrimitiveType::String),
+                FacetType::List(Box::new(FacetType::Any)),
+                FacetType::Map(Box::new(FacetType::Any)),
+            ]),
+            bool_type(),
+            vec![value_param("value", FacetType::Any)],
+        ));
+        self.register(LensSignature::new(
+            "is_empty".to_string(),
+            FacetType::Union(vec![
+                FacetType::Primitive(PrimitiveType::String),
+                FacetType::List(Box::new(FacetType::Any)),
+                FacetType::Map(Box::new(FacetType::Any)),
+                FacetType::Primitive(PrimitiveType::Null),
+            ]),
+            bool_type(),
+            vec![],
+        ));
+        self.register(LensSignature::new(
+            "not".to_string(),
+            bool_type(),
+            bool_type(),
+            vec![],
+        ));
+        for name in ["and", "or"] {
+            self.register(LensSignature::variadic(
+                name.to_string(),
+                bool_type(),
+                bool_type(),
+                vec![value_param("other", bool_type())],
+                Some(bool_type()),
+            ));
+        }
+        self.register(LensSignature::new(
+            "if_else".to_string(),
+            bool_type(),
+            FacetType::Any,
+            vec![
+                value_param("then", FacetType::Any),
+                value_param("else", FacetType::Any),
+            ],
+        ));
+
+        // Layout compression lenses: section strategies sized in FACET Units
+        let text_type = || FacetType::Primitive(PrimitiveType::String);
+        let target_param = |required: bool| ParameterSignature {
+            name: "target".to_string(),
+            param_type: FacetType::Primitive(PrimitiveType::Int),
+            required,
+        };
+        self.register(LensSignature::new(
+            "truncate_sentences".to_string(),
+            text_type(),
+            text_type(),
+            vec![target_param(true)],
+        ));
+        self.register(LensSignature::new(
+            "keep_head_tail".to_string(),
+            text_type(),
+            text_type(),
+            vec![
+                target_param(true),
+                ParameterSignature {
+                    name: "marker".to_string(),
+                    param_type: text_type(),
+                    required: false,
+                },
+            ],
+        ));
+        for name in ["collapse_whitespace", "strip_markdown"] {
+            self.register(LensSignature::new(
+                name.to_string(),
+                text_type(),
+                text_type(),
+                vec![target_param(false)],
+            ));
+        }
+        self.register(LensSignature::new(
+            "drop_lowest".to_string(),
+            FacetType::List(Box::new(FacetType::Any)),
+            FacetType::List(Box::new(FacetType::Any)),
+            vec![target_param(true), value_param("field", text_type())],
+        ));
+
+        // Formatting lenses: structured data to prompt text
+        let string_type = || FacetType::Primitive(PrimitiveType::String);
+        let optional_param = |name: &str, param_type: FacetType| ParameterSignature {
+            name: name.to_string(),
+            param_type,
+            required: false,
+        };
+        self.register(LensSignature::new(
+            "to_markdown_table".to_string(),
+            FacetType::List(Box::new(FacetType::Any)),
+            string_type(),
+            vec![optional_param(
+                "columns",
+                FacetType::List(Box::new(string_type())),
+            )],
+        ));
+        for name in ["to_bullets", "to_numbered"] {
+            self.register(LensSignature::new(
+                name.to_string(),
+                FacetType::List(Box::new(FacetType::Any)),
+                string_type(),
+                vec![optional_param("field", string_type())],
+            ));
+        }
+        self.register(LensSignature::new(
+            "xml_wrap".to_string(),
+            FacetType::Any,
+            string_type(),
+            vec![value_param("tag", string_type())],
+        ));
+        self.register(LensSignature::new(
+            "to_yaml".to_string(),
+            FacetType::Any,
+            string_type(),
+            vec![],
+        ));
+
         // Type conversion lenses
         self.register(LensSignature::new(
             "to_string".to_string(),
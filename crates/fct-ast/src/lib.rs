@@ -4,6 +4,9 @@ use serde::{Deserialize, Serialize};
 pub type OrderedMap<K, V> = IndexMap<K, V>;
 pub const FACET_VERSION: &str = "2.1.3";
 pub const POLICY_VERSION: &str = "1";
+/// `metadata.profile` labels of the Core and Hypervisor profiles (§2.1).
+pub const CORE_PROFILE: &str = "core";
+pub const HYPERVISOR_PROFILE: &str = "hypervisor";
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Span {
@@ -28,6 +31,7 @@ pub enum FacetNode {
     Assistant(FacetBlock),
     Vars(FacetBlock),
     VarTypes(FacetBlock),
+    Types(TypesNode),
     Context(FacetBlock),
     Policy(FacetBlock),
     Import(ImportNode),
@@ -75,6 +79,21 @@ pub struct Parameter {
     pub span: Span,
 }
 
+/// Named type aliases declared in a `@types` block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypesNode {
+    pub aliases: Vec<TypeAliasNode>,
+    pub span: Span,
+}
+
+/// Single `Name: <type>` alias declaration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypeAliasNode {
+    pub name: String,
+    pub type_node: TypeNode,
+    pub span: Span,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TestBlock {
     pub name: String,
@@ -250,6 +269,41 @@ pub enum TypeNode {
     },
 }
 
+/// Built-in FTS primitive names; any other `TypeNode::Primitive` name is an alias reference.
+pub const PRIMITIVE_TYPE_NAMES: &[&str] = &["string", "int", "float", "bool", "null", "any"];
+
+impl TypeNode {
+    /// Names of `@types` aliases referenced by this type, in first-seen order.
+    pub fn alias_refs(&self) -> Vec<&str> {
+        let mut refs = Vec::new();
+        self.collect_alias_refs(&mut refs);
+        refs
+    }
+
+    fn collect_alias_refs<'a>(&'a self, refs: &mut Vec<&'a str>) {
+        match self {
+            TypeNode::Primitive(name) => {
+                if !PRIMITIVE_TYPE_NAMES.contains(&name.as_str()) && !refs.contains(&name.as_str())
+                {
+                    refs.push(name.as_str());
+                }
+            }
+            TypeNode::Struct(fields) => {
+                for field in fields.values() {
+                    field.collect_alias_refs(refs);
+                }
+            }
+            TypeNode::List(inner) | TypeNode::Map(inner) => inner.collect_alias_refs(refs),
+            TypeNode::Union(types) => {
+                for ty in types {
+                    ty.collect_alias_refs(refs);
+                }
+            }
+            TypeNode::Image { .. } | TypeNode::Audio { .. } | TypeNode::Embedding { .. } => {}
+        }
+    }
+}
+
 // Export type system and lens signature system
 pub mod lens_signatures;
 pub mod types;
@@ -284,6 +338,27 @@ mod tests {
         assert!(node.path_segments.is_empty());
     }
 
+    #[test]
+    fn type_node_alias_refs_skip_primitives_and_dedupe() {
+        let mut fields = OrderedMap::new();
+        fields.insert(
+            "home".to_string(),
+            TypeNode::Primitive("Address".to_string()),
+        );
+        fields.insert(
+            "tags".to_string(),
+            TypeNode::List(Box::new(TypeNode::Primitive("string".to_string()))),
+        );
+        fields.insert(
+            "work".to_string(),
+            TypeNode::Union(vec![
+                TypeNode::Primitive("Address".to_string()),
+                TypeNode::Primitive("null".to_string()),
+            ]),
+        );
+        assert_eq!(TypeNode::Struct(fields).alias_refs(), vec!["Address"]);
+    }
+
     #[test]
     fn map_node_preserves_insertion_order() {
         let mut entries = OrderedMap::new();
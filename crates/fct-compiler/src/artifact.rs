@@ -0,0 +1,724 @@
+//! Execution Artifact (Appendix F): the guard decisions of one run, chained
+//! into a tamper-evident hash chain seeded by the payload metadata.
+
+use crate::error::{CompilerError, CompilerResult};
+use fct_engine::ExecutionGuardDecision;
+use fct_policy::HostContext;
+use fct_render::{CanonicalPayload, GuardDecision};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Execution Artifact of one run or guard session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionArtifact {
+    pub metadata: ArtifactMetadata,
+    pub provenance: Provenance,
+    /// Host signature over the artifact; `null` when unsigned.
+    pub attestation: Option<Attestation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactMetadata {
+    pub facet_version: String,
+    pub host_profile_id: String,
+    pub document_hash: String,
+    pub policy_hash: Option<String>,
+    pub policy_version: String,
+    /// Host context the guards saw, when one was supplied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_context: Option<RecordedHostContext>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedHostContext {
+    pub hash: String,
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Guard decisions, resequenced from 1.
+    pub events: Vec<GuardDecision>,
+    pub hash_chain: HashChain,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HashChain {
+    pub algo: String,
+    pub head: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attestation {
+    pub algo: String,
+    pub key_id: String,
+    pub sig: String,
+}
+
+impl ExecutionArtifact {
+    /// The artifact as a JSON value.
+    pub fn to_json(&self) -> CompilerResult<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+}
+
+pub fn build_execution_artifact(
+    payload: &CanonicalPayload,
+    decisions: &[GuardDecision],
+) -> CompilerResult<ExecutionArtifact> {
+    build_artifact(payload, decisions, None, None)
+}
+
+pub fn build_execution_artifact_with_attestation(
+    payload: &CanonicalPayload,
+    decisions: &[GuardDecision],
+    attestation: Option<serde_json::Value>,
+) -> CompilerResult<ExecutionArtifact> {
+    build_artifact(payload, decisions, None, attestation)
+}
+
+/// Execution artifact for a run whose guards saw `host_context`; the context and
+/// its hash are recorded under `metadata.host_context`.
+pub fn build_execution_artifact_with_host_context(
+    payload: &CanonicalPayload,
+    decisions: &[GuardDecision],
+    host_context: Option<&HostContext>,
+) -> CompilerResult<ExecutionArtifact> {
+    build_artifact(payload, decisions, host_context, None)
+}
+
+fn build_artifact(
+    payload: &CanonicalPayload,
+    decisions: &[GuardDecision],
+    host_context: Option<&HostContext>,
+    attestation: Option<serde_json::Value>,
+) -> CompilerResult<ExecutionArtifact> {
+    let host_context = match host_context {
+        Some(host_context) => Some(RecordedHostContext {
+            hash: host_context.hash().to_string(),
+            value: host_context.to_json()?,
+        }),
+        None => None,
+    };
+    let metadata = ArtifactMetadata {
+        facet_version: payload.metadata.facet_version.clone(),
+        host_profile_id: payload.metadata.host_profile_id.clone(),
+        document_hash: payload.metadata.document_hash.clone(),
+        policy_hash: payload.metadata.policy_hash.clone(),
+        policy_version: payload.metadata.policy_version.clone(),
+        host_context,
+    };
+
+    let events = normalize_guard_decisions(decisions);
+    let mut prev = sha256_prefixed(canonicalize_json(&hash_chain_seed_input(payload))?.as_bytes());
+    for event in &events {
+        let chain_input = serde_json::json!({
+            "prev": prev,
+            "event": serde_json::to_value(event)?,
+        });
+        prev = sha256_prefixed(canonicalize_json(&chain_input)?.as_bytes());
+    }
+
+    let attestation = match attestation {
+        Some(value) => Some(validate_attestation_envelope(value)?),
+        None => None,
+    };
+
+    Ok(ExecutionArtifact {
+        metadata,
+        provenance: Provenance {
+            events,
+            hash_chain: HashChain {
+                algo: "sha256".to_string(),
+                head: prev,
+            },
+        },
+        attestation,
+    })
+}
+
+/// Input of the hash chain's first link (`h0`).
+pub fn hash_chain_seed_input(payload: &CanonicalPayload) -> serde_json::Value {
+    serde_json::json!({
+        "facet_version": payload.metadata.facet_version,
+        "host_profile_id": payload.metadata.host_profile_id,
+        "document_hash": payload.metadata.document_hash,
+        "policy_hash": payload.metadata.policy_hash,
+        "policy_version": payload.metadata.policy_version,
+        "profile": payload.metadata.profile,
+        "mode": payload.metadata.mode,
+    })
+}
+
+/// Engine decisions followed by renderer decisions, resequenced from 1.
+pub fn merge_guard_decisions(
+    engine_decisions: &[ExecutionGuardDecision],
+    render_decisions: &[GuardDecision],
+) -> Vec<GuardDecision> {
+    let mut merged = Vec::with_capacity(engine_decisions.len() + render_decisions.len());
+
+    for decision in engine_decisions {
+        merged.push(GuardDecision {
+            seq: 0,
+            op: decision.op.clone(),
+            name: decision.name.clone(),
+            effect_class: decision.effect_class.clone(),
+            mode: decision.mode.clone(),
+            decision: decision.decision.clone(),
+            policy_rule_id: decision.policy_rule_id.clone(),
+            input_hash: decision.input_hash.clone(),
+            error_code: decision.error_code.clone(),
+        });
+    }
+    merged.extend(render_decisions.iter().cloned());
+
+    normalize_guard_decisions(&merged)
+}
+
+pub fn normalize_guard_decisions(decisions: &[GuardDecision]) -> Vec<GuardDecision> {
+    decisions
+        .iter()
+        .enumerate()
+        .map(|(idx, d)| {
+            let mut out = d.clone();
+            out.seq = idx + 1;
+            out
+        })
+        .collect()
+}
+
+/// RFC 8785 (JCS) canonical form of `value`.
+pub fn canonicalize_json(value: &serde_json::Value) -> CompilerResult<String> {
+    Ok(serde_json_canonicalizer::to_string(value)?)
+}
+
+pub fn sha256_prefixed(bytes: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(bytes))
+}
+
+fn validate_attestation_envelope(attestation: serde_json::Value) -> CompilerResult<Attestation> {
+    let obj = attestation
+        .as_object()
+        .ok_or_else(|| CompilerError::Attestation("Attestation must be an object".to_string()))?;
+
+    if obj.len() != 3 {
+        return Err(CompilerError::Attestation(
+            "Attestation must contain exactly: algo, key_id, sig".to_string(),
+        ));
+    }
+
+    let field = |key: &str| {
+        obj.get(key).and_then(|v| v.as_str()).ok_or_else(|| {
+            CompilerError::Attestation(format!("Attestation.{} must be a string", key))
+        })
+    };
+    let algo = field("algo")?;
+    let key_id = field("key_id")?;
+    let sig = field("sig")?;
+
+    let namespaced_algo = algo.starts_with("x.")
+        && algo.split('.').count() >= 3
+        && !algo.split('.').any(|seg| seg.is_empty());
+    if algo != "ed25519" && !namespaced_algo {
+        return Err(CompilerError::Attestation(
+            "Attestation.algo must be 'ed25519' or namespaced 'x.<host>.<algo>'".to_string(),
+        ));
+    }
+    if key_id.trim().is_empty() {
+        return Err(CompilerError::Attestation(
+            "Attestation.key_id must be non-empty".to_string(),
+        ));
+    }
+    if sig.is_empty()
+        || !sig
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(CompilerError::Attestation(
+            "Attestation.sig must be non-empty base64url (unpadded)".to_string(),
+        ));
+    }
+
+    Ok(Attestation {
+        algo: algo.to_string(),
+        key_id: key_id.to_string(),
+        sig: sig.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fct_ast::{OrderedMap, ScalarValue, ValueNode};
+    use fct_render::Metadata;
+
+    fn sample_payload() -> CanonicalPayload {
+        CanonicalPayload {
+            metadata: Metadata {
+                facet_version: "2.1.3".to_string(),
+                profile: "hypervisor".to_string(),
+                mode: "exec".to_string(),
+                host_profile_id: "local.default.v1".to_string(),
+                policy_version: "1".to_string(),
+                document_hash: "sha256:abc".to_string(),
+                policy_hash: Some("sha256:def".to_string()),
+                budget_units: 32000,
+                target_provider_id: "generic-llm".to_string(),
+                reserved_tool_units: 0,
+                reserved_output_units: 0,
+            },
+            tools: Vec::new(),
+            messages: Vec::new(),
+        }
+    }
+
+    fn artifact_json(payload: &CanonicalPayload, decisions: &[GuardDecision]) -> serde_json::Value {
+        build_execution_artifact(payload, decisions)
+            .expect("artifact must build")
+            .to_json()
+            .expect("artifact json")
+    }
+
+    #[test]
+    fn build_execution_artifact_emits_hash_chain_head() {
+        let artifact = artifact_json(&sample_payload(), &[]);
+        assert_eq!(
+            artifact
+                .pointer("/provenance/hash_chain/algo")
+                .expect("algo field"),
+            "sha256"
+        );
+        assert!(artifact
+            .pointer("/provenance/hash_chain/head")
+            .and_then(|v| v.as_str())
+            .is_some());
+    }
+
+    #[test]
+    fn host_context_is_recorded_only_when_supplied() {
+        let context = HostContext::new(ValueNode::Map(OrderedMap::from_iter([(
+            "acme".to_string(),
+            ValueNode::Scalar(ScalarValue::Bool(true)),
+        )])))
+        .expect("context");
+
+        let artifact =
+            build_execution_artifact_with_host_context(&sample_payload(), &[], Some(&context))
+                .unwrap()
+                .to_json()
+                .unwrap();
+        assert_eq!(
+            artifact.pointer("/metadata/host_context/hash").unwrap(),
+            context.hash()
+        );
+        assert_eq!(
+            artifact
+                .pointer("/metadata/host_context/value/acme")
+                .unwrap(),
+            true
+        );
+
+        let plain = artifact_json(&sample_payload(), &[]);
+        assert!(plain.pointer("/metadata/host_context").is_none());
+    }
+
+    #[test]
+    fn merge_guard_decisions_resequences_all_events() {
+        let engine = vec![ExecutionGuardDecision {
+            seq: 9,
+            op: "lens_call".to_string(),
+            name: "trim".to_string(),
+            effect_class: Some("read".to_string()),
+            mode: "exec".to_string(),
+            decision: "allowed".to_string(),
+            policy_rule_id: None,
+            input_hash: "sha256:a".to_string(),
+            error_code: None,
+        }];
+        let render = vec![GuardDecision {
+            seq: 42,
+            op: "message_emit".to_string(),
+            name: "user#1".to_string(),
+            effect_class: None,
+            mode: "exec".to_string(),
+            decision: "allowed".to_string(),
+            policy_rule_id: None,
+            input_hash: "sha256:b".to_string(),
+            error_code: None,
+        }];
+
+        let merged = merge_guard_decisions(&engine, &render);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].seq, 1);
+        assert_eq!(merged[1].seq, 2);
+    }
+
+    #[test]
+    fn canonicalize_json_is_stable_for_equivalent_maps() {
+        let a = serde_json::json!({"b":2,"a":1});
+        let b = serde_json::json!({"a":1,"b":2});
+        assert_eq!(
+            canonicalize_json(&a).unwrap(),
+            canonicalize_json(&b).unwrap()
+        );
+    }
+
+    #[test]
+    fn execution_artifact_metadata_has_required_fields() {
+        let payload = sample_payload();
+        let artifact = artifact_json(&payload, &[]);
+
+        let md = artifact
+            .get("metadata")
+            .and_then(|v| v.as_object())
+            .expect("metadata object");
+
+        assert_eq!(md.get("facet_version").unwrap(), "2.1.3");
+        assert_eq!(md.get("host_profile_id").unwrap(), "local.default.v1");
+        assert_eq!(md.get("document_hash").unwrap(), "sha256:abc");
+        assert_eq!(md.get("policy_hash").unwrap(), "sha256:def");
+        assert_eq!(md.get("policy_version").unwrap(), "1");
+        assert_eq!(
+            md.len(),
+            5,
+            "metadata should contain only Appendix F fields"
+        );
+    }
+
+    #[test]
+    fn execution_artifact_events_are_resequenced_without_gaps() {
+        let payload = sample_payload();
+        let decisions = vec![
+            GuardDecision {
+                seq: 7,
+                op: "message_emit".to_string(),
+                name: "system#1".to_string(),
+                effect_class: None,
+                mode: "exec".to_string(),
+                decision: "allowed".to_string(),
+                policy_rule_id: None,
+                input_hash: "sha256:a".to_string(),
+                error_code: None,
+            },
+            GuardDecision {
+                seq: 42,
+                op: "lens_call".to_string(),
+                name: "trim".to_string(),
+                effect_class: Some("read".to_string()),
+                mode: "exec".to_string(),
+                decision: "denied".to_string(),
+                policy_rule_id: Some("r1".to_string()),
+                input_hash: "sha256:b".to_string(),
+                error_code: Some("F454".to_string()),
+            },
+        ];
+
+        let artifact = artifact_json(&payload, &decisions);
+        let events = artifact
+            .get("provenance")
+            .and_then(|v| v.get("events"))
+            .and_then(|v| v.as_array())
+            .expect("events array");
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].get("seq").unwrap(), 1);
+        assert_eq!(events[1].get("seq").unwrap(), 2);
+    }
+
+    #[test]
+    fn execution_artifact_event_has_required_schema_fields() {
+        let payload = sample_payload();
+        let decisions = vec![GuardDecision {
+            seq: 99,
+            op: "tool_expose".to_string(),
+            name: "WeatherAPI.get_current".to_string(),
+            effect_class: Some("read".to_string()),
+            mode: "exec".to_string(),
+            decision: "allowed".to_string(),
+            policy_rule_id: Some("allow-1".to_string()),
+            input_hash: "sha256:abc123".to_string(),
+            error_code: None,
+        }];
+
+        let artifact = artifact_json(&payload, &decisions);
+        let event = artifact
+            .get("provenance")
+            .and_then(|v| v.get("events"))
+            .and_then(|v| v.as_array())
+            .and_then(|arr| arr.first())
+            .and_then(|v| v.as_object())
+            .expect("first event object");
+
+        let required = [
+            "seq",
+            "op",
+            "name",
+            "effect_class",
+            "mode",
+            "decision",
+            "policy_rule_id",
+            "input_hash",
+        ];
+
+        for key in required {
+            assert!(
+                event.contains_key(key),
+                "missing required event key '{}'",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn execution_artifact_preserves_input_hash_values() {
+        let payload = sample_payload();
+        let decisions = vec![
+            GuardDecision {
+                seq: 4,
+                op: "tool_call".to_string(),
+                name: "WeatherAPI.get_current".to_string(),
+                effect_class: Some("read".to_string()),
+                mode: "exec".to_string(),
+                decision: "allowed".to_string(),
+                policy_rule_id: Some("allow-tool".to_string()),
+                input_hash: "sha256:tool".to_string(),
+                error_code: None,
+            },
+            GuardDecision {
+                seq: 1,
+                op: "lens_call".to_string(),
+                name: "trim".to_string(),
+                effect_class: Some("read".to_string()),
+                mode: "exec".to_string(),
+                decision: "allowed".to_string(),
+                policy_rule_id: Some("allow-lens".to_string()),
+                input_hash: "sha256:lens".to_string(),
+                error_code: None,
+            },
+            GuardDecision {
+                seq: 20,
+                op: "tool_expose".to_string(),
+                name: "WeatherAPI.get_current".to_string(),
+                effect_class: Some("read".to_string()),
+                mode: "exec".to_string(),
+                decision: "allowed".to_string(),
+                policy_rule_id: Some("allow-expose".to_string()),
+                input_hash: "sha256:expose".to_string(),
+                error_code: None,
+            },
+            GuardDecision {
+                seq: 8,
+                op: "message_emit".to_string(),
+                name: "user#1".to_string(),
+                effect_class: None,
+                mode: "exec".to_string(),
+                decision: "allowed".to_string(),
+                policy_rule_id: Some("allow-message".to_string()),
+                input_hash: "sha256:message".to_string(),
+                error_code: None,
+            },
+        ];
+
+        let artifact = artifact_json(&payload, &decisions);
+        let events = artifact
+            .get("provenance")
+            .and_then(|v| v.get("events"))
+            .and_then(|v| v.as_array())
+            .expect("events array");
+
+        let hashes: Vec<&str> = events
+            .iter()
+            .map(|e| {
+                e.get("input_hash")
+                    .and_then(|v| v.as_str())
+                    .expect("input_hash string")
+            })
+            .collect();
+        assert_eq!(
+            hashes,
+            vec![
+                "sha256:tool",
+                "sha256:lens",
+                "sha256:expose",
+                "sha256:message"
+            ]
+        );
+    }
+
+    #[test]
+    fn execution_artifact_hash_chain_replay_matches_head() {
+        let payload = sample_payload();
+        let decisions = vec![
+            GuardDecision {
+                seq: 3,
+                op: "tool_expose".to_string(),
+                name: "WeatherAPI.get_current".to_string(),
+                effect_class: Some("read".to_string()),
+                mode: "exec".to_string(),
+                decision: "allowed".to_string(),
+                policy_rule_id: Some("allow-expose".to_string()),
+                input_hash: "sha256:aaa".to_string(),
+                error_code: None,
+            },
+            GuardDecision {
+                seq: 9,
+                op: "message_emit".to_string(),
+                name: "user#1".to_string(),
+                effect_class: None,
+                mode: "exec".to_string(),
+                decision: "denied".to_string(),
+                policy_rule_id: None,
+                input_hash: "sha256:bbb".to_string(),
+                error_code: Some("F454".to_string()),
+            },
+        ];
+
+        let artifact = artifact_json(&payload, &decisions);
+        let events = artifact
+            .get("provenance")
+            .and_then(|v| v.get("events"))
+            .and_then(|v| v.as_array())
+            .expect("events array");
+        let emitted_head = artifact
+            .get("provenance")
+            .and_then(|v| v.get("hash_chain"))
+            .and_then(|v| v.get("head"))
+            .and_then(|v| v.as_str())
+            .expect("head");
+
+        let h0_input = serde_json::json!({
+            "facet_version": payload.metadata.facet_version,
+            "host_profile_id": payload.metadata.host_profile_id,
+            "document_hash": payload.metadata.document_hash,
+            "policy_hash": payload.metadata.policy_hash,
+            "policy_version": payload.metadata.policy_version,
+            "profile": payload.metadata.profile,
+            "mode": payload.metadata.mode,
+        });
+
+        let mut replay_head = sha256_prefixed(canonicalize_json(&h0_input).unwrap().as_bytes());
+        for event in events {
+            let chain_input = serde_json::json!({
+                "prev": replay_head,
+                "event": event,
+            });
+            replay_head = sha256_prefixed(canonicalize_json(&chain_input).unwrap().as_bytes());
+        }
+
+        assert_eq!(replay_head, emitted_head);
+    }
+
+    #[test]
+    fn execution_artifact_hash_chain_changes_on_event_tamper() {
+        let payload = sample_payload();
+        let decisions = vec![GuardDecision {
+            seq: 1,
+            op: "tool_call".to_string(),
+            name: "WeatherAPI.get_current".to_string(),
+            effect_class: Some("read".to_string()),
+            mode: "exec".to_string(),
+            decision: "allowed".to_string(),
+            policy_rule_id: None,
+            input_hash: "sha256:toolhash".to_string(),
+            error_code: None,
+        }];
+
+        let artifact = artifact_json(&payload, &decisions);
+        let original_head = artifact
+            .get("provenance")
+            .and_then(|v| v.get("hash_chain"))
+            .and_then(|v| v.get("head"))
+            .and_then(|v| v.as_str())
+            .expect("head")
+            .to_string();
+
+        let mut tampered_event = artifact
+            .get("provenance")
+            .and_then(|v| v.get("events"))
+            .and_then(|v| v.as_array())
+            .and_then(|arr| arr.first())
+            .cloned()
+            .expect("event");
+        tampered_event["name"] = serde_json::Value::String("WeatherAPI.refund".to_string());
+
+        let h0_input = serde_json::json!({
+            "facet_version": payload.metadata.facet_version,
+            "host_profile_id": payload.metadata.host_profile_id,
+            "document_hash": payload.metadata.document_hash,
+            "policy_hash": payload.metadata.policy_hash,
+            "policy_version": payload.metadata.policy_version,
+            "profile": payload.metadata.profile,
+            "mode": payload.metadata.mode,
+        });
+
+        let h0 = sha256_prefixed(canonicalize_json(&h0_input).unwrap().as_bytes());
+        let tampered_chain_input = serde_json::json!({
+            "prev": h0,
+            "event": tampered_event,
+        });
+        let tampered_head =
+            sha256_prefixed(canonicalize_json(&tampered_chain_input).unwrap().as_bytes());
+
+        assert_ne!(tampered_head, original_head);
+    }
+
+    #[test]
+    fn execution_artifact_includes_valid_attestation_envelope() {
+        let payload = sample_payload();
+        let artifact = build_execution_artifact_with_attestation(
+            &payload,
+            &[],
+            Some(serde_json::json!({
+                "algo": "ed25519",
+                "key_id": "k1",
+                "sig": "AbCdEf0123_-",
+            })),
+        )
+        .expect("artifact with attestation");
+
+        assert_eq!(
+            artifact.attestation,
+            Some(Attestation {
+                algo: "ed25519".to_string(),
+                key_id: "k1".to_string(),
+                sig: "AbCdEf0123_-".to_string(),
+            })
+        );
+        let json = artifact.to_json().unwrap();
+        assert_eq!(json.pointer("/attestation/sig").unwrap(), "AbCdEf0123_-");
+    }
+
+    #[test]
+    fn execution_artifact_rejects_invalid_attestation_algo() {
+        let payload = sample_payload();
+        let err = build_execution_artifact_with_attestation(
+            &payload,
+            &[],
+            Some(serde_json::json!({
+                "algo": "rsa2048",
+                "key_id": "k1",
+                "sig": "AbCdEf0123_-",
+            })),
+        )
+        .unwrap_err();
+
+        assert!(err.to_string().contains("Attestation.algo"));
+    }
+
+    #[test]
+    fn execution_artifact_rejects_invalid_attestation_sig() {
+        let payload = sample_payload();
+        let err = build_execution_artifact_with_attestation(
+            &payload,
+            &[],
+            Some(serde_json::json!({
+                "algo": "x.acme.ed25519",
+                "key_id": "k2",
+                "sig": "not+base64url",
+            })),
+        )
+        .unwrap_err();
+
+        assert!(err.to_string().contains("Attestation.sig"));
+    }
+}
@@ -0,0 +1,425 @@
+//! Precompiled contract bundles (`.fctb`).
+//!
+//! A bundle is the output of Phases 1-2 plus what Phase 3 needs up front: the
+//! resolved AST, the variable types, the R-DAG evaluation order and the
+//! effective policy, with the document and policy hashes. It is canonical JSON
+//! sealed by an `integrity` hash over every other field.
+//!
+//! [`Compiler::load_bundle`] turns a bundle back into a [`Session`] without
+//! parsing, resolving or type checking, so each run executes Phases 3-5 only.
+//! Bundles are rejected when they were built by another compiler version or
+//! spec version, for another profile, or for another host profile or host
+//! context schema. The stored policy and R-DAG order must also agree with the
+//! bundled document.
+
+use crate::artifact::{canonicalize_json, sha256_prefixed};
+use crate::compiler::{profile_label, Compiler, PhaseTimings, Session};
+use crate::error::{CompilerError, CompilerResult};
+use fct_ast::FacetDocument;
+use fct_engine::{ExecutionTrace, RDagEngine};
+use fct_policy::EffectivePolicy;
+use fct_validator::{HostContextSchema, ValidationProfile, VarTypeDecl};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+/// `format` tag of a contract bundle.
+pub const BUNDLE_FORMAT: &str = "fctb";
+/// Layout version of the bundle; bumped on any incompatible change.
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+/// Version of this compiler, recorded in and required of every bundle.
+pub const COMPILER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A resolved, validated contract ready to run without Phases 1-2.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractBundle {
+    pub format: String,
+    pub format_version: u32,
+    pub compiler_version: String,
+    pub spec_version: String,
+    /// Validation profile the contract was checked under (`core` or `hypervisor`).
+    pub profile: String,
+    pub host_profile_id: String,
+    /// Hash of the host context types the contract was checked against.
+    pub host_context_schema_hash: Option<String>,
+    pub document_hash: String,
+    pub policy_hash: Option<String>,
+    pub document: FacetDocument,
+    pub var_types: BTreeMap<String, VarTypeDecl>,
+    /// R-DAG evaluation order of `@vars`; empty under the Core profile.
+    pub rdag_order: Vec<String>,
+    pub effective_policy: Option<serde_json::Value>,
+    /// `sha256:` of the canonical JSON of all other fields.
+    pub integrity: String,
+}
+
+impl ContractBundle {
+    /// Bundle a loaded session.
+    pub fn from_session(session: &Session) -> CompilerResult<Self> {
+        let options = session.options();
+        let effective_policy = EffectivePolicy::from_document(session.document());
+        let mut bundle = Self {
+            format: BUNDLE_FORMAT.to_string(),
+            format_version: BUNDLE_FORMAT_VERSION,
+            compiler_version: COMPILER_VERSION.to_string(),
+            spec_version: fct_ast::FACET_VERSION.to_string(),
+            profile: profile_label(options.profile).to_string(),
+            host_profile_id: options.host_profile_id.clone(),
+            host_context_schema_hash: host_context_schema_hash(
+                options.host_context_schema.as_ref(),
+            )?,
+            document_hash: session.document_hash().to_string(),
+            policy_hash: effective_policy
+                .as_ref()
+                .map(EffectivePolicy::policy_hash)
+                .transpose()?,
+            document: session.document().clone(),
+            var_types: session.var_types().clone(),
+            rdag_order: session.rdag_order()?,
+            effective_policy: effective_policy
+                .as_ref()
+                .map(EffectivePolicy::to_json)
+                .transpose()?,
+            integrity: String::new(),
+        };
+        bundle.integrity = bundle.content_hash()?;
+        Ok(bundle)
+    }
+
+    /// Parse a bundle, checking its format tag and version before its layout.
+    pub fn from_slice(bytes: &[u8]) -> CompilerResult<Self> {
+        let value: serde_json::Value = serde_json::from_slice(bytes)
+            .map_err(|e| CompilerError::Bundle(format!("Bundle is not valid JSON: {}", e)))?;
+        if value.get("format").and_then(serde_json::Value::as_str) != Some(BUNDLE_FORMAT) {
+            return Err(CompilerError::Bundle(
+                "Not a FACET contract bundle (missing format \"fctb\")".to_string(),
+            ));
+        }
+        let format_version = value
+            .get("format_version")
+            .and_then(serde_json::Value::as_u64);
+        if format_version != Some(u64::from(BUNDLE_FORMAT_VERSION)) {
+            return Err(CompilerError::Bundle(format!(
+                "Unsupported bundle format version {}, expected {}",
+                format_version.map_or_else(|| "<missing>".to_string(), |v| v.to_string()),
+                BUNDLE_FORMAT_VERSION
+            )));
+        }
+        serde_json::from_value(value)
+            .map_err(|e| CompilerError::Bundle(format!("Malformed bundle: {}", e)))
+    }
+
+    /// Canonical JSON bytes of the bundle.
+    pub fn to_bytes(&self) -> CompilerResult<Vec<u8>> {
+        Ok(canonicalize_json(&serde_json::to_value(self)?)?.into_bytes())
+    }
+
+    /// Check the `integrity` hash against the bundle contents.
+    pub fn verify_integrity(&self) -> CompilerResult<()> {
+        let actual = self.content_hash()?;
+        if actual != self.integrity {
+            return Err(CompilerError::Bundle(format!(
+                "Bundle integrity check failed: recorded {}, computed {}",
+                self.integrity, actual
+            )));
+        }
+        Ok(())
+    }
+
+    fn content_hash(&self) -> CompilerResult<String> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("integrity");
+        }
+        Ok(sha256_prefixed(canonicalize_json(&value)?.as_bytes()))
+    }
+}
+
+impl Session {
+    /// Bundle this session's resolved and validated contract.
+    pub fn bundle(&self) -> CompilerResult<ContractBundle> {
+        ContractBundle::from_session(self)
+    }
+}
+
+impl Compiler {
+    /// Session over a precompiled bundle: runs go straight to Phases 3-5.
+    pub fn load_bundle(&self, bundle: ContractBundle) -> CompilerResult<Session> {
+        let mut trace = self.options().trace.then(ExecutionTrace::new);
+        let started = Instant::now();
+        bundle.verify_integrity()?;
+        self.check_bundle_compatible(&bundle)?;
+        check_bundle_policy(&bundle)?;
+        let rdag_order = match self.options().profile {
+            ValidationProfile::Core => None,
+            ValidationProfile::Hypervisor => {
+                check_bundle_rdag_order(&bundle)?;
+                Some(bundle.rdag_order)
+            }
+        };
+        if let Some(trace) = &mut trace {
+            trace.record("load_bundle", "phase", started, serde_json::json!({}));
+        }
+
+        Ok(self.session(
+            bundle.document,
+            bundle.document_hash,
+            bundle.var_types,
+            None,
+            rdag_order,
+            PhaseTimings {
+                resolve: started.elapsed(),
+                ..PhaseTimings::default()
+            },
+            trace,
+        ))
+    }
+
+    /// [`Compiler::load_bundle`] for the bundle file at `path`.
+    pub fn load_bundle_file(&self, path: &Path) -> CompilerResult<Session> {
+        let bytes = fs::read(path).map_err(|source| CompilerError::Io {
+            kind: "contract bundle",
+            path: path.to_path_buf(),
+            source,
+        })?;
+        self.load_bundle(ContractBundle::from_slice(&bytes)?)
+    }
+
+    /// Load `path` as a bundle when it has the `.fctb` extension, otherwise as
+    /// FACET source through [`Compiler::load_file`].
+    pub fn load_contract_file(&self, path: &Path) -> CompilerResult<Session> {
+        if path.extension().is_some_and(|ext| ext == BUNDLE_FORMAT) {
+            self.load_bundle_file(path)
+        } else {
+            self.load_file(path)
+        }
+    }
+
+    fn check_bundle_compatible(&self, bundle: &ContractBundle) -> CompilerResult<()> {
+        let options = self.options();
+        if bundle.compiler_version != COMPILER_VERSION {
+            return Err(CompilerError::Bundle(format!(
+                "Bundle was built by fct-compiler {}, this is {}; recompile the contract",
+                bundle.compiler_version, COMPILER_VERSION
+            )));
+        }
+        if bundle.spec_version != fct_ast::FACET_VERSION {
+            return Err(CompilerError::Bundle(format!(
+                "Bundle targets FACET {}, this compiler implements {}",
+                bundle.spec_version,
+                fct_ast::FACET_VERSION
+            )));
+        }
+        let profile = profile_label(options.profile);
+        if bundle.profile != profile {
+            return Err(CompilerError::Bundle(format!(
+                "Bundle was validated under the {} profile, not {}",
+                bundle.profile, profile
+            )));
+        }
+        if bundle.host_profile_id != options.host_profile_id {
+            return Err(CompilerError::Bundle(format!(
+                "Bundle was built for host profile '{}', not '{}'",
+                bundle.host_profile_id, options.host_profile_id
+            )));
+        }
+        if bundle.host_context_schema_hash
+            != host_context_schema_hash(options.host_context_schema.as_ref())?
+        {
+            return Err(CompilerError::Bundle(format!(
+                "Bundle was checked against different host context types for '{}'",
+                bundle.host_profile_id
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The stored effective policy and its hash must be the ones the document yields.
+fn check_bundle_policy(bundle: &ContractBundle) -> CompilerResult<()> {
+    let effective_policy = EffectivePolicy::from_document(&bundle.document);
+    let policy_hash = effective_policy
+        .as_ref()
+        .map(EffectivePolicy::policy_hash)
+        .transpose()?;
+    if bundle.policy_hash != policy_hash {
+        return Err(CompilerError::Bundle(format!(
+            "Bundle policy_hash {} does not match its document ({})",
+            bundle.policy_hash.as_deref().unwrap_or("null"),
+            policy_hash.as_deref().unwrap_or("null")
+        )));
+    }
+    let policy_json = effective_policy
+        .as_ref()
+        .map(EffectivePolicy::to_json)
+        .transpose()?;
+    if bundle.effective_policy != policy_json {
+        return Err(CompilerError::Bundle(
+            "Bundle effective_policy does not match its document".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The stored R-DAG order is executed as is, so it must sort the rebuilt graph.
+fn check_bundle_rdag_order(bundle: &ContractBundle) -> CompilerResult<()> {
+    let mut engine = RDagEngine::new();
+    engine.build(&bundle.document)?;
+    if !engine.is_topological_order(&bundle.rdag_order) {
+        return Err(CompilerError::Bundle(
+            "Bundle rdag_order is not a valid evaluation order of its @vars graph".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn host_context_schema_hash(schema: Option<&HostContextSchema>) -> CompilerResult<Option<String>> {
+    match schema {
+        Some(schema) if !schema.is_empty() => {
+            let value = serde_json::to_value(schema.namespaces())?;
+            Ok(Some(sha256_prefixed(canonicalize_json(&value)?.as_bytes())))
+        }
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fct_ast::ValueNode;
+    use std::collections::HashMap;
+
+    const CONTRACT: &str = r#"@vars
+  name: @input(type="string")
+  greeting: "Hello"
+  line: $greeting
+
+@user
+  content: $name
+"#;
+
+    fn bundle_for(compiler: &Compiler) -> ContractBundle {
+        compiler
+            .load_source(CONTRACT, Path::new("."))
+            .unwrap()
+            .bundle()
+            .unwrap()
+    }
+
+    fn inputs(name: &str) -> HashMap<String, ValueNode> {
+        HashMap::from([("name".to_string(), ValueNode::String(name.to_string()))])
+    }
+
+    #[test]
+    fn bundle_round_trips_and_runs_like_the_source() {
+        let compiler = Compiler::builder().host_profile_id("acme.prod.v1").build();
+        let bundle = bundle_for(&compiler);
+        assert_eq!(bundle.rdag_order, vec!["name", "greeting", "line"]);
+        assert!(bundle.var_types.contains_key("greeting"));
+
+        let bytes = bundle.to_bytes().unwrap();
+        let parsed = ContractBundle::from_slice(&bytes).unwrap();
+        assert_eq!(parsed.to_bytes().unwrap(), bytes);
+
+        let mut from_bundle = compiler.load_bundle(parsed).unwrap();
+        let mut from_source = compiler.load_source(CONTRACT, Path::new(".")).unwrap();
+        let expected = from_source.run(inputs("Ada")).unwrap();
+        let actual = from_bundle.run(inputs("Ada")).unwrap();
+        assert_eq!(
+            serde_json::to_value(&actual.payload).unwrap(),
+            serde_json::to_value(&expected.payload).unwrap()
+        );
+        assert_eq!(
+            actual.artifact.unwrap().to_json().unwrap(),
+            expected.artifact.unwrap().to_json().unwrap()
+        );
+    }
+
+    #[test]
+    fn load_contract_file_picks_bundles_by_extension() {
+        let compiler = Compiler::new();
+        let dir = std::env::temp_dir().join(format!("fct-bundle-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("contract.facet");
+        let bundle = dir.join("contract.fctb");
+        fs::write(&source, CONTRACT).unwrap();
+        fs::write(&bundle, bundle_for(&compiler).to_bytes().unwrap()).unwrap();
+
+        let from_source = compiler.load_contract_file(&source).unwrap();
+        let from_bundle = compiler.load_contract_file(&bundle).unwrap();
+        assert_eq!(from_bundle.document_hash(), from_source.document_hash());
+        assert!(compiler.load_file(&bundle).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_tampered_and_foreign_bundles() {
+        let compiler = Compiler::new();
+        let mut tampered = bundle_for(&compiler);
+        tampered.rdag_order.reverse();
+        let err = compiler.load_bundle(tampered).err().expect("tampered");
+        assert!(err.to_string().contains("integrity"), "{err}");
+
+        let other_host = Compiler::builder().host_profile_id("acme.prod.v1").build();
+        let err = other_host
+            .load_bundle(bundle_for(&compiler))
+            .err()
+            .expect("host profile mismatch");
+        assert!(err.to_string().contains("host profile"), "{err}");
+
+        let mut old = bundle_for(&compiler);
+        old.compiler_version = "0.0.1".to_string();
+        old.integrity = old.content_hash().unwrap();
+        let err = compiler.load_bundle(old).err().expect("version mismatch");
+        assert!(err.to_string().contains("0.0.1"), "{err}");
+
+        let err =
+            ContractBundle::from_slice(br#"{"format":"fctb","format_version":99}"#).unwrap_err();
+        assert!(err.to_string().contains("format version 99"), "{err}");
+    }
+
+    #[test]
+    fn rejects_resealed_bundles_inconsistent_with_their_document() {
+        let compiler = Compiler::new();
+        let reseal = |mut bundle: ContractBundle| {
+            bundle.integrity = bundle.content_hash().unwrap();
+            bundle
+        };
+
+        let mut reordered = bundle_for(&compiler);
+        reordered.rdag_order.reverse();
+        let err = compiler
+            .load_bundle(reseal(reordered))
+            .err()
+            .expect("order");
+        assert!(err.to_string().contains("rdag_order"), "{err}");
+
+        let mut truncated = bundle_for(&compiler);
+        truncated.rdag_order.pop();
+        let err = compiler
+            .load_bundle(reseal(truncated))
+            .err()
+            .expect("order");
+        assert!(err.to_string().contains("rdag_order"), "{err}");
+
+        let mut policy = bundle_for(&compiler);
+        policy.policy_hash = Some("sha256:00".to_string());
+        let err = compiler.load_bundle(reseal(policy)).err().expect("policy");
+        assert!(err.to_string().contains("policy_hash"), "{err}");
+
+        let mut policy_body = bundle_for(&compiler);
+        policy_body.effective_policy = Some(serde_json::json!({ "allow": [] }));
+        let err = compiler
+            .load_bundle(reseal(policy_body))
+            .err()
+            .expect("policy");
+        assert!(err.to_string().contains("effective_policy"), "{err}");
+
+        let mut spec = bundle_for(&compiler);
+        spec.spec_version = "2.0.0".to_string();
+        let err = compiler.load_bundle(reseal(spec)).err().expect("spec");
+        assert!(err.to_string().contains("FACET 2.0.0"), "{err}");
+    }
+}
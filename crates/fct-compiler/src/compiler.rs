@@ -0,0 +1,1333 @@
+//! [`Compiler`] and [`Session`]: the five phases behind one configured entry point.
+//!
+//! A `Compiler` holds settings shared by every document it loads. Loading a
+//! document runs Phases 1-2 once and yields a [`Session`]; each
+//! [`Session::run`] then runs Phases 3-5 for one set of runtime inputs.
+
+use crate::artifact::{
+    build_execution_artifact_with_host_context, merge_guard_decisions, sha256_prefixed,
+    ExecutionArtifact,
+};
+use crate::error::{CompilerError, CompilerResult};
+use crate::sections::{doc_to_sections, effective_layout_budget, layout_reservation, literal_vars};
+use fct_ast::{FacetDocument, ValueNode};
+use fct_engine::{
+    AllocationResult, ExecutionContext, ExecutionMode, ExecutionTrace, LayoutReservation,
+    RDagEngine, RDagMemo, Section, TokenBoxModel,
+};
+use fct_parser::parse_document;
+use fct_policy::{EffectivePolicy, HostContext};
+use fct_render::{
+    policy_hash_for_document, CanonicalPayload, GuardDecision, RenderContext, Renderer,
+};
+use fct_resolver::{Resolver, ResolverConfig};
+use fct_std::LensRegistry;
+use fct_validator::{HostContextSchema, TypeChecker, ValidationProfile, VarTypeDecl};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Host profile id used when none is configured.
+pub const DEFAULT_HOST_PROFILE_ID: &str = "local.default.v1";
+
+type LensRegistryFactory = Arc<dyn Fn() -> LensRegistry + Send + Sync>;
+
+/// Settings shared by every [`Session`] of a [`Compiler`].
+#[derive(Debug, Clone)]
+pub struct CompilerOptions {
+    pub mode: ExecutionMode,
+    pub profile: ValidationProfile,
+    /// Host layout budget; `@context.budget` takes precedence.
+    pub budget: usize,
+    /// Gas limit of the R-DAG execution context.
+    pub context_budget: usize,
+    /// Layout units kept free for the model's reply; `@context.x_fct_output_reserve`
+    /// takes precedence.
+    pub output_reserve: usize,
+    pub host_profile_id: String,
+    /// Declared types of the host context; enables `$x.<host>...` in Phase 2.
+    pub host_context_schema: Option<HostContextSchema>,
+    pub host_context: Option<HostContext>,
+    pub target_provider_id: String,
+    /// Level-1 lens results every session starts from (Pure mode is cache-only).
+    pub lens_cache: HashMap<String, ValueNode>,
+    /// Memoize `@vars` across a session's runs and recompute only the nodes
+    /// whose dependencies or runtime inputs changed (see [`RDagMemo`]).
+    pub reactive: bool,
+    /// Evaluate only the `@vars` that messages and `@policy` need
+    /// (see [`RDagEngine::with_demand_driven`]).
+    pub demand_driven: bool,
+    /// Evaluate independent `@vars` concurrently, with results identical to a
+    /// sequential run (see [`RDagEngine::with_parallel`]).
+    pub parallel: bool,
+    /// Record each run's timeline in [`Compilation::trace`].
+    pub trace: bool,
+}
+
+impl Default for CompilerOptions {
+    fn default() -> Self {
+        Self {
+            mode: ExecutionMode::Exec,
+            profile: ValidationProfile::Hypervisor,
+            budget: 4096,
+            context_budget: 10_000,
+            output_reserve: 0,
+            host_profile_id: DEFAULT_HOST_PROFILE_ID.to_string(),
+            host_context_schema: None,
+            host_context: None,
+            target_provider_id: "unknown-provider".to_string(),
+            lens_cache: HashMap::new(),
+            reactive: false,
+            demand_driven: false,
+            parallel: false,
+            trace: false,
+        }
+    }
+}
+
+/// Builder for [`Compiler`].
+#[derive(Clone)]
+pub struct CompilerBuilder {
+    options: CompilerOptions,
+    lens_registry: Option<LensRegistryFactory>,
+}
+
+impl CompilerBuilder {
+    pub fn mode(mut self, mode: ExecutionMode) -> Self {
+        self.options.mode = mode;
+        self
+    }
+
+    pub fn profile(mut self, profile: ValidationProfile) -> Self {
+        self.options.profile = profile;
+        self
+    }
+
+    pub fn budget(mut self, budget: usize) -> Self {
+        self.options.budget = budget;
+        self
+    }
+
+    pub fn context_budget(mut self, context_budget: usize) -> Self {
+        self.options.context_budget = context_budget;
+        self
+    }
+
+    pub fn output_reserve(mut self, output_reserve: usize) -> Self {
+        self.options.output_reserve = output_reserve;
+        self
+    }
+
+    pub fn host_profile_id(mut self, host_profile_id: impl Into<String>) -> Self {
+        self.options.host_profile_id = host_profile_id.into();
+        self
+    }
+
+    pub fn host_context_schema(mut self, schema: HostContextSchema) -> Self {
+        self.options.host_context_schema = Some(schema);
+        self
+    }
+
+    pub fn host_context(mut self, host_context: HostContext) -> Self {
+        self.options.host_context = Some(host_context);
+        self
+    }
+
+    pub fn target_provider_id(mut self, target_provider_id: impl Into<String>) -> Self {
+        self.options.target_provider_id = target_provider_id.into();
+        self
+    }
+
+    /// Lens registry of each new session, e.g. the standard lenses plus the
+    /// host's own. Defaults to [`LensRegistry::new`].
+    pub fn lens_registry<F>(mut self, factory: F) -> Self
+    where
+        F: Fn() -> LensRegistry + Send + Sync + 'static,
+    {
+        self.lens_registry = Some(Arc::new(factory));
+        self
+    }
+
+    pub fn lens_cache(mut self, lens_cache: HashMap<String, ValueNode>) -> Self {
+        self.options.lens_cache = lens_cache;
+        self
+    }
+
+    pub fn reactive(mut self, reactive: bool) -> Self {
+        self.options.reactive = reactive;
+        self
+    }
+
+    pub fn demand_driven(mut self, demand_driven: bool) -> Self {
+        self.options.demand_driven = demand_driven;
+        self
+    }
+
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.options.parallel = parallel;
+        self
+    }
+
+    pub fn trace(mut self, trace: bool) -> Self {
+        self.options.trace = trace;
+        self
+    }
+
+    pub fn build(self) -> Compiler {
+        Compiler {
+            options: self.options,
+            lens_registry: self
+                .lens_registry
+                .unwrap_or_else(|| Arc::new(LensRegistry::new)),
+        }
+    }
+}
+
+/// Configured FACET compiler. Cheap to clone and shareable across threads.
+#[derive(Clone)]
+pub struct Compiler {
+    options: CompilerOptions,
+    lens_registry: LensRegistryFactory,
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Compiler {
+    pub fn new() -> Self {
+        Self::builder().build()
+    }
+
+    pub fn builder() -> CompilerBuilder {
+        CompilerBuilder {
+            options: CompilerOptions::default(),
+            lens_registry: None,
+        }
+    }
+
+    pub fn options(&self) -> &CompilerOptions {
+        &self.options
+    }
+
+    /// Phases 1-2 for the file at `path`; imports are confined to its directory.
+    pub fn load_file(&self, path: &Path) -> CompilerResult<Session> {
+        let source = fs::read_to_string(path).map_err(|source| CompilerError::Io {
+            kind: "input file",
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let base_dir = path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."));
+        self.load_source(&source, &base_dir)
+    }
+
+    /// Phases 1-2 for `source`, resolving imports relative to and only within
+    /// `base_dir`.
+    pub fn load_source(&self, source: &str, base_dir: &Path) -> CompilerResult<Session> {
+        let mut trace = self.options.trace.then(ExecutionTrace::new);
+        let started = Instant::now();
+        let parsed = parse_document(source).map_err(CompilerError::Parse)?;
+        record_phase(&mut trace, "parse", started, serde_json::json!({}));
+
+        let resolve_started = Instant::now();
+        let mut resolver = Resolver::new(ResolverConfig {
+            allowed_roots: vec![base_dir.to_path_buf()],
+            base_dir: base_dir.to_path_buf(),
+        });
+        let resolved_source_form = resolver.resolve_source_form(source)?;
+        let document = resolver.resolve(parsed)?;
+        let document_hash = sha256_prefixed(resolved_source_form.as_bytes());
+        let resolve = started.elapsed();
+        record_phase(
+            &mut trace,
+            "resolve",
+            resolve_started,
+            serde_json::json!({}),
+        );
+
+        let started = Instant::now();
+        let mut checker = TypeChecker::new().with_profile(self.options.profile);
+        if let Some(schema) = &self.options.host_context_schema {
+            checker = checker.with_host_context(schema.clone());
+        }
+        checker.validate(&document)?;
+        let validate = started.elapsed();
+        record_phase(&mut trace, "type_check", started, serde_json::json!({}));
+
+        Ok(self.session(
+            document,
+            document_hash,
+            checker.var_types(),
+            Some(checker),
+            None,
+            PhaseTimings {
+                resolve,
+                validate,
+                ..PhaseTimings::default()
+            },
+            trace,
+        ))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn session(
+        &self,
+        document: FacetDocument,
+        document_hash: String,
+        var_types: BTreeMap<String, VarTypeDecl>,
+        type_checker: Option<TypeChecker>,
+        rdag_order: Option<Vec<String>>,
+        load_timings: PhaseTimings,
+        load_trace: Option<ExecutionTrace>,
+    ) -> Session {
+        Session {
+            options: self.options.clone(),
+            document,
+            document_hash,
+            var_types,
+            type_checker,
+            rdag_order,
+            new_lens_registry: Arc::clone(&self.lens_registry),
+            lens_registry: (self.lens_registry)(),
+            lens_cache: self.options.lens_cache.clone(),
+            rdag_memo: RDagMemo::new(),
+            load_timings,
+            load_trace,
+        }
+    }
+
+    /// Load the file at `path` and run it once.
+    pub fn compile_file(
+        &self,
+        path: &Path,
+        runtime_inputs: HashMap<String, ValueNode>,
+    ) -> CompilerResult<Compilation> {
+        self.load_file(path)?.run(runtime_inputs)
+    }
+}
+
+/// A resolved and validated document, ready to run any number of times.
+///
+/// The session keeps its lens registry and Level-1 lens cache across runs, so
+/// a Pure-mode run can replay lens results an earlier Exec-mode run produced.
+pub struct Session {
+    options: CompilerOptions,
+    document: FacetDocument,
+    document_hash: String,
+    var_types: BTreeMap<String, VarTypeDecl>,
+    /// Phase 2 state; `None` for sessions restored from a bundle.
+    type_checker: Option<TypeChecker>,
+    /// R-DAG evaluation order, when known ahead of the run (bundles).
+    rdag_order: Option<Vec<String>>,
+    /// Builds the registry of each [`Session::execute`].
+    new_lens_registry: LensRegistryFactory,
+    lens_registry: LensRegistry,
+    lens_cache: HashMap<String, ValueNode>,
+    /// Node results of earlier runs; used only by reactive sessions.
+    rdag_memo: RDagMemo,
+    load_timings: PhaseTimings,
+    /// Phase 1-2 spans every traced run's timeline starts with.
+    load_trace: Option<ExecutionTrace>,
+}
+
+impl Session {
+    pub fn options(&self) -> &CompilerOptions {
+        &self.options
+    }
+
+    /// The resolved AST (Phase 1 output).
+    pub fn document(&self) -> &FacetDocument {
+        &self.document
+    }
+
+    pub fn document_hash(&self) -> &str {
+        &self.document_hash
+    }
+
+    /// Declared or inferred type of every `@vars` entry (Phase 2 output).
+    pub fn var_types(&self) -> &BTreeMap<String, VarTypeDecl> {
+        &self.var_types
+    }
+
+    /// The Phase 2 type checker, for typing further expressions against the
+    /// document. `None` when the session was restored from a bundle, which
+    /// skips Phase 2.
+    pub fn type_checker(&self) -> Option<&TypeChecker> {
+        self.type_checker.as_ref()
+    }
+
+    /// Evaluation order of the `@vars` graph. Empty under the Core profile,
+    /// which has no R-DAG.
+    pub fn rdag_order(&self) -> CompilerResult<Vec<String>> {
+        if let Some(order) = &self.rdag_order {
+            return Ok(order.clone());
+        }
+        if self.options.profile == ValidationProfile::Core {
+            return Ok(Vec::new());
+        }
+        let mut engine = RDagEngine::new();
+        engine.build(&self.document)?;
+        engine.validate()?;
+        Ok(engine.topological_order()?)
+    }
+
+    pub fn lens_cache(&self) -> &HashMap<String, ValueNode> {
+        &self.lens_cache
+    }
+
+    /// Phases 3-5 with `runtime_inputs` for `@input(...)` variables. Under the
+    /// Core profile only Phase 5 runs, over the literal `@vars`.
+    pub fn run(
+        &mut self,
+        runtime_inputs: HashMap<String, ValueNode>,
+    ) -> CompilerResult<Compilation> {
+        if self.options.profile == ValidationProfile::Core {
+            return self.run_core(runtime_inputs);
+        }
+
+        let mut exec_ctx = self.execution_context(runtime_inputs);
+
+        // Lend the session's registry and cache to the run, then take them back
+        // whatever the outcome.
+        std::mem::swap(&mut self.lens_registry, &mut exec_ctx.lens_registry);
+        std::mem::swap(&mut self.lens_cache, &mut exec_ctx.lens_cache);
+        let mut memo = std::mem::take(&mut self.rdag_memo);
+        let result = self.run_phases(&mut exec_ctx, &mut memo);
+        self.rdag_memo = memo;
+        std::mem::swap(&mut self.lens_registry, &mut exec_ctx.lens_registry);
+        std::mem::swap(&mut self.lens_cache, &mut exec_ctx.lens_cache);
+        result
+    }
+
+    /// Phase 3 alone: the computed `@vars` and the engine that computed them,
+    /// for tools that inspect them or lay them out at several budgets with
+    /// [`Session::lay_out`]. The execution gets its own lens registry and a
+    /// copy of the session's lens cache, so it leaves later runs unchanged.
+    /// Not available under the Core profile, which has no R-DAG.
+    pub fn execute(&self, runtime_inputs: HashMap<String, ValueNode>) -> CompilerResult<Execution> {
+        if self.options.profile == ValidationProfile::Core {
+            return Err(CompilerError::Profile(
+                "R-DAG execution is not available under the Core profile".to_string(),
+            ));
+        }
+        let mut context = self.execution_context(runtime_inputs);
+        context.lens_registry = (self.new_lens_registry)();
+        context.lens_cache = self.lens_cache.clone();
+        let engine = self.execute_phase(&mut context, &mut RDagMemo::new())?;
+        Ok(Execution { engine, context })
+    }
+
+    /// Phase 4 over `execution`: the message sections, the fixed load ahead of
+    /// them and their allocation at `budget`, or at the effective layout
+    /// budget (`@context.budget` over the configured one) when `None`.
+    pub fn lay_out(
+        &self,
+        execution: &mut Execution,
+        budget: Option<usize>,
+    ) -> CompilerResult<Layout> {
+        let budget =
+            budget.unwrap_or_else(|| effective_layout_budget(&self.document, self.options.budget));
+        let exec_ctx = &mut execution.context;
+        let (_, reservation, sections) = self.layout_inputs(exec_ctx, budget)?;
+        let allocation = self.allocate(exec_ctx, budget, reservation, sections.clone())?;
+        Ok(Layout {
+            sections,
+            reservation,
+            allocation,
+        })
+    }
+
+    /// Execution context of one run, before the lens registry and cache.
+    fn execution_context(&self, runtime_inputs: HashMap<String, ValueNode>) -> ExecutionContext {
+        let mut exec_ctx =
+            ExecutionContext::new_with_mode(self.options.context_budget, self.options.mode);
+        exec_ctx.set_inputs(runtime_inputs);
+        exec_ctx.host_profile_id = self.options.host_profile_id.clone();
+        if let Some(host_context) = &self.options.host_context {
+            exec_ctx.set_host_context(host_context.clone());
+        }
+        exec_ctx.trace = self.load_trace.clone();
+        exec_ctx
+    }
+
+    fn run_phases(
+        &self,
+        exec_ctx: &mut ExecutionContext,
+        memo: &mut RDagMemo,
+    ) -> CompilerResult<Compilation> {
+        let mut timings = self.load_timings.clone();
+
+        let started = Instant::now();
+        let engine = self.execute_phase(exec_ctx, memo)?;
+        timings.execute = started.elapsed();
+
+        let started = Instant::now();
+        let budget = effective_layout_budget(&self.document, self.options.budget);
+        let (render_context, reservation, sections) = self.layout_inputs(exec_ctx, budget)?;
+        let allocation = self.allocate(exec_ctx, budget, reservation, sections)?;
+        timings.layout = started.elapsed();
+        record_phase(
+            &mut exec_ctx.trace,
+            "layout",
+            started,
+            serde_json::json!({
+                "budget": budget,
+                "total": allocation.total_size,
+                "reserved": reservation.total(),
+            }),
+        );
+
+        let started = Instant::now();
+        let render_output =
+            Renderer::new().render_with_trace(&self.document, &allocation, render_context)?;
+        timings.render = started.elapsed();
+        record_phase(
+            &mut exec_ctx.trace,
+            "render",
+            started,
+            serde_json::json!({}),
+        );
+
+        let guard_decisions =
+            merge_guard_decisions(&exec_ctx.guard_decisions, &render_output.guard_decisions);
+        let artifact = build_execution_artifact_with_host_context(
+            &render_output.payload,
+            &guard_decisions,
+            self.options.host_context.as_ref(),
+        )?;
+
+        let mut telemetry = Telemetry::new(timings, exec_ctx, &allocation);
+        telemetry.nodes_skipped = engine.skipped_nodes().len();
+        if self.options.reactive {
+            telemetry.nodes_reused = memo.reused().len();
+            telemetry.nodes_recomputed = memo.recomputed().len();
+        }
+
+        Ok(Compilation {
+            telemetry,
+            payload: render_output.payload,
+            guard_decisions,
+            artifact: Some(artifact),
+            computed_vars: std::mem::take(&mut exec_ctx.variables),
+            effective_policy: exec_ctx.effective_policy.take(),
+            trace: exec_ctx.trace.take(),
+        })
+    }
+
+    /// Phase 3: build the R-DAG and compute `@vars` into `exec_ctx`.
+    fn execute_phase(
+        &self,
+        exec_ctx: &mut ExecutionContext,
+        memo: &mut RDagMemo,
+    ) -> CompilerResult<RDagEngine> {
+        let started = Instant::now();
+        let mut engine = RDagEngine::new()
+            .with_demand_driven(self.options.demand_driven)
+            .with_parallel(self.options.parallel);
+        engine.build(&self.document)?;
+        match (&self.rdag_order, self.options.reactive) {
+            (Some(order), false) => engine.execute_in_order(exec_ctx, order)?,
+            (Some(order), true) => engine.execute_reactive_in_order(exec_ctx, order, memo)?,
+            (None, reactive) => {
+                engine.validate()?;
+                if reactive {
+                    engine.execute_reactive(exec_ctx, memo)?;
+                } else {
+                    engine.execute(exec_ctx)?;
+                }
+            }
+        }
+        let gas = exec_ctx.gas.consumed;
+        record_phase(
+            &mut exec_ctx.trace,
+            "execute",
+            started,
+            serde_json::json!({ "gas": gas }),
+        );
+        Ok(engine)
+    }
+
+    /// What Phase 4 lays out: the render context the tools are measured under,
+    /// the fixed load and the message sections.
+    fn layout_inputs(
+        &self,
+        exec_ctx: &ExecutionContext,
+        budget: usize,
+    ) -> CompilerResult<(RenderContext, LayoutReservation, Vec<Section>)> {
+        let render_context = self.render_context(
+            profile_label(ValidationProfile::Hypervisor),
+            exec_ctx.policy_hash.clone(),
+            budget,
+            &exec_ctx.variables,
+        );
+        // Tool definitions and the reply are sent with the messages, so they
+        // are fixed load ahead of every section.
+        let reservation =
+            layout_reservation(&self.document, &render_context, self.options.output_reserve)?;
+        let sections =
+            doc_to_sections(&self.document, &exec_ctx.variables, &exec_ctx.lens_registry)?;
+        Ok((render_context, reservation, sections))
+    }
+
+    fn allocate(
+        &self,
+        exec_ctx: &mut ExecutionContext,
+        budget: usize,
+        reservation: LayoutReservation,
+        sections: Vec<Section>,
+    ) -> CompilerResult<AllocationResult> {
+        let model = TokenBoxModel::new(budget).with_reservation(reservation);
+        let allocation = match exec_ctx.trace.as_mut() {
+            Some(trace) => model.allocate_traced(
+                sections,
+                &exec_ctx.lens_registry,
+                self.options.mode,
+                trace,
+            )?,
+            None => {
+                model.allocate_with_mode(sections, &exec_ctx.lens_registry, self.options.mode)?
+            }
+        };
+        Ok(allocation)
+    }
+
+    /// Core profile (§2.1.1): no R-DAG, no layout, no guard and no artifact.
+    fn run_core(&self, runtime_inputs: HashMap<String, ValueNode>) -> CompilerResult<Compilation> {
+        if !runtime_inputs.is_empty() {
+            return Err(CompilerError::Profile(
+                "Runtime inputs are not available under the Core profile".to_string(),
+            ));
+        }
+        if self.options.host_context.is_some() {
+            return Err(CompilerError::Profile(
+                "Host context is not available under the Core profile".to_string(),
+            ));
+        }
+
+        let mut timings = self.load_timings.clone();
+        let mut trace = self.load_trace.clone();
+        let started = Instant::now();
+        let vars = literal_vars(&self.document);
+        let budget = effective_layout_budget(&self.document, self.options.budget);
+        let sections = doc_to_sections(&self.document, &vars, &self.lens_registry)?;
+        let allocation = AllocationResult::unconstrained(sections);
+        let render_output = Renderer::new().render_with_trace(
+            &self.document,
+            &allocation,
+            self.render_context(
+                profile_label(ValidationProfile::Core),
+                policy_hash_for_document(&self.document)?,
+                budget,
+                &vars,
+            ),
+        )?;
+        timings.render = started.elapsed();
+        record_phase(&mut trace, "render", started, serde_json::json!({}));
+
+        Ok(Compilation {
+            payload: render_output.payload,
+            guard_decisions: Vec::new(),
+            artifact: None,
+            telemetry: Telemetry {
+                timings,
+                gas_limit: self.options.context_budget,
+                layout_budget: budget,
+                layout_total: allocation.total_size,
+                ..Telemetry::default()
+            },
+            computed_vars: vars,
+            effective_policy: EffectivePolicy::from_document(&self.document),
+            trace,
+        })
+    }
+
+    fn render_context(
+        &self,
+        profile: &str,
+        policy_hash: Option<String>,
+        budget: usize,
+        computed_vars: &HashMap<String, ValueNode>,
+    ) -> RenderContext {
+        RenderContext {
+            document_hash: Some(self.document_hash.clone()),
+            policy_hash,
+            profile: Some(profile.to_string()),
+            mode: Some(mode_label(self.options.mode).to_string()),
+            host_profile_id: Some(self.options.host_profile_id.clone()),
+            budget_units: Some(budget),
+            target_provider_id: Some(self.options.target_provider_id.clone()),
+            computed_vars: Some(computed_vars.clone()),
+            host_context: self.options.host_context.clone(),
+        }
+    }
+}
+
+/// Everything one run produced.
+#[derive(Debug, Clone)]
+pub struct Compilation {
+    pub payload: CanonicalPayload,
+    /// Engine and renderer guard decisions, merged and resequenced.
+    pub guard_decisions: Vec<GuardDecision>,
+    /// `None` under the Core profile, which has no guard.
+    pub artifact: Option<ExecutionArtifact>,
+    pub telemetry: Telemetry,
+    pub computed_vars: HashMap<String, ValueNode>,
+    pub effective_policy: Option<EffectivePolicy>,
+    /// Phase, R-DAG node, lens call and layout spans, when tracing is on.
+    pub trace: Option<ExecutionTrace>,
+}
+
+/// Phase 3 output of [`Session::execute`].
+pub struct Execution {
+    /// The evaluated R-DAG: its nodes, order and demand-driven skips.
+    pub engine: RDagEngine,
+    /// Computed `@vars`, gas, guard decisions and the lens registry.
+    pub context: ExecutionContext,
+}
+
+/// Phase 4 output of [`Session::lay_out`].
+#[derive(Debug, Clone)]
+pub struct Layout {
+    /// Message sections as built, before allocation.
+    pub sections: Vec<Section>,
+    /// Fixed load of the tools and the output reservation.
+    pub reservation: LayoutReservation,
+    pub allocation: AllocationResult,
+}
+
+/// Wall-clock time spent in each phase.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PhaseTimings {
+    /// Phase 1: parse and resolve.
+    pub resolve: Duration,
+    /// Phase 2: type check.
+    pub validate: Duration,
+    /// Phase 3: R-DAG execution.
+    pub execute: Duration,
+    /// Phase 4: section building and layout.
+    pub layout: Duration,
+    /// Phase 5: render.
+    pub render: Duration,
+}
+
+/// Resource usage of one run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Telemetry {
+    pub timings: PhaseTimings,
+    pub gas_consumed: usize,
+    pub gas_limit: usize,
+    /// Effective layout budget (after `@context.budget`).
+    pub layout_budget: usize,
+    /// Units of the laid-out sections.
+    pub layout_total: usize,
+    pub layout_overflow: usize,
+    pub dropped_sections: usize,
+    /// `@vars` reused from earlier runs (reactive sessions only).
+    pub nodes_reused: usize,
+    /// `@vars` evaluated by this run (reactive sessions only).
+    pub nodes_recomputed: usize,
+    /// `@vars` no output needed (demand-driven sessions only).
+    pub nodes_skipped: usize,
+}
+
+impl Telemetry {
+    fn new(
+        timings: PhaseTimings,
+        exec_ctx: &ExecutionContext,
+        allocation: &AllocationResult,
+    ) -> Self {
+        Self {
+            timings,
+            gas_consumed: exec_ctx.gas.consumed,
+            gas_limit: exec_ctx.gas.limit,
+            layout_budget: allocation.budget,
+            layout_total: allocation.total_size,
+            layout_overflow: allocation.overflow,
+            dropped_sections: allocation
+                .sections
+                .iter()
+                .filter(|section| section.was_dropped)
+                .count(),
+            nodes_reused: 0,
+            nodes_recomputed: 0,
+            nodes_skipped: 0,
+        }
+    }
+}
+
+fn record_phase(
+    trace: &mut Option<ExecutionTrace>,
+    name: &str,
+    started: Instant,
+    args: serde_json::Value,
+) {
+    if let Some(trace) = trace {
+        trace.record(name, "phase", started, args);
+    }
+}
+
+/// Payload `metadata.profile` of a validation profile.
+pub fn profile_label(profile: ValidationProfile) -> &'static str {
+    profile.as_str()
+}
+
+/// Payload `metadata.mode` of an execution mode.
+pub fn mode_label(mode: ExecutionMode) -> &'static str {
+    match mode {
+        ExecutionMode::Pure => "pure",
+        ExecutionMode::Exec => "exec",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT: &str = r#"@vars
+  name: @input(type="string")
+  greeting: "Hello"
+
+@system
+  content: $greeting
+
+@user
+  content: $name
+
+@policy
+  allow: [{ op: "message_emit", name: "system#1" }, { op: "message_emit", name: "user#1" }]
+"#;
+
+    fn inputs(name: &str) -> HashMap<String, ValueNode> {
+        HashMap::from([("name".to_string(), ValueNode::String(name.to_string()))])
+    }
+
+    #[test]
+    fn session_runs_phases_three_to_five_per_input_set() {
+        let compiler = Compiler::builder()
+            .budget(512)
+            .context_budget(1_000)
+            .host_profile_id("acme.prod.v1")
+            .build();
+        let mut session = compiler.load_source(CONTRACT, Path::new(".")).unwrap();
+        assert!(session.document_hash().starts_with("sha256:"));
+
+        let first = session.run(inputs("Ada")).unwrap();
+        let second = session.run(inputs("Grace")).unwrap();
+
+        assert_eq!(first.payload.metadata.host_profile_id, "acme.prod.v1");
+        assert_eq!(
+            first.payload.metadata.document_hash,
+            session.document_hash()
+        );
+        assert_eq!(first.computed_vars["name"], ValueNode::String("Ada".into()));
+        assert_eq!(
+            second.computed_vars["name"],
+            ValueNode::String("Grace".into())
+        );
+
+        let artifact = first.artifact.expect("hypervisor runs produce an artifact");
+        assert_eq!(
+            artifact.provenance.events.len(),
+            first.guard_decisions.len()
+        );
+        assert!(first
+            .guard_decisions
+            .iter()
+            .all(|decision| decision.op == "message_emit" && decision.decision == "allowed"));
+        assert_eq!(first.telemetry.gas_limit, 1_000);
+        assert_eq!(first.telemetry.layout_budget, 512);
+        assert!(first.effective_policy.is_some());
+    }
+
+    #[test]
+    fn core_profile_renders_literal_vars_without_guard_or_artifact() {
+        let source = "@vars\n  name: \"Ada\"\n\n@user\n  content: $name\n\n@policy\n  deny: [{ op: \"message_emit\", name: \"user#1\" }]\n";
+        let compiler = Compiler::builder().profile(ValidationProfile::Core).build();
+        let mut session = compiler.load_source(source, Path::new(".")).unwrap();
+
+        let compilation = session.run(HashMap::new()).unwrap();
+        assert_eq!(compilation.payload.metadata.profile, "core");
+        assert!(compilation.payload.metadata.policy_hash.is_some());
+        assert_eq!(compilation.payload.messages.len(), 1);
+        assert!(compilation.guard_decisions.is_empty());
+        assert!(compilation.artifact.is_none());
+
+        let err = session.run(inputs("Grace")).unwrap_err();
+        assert!(matches!(err, CompilerError::Profile(_)), "{err}");
+        let err = compiler
+            .load_source(CONTRACT, Path::new("."))
+            .err()
+            .expect("@input is not Core");
+        assert!(err.to_string().contains("F801"), "{err}");
+    }
+
+    #[test]
+    fn phase_errors_keep_their_prefix_and_code() {
+        let compiler = Compiler::new();
+        let err = compiler
+            .load_source("@system\n content: \"bad indent\"\n", Path::new("."))
+            .err()
+            .expect("parse error");
+        assert!(err.to_string().starts_with("Parse error"), "{err}");
+
+        let mut session = compiler.load_source(CONTRACT, Path::new(".")).unwrap();
+        let err = session.run(HashMap::new()).unwrap_err();
+        assert!(err.to_string().contains("F453"), "{err}");
+    }
+
+    struct CountingLlmCall(Arc<std::sync::atomic::AtomicUsize>);
+
+    impl fct_std::Lens for CountingLlmCall {
+        fn execute(
+            &self,
+            input: ValueNode,
+            args: Vec<ValueNode>,
+            kwargs: HashMap<String, ValueNode>,
+            ctx: &fct_std::LensContext,
+        ) -> fct_std::LensResult<ValueNode> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            fct_std::LlmCallLens.execute(input, args, kwargs, ctx)
+        }
+
+        fn signature(&self) -> fct_std::LensSignature {
+            fct_std::LlmCallLens.signature()
+        }
+
+        fn effect_class(&self) -> Option<&'static str> {
+            Some("external")
+        }
+    }
+
+    #[test]
+    fn reactive_session_skips_unchanged_lens_calls_but_replays_their_guards() {
+        let source = r#"@vars
+  name: @input(type="string")
+  summary: "weather report" |> llm_call()
+  greeting: $name
+
+@user
+  content: $greeting
+
+@policy
+  allow: [{ id: "llm", op: "lens_call", name: "llm_call" }, { op: "message_emit", name: "user#1" }]
+"#;
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let compiler = |reactive: bool| {
+            let calls = calls.clone();
+            Compiler::builder()
+                .reactive(reactive)
+                .lens_registry(move || {
+                    let mut registry = LensRegistry::new();
+                    registry.register(Box::new(CountingLlmCall(calls.clone())));
+                    registry
+                })
+                .build()
+        };
+        let mut reactive = compiler(true).load_source(source, Path::new(".")).unwrap();
+        let mut plain = compiler(false).load_source(source, Path::new(".")).unwrap();
+
+        for name in ["Ada", "Grace", "Grace"] {
+            let expected = plain.run(inputs(name)).unwrap();
+            let actual = reactive.run(inputs(name)).unwrap();
+            assert_eq!(
+                serde_json::to_value(&actual.guard_decisions).unwrap(),
+                serde_json::to_value(&expected.guard_decisions).unwrap()
+            );
+            assert_eq!(
+                actual.artifact.unwrap().to_json().unwrap(),
+                expected.artifact.unwrap().to_json().unwrap()
+            );
+            assert_eq!(
+                actual.telemetry.gas_consumed,
+                expected.telemetry.gas_consumed
+            );
+        }
+        // 3 plain runs + 1 reactive run; later reactive runs reuse `summary`.
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 4);
+
+        let last = reactive.run(inputs("Lin")).unwrap();
+        assert_eq!(last.telemetry.nodes_reused, 1);
+        assert_eq!(last.telemetry.nodes_recomputed, 2);
+        assert!(last
+            .guard_decisions
+            .iter()
+            .any(|decision| decision.op == "lens_call" && decision.decision == "allowed"));
+    }
+
+    #[test]
+    fn logic_lenses_gate_messages_on_computed_bools() {
+        let source = r#"@vars
+  docs: @input(type="list<string>")
+  has_docs: $docs |> length() |> gt(0)
+  heading: $has_docs |> if_else("Sources:", "No sources")
+
+@system(when=$has_docs)
+  content: $docs |> join(", ")
+
+@user
+  content: $heading
+"#;
+        let mut session = Compiler::new().load_source(source, Path::new(".")).unwrap();
+        let docs = |items: &[&str]| {
+            HashMap::from([(
+                "docs".to_string(),
+                ValueNode::List(
+                    items
+                        .iter()
+                        .map(|item| ValueNode::String(item.to_string()))
+                        .collect(),
+                ),
+            )])
+        };
+
+        let with_docs = session.run(docs(&["a.md", "b.md"])).unwrap();
+        assert_eq!(with_docs.payload.messages.len(), 2);
+        assert_eq!(
+            with_docs.computed_vars["heading"],
+            ValueNode::String("Sources:".to_string())
+        );
+
+        let without = session.run(docs(&[])).unwrap();
+        assert_eq!(without.payload.messages.len(), 1);
+        assert_eq!(
+            without.computed_vars["heading"],
+            ValueNode::String("No sources".to_string())
+        );
+    }
+
+    #[test]
+    fn compression_strategy_truncates_at_sentence_boundaries() {
+        let source = r#"@context
+  budget: 40
+
+@system
+  content: "Be brief."
+
+@user
+  shrink: 1
+  strategy: "" |> collapse_whitespace() |> truncate_sentences(target=30)
+  content: "First   sentence here.  Second sentence here. Third sentence."
+"#;
+        let compilation = Compiler::new()
+            .load_source(source, Path::new("."))
+            .unwrap()
+            .run(HashMap::new())
+            .unwrap();
+
+        let messages = &compilation.payload.messages;
+        assert_eq!(
+            messages[1].content,
+            fct_render::Content::Text("First sentence here.".to_string())
+        );
+    }
+
+    #[test]
+    fn tools_and_output_reserve_count_as_layout_fixed_load() {
+        let source = |budget: usize, reserve: &str| {
+            format!(
+                "@context\n  budget: {budget}\n{reserve}\n@interface WeatherAPI\n  fn get(city: string) -> string (effect=\"read\")\n\n@system\n  tools: [$WeatherAPI]\n  content: \"Use tools.\"\n\n@user\n  shrink: 1\n  content: \"{}\"\n",
+                "x".repeat(100)
+            )
+        };
+        let run = |source: &str, output_reserve: usize| {
+            Compiler::builder()
+                .output_reserve(output_reserve)
+                .build()
+                .load_source(source, Path::new("."))
+                .unwrap()
+                .run(HashMap::new())
+        };
+
+        let roomy = run(&source(4096, ""), 0).unwrap();
+        let tools_json = crate::artifact::canonicalize_json(
+            &serde_json::to_value(&roomy.payload.tools).unwrap(),
+        )
+        .unwrap();
+        let tool_units = fct_engine::count_facet_units(&tools_json);
+        assert!(tool_units > 0);
+        assert_eq!(roomy.payload.metadata.reserved_tool_units, tool_units);
+        assert_eq!(roomy.payload.metadata.reserved_output_units, 0);
+        let metadata = serde_json::to_value(&roomy.payload.metadata).unwrap();
+        assert_eq!(metadata["x_fct_reserved_tool_units"], tool_units);
+        assert!(metadata.get("reserved_tool_units").is_none());
+        assert!(metadata.get("x_fct_reserved_output_units").is_none());
+
+        // System (10 units) and 40 units of user content fit next to the
+        // tools and a 50 unit reply; `@context` overrides the host reserve.
+        let budget = tool_units + 50 + 10 + 40;
+        let reserve = "  x_fct_output_reserve: 50\n";
+        let tight = run(&source(budget, reserve), 7).unwrap();
+        assert_eq!(tight.payload.metadata.reserved_output_units, 50);
+        assert_eq!(
+            tight.payload.messages[1].content,
+            fct_render::Content::Text("x".repeat(40))
+        );
+        assert_eq!(tight.telemetry.layout_total, 50);
+        assert_eq!(tight.telemetry.layout_overflow, 0);
+
+        let host_reserved = run(&source(budget, ""), 50).unwrap();
+        assert_eq!(host_reserved.payload.metadata.reserved_output_units, 50);
+
+        // Below the critical system section plus the reservation: F901.
+        let err = run(&source(budget - 41, reserve), 0).unwrap_err();
+        assert!(err.to_string().contains("F901"), "{err}");
+    }
+
+    #[test]
+    fn execute_and_lay_out_match_the_run_they_stop_short_of() {
+        let source = format!(
+            "@context\n  budget: 30\n\n@vars\n  name: @input(type=\"string\")\n\n@system\n  content: \"Be brief.\"\n\n@user\n  shrink: 1\n  content: \"{}\"\n",
+            "x".repeat(100)
+        );
+        let mut session = Compiler::new()
+            .load_source(&source, Path::new("."))
+            .unwrap();
+        assert!(session.type_checker().is_some());
+
+        let mut execution = session.execute(inputs("Ada")).unwrap();
+        assert_eq!(
+            execution.context.get_variable("name"),
+            Some(&ValueNode::String("Ada".to_string()))
+        );
+        assert_eq!(execution.engine.nodes()[0].name, "name");
+
+        // `@context.budget` wins over the configured 4096, as in a run.
+        let layout = session.lay_out(&mut execution, None).unwrap();
+        let compilation = session.run(inputs("Ada")).unwrap();
+        assert_eq!(layout.allocation.budget, 30);
+        assert_eq!(
+            layout.allocation.total_size,
+            compilation.telemetry.layout_total
+        );
+        assert_eq!(layout.sections[1].base_size, 100);
+        let system_size = layout.sections[0].base_size;
+        assert_eq!(layout.allocation.sections[1].final_size, 30 - system_size);
+
+        let roomy = session.lay_out(&mut execution, Some(200)).unwrap();
+        assert_eq!(roomy.allocation.total_size, system_size + 100);
+
+        let core = Compiler::builder()
+            .profile(ValidationProfile::Core)
+            .build()
+            .load_source("@user\n  content: \"hi\"\n", Path::new("."))
+            .unwrap();
+        assert!(matches!(
+            core.execute(HashMap::new()),
+            Err(CompilerError::Profile(_))
+        ));
+    }
+
+    #[test]
+    fn template_lens_renders_sections_with_partials_from_vars() {
+        let source = r#"@vars
+  docs: @input(type="list<string>")
+  item: "- {{.}}"
+  partials: {item: $item}
+  prompt: "{{#docs}}{{> item}}\n{{/docs}}{{^docs}}No sources{{/docs}}" |> template(docs=$docs, partials=$partials)
+
+@user
+  content: $prompt
+"#;
+        let mut session = Compiler::new().load_source(source, Path::new(".")).unwrap();
+        let docs = |items: &[&str]| {
+            HashMap::from([(
+                "docs".to_string(),
+                ValueNode::List(
+                    items
+                        .iter()
+                        .map(|item| ValueNode::String(item.to_string()))
+                        .collect(),
+                ),
+            )])
+        };
+
+        let with_docs = session.run(docs(&["a.md", "b.md"])).unwrap();
+        assert_eq!(
+            with_docs.computed_vars["prompt"],
+            ValueNode::String("- a.md\n- b.md\n".to_string())
+        );
+
+        let without = session.run(docs(&[])).unwrap();
+        assert_eq!(
+            without.computed_vars["prompt"],
+            ValueNode::String("No sources".to_string())
+        );
+    }
+
+    #[test]
+    fn demand_driven_session_skips_unreferenced_vars() {
+        let source = r#"@vars
+  name: @input(type="string")
+  shared_summary: "library text" |> llm_call()
+  unused_input: @input(type="string")
+  audience: "devs"
+  admin: false
+  show: true
+  label: $audience
+
+@user(when=$show)
+  content: $name
+
+@assistant
+  content: $label
+
+@policy
+  allow: [{ op: "message_emit", name: "user#1", unless: $admin }, { op: "message_emit", name: "assistant#1" }]
+"#;
+        let compiler = Compiler::builder().demand_driven(true).build();
+        let mut session = compiler.load_source(source, Path::new(".")).unwrap();
+
+        // No lens guard or missing-input error: neither var is needed.
+        let compilation = session.run(inputs("Ada")).unwrap();
+        assert_eq!(compilation.telemetry.nodes_skipped, 2);
+        assert!(!compilation.computed_vars.contains_key("shared_summary"));
+        assert!(compilation.computed_vars.contains_key("admin"));
+        assert_eq!(compilation.payload.messages.len(), 2);
+        assert!(compilation
+            .guard_decisions
+            .iter()
+            .all(|decision| decision.op != "lens_call"));
+
+        let err = Compiler::new()
+            .load_source(source, Path::new("."))
+            .unwrap()
+            .run(inputs("Ada"))
+            .unwrap_err();
+        assert!(err.to_string().contains("F454"), "{err}");
+    }
+
+    #[test]
+    fn parallel_session_matches_sequential_runs() {
+        let source = r#"@vars
+  name: @input(type="string")
+  summary: "weather report" |> llm_call()
+  outlook: "travel advice" |> llm_call()
+  headline: $name |> llm_call()
+  digest: [$summary, $outlook, $headline]
+
+@user
+  content: $headline
+
+@policy
+  allow: [{ id: "llm", op: "lens_call", name: "llm_call" }, { op: "message_emit", name: "user#1" }]
+"#;
+        let session = |parallel: bool, reactive: bool| {
+            Compiler::builder()
+                .parallel(parallel)
+                .reactive(reactive)
+                .build()
+                .load_source(source, Path::new("."))
+                .unwrap()
+        };
+        let mut sequential = session(false, false);
+        let mut parallel = session(true, false);
+        let mut parallel_reactive = session(true, true);
+
+        for name in ["Ada", "Grace", "Grace"] {
+            let expected = sequential.run(inputs(name)).unwrap();
+            for actual in [
+                parallel.run(inputs(name)).unwrap(),
+                parallel_reactive.run(inputs(name)).unwrap(),
+            ] {
+                assert_eq!(
+                    serde_json::to_value(&actual.guard_decisions).unwrap(),
+                    serde_json::to_value(&expected.guard_decisions).unwrap()
+                );
+                assert_eq!(
+                    actual.artifact.unwrap().to_json().unwrap(),
+                    expected.artifact.as_ref().unwrap().to_json().unwrap()
+                );
+                assert_eq!(
+                    actual.telemetry.gas_consumed,
+                    expected.telemetry.gas_consumed
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn traced_runs_record_phases_nodes_and_lens_cache_use() {
+        let source = r#"@vars
+  name: @input(type="string")
+  summary: "weather report" |> llm_call()
+
+@user
+  content: $summary
+
+@policy
+  allow: [{ id: "llm", op: "lens_call", name: "llm_call" }, { op: "message_emit", name: "user#1" }]
+"#;
+        let exec = Compiler::builder()
+            .parallel(true)
+            .trace(true)
+            .build()
+            .load_source(source, Path::new("."))
+            .unwrap()
+            .run(inputs("Ada"))
+            .unwrap();
+        let trace = exec.trace.expect("trace");
+        let names: Vec<(&str, &str)> = trace
+            .spans()
+            .iter()
+            .map(|span| (span.category, span.name.as_str()))
+            .collect();
+        for expected in [
+            ("phase", "parse"),
+            ("phase", "resolve"),
+            ("phase", "type_check"),
+            ("rdag", "name"),
+            ("rdag", "summary"),
+            ("phase", "execute"),
+            ("phase", "layout"),
+            ("phase", "render"),
+        ] {
+            assert!(names.contains(&expected), "{expected:?} in {names:?}");
+        }
+        let lens = |trace: &ExecutionTrace| {
+            trace
+                .spans()
+                .iter()
+                .find(|span| span.category == "lens")
+                .map(|span| span.args.clone())
+                .expect("llm_call span")
+        };
+        assert_eq!(lens(&trace)["cache"], "miss");
+        assert!(lens(&trace)["gas"].as_u64().unwrap() > 0);
+        let events = trace.to_chrome_json()["traceEvents"]
+            .as_array()
+            .unwrap()
+            .len();
+        assert!(events > names.len(), "lane names are emitted too");
+
+        let replay = Compiler::builder()
+            .mode(ExecutionMode::Pure)
+            .lens_cache(exec_cache(source))
+            .trace(true)
+            .build()
+            .load_source(source, Path::new("."))
+            .unwrap()
+            .run(inputs("Ada"))
+            .unwrap();
+        assert_eq!(lens(replay.trace.as_ref().unwrap())["cache"], "hit");
+        assert!(Compiler::new()
+            .load_source(source, Path::new("."))
+            .unwrap()
+            .run(inputs("Ada"))
+            .unwrap()
+            .trace
+            .is_none());
+    }
+
+    fn exec_cache(source: &str) -> HashMap<String, ValueNode> {
+        let mut session = Compiler::new().load_source(source, Path::new(".")).unwrap();
+        session.run(inputs("Ada")).unwrap();
+        session.lens_cache().clone()
+    }
+}
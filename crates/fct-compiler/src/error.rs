@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Errors raised while compiling or running a FACET document.
+///
+/// Phase errors keep the code-prefixed messages of the crate that raised them
+/// (`F401`, `F455`, ...), so hosts can match on the code.
+#[derive(Error, Debug)]
+pub enum CompilerError {
+    #[error("Failed to read {kind}: {path:?}")]
+    Io {
+        kind: &'static str,
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Parse error: {0}")]
+    Parse(String),
+
+    #[error("Resolution error: {0}")]
+    Resolve(#[from] fct_resolver::ResolverError),
+
+    #[error("Validation error: {0}")]
+    Validation(#[from] fct_validator::ValidationError),
+
+    #[error(transparent)]
+    Engine(#[from] fct_engine::EngineError),
+
+    #[error(transparent)]
+    Render(#[from] fct_render::RenderError),
+
+    #[error(transparent)]
+    Policy(#[from] fct_policy::PolicyError),
+
+    /// Message content or `when` gate that cannot be materialized (Phase 4).
+    #[error("{0}")]
+    Message(String),
+
+    #[error("{0}")]
+    Input(String),
+
+    /// Operation the active profile does not support.
+    #[error("{0}")]
+    Profile(String),
+
+    #[error("{0}")]
+    Attestation(String),
+
+    /// Unreadable, tampered or incompatible contract bundle.
+    #[error("Bundle error: {0}")]
+    Bundle(String),
+
+    #[error("JSON serialization error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+pub type CompilerResult<T> = Result<T, CompilerError>;
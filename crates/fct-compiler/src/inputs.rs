@@ -0,0 +1,76 @@
+//! Runtime `@input` values supplied by the host as JSON.
+
+use crate::error::{CompilerError, CompilerResult};
+use fct_ast::{OrderedMap, ScalarValue, ValueNode};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Load runtime input values from a JSON object file (`{"<var>": <value>, ...}`).
+pub fn load_runtime_inputs(path: &Path) -> CompilerResult<HashMap<String, ValueNode>> {
+    let raw = fs::read_to_string(path).map_err(|source| CompilerError::Io {
+        kind: "runtime input file",
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let json: serde_json::Value = serde_json::from_str(&raw).map_err(|_| {
+        CompilerError::Input(format!("Runtime input file is not valid JSON: {:?}", path))
+    })?;
+    if !json.is_object() {
+        return Err(CompilerError::Input(format!(
+            "Runtime input JSON root must be an object: {:?}",
+            path
+        )));
+    }
+    runtime_inputs_from_json(&json)
+}
+
+/// Runtime input values from a JSON object.
+pub fn runtime_inputs_from_json(
+    json: &serde_json::Value,
+) -> CompilerResult<HashMap<String, ValueNode>> {
+    let obj = json.as_object().ok_or_else(|| {
+        CompilerError::Input("Runtime input JSON root must be an object".to_string())
+    })?;
+
+    let mut out = HashMap::new();
+    for (k, v) in obj {
+        out.insert(k.clone(), json_to_value_node(v)?);
+    }
+    Ok(out)
+}
+
+/// Convert a JSON value into the equivalent FACET value.
+pub fn json_to_value_node(value: &serde_json::Value) -> CompilerResult<ValueNode> {
+    Ok(match value {
+        serde_json::Value::Null => ValueNode::Scalar(ScalarValue::Null),
+        serde_json::Value::Bool(b) => ValueNode::Scalar(ScalarValue::Bool(*b)),
+        serde_json::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                ValueNode::Scalar(ScalarValue::Int(i))
+            } else if let Some(f) = n.as_f64() {
+                ValueNode::Scalar(ScalarValue::Float(f))
+            } else {
+                return Err(CompilerError::Input(format!(
+                    "Unsupported JSON number: {}",
+                    n
+                )));
+            }
+        }
+        serde_json::Value::String(s) => ValueNode::String(s.clone()),
+        serde_json::Value::Array(items) => {
+            let mut out = Vec::with_capacity(items.len());
+            for item in items {
+                out.push(json_to_value_node(item)?);
+            }
+            ValueNode::List(out)
+        }
+        serde_json::Value::Object(map) => {
+            let mut out = OrderedMap::new();
+            for (k, v) in map {
+                out.insert(k.clone(), json_to_value_node(v)?);
+            }
+            ValueNode::Map(out)
+        }
+    })
+}
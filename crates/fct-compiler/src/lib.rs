@@ -0,0 +1,78 @@
+//! # FACET Compiler
+//!
+//! Library facade over the whole FACET v2.1.3 pipeline, for hosts that embed
+//! the compiler instead of shelling out to `facet-fct`:
+//!
+//! 1. parse and resolve (`fct-parser`, `fct-resolver`),
+//! 2. type check (`fct-validator`),
+//! 3. compute `@vars` (`fct-engine` R-DAG),
+//! 4. build message sections and lay them out (Token Box Model),
+//! 5. render the canonical payload (`fct-render`),
+//!
+//! plus the Execution Artifact over the run's guard decisions.
+//!
+//! A [`Compiler`] is configured once with a [`CompilerBuilder`] (mode, profile,
+//! budgets, host profile and context, lens registry, lens cache). Loading a
+//! document runs Phases 1-2 and yields a [`Session`]; every [`Session::run`]
+//! runs Phases 3-5 for one set of runtime inputs and returns a [`Compilation`]
+//! with the payload, guard decisions, artifact and [`Telemetry`].
+//! [`Session::execute`] and [`Session::lay_out`] stop after Phase 3 or 4 for
+//! tools that show the computed `@vars` or the layout instead.
+//!
+//! [`Session::bundle`] seals the output of Phases 1-2 into a [`ContractBundle`]
+//! (`.fctb`); [`Compiler::load_bundle`] restores a session from it without
+//! re-parsing or re-validating the contract.
+//!
+//! ## Basic Usage
+//!
+//! ```
+//! use fct_ast::ValueNode;
+//! use fct_compiler::{Compiler, ExecutionMode};
+//! use std::collections::HashMap;
+//! use std::path::Path;
+//!
+//! let compiler = Compiler::builder()
+//!     .mode(ExecutionMode::Exec)
+//!     .budget(2048)
+//!     .build();
+//! let mut session = compiler
+//!     .load_source(
+//!         "@vars\n  name: @input(type=\"string\")\n\n@user\n  content: $name\n",
+//!         Path::new("."),
+//!     )
+//!     .unwrap();
+//!
+//! for name in ["Ada", "Grace"] {
+//!     let inputs = HashMap::from([("name".to_string(), ValueNode::String(name.to_string()))]);
+//!     let compilation = session.run(inputs).unwrap();
+//!     assert_eq!(compilation.payload.messages.len(), 1);
+//!     assert!(compilation.artifact.is_some());
+//! }
+//! ```
+
+mod artifact;
+mod bundle;
+mod compiler;
+mod error;
+mod inputs;
+mod sections;
+
+pub use artifact::{
+    build_execution_artifact, build_execution_artifact_with_attestation,
+    build_execution_artifact_with_host_context, canonicalize_json, hash_chain_seed_input,
+    merge_guard_decisions, normalize_guard_decisions, sha256_prefixed, ArtifactMetadata,
+    Attestation, ExecutionArtifact, HashChain, Provenance, RecordedHostContext,
+};
+pub use bundle::{ContractBundle, BUNDLE_FORMAT, BUNDLE_FORMAT_VERSION, COMPILER_VERSION};
+pub use compiler::{
+    mode_label, profile_label, Compilation, Compiler, CompilerBuilder, CompilerOptions, Execution,
+    Layout, PhaseTimings, Session, Telemetry, DEFAULT_HOST_PROFILE_ID,
+};
+pub use error::{CompilerError, CompilerResult};
+pub use fct_engine::{ExecutionMode, ExecutionTrace, TraceSpan};
+pub use fct_validator::ValidationProfile;
+pub use inputs::{json_to_value_node, load_runtime_inputs, runtime_inputs_from_json};
+pub use sections::{
+    doc_to_sections, effective_layout_budget, effective_output_reserve, layout_reservation,
+    literal_vars, resolve_message_value, OUTPUT_RESERVE_KEY,
+};
@@ -0,0 +1,599 @@
+//! Phase 4 inputs: message sections built from the resolved document and the
+//! computed variables.
+//!
+//! Each `@system`/`@user`/`@assistant` block that passes its `when` gates becomes
+//! one [`Section`], with `@context.defaults` applied under the block's own layout
+//! keys. Message content may reference variables and apply Level-0 lenses only.
+
+use crate::error::{CompilerError, CompilerResult};
+use fct_ast::{
+    BodyNode, FacetDocument, FacetNode, OrderedMap, PipelineNode, ScalarValue, ValueNode,
+};
+use fct_engine::{
+    count_facet_units_in_value, derive_message_section_id, ItemPacking, LayoutReservation, Section,
+    PACK_KEY,
+};
+use fct_render::{tools_facet_units, RenderContext};
+use fct_std::{LensContext, LensRegistry, TrustLevel};
+use std::collections::HashMap;
+
+/// Merged `@vars`, later blocks overriding earlier ones. Under the Core profile
+/// every value is already a literal, so this is the whole of "Phase 3".
+pub fn literal_vars(doc: &FacetDocument) -> HashMap<String, ValueNode> {
+    let mut vars = HashMap::new();
+    for block in &doc.blocks {
+        if let FacetNode::Vars(vars_block) = block {
+            for body in &vars_block.body {
+                if let BodyNode::KeyValue(kv) = body {
+                    vars.insert(kv.key.clone(), kv.value.clone());
+                }
+            }
+        }
+    }
+    vars
+}
+
+/// `@context` host extension reserving FACET Units for the model's reply.
+pub const OUTPUT_RESERVE_KEY: &str = "x_fct_output_reserve";
+
+/// Layout budget: `@context.budget` when declared, otherwise `host_budget`.
+pub fn effective_layout_budget(doc: &FacetDocument, host_budget: usize) -> usize {
+    context_int_from_doc(doc, "budget").unwrap_or(host_budget)
+}
+
+/// Output reservation: `@context.x_fct_output_reserve` when declared,
+/// otherwise `host_reserve`.
+pub fn effective_output_reserve(doc: &FacetDocument, host_reserve: usize) -> usize {
+    context_int_from_doc(doc, OUTPUT_RESERVE_KEY).unwrap_or(host_reserve)
+}
+
+/// Layout fixed load outside the messages: the units of the `tools` the render
+/// would emit under `render_context`, and the effective output reservation.
+pub fn layout_reservation(
+    doc: &FacetDocument,
+    render_context: &RenderContext,
+    host_output_reserve: usize,
+) -> CompilerResult<LayoutReservation> {
+    Ok(LayoutReservation {
+        tools: tools_facet_units(doc, render_context)?,
+        output: effective_output_reserve(doc, host_output_reserve),
+    })
+}
+
+fn context_int_from_doc(doc: &FacetDocument, key: &str) -> Option<usize> {
+    for block in &doc.blocks {
+        if let FacetNode::Context(ctx) = block {
+            for body in &ctx.body {
+                if let BodyNode::KeyValue(kv) = body {
+                    if kv.key == key {
+                        if let ValueNode::Scalar(ScalarValue::Int(v)) = kv.value {
+                            if v >= 0 {
+                                return Some(v as usize);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Layout sections for every emitted message block, in document order.
+pub fn doc_to_sections(
+    doc: &FacetDocument,
+    computed_vars: &HashMap<String, ValueNode>,
+    lens_registry: &LensRegistry,
+) -> CompilerResult<Vec<Section>> {
+    let mut sections = Vec::new();
+    let defaults = context_layout_defaults_from_doc(doc);
+    let mut system_count = 0usize;
+    let mut user_count = 0usize;
+    let mut assistant_count = 0usize;
+
+    for block in &doc.blocks {
+        let (role, derived_id, body) = match block {
+            FacetNode::System(b) => {
+                system_count += 1;
+                (
+                    "system",
+                    derive_message_section_id("system", system_count),
+                    b,
+                )
+            }
+            FacetNode::User(b) => {
+                user_count += 1;
+                ("user", derive_message_section_id("user", user_count), b)
+            }
+            FacetNode::Assistant(b) => {
+                assistant_count += 1;
+                (
+                    "assistant",
+                    derive_message_section_id("assistant", assistant_count),
+                    b,
+                )
+            }
+            _ => continue,
+        };
+
+        if !should_emit_message_block(body, computed_vars)? {
+            continue;
+        }
+
+        let layout = resolve_section_layout(body, &defaults, &derived_id);
+        let content = block_content_or_default(body, computed_vars, lens_registry)?;
+        let base_size = count_facet_units_in_value(&content);
+        let mut section = Section::new(layout.id, content, base_size)
+            .with_role(role)
+            .with_priority(layout.priority)
+            .with_limits(layout.min, layout.grow, layout.shrink);
+        if let Some(strategy) = layout.strategy {
+            section = section.with_strategy(strategy);
+        }
+        if let Some(packing) = layout.packing {
+            section = section.with_packing(packing);
+        }
+        sections.push(section);
+    }
+
+    Ok(sections)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LayoutDefaults {
+    priority: i32,
+    min: usize,
+    grow: f64,
+    shrink: f64,
+}
+
+#[derive(Debug, Clone)]
+struct SectionLayout {
+    id: String,
+    priority: i32,
+    min: usize,
+    grow: f64,
+    shrink: f64,
+    strategy: Option<PipelineNode>,
+    packing: Option<ItemPacking>,
+}
+
+fn context_layout_defaults_from_doc(doc: &FacetDocument) -> LayoutDefaults {
+    let mut out = LayoutDefaults {
+        priority: 500,
+        min: 0,
+        grow: 0.0,
+        shrink: 0.0,
+    };
+
+    for block in &doc.blocks {
+        let FacetNode::Context(ctx) = block else {
+            continue;
+        };
+        for body in &ctx.body {
+            let BodyNode::KeyValue(kv) = body else {
+                continue;
+            };
+            if kv.key != "defaults" {
+                continue;
+            }
+            let ValueNode::Map(defaults) = &kv.value else {
+                continue;
+            };
+            if let Some(v) = defaults.get("priority").and_then(value_as_i32) {
+                out.priority = v;
+            }
+            if let Some(v) = defaults.get("min").and_then(value_as_usize) {
+                out.min = v;
+            }
+            if let Some(v) = defaults.get("grow").and_then(value_as_f64) {
+                out.grow = v;
+            }
+            if let Some(v) = defaults.get("shrink").and_then(value_as_f64) {
+                out.shrink = v;
+            }
+        }
+    }
+
+    out
+}
+
+fn resolve_section_layout(
+    block: &fct_ast::FacetBlock,
+    defaults: &LayoutDefaults,
+    derived_id: &str,
+) -> SectionLayout {
+    let mut layout = SectionLayout {
+        id: derived_id.to_string(),
+        priority: defaults.priority,
+        min: defaults.min,
+        grow: defaults.grow,
+        shrink: defaults.shrink,
+        strategy: None,
+        packing: None,
+    };
+
+    for body in &block.body {
+        let BodyNode::KeyValue(kv) = body else {
+            continue;
+        };
+        match kv.key.as_str() {
+            "id" => {
+                if let ValueNode::String(v) = &kv.value {
+                    layout.id = v.clone();
+                }
+            }
+            "priority" => {
+                if let Some(v) = value_as_i32(&kv.value) {
+                    layout.priority = v;
+                }
+            }
+            "min" => {
+                if let Some(v) = value_as_usize(&kv.value) {
+                    layout.min = v;
+                }
+            }
+            "grow" => {
+                if let Some(v) = value_as_f64(&kv.value) {
+                    layout.grow = v;
+                }
+            }
+            "shrink" => {
+                if let Some(v) = value_as_f64(&kv.value) {
+                    layout.shrink = v;
+                }
+            }
+            "strategy" => {
+                if let ValueNode::Pipeline(p) = &kv.value {
+                    layout.strategy = Some(p.clone());
+                }
+            }
+            PACK_KEY => layout.packing = ItemPacking::from_value(&kv.value),
+            _ => {}
+        }
+    }
+
+    layout
+}
+
+fn value_as_i32(value: &ValueNode) -> Option<i32> {
+    match value {
+        ValueNode::Scalar(ScalarValue::Int(v)) => Some((*v).clamp(0, i32::MAX as i64) as i32),
+        _ => None,
+    }
+}
+
+fn value_as_usize(value: &ValueNode) -> Option<usize> {
+    match value {
+        ValueNode::Scalar(ScalarValue::Int(v)) if *v >= 0 => Some(*v as usize),
+        _ => None,
+    }
+}
+
+fn value_as_f64(value: &ValueNode) -> Option<f64> {
+    match value {
+        ValueNode::Scalar(ScalarValue::Int(v)) if *v >= 0 => Some(*v as f64),
+        ValueNode::Scalar(ScalarValue::Float(v)) if *v >= 0.0 => Some(*v),
+        _ => None,
+    }
+}
+
+fn should_emit_message_block(
+    block: &fct_ast::FacetBlock,
+    computed_vars: &HashMap<String, ValueNode>,
+) -> CompilerResult<bool> {
+    let attr_when = match block.attributes.get("when") {
+        Some(v) => eval_when_atom(v, computed_vars)?,
+        None => true,
+    };
+
+    let mut body_when = true;
+    for body in &block.body {
+        let BodyNode::KeyValue(kv) = body else {
+            continue;
+        };
+        if kv.key == "when" {
+            body_when = eval_when_atom(&kv.value, computed_vars)?;
+        }
+    }
+
+    Ok(attr_when && body_when)
+}
+
+fn eval_when_atom(
+    when_value: &ValueNode,
+    computed_vars: &HashMap<String, ValueNode>,
+) -> CompilerResult<bool> {
+    match when_value {
+        ValueNode::Scalar(ScalarValue::Bool(v)) => Ok(*v),
+        ValueNode::Variable(var_ref) => match resolve_variable_ref(var_ref, computed_vars)? {
+            ValueNode::Scalar(ScalarValue::Bool(v)) => Ok(v),
+            _ => Err(CompilerError::Message(
+                "F451: Type mismatch: 'when' must evaluate to bool".to_string(),
+            )),
+        },
+        _ => Err(CompilerError::Message(
+            "F451: Type mismatch: 'when' must be bool or variable reference".to_string(),
+        )),
+    }
+}
+
+fn block_content_or_default(
+    block: &fct_ast::FacetBlock,
+    computed_vars: &HashMap<String, ValueNode>,
+    lens_registry: &LensRegistry,
+) -> CompilerResult<ValueNode> {
+    for body in &block.body {
+        if let BodyNode::KeyValue(kv) = body {
+            if kv.key == "content" {
+                return resolve_message_value(&kv.value, computed_vars, lens_registry);
+            }
+        }
+    }
+    Ok(ValueNode::String(format!("{} block", block.name)))
+}
+
+/// Materialize message content against `computed_vars`, applying Level-0 lenses.
+pub fn resolve_message_value(
+    value: &ValueNode,
+    computed_vars: &HashMap<String, ValueNode>,
+    lens_registry: &LensRegistry,
+) -> CompilerResult<ValueNode> {
+    match value {
+        ValueNode::Variable(var_ref) => resolve_variable_ref(var_ref, computed_vars),
+        ValueNode::List(items) => {
+            let mut out = Vec::with_capacity(items.len());
+            for item in items {
+                out.push(resolve_message_value(item, computed_vars, lens_registry)?);
+            }
+            Ok(ValueNode::List(out))
+        }
+        ValueNode::Map(map) => {
+            let mut out = OrderedMap::new();
+            for (k, v) in map {
+                out.insert(
+                    k.clone(),
+                    resolve_message_value(v, computed_vars, lens_registry)?,
+                );
+            }
+            Ok(ValueNode::Map(out))
+        }
+        ValueNode::Pipeline(pipeline) => {
+            let mut current =
+                resolve_message_value(&pipeline.initial, computed_vars, lens_registry)?;
+            let ctx = LensContext {
+                variables: computed_vars.clone(),
+            };
+
+            for lens_call in &pipeline.lenses {
+                let lens = lens_registry.get(&lens_call.name).ok_or_else(|| {
+                    CompilerError::Message(format!(
+                        "F802: Unknown lens in message content: {}",
+                        lens_call.name
+                    ))
+                })?;
+                let signature = lens.signature();
+                if signature.trust_level != TrustLevel::Pure {
+                    return Err(CompilerError::Message(format!(
+                        "F801: Message content lens '{}' must be Level-0 (pure)",
+                        lens_call.name
+                    )));
+                }
+
+                let mut resolved_args = Vec::with_capacity(lens_call.args.len());
+                for arg in &lens_call.args {
+                    resolved_args.push(resolve_message_value(arg, computed_vars, lens_registry)?);
+                }
+                let mut resolved_kwargs = HashMap::with_capacity(lens_call.kwargs.len());
+                for (k, v) in &lens_call.kwargs {
+                    resolved_kwargs.insert(
+                        k.clone(),
+                        resolve_message_value(v, computed_vars, lens_registry)?,
+                    );
+                }
+
+                current = lens
+                    .execute(current, resolved_args, resolved_kwargs, &ctx)
+                    .map_err(|e| {
+                        CompilerError::Message(format!(
+                            "F801: Message content lens execution failed: {}",
+                            e
+                        ))
+                    })?;
+            }
+            Ok(current)
+        }
+        ValueNode::Directive(_) => Err(CompilerError::Message(
+            "Unresolved directive in message content; expected computed value".to_string(),
+        )),
+        _ => Ok(value.clone()),
+    }
+}
+
+fn resolve_variable_ref(
+    var_ref: &str,
+    computed_vars: &HashMap<String, ValueNode>,
+) -> CompilerResult<ValueNode> {
+    let mut parts = var_ref.split('.');
+    let base = parts.next().unwrap_or(var_ref);
+    let mut current = computed_vars
+        .get(base)
+        .cloned()
+        .ok_or_else(|| CompilerError::Message(format!("F401: Variable not found: {}", base)))?;
+
+    for segment in parts {
+        if segment.chars().all(|c| c.is_ascii_digit()) {
+            return Err(CompilerError::Message(format!(
+                "F452: Numeric indexing is not standardized in v2.1.3: {}",
+                var_ref
+            )));
+        }
+
+        current = match current {
+            ValueNode::Map(map) => map.get(segment).cloned().ok_or_else(|| {
+                CompilerError::Message(format!("F405: Invalid variable path: {}", var_ref))
+            })?,
+            _ => {
+                return Err(CompilerError::Message(format!(
+                    "F405: Invalid variable path: {}",
+                    var_ref
+                )))
+            }
+        };
+    }
+
+    Ok(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fct_ast::{LensCallNode, Span};
+    use fct_parser::parse_document;
+
+    #[test]
+    fn resolve_message_value_evaluates_pipeline_from_computed_vars() {
+        let value = ValueNode::Pipeline(PipelineNode {
+            initial: Box::new(ValueNode::Variable("name".to_string())),
+            lenses: vec![LensCallNode {
+                name: "uppercase".to_string(),
+                args: vec![],
+                kwargs: OrderedMap::new(),
+                span: Span {
+                    start: 0,
+                    end: 0,
+                    line: 1,
+                    column: 1,
+                },
+            }],
+            span: Span {
+                start: 0,
+                end: 0,
+                line: 1,
+                column: 1,
+            },
+        });
+        let computed_vars =
+            HashMap::from([("name".to_string(), ValueNode::String("world".to_string()))]);
+        let lens_registry = LensRegistry::new();
+
+        let resolved = resolve_message_value(&value, &computed_vars, &lens_registry)
+            .expect("message pipeline should resolve");
+        assert_eq!(resolved, ValueNode::String("WORLD".to_string()));
+    }
+
+    #[test]
+    fn resolve_message_value_rejects_non_pure_lens_in_message_content() {
+        let value = ValueNode::Pipeline(PipelineNode {
+            initial: Box::new(ValueNode::String("hello".to_string())),
+            lenses: vec![LensCallNode {
+                name: "llm_call".to_string(),
+                args: vec![],
+                kwargs: OrderedMap::new(),
+                span: Span {
+                    start: 0,
+                    end: 0,
+                    line: 1,
+                    column: 1,
+                },
+            }],
+            span: Span {
+                start: 0,
+                end: 0,
+                line: 1,
+                column: 1,
+            },
+        });
+        let computed_vars = HashMap::new();
+        let lens_registry = LensRegistry::new();
+
+        let err = resolve_message_value(&value, &computed_vars, &lens_registry)
+            .expect_err("non-pure message lens must be rejected");
+        let text = err.to_string();
+        assert!(text.contains("F801"));
+        assert!(text.contains("Level-0 (pure)"));
+    }
+
+    #[test]
+    fn effective_layout_budget_prefers_context_budget() {
+        let source = r#"
+@context
+  budget: 123
+
+@user
+  content: "hello"
+"#;
+        let doc = parse_document(source).expect("doc should parse");
+        assert_eq!(effective_layout_budget(&doc, 4096), 123);
+    }
+
+    #[test]
+    fn effective_layout_budget_uses_host_budget_when_context_absent() {
+        let source = r#"
+@user
+  content: "hello"
+"#;
+        let doc = parse_document(source).expect("doc should parse");
+        assert_eq!(effective_layout_budget(&doc, 4096), 4096);
+    }
+
+    #[test]
+    fn doc_to_sections_applies_context_defaults_and_message_overrides() {
+        let source = r#"
+@context
+  budget: 500
+  defaults: { priority: 610, min: 3, grow: 0.7, shrink: 0.4 }
+
+@user
+  content: "first"
+
+@user
+  id: "u.custom"
+  priority: 10
+  min: 1
+  grow: 2
+  shrink: 0
+  strategy: " text " |> trim()
+  content: "second"
+"#;
+        let doc = parse_document(source).expect("doc should parse");
+        let sections = doc_to_sections(&doc, &HashMap::new(), &LensRegistry::new())
+            .expect("sections should build");
+
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].id, "user#1");
+        assert_eq!(sections[0].role.as_deref(), Some("user"));
+        assert_eq!(sections[0].priority, 610);
+        assert_eq!(sections[0].min, 3);
+        assert_eq!(sections[0].grow, 0.7);
+        assert_eq!(sections[0].shrink, 0.4);
+
+        assert_eq!(sections[1].id, "u.custom");
+        assert_eq!(sections[1].role.as_deref(), Some("user"));
+        assert_eq!(sections[1].priority, 10);
+        assert_eq!(sections[1].min, 1);
+        assert_eq!(sections[1].grow, 2.0);
+        assert_eq!(sections[1].shrink, 0.0);
+        assert!(sections[1].strategy.is_some());
+        assert!(sections[1].is_critical);
+    }
+
+    #[test]
+    fn doc_to_sections_applies_body_when_gating() {
+        let source = r#"
+@user
+  when: false
+  content: "hidden"
+
+@user
+  content: "visible"
+"#;
+        let doc = parse_document(source).expect("doc should parse");
+        let sections = doc_to_sections(&doc, &HashMap::new(), &LensRegistry::new())
+            .expect("sections should build");
+
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].id, "user#2");
+        assert_eq!(sections[0].role.as_deref(), Some("user"));
+    }
+}
@@ -5,9 +5,53 @@
 use crate::errors::{EngineError, EngineResult};
 use crate::r_dag::ExecutionMode;
 use crate::tokenizer::Tokenizer;
+use crate::trace::ExecutionTrace;
 use fct_ast::{OrderedMap, PipelineNode, ValueNode};
-use fct_std::{LensContext, LensRegistry, TrustLevel};
+use fct_std::{item_score, lowest_score_order, LensContext, LensRegistry, TrustLevel};
 use std::collections::HashMap;
+use std::time::Instant;
+
+/// Message field (a host extension) that sets a section's [`ItemPacking`].
+pub const PACK_KEY: &str = "x_fct_pack";
+
+/// Order in which a list-valued section gives up whole items under pressure
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemPacking {
+    /// Drop from the end of the list.
+    Position,
+    /// Drop the lowest value of a numeric item field first. Items without a
+    /// numeric score count as lowest; among equal scores the later item goes first.
+    Score(String),
+}
+
+impl ItemPacking {
+    /// Parse a message [`PACK_KEY`] field: `"position"` or `{ score: "<field>" }`.
+    pub fn from_value(value: &ValueNode) -> Option<Self> {
+        match value {
+            ValueNode::String(order) if order == "position" => Some(Self::Position),
+            ValueNode::Map(map) if map.len() == 1 => match map.get("score") {
+                Some(ValueNode::String(field)) => Some(Self::Score(field.clone())),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn score(&self, item: &ValueNode) -> Option<f64> {
+        match self {
+            Self::Position => None,
+            Self::Score(field) => item_score(item, field),
+        }
+    }
+}
+
+/// A list item removed whole by [`ItemPacking`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DroppedItem {
+    pub index: usize,       // position in the original list
+    pub size: usize,        // FACET Units of the item
+    pub score: Option<f64>, // packing score, when packing by score
+}
 
 /// Represents a logical prompt section with allocation attributes
 #[derive(Debug, Clone)]
@@ -21,6 +65,7 @@ pub struct Section {
     pub grow: f64,                      // weight for distributing excess space
     pub shrink: f64,                    // weight for compression/removal
     pub strategy: Option<PipelineNode>, // compression lens pipeline
+    pub packing: Option<ItemPacking>,   // item-level packing for list content
     pub content: ValueNode,             // actual content
     pub current_size: usize,            // current allocated size
     pub is_critical: bool,              // shrink == 0
@@ -38,6 +83,7 @@ impl Section {
             grow: 0.0,
             shrink: 0.0,
             strategy: None,
+            packing: None,
             content,
             current_size: base_size,
             is_critical: false, // will be calculated
@@ -56,6 +102,7 @@ impl Section {
             grow: 0.0,
             shrink: 0.0,
             strategy: None,
+            packing: None,
             content,
             current_size: base_size,
             is_critical: false,
@@ -85,6 +132,11 @@ impl Section {
         self
     }
 
+    pub fn with_packing(mut self, packing: ItemPacking) -> Self {
+        self.packing = Some(packing);
+        self
+    }
+
     /// Apply compression strategy and return new size
     pub fn apply_compression(
         &self,
@@ -146,13 +198,56 @@ impl Section {
     }
 }
 
+/// Fixed load outside the message sections, taken from the budget before packing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LayoutReservation {
+    pub tools: usize,  // FACET Units of the canonical tool definitions
+    pub output: usize, // FACET Units kept free for the model's reply
+}
+
+impl LayoutReservation {
+    pub fn total(&self) -> usize {
+        self.tools.saturating_add(self.output)
+    }
+}
+
 /// Result of Token Box Model allocation
 #[derive(Debug, Clone)]
 pub struct AllocationResult {
     pub sections: Vec<AllocatedSection>,
-    pub total_size: usize,
+    pub total_size: usize, // units of the laid-out sections, without `reserved`
     pub budget: usize,
     pub overflow: usize,
+    pub reserved: LayoutReservation,
+}
+
+impl AllocationResult {
+    /// Every section at its base size, in source order, without layout.
+    ///
+    /// Used by the Core profile, which renders without the Token Box Model;
+    /// the budget is the total size, so nothing overflows.
+    pub fn unconstrained(mut sections: Vec<Section>) -> Self {
+        sections.sort_by_key(|section| section.source_index);
+        let total_size = sections.iter().map(|section| section.base_size).sum();
+        Self {
+            sections: sections
+                .into_iter()
+                .map(|section| AllocatedSection {
+                    final_size: section.base_size,
+                    section,
+                    was_compressed: false,
+                    strategy_applied: false,
+                    was_truncated: false,
+                    was_dropped: false,
+                    dropped_items: Vec::new(),
+                })
+                .collect(),
+            total_size,
+            budget: total_size,
+            overflow: 0,
+            reserved: LayoutReservation::default(),
+        }
+    }
 }
 
 /// A section with its final allocated size
@@ -161,14 +256,17 @@ pub struct AllocatedSection {
     pub section: Section,
     pub final_size: usize,
     pub was_compressed: bool,
+    pub strategy_applied: bool, // shrunk by its compression strategy
     pub was_truncated: bool,
     pub was_dropped: bool,
+    pub dropped_items: Vec<DroppedItem>, // whole list items removed by packing
 }
 
 /// Token Box Model implementation
 pub struct TokenBoxModel {
     budget: usize,
     tokenizer: Tokenizer,
+    reservation: LayoutReservation,
 }
 
 impl TokenBoxModel {
@@ -177,11 +275,21 @@ impl TokenBoxModel {
             // Fallback to default tokenizer if initialization fails
             Tokenizer::default()
         });
-        Self { budget, tokenizer }
+        Self::with_tokenizer(budget, tokenizer)
     }
 
     pub fn with_tokenizer(budget: usize, tokenizer: Tokenizer) -> Self {
-        Self { budget, tokenizer }
+        Self {
+            budget,
+            tokenizer,
+            reservation: LayoutReservation::default(),
+        }
+    }
+
+    /// Count tool definitions and the output reservation as fixed load.
+    pub fn with_reservation(mut self, reservation: LayoutReservation) -> Self {
+        self.reservation = reservation;
+        self
     }
 
     /// Get reference to tokenizer
@@ -199,17 +307,39 @@ impl TokenBoxModel {
     }
 
     pub fn allocate_with_mode(
+        &self,
+        sections: Vec<Section>,
+        lens_registry: &LensRegistry,
+        mode: ExecutionMode,
+    ) -> EngineResult<AllocationResult> {
+        self.allocate_inner(sections, lens_registry, mode, None)
+    }
+
+    /// [`TokenBoxModel::allocate_with_mode`], recording each flexible section's
+    /// compression step (kept, compressed, truncated or dropped) in `trace`.
+    pub fn allocate_traced(
+        &self,
+        sections: Vec<Section>,
+        lens_registry: &LensRegistry,
+        mode: ExecutionMode,
+        trace: &mut ExecutionTrace,
+    ) -> EngineResult<AllocationResult> {
+        self.allocate_inner(sections, lens_registry, mode, Some(trace))
+    }
+
+    fn allocate_inner(
         &self,
         mut sections: Vec<Section>,
         lens_registry: &LensRegistry,
         mode: ExecutionMode,
+        trace: Option<&mut ExecutionTrace>,
     ) -> EngineResult<AllocationResult> {
         for (idx, section) in sections.iter_mut().enumerate() {
             section.source_index = idx;
         }
 
         // Step 1: Calculate Fixed Load
-        let (fixed_load, _critical_sections) = self.calculate_fixed_load(&sections)?;
+        let fixed_load = self.fixed_load(&sections);
 
         if fixed_load > self.budget {
             return Err(EngineError::BudgetExceeded {
@@ -220,44 +350,61 @@ impl TokenBoxModel {
 
         // Step 2: If everything fits, keep all sections as-is.
         let current_total: usize = sections.iter().map(|s| s.current_size).sum();
-        if current_total <= self.budget {
+        if current_total <= self.section_budget() {
             let mut allocated_sections = Vec::new();
             for section in sections {
                 allocated_sections.push(AllocatedSection {
                     final_size: section.current_size,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section,
                 });
             }
-            return Ok(AllocationResult {
-                sections: sort_allocated_by_source(allocated_sections),
-                total_size: current_total,
-                budget: self.budget,
-                overflow: 0,
-            });
+            return Ok(self.result(
+                sort_allocated_by_source(allocated_sections),
+                current_total,
+                0,
+            ));
         }
 
         // Step 3: Compression/drop for flexible sections (if needed)
-        let allocation_result = self.compress_sections(sections, lens_registry, mode)?;
+        let allocation_result = self.compress_sections(sections, lens_registry, mode, trace)?;
 
         Ok(allocation_result)
     }
 
-    /// Step 1: Calculate Fixed Load from critical sections
-    fn calculate_fixed_load(&self, sections: &[Section]) -> EngineResult<(usize, Vec<usize>)> {
-        let mut fixed_load = 0;
-        let mut critical_indices = Vec::new();
+    /// Step 1: Fixed Load, the size of the critical sections plus the
+    /// reservation. Budgets below it fail with F901.
+    pub fn fixed_load(&self, sections: &[Section]) -> usize {
+        sections
+            .iter()
+            .filter(|section| section.is_critical)
+            .map(|section| section.base_size)
+            .sum::<usize>()
+            .saturating_add(self.reservation.total())
+    }
 
-        for (i, section) in sections.iter().enumerate() {
-            if section.is_critical {
-                fixed_load += section.base_size;
-                critical_indices.push(i);
-            }
-        }
+    /// Budget left for the message sections once the reservation is taken.
+    fn section_budget(&self) -> usize {
+        self.budget.saturating_sub(self.reservation.total())
+    }
 
-        Ok((fixed_load, critical_indices))
+    fn result(
+        &self,
+        sections: Vec<AllocatedSection>,
+        total_size: usize,
+        overflow: usize,
+    ) -> AllocationResult {
+        AllocationResult {
+            sections,
+            total_size,
+            budget: self.budget,
+            overflow,
+            reserved: self.reservation,
+        }
     }
 
     /// Step 3: Compress sections to fit budget
@@ -266,7 +413,10 @@ impl TokenBoxModel {
         sections: Vec<Section>,
         lens_registry: &LensRegistry,
         mode: ExecutionMode,
+        mut trace: Option<&mut ExecutionTrace>,
     ) -> EngineResult<AllocationResult> {
+        let budget = self.section_budget();
+
         // Separate critical and flexible sections
         let mut critical_sections: Vec<Section> = Vec::new();
         let mut flexible_sections: Vec<Section> = Vec::new();
@@ -285,8 +435,10 @@ impl TokenBoxModel {
             .map(|s| AllocatedSection {
                 final_size: s.current_size,
                 was_compressed: false,
+                strategy_applied: false,
                 was_truncated: false,
                 was_dropped: false,
+                dropped_items: Vec::new(),
                 section: s,
             })
             .collect();
@@ -294,35 +446,35 @@ impl TokenBoxModel {
         let critical_total: usize = allocated_sections.iter().map(|a| a.final_size).sum();
 
         if flexible_sections.is_empty() {
-            return Ok(AllocationResult {
-                sections: allocated_sections,
-                total_size: critical_total,
-                budget: self.budget,
-                overflow: critical_total.saturating_sub(self.budget),
-            });
+            return Ok(self.result(
+                allocated_sections,
+                critical_total,
+                critical_total.saturating_sub(budget),
+            ));
         }
 
         let flexible_total: usize = flexible_sections.iter().map(|s| s.current_size).sum();
         let current_total = critical_total + flexible_total;
 
-        if current_total <= self.budget {
+        if current_total <= budget {
             // No compression needed, add all flexible sections
             for section in flexible_sections {
                 allocated_sections.push(AllocatedSection {
                     final_size: section.current_size,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section,
                 });
             }
 
-            return Ok(AllocationResult {
-                sections: sort_allocated_by_source(allocated_sections),
-                total_size: current_total,
-                budget: self.budget,
-                overflow: 0,
-            });
+            return Ok(self.result(
+                sort_allocated_by_source(allocated_sections),
+                current_total,
+                0,
+            ));
         }
 
         // Sort flexible sections by (priority ASC, shrink DESC, original section order ASC)
@@ -340,18 +492,24 @@ impl TokenBoxModel {
         let mut running_total = current_total;
 
         for mut section in flexible_sections.into_iter() {
+            let started = trace.is_some().then(Instant::now);
             let mut was_compressed = false;
+            let mut strategy_applied = false;
             let mut was_truncated = false;
             let original_size = section.current_size;
 
-            if running_total <= self.budget {
-                allocated_sections.push(AllocatedSection {
+            if running_total <= budget {
+                let allocated = AllocatedSection {
                     final_size: section.current_size,
                     was_compressed: was_compressed || section.current_size < original_size,
+                    strategy_applied,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section,
-                });
+                };
+                trace_layout_step(trace.as_deref_mut(), started, &allocated, original_size);
+                allocated_sections.push(allocated);
                 continue;
             }
 
@@ -366,12 +524,28 @@ impl TokenBoxModel {
                     section.current_size = compressed_size;
                     running_total = running_total.saturating_sub(size_reduction);
                     was_compressed = true;
+                    strategy_applied = true;
+                }
+            }
+
+            // If still over budget, drop whole list items in the declared packing order.
+            let mut dropped_items = Vec::new();
+            if running_total > budget && section.current_size > section.min {
+                let need = running_total - budget;
+                if let Some((packed_content, packed_size, dropped)) =
+                    self.pack_items(&section, need)
+                {
+                    running_total = running_total
+                        .saturating_sub(section.current_size.saturating_sub(packed_size));
+                    section.content = packed_content;
+                    section.current_size = packed_size;
+                    dropped_items = dropped;
                 }
             }
 
             // If still over budget, truncate deterministically from the end down to `min`.
-            if running_total > self.budget && section.current_size > section.min {
-                let need = running_total - self.budget;
+            if running_total > budget && section.current_size > section.min {
+                let need = running_total - budget;
                 let reducible = section.current_size - section.min;
                 let requested_reduction = std::cmp::min(need, reducible);
                 if requested_reduction > 0 {
@@ -389,36 +563,97 @@ impl TokenBoxModel {
             }
 
             // If still over budget and this section is at min, drop it.
-            if running_total > self.budget && section.current_size == section.min {
+            let allocated = if running_total > budget && section.current_size == section.min {
                 running_total = running_total.saturating_sub(section.current_size);
-                allocated_sections.push(AllocatedSection {
+                AllocatedSection {
                     final_size: 0,
                     was_compressed,
+                    strategy_applied,
                     was_truncated,
                     was_dropped: true,
+                    dropped_items,
                     section,
-                });
+                }
             } else {
-                allocated_sections.push(AllocatedSection {
+                AllocatedSection {
                     final_size: section.current_size,
                     was_compressed: was_compressed || section.current_size < original_size,
+                    strategy_applied,
                     was_truncated,
                     was_dropped: false,
+                    dropped_items,
                     section,
-                });
-            }
+                }
+            };
+            trace_layout_step(trace.as_deref_mut(), started, &allocated, original_size);
+            allocated_sections.push(allocated);
         }
 
         let allocated_sections = sort_allocated_by_source(allocated_sections);
 
         let final_total: usize = allocated_sections.iter().map(|a| a.final_size).sum();
 
-        Ok(AllocationResult {
-            sections: allocated_sections,
-            total_size: final_total,
-            budget: self.budget,
-            overflow: final_total.saturating_sub(self.budget),
-        })
+        Ok(self.result(
+            allocated_sections,
+            final_total,
+            final_total.saturating_sub(budget),
+        ))
+    }
+
+    /// Drop whole list items in the section's packing order until `need` units
+    /// are freed or the next drop would go below `min`. Returns `None` when the
+    /// section has no packing, is not a list, or nothing could be dropped.
+    fn pack_items(
+        &self,
+        section: &Section,
+        need: usize,
+    ) -> Option<(ValueNode, usize, Vec<DroppedItem>)> {
+        let packing = section.packing.as_ref()?;
+        let ValueNode::List(items) = &section.content else {
+            return None;
+        };
+
+        let sizes: Vec<usize> = items
+            .iter()
+            .map(|item| self.tokenizer.count_facet_units_in_value(item))
+            .collect();
+        let scores: Vec<Option<f64>> = items.iter().map(|item| packing.score(item)).collect();
+        let order: Vec<usize> = match packing {
+            ItemPacking::Position => (0..items.len()).rev().collect(),
+            ItemPacking::Score(_) => lowest_score_order(&scores),
+        };
+
+        let mut size = section.current_size;
+        let mut freed = 0;
+        let mut keep = vec![true; items.len()];
+        let mut dropped = Vec::new();
+        for index in order {
+            if freed >= need {
+                break;
+            }
+            if size.saturating_sub(sizes[index]) < section.min {
+                break;
+            }
+            keep[index] = false;
+            size -= sizes[index];
+            freed += sizes[index];
+            dropped.push(DroppedItem {
+                index,
+                size: sizes[index],
+                score: scores[index],
+            });
+        }
+        if dropped.is_empty() {
+            return None;
+        }
+
+        let packed = items
+            .iter()
+            .zip(keep)
+            .filter(|(_, keep)| *keep)
+            .map(|(item, _)| item.clone())
+            .collect();
+        Some((ValueNode::List(packed), size, dropped))
     }
 
     fn truncate_content(
@@ -566,6 +801,40 @@ impl TokenBoxModel {
     }
 }
 
+fn trace_layout_step(
+    trace: Option<&mut ExecutionTrace>,
+    started: Option<Instant>,
+    allocated: &AllocatedSection,
+    original_size: usize,
+) {
+    let (Some(trace), Some(started)) = (trace, started) else {
+        return;
+    };
+    let step = if allocated.was_dropped {
+        "dropped"
+    } else if allocated.was_truncated {
+        "truncated"
+    } else if !allocated.dropped_items.is_empty() {
+        "packed"
+    } else if allocated.was_compressed {
+        "compressed"
+    } else {
+        "kept"
+    };
+    trace.record(
+        allocated.section.id.clone(),
+        "layout",
+        started,
+        serde_json::json!({
+            "step": step,
+            "from": original_size,
+            "to": allocated.final_size,
+            "priority": allocated.section.priority,
+            "dropped_items": allocated.dropped_items.len(),
+        }),
+    );
+}
+
 fn sort_allocated_by_source(mut sections: Vec<AllocatedSection>) -> Vec<AllocatedSection> {
     sections.sort_by(|a, b| a.section.source_index.cmp(&b.section.source_index));
     sections
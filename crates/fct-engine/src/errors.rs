@@ -4,6 +4,8 @@ use thiserror::Error;
 pub enum EngineError {
     #[error("JSON serialization error: {0}")]
     JsonError(#[from] serde_json::Error),
+    #[error("Policy serialization error: {0}")]
+    PolicyError(#[from] fct_policy::PolicyError),
     #[error("F505: Cyclic dependency detected in variable graph: {cycle}")]
     CyclicDependency { cycle: String },
 
@@ -31,6 +33,12 @@ pub enum EngineError {
     #[error("F455: Guard undecidable for operation: {name}")]
     GuardUndecidable { name: String },
 
+    #[error("X.fct.QUOTA_EXHAUSTED: Call quota of policy rule '{policy_rule_id}' exhausted for operation: {name}")]
+    QuotaExhausted {
+        name: String,
+        policy_rule_id: String,
+    },
+
     #[error("F902: Compute gas exhausted (limit: {limit})")]
     GasExhausted { limit: usize },
 
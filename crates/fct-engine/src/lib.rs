@@ -14,6 +14,7 @@
 //!   memory and computational resources
 //! - **Tokenizer**: Thread-safe token counting and text processing utilities
 //! - **Test Runner**: Comprehensive testing framework with performance telemetry
+//! - **Execution Trace**: Chrome-trace timeline of R-DAG nodes, lens calls and layout steps
 //! - **Error Handling**: Robust error management with detailed error codes and recovery
 //!
 //! ## Features
@@ -89,6 +90,7 @@
 
 mod box_model;
 mod errors;
+mod memo;
 mod mock_system;
 mod r_dag;
 mod test_reporter;
@@ -96,16 +98,24 @@ mod test_runner;
 mod tokenizer;
 mod tool_executor;
 mod tool_schemas;
+mod trace;
 
 // ============================================================================
 // PUBLIC API EXPORTS
 // ============================================================================
 
 // Re-export public API for convenient use
-pub use box_model::{AllocatedSection, AllocationResult, Section, TokenBoxModel};
+pub use box_model::{
+    AllocatedSection, AllocationResult, DroppedItem, ItemPacking, LayoutReservation, Section,
+    TokenBoxModel, PACK_KEY,
+};
 pub use errors::{EngineError, EngineResult};
+pub use memo::RDagMemo;
 pub use mock_system::{EnhancedMockRegistry, MockBehavior, MockBuilder, MockDefinition};
-pub use r_dag::{ExecutionContext, ExecutionGuardDecision, ExecutionMode, GasContext, RDagEngine};
+pub use r_dag::{
+    demand_roots, lens_call_input_hash, ExecutionContext, ExecutionGuardDecision, ExecutionMode,
+    GasContext, RDagEngine, VarNode,
+};
 pub use test_reporter::{
     AssertionReport, ReportFormat, ReportMetadata, TelemetryReport, TestReportEntry, TestReporter,
     TestStatus, TestSuiteReport, TestSummary,
@@ -117,13 +127,14 @@ pub use tokenizer::{
     count_facet_units, count_facet_units_in_value, count_tokens, count_tokens_in_value, Tokenizer,
 };
 pub use tool_executor::{
-    value_node_map_to_json, value_node_to_json, ToolDefinition, ToolExecutor, ToolHandler,
-    ToolInvocation, ToolResult,
+    tool_call_input_hash, value_node_map_to_json, value_node_to_json, ToolDefinition, ToolExecutor,
+    ToolHandler, ToolInvocation, ToolResult,
 };
 pub use tool_schemas::{
     create_number_param, create_object_param, create_string_param, AnthropicTool, LlamaFunction,
     LlamaTool, OpenAIFunction, OpenAITool, Provider, SchemaConverter,
 };
+pub use trace::{ExecutionTrace, TraceSpan};
 
 /// Derive deterministic canonical section id for a message role and 1-based occurrence index.
 pub fn derive_message_section_id(role: &str, ordinal: usize) -> String {
@@ -579,6 +590,51 @@ mod token_box_tests {
         assert_eq!(result.overflow, 0);
     }
 
+    #[test]
+    fn test_allocate_traced_records_each_flexible_section_step() {
+        let model = TokenBoxModel::new(8);
+        let lens_registry = LensRegistry::new();
+        let sections = vec![
+            Section::new("keep".to_string(), ValueNode::String("ab".to_string()), 2)
+                .with_priority(200)
+                .with_limits(2, 0.0, 0.5),
+            Section::new(
+                "drop".to_string(),
+                ValueNode::String("abcdef".to_string()),
+                6,
+            )
+            .with_priority(100)
+            .with_limits(6, 0.0, 0.5),
+            Section::new("sys".to_string(), ValueNode::String("abc".to_string()), 3)
+                .with_limits(3, 0.0, 0.0),
+        ];
+
+        let mut trace = ExecutionTrace::new();
+        let traced = model
+            .allocate_traced(
+                sections.clone(),
+                &lens_registry,
+                ExecutionMode::Exec,
+                &mut trace,
+            )
+            .unwrap();
+        let untraced = model.allocate(sections, &lens_registry).unwrap();
+        assert_eq!(traced.total_size, untraced.total_size);
+
+        let steps: Vec<(&str, &str)> = trace
+            .spans()
+            .iter()
+            .map(|span| {
+                assert_eq!(span.category, "layout");
+                (span.name.as_str(), span.args["step"].as_str().unwrap())
+            })
+            .collect();
+        // Critical sections have no step; flexible ones go in compression order.
+        assert_eq!(steps, [("drop", "dropped"), ("keep", "kept")]);
+        assert_eq!(trace.spans()[0].args["from"], 6);
+        assert_eq!(trace.spans()[0].args["to"], 0);
+    }
+
     #[test]
     fn test_non_string_list_truncation_updates_content() {
         let lens_registry = LensRegistry::new();
@@ -664,4 +720,188 @@ mod token_box_tests {
             _ => panic!("expected list content"),
         }
     }
+
+    #[test]
+    fn test_position_packing_drops_whole_items_before_truncating() {
+        let lens_registry = LensRegistry::new();
+        let content = ValueNode::List(vec![
+            ValueNode::String("aaaa".to_string()),
+            ValueNode::String("bbbb".to_string()),
+            ValueNode::String("cccc".to_string()),
+        ]);
+        let section = |min| {
+            Section::new("docs".to_string(), content.clone(), 12)
+                .with_limits(min, 0.0, 1.0)
+                .with_packing(ItemPacking::Position)
+        };
+
+        let result = TokenBoxModel::new(7)
+            .allocate(vec![section(0)], &lens_registry)
+            .unwrap();
+        let packed = &result.sections[0];
+        assert!(!packed.was_truncated);
+        assert_eq!(packed.final_size, 4);
+        assert_eq!(
+            packed.section.content,
+            ValueNode::List(vec![ValueNode::String("aaaa".to_string())])
+        );
+        let dropped: Vec<usize> = packed.dropped_items.iter().map(|d| d.index).collect();
+        assert_eq!(dropped, [2, 1]);
+
+        // Packing stops where the next drop would go below `min`; truncation
+        // takes the rest.
+        let result = TokenBoxModel::new(7)
+            .allocate(vec![section(6)], &lens_registry)
+            .unwrap();
+        let packed = &result.sections[0];
+        assert!(packed.was_truncated);
+        assert_eq!(packed.final_size, 7);
+        assert_eq!(
+            packed.dropped_items,
+            [DroppedItem {
+                index: 2,
+                size: 4,
+                score: None
+            }]
+        );
+        assert_eq!(
+            packed.section.content,
+            ValueNode::List(vec![
+                ValueNode::String("aaaa".to_string()),
+                ValueNode::String("bbb".to_string())
+            ])
+        );
+    }
+
+    #[test]
+    fn test_score_packing_drops_lowest_scored_items_first() {
+        let lens_registry = LensRegistry::new();
+        let item = |text: &str, score: Option<i64>| {
+            let mut map = OrderedMap::new();
+            map.insert("text".to_string(), ValueNode::String(text.to_string()));
+            if let Some(score) = score {
+                map.insert(
+                    "relevance".to_string(),
+                    ValueNode::Scalar(fct_ast::ScalarValue::Int(score)),
+                );
+            }
+            ValueNode::Map(map)
+        };
+        let items = vec![
+            item("a", Some(3)),
+            item("b", None),
+            item("c", Some(1)),
+            item("d", Some(1)),
+        ];
+        let tokenizer = Tokenizer::new().unwrap();
+        let sizes: Vec<usize> = items
+            .iter()
+            .map(|item| tokenizer.count_facet_units_in_value(item))
+            .collect();
+        let base_size = sizes.iter().sum();
+        let section = Section::new(
+            "docs".to_string(),
+            ValueNode::List(items.clone()),
+            base_size,
+        )
+        .with_limits(0, 0.0, 1.0)
+        .with_packing(ItemPacking::Score("relevance".to_string()));
+
+        // Missing score goes first, then the later of the tied items.
+        let budget = base_size - sizes[1] - sizes[3];
+        let result = TokenBoxModel::new(budget)
+            .allocate(vec![section], &lens_registry)
+            .unwrap();
+        let packed = &result.sections[0];
+        assert!(!packed.was_truncated);
+        assert_eq!(packed.final_size, budget);
+        assert_eq!(
+            packed.dropped_items,
+            [
+                DroppedItem {
+                    index: 1,
+                    size: sizes[1],
+                    score: None
+                },
+                DroppedItem {
+                    index: 3,
+                    size: sizes[3],
+                    score: Some(1.0)
+                }
+            ]
+        );
+        assert_eq!(
+            packed.section.content,
+            ValueNode::List(vec![items[0].clone(), items[2].clone()])
+        );
+    }
+
+    #[test]
+    fn test_item_packing_parses_message_pack_field() {
+        let mut score = OrderedMap::new();
+        score.insert(
+            "score".to_string(),
+            ValueNode::String("relevance".to_string()),
+        );
+        assert_eq!(
+            ItemPacking::from_value(&ValueNode::String("position".to_string())),
+            Some(ItemPacking::Position)
+        );
+        assert_eq!(
+            ItemPacking::from_value(&ValueNode::Map(score)),
+            Some(ItemPacking::Score("relevance".to_string()))
+        );
+        assert_eq!(
+            ItemPacking::from_value(&ValueNode::String("score".to_string())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_reservation_is_fixed_load_ahead_of_sections() {
+        let lens_registry = LensRegistry::new();
+        let sections = || {
+            vec![
+                Section::new(
+                    "system".to_string(),
+                    ValueNode::String("sys".to_string()),
+                    3,
+                )
+                .with_limits(0, 0.0, 0.0),
+                Section::new(
+                    "user".to_string(),
+                    ValueNode::String("abcdefghij".to_string()),
+                    10,
+                )
+                .with_limits(0, 0.0, 1.0),
+            ]
+        };
+        let reservation = LayoutReservation {
+            tools: 4,
+            output: 6,
+        };
+        let model = TokenBoxModel::new(20).with_reservation(reservation);
+        assert_eq!(model.fixed_load(&sections()), 13);
+
+        let result = model.allocate(sections(), &lens_registry).unwrap();
+        assert_eq!(result.reserved, reservation);
+        assert_eq!(result.budget, 20);
+        assert_eq!(result.total_size, 10);
+        assert_eq!(result.overflow, 0);
+        assert_eq!(
+            result.sections[1].section.content,
+            ValueNode::String("abcdefg".to_string())
+        );
+
+        let err = TokenBoxModel::new(12)
+            .with_reservation(reservation)
+            .allocate(sections(), &lens_registry)
+            .unwrap_err();
+        match err {
+            EngineError::BudgetExceeded { budget, required } => {
+                assert_eq!((budget, required), (12, 13));
+            }
+            other => panic!("expected F901, got {other:?}"),
+        }
+    }
 }
@@ -0,0 +1,91 @@
+// ============================================================================
+// R-DAG NODE MEMO
+// ============================================================================
+
+use crate::r_dag::ExecutionGuardDecision;
+use fct_ast::ValueNode;
+use std::collections::HashMap;
+
+/// Memoized `@vars` results carried between runs of the same document, for
+/// reactive re-execution with [`crate::RDagEngine::execute_reactive`].
+///
+/// Each node is stored with a fingerprint of its definition, the values of its
+/// direct dependencies and its runtime input. A node whose fingerprint is
+/// unchanged is not re-evaluated: its value is reused, and the gas it consumed
+/// and the guard decisions it recorded are replayed, so gas exhaustion and
+/// guard `seq` numbering match a full run. The whole memo is dropped when the
+/// execution mode, host profile, host context or effective policy changes.
+///
+/// Lenses are assumed to read only their input and arguments; a host lens that
+/// reads other variables through `LensContext::variables` is not tracked.
+#[derive(Debug, Clone, Default)]
+pub struct RDagMemo {
+    context: Option<String>,
+    nodes: HashMap<String, MemoizedNode>,
+    reused: Vec<String>,
+    recomputed: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MemoizedNode {
+    pub(crate) fingerprint: String,
+    pub(crate) value: ValueNode,
+    /// Decisions recorded while evaluating the node, in order (`seq` is reassigned on replay).
+    pub(crate) guard_decisions: Vec<ExecutionGuardDecision>,
+    /// Gas the evaluation consumed, including the per-node charge.
+    pub(crate) gas: usize,
+}
+
+impl RDagMemo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of memoized nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Nodes reused from the memo by the last run, in evaluation order.
+    pub fn reused(&self) -> &[String] {
+        &self.reused
+    }
+
+    /// Nodes evaluated by the last run, in evaluation order.
+    pub fn recomputed(&self) -> &[String] {
+        &self.recomputed
+    }
+
+    /// Start a run under `context`, dropping every node memoized under another.
+    pub(crate) fn begin_run(&mut self, context: String) {
+        if self.context.as_deref() != Some(context.as_str()) {
+            self.nodes.clear();
+            self.context = Some(context);
+        }
+        self.reused.clear();
+        self.recomputed.clear();
+    }
+
+    pub(crate) fn lookup(&self, name: &str, fingerprint: &str) -> Option<&MemoizedNode> {
+        self.nodes
+            .get(name)
+            .filter(|node| node.fingerprint == fingerprint)
+    }
+
+    pub(crate) fn mark_reused(&mut self, name: &str) {
+        self.reused.push(name.to_string());
+    }
+
+    pub(crate) fn store(&mut self, name: &str, node: MemoizedNode) {
+        self.recomputed.push(name.to_string());
+        self.nodes.insert(name.to_string(), node);
+    }
+}
@@ -3,14 +3,24 @@
 // ============================================================================
 
 use crate::errors::{EngineError, EngineResult};
+use crate::memo::{MemoizedNode, RDagMemo};
+use crate::trace::{ExecutionTrace, TraceSpan};
 use fct_ast::{
-    BodyNode, FacetDocument, FacetNode, OrderedMap, PipelineNode, ScalarValue, ValueNode,
-    FACET_VERSION, POLICY_VERSION,
+    BodyNode, FacetBlock, FacetDocument, FacetNode, OrderedMap, PipelineNode, ScalarValue,
+    ValueNode, FACET_VERSION,
 };
-use fct_std::{LensContext, LensRegistry, TrustLevel};
+use fct_policy::{
+    evaluate_guard, fold_input_hash, policy_scope, EffectivePolicy, HostContext, OpDesc,
+    PolicyDecision, PolicyOp,
+};
+use fct_std::{LensContext, LensError, LensRegistry, TrustLevel};
+use fct_validator::values::{describe_violations, InputTypeTable};
 use sha2::{Digest, Sha256};
 use std::cmp::Reverse;
 use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::thread;
+use std::time::Instant;
 
 /// Node in the dependency graph
 #[derive(Debug, Clone)]
@@ -117,6 +127,22 @@ impl DependencyGraph {
         deps
     }
 
+    /// Nodes reachable from `roots` through dependencies, roots included.
+    /// Roots that are not nodes of the graph are ignored.
+    pub fn reachable_from<'a>(&self, roots: impl IntoIterator<Item = &'a str>) -> HashSet<String> {
+        let mut reached = HashSet::new();
+        let mut stack: Vec<&str> = roots.into_iter().collect();
+        while let Some(name) = stack.pop() {
+            let Some(node) = self.nodes.get(name) else {
+                continue;
+            };
+            if reached.insert(name.to_string()) {
+                stack.extend(node.dependencies.iter().map(String::as_str));
+            }
+        }
+        reached
+    }
+
     /// Detect cycles using DFS
     pub fn detect_cycles(&self) -> EngineResult<()> {
         let mut visited = HashSet::new();
@@ -161,6 +187,21 @@ impl DependencyGraph {
         Ok(())
     }
 
+    /// Whether `order` lists every node exactly once, each after all of its dependencies.
+    pub fn is_topological_order(&self, order: &[String]) -> bool {
+        if order.len() != self.nodes.len() {
+            return false;
+        }
+        let mut placed: HashSet<&str> = HashSet::with_capacity(order.len());
+        order.iter().all(|name| {
+            self.nodes.get(name).is_some_and(|node| {
+                node.dependencies
+                    .iter()
+                    .all(|dep| placed.contains(dep.as_str()))
+            }) && placed.insert(name.as_str())
+        })
+    }
+
     /// Topological sort (Kahn's algorithm)
     pub fn topological_sort(&self) -> EngineResult<Vec<String>> {
         let node_count = self.nodes.len();
@@ -240,6 +281,59 @@ impl Default for DependencyGraph {
     }
 }
 
+/// Variables referenced by message blocks (attributes such as `when`, content,
+/// `tools`) and `@policy` blocks: the roots of demand-driven evaluation.
+pub fn demand_roots(doc: &FacetDocument) -> Vec<String> {
+    let mut roots = Vec::new();
+    for node in &doc.blocks {
+        let (FacetNode::System(block)
+        | FacetNode::User(block)
+        | FacetNode::Assistant(block)
+        | FacetNode::Policy(block)) = node
+        else {
+            continue;
+        };
+        roots.extend(block_var_refs(block));
+    }
+    roots
+}
+
+/// Variables referenced from `@policy` blocks.
+fn policy_vars(doc: &FacetDocument) -> HashSet<String> {
+    let mut vars = HashSet::new();
+    for node in &doc.blocks {
+        if let FacetNode::Policy(block) = node {
+            vars.extend(block_var_refs(block));
+        }
+    }
+    vars
+}
+
+/// Variables referenced from a block's attributes and body.
+fn block_var_refs(block: &FacetBlock) -> Vec<String> {
+    let mut refs = Vec::new();
+    for value in block.attributes.values() {
+        refs.extend(DependencyGraph::extract_dependencies(value));
+    }
+    for body in &block.body {
+        let value = match body {
+            BodyNode::KeyValue(kv) => &kv.value,
+            BodyNode::ListItem(item) => &item.value,
+        };
+        refs.extend(DependencyGraph::extract_dependencies(value));
+    }
+    refs
+}
+
+fn contains_pipeline(value: &ValueNode) -> bool {
+    match value {
+        ValueNode::Pipeline(_) => true,
+        ValueNode::List(items) => items.iter().any(contains_pipeline),
+        ValueNode::Map(map) => map.values().any(contains_pipeline),
+        _ => false,
+    }
+}
+
 fn base_var_name(var_ref: &str) -> &str {
     var_ref.split('.').next().unwrap_or(var_ref)
 }
@@ -288,25 +382,22 @@ pub struct ExecutionGuardDecision {
     pub error_code: Option<String>,
 }
 
-#[derive(Debug, Clone)]
-struct LensPolicyDecision {
-    allowed: bool,
-    policy_rule_id: Option<String>,
-    error_code: Option<String>,
-}
-
 /// Execution context
 pub struct ExecutionContext {
     pub variables: HashMap<String, ValueNode>,
     pub runtime_inputs: HashMap<String, ValueNode>,
     pub lens_cache: HashMap<String, ValueNode>,
     pub guard_decisions: Vec<ExecutionGuardDecision>,
-    pub effective_policy: Option<OrderedMap<String, ValueNode>>,
+    pub effective_policy: Option<EffectivePolicy>,
     pub policy_hash: Option<String>,
     pub gas: GasContext,
     pub lens_registry: LensRegistry,
     pub mode: ExecutionMode,
     pub host_profile_id: String,
+    /// Read-only host context visible to policy conditions as `$x.<host>...`.
+    pub host_context: Option<HostContext>,
+    /// When set, R-DAG nodes and lens calls are recorded as trace spans.
+    pub trace: Option<ExecutionTrace>,
     variables_frozen: bool,
     next_guard_seq: usize,
 }
@@ -328,6 +419,8 @@ impl ExecutionContext {
             lens_registry: LensRegistry::new(),
             mode,
             host_profile_id: "local.default.v1".to_string(),
+            host_context: None,
+            trace: None,
             variables_frozen: false,
             next_guard_seq: 1,
         }
@@ -355,6 +448,10 @@ impl ExecutionContext {
         self.runtime_inputs = inputs;
     }
 
+    pub fn set_host_context(&mut self, host_context: HostContext) {
+        self.host_context = Some(host_context);
+    }
+
     pub fn get_input(&self, name: &str) -> Option<&ValueNode> {
         self.runtime_inputs.get(name)
     }
@@ -385,7 +482,184 @@ impl ExecutionContext {
 /// R-DAG Execution Engine
 pub struct RDagEngine {
     graph: DependencyGraph,
-    effective_policy: Option<OrderedMap<String, ValueNode>>,
+    effective_policy: Option<EffectivePolicy>,
+    input_types: InputTypeTable,
+    demand_driven: bool,
+    /// Variables the document's output needs, when evaluation is demand-driven.
+    demanded: Option<HashSet<String>>,
+    parallel: bool,
+    /// Variables `@policy` conditions read.
+    policy_vars: HashSet<String>,
+}
+
+/// Read-only view of the execution context a single node is evaluated against.
+struct NodeEnv<'a> {
+    variables: &'a HashMap<String, ValueNode>,
+    runtime_inputs: &'a HashMap<String, ValueNode>,
+    lens_cache: &'a HashMap<String, ValueNode>,
+    lens_registry: &'a LensRegistry,
+    mode: ExecutionMode,
+    host_profile_id: &'a str,
+    host_context: Option<&'a HostContext>,
+    trace_origin: Option<Instant>,
+}
+
+impl<'a> NodeEnv<'a> {
+    fn new(ctx: &'a ExecutionContext, variables: &'a HashMap<String, ValueNode>) -> Self {
+        Self {
+            variables,
+            runtime_inputs: &ctx.runtime_inputs,
+            lens_cache: &ctx.lens_cache,
+            lens_registry: &ctx.lens_registry,
+            mode: ctx.mode,
+            host_profile_id: &ctx.host_profile_id,
+            host_context: ctx.host_context.as_ref(),
+            trace_origin: ctx.trace.as_ref().map(ExecutionTrace::origin),
+        }
+    }
+}
+
+/// A change a node evaluation makes to the execution context.
+enum NodeEffect {
+    Gas(usize),
+    Guard(ExecutionGuardDecision),
+    CacheWrite(String, ValueNode),
+}
+
+/// Effects of one node evaluation, recorded in order and committed later.
+struct NodeEffects {
+    effects: Vec<NodeEffect>,
+    gas_limit: usize,
+    gas_available: usize,
+    trace_origin: Option<Instant>,
+    lane: usize,
+    spans: Vec<TraceSpan>,
+}
+
+impl NodeEffects {
+    fn new(gas: &GasContext, trace_origin: Option<Instant>, lane: usize) -> Self {
+        Self {
+            effects: Vec::new(),
+            gas_limit: gas.limit,
+            gas_available: gas.limit.saturating_sub(gas.consumed),
+            trace_origin,
+            lane,
+            spans: Vec::new(),
+        }
+    }
+
+    /// Start time of a span, when tracing.
+    fn span_start(&self) -> Option<Instant> {
+        self.trace_origin.map(|_| Instant::now())
+    }
+
+    fn end_span(
+        &mut self,
+        started: Option<Instant>,
+        name: &str,
+        category: &'static str,
+        args: serde_json::Value,
+    ) {
+        if let (Some(origin), Some(started)) = (self.trace_origin, started) {
+            self.spans.push(TraceSpan::since(
+                origin, started, name, category, self.lane, args,
+            ));
+        }
+    }
+
+    fn gas(&self) -> usize {
+        effects_gas(&self.effects)
+    }
+
+    fn consume_gas(&mut self, amount: usize) -> EngineResult<()> {
+        self.effects.push(NodeEffect::Gas(amount));
+        if amount > self.gas_available {
+            return Err(EngineError::GasExhausted {
+                limit: self.gas_limit,
+            });
+        }
+        self.gas_available -= amount;
+        Ok(())
+    }
+
+    /// Record a charge that does not fit the remaining gas.
+    fn exhaust_gas(&mut self, needed: usize) -> EngineError {
+        self.effects.push(NodeEffect::Gas(needed));
+        EngineError::GasExhausted {
+            limit: self.gas_limit,
+        }
+    }
+
+    fn record_guard_decision(&mut self, decision: ExecutionGuardDecision) {
+        self.effects.push(NodeEffect::Guard(decision));
+    }
+
+    fn set_lens_cache_entry(&mut self, key: String, value: ValueNode) {
+        self.effects.push(NodeEffect::CacheWrite(key, value));
+    }
+
+    /// Charge a memoized node's gas and guard decisions without re-evaluating it.
+    fn replay(&mut self, cached: &MemoizedNode) {
+        self.effects.push(NodeEffect::Gas(cached.gas));
+        self.effects.extend(
+            cached
+                .guard_decisions
+                .iter()
+                .cloned()
+                .map(NodeEffect::Guard),
+        );
+    }
+
+    fn finish(self, result: EngineResult<ValueNode>, memo: MemoUpdate) -> NodeOutcome {
+        NodeOutcome {
+            effects: self.effects,
+            spans: self.spans,
+            result,
+            memo,
+        }
+    }
+}
+
+enum MemoUpdate {
+    None,
+    Reused,
+    Store(String),
+}
+
+/// Evaluated node waiting to be committed in evaluation order.
+struct NodeOutcome {
+    effects: Vec<NodeEffect>,
+    spans: Vec<TraceSpan>,
+    result: EngineResult<ValueNode>,
+    memo: MemoUpdate,
+}
+
+impl NodeOutcome {
+    fn gas(&self) -> usize {
+        effects_gas(&self.effects)
+    }
+}
+
+/// Apply effects to `ctx` in the order they occurred, stopping where gas runs out.
+fn apply_effects(ctx: &mut ExecutionContext, effects: Vec<NodeEffect>) -> EngineResult<()> {
+    for effect in effects {
+        match effect {
+            NodeEffect::Gas(amount) => ctx.gas.consume(amount)?,
+            NodeEffect::Guard(decision) => ctx.record_guard_decision(decision),
+            NodeEffect::CacheWrite(key, value) => ctx.set_lens_cache_entry(key, value),
+        }
+    }
+    Ok(())
+}
+
+fn effects_gas(effects: &[NodeEffect]) -> usize {
+    effects
+        .iter()
+        .map(|effect| match effect {
+            NodeEffect::Gas(amount) => *amount,
+            _ => 0,
+        })
+        .sum()
 }
 
 impl RDagEngine {
@@ -393,75 +667,513 @@ impl RDagEngine {
         Self {
             graph: DependencyGraph::new(),
             effective_policy: None,
+            input_types: InputTypeTable::default(),
+            demand_driven: false,
+            demanded: None,
+            parallel: false,
+            policy_vars: HashSet::new(),
         }
     }
 
+    /// Evaluate only the variables reachable from message blocks (content,
+    /// `when` gates, `tools`) and `@policy` conditions; the rest are skipped
+    /// without charging gas or calling their lenses. Off by default: the spec
+    /// evaluates every `@vars` entry.
+    pub fn with_demand_driven(mut self, enabled: bool) -> Self {
+        self.demand_driven = enabled;
+        self
+    }
+
+    /// Evaluate independent nodes concurrently on scoped threads. Results,
+    /// gas totals, lens cache writes and guard decisions (including `seq`)
+    /// are committed in topological order and match a sequential run. The one
+    /// observable difference: `LensContext::variables` only holds variables
+    /// evaluated in earlier waves, not every variable earlier in the order.
+    pub fn with_parallel(mut self, enabled: bool) -> Self {
+        self.parallel = enabled;
+        self
+    }
+
     /// Build graph from document
     pub fn build(&mut self, doc: &FacetDocument) -> EngineResult<()> {
         self.graph.build_from_document(doc)?;
-        self.effective_policy = collect_effective_policy(doc);
+        self.effective_policy = EffectivePolicy::from_document(doc);
+        self.input_types = InputTypeTable::from_document(doc);
+        self.policy_vars = policy_vars(doc);
+        self.demanded = if self.demand_driven {
+            let roots = demand_roots(doc);
+            Some(self.graph.reachable_from(roots.iter().map(String::as_str)))
+        } else {
+            None
+        };
         Ok(())
     }
 
+    /// Variables demand-driven evaluation skips, in declaration order. Always
+    /// empty unless [`RDagEngine::with_demand_driven`] is on.
+    pub fn skipped_nodes(&self) -> Vec<String> {
+        let Some(demanded) = &self.demanded else {
+            return Vec::new();
+        };
+        self.graph
+            .insertion_order
+            .iter()
+            .filter(|name| !demanded.contains(*name))
+            .cloned()
+            .collect()
+    }
+
+    /// Every `@vars` node with its dependencies, in declaration order.
+    pub fn nodes(&self) -> Vec<&VarNode> {
+        self.graph
+            .insertion_order
+            .iter()
+            .filter_map(|name| self.graph.nodes.get(name))
+            .collect()
+    }
+
+    fn is_demanded(&self, name: &str) -> bool {
+        self.demanded
+            .as_ref()
+            .is_none_or(|demanded| demanded.contains(name))
+    }
+
     /// Validate graph (detect cycles)
     pub fn validate(&self) -> EngineResult<()> {
         self.graph.detect_cycles()?;
         Ok(())
     }
 
+    /// Evaluation order of the `@vars` graph (deterministic topological sort).
+    pub fn topological_order(&self) -> EngineResult<Vec<String>> {
+        self.graph.topological_sort()
+    }
+
+    /// Whether `order` is a valid evaluation order of the built graph, e.g. one
+    /// stored alongside a precompiled contract.
+    pub fn is_topological_order(&self, order: &[String]) -> bool {
+        self.graph.is_topological_order(order)
+    }
+
     /// Execute graph and compute all variables
     pub fn execute(&self, ctx: &mut ExecutionContext) -> EngineResult<()> {
-        // Get topological order
         let order = self.graph.topological_sort()?;
+        self.execute_in_order(ctx, &order)
+    }
 
-        // Execute nodes in order
-        for node_name in order {
-            ctx.gas.consume(1)?; // Each variable evaluation costs 1 gas
+    /// Execute the graph in a previously computed [`RDagEngine::topological_order`],
+    /// skipping cycle detection and sorting.
+    pub fn execute_in_order(
+        &self,
+        ctx: &mut ExecutionContext,
+        order: &[String],
+    ) -> EngineResult<()> {
+        self.run_nodes(ctx, order, None)
+    }
+
+    /// Execute the graph reusing the nodes of `memo` whose definition,
+    /// dependency values and runtime input are unchanged since the last run,
+    /// and memoize the rest. See [`RDagMemo`].
+    pub fn execute_reactive(
+        &self,
+        ctx: &mut ExecutionContext,
+        memo: &mut RDagMemo,
+    ) -> EngineResult<()> {
+        let order = self.graph.topological_sort()?;
+        self.run_nodes(ctx, &order, Some(memo))
+    }
+
+    /// [`RDagEngine::execute_reactive`] in a previously computed order.
+    pub fn execute_reactive_in_order(
+        &self,
+        ctx: &mut ExecutionContext,
+        order: &[String],
+        memo: &mut RDagMemo,
+    ) -> EngineResult<()> {
+        self.run_nodes(ctx, order, Some(memo))
+    }
+
+    fn run_nodes(
+        &self,
+        ctx: &mut ExecutionContext,
+        order: &[String],
+        mut memo: Option<&mut RDagMemo>,
+    ) -> EngineResult<()> {
+        if let Some(memo) = memo.as_deref_mut() {
+            memo.begin_run(self.memo_context(ctx)?);
+        }
 
-            if let Some(node) = self.graph.nodes.get(&node_name) {
-                // Evaluate the variable
-                let value = self.evaluate_value(&node.value, ctx, Some(&node_name))?;
-                ctx.set_variable(node_name.clone(), value)?;
+        if self.parallel {
+            self.run_waves(ctx, order, memo)?;
+        } else {
+            // Execute nodes in order
+            for node_name in order {
+                if !self.is_demanded(node_name) {
+                    continue;
+                }
+                let Some(node) = self.graph.nodes.get(node_name) else {
+                    ctx.gas.consume(1)?;
+                    continue;
+                };
+                let outcome = self.evaluate_node(
+                    node,
+                    &NodeEnv::new(ctx, &ctx.variables),
+                    &ctx.gas,
+                    memo.as_deref(),
+                    0,
+                );
+                self.commit_node(ctx, node, outcome, memo.as_deref_mut())?;
             }
         }
 
         ctx.effective_policy = self.effective_policy.clone();
-        ctx.policy_hash = compute_policy_hash(self.effective_policy.as_ref())?;
+        ctx.policy_hash = self
+            .effective_policy
+            .as_ref()
+            .map(EffectivePolicy::policy_hash)
+            .transpose()?;
         ctx.freeze_variables();
         Ok(())
     }
 
+    /// Parallel counterpart of the sequential loop in `run_nodes`: nodes are
+    /// grouped into waves whose members do not depend on each other, each wave
+    /// is evaluated concurrently, and outcomes are committed to `ctx` strictly
+    /// in `order` so gas, guard `seq` and errors match a sequential run.
+    fn run_waves(
+        &self,
+        ctx: &mut ExecutionContext,
+        order: &[String],
+        mut memo: Option<&mut RDagMemo>,
+    ) -> EngineResult<()> {
+        let order: Vec<&str> = order
+            .iter()
+            .map(String::as_str)
+            .filter(|name| self.is_demanded(name))
+            .collect();
+        let nodes: Vec<Option<&VarNode>> = order
+            .iter()
+            .map(|name| self.graph.nodes.get(*name))
+            .collect();
+        let waves = self.schedule_waves(&order, &nodes);
+        let wave_count = waves.iter().max().map_or(0, |last| last + 1);
+
+        let mut outcomes: Vec<Option<NodeOutcome>> = order.iter().map(|_| None).collect();
+        let mut next_commit = 0;
+        let mut first_failure: Option<usize> = None;
+
+        for wave in 0..wave_count {
+            let members: Vec<usize> = (0..order.len())
+                .filter(|&i| waves[i] == wave)
+                .filter(|&i| first_failure.is_none_or(|failed| i < failed))
+                .collect();
+
+            // Everything evaluated before position `i` is visible to node `i`,
+            // and its gas counts against the budget `i` may spend.
+            let mut pending_gas = Vec::with_capacity(order.len());
+            let mut spent = ctx.gas.consumed;
+            for (i, node) in nodes.iter().enumerate() {
+                pending_gas.push(spent);
+                if i >= next_commit {
+                    spent += match (node, &outcomes[i]) {
+                        (None, _) => 1,
+                        (Some(_), Some(outcome)) => outcome.gas(),
+                        (Some(_), None) => 0,
+                    };
+                }
+            }
+            let jobs: Vec<(usize, &VarNode, HashMap<String, ValueNode>, GasContext)> = members
+                .iter()
+                .filter_map(|&i| {
+                    let node = nodes[i]?;
+                    let mut visible = ctx.variables.clone();
+                    for (j, outcome) in outcomes.iter().enumerate().take(i).skip(next_commit) {
+                        if let Some(NodeOutcome {
+                            result: Ok(value), ..
+                        }) = outcome
+                        {
+                            visible.insert(order[j].to_string(), value.clone());
+                        }
+                    }
+                    let gas = GasContext {
+                        limit: ctx.gas.limit,
+                        consumed: pending_gas[i],
+                    };
+                    Some((i, node, visible, gas))
+                })
+                .collect();
+
+            for (i, outcome) in self.evaluate_wave(ctx, &jobs, memo.as_deref()) {
+                if outcome.result.is_err() {
+                    first_failure = Some(first_failure.map_or(i, |failed| failed.min(i)));
+                }
+                outcomes[i] = Some(outcome);
+            }
+
+            while next_commit < order.len() {
+                match nodes[next_commit] {
+                    None => ctx.gas.consume(1)?,
+                    Some(node) => {
+                        let Some(outcome) = outcomes[next_commit].take() else {
+                            break;
+                        };
+                        self.commit_node(ctx, node, outcome, memo.as_deref_mut())?;
+                    }
+                }
+                next_commit += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Wave of each node in `order`: one past the latest wave among its
+    /// dependencies. Nodes that run lenses also wait for every earlier
+    /// variable the policy reads, so guard decisions see the same `$vars`.
+    fn schedule_waves(&self, order: &[&str], nodes: &[Option<&VarNode>]) -> Vec<usize> {
+        let position: HashMap<&str, usize> = order
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (*name, i))
+            .collect();
+        let mut waves: Vec<usize> = Vec::with_capacity(order.len());
+        for (i, node) in nodes.iter().enumerate() {
+            let mut wave = 0;
+            if let Some(node) = node {
+                let policy_vars = contains_pipeline(&node.value)
+                    .then_some(&self.policy_vars)
+                    .into_iter()
+                    .flatten();
+                for dep in node.dependencies.iter().chain(policy_vars) {
+                    if let Some(&j) = position.get(dep.as_str()) {
+                        if j < i {
+                            wave = wave.max(waves[j] + 1);
+                        }
+                    }
+                }
+            }
+            waves.push(wave);
+        }
+        waves
+    }
+
+    fn evaluate_wave(
+        &self,
+        ctx: &ExecutionContext,
+        jobs: &[(usize, &VarNode, HashMap<String, ValueNode>, GasContext)],
+        memo: Option<&RDagMemo>,
+    ) -> Vec<(usize, NodeOutcome)> {
+        let evaluate = |(i, node, visible, gas): &(usize, &VarNode, _, GasContext), lane| {
+            let outcome = self.evaluate_node(node, &NodeEnv::new(ctx, visible), gas, memo, lane);
+            (*i, outcome)
+        };
+        if jobs.len() <= 1 {
+            return jobs.iter().map(|job| evaluate(job, 0)).collect();
+        }
+
+        let workers = thread::available_parallelism()
+            .map_or(1, NonZeroUsize::get)
+            .min(jobs.len());
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|worker| {
+                    let evaluate = &evaluate;
+                    scope.spawn(move || {
+                        jobs.iter()
+                            .skip(worker)
+                            .step_by(workers)
+                            .map(|job| evaluate(job, worker + 1))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect()
+        })
+    }
+
+    /// Evaluate `node` against `env`, or reuse its memoized result. Nothing is
+    /// written to the execution context; see [`RDagEngine::commit_node`].
+    fn evaluate_node(
+        &self,
+        node: &VarNode,
+        env: &NodeEnv<'_>,
+        gas: &GasContext,
+        memo: Option<&RDagMemo>,
+        lane: usize,
+    ) -> NodeOutcome {
+        let mut effects = NodeEffects::new(gas, env.trace_origin, lane);
+        let started = effects.span_start();
+        let mut memo_update = MemoUpdate::None;
+        if let Some(memo) = memo {
+            let fingerprint = match self.node_fingerprint(node, env) {
+                Ok(fingerprint) => fingerprint,
+                Err(error) => return effects.finish(Err(error), memo_update),
+            };
+            if let Some(cached) = memo.lookup(&node.name, &fingerprint) {
+                if self.guards_unchanged(&cached.guard_decisions, env) {
+                    effects.replay(cached);
+                    let args = serde_json::json!({ "gas": effects.gas(), "reused": true });
+                    effects.end_span(started, &node.name, "rdag", args);
+                    return effects.finish(Ok(cached.value.clone()), MemoUpdate::Reused);
+                }
+            }
+            memo_update = MemoUpdate::Store(fingerprint);
+        }
+
+        // Each variable evaluation costs 1 gas
+        let result = effects
+            .consume_gas(1)
+            .and_then(|()| self.evaluate_value(&node.value, env, &mut effects, Some(&node.name)));
+        let mut args = serde_json::json!({ "gas": effects.gas(), "reused": false });
+        if let Err(error) = &result {
+            args["error"] = serde_json::Value::String(error.to_string());
+        }
+        effects.end_span(started, &node.name, "rdag", args);
+        effects.finish(result, memo_update)
+    }
+
+    /// Apply a node's effects to `ctx` in the order they occurred, then store
+    /// its value (and memo entry) unless evaluation failed.
+    fn commit_node(
+        &self,
+        ctx: &mut ExecutionContext,
+        node: &VarNode,
+        outcome: NodeOutcome,
+        memo: Option<&mut RDagMemo>,
+    ) -> EngineResult<()> {
+        if let Some(trace) = ctx.trace.as_mut() {
+            trace.extend(outcome.spans);
+        }
+        let gas_before = ctx.gas.consumed;
+        let decisions_before = ctx.guard_decisions.len();
+        apply_effects(ctx, outcome.effects)?;
+        let value = outcome.result?;
+
+        match (memo, outcome.memo) {
+            (Some(memo), MemoUpdate::Reused) => memo.mark_reused(&node.name),
+            (Some(memo), MemoUpdate::Store(fingerprint)) => memo.store(
+                &node.name,
+                MemoizedNode {
+                    fingerprint,
+                    value: value.clone(),
+                    guard_decisions: ctx.guard_decisions[decisions_before..].to_vec(),
+                    gas: ctx.gas.consumed - gas_before,
+                },
+            ),
+            _ => {}
+        }
+        ctx.set_variable(node.name.clone(), value)
+    }
+
+    /// Evaluate a standalone value expression against the variables `ctx`
+    /// already holds (the `repl` command). Its gas, lens guard decisions and
+    /// cache writes are committed to `ctx`; no variable is set, so this works
+    /// after [`RDagEngine::execute`] has frozen them.
+    pub fn evaluate_expression(
+        &self,
+        value: &ValueNode,
+        ctx: &mut ExecutionContext,
+    ) -> EngineResult<ValueNode> {
+        let mut effects = NodeEffects::new(&ctx.gas, None, 0);
+        let result = self.evaluate_value(
+            value,
+            &NodeEnv::new(ctx, &ctx.variables),
+            &mut effects,
+            None,
+        );
+        apply_effects(ctx, effects.effects)?;
+        result
+    }
+
+    /// Everything outside the node that can change any node's result: mode,
+    /// host profile, host context and effective policy.
+    fn memo_context(&self, ctx: &ExecutionContext) -> EngineResult<String> {
+        let context = serde_json::json!({
+            "mode": format!("{:?}", ctx.mode),
+            "host_profile_id": ctx.host_profile_id,
+            "host_context": ctx.host_context.as_ref().map(HostContext::hash),
+            "policy_hash": self
+                .effective_policy
+                .as_ref()
+                .map(EffectivePolicy::policy_hash)
+                .transpose()?,
+        });
+        Ok(format!(
+            "{:x}",
+            Sha256::digest(context.to_string().as_bytes())
+        ))
+    }
+
+    fn node_fingerprint(&self, node: &VarNode, env: &NodeEnv<'_>) -> EngineResult<String> {
+        let dependencies: Vec<(&str, Option<&ValueNode>)> = node
+            .dependencies
+            .iter()
+            .map(|dep| (dep.as_str(), env.variables.get(dep)))
+            .collect();
+        let fingerprint = serde_json::to_vec(&(
+            &node.value,
+            dependencies,
+            env.runtime_inputs.get(&node.name),
+        ))?;
+        Ok(format!("{:x}", Sha256::digest(&fingerprint)))
+    }
+
+    /// Whether every lens guard a memoized node passed still decides the same
+    /// way; `when`/`unless` may read variables outside the node's dependencies.
+    fn guards_unchanged(&self, decisions: &[ExecutionGuardDecision], env: &NodeEnv<'_>) -> bool {
+        decisions.iter().all(|recorded| {
+            let guard = self.evaluate_lens_call_policy(
+                &recorded.name,
+                recorded.effect_class.as_deref(),
+                env,
+            );
+            guard.decision() == recorded.decision
+                && guard.policy_rule_id == recorded.policy_rule_id
+                && guard.error_code == recorded.error_code
+        })
+    }
+
     /// Evaluate a value node (resolve variables, execute pipelines)
     fn evaluate_value(
         &self,
         value: &ValueNode,
-        ctx: &mut ExecutionContext,
+        env: &NodeEnv<'_>,
+        effects: &mut NodeEffects,
         current_var: Option<&str>,
     ) -> EngineResult<ValueNode> {
         match value {
-            ValueNode::Variable(var_name) => self.resolve_variable_ref(var_name, ctx),
+            ValueNode::Variable(var_name) => self.resolve_variable_ref(var_name, env),
             ValueNode::Directive(d) => {
                 if d.name == "input" {
-                    self.resolve_input_directive(d, current_var, ctx)
+                    self.resolve_input_directive(d, current_var, env)
                 } else {
                     Ok(ValueNode::Directive(d.clone()))
                 }
             }
             ValueNode::Pipeline(pipeline) => {
                 // Execute lens pipeline
-                self.execute_pipeline(pipeline, ctx, current_var)
+                self.execute_pipeline(pipeline, env, effects, current_var)
             }
             ValueNode::List(items) => {
                 let mut evaluated_items = Vec::new();
                 for item in items {
-                    evaluated_items.push(self.evaluate_value(item, ctx, current_var)?);
+                    evaluated_items.push(self.evaluate_value(item, env, effects, current_var)?);
                 }
                 Ok(ValueNode::List(evaluated_items))
             }
             ValueNode::Map(map) => {
                 let mut evaluated_map = OrderedMap::new();
                 for (key, val) in map {
-                    evaluated_map.insert(key.clone(), self.evaluate_value(val, ctx, current_var)?);
+                    evaluated_map.insert(
+                        key.clone(),
+                        self.evaluate_value(val, env, effects, current_var)?,
+                    );
                 }
                 Ok(ValueNode::Map(evaluated_map))
             }
@@ -474,7 +1186,7 @@ impl RDagEngine {
         &self,
         directive: &fct_ast::DirectiveNode,
         current_var: Option<&str>,
-        ctx: &mut ExecutionContext,
+        env: &NodeEnv<'_>,
     ) -> EngineResult<ValueNode> {
         let var_name = current_var.ok_or_else(|| EngineError::InputValidationFailed {
             message: "@input(...) used outside variable evaluation context".to_string(),
@@ -492,7 +1204,7 @@ impl RDagEngine {
             }
         };
 
-        let value = if let Some(provided) = ctx.get_input(var_name) {
+        let value = if let Some(provided) = env.runtime_inputs.get(var_name) {
             provided.clone()
         } else if let Some(default) = directive.args.get("default") {
             default.clone()
@@ -502,11 +1214,16 @@ impl RDagEngine {
             });
         };
 
-        if !Self::value_matches_runtime_type(&value, declared_type) {
+        if let Err(violations) = self
+            .input_types
+            .check_input(var_name, declared_type, &value)
+        {
             return Err(EngineError::InputValidationFailed {
                 message: format!(
-                    "Input '{}' does not satisfy declared type '{}'",
-                    var_name, declared_type
+                    "Input '{}' does not satisfy declared type '{}': {}",
+                    var_name,
+                    declared_type,
+                    describe_violations(&violations)
                 ),
             });
         }
@@ -514,32 +1231,14 @@ impl RDagEngine {
         Ok(value)
     }
 
-    fn value_matches_runtime_type(value: &ValueNode, declared_type: &str) -> bool {
-        match declared_type {
-            "any" => true,
-            "string" => matches!(value, ValueNode::String(_)),
-            "int" => matches!(value, ValueNode::Scalar(ScalarValue::Int(_))),
-            "float" => matches!(
-                value,
-                ValueNode::Scalar(ScalarValue::Float(_)) | ValueNode::Scalar(ScalarValue::Int(_))
-            ),
-            "bool" => matches!(value, ValueNode::Scalar(ScalarValue::Bool(_))),
-            "null" => matches!(value, ValueNode::Scalar(ScalarValue::Null)),
-            _ => false,
-        }
-    }
-
-    fn resolve_variable_ref(
-        &self,
-        var_ref: &str,
-        ctx: &ExecutionContext,
-    ) -> EngineResult<ValueNode> {
+    fn resolve_variable_ref(&self, var_ref: &str, env: &NodeEnv<'_>) -> EngineResult<ValueNode> {
         let mut parts = var_ref.split('.');
         let base = parts.next().unwrap_or(var_ref);
         let segments: Vec<&str> = parts.collect();
 
         let mut current =
-            ctx.get_variable(base)
+            env.variables
+                .get(base)
                 .cloned()
                 .ok_or_else(|| EngineError::VariableNotFound {
                     var: base.to_string(),
@@ -581,21 +1280,24 @@ impl RDagEngine {
     fn execute_pipeline(
         &self,
         pipeline: &PipelineNode,
-        ctx: &mut ExecutionContext,
+        env: &NodeEnv<'_>,
+        effects: &mut NodeEffects,
         current_var: Option<&str>,
     ) -> EngineResult<ValueNode> {
         // Evaluate initial value
-        let mut current_value = self.evaluate_value(&pipeline.initial, ctx, current_var)?;
+        let mut current_value =
+            self.evaluate_value(&pipeline.initial, env, effects, current_var)?;
 
         // Create lens context
         let lens_ctx = LensContext {
-            variables: ctx.variables.clone(),
+            variables: env.variables.clone(),
         };
 
         // Execute each lens in sequence
         for lens_call in &pipeline.lenses {
+            let started = effects.span_start();
             let (signature, effect_class, lens_version) = {
-                let lens = ctx.lens_registry.get(&lens_call.name).ok_or_else(|| {
+                let lens = env.lens_registry.get(&lens_call.name).ok_or_else(|| {
                     EngineError::UnknownLens {
                         name: lens_call.name.clone(),
                     }
@@ -631,7 +1333,7 @@ impl RDagEngine {
 
             let pre_lens_input = current_value.clone();
 
-            if ctx.mode == ExecutionMode::Pure {
+            if env.mode == ExecutionMode::Pure {
                 match signature.trust_level {
                     TrustLevel::Pure => {}
                     TrustLevel::Bounded => {
@@ -641,10 +1343,12 @@ impl RDagEngine {
                             &pre_lens_input,
                             &lens_call.args,
                             &lens_call.kwargs,
-                            &ctx.host_profile_id,
+                            env.host_profile_id,
                         )?;
-                        if let Some(cached) = ctx.get_lens_cache_entry(&cache_key).cloned() {
+                        if let Some(cached) = env.lens_cache.get(&cache_key).cloned() {
                             current_value = cached;
+                            let args = serde_json::json!({ "gas": 0, "cache": "hit" });
+                            effects.end_span(started, &lens_call.name, "lens", args);
                             continue;
                         }
                         return Err(EngineError::ExecutionError {
@@ -666,532 +1370,193 @@ impl RDagEngine {
             }
 
             // Guard check for dangerous lens operations before invocation.
-            if self.should_guard_lens_call(signature.trust_level, ctx.mode) {
-                let guard =
-                    self.evaluate_lens_call_policy(&lens_call.name, effect_class.as_deref(), ctx)?;
-                let input_hash = self.lens_call_input_hash(
-                    &lens_call.name,
-                    &lens_version,
-                    &pre_lens_input,
-                    &lens_call.args,
-                    &lens_call.kwargs,
-                    &ctx.host_profile_id,
-                )?;
-                ctx.record_guard_decision(ExecutionGuardDecision {
-                    seq: 0,
-                    op: "lens_call".to_string(),
-                    name: lens_call.name.clone(),
-                    effect_class: effect_class.clone(),
-                    mode: match ctx.mode {
-                        ExecutionMode::Pure => "pure".to_string(),
-                        ExecutionMode::Exec => "exec".to_string(),
-                    },
-                    decision: if guard.allowed {
-                        "allowed".to_string()
-                    } else {
-                        "denied".to_string()
-                    },
-                    policy_rule_id: guard.policy_rule_id.clone(),
-                    input_hash,
-                    error_code: guard.error_code.clone(),
-                });
-
-                if guard.error_code.as_deref() == Some("F455") {
-                    return Err(EngineError::GuardUndecidable {
-                        name: lens_call.name.clone(),
-                    });
-                }
-                if !guard.allowed {
-                    return Err(EngineError::PolicyDenied {
-                        name: lens_call.name.clone(),
-                    });
-                }
-            }
-
-            // Evaluate arguments
-            let mut evaluated_args = Vec::new();
-            for arg in &lens_call.args {
-                evaluated_args.push(self.evaluate_value(arg, ctx, current_var)?);
-            }
-
-            let mut evaluated_kwargs = HashMap::new();
-            for (key, val) in &lens_call.kwargs {
-                evaluated_kwargs.insert(key.clone(), self.evaluate_value(val, ctx, current_var)?);
-            }
-
-            // Look up lens in registry
-            let lens =
-                ctx.lens_registry
-                    .get(&lens_call.name)
-                    .ok_or_else(|| EngineError::UnknownLens {
-                        name: lens_call.name.clone(),
-                    })?;
-
-            let lens_gas_cost = lens.gas_cost(&current_value, &evaluated_args, &evaluated_kwargs);
-            ctx.gas.consume(lens_gas_cost)?;
-
-            // Execute lens
-            current_value = lens
-                .execute(current_value, evaluated_args, evaluated_kwargs, &lens_ctx)
-                .map_err(|e| EngineError::LensExecutionFailed {
-                    message: format!("Lens '{}' failed: {}", lens_call.name, e),
-                })?;
-
-            if ctx.mode == ExecutionMode::Exec
-                && matches!(signature.trust_level, TrustLevel::Bounded)
-            {
-                let cache_key = self.level1_cache_key(
-                    &lens_call.name,
-                    &lens_version,
-                    &pre_lens_input,
-                    &lens_call.args,
-                    &lens_call.kwargs,
-                    &ctx.host_profile_id,
-                )?;
-                ctx.set_lens_cache_entry(cache_key, current_value.clone());
-            }
-        }
-
-        Ok(current_value)
-    }
-
-    fn should_guard_lens_call(&self, trust_level: TrustLevel, mode: ExecutionMode) -> bool {
-        matches!(trust_level, TrustLevel::Volatile)
-            || (matches!(trust_level, TrustLevel::Bounded) && mode == ExecutionMode::Exec)
-    }
-
-    fn evaluate_lens_call_policy(
-        &self,
-        lens_name: &str,
-        effect_class: Option<&str>,
-        ctx: &ExecutionContext,
-    ) -> EngineResult<LensPolicyDecision> {
-        let Some(policy_map) = self.effective_policy.as_ref() else {
-            return Ok(LensPolicyDecision {
-                allowed: false,
-                policy_rule_id: None,
-                error_code: Some("F454".to_string()),
-            });
-        };
-
-        if let Some(deny_rules) = policy_map.get("deny").and_then(as_rule_list) {
-            for rule in deny_rules {
-                match rule_matches_lens_call(rule, lens_name, effect_class, Some(&ctx.variables)) {
-                    RuleMatch::Matched(rule_id) => {
-                        return Ok(LensPolicyDecision {
-                            allowed: false,
-                            policy_rule_id: rule_id,
-                            error_code: Some("F454".to_string()),
-                        });
-                    }
-                    RuleMatch::Undecidable(rule_id) => {
-                        return Ok(LensPolicyDecision {
-                            allowed: false,
-                            policy_rule_id: rule_id,
-                            error_code: Some("F455".to_string()),
-                        });
-                    }
-                    RuleMatch::NoMatch => {}
-                }
-            }
-        }
-
-        if let Some(allow_rules) = policy_map.get("allow").and_then(as_rule_list) {
-            for rule in allow_rules {
-                match rule_matches_lens_call(rule, lens_name, effect_class, Some(&ctx.variables)) {
-                    RuleMatch::Matched(rule_id) => {
-                        return Ok(LensPolicyDecision {
-                            allowed: true,
-                            policy_rule_id: rule_id,
-                            error_code: None,
-                        });
-                    }
-                    RuleMatch::Undecidable(rule_id) => {
-                        return Ok(LensPolicyDecision {
-                            allowed: false,
-                            policy_rule_id: rule_id,
-                            error_code: Some("F455".to_string()),
-                        });
-                    }
-                    RuleMatch::NoMatch => {}
-                }
-            }
-        }
-
-        Ok(default_guard_decision(policy_map, "lens_call", false))
-    }
-
-    fn lens_call_input_hash(
-        &self,
-        lens_name: &str,
-        lens_version: &str,
-        input: &ValueNode,
-        args: &[ValueNode],
-        named_args: &OrderedMap<String, ValueNode>,
-        host_profile_id: &str,
-    ) -> EngineResult<String> {
-        let cache_key = self.level1_cache_key(
-            lens_name,
-            lens_version,
-            input,
-            args,
-            named_args,
-            host_profile_id,
-        )?;
-        Ok(format!("sha256:{}", cache_key))
-    }
-
-    fn level1_cache_key(
-        &self,
-        lens_name: &str,
-        lens_version: &str,
-        input: &ValueNode,
-        args: &[ValueNode],
-        named_args: &OrderedMap<String, ValueNode>,
-        host_profile_id: &str,
-    ) -> EngineResult<String> {
-        let envelope = serde_json::json!({
-            "lens": {
-                "name": lens_name,
-                "version": lens_version,
-            },
-            "input": value_node_to_json(input)?,
-            "args": value_nodes_to_json(args)?,
-            "named_args": ordered_map_to_json(named_args)?,
-            "host_profile_id": host_profile_id,
-            "facet_version": FACET_VERSION,
-        });
-        let canonical = canonicalize_json(&envelope)?;
-        Ok(format!("{:x}", Sha256::digest(canonical.as_bytes())))
-    }
-}
-
-fn collect_effective_policy(document: &FacetDocument) -> Option<OrderedMap<String, ValueNode>> {
-    let mut effective: OrderedMap<String, ValueNode> = OrderedMap::new();
-    let mut seen_policy = false;
-
-    for node in &document.blocks {
-        if let FacetNode::Policy(policy_block) = node {
-            seen_policy = true;
-            for body in &policy_block.body {
-                if let BodyNode::KeyValue(kv) = body {
-                    if let Some(existing) = effective.get_mut(&kv.key) {
-                        let merged =
-                            merge_policy_value(&kv.key, existing.clone(), kv.value.clone());
-                        *existing = merged;
-                    } else {
-                        effective.insert(kv.key.clone(), kv.value.clone());
-                    }
-                }
-            }
-        }
-    }
-
-    seen_policy.then_some(effective)
-}
-
-fn compute_policy_hash(
-    effective_policy: Option<&OrderedMap<String, ValueNode>>,
-) -> EngineResult<Option<String>> {
-    let Some(policy_map) = effective_policy else {
-        return Ok(None);
-    };
-
-    let envelope = serde_json::json!({
-        "policy_version": POLICY_VERSION,
-        "policy": ordered_map_to_json(policy_map)?,
-    });
-    let canonical = canonicalize_json(&envelope)?;
-    let digest = Sha256::digest(canonical.as_bytes());
-    Ok(Some(format!("sha256:{:x}", digest)))
-}
-
-fn merge_policy_value(key: &str, old: ValueNode, new: ValueNode) -> ValueNode {
-    match (old, new) {
-        (ValueNode::Map(old_map), ValueNode::Map(new_map)) => {
-            ValueNode::Map(merge_policy_maps(old_map, new_map))
-        }
-        (ValueNode::List(old_list), ValueNode::List(new_list))
-            if key == "allow" || key == "deny" =>
-        {
-            ValueNode::List(merge_policy_lists(old_list, new_list))
-        }
-        (_, replacement) => replacement,
-    }
-}
-
-fn merge_policy_maps(
-    mut old_map: OrderedMap<String, ValueNode>,
-    new_map: OrderedMap<String, ValueNode>,
-) -> OrderedMap<String, ValueNode> {
-    for (key, new_value) in new_map {
-        if let Some(old_value) = old_map.get_mut(&key) {
-            *old_value = merge_policy_value(&key, old_value.clone(), new_value);
-        } else {
-            old_map.insert(key, new_value);
-        }
-    }
-    old_map
-}
-
-fn merge_policy_lists(old_items: Vec<ValueNode>, new_items: Vec<ValueNode>) -> Vec<ValueNode> {
-    let mut merged = Vec::new();
-    let mut id_index: OrderedMap<String, usize> = OrderedMap::new();
-
-    for item in old_items {
-        if let Some(id) = policy_rule_id(&item) {
-            id_index.insert(id.to_string(), merged.len());
-        }
-        merged.push(item);
-    }
-
-    for item in new_items {
-        if let Some(id) = policy_rule_id(&item) {
-            if let Some(pos) = id_index.get(id).copied() {
-                let old_item = merged[pos].clone();
-                merged[pos] = merge_policy_value("", old_item, item);
-            } else {
-                id_index.insert(id.to_string(), merged.len());
-                merged.push(item);
-            }
-        } else {
-            merged.push(item);
-        }
-    }
-
-    merged
-}
-
-fn policy_rule_id(item: &ValueNode) -> Option<&str> {
-    match item {
-        ValueNode::Map(map) => match map.get("id") {
-            Some(ValueNode::String(id)) => Some(id.as_str()),
-            _ => None,
-        },
-        _ => None,
-    }
-}
-
-fn as_rule_list(value: &ValueNode) -> Option<&Vec<ValueNode>> {
-    match value {
-        ValueNode::List(items) => Some(items),
-        _ => None,
-    }
-}
-
-enum RuleMatch {
-    Matched(Option<String>),
-    Undecidable(Option<String>),
-    NoMatch,
-}
-
-fn rule_matches_lens_call(
-    rule: &ValueNode,
-    lens_name: &str,
-    lens_effect_class: Option<&str>,
-    computed_vars: Option<&HashMap<String, ValueNode>>,
-) -> RuleMatch {
-    let ValueNode::Map(map) = rule else {
-        return RuleMatch::NoMatch;
-    };
-
-    let Some(ValueNode::String(op)) = map.get("op") else {
-        return RuleMatch::NoMatch;
-    };
-    if op != "lens_call" {
-        return RuleMatch::NoMatch;
-    }
-    let rule_id = match map.get("id") {
-        Some(ValueNode::String(id)) => Some(id.clone()),
-        _ => None,
-    };
-
-    let name_match = match map.get("name") {
-        Some(ValueNode::String(pattern)) => matcher_matches(pattern, lens_name),
-        Some(_) => return RuleMatch::Undecidable(rule_id),
-        None => true,
-    };
-    if !name_match {
-        return RuleMatch::NoMatch;
-    }
+            if self.should_guard_lens_call(signature.trust_level, env.mode) {
+                let guard =
+                    self.evaluate_lens_call_policy(&lens_call.name, effect_class.as_deref(), env);
+                let input_hash = fold_input_hash(
+                    lens_call_input_hash(
+                        &lens_call.name,
+                        &lens_version,
+                        &pre_lens_input,
+                        &lens_call.args,
+                        &lens_call.kwargs,
+                        env.host_profile_id,
+                    )?,
+                    env.host_context,
+                )?;
+                effects.record_guard_decision(ExecutionGuardDecision {
+                    seq: 0,
+                    op: "lens_call".to_string(),
+                    name: lens_call.name.clone(),
+                    effect_class: effect_class.clone(),
+                    mode: match env.mode {
+                        ExecutionMode::Pure => "pure".to_string(),
+                        ExecutionMode::Exec => "exec".to_string(),
+                    },
+                    decision: guard.decision().to_string(),
+                    policy_rule_id: guard.policy_rule_id.clone(),
+                    input_hash,
+                    error_code: guard.error_code.clone(),
+                });
 
-    if let Some(effect_matcher) = map.get("effect") {
-        match effect_matcher {
-            ValueNode::String(pattern) => {
-                let Some(effect_class) = lens_effect_class else {
-                    return RuleMatch::NoMatch;
-                };
-                if !matcher_matches(pattern, effect_class) {
-                    return RuleMatch::NoMatch;
+                if guard.is_undecidable() {
+                    return Err(EngineError::GuardUndecidable {
+                        name: lens_call.name.clone(),
+                    });
+                }
+                if !guard.allowed {
+                    return Err(EngineError::PolicyDenied {
+                        name: lens_call.name.clone(),
+                    });
                 }
             }
-            _ => return RuleMatch::Undecidable(rule_id),
-        }
-    }
 
-    let when_eval = match map.get("when") {
-        None => true,
-        Some(cond) => match eval_policy_cond(cond, computed_vars) {
-            Ok(v) => v,
-            Err(()) => return RuleMatch::Undecidable(rule_id),
-        },
-    };
-    if !when_eval {
-        return RuleMatch::NoMatch;
-    }
+            // Evaluate arguments
+            let mut evaluated_args = Vec::new();
+            for arg in &lens_call.args {
+                evaluated_args.push(self.evaluate_value(arg, env, effects, current_var)?);
+            }
 
-    let unless_eval = match map.get("unless") {
-        None => false,
-        Some(cond) => match eval_policy_cond(cond, computed_vars) {
-            Ok(v) => v,
-            Err(()) => return RuleMatch::Undecidable(rule_id),
-        },
-    };
-    if unless_eval {
-        return RuleMatch::NoMatch;
-    }
+            let mut evaluated_kwargs = HashMap::new();
+            for (key, val) in &lens_call.kwargs {
+                evaluated_kwargs.insert(
+                    key.clone(),
+                    self.evaluate_value(val, env, effects, current_var)?,
+                );
+            }
 
-    RuleMatch::Matched(rule_id)
-}
+            // Look up lens in registry
+            let lens =
+                env.lens_registry
+                    .get(&lens_call.name)
+                    .ok_or_else(|| EngineError::UnknownLens {
+                        name: lens_call.name.clone(),
+                    })?;
 
-fn default_guard_decision(
-    policy_map: &OrderedMap<String, ValueNode>,
-    op: &str,
-    fallback_allow: bool,
-) -> LensPolicyDecision {
-    if let Some(defaults_node) = policy_map.get("defaults") {
-        let defaults_map = match defaults_node {
-            ValueNode::Map(map) => map,
-            _ => {
-                return LensPolicyDecision {
-                    allowed: false,
-                    policy_rule_id: None,
-                    error_code: Some("F455".to_string()),
+            // Execute lens within the gas left to this node
+            let metered = lens.execute_metered(
+                current_value,
+                evaluated_args,
+                evaluated_kwargs,
+                &lens_ctx,
+                effects.gas_available,
+            );
+            let (output, lens_gas_cost) = match metered {
+                Ok(metered) => metered,
+                Err(LensError::GasExhausted { needed }) => return Err(effects.exhaust_gas(needed)),
+                Err(e) => {
+                    return Err(EngineError::LensExecutionFailed {
+                        message: format!("Lens '{}' failed: {}", lens_call.name, e),
+                    })
                 }
+            };
+            effects.consume_gas(lens_gas_cost)?;
+            current_value = output;
+
+            if env.mode == ExecutionMode::Exec
+                && matches!(signature.trust_level, TrustLevel::Bounded)
+            {
+                let cache_key = self.level1_cache_key(
+                    &lens_call.name,
+                    &lens_version,
+                    &pre_lens_input,
+                    &lens_call.args,
+                    &lens_call.kwargs,
+                    env.host_profile_id,
+                )?;
+                effects.set_lens_cache_entry(cache_key, current_value.clone());
             }
-        };
 
-        if let Some(op_default) = defaults_map.get(op) {
-            return match op_default {
-                ValueNode::String(s) if s == "allow" => LensPolicyDecision {
-                    allowed: true,
-                    policy_rule_id: None,
-                    error_code: None,
-                },
-                ValueNode::String(s) if s == "deny" => LensPolicyDecision {
-                    allowed: false,
-                    policy_rule_id: None,
-                    error_code: Some("F454".to_string()),
-                },
-                ValueNode::Scalar(ScalarValue::Bool(true)) => LensPolicyDecision {
-                    allowed: true,
-                    policy_rule_id: None,
-                    error_code: None,
-                },
-                ValueNode::Scalar(ScalarValue::Bool(false)) => LensPolicyDecision {
-                    allowed: false,
-                    policy_rule_id: None,
-                    error_code: Some("F454".to_string()),
-                },
-                _ => LensPolicyDecision {
-                    allowed: false,
-                    policy_rule_id: None,
-                    error_code: Some("F455".to_string()),
-                },
+            let cache = if matches!(signature.trust_level, TrustLevel::Bounded) {
+                "miss"
+            } else {
+                "uncached"
             };
+            let args = serde_json::json!({ "gas": lens_gas_cost, "cache": cache });
+            effects.end_span(started, &lens_call.name, "lens", args);
         }
+
+        Ok(current_value)
     }
 
-    LensPolicyDecision {
-        allowed: fallback_allow,
-        policy_rule_id: None,
-        error_code: if fallback_allow {
-            None
-        } else {
-            Some("F454".to_string())
-        },
+    fn should_guard_lens_call(&self, trust_level: TrustLevel, mode: ExecutionMode) -> bool {
+        matches!(trust_level, TrustLevel::Volatile)
+            || (matches!(trust_level, TrustLevel::Bounded) && mode == ExecutionMode::Exec)
     }
-}
 
-fn eval_policy_cond(
-    cond: &ValueNode,
-    computed_vars: Option<&HashMap<String, ValueNode>>,
-) -> Result<bool, ()> {
-    match cond {
-        ValueNode::Scalar(ScalarValue::Bool(v)) => Ok(*v),
-        ValueNode::Variable(var_ref) => {
-            let vars = computed_vars.ok_or(())?;
-            let value = resolve_policy_var(var_ref, vars)?;
-            match value {
-                ValueNode::Scalar(ScalarValue::Bool(v)) => Ok(*v),
-                _ => Err(()),
-            }
-        }
-        ValueNode::Map(map) => {
-            if map.len() != 1 {
-                return Err(());
-            }
-            let (op, arg) = map.iter().next().ok_or(())?;
-            match op.as_str() {
-                "not" => Ok(!eval_policy_cond(arg, computed_vars)?),
-                "all" => {
-                    let items = match arg {
-                        ValueNode::List(items) if !items.is_empty() => items,
-                        _ => return Err(()),
-                    };
-                    for item in items {
-                        if !eval_policy_cond(item, computed_vars)? {
-                            return Ok(false);
-                        }
-                    }
-                    Ok(true)
-                }
-                "any" => {
-                    let items = match arg {
-                        ValueNode::List(items) if !items.is_empty() => items,
-                        _ => return Err(()),
-                    };
-                    for item in items {
-                        if eval_policy_cond(item, computed_vars)? {
-                            return Ok(true);
-                        }
-                    }
-                    Ok(false)
-                }
-                _ => Err(()),
-            }
-        }
-        _ => Err(()),
+    fn evaluate_lens_call_policy(
+        &self,
+        lens_name: &str,
+        effect_class: Option<&str>,
+        env: &NodeEnv<'_>,
+    ) -> PolicyDecision {
+        let op = OpDesc::new(PolicyOp::LensCall, lens_name, effect_class);
+        let scope = policy_scope(Some(env.variables), env.host_context);
+        evaluate_guard(self.effective_policy.as_ref(), &op, scope.as_deref())
     }
-}
 
-fn resolve_policy_var<'a>(
-    var_ref: &str,
-    vars: &'a HashMap<String, ValueNode>,
-) -> Result<&'a ValueNode, ()> {
-    let mut parts = var_ref.split('.');
-    let base = parts.next().ok_or(())?;
-    let mut current = vars.get(base).ok_or(())?;
-
-    for seg in parts {
-        if seg.chars().all(|c| c.is_ascii_digit()) {
-            return Err(());
-        }
-        current = match current {
-            ValueNode::Map(map) => map.get(seg).ok_or(())?,
-            _ => return Err(()),
-        };
+    fn level1_cache_key(
+        &self,
+        lens_name: &str,
+        lens_version: &str,
+        input: &ValueNode,
+        args: &[ValueNode],
+        named_args: &OrderedMap<String, ValueNode>,
+        host_profile_id: &str,
+    ) -> EngineResult<String> {
+        level1_cache_key(
+            lens_name,
+            lens_version,
+            input,
+            args,
+            named_args,
+            host_profile_id,
+        )
     }
+}
 
-    Ok(current)
+/// `input_hash` of a `lens_call` guard decision: the prefixed Level-1 cache key
+/// (Appendix C) of the lens invocation.
+pub fn lens_call_input_hash(
+    lens_name: &str,
+    lens_version: &str,
+    input: &ValueNode,
+    args: &[ValueNode],
+    named_args: &OrderedMap<String, ValueNode>,
+    host_profile_id: &str,
+) -> EngineResult<String> {
+    let cache_key = level1_cache_key(
+        lens_name,
+        lens_version,
+        input,
+        args,
+        named_args,
+        host_profile_id,
+    )?;
+    Ok(format!("sha256:{}", cache_key))
 }
 
-fn matcher_matches(pattern: &str, value: &str) -> bool {
-    if let Some(prefix) = pattern.strip_suffix(".*") {
-        value.starts_with(prefix)
-    } else {
-        pattern == value
-    }
+fn level1_cache_key(
+    lens_name: &str,
+    lens_version: &str,
+    input: &ValueNode,
+    args: &[ValueNode],
+    named_args: &OrderedMap<String, ValueNode>,
+    host_profile_id: &str,
+) -> EngineResult<String> {
+    let envelope = serde_json::json!({
+        "lens": {
+            "name": lens_name,
+            "version": lens_version,
+        },
+        "input": value_node_to_json(input)?,
+        "args": value_nodes_to_json(args)?,
+        "named_args": ordered_map_to_json(named_args)?,
+        "host_profile_id": host_profile_id,
+        "facet_version": FACET_VERSION,
+    });
+    let canonical = canonicalize_json(&envelope)?;
+    Ok(format!("{:x}", Sha256::digest(canonical.as_bytes())))
 }
 
 fn is_valid_effect_class(effect: &str) -> bool {
@@ -1709,6 +2074,56 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_evaluate_expression_reads_computed_variables_and_charges_gas() {
+        let span = Span {
+            start: 0,
+            end: 0,
+            line: 1,
+            column: 1,
+        };
+        let pipeline = |initial: ValueNode, lens: &str, arg: &str| {
+            ValueNode::Pipeline(PipelineNode {
+                initial: Box::new(initial),
+                lenses: vec![LensCallNode {
+                    name: lens.to_string(),
+                    args: vec![ValueNode::String(arg.to_string())],
+                    kwargs: OrderedMap::new(),
+                    span: span.clone(),
+                }],
+                span: span.clone(),
+            })
+        };
+        let doc = FacetDocument {
+            blocks: vec![FacetNode::Vars(FacetBlock {
+                name: "vars".to_string(),
+                attributes: OrderedMap::new(),
+                body: vec![BodyNode::KeyValue(KeyValueNode {
+                    key: "items".to_string(),
+                    key_kind: Default::default(),
+                    value: pipeline(ValueNode::String("a,b,c".to_string()), "split", ","),
+                    span: span.clone(),
+                })],
+                span: span.clone(),
+            })],
+            span: span.clone(),
+        };
+
+        let mut engine = RDagEngine::new();
+        engine.build(&doc).unwrap();
+        let mut ctx = ExecutionContext::new(1000);
+        engine.execute(&mut ctx).unwrap();
+        let gas_after_vars = ctx.gas.consumed;
+
+        // $items |> join("-")
+        let expression = pipeline(ValueNode::Variable("items".to_string()), "join", "-");
+        let value = engine.evaluate_expression(&expression, &mut ctx).unwrap();
+
+        assert_eq!(value, ValueNode::String("a-b-c".to_string()));
+        assert!(ctx.gas.consumed > gas_after_vars);
+        assert_eq!(ctx.variables.len(), 1);
+    }
+
     #[test]
     fn test_topological_sort_uses_vars_insertion_order_for_independent_nodes() {
         let doc = FacetDocument {
@@ -2146,8 +2561,8 @@ mod tests {
             .as_ref()
             .expect("effective policy must be materialized");
         let envelope = serde_json::json!({
-            "policy_version": POLICY_VERSION,
-            "policy": ordered_map_to_json(effective).expect("policy to json"),
+            "policy_version": fct_ast::POLICY_VERSION,
+            "policy": ordered_map_to_json(effective.as_map()).expect("policy to json"),
         });
         let canonical = canonicalize_json(&envelope).expect("canonical policy envelope");
         let expected_hash = format!("sha256:{:x}", Sha256::digest(canonical.as_bytes()));
@@ -3732,4 +4147,269 @@ mod tests {
         assert_eq!(ctx.guard_decisions[0].error_code.as_deref(), Some("F455"));
         assert_eq!(BOUNDED_COUNTING_LENS_EXECUTIONS.load(Ordering::SeqCst), 0);
     }
+
+    static REACTIVE_COUNTING_LENS_EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+
+    struct ReactiveCountingLens;
+
+    impl Lens for ReactiveCountingLens {
+        fn execute(
+            &self,
+            input: ValueNode,
+            _args: Vec<ValueNode>,
+            _kwargs: HashMap<String, ValueNode>,
+            _ctx: &LensContext,
+        ) -> Result<ValueNode, LensError> {
+            REACTIVE_COUNTING_LENS_EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+            Ok(input)
+        }
+
+        fn signature(&self) -> LensSignature {
+            LensSignature {
+                name: "reactive_counting".to_string(),
+                input_type: "any".to_string(),
+                output_type: "any".to_string(),
+                trust_level: TrustLevel::Pure,
+                deterministic: true,
+            }
+        }
+    }
+
+    #[test]
+    fn test_execute_reactive_recomputes_only_dependents_of_changed_inputs() {
+        let span = Span {
+            start: 0,
+            end: 0,
+            line: 1,
+            column: 1,
+        };
+        let counted = |initial: ValueNode| {
+            ValueNode::Pipeline(PipelineNode {
+                initial: Box::new(initial),
+                lenses: vec![LensCallNode {
+                    name: "reactive_counting".to_string(),
+                    args: vec![],
+                    kwargs: OrderedMap::new(),
+                    span: span.clone(),
+                }],
+                span: span.clone(),
+            })
+        };
+        let var = |key: &str, value: ValueNode| {
+            BodyNode::KeyValue(KeyValueNode {
+                key: key.to_string(),
+                key_kind: Default::default(),
+                value,
+                span: span.clone(),
+            })
+        };
+        let doc = FacetDocument {
+            blocks: vec![FacetNode::Vars(FacetBlock {
+                name: "vars".to_string(),
+                attributes: OrderedMap::new(),
+                body: vec![
+                    var(
+                        "name",
+                        ValueNode::Directive(DirectiveNode {
+                            name: "input".to_string(),
+                            args: OrderedMap::from([(
+                                "type".to_string(),
+                                ValueNode::String("string".to_string()),
+                            )]),
+                            span: span.clone(),
+                        }),
+                    ),
+                    var("topic", counted(ValueNode::String("weather".to_string()))),
+                    var("greeting", counted(ValueNode::Variable("name".to_string()))),
+                ],
+                span: span.clone(),
+            })],
+            span: span.clone(),
+        };
+
+        let mut engine = RDagEngine::new();
+        engine.build(&doc).unwrap();
+        let mut memo = RDagMemo::new();
+        let run = |engine: &RDagEngine, memo: &mut RDagMemo, mode, name: &str| {
+            let mut ctx = ExecutionContext::new_with_mode(1_000, mode);
+            ctx.lens_registry.register(Box::new(ReactiveCountingLens));
+            ctx.set_input("name".to_string(), ValueNode::String(name.to_string()));
+            engine.execute_reactive(&mut ctx, memo).unwrap();
+            ctx
+        };
+
+        REACTIVE_COUNTING_LENS_EXECUTIONS.store(0, Ordering::SeqCst);
+        let first = run(&engine, &mut memo, ExecutionMode::Exec, "Ada");
+        assert_eq!(memo.recomputed(), ["name", "topic", "greeting"]);
+        assert_eq!(REACTIVE_COUNTING_LENS_EXECUTIONS.load(Ordering::SeqCst), 2);
+
+        let second = run(&engine, &mut memo, ExecutionMode::Exec, "Grace");
+        assert_eq!(memo.reused(), ["topic"]);
+        assert_eq!(memo.recomputed(), ["name", "greeting"]);
+        assert_eq!(REACTIVE_COUNTING_LENS_EXECUTIONS.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            second.get_variable("greeting"),
+            Some(&ValueNode::String("Grace".to_string()))
+        );
+        assert_eq!(second.get_variable("topic"), first.get_variable("topic"));
+        assert_eq!(second.gas.consumed, first.gas.consumed);
+        assert!(second.is_variables_frozen());
+
+        run(&engine, &mut memo, ExecutionMode::Pure, "Grace");
+        assert!(memo.reused().is_empty(), "mode change drops the memo");
+        assert_eq!(memo.len(), 3);
+    }
+
+    /// Bounded lens that finishes later the earlier its input sorts, so
+    /// concurrent nodes complete out of evaluation order.
+    struct StaggeredLens;
+
+    impl Lens for StaggeredLens {
+        fn execute(
+            &self,
+            input: ValueNode,
+            _args: Vec<ValueNode>,
+            _kwargs: HashMap<String, ValueNode>,
+            _ctx: &LensContext,
+        ) -> Result<ValueNode, LensError> {
+            if input == ValueNode::String("slow".to_string()) {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+            }
+            Ok(input)
+        }
+
+        fn signature(&self) -> LensSignature {
+            LensSignature {
+                name: "staggered".to_string(),
+                input_type: "any".to_string(),
+                output_type: "any".to_string(),
+                trust_level: TrustLevel::Bounded,
+                deterministic: true,
+            }
+        }
+
+        fn effect_class(&self) -> Option<&'static str> {
+            Some("external")
+        }
+    }
+
+    #[test]
+    fn test_parallel_execution_matches_sequential() {
+        let span = Span {
+            start: 0,
+            end: 0,
+            line: 1,
+            column: 1,
+        };
+        let staggered = |initial: ValueNode| {
+            ValueNode::Pipeline(PipelineNode {
+                initial: Box::new(initial),
+                lenses: vec![LensCallNode {
+                    name: "staggered".to_string(),
+                    args: vec![],
+                    kwargs: OrderedMap::new(),
+                    span: span.clone(),
+                }],
+                span: span.clone(),
+            })
+        };
+        let entry = |key: &str, value: ValueNode| {
+            BodyNode::KeyValue(KeyValueNode {
+                key: key.to_string(),
+                key_kind: Default::default(),
+                value,
+                span: span.clone(),
+            })
+        };
+        let allow_rule = ValueNode::Map(OrderedMap::from([
+            (
+                "id".to_string(),
+                ValueNode::String("allow-staggered".to_string()),
+            ),
+            ("op".to_string(), ValueNode::String("lens_call".to_string())),
+            (
+                "name".to_string(),
+                ValueNode::String("staggered".to_string()),
+            ),
+            (
+                "when".to_string(),
+                ValueNode::Variable("enabled".to_string()),
+            ),
+        ]));
+        let doc = FacetDocument {
+            blocks: vec![
+                FacetNode::Policy(FacetBlock {
+                    name: "policy".to_string(),
+                    attributes: OrderedMap::new(),
+                    body: vec![entry("allow", ValueNode::List(vec![allow_rule]))],
+                    span: span.clone(),
+                }),
+                FacetNode::Vars(FacetBlock {
+                    name: "vars".to_string(),
+                    attributes: OrderedMap::new(),
+                    body: vec![
+                        entry("enabled", ValueNode::Scalar(ScalarValue::Bool(true))),
+                        entry("a", staggered(ValueNode::String("slow".to_string()))),
+                        entry("b", staggered(ValueNode::String("fast".to_string()))),
+                        entry(
+                            "c",
+                            ValueNode::List(vec![
+                                ValueNode::Variable("a".to_string()),
+                                ValueNode::Variable("b".to_string()),
+                            ]),
+                        ),
+                        entry("d", staggered(ValueNode::Variable("c".to_string()))),
+                        entry("e", staggered(ValueNode::String("other".to_string()))),
+                    ],
+                    span: span.clone(),
+                }),
+            ],
+            span: span.clone(),
+        };
+
+        let run = |parallel: bool, gas_limit: usize| {
+            let mut engine = RDagEngine::new().with_parallel(parallel);
+            engine.build(&doc).unwrap();
+            let mut ctx = ExecutionContext::new_with_mode(gas_limit, ExecutionMode::Exec);
+            ctx.lens_registry.register(Box::new(StaggeredLens));
+            let result = engine.execute(&mut ctx).map_err(|e| e.to_string());
+            (engine, ctx, result)
+        };
+
+        let (engine, parallel, result) = run(true, 1_000);
+        result.unwrap();
+        let order = engine.topological_order().unwrap();
+        let order: Vec<&str> = order.iter().map(String::as_str).collect();
+        let nodes: Vec<_> = order
+            .iter()
+            .map(|name| engine.graph.nodes.get(*name))
+            .collect();
+        assert_eq!(order, ["enabled", "a", "b", "c", "d", "e"]);
+        assert_eq!(engine.schedule_waves(&order, &nodes), [0, 1, 1, 2, 3, 1]);
+
+        let (_, sequential, result) = run(false, 1_000);
+        result.unwrap();
+        assert_eq!(parallel.variables, sequential.variables);
+        assert_eq!(parallel.lens_cache, sequential.lens_cache);
+        assert_eq!(parallel.gas.consumed, sequential.gas.consumed);
+        assert_eq!(
+            serde_json::to_value(&parallel.guard_decisions).unwrap(),
+            serde_json::to_value(&sequential.guard_decisions).unwrap()
+        );
+        let seqs: Vec<usize> = parallel.guard_decisions.iter().map(|d| d.seq).collect();
+        assert_eq!(seqs, [1, 2, 3, 4]);
+
+        // Exhausting gas part-way stops both runs at the same node.
+        let limit = sequential.gas.consumed - 3;
+        let (_, parallel, parallel_result) = run(true, limit);
+        let (_, sequential, sequential_result) = run(false, limit);
+        assert!(sequential_result.is_err());
+        assert_eq!(parallel_result, sequential_result);
+        assert_eq!(parallel.gas.consumed, sequential.gas.consumed);
+        assert_eq!(parallel.variables, sequential.variables);
+        assert_eq!(
+            parallel.guard_decisions.len(),
+            sequential.guard_decisions.len()
+        );
+    }
 }
@@ -5,12 +5,14 @@
 use crate::errors::{EngineError, EngineResult};
 use crate::{
     count_facet_units_in_value, derive_message_section_id, value_node_to_json, ExecutionContext,
-    RDagEngine, Section, TokenBoxModel, ToolDefinition, ToolExecutor, ToolInvocation,
+    ExecutionTrace, ItemPacking, LayoutReservation, RDagEngine, Section, TokenBoxModel,
+    ToolDefinition, ToolExecutor, ToolInvocation, PACK_KEY,
 };
 use fct_ast::{
     Assertion, AssertionKind, BodyNode, FacetBlock, FacetDocument, FacetNode, KeyValueNode,
     MockDefinition, OrderedMap, PipelineNode, ScalarValue, TestBlock, ValueNode, FACET_VERSION,
 };
+use fct_policy::{EffectivePolicy, HostContext};
 use fct_std::{LensContext, LensRegistry, TrustLevel};
 use serde_json::json;
 use std::collections::HashMap;
@@ -69,6 +71,9 @@ pub struct TestRunner {
     pub gas_limit: usize,
     pub token_budget: usize,
     pub mode: crate::ExecutionMode,
+    pub host_profile_id: String,
+    /// Read-only `$x.<host>...` context for policy conditions.
+    pub host_context: Option<HostContext>,
 }
 
 impl TestRunner {
@@ -87,9 +92,23 @@ impl TestRunner {
             gas_limit,
             token_budget,
             mode,
+            host_profile_id: "local.default.v1".to_string(),
+            host_context: None,
         }
     }
 
+    /// Run tests as the host `host_profile_id`, optionally with the context it
+    /// supplies to policy conditions.
+    pub fn with_host(
+        mut self,
+        host_profile_id: impl Into<String>,
+        host_context: Option<HostContext>,
+    ) -> Self {
+        self.host_profile_id = host_profile_id.into();
+        self.host_context = host_context;
+        self
+    }
+
     /// Discover all @test blocks in a document
     pub fn discover_tests<'a>(&self, doc: &'a FacetDocument) -> Vec<&'a TestBlock> {
         doc.blocks
@@ -106,10 +125,46 @@ impl TestRunner {
 
     /// Run a single test
     pub fn run_test(&self, doc: &FacetDocument, test: &TestBlock) -> EngineResult<TestResult> {
-        let start_time = Instant::now();
-
         // Create isolated test context
         let mut test_ctx = self.create_test_context(test)?;
+        self.run_test_in(doc, test, &mut test_ctx)
+    }
+
+    /// Run a single test, adding a `test` span and the spans of its R-DAG
+    /// nodes, lens calls and layout steps to `trace`.
+    pub fn run_test_traced(
+        &self,
+        doc: &FacetDocument,
+        test: &TestBlock,
+        trace: &mut ExecutionTrace,
+    ) -> EngineResult<TestResult> {
+        let started = Instant::now();
+        let mut test_ctx = self.create_test_context(test)?;
+        test_ctx.execution_ctx.trace = Some(ExecutionTrace::with_origin(trace.origin()));
+        let result = self.run_test_in(doc, test, &mut test_ctx);
+
+        if let Some(test_trace) = test_ctx.execution_ctx.trace.take() {
+            trace.extend(test_trace.into_spans());
+        }
+        trace.record(
+            test.name.clone(),
+            "test",
+            started,
+            json!({
+                "passed": result.as_ref().is_ok_and(|result| result.passed),
+                "gas": test_ctx.execution_ctx.gas.consumed,
+            }),
+        );
+        result
+    }
+
+    fn run_test_in(
+        &self,
+        doc: &FacetDocument,
+        test: &TestBlock,
+        test_ctx: &mut TestContext,
+    ) -> EngineResult<TestResult> {
+        let start_time = Instant::now();
 
         // Build effective document with @test vars overrides.
         let effective_doc = match self.build_doc_with_var_overrides(doc, &test.vars) {
@@ -119,7 +174,7 @@ impl TestRunner {
                     name: test.name.clone(),
                     passed: false,
                     assertions: Vec::new(),
-                    telemetry: test_ctx.telemetry,
+                    telemetry: test_ctx.telemetry.clone(),
                     rendered_output: None,
                     canonical_output: None,
                     execution_output: build_execution_test_view(&test_ctx.execution_ctx)
@@ -131,12 +186,12 @@ impl TestRunner {
         };
 
         // Apply runtime input overrides for @input(...) directives
-        if let Err(e) = self.apply_input_overrides(&mut test_ctx, &test.input) {
+        if let Err(e) = self.apply_input_overrides(test_ctx, &test.input) {
             return Ok(TestResult {
                 name: test.name.clone(),
                 passed: false,
                 assertions: Vec::new(),
-                telemetry: test_ctx.telemetry,
+                telemetry: test_ctx.telemetry.clone(),
                 rendered_output: None,
                 canonical_output: None,
                 execution_output: build_execution_test_view(&test_ctx.execution_ctx)
@@ -147,12 +202,12 @@ impl TestRunner {
         }
 
         // Apply mocks
-        if let Err(e) = self.apply_mocks(&mut test_ctx, &test.mocks) {
+        if let Err(e) = self.apply_mocks(test_ctx, &test.mocks) {
             return Ok(TestResult {
                 name: test.name.clone(),
                 passed: false,
                 assertions: Vec::new(),
-                telemetry: test_ctx.telemetry,
+                telemetry: test_ctx.telemetry.clone(),
                 rendered_output: None,
                 canonical_output: None,
                 execution_output: build_execution_test_view(&test_ctx.execution_ctx)
@@ -163,14 +218,14 @@ impl TestRunner {
         }
 
         // Execute the full pipeline
-        let rendered_output = match self.execute_pipeline(&effective_doc, &mut test_ctx) {
+        let rendered_output = match self.execute_pipeline(&effective_doc, test_ctx) {
             Ok(output) => Some(output),
             Err(e) => {
                 return Ok(TestResult {
                     name: test.name.clone(),
                     passed: false,
                     assertions: Vec::new(),
-                    telemetry: test_ctx.telemetry,
+                    telemetry: test_ctx.telemetry.clone(),
                     rendered_output: None,
                     canonical_output: None,
                     execution_output: build_execution_test_view(&test_ctx.execution_ctx)
@@ -187,7 +242,7 @@ impl TestRunner {
         // Evaluate assertions
         let assertions = self.evaluate_assertions(
             rendered_output.as_deref().unwrap_or(""),
-            &test_ctx,
+            test_ctx,
             &test.assertions,
         );
 
@@ -198,7 +253,7 @@ impl TestRunner {
             name: test.name.clone(),
             passed,
             assertions,
-            telemetry: test_ctx.telemetry,
+            telemetry: test_ctx.telemetry.clone(),
             rendered_output,
             canonical_output: test_ctx.canonical.as_ref().map(|v| v.to_string()),
             execution_output: test_ctx.execution.as_ref().map(|v| v.to_string()),
@@ -235,8 +290,13 @@ impl TestRunner {
 
     /// Create isolated test context
     fn create_test_context(&self, _test: &TestBlock) -> EngineResult<TestContext> {
+        let mut execution_ctx = ExecutionContext::new_with_mode(self.gas_limit, self.mode);
+        execution_ctx.host_profile_id = self.host_profile_id.clone();
+        if let Some(host_context) = &self.host_context {
+            execution_ctx.set_host_context(host_context.clone());
+        }
         Ok(TestContext {
-            execution_ctx: ExecutionContext::new_with_mode(self.gas_limit, self.mode),
+            execution_ctx,
             mock_registry: MockRegistry::default(),
             telemetry: TestTelemetry {
                 tokens_used: 0,
@@ -323,7 +383,16 @@ impl TestRunner {
         engine.validate()?;
 
         // Execute R-DAG
+        let started = Instant::now();
         engine.execute(&mut ctx.execution_ctx)?;
+        if let Some(trace) = ctx.execution_ctx.trace.as_mut() {
+            trace.record(
+                "execute",
+                "phase",
+                started,
+                json!({ "gas": ctx.execution_ctx.gas.consumed }),
+            );
+        }
 
         // Simulate @test interface mock calls through ToolExecutor guard path.
         self.execute_mocked_interface_calls(doc, ctx)?;
@@ -358,12 +427,34 @@ impl TestRunner {
 
         // Allocate tokens
         let effective_budget = effective_layout_budget_from_doc(doc, self.token_budget);
-        let model = TokenBoxModel::new(effective_budget);
-        let allocation = model.allocate_with_mode(
-            sections,
-            &ctx.execution_ctx.lens_registry,
-            ctx.execution_ctx.mode,
-        )?;
+        // The canonical test view has no tools, so only the output is reserved.
+        let reservation = LayoutReservation {
+            tools: 0,
+            output: context_int_from_doc(doc, "x_fct_output_reserve").unwrap_or(0),
+        };
+        let model = TokenBoxModel::new(effective_budget).with_reservation(reservation);
+        let started = Instant::now();
+        let allocation = match ctx.execution_ctx.trace.as_mut() {
+            Some(trace) => model.allocate_traced(
+                sections,
+                &ctx.execution_ctx.lens_registry,
+                ctx.execution_ctx.mode,
+                trace,
+            )?,
+            None => model.allocate_with_mode(
+                sections,
+                &ctx.execution_ctx.lens_registry,
+                ctx.execution_ctx.mode,
+            )?,
+        };
+        if let Some(trace) = ctx.execution_ctx.trace.as_mut() {
+            trace.record(
+                "layout",
+                "phase",
+                started,
+                json!({ "budget": effective_budget, "total": allocation.total_size }),
+            );
+        }
 
         // Update token telemetry
         ctx.telemetry.tokens_used = allocation.total_size;
@@ -394,7 +485,7 @@ impl TestRunner {
             return Ok(());
         }
 
-        let policy = collect_effective_policy(doc);
+        let policy = EffectivePolicy::from_document(doc);
         let effect_by_tool = collect_interface_effects(doc);
         let mode = match ctx.execution_ctx.mode {
             crate::ExecutionMode::Pure => "pure",
@@ -404,6 +495,9 @@ impl TestRunner {
 
         for (target, return_value) in &ctx.mock_registry.interface_mocks {
             let mut executor = ToolExecutor::new();
+            if let Some(host_context) = &ctx.execution_ctx.host_context {
+                executor = executor.with_host_context(host_context.clone());
+            }
             executor.register_tool(ToolDefinition {
                 name: target.clone(),
                 description: "test mock tool".to_string(),
@@ -899,6 +993,7 @@ struct SectionLayout {
     grow: f64,
     shrink: f64,
     strategy: Option<PipelineNode>,
+    packing: Option<ItemPacking>,
 }
 
 fn doc_to_sections(
@@ -951,6 +1046,9 @@ fn doc_to_sections(
         if let Some(strategy) = layout.strategy {
             section = section.with_strategy(strategy);
         }
+        if let Some(packing) = layout.packing {
+            section = section.with_packing(packing);
+        }
         sections.push(section);
     }
 
@@ -958,6 +1056,10 @@ fn doc_to_sections(
 }
 
 fn effective_layout_budget_from_doc(doc: &FacetDocument, fallback_budget: usize) -> usize {
+    context_int_from_doc(doc, "budget").unwrap_or(fallback_budget)
+}
+
+fn context_int_from_doc(doc: &FacetDocument, key: &str) -> Option<usize> {
     for block in &doc.blocks {
         let FacetNode::Context(ctx) = block else {
             continue;
@@ -966,17 +1068,17 @@ fn effective_layout_budget_from_doc(doc: &FacetDocument, fallback_budget: usize)
             let BodyNode::KeyValue(kv) = body else {
                 continue;
             };
-            if kv.key != "budget" {
+            if kv.key != key {
                 continue;
             }
             if let ValueNode::Scalar(ScalarValue::Int(v)) = &kv.value {
                 if *v >= 0 {
-                    return *v as usize;
+                    return Some(*v as usize);
                 }
             }
         }
     }
-    fallback_budget
+    None
 }
 
 fn context_layout_defaults_from_doc(doc: &FacetDocument) -> LayoutDefaults {
@@ -1031,6 +1133,7 @@ fn resolve_section_layout(
         grow: defaults.grow,
         shrink: defaults.shrink,
         strategy: None,
+        packing: None,
     };
 
     for body in &block.body {
@@ -1068,6 +1171,7 @@ fn resolve_section_layout(
                     layout.strategy = Some(p.clone());
                 }
             }
+            PACK_KEY => layout.packing = ItemPacking::from_value(&kv.value),
             _ => {}
         }
     }
@@ -1483,94 +1587,6 @@ fn json_value_to_display(value: &serde_json::Value) -> String {
     }
 }
 
-fn collect_effective_policy(document: &FacetDocument) -> Option<OrderedMap<String, ValueNode>> {
-    let mut effective: OrderedMap<String, ValueNode> = OrderedMap::new();
-    let mut seen_policy = false;
-
-    for node in &document.blocks {
-        if let FacetNode::Policy(policy_block) = node {
-            seen_policy = true;
-            for body in &policy_block.body {
-                if let BodyNode::KeyValue(kv) = body {
-                    if let Some(existing) = effective.get_mut(&kv.key) {
-                        *existing = merge_policy_value(&kv.key, existing.clone(), kv.value.clone());
-                    } else {
-                        effective.insert(kv.key.clone(), kv.value.clone());
-                    }
-                }
-            }
-        }
-    }
-
-    seen_policy.then_some(effective)
-}
-
-fn merge_policy_value(key: &str, old: ValueNode, new: ValueNode) -> ValueNode {
-    match (old, new) {
-        (ValueNode::Map(old_map), ValueNode::Map(new_map)) => {
-            ValueNode::Map(merge_policy_maps(old_map, new_map))
-        }
-        (ValueNode::List(old_list), ValueNode::List(new_list))
-            if key == "allow" || key == "deny" =>
-        {
-            ValueNode::List(merge_policy_lists(old_list, new_list))
-        }
-        (_, replacement) => replacement,
-    }
-}
-
-fn merge_policy_maps(
-    mut old_map: OrderedMap<String, ValueNode>,
-    new_map: OrderedMap<String, ValueNode>,
-) -> OrderedMap<String, ValueNode> {
-    for (key, new_value) in new_map {
-        if let Some(old_value) = old_map.get_mut(&key) {
-            *old_value = merge_policy_value(&key, old_value.clone(), new_value);
-        } else {
-            old_map.insert(key, new_value);
-        }
-    }
-    old_map
-}
-
-fn merge_policy_lists(old_items: Vec<ValueNode>, new_items: Vec<ValueNode>) -> Vec<ValueNode> {
-    let mut merged = Vec::new();
-    let mut id_index: OrderedMap<String, usize> = OrderedMap::new();
-
-    for item in old_items {
-        if let Some(id) = policy_rule_id(&item) {
-            id_index.insert(id.to_string(), merged.len());
-        }
-        merged.push(item);
-    }
-
-    for item in new_items {
-        if let Some(id) = policy_rule_id(&item) {
-            if let Some(pos) = id_index.get(id).copied() {
-                let old_item = merged[pos].clone();
-                merged[pos] = merge_policy_value("", old_item, item);
-            } else {
-                id_index.insert(id.to_string(), merged.len());
-                merged.push(item);
-            }
-        } else {
-            merged.push(item);
-        }
-    }
-
-    merged
-}
-
-fn policy_rule_id(item: &ValueNode) -> Option<&str> {
-    match item {
-        ValueNode::Map(map) => match map.get("id") {
-            Some(ValueNode::String(id)) => Some(id.as_str()),
-            _ => None,
-        },
-        _ => None,
-    }
-}
-
 fn collect_interface_effects(document: &FacetDocument) -> HashMap<String, String> {
     let mut out = HashMap::new();
     for node in &document.blocks {
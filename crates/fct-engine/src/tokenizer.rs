@@ -3,9 +3,8 @@
 // ============================================================================
 
 use crate::errors::EngineResult;
-use fct_ast::{ScalarValue, ValueNode};
+use fct_ast::ValueNode;
 use once_cell::sync::Lazy;
-use unicode_normalization::UnicodeNormalization;
 
 /// Tokenizer for production token counting
 /// Uses simple approximation that works well for most text
@@ -93,35 +92,13 @@ impl Tokenizer {
     /// Count FACET Units for a string:
     /// byte_length(UTF-8(NFC+LF normalized s))
     pub fn count_facet_units(&self, text: &str) -> usize {
-        normalize_for_facet_units(text).len()
+        fct_std::count_facet_units(text)
     }
 
     /// Count FACET Units in a ValueNode recursively.
     /// For non-string values this uses their deterministic textual form.
     pub fn count_facet_units_in_value(&self, value: &ValueNode) -> usize {
-        match value {
-            ValueNode::String(s) => self.count_facet_units(s),
-            ValueNode::Scalar(scalar) => match scalar {
-                ScalarValue::Int(i) => self.count_facet_units(&i.to_string()),
-                ScalarValue::Float(f) => self.count_facet_units(&f.to_string()),
-                ScalarValue::Bool(true) => self.count_facet_units("true"),
-                ScalarValue::Bool(false) => self.count_facet_units("false"),
-                ScalarValue::Null => self.count_facet_units("null"),
-            },
-            ValueNode::Variable(var) => self.count_facet_units(var),
-            ValueNode::List(items) => items
-                .iter()
-                .map(|item| self.count_facet_units_in_value(item))
-                .sum(),
-            ValueNode::Map(map) => map
-                .iter()
-                .map(|(key, val)| {
-                    self.count_facet_units(key) + self.count_facet_units_in_value(val)
-                })
-                .sum(),
-            ValueNode::Pipeline(_) => self.count_facet_units("<pipeline>"),
-            ValueNode::Directive(_) => self.count_facet_units("<directive>"),
-        }
+        fct_std::count_facet_units_in_value(value)
     }
 
     /// Encode text to token IDs (simplified for production)
@@ -185,12 +162,6 @@ pub fn count_tokens_in_value(value: &ValueNode) -> usize {
     get_global_tokenizer().count_tokens_in_value(value)
 }
 
-/// Normalize string to NFC and LF line endings for FACET Units.
-pub fn normalize_for_facet_units(input: &str) -> String {
-    let nfc: String = input.nfc().collect();
-    nfc.replace("\r\n", "\n").replace('\r', "\n")
-}
-
 /// Convenience function to count FACET Units in text.
 pub fn count_facet_units(text: &str) -> usize {
     get_global_tokenizer().count_facet_units(text)
@@ -6,7 +6,11 @@
 
 use crate::errors::{EngineError, EngineResult};
 use crate::r_dag::ExecutionGuardDecision;
-use fct_ast::{OrderedMap, ScalarValue, ValueNode, FACET_VERSION};
+use fct_ast::{ValueNode, FACET_VERSION};
+use fct_policy::{
+    evaluate_guard, fold_input_hash, policy_scope, CallQuotas, EffectivePolicy, HostContext,
+    OpDesc, PolicyOp, GUARD_UNDECIDABLE, QUOTA_EXHAUSTED,
+};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
@@ -55,13 +59,6 @@ pub struct ToolResult {
 /// Tool execution handler function type
 pub type ToolHandler = Box<dyn Fn(&ToolInvocation) -> EngineResult<ValueNode> + Send + Sync>;
 
-#[derive(Debug, Clone)]
-struct ToolPolicyDecision {
-    allowed: bool,
-    policy_rule_id: Option<String>,
-    error_code: Option<String>,
-}
-
 // ============================================================================
 // TOOL EXECUTOR
 // ============================================================================
@@ -73,6 +70,12 @@ pub struct ToolExecutor {
     tools: HashMap<String, ToolDefinition>,
     /// Tool handlers (runtime implementations)
     handlers: HashMap<String, ToolHandler>,
+    /// Host context for guard decisions (`$x.<host>...` in policy conditions)
+    host_context: Option<HostContext>,
+    /// `x_fct_max_calls` counters for this executor's run or session
+    quotas: CallQuotas,
+    /// Guard decisions of `execute_with_guard`, in call order
+    guard_decisions: Vec<ExecutionGuardDecision>,
 }
 
 impl ToolExecutor {
@@ -81,9 +84,19 @@ impl ToolExecutor {
         Self {
             tools: HashMap::new(),
             handlers: HashMap::new(),
+            host_context: None,
+            quotas: CallQuotas::new(),
+            guard_decisions: Vec::new(),
         }
     }
 
+    /// Guard tool calls with a host context: policy conditions can read it and
+    /// decision `input_hash`es fold in its hash.
+    pub fn with_host_context(mut self, host_context: HostContext) -> Self {
+        self.host_context = Some(host_context);
+        self
+    }
+
     /// Register a new tool with its definition
     pub fn register_tool(&mut self, tool: ToolDefinition) -> EngineResult<()> {
         if self.tools.contains_key(&tool.name) {
@@ -145,18 +158,26 @@ impl ToolExecutor {
     pub fn evaluate_tool_call_guard(
         &self,
         invocation: &ToolInvocation,
-        policy: Option<&OrderedMap<String, ValueNode>>,
+        policy: Option<&EffectivePolicy>,
         computed_vars: Option<&HashMap<String, ValueNode>>,
         mode: &str,
         host_profile_id: &str,
         effect_class: Option<&str>,
     ) -> EngineResult<ExecutionGuardDecision> {
-        let decision =
-            evaluate_tool_call(policy, &invocation.tool_name, effect_class, computed_vars);
-        let input_hash = tool_call_input_hash(
-            &invocation.tool_name,
-            &invocation.arguments,
-            host_profile_id,
+        let op = OpDesc::new(
+            PolicyOp::ToolCall,
+            invocation.tool_name.clone(),
+            effect_class,
+        );
+        let scope = policy_scope(computed_vars, self.host_context.as_ref());
+        let decision = evaluate_guard(policy, &op, scope.as_deref());
+        let input_hash = fold_input_hash(
+            tool_call_input_hash(
+                &invocation.tool_name,
+                &invocation.arguments,
+                host_profile_id,
+            )?,
+            self.host_context.as_ref(),
         )?;
 
         Ok(ExecutionGuardDecision {
@@ -165,28 +186,66 @@ impl ToolExecutor {
             name: invocation.tool_name.clone(),
             effect_class: effect_class.map(|s| s.to_string()),
             mode: mode.to_string(),
-            decision: if decision.allowed {
-                "allowed".to_string()
-            } else {
-                "denied".to_string()
-            },
+            decision: decision.decision().to_string(),
             policy_rule_id: decision.policy_rule_id,
             input_hash,
             error_code: decision.error_code,
         })
     }
 
+    /// Guard a tool call and charge it against the allowing rule's
+    /// `x_fct_max_calls` quota. Once the quota is used up the call is denied with
+    /// `X.fct.QUOTA_EXHAUSTED`, attributed to that rule.
+    pub fn guard_tool_call(
+        &mut self,
+        invocation: &ToolInvocation,
+        policy: Option<&EffectivePolicy>,
+        computed_vars: Option<&HashMap<String, ValueNode>>,
+        mode: &str,
+        host_profile_id: &str,
+        effect_class: Option<&str>,
+    ) -> EngineResult<ExecutionGuardDecision> {
+        let mut decision = self.evaluate_tool_call_guard(
+            invocation,
+            policy,
+            computed_vars,
+            mode,
+            host_profile_id,
+            effect_class,
+        )?;
+
+        if decision.decision == "allowed"
+            && !self
+                .quotas
+                .charge(policy, decision.policy_rule_id.as_deref())
+        {
+            decision.decision = "denied".to_string();
+            decision.error_code = Some(QUOTA_EXHAUSTED.to_string());
+        }
+        Ok(decision)
+    }
+
+    /// Guard decisions recorded by `execute_with_guard`, with `seq` from 1.
+    pub fn guard_decisions(&self) -> &[ExecutionGuardDecision] {
+        &self.guard_decisions
+    }
+
+    /// Call counters charged so far.
+    pub fn call_quotas(&self) -> &CallQuotas {
+        &self.quotas
+    }
+
     /// Execute a tool invocation with fail-closed guard semantics.
     pub fn execute_with_guard(
-        &self,
+        &mut self,
         invocation: ToolInvocation,
-        policy: Option<&OrderedMap<String, ValueNode>>,
+        policy: Option<&EffectivePolicy>,
         computed_vars: Option<&HashMap<String, ValueNode>>,
         mode: &str,
         host_profile_id: &str,
         effect_class: Option<&str>,
     ) -> EngineResult<(ToolResult, ExecutionGuardDecision)> {
-        let decision = self.evaluate_tool_call_guard(
+        let mut decision = self.guard_tool_call(
             &invocation,
             policy,
             computed_vars,
@@ -194,11 +253,22 @@ impl ToolExecutor {
             host_profile_id,
             effect_class,
         )?;
+        decision.seq = self.guard_decisions.len() + 1;
+        self.guard_decisions.push(decision.clone());
 
-        if decision.error_code.as_deref() == Some("F455") {
-            return Err(EngineError::GuardUndecidable {
-                name: invocation.tool_name.clone(),
-            });
+        match decision.error_code.as_deref() {
+            Some(GUARD_UNDECIDABLE) => {
+                return Err(EngineError::GuardUndecidable {
+                    name: invocation.tool_name.clone(),
+                })
+            }
+            Some(QUOTA_EXHAUSTED) => {
+                return Err(EngineError::QuotaExhausted {
+                    name: invocation.tool_name.clone(),
+                    policy_rule_id: decision.policy_rule_id.clone().unwrap_or_default(),
+                })
+            }
+            _ => {}
         }
         if decision.decision == "denied" {
             return Err(EngineError::PolicyDenied {
@@ -264,295 +334,12 @@ impl Default for ToolExecutor {
     }
 }
 
-fn evaluate_tool_call(
-    policy: Option<&OrderedMap<String, ValueNode>>,
-    tool_name: &str,
-    effect_class: Option<&str>,
-    computed_vars: Option<&HashMap<String, ValueNode>>,
-) -> ToolPolicyDecision {
-    let Some(policy_map) = policy else {
-        return ToolPolicyDecision {
-            allowed: false,
-            policy_rule_id: None,
-            error_code: Some("F454".to_string()),
-        };
-    };
-
-    if let Some(deny_rules) = policy_map.get("deny").and_then(as_rule_list) {
-        for rule in deny_rules {
-            match rule_matches_tool_call(rule, tool_name, effect_class, computed_vars) {
-                RuleMatch::Matched(rule_id) => {
-                    return ToolPolicyDecision {
-                        allowed: false,
-                        policy_rule_id: rule_id,
-                        error_code: Some("F454".to_string()),
-                    }
-                }
-                RuleMatch::Undecidable(rule_id) => {
-                    return ToolPolicyDecision {
-                        allowed: false,
-                        policy_rule_id: rule_id,
-                        error_code: Some("F455".to_string()),
-                    }
-                }
-                RuleMatch::NoMatch => {}
-            }
-        }
-    }
-
-    if let Some(allow_rules) = policy_map.get("allow").and_then(as_rule_list) {
-        for rule in allow_rules {
-            match rule_matches_tool_call(rule, tool_name, effect_class, computed_vars) {
-                RuleMatch::Matched(rule_id) => {
-                    return ToolPolicyDecision {
-                        allowed: true,
-                        policy_rule_id: rule_id,
-                        error_code: None,
-                    }
-                }
-                RuleMatch::Undecidable(rule_id) => {
-                    return ToolPolicyDecision {
-                        allowed: false,
-                        policy_rule_id: rule_id,
-                        error_code: Some("F455".to_string()),
-                    }
-                }
-                RuleMatch::NoMatch => {}
-            }
-        }
-    }
-
-    default_guard_decision(policy_map, "tool_call", false)
-}
-
-fn as_rule_list(value: &ValueNode) -> Option<&Vec<ValueNode>> {
-    match value {
-        ValueNode::List(items) => Some(items),
-        _ => None,
-    }
-}
-
-enum RuleMatch {
-    Matched(Option<String>),
-    Undecidable(Option<String>),
-    NoMatch,
-}
-
-fn rule_matches_tool_call(
-    rule: &ValueNode,
-    tool_name: &str,
-    effect_class: Option<&str>,
-    computed_vars: Option<&HashMap<String, ValueNode>>,
-) -> RuleMatch {
-    let ValueNode::Map(map) = rule else {
-        return RuleMatch::NoMatch;
-    };
-
-    let Some(ValueNode::String(op)) = map.get("op") else {
-        return RuleMatch::NoMatch;
-    };
-    if op != "tool_call" {
-        return RuleMatch::NoMatch;
-    }
-    let rule_id = match map.get("id") {
-        Some(ValueNode::String(id)) => Some(id.clone()),
-        _ => None,
-    };
-
-    let name_match = match map.get("name") {
-        Some(ValueNode::String(pattern)) => matcher_matches(pattern, tool_name),
-        Some(_) => return RuleMatch::Undecidable(rule_id),
-        None => true,
-    };
-    if !name_match {
-        return RuleMatch::NoMatch;
-    }
-
-    if let Some(effect_matcher) = map.get("effect") {
-        match effect_matcher {
-            ValueNode::String(pattern) => {
-                let Some(op_effect) = effect_class else {
-                    return RuleMatch::NoMatch;
-                };
-                if !matcher_matches(pattern, op_effect) {
-                    return RuleMatch::NoMatch;
-                }
-            }
-            _ => return RuleMatch::Undecidable(rule_id),
-        }
-    }
-
-    let when_eval = match map.get("when") {
-        None => true,
-        Some(cond) => match eval_policy_cond(cond, computed_vars) {
-            Ok(v) => v,
-            Err(()) => return RuleMatch::Undecidable(rule_id),
-        },
-    };
-    if !when_eval {
-        return RuleMatch::NoMatch;
-    }
-
-    let unless_eval = match map.get("unless") {
-        None => false,
-        Some(cond) => match eval_policy_cond(cond, computed_vars) {
-            Ok(v) => v,
-            Err(()) => return RuleMatch::Undecidable(rule_id),
-        },
-    };
-    if unless_eval {
-        return RuleMatch::NoMatch;
-    }
-
-    RuleMatch::Matched(rule_id)
-}
-
-fn eval_policy_cond(
-    cond: &ValueNode,
-    computed_vars: Option<&HashMap<String, ValueNode>>,
-) -> Result<bool, ()> {
-    match cond {
-        ValueNode::Scalar(ScalarValue::Bool(v)) => Ok(*v),
-        ValueNode::Variable(var_ref) => {
-            let vars = computed_vars.ok_or(())?;
-            let value = resolve_policy_var(var_ref, vars)?;
-            match value {
-                ValueNode::Scalar(ScalarValue::Bool(v)) => Ok(*v),
-                _ => Err(()),
-            }
-        }
-        ValueNode::Map(map) => {
-            if map.len() != 1 {
-                return Err(());
-            }
-
-            let (op, arg) = map.iter().next().ok_or(())?;
-            match op.as_str() {
-                "not" => Ok(!eval_policy_cond(arg, computed_vars)?),
-                "all" => {
-                    let items = match arg {
-                        ValueNode::List(items) if !items.is_empty() => items,
-                        _ => return Err(()),
-                    };
-                    for item in items {
-                        if !eval_policy_cond(item, computed_vars)? {
-                            return Ok(false);
-                        }
-                    }
-                    Ok(true)
-                }
-                "any" => {
-                    let items = match arg {
-                        ValueNode::List(items) if !items.is_empty() => items,
-                        _ => return Err(()),
-                    };
-                    for item in items {
-                        if eval_policy_cond(item, computed_vars)? {
-                            return Ok(true);
-                        }
-                    }
-                    Ok(false)
-                }
-                _ => Err(()),
-            }
-        }
-        _ => Err(()),
-    }
-}
-
-fn resolve_policy_var<'a>(
-    var_ref: &str,
-    vars: &'a HashMap<String, ValueNode>,
-) -> Result<&'a ValueNode, ()> {
-    let mut parts = var_ref.split('.');
-    let base = parts.next().ok_or(())?;
-    let mut current = vars.get(base).ok_or(())?;
-
-    for seg in parts {
-        if seg.chars().all(|c| c.is_ascii_digit()) {
-            return Err(());
-        }
-        current = match current {
-            ValueNode::Map(map) => map.get(seg).ok_or(())?,
-            _ => return Err(()),
-        };
-    }
-
-    Ok(current)
-}
-
-fn default_guard_decision(
-    policy_map: &OrderedMap<String, ValueNode>,
-    op: &str,
-    fallback_allow: bool,
-) -> ToolPolicyDecision {
-    if let Some(defaults_node) = policy_map.get("defaults") {
-        let defaults_map = match defaults_node {
-            ValueNode::Map(map) => map,
-            _ => {
-                return ToolPolicyDecision {
-                    allowed: false,
-                    policy_rule_id: None,
-                    error_code: Some("F455".to_string()),
-                };
-            }
-        };
-
-        if let Some(op_default) = defaults_map.get(op) {
-            return match op_default {
-                ValueNode::String(s) if s == "allow" => ToolPolicyDecision {
-                    allowed: true,
-                    policy_rule_id: None,
-                    error_code: None,
-                },
-                ValueNode::String(s) if s == "deny" => ToolPolicyDecision {
-                    allowed: false,
-                    policy_rule_id: None,
-                    error_code: Some("F454".to_string()),
-                },
-                ValueNode::Scalar(ScalarValue::Bool(true)) => ToolPolicyDecision {
-                    allowed: true,
-                    policy_rule_id: None,
-                    error_code: None,
-                },
-                ValueNode::Scalar(ScalarValue::Bool(false)) => ToolPolicyDecision {
-                    allowed: false,
-                    policy_rule_id: None,
-                    error_code: Some("F454".to_string()),
-                },
-                _ => ToolPolicyDecision {
-                    allowed: false,
-                    policy_rule_id: None,
-                    error_code: Some("F455".to_string()),
-                },
-            };
-        }
-    }
-
-    ToolPolicyDecision {
-        allowed: fallback_allow,
-        policy_rule_id: None,
-        error_code: if fallback_allow {
-            None
-        } else {
-            Some("F454".to_string())
-        },
-    }
-}
-
-fn matcher_matches(pattern: &str, value: &str) -> bool {
-    if let Some(prefix) = pattern.strip_suffix(".*") {
-        value.starts_with(prefix)
-    } else {
-        pattern == value
-    }
-}
-
 fn canonicalize_json(value: &serde_json::Value) -> EngineResult<String> {
     Ok(serde_json_canonicalizer::to_string(value)?)
 }
 
-fn tool_call_input_hash(
+/// `input_hash` of a `tool_call` guard decision for `Interface.fn` and its arguments.
+pub fn tool_call_input_hash(
     tool_name: &str,
     args: &HashMap<String, ValueNode>,
     host_profile_id: &str,
@@ -827,7 +614,10 @@ mod tests {
             ),
             ("effect".to_string(), ValueNode::String("read".to_string())),
         ]));
-        let policy = OrderedMap::from([("allow".to_string(), ValueNode::List(vec![allow_rule]))]);
+        let policy = EffectivePolicy::from(OrderedMap::from([(
+            "allow".to_string(),
+            ValueNode::List(vec![allow_rule]),
+        )]));
 
         let invocation = ToolInvocation {
             tool_name: "WeatherAPI.get_current".to_string(),
@@ -890,7 +680,10 @@ mod tests {
             ),
             ("effect".to_string(), ValueNode::String("read".to_string())),
         ]));
-        let policy = OrderedMap::from([("allow".to_string(), ValueNode::List(vec![allow_rule]))]);
+        let policy = EffectivePolicy::from(OrderedMap::from([(
+            "allow".to_string(),
+            ValueNode::List(vec![allow_rule]),
+        )]));
 
         let invocation = ToolInvocation {
             tool_name: "WeatherAPI.get_current".to_string(),
@@ -946,7 +739,10 @@ mod tests {
                 )])),
             ),
         ]));
-        let policy = OrderedMap::from([("allow".to_string(), ValueNode::List(vec![allow_rule]))]);
+        let policy = EffectivePolicy::from(OrderedMap::from([(
+            "allow".to_string(),
+            ValueNode::List(vec![allow_rule]),
+        )]));
 
         let invocation = ToolInvocation {
             tool_name: "WeatherAPI.get_current".to_string(),
@@ -996,7 +792,10 @@ mod tests {
                 ValueNode::Variable("missing.flag".to_string()),
             ),
         ]));
-        let policy = OrderedMap::from([("allow".to_string(), ValueNode::List(vec![allow_rule]))]);
+        let policy = EffectivePolicy::from(OrderedMap::from([(
+            "allow".to_string(),
+            ValueNode::List(vec![allow_rule]),
+        )]));
 
         let invocation = ToolInvocation {
             tool_name: "WeatherAPI.get_current".to_string(),
@@ -1092,7 +891,10 @@ mod tests {
                 ValueNode::Variable("missing.flag".to_string()),
             ),
         ]));
-        let policy = OrderedMap::from([("allow".to_string(), ValueNode::List(vec![allow_rule]))]);
+        let policy = EffectivePolicy::from(OrderedMap::from([(
+            "allow".to_string(),
+            ValueNode::List(vec![allow_rule]),
+        )]));
 
         let invocation = ToolInvocation {
             tool_name: "WeatherAPI.get_current".to_string(),
@@ -1114,4 +916,90 @@ mod tests {
         assert!(matches!(err, EngineError::GuardUndecidable { .. }));
         assert_eq!(handler_calls.load(Ordering::SeqCst), 0);
     }
+
+    #[test]
+    fn test_tool_call_quota_denies_once_exhausted() {
+        let mut executor = ToolExecutor::new();
+        executor
+            .register_tool(ToolDefinition {
+                name: "PaymentAPI.refund".to_string(),
+                description: "refund".to_string(),
+                input_schema: serde_json::json!({}),
+                output_schema: None,
+            })
+            .unwrap();
+        let handler_calls = Arc::new(AtomicUsize::new(0));
+        let handler_calls_clone = Arc::clone(&handler_calls);
+        executor
+            .register_handler("PaymentAPI.refund".to_string(), move |_| {
+                handler_calls_clone.fetch_add(1, Ordering::SeqCst);
+                Ok(ValueNode::String("ok".to_string()))
+            })
+            .unwrap();
+
+        let allow_rule = ValueNode::Map(OrderedMap::from([
+            ("id".to_string(), ValueNode::String("refunds".to_string())),
+            ("op".to_string(), ValueNode::String("tool_call".to_string())),
+            (
+                "name".to_string(),
+                ValueNode::String("PaymentAPI.refund".to_string()),
+            ),
+            (
+                "x_fct_max_calls".to_string(),
+                ValueNode::Scalar(ScalarValue::Int(2)),
+            ),
+        ]));
+        let policy = EffectivePolicy::from(OrderedMap::from([(
+            "allow".to_string(),
+            ValueNode::List(vec![allow_rule]),
+        )]));
+        let invocation = ToolInvocation {
+            tool_name: "PaymentAPI.refund".to_string(),
+            arguments: HashMap::new(),
+            invocation_id: None,
+        };
+
+        for _ in 0..2 {
+            executor
+                .execute_with_guard(
+                    invocation.clone(),
+                    Some(&policy),
+                    None,
+                    "exec",
+                    "local.default.v1",
+                    Some("payment"),
+                )
+                .unwrap();
+        }
+        let err = executor
+            .execute_with_guard(
+                invocation,
+                Some(&policy),
+                None,
+                "exec",
+                "local.default.v1",
+                Some("payment"),
+            )
+            .unwrap_err();
+
+        assert!(matches!(err, EngineError::QuotaExhausted { .. }));
+        assert!(err.to_string().starts_with(QUOTA_EXHAUSTED));
+        assert_eq!(handler_calls.load(Ordering::SeqCst), 2);
+        assert_eq!(executor.call_quotas().used("refunds"), 2);
+
+        let decisions = executor.guard_decisions();
+        let summary: Vec<_> = decisions
+            .iter()
+            .map(|d| (d.seq, d.decision.as_str(), d.error_code.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, "allowed", None),
+                (2, "allowed", None),
+                (3, "denied", Some(QUOTA_EXHAUSTED)),
+            ]
+        );
+        assert_eq!(decisions[2].policy_rule_id.as_deref(), Some("refunds"));
+    }
 }
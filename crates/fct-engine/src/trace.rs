@@ -0,0 +1,147 @@
+// ============================================================================
+// EXECUTION TRACE
+// ============================================================================
+
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+/// A timed piece of work on one lane of the trace timeline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceSpan {
+    pub name: String,
+    /// `phase`, `test`, `rdag`, `lens` or `layout`.
+    pub category: &'static str,
+    /// Offset from the trace origin.
+    pub start: Duration,
+    pub duration: Duration,
+    /// Timeline row: 0 for the calling thread, one per parallel R-DAG worker.
+    pub lane: usize,
+    /// Span details (gas, cache hit/miss, layout step, ...).
+    pub args: Value,
+}
+
+impl TraceSpan {
+    /// Span from `started` until now on `lane`, offset from `origin`.
+    pub fn since(
+        origin: Instant,
+        started: Instant,
+        name: impl Into<String>,
+        category: &'static str,
+        lane: usize,
+        args: Value,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            category,
+            start: started.saturating_duration_since(origin),
+            duration: started.elapsed(),
+            lane,
+            args,
+        }
+    }
+}
+
+/// Timeline of one compiler run, exported in the Chrome trace event format
+/// that Perfetto and `chrome://tracing` load.
+#[derive(Debug, Clone)]
+pub struct ExecutionTrace {
+    origin: Instant,
+    spans: Vec<TraceSpan>,
+}
+
+impl ExecutionTrace {
+    pub fn new() -> Self {
+        Self::with_origin(Instant::now())
+    }
+
+    /// Empty trace whose timestamps are offsets from `origin`.
+    pub fn with_origin(origin: Instant) -> Self {
+        Self {
+            origin,
+            spans: Vec::new(),
+        }
+    }
+
+    pub fn origin(&self) -> Instant {
+        self.origin
+    }
+
+    pub fn spans(&self) -> &[TraceSpan] {
+        &self.spans
+    }
+
+    pub fn into_spans(self) -> Vec<TraceSpan> {
+        self.spans
+    }
+
+    /// Record a span on lane 0 from `started` until now.
+    pub fn record(
+        &mut self,
+        name: impl Into<String>,
+        category: &'static str,
+        started: Instant,
+        args: Value,
+    ) {
+        let span = TraceSpan::since(self.origin, started, name, category, 0, args);
+        self.spans.push(span);
+    }
+
+    pub fn push(&mut self, span: TraceSpan) {
+        self.spans.push(span);
+    }
+
+    /// Spans of another trace taken against the same origin.
+    pub fn extend(&mut self, spans: impl IntoIterator<Item = TraceSpan>) {
+        self.spans.extend(spans);
+    }
+
+    /// `{"traceEvents": [...]}` with one complete (`"ph": "X"`) event per span,
+    /// timestamps in microseconds, and a name for every lane in use.
+    pub fn to_chrome_json(&self) -> Value {
+        let mut lanes: Vec<usize> = self.spans.iter().map(|span| span.lane).collect();
+        lanes.sort_unstable();
+        lanes.dedup();
+
+        let names = lanes.into_iter().map(|lane| {
+            let name = if lane == 0 {
+                "main".to_string()
+            } else {
+                format!("rdag worker {}", lane)
+            };
+            json!({
+                "name": "thread_name",
+                "ph": "M",
+                "pid": 1,
+                "tid": lane,
+                "args": { "name": name },
+            })
+        });
+        let events = self.spans.iter().map(|span| {
+            json!({
+                "name": span.name,
+                "cat": span.category,
+                "ph": "X",
+                "ts": micros(span.start),
+                "dur": micros(span.duration),
+                "pid": 1,
+                "tid": span.lane,
+                "args": if span.args.is_null() { json!({}) } else { span.args.clone() },
+            })
+        });
+
+        json!({
+            "traceEvents": names.chain(events).collect::<Vec<_>>(),
+            "displayTimeUnit": "ms",
+        })
+    }
+}
+
+impl Default for ExecutionTrace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn micros(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1_000_000.0
+}
@@ -3,4 +3,7 @@ pub mod parser;
 #[cfg(test)]
 pub mod test_parser;
 
-pub use parser::{compute_document_hash, normalize_source, parse_document, parse_document_bytes};
+pub use parser::{
+    compute_document_hash, list_item_spans, normalize_source, parse_document, parse_document_bytes,
+    parse_type_expression, parse_value_expression,
+};
@@ -2,7 +2,7 @@ use crate::error::{ParseResult, SpanInput};
 use fct_ast::{
     BodyNode, DirectiveNode, FacetBlock, FacetDocument, FacetNode, FunctionSignature, KeyValueNode,
     LensCallNode, ListItemNode, MapKeyKind, OrderedMap, Parameter, PipelineNode, ScalarValue, Span,
-    TypeNode, ValueNode,
+    TypeAliasNode, TypeNode, TypesNode, ValueNode,
 };
 use nom::{
     branch::alt,
@@ -26,6 +26,14 @@ fn to_span(input: SpanInput) -> Span {
     }
 }
 
+/// Span from `start` up to where `rest` begins.
+fn span_between(start: SpanInput, rest: SpanInput) -> Span {
+    Span {
+        end: rest.location_offset(),
+        ..to_span(start)
+    }
+}
+
 fn comment(input: SpanInput) -> ParseResult<SpanInput> {
     recognize(pair(char('#'), is_not("\n\r")))(input)
 }
@@ -182,6 +190,13 @@ fn type_node(input: SpanInput) -> ParseResult<TypeNode> {
     parse_union_type(input)
 }
 
+/// Parse a standalone type expression such as the `type` argument of `@input(...)`.
+pub fn parse_type_expression(input: &str) -> Result<TypeNode, String> {
+    all_consuming(delimited(multispace0, type_node, multispace0))(SpanInput::new(input))
+        .map(|(_, type_node)| type_node)
+        .map_err(|_| format!("Invalid type expression: '{}'", input))
+}
+
 fn parse_union_type(input: SpanInput) -> ParseResult<TypeNode> {
     let (mut input, first) = parse_primary_type(input)?;
     let mut members = vec![first];
@@ -194,7 +209,8 @@ fn parse_union_type(input: SpanInput) -> ParseResult<TypeNode> {
             members.push(next);
             input = after_ty;
         } else {
-            input = after_ws;
+            // Leave trailing whitespace (including newlines) to the caller so
+            // line-oriented contexts like `@types` entries can still match `eol`.
             break;
         }
     }
@@ -477,9 +493,12 @@ fn lens_args(input: SpanInput) -> ParseResult<(Vec<ValueNode>, OrderedMap<String
     Ok((input, (positional, named)))
 }
 
-// Simple value parser (no pipeline support to avoid recursion issues)
+// Simple value parser (no pipeline support to avoid recursion issues);
+// bracketed literals are unambiguous, so `columns=["a", "b"]` is allowed.
 fn parse_value_simple(input: SpanInput) -> ParseResult<ValueNode> {
     alt((
+        map_literal,
+        list_literal,
         map(scalar_value, ValueNode::Scalar),
         map(string_literal, ValueNode::String),
         map(variable_ref, ValueNode::Variable),
@@ -507,6 +526,15 @@ fn lens_call(input: SpanInput) -> ParseResult<LensCallNode> {
 }
 
 fn list_literal(input: SpanInput) -> ParseResult<ValueNode> {
+    let (input, items) = list_items(input)?;
+    Ok((
+        input,
+        ValueNode::List(items.into_iter().map(|(_, item)| item).collect()),
+    ))
+}
+
+/// Bracketed list items, each paired with the span where it starts.
+fn list_items(input: SpanInput) -> ParseResult<Vec<(Span, ValueNode)>> {
     let (input, _) = char('[')(input)?;
     let (input, _) = multispace0(input)?;
     // Allow separation by comma or newline, with arbitrary whitespace
@@ -514,7 +542,11 @@ fn list_literal(input: SpanInput) -> ParseResult<ValueNode> {
         value((), delimited(multispace0, char(','), multispace0)),
         value((), line_ending),
     ));
-    let (input, items) = separated_list0(separator, preceded(multispace0, parse_value))(input)?;
+    let item = preceded(multispace0, |input| {
+        let (rest, value) = parse_value(input)?;
+        Ok((rest, (span_between(input, rest), value)))
+    });
+    let (input, items) = separated_list0(separator, item)(input)?;
     let (input, _) = multispace0(input)?;
     if input.fragment().starts_with(',') {
         return Err(nom::Err::Error(nom::error::VerboseError {
@@ -525,7 +557,7 @@ fn list_literal(input: SpanInput) -> ParseResult<ValueNode> {
         }));
     }
     let (input, _) = char(']')(input)?;
-    Ok((input, ValueNode::List(items)))
+    Ok((input, items))
 }
 
 fn map_literal(input: SpanInput) -> ParseResult<ValueNode> {
@@ -614,6 +646,13 @@ fn parse_value(input: SpanInput) -> ParseResult<ValueNode> {
     }
 }
 
+/// Parse a standalone value expression such as `$docs |> map("title") |> join(", ")`.
+pub fn parse_value_expression(input: &str) -> Result<ValueNode, String> {
+    all_consuming(delimited(multispace0, parse_value, multispace0))(SpanInput::new(input))
+        .map(|(_, value)| value)
+        .map_err(|_| format!("Invalid value expression: '{}'", input))
+}
+
 // --- Body Parsing ---
 
 fn map_key(input: SpanInput) -> ParseResult<(String, MapKeyKind)> {
@@ -624,6 +663,7 @@ fn map_key(input: SpanInput) -> ParseResult<(String, MapKeyKind)> {
 }
 
 fn key_value(input: SpanInput) -> ParseResult<KeyValueNode> {
+    let start = input;
     let (input, (key, key_kind)) = map_key(input)?;
     let (input, _) = space0(input)?;
     let (input, _) = char(':')(input)?;
@@ -636,11 +676,32 @@ fn key_value(input: SpanInput) -> ParseResult<KeyValueNode> {
             key,
             key_kind,
             value,
-            span: to_span(input), // Approx
+            span: span_between(start, input),
         },
     ))
 }
 
+/// Spans of the items in the inline `key: [...]` list whose entry starts at `entry`.
+///
+/// Value nodes carry no spans of their own, so diagnostics that need to point at a
+/// single list item re-read it from the source the document was parsed from.
+pub fn list_item_spans(source: &str, entry: &Span) -> Option<Vec<Span>> {
+    use nom::Slice;
+
+    let normalized = normalize_source(source);
+    if !normalized.is_char_boundary(entry.start) {
+        return None;
+    }
+    let input = SpanInput::new(normalized.as_str()).slice(entry.start..);
+    let entry_list = |input| -> ParseResult<Vec<(Span, ValueNode)>> {
+        let (input, _) = map_key(input)?;
+        let (input, _) = tuple((space0, char(':'), space0))(input)?;
+        list_items(input)
+    };
+    let (_, items) = entry_list(input).ok()?;
+    Some(items.into_iter().map(|(span, _)| span).collect())
+}
+
 fn list_item(input: SpanInput) -> ParseResult<ListItemNode> {
     let (input, _) = char('-')(input)?;
     let (input, _) = space1(input)?;
@@ -911,6 +972,34 @@ fn interface_body(level: usize) -> impl Fn(SpanInput) -> ParseResult<Vec<Functio
     move |input: SpanInput| many0(preceded(empty_lines, interface_fn(level)))(input)
 }
 
+fn type_alias(level: usize) -> impl Fn(SpanInput) -> ParseResult<TypeAliasNode> {
+    move |input: SpanInput| {
+        let alias_start = input;
+        let (input, _) = indentation(level)(input)?;
+        let (input, name) = identifier(input)?;
+        let (input, _) = space0(input)?;
+        let (input, _) = char(':')(input)?;
+        let (input, _) = space0(input)?;
+        let (input, type_node) = type_node(input)?;
+        let (input, _) = space0(input)?;
+        let (input, _) = opt(comment)(input)?;
+        let (input, _) = eol(input)?;
+
+        Ok((
+            input,
+            TypeAliasNode {
+                name,
+                type_node,
+                span: to_span(alias_start),
+            },
+        ))
+    }
+}
+
+fn types_body(level: usize) -> impl Fn(SpanInput) -> ParseResult<Vec<TypeAliasNode>> {
+    move |input: SpanInput| many0(preceded(empty_lines, type_alias(level)))(input)
+}
+
 // --- Block Parsing ---
 
 fn facet_block(input: SpanInput, level: usize) -> ParseResult<FacetNode> {
@@ -971,6 +1060,22 @@ fn facet_block(input: SpanInput, level: usize) -> ParseResult<FacetNode> {
         return Ok((input, node));
     }
 
+    // Special-case @types: `Name: <type>` alias declarations
+    if name == "types" {
+        let (input, _) = eol(input)?;
+        let (input, aliases) = types_body(level + 1)(input)?;
+        let (input, end_pos) = nom_locate::position(input)?;
+
+        let span = Span {
+            start: start_pos.location_offset(),
+            end: end_pos.location_offset(),
+            line: start_pos.location_line() as usize,
+            column: start_pos.get_utf8_column(),
+        };
+
+        return Ok((input, FacetNode::Types(TypesNode { aliases, span })));
+    }
+
     // Parse facet attributes.
     // @test supports both legacy `@test(name="...")` and spec form `@test "..."`.
     let (input, parsed_attributes) = if name == "test" {
@@ -1223,6 +1328,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_standalone_type_expression() {
+        let ty = parse_type_expression(" list<struct { name: string, age: int }> | null ")
+            .expect("type expression should parse");
+        match ty {
+            TypeNode::Union(members) => {
+                assert!(matches!(&members[0], TypeNode::List(_)));
+                assert!(matches!(&members[1], TypeNode::Primitive(p) if p == "null"));
+            }
+            other => panic!("expected union, got {:?}", other),
+        }
+        assert!(parse_type_expression("list<string").is_err());
+    }
+
+    #[test]
+    fn parses_standalone_value_expression() {
+        let value = parse_value_expression(" $docs |> map(\"title\") |> join(\", \") ")
+            .expect("value expression should parse");
+        match value {
+            ValueNode::Pipeline(pipeline) => {
+                assert_eq!(*pipeline.initial, ValueNode::Variable("docs".to_string()));
+                let names: Vec<&str> = pipeline.lenses.iter().map(|l| l.name.as_str()).collect();
+                assert_eq!(names, ["map", "join"]);
+            }
+            other => panic!("expected pipeline, got {:?}", other),
+        }
+        assert!(parse_value_expression("$docs |>").is_err());
+    }
+
+    #[test]
+    fn parses_list_and_map_literal_lens_arguments() {
+        let value = parse_value_expression(
+            "$rows |> to_markdown_table(columns=[\"name\", $extra]) |> template(partials={row: $row})",
+        )
+        .expect("literal lens arguments should parse");
+        let ValueNode::Pipeline(pipeline) = value else {
+            panic!("expected pipeline, got {:?}", value);
+        };
+        assert_eq!(
+            pipeline.lenses[0].kwargs["columns"],
+            ValueNode::List(vec![
+                ValueNode::String("name".to_string()),
+                ValueNode::Variable("extra".to_string()),
+            ])
+        );
+        assert!(matches!(
+            &pipeline.lenses[1].kwargs["partials"],
+            ValueNode::Map(map) if map["row"] == ValueNode::Variable("row".to_string())
+        ));
+    }
+
     #[test]
     fn parses_interface_header() {
         let src = "@interface WeatherAPI\n  fn get(city: string) -> string\n";
@@ -1318,6 +1474,47 @@ mod tests {
         assert!(matches!(func.return_type, TypeNode::Struct(_)));
     }
 
+    #[test]
+    fn parses_types_block_aliases() {
+        let src = "@types\n  Address: struct {\n    street: string\n    city: string\n  }\n  # comment\n  Tags: list<string>\n  MaybeAddress: Address | null\n\n@interface Geo\n  fn lookup(addr: Address) -> Tags (effect=\"read\")\n";
+        let doc = parse_document(src).expect("@types block should parse");
+
+        let types = match &doc.blocks[0] {
+            FacetNode::Types(types) => types,
+            other => panic!("expected types node, got {:?}", other),
+        };
+        let names: Vec<_> = types.aliases.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["Address", "Tags", "MaybeAddress"]);
+        assert!(matches!(types.aliases[0].type_node, TypeNode::Struct(_)));
+        assert_eq!(
+            types.aliases[2].type_node,
+            TypeNode::Union(vec![
+                TypeNode::Primitive("Address".to_string()),
+                TypeNode::Primitive("null".to_string()),
+            ])
+        );
+
+        let iface = match &doc.blocks[1] {
+            FacetNode::Interface(iface) => iface,
+            other => panic!("expected interface node, got {:?}", other),
+        };
+        assert_eq!(
+            iface.functions[0].params[0].type_node,
+            TypeNode::Primitive("Address".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_interface_functions_without_attributes_on_separate_lines() {
+        let src = "@interface Api\n  fn a(x: string) -> string\n  fn b(y: int) -> int\n";
+        let doc = parse_document(src).expect("consecutive functions should parse");
+        let iface = match &doc.blocks[0] {
+            FacetNode::Interface(iface) => iface,
+            other => panic!("expected interface node, got {:?}", other),
+        };
+        assert_eq!(iface.functions.len(), 2);
+    }
+
     #[test]
     fn parses_float_literals() {
         // Test basic float
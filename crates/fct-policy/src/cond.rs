@@ -0,0 +1,195 @@
+//! `PolicyCond` evaluation (§16.5).
+
+use fct_ast::{ScalarValue, ValueNode};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Reason a condition could not be evaluated. Any error makes the rule undecidable (F455).
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CondError {
+    #[error("no computed variables available to resolve ${0}")]
+    NoVariables(String),
+
+    #[error("unknown variable ${0}")]
+    UnknownVariable(String),
+
+    #[error("invalid variable path ${0}")]
+    InvalidPath(String),
+
+    #[error("variable ${0} does not resolve to a bool")]
+    NotBool(String),
+
+    #[error("malformed condition: {0}")]
+    Malformed(String),
+}
+
+/// Evaluate a `PolicyCond` against computed variables.
+///
+/// Accepted forms are a bool literal, a `$var.path` reference to a bool, and
+/// single-key maps `{ not: c }`, `{ all: [c, ...] }`, `{ any: [c, ...] }`.
+/// `all` stops at the first false item and `any` at the first true item, so
+/// items after the deciding one are never evaluated.
+pub fn eval_policy_cond(
+    cond: &ValueNode,
+    computed_vars: Option<&HashMap<String, ValueNode>>,
+) -> Result<bool, CondError> {
+    match cond {
+        ValueNode::Scalar(ScalarValue::Bool(v)) => Ok(*v),
+        ValueNode::Variable(var_ref) => {
+            let vars = computed_vars.ok_or_else(|| CondError::NoVariables(var_ref.clone()))?;
+            match resolve_policy_var(var_ref, vars)? {
+                ValueNode::Scalar(ScalarValue::Bool(v)) => Ok(*v),
+                _ => Err(CondError::NotBool(var_ref.clone())),
+            }
+        }
+        ValueNode::Map(map) => {
+            let mut entries = map.iter();
+            let (Some((op, arg)), None) = (entries.next(), entries.next()) else {
+                return Err(CondError::Malformed(
+                    "condition map must have exactly one key".to_string(),
+                ));
+            };
+            match op.as_str() {
+                "not" => Ok(!eval_policy_cond(arg, computed_vars)?),
+                "all" => {
+                    for item in cond_items(op, arg)? {
+                        if !eval_policy_cond(item, computed_vars)? {
+                            return Ok(false);
+                        }
+                    }
+                    Ok(true)
+                }
+                "any" => {
+                    for item in cond_items(op, arg)? {
+                        if eval_policy_cond(item, computed_vars)? {
+                            return Ok(true);
+                        }
+                    }
+                    Ok(false)
+                }
+                other => Err(CondError::Malformed(format!(
+                    "unknown condition operator '{}'",
+                    other
+                ))),
+            }
+        }
+        _ => Err(CondError::Malformed(
+            "condition must be a bool, a variable, or a not/all/any map".to_string(),
+        )),
+    }
+}
+
+fn cond_items<'a>(op: &str, arg: &'a ValueNode) -> Result<&'a [ValueNode], CondError> {
+    match arg {
+        ValueNode::List(items) if !items.is_empty() => Ok(items),
+        _ => Err(CondError::Malformed(format!(
+            "'{}' expects a non-empty list",
+            op
+        ))),
+    }
+}
+
+/// Resolve a dotted `$var.field` reference. Numeric (list index) segments are not allowed.
+pub fn resolve_policy_var<'a>(
+    var_ref: &str,
+    vars: &'a HashMap<String, ValueNode>,
+) -> Result<&'a ValueNode, CondError> {
+    let mut parts = var_ref.split('.');
+    let base = parts.next().unwrap_or_default();
+    let mut current = vars
+        .get(base)
+        .ok_or_else(|| CondError::UnknownVariable(var_ref.to_string()))?;
+
+    for seg in parts {
+        if seg.chars().all(|c| c.is_ascii_digit()) {
+            return Err(CondError::InvalidPath(var_ref.to_string()));
+        }
+        current = match current {
+            ValueNode::Map(map) => map
+                .get(seg)
+                .ok_or_else(|| CondError::UnknownVariable(var_ref.to_string()))?,
+            _ => return Err(CondError::InvalidPath(var_ref.to_string())),
+        };
+    }
+
+    Ok(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fct_ast::OrderedMap;
+
+    fn op(name: &str, arg: ValueNode) -> ValueNode {
+        ValueNode::Map(OrderedMap::from([(name.to_string(), arg)]))
+    }
+
+    fn var(name: &str) -> ValueNode {
+        ValueNode::Variable(name.to_string())
+    }
+
+    fn bool_node(v: bool) -> ValueNode {
+        ValueNode::Scalar(ScalarValue::Bool(v))
+    }
+
+    fn vars() -> HashMap<String, ValueNode> {
+        let ctx = OrderedMap::from([("trusted".to_string(), bool_node(true))]);
+        HashMap::from([
+            ("enabled".to_string(), bool_node(false)),
+            ("ctx".to_string(), ValueNode::Map(ctx)),
+            ("name".to_string(), ValueNode::String("x".to_string())),
+        ])
+    }
+
+    #[test]
+    fn evaluates_literals_variables_and_nested_paths() {
+        let vars = vars();
+        assert_eq!(eval_policy_cond(&bool_node(true), None), Ok(true));
+        assert_eq!(eval_policy_cond(&var("enabled"), Some(&vars)), Ok(false));
+        assert_eq!(eval_policy_cond(&var("ctx.trusted"), Some(&vars)), Ok(true));
+        assert_eq!(
+            eval_policy_cond(&op("not", var("ctx.trusted")), Some(&vars)),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn all_and_any_short_circuit_before_failing_items() {
+        let vars = vars();
+        let all = op("all", ValueNode::List(vec![var("enabled"), var("missing")]));
+        assert_eq!(eval_policy_cond(&all, Some(&vars)), Ok(false));
+
+        let any = op(
+            "any",
+            ValueNode::List(vec![var("ctx.trusted"), var("name")]),
+        );
+        assert_eq!(eval_policy_cond(&any, Some(&vars)), Ok(true));
+
+        let failing = op("any", ValueNode::List(vec![var("enabled"), var("name")]));
+        assert_eq!(
+            eval_policy_cond(&failing, Some(&vars)),
+            Err(CondError::NotBool("name".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_conditions_and_bad_paths() {
+        let vars = vars();
+        assert!(matches!(
+            eval_policy_cond(&op("all", ValueNode::List(vec![])), Some(&vars)),
+            Err(CondError::Malformed(_))
+        ));
+        assert!(matches!(
+            eval_policy_cond(&op("xor", bool_node(true)), Some(&vars)),
+            Err(CondError::Malformed(_))
+        ));
+        assert_eq!(
+            eval_policy_cond(&var("ctx.0"), Some(&vars)),
+            Err(CondError::InvalidPath("ctx.0".to_string()))
+        );
+        assert_eq!(
+            eval_policy_cond(&var("enabled"), None),
+            Err(CondError::NoVariables("enabled".to_string()))
+        );
+    }
+}
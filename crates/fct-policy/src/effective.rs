@@ -0,0 +1,247 @@
+//! Effective policy merging and hashing (§16.4).
+
+use fct_ast::{
+    BodyNode, FacetDocument, FacetNode, OrderedMap, ScalarValue, ValueNode, POLICY_VERSION,
+};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Errors that can occur while serializing a policy or host context.
+#[derive(Error, Debug)]
+pub enum PolicyError {
+    #[error("JSON serialization error: {0}")]
+    SerializationError(#[from] serde_json::Error),
+
+    #[error("Invalid host context: {0}")]
+    InvalidHostContext(String),
+}
+
+/// The merged `@policy` of a resolved document.
+///
+/// Keys of later blocks override earlier ones; nested maps merge recursively and
+/// `allow`/`deny` rule lists merge by rule `id` (rules without an id are appended).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EffectivePolicy {
+    map: OrderedMap<String, ValueNode>,
+}
+
+impl EffectivePolicy {
+    /// Merge all `@policy` blocks of `document` in source order, or `None` when there are none.
+    pub fn from_document(document: &FacetDocument) -> Option<Self> {
+        let mut effective = Self::default();
+        let mut seen_policy = false;
+
+        for node in &document.blocks {
+            if let FacetNode::Policy(policy_block) = node {
+                seen_policy = true;
+                for body in &policy_block.body {
+                    if let BodyNode::KeyValue(kv) = body {
+                        effective.merge_entry(&kv.key, kv.value.clone());
+                    }
+                }
+            }
+        }
+
+        seen_policy.then_some(effective)
+    }
+
+    /// Merge one top-level policy key as if it appeared in a later `@policy` block.
+    pub fn merge_entry(&mut self, key: &str, value: ValueNode) {
+        if let Some(existing) = self.map.get_mut(key) {
+            *existing = merge_policy_value(key, existing.clone(), value);
+        } else {
+            self.map.insert(key.to_string(), value);
+        }
+    }
+
+    pub fn as_map(&self) -> &OrderedMap<String, ValueNode> {
+        &self.map
+    }
+
+    pub fn into_map(self) -> OrderedMap<String, ValueNode> {
+        self.map
+    }
+
+    pub fn get(&self, key: &str) -> Option<&ValueNode> {
+        self.map.get(key)
+    }
+
+    /// Rules under `deny` or `allow`; empty when the key is absent or not a list.
+    pub fn rules(&self, key: &str) -> &[ValueNode] {
+        match self.map.get(key) {
+            Some(ValueNode::List(items)) => items,
+            _ => &[],
+        }
+    }
+
+    /// The effective policy as plain JSON.
+    pub fn to_json(&self) -> Result<serde_json::Value, PolicyError> {
+        ordered_map_to_json(&self.map)
+    }
+
+    /// v2.1.3 `policy_hash`: SHA-256 over the JCS form of `{policy_version, policy}`.
+    pub fn policy_hash(&self) -> Result<String, PolicyError> {
+        let envelope = serde_json::json!({
+            "policy_version": POLICY_VERSION,
+            "policy": self.to_json()?,
+        });
+        let canonical = serde_json_canonicalizer::to_string(&envelope)?;
+        Ok(format!("sha256:{:x}", Sha256::digest(canonical.as_bytes())))
+    }
+}
+
+impl From<OrderedMap<String, ValueNode>> for EffectivePolicy {
+    fn from(map: OrderedMap<String, ValueNode>) -> Self {
+        Self { map }
+    }
+}
+
+/// The `id` of a policy rule, if it has a string one.
+pub fn policy_rule_id(rule: &ValueNode) -> Option<&str> {
+    match rule {
+        ValueNode::Map(map) => match map.get("id") {
+            Some(ValueNode::String(id)) => Some(id.as_str()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn merge_policy_value(key: &str, old: ValueNode, new: ValueNode) -> ValueNode {
+    match (old, new) {
+        (ValueNode::Map(old_map), ValueNode::Map(new_map)) => {
+            ValueNode::Map(merge_policy_maps(old_map, new_map))
+        }
+        (ValueNode::List(old_list), ValueNode::List(new_list))
+            if key == "allow" || key == "deny" =>
+        {
+            ValueNode::List(merge_policy_lists(old_list, new_list))
+        }
+        (_, replacement) => replacement,
+    }
+}
+
+fn merge_policy_maps(
+    mut old_map: OrderedMap<String, ValueNode>,
+    new_map: OrderedMap<String, ValueNode>,
+) -> OrderedMap<String, ValueNode> {
+    for (key, new_value) in new_map {
+        if let Some(old_value) = old_map.get_mut(&key) {
+            *old_value = merge_policy_value(&key, old_value.clone(), new_value);
+        } else {
+            old_map.insert(key, new_value);
+        }
+    }
+    old_map
+}
+
+fn merge_policy_lists(old_items: Vec<ValueNode>, new_items: Vec<ValueNode>) -> Vec<ValueNode> {
+    let mut merged = Vec::new();
+    let mut id_index: OrderedMap<String, usize> = OrderedMap::new();
+
+    for item in old_items {
+        if let Some(id) = policy_rule_id(&item) {
+            id_index.insert(id.to_string(), merged.len());
+        }
+        merged.push(item);
+    }
+
+    for item in new_items {
+        if let Some(id) = policy_rule_id(&item) {
+            if let Some(pos) = id_index.get(id).copied() {
+                let old_item = merged[pos].clone();
+                merged[pos] = merge_policy_value("", old_item, item);
+            } else {
+                id_index.insert(id.to_string(), merged.len());
+                merged.push(item);
+            }
+        } else {
+            merged.push(item);
+        }
+    }
+
+    merged
+}
+
+fn ordered_map_to_json(
+    map: &OrderedMap<String, ValueNode>,
+) -> Result<serde_json::Value, PolicyError> {
+    let mut out = serde_json::Map::new();
+    for (k, v) in map {
+        out.insert(k.clone(), value_node_to_json(v)?);
+    }
+    Ok(serde_json::Value::Object(out))
+}
+
+pub(crate) fn value_node_to_json(value: &ValueNode) -> Result<serde_json::Value, PolicyError> {
+    match value {
+        ValueNode::Scalar(ScalarValue::Int(v)) => Ok(serde_json::json!(v)),
+        ValueNode::Scalar(ScalarValue::Float(v)) => Ok(serde_json::json!(v)),
+        ValueNode::Scalar(ScalarValue::Bool(v)) => Ok(serde_json::json!(v)),
+        ValueNode::Scalar(ScalarValue::Null) => Ok(serde_json::Value::Null),
+        ValueNode::String(v) => Ok(serde_json::json!(v)),
+        ValueNode::Variable(v) => Ok(serde_json::json!(format!("${v}"))),
+        ValueNode::Directive(d) => Ok(serde_json::to_value(d)?),
+        ValueNode::Pipeline(p) => Ok(serde_json::to_value(p)?),
+        ValueNode::List(items) => items
+            .iter()
+            .map(value_node_to_json)
+            .collect::<Result<Vec<_>, _>>()
+            .map(serde_json::Value::Array),
+        ValueNode::Map(map) => ordered_map_to_json(map),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fct_parser::parse_document;
+
+    fn policy(source: &str) -> Option<EffectivePolicy> {
+        EffectivePolicy::from_document(&parse_document(source).expect("parse"))
+    }
+
+    #[test]
+    fn absent_policy_yields_none() {
+        assert!(policy("@user\n  content: \"hi\"\n").is_none());
+    }
+
+    #[test]
+    fn later_blocks_merge_rules_by_id_and_override_defaults() {
+        let merged = policy(
+            "@policy\n  defaults: { tool_call: \"deny\", message_emit: \"allow\" }\n  allow: [{ id: \"a\", op: \"tool_call\", name: \"A.*\" }, { op: \"tool_call\", name: \"B.x\" }]\n\
+             @policy\n  defaults: { tool_call: \"allow\" }\n  allow: [{ id: \"a\", op: \"tool_call\", name: \"C.*\" }, { id: \"b\", op: \"lens_call\" }]\n",
+        )
+        .expect("policy");
+
+        assert_eq!(
+            merged.to_json().unwrap(),
+            serde_json::json!({
+                "defaults": { "tool_call": "allow", "message_emit": "allow" },
+                "allow": [
+                    { "id": "a", "op": "tool_call", "name": "C.*" },
+                    { "op": "tool_call", "name": "B.x" },
+                    { "id": "b", "op": "lens_call" }
+                ]
+            })
+        );
+        let ids: Vec<_> = merged.rules("allow").iter().map(policy_rule_id).collect();
+        assert_eq!(ids, vec![Some("a"), None, Some("b")]);
+        assert!(merged.rules("deny").is_empty());
+    }
+
+    #[test]
+    fn policy_hash_covers_version_and_canonical_policy() {
+        let merged =
+            policy("@policy\n  deny: [{ op: \"tool_call\", name: \"X.y\" }]\n").expect("policy");
+        let envelope = serde_json::json!({
+            "policy_version": POLICY_VERSION,
+            "policy": { "deny": [{ "op": "tool_call", "name": "X.y" }] },
+        });
+        let canonical = serde_json_canonicalizer::to_string(&envelope).unwrap();
+        assert_eq!(
+            merged.policy_hash().unwrap(),
+            format!("sha256:{:x}", Sha256::digest(canonical.as_bytes()))
+        );
+    }
+}
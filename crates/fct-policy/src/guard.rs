@@ -0,0 +1,352 @@
+//! Rule matching and the guard decision algorithm (§16.6.2).
+
+use crate::cond::eval_policy_cond;
+use crate::effective::{policy_rule_id, EffectivePolicy};
+use fct_ast::{ScalarValue, ValueNode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Error code of a policy denial.
+pub const POLICY_DENIED: &str = "F454";
+/// Error code of an undecidable guard (fail-closed).
+pub const GUARD_UNDECIDABLE: &str = "F455";
+
+/// Guarded operation kinds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyOp {
+    ToolExpose,
+    ToolCall,
+    LensCall,
+    MessageEmit,
+}
+
+impl PolicyOp {
+    pub const ALL: [PolicyOp; 4] = [
+        PolicyOp::ToolExpose,
+        PolicyOp::ToolCall,
+        PolicyOp::LensCall,
+        PolicyOp::MessageEmit,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PolicyOp::ToolExpose => "tool_expose",
+            PolicyOp::ToolCall => "tool_call",
+            PolicyOp::LensCall => "lens_call",
+            PolicyOp::MessageEmit => "message_emit",
+        }
+    }
+
+    pub fn parse(op: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|candidate| candidate.as_str() == op)
+    }
+
+    /// Outcome when no rule and no `defaults.<op>` applies: exposure and emission
+    /// are allowed, calls are denied.
+    pub fn fallback_allows(&self) -> bool {
+        matches!(self, PolicyOp::ToolExpose | PolicyOp::MessageEmit)
+    }
+}
+
+/// Operation descriptor matched against policy rules.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpDesc {
+    pub op: PolicyOp,
+    /// Canonical name: `Iface.fn` for tools, the lens name, or the message id.
+    pub name: String,
+    pub effect_class: Option<String>,
+}
+
+impl OpDesc {
+    pub fn new(op: PolicyOp, name: impl Into<String>, effect_class: Option<&str>) -> Self {
+        Self {
+            op,
+            name: name.into(),
+            effect_class: effect_class.map(str::to_string),
+        }
+    }
+}
+
+/// Outcome of guarding one operation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyDecision {
+    pub allowed: bool,
+    pub policy_rule_id: Option<String>,
+    pub error_code: Option<String>,
+}
+
+impl PolicyDecision {
+    fn allow(policy_rule_id: Option<String>) -> Self {
+        Self {
+            allowed: true,
+            policy_rule_id,
+            error_code: None,
+        }
+    }
+
+    fn deny(policy_rule_id: Option<String>) -> Self {
+        Self {
+            allowed: false,
+            policy_rule_id,
+            error_code: Some(POLICY_DENIED.to_string()),
+        }
+    }
+
+    fn undecidable(policy_rule_id: Option<String>) -> Self {
+        Self {
+            allowed: false,
+            policy_rule_id,
+            error_code: Some(GUARD_UNDECIDABLE.to_string()),
+        }
+    }
+
+    pub fn is_undecidable(&self) -> bool {
+        self.error_code.as_deref() == Some(GUARD_UNDECIDABLE)
+    }
+
+    /// `"allowed"` or `"denied"`, as recorded in guard decision events.
+    pub fn decision(&self) -> &'static str {
+        if self.allowed {
+            "allowed"
+        } else {
+            "denied"
+        }
+    }
+}
+
+/// Result of matching one rule against an operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleMatch {
+    Matched(Option<String>),
+    Undecidable(Option<String>),
+    NoMatch,
+}
+
+/// Guard `op` against an optional effective policy.
+///
+/// Without a policy the per-operation fallback applies.
+pub fn evaluate_guard(
+    policy: Option<&EffectivePolicy>,
+    op: &OpDesc,
+    computed_vars: Option<&HashMap<String, ValueNode>>,
+) -> PolicyDecision {
+    match policy {
+        Some(policy) => policy.evaluate(op, computed_vars),
+        None if op.op.fallback_allows() => PolicyDecision::allow(None),
+        None => PolicyDecision::deny(None),
+    }
+}
+
+impl EffectivePolicy {
+    /// Guard `op`: the first matching deny rule, then the first matching allow rule,
+    /// then `defaults.<op>`, then the per-operation fallback. A rule that cannot be
+    /// evaluated decides the operation as undecidable.
+    pub fn evaluate(
+        &self,
+        op: &OpDesc,
+        computed_vars: Option<&HashMap<String, ValueNode>>,
+    ) -> PolicyDecision {
+        for rule in self.rules("deny") {
+            match rule_matches(rule, op, computed_vars) {
+                RuleMatch::Matched(rule_id) => return PolicyDecision::deny(rule_id),
+                RuleMatch::Undecidable(rule_id) => return PolicyDecision::undecidable(rule_id),
+                RuleMatch::NoMatch => {}
+            }
+        }
+
+        for rule in self.rules("allow") {
+            match rule_matches(rule, op, computed_vars) {
+                RuleMatch::Matched(rule_id) => return PolicyDecision::allow(rule_id),
+                RuleMatch::Undecidable(rule_id) => return PolicyDecision::undecidable(rule_id),
+                RuleMatch::NoMatch => {}
+            }
+        }
+
+        self.default_decision(op.op)
+    }
+
+    fn default_decision(&self, op: PolicyOp) -> PolicyDecision {
+        if let Some(defaults_node) = self.get("defaults") {
+            let ValueNode::Map(defaults_map) = defaults_node else {
+                return PolicyDecision::undecidable(None);
+            };
+
+            if let Some(op_default) = defaults_map.get(op.as_str()) {
+                return match op_default {
+                    ValueNode::String(s) if s == "allow" => PolicyDecision::allow(None),
+                    ValueNode::String(s) if s == "deny" => PolicyDecision::deny(None),
+                    ValueNode::Scalar(ScalarValue::Bool(true)) => PolicyDecision::allow(None),
+                    ValueNode::Scalar(ScalarValue::Bool(false)) => PolicyDecision::deny(None),
+                    _ => PolicyDecision::undecidable(None),
+                };
+            }
+        }
+
+        if op.fallback_allows() {
+            PolicyDecision::allow(None)
+        } else {
+            PolicyDecision::deny(None)
+        }
+    }
+}
+
+/// Match one policy rule against `op`.
+///
+/// `op`, `name` and `effect` are conjunctive filters; `when` must hold and `unless`
+/// must not. A rule that is not a map or has no string `op` never matches.
+pub fn rule_matches(
+    rule: &ValueNode,
+    op: &OpDesc,
+    computed_vars: Option<&HashMap<String, ValueNode>>,
+) -> RuleMatch {
+    let ValueNode::Map(map) = rule else {
+        return RuleMatch::NoMatch;
+    };
+
+    let Some(ValueNode::String(rule_op)) = map.get("op") else {
+        return RuleMatch::NoMatch;
+    };
+    if rule_op != op.op.as_str() {
+        return RuleMatch::NoMatch;
+    }
+    let rule_id = policy_rule_id(rule).map(str::to_string);
+
+    match map.get("name") {
+        Some(ValueNode::String(pattern)) if !matcher_matches(pattern, &op.name) => {
+            return RuleMatch::NoMatch
+        }
+        Some(ValueNode::String(_)) | None => {}
+        Some(_) => return RuleMatch::Undecidable(rule_id),
+    }
+
+    match map.get("effect") {
+        Some(ValueNode::String(pattern)) => match op.effect_class.as_deref() {
+            Some(effect_class) if matcher_matches(pattern, effect_class) => {}
+            _ => return RuleMatch::NoMatch,
+        },
+        Some(_) => return RuleMatch::Undecidable(rule_id),
+        None => {}
+    }
+
+    if let Some(cond) = map.get("when") {
+        match eval_policy_cond(cond, computed_vars) {
+            Ok(true) => {}
+            Ok(false) => return RuleMatch::NoMatch,
+            Err(_) => return RuleMatch::Undecidable(rule_id),
+        }
+    }
+
+    if let Some(cond) = map.get("unless") {
+        match eval_policy_cond(cond, computed_vars) {
+            Ok(false) => {}
+            Ok(true) => return RuleMatch::NoMatch,
+            Err(_) => return RuleMatch::Undecidable(rule_id),
+        }
+    }
+
+    RuleMatch::Matched(rule_id)
+}
+
+/// Name/effect matcher: `prefix.*` matches by prefix, anything else matches exactly.
+pub fn matcher_matches(pattern: &str, value: &str) -> bool {
+    if let Some(prefix) = pattern.strip_suffix(".*") {
+        value.starts_with(prefix)
+    } else {
+        pattern == value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fct_parser::parse_document;
+
+    fn policy(source: &str) -> EffectivePolicy {
+        EffectivePolicy::from_document(&parse_document(source).expect("parse")).expect("policy")
+    }
+
+    fn bool_vars(name: &str, value: bool) -> HashMap<String, ValueNode> {
+        HashMap::from([(
+            name.to_string(),
+            ValueNode::Scalar(ScalarValue::Bool(value)),
+        )])
+    }
+
+    #[test]
+    fn no_policy_uses_per_op_fallbacks() {
+        for op in PolicyOp::ALL {
+            let decision = evaluate_guard(None, &OpDesc::new(op, "x", None), None);
+            assert_eq!(decision.allowed, op.fallback_allows(), "{:?}", op);
+            if !decision.allowed {
+                assert_eq!(decision.error_code.as_deref(), Some(POLICY_DENIED));
+            }
+        }
+    }
+
+    #[test]
+    fn deny_rules_win_over_allow_rules() {
+        let policy = policy(
+            "@policy\n  allow: [{ id: \"all-tools\", op: \"tool_call\", name: \"Api.*\" }]\n  deny: [{ id: \"no-pay\", op: \"tool_call\", effect: \"payment\" }]\n",
+        );
+
+        let pay = OpDesc::new(PolicyOp::ToolCall, "Api.charge", Some("payment"));
+        let decision = policy.evaluate(&pay, None);
+        assert!(!decision.allowed);
+        assert_eq!(decision.policy_rule_id.as_deref(), Some("no-pay"));
+        assert_eq!(decision.error_code.as_deref(), Some(POLICY_DENIED));
+
+        let read = OpDesc::new(PolicyOp::ToolCall, "Api.get", Some("read"));
+        let decision = policy.evaluate(&read, None);
+        assert!(decision.allowed);
+        assert_eq!(decision.policy_rule_id.as_deref(), Some("all-tools"));
+
+        // An effect matcher never matches an operation without an effect class.
+        let bare = OpDesc::new(PolicyOp::ToolCall, "Api.get", None);
+        assert_eq!(
+            policy.evaluate(&bare, None).policy_rule_id.as_deref(),
+            Some("all-tools")
+        );
+    }
+
+    #[test]
+    fn conditions_select_rules_and_failures_are_undecidable() {
+        let policy = policy(
+            "@policy\n  allow: [{ id: \"gated\", op: \"lens_call\", name: \"llm_call\", when: $enabled }]\n",
+        );
+        let op = OpDesc::new(PolicyOp::LensCall, "llm_call", Some("network"));
+
+        assert!(
+            policy
+                .evaluate(&op, Some(&bool_vars("enabled", true)))
+                .allowed
+        );
+
+        let off = policy.evaluate(&op, Some(&bool_vars("enabled", false)));
+        assert!(!off.allowed);
+        assert_eq!(off.policy_rule_id, None);
+        assert_eq!(off.error_code.as_deref(), Some(POLICY_DENIED));
+
+        let missing = policy.evaluate(&op, None);
+        assert!(missing.is_undecidable());
+        assert_eq!(missing.policy_rule_id.as_deref(), Some("gated"));
+    }
+
+    #[test]
+    fn defaults_override_fallbacks_and_invalid_defaults_fail_closed() {
+        let policy = policy(
+            "@policy\n  defaults: { tool_call: true, message_emit: \"deny\", lens_call: 3 }\n",
+        );
+        let decide = |op| policy.evaluate(&OpDesc::new(op, "x", None), None);
+
+        assert!(decide(PolicyOp::ToolCall).allowed);
+        assert_eq!(
+            decide(PolicyOp::MessageEmit).error_code.as_deref(),
+            Some(POLICY_DENIED)
+        );
+        assert!(decide(PolicyOp::LensCall).is_undecidable());
+        assert!(decide(PolicyOp::ToolExpose).allowed);
+    }
+}
@@ -0,0 +1,168 @@
+//! Host-supplied, read-only policy context (`$x.<host>...`).
+//!
+//! A host can describe the caller of an operation (principal, tenant, role
+//! flags) without threading it through `@input` variables. The context is a map
+//! of host namespaces; `PolicyCond` paths reach it under the reserved `x` root,
+//! e.g. `$x.acme.principal.admin`. Guard decisions made with a context fold its
+//! hash into their `input_hash`, so the hash chain records who was authorized.
+
+use crate::effective::{value_node_to_json, PolicyError};
+use fct_ast::ValueNode;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Root variable under which the host context is visible to `PolicyCond`.
+pub const HOST_CONTEXT_ROOT: &str = "x";
+
+/// Read-only host context for one execution or guard session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostContext {
+    value: ValueNode,
+    hash: String,
+}
+
+impl HostContext {
+    /// Wrap a map of host namespaces (`{ "<host>": { ... } }`).
+    pub fn new(value: ValueNode) -> Result<Self, PolicyError> {
+        let ValueNode::Map(namespaces) = &value else {
+            return Err(PolicyError::InvalidHostContext(
+                "host context must be a map of host namespaces".to_string(),
+            ));
+        };
+        if let Some(host) = namespaces.keys().find(|host| !is_identifier(host)) {
+            return Err(PolicyError::InvalidHostContext(format!(
+                "host namespace '{}' is not an identifier",
+                host
+            )));
+        }
+
+        let canonical = serde_json_canonicalizer::to_string(&value_node_to_json(&value)?)?;
+        let hash = format!("sha256:{:x}", Sha256::digest(canonical.as_bytes()));
+        Ok(Self { value, hash })
+    }
+
+    pub fn value(&self) -> &ValueNode {
+        &self.value
+    }
+
+    /// SHA-256 over the JCS form of the context.
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    pub fn to_json(&self) -> Result<serde_json::Value, PolicyError> {
+        value_node_to_json(&self.value)
+    }
+
+    /// `input_hash` of a guard decision made under this context:
+    /// SHA-256 over the JCS form of `{input_hash, host_context_hash}`.
+    pub fn fold_input_hash(&self, input_hash: &str) -> Result<String, PolicyError> {
+        let envelope = serde_json::json!({
+            "input_hash": input_hash,
+            "host_context_hash": self.hash,
+        });
+        let canonical = serde_json_canonicalizer::to_string(&envelope)?;
+        Ok(format!("sha256:{:x}", Sha256::digest(canonical.as_bytes())))
+    }
+}
+
+/// Variables visible to `PolicyCond`: the computed variables plus, when present,
+/// the host context under [`HOST_CONTEXT_ROOT`]. The host context wins over a
+/// computed variable of the same name.
+pub fn policy_scope<'a>(
+    computed_vars: Option<&'a HashMap<String, ValueNode>>,
+    host_context: Option<&HostContext>,
+) -> Option<Cow<'a, HashMap<String, ValueNode>>> {
+    let Some(host_context) = host_context else {
+        return computed_vars.map(Cow::Borrowed);
+    };
+    let mut scope = computed_vars.cloned().unwrap_or_default();
+    scope.insert(HOST_CONTEXT_ROOT.to_string(), host_context.value.clone());
+    Some(Cow::Owned(scope))
+}
+
+/// Fold `host_context` into `input_hash`; unchanged without a context.
+pub fn fold_input_hash(
+    input_hash: String,
+    host_context: Option<&HostContext>,
+) -> Result<String, PolicyError> {
+    match host_context {
+        Some(host_context) => host_context.fold_input_hash(&input_hash),
+        None => Ok(input_hash),
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cond::eval_policy_cond;
+    use fct_ast::{OrderedMap, ScalarValue};
+
+    fn map(entries: Vec<(&str, ValueNode)>) -> ValueNode {
+        ValueNode::Map(
+            entries
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect::<OrderedMap<_, _>>(),
+        )
+    }
+
+    fn acme(admin: bool) -> HostContext {
+        HostContext::new(map(vec![(
+            "acme",
+            map(vec![(
+                "principal",
+                map(vec![
+                    ("admin", ValueNode::Scalar(ScalarValue::Bool(admin))),
+                    ("role", ValueNode::String("ops".to_string())),
+                ]),
+            )]),
+        )]))
+        .expect("valid context")
+    }
+
+    #[test]
+    fn scope_exposes_the_context_under_x() {
+        let vars = HashMap::from([(
+            "enabled".to_string(),
+            ValueNode::Scalar(ScalarValue::Bool(true)),
+        )]);
+        let context = acme(true);
+        let scope = policy_scope(Some(&vars), Some(&context)).unwrap();
+
+        let cond = ValueNode::Variable("x.acme.principal.admin".to_string());
+        assert_eq!(eval_policy_cond(&cond, Some(&scope)), Ok(true));
+        let enabled = ValueNode::Variable("enabled".to_string());
+        assert_eq!(eval_policy_cond(&enabled, Some(&scope)), Ok(true));
+
+        assert!(matches!(
+            policy_scope(Some(&vars), None),
+            Some(Cow::Borrowed(_))
+        ));
+    }
+
+    #[test]
+    fn folded_input_hash_depends_on_the_context() {
+        let base = "sha256:abc".to_string();
+        assert_eq!(fold_input_hash(base.clone(), None).unwrap(), base);
+
+        let admin = fold_input_hash(base.clone(), Some(&acme(true))).unwrap();
+        let user = fold_input_hash(base.clone(), Some(&acme(false))).unwrap();
+        assert_ne!(admin, base);
+        assert_ne!(admin, user);
+        assert_eq!(admin, acme(true).fold_input_hash(&base).unwrap());
+    }
+
+    #[test]
+    fn rejects_non_map_contexts_and_bad_namespaces() {
+        assert!(HostContext::new(ValueNode::String("x".to_string())).is_err());
+        assert!(HostContext::new(map(vec![("not-a-host", map(vec![]))])).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+//! # FACET Policy Engine
+//!
+//! Reusable implementation of the FACET v2.1.3 `@policy` semantics:
+//!
+//! - **Effective policy** (§16.4): merging of `@policy` blocks in resolved source
+//!   order, with `allow`/`deny` rules merged by `id`, and the canonical `policy_hash`.
+//! - **PolicyCond** (§16.5): evaluation of `when`/`unless` conditions over computed
+//!   variables with `not`/`all`/`any` short-circuiting.
+//! - **Guard decisions** (§16.6.2): deny rules, then allow rules, then
+//!   `defaults.<op>`, then the per-operation fallback, for any [`OpDesc`].
+//! - **Host context**: a read-only, host-supplied [`HostContext`] visible to
+//!   conditions as `$x.<host>...` and folded into decision `input_hash`es.
+//! - **Call quotas**: per-rule `x_fct_max_calls` limits counted by [`CallQuotas`].
+//! - **Traces**: [`trace_guard`] explains a decision rule by rule, including how
+//!   each `when`/`unless` condition evaluated.
+//!
+//! The renderer, the R-DAG engine and the tool executor all guard their
+//! operations through this crate, and hosts can do the same for their own
+//! tool calls with identical results.
+//!
+//! ## Basic Usage
+//!
+//! ```
+//! use fct_policy::{evaluate_guard, EffectivePolicy, OpDesc, PolicyOp};
+//!
+//! let document = fct_parser::parse_document(
+//!     "@policy\n  allow: [{ id: \"weather\", op: \"tool_call\", name: \"Weather.*\" }]\n",
+//! )
+//! .unwrap();
+//! let policy = EffectivePolicy::from_document(&document);
+//!
+//! let op = OpDesc::new(PolicyOp::ToolCall, "Weather.get", Some("read"));
+//! let decision = evaluate_guard(policy.as_ref(), &op, None);
+//! assert!(decision.allowed);
+//! assert_eq!(decision.policy_rule_id.as_deref(), Some("weather"));
+//! ```
+
+mod cond;
+mod effective;
+mod guard;
+mod host;
+mod quota;
+mod trace;
+
+pub use cond::{eval_policy_cond, resolve_policy_var, CondError};
+pub use effective::{policy_rule_id, EffectivePolicy, PolicyError};
+pub use guard::{
+    evaluate_guard, matcher_matches, rule_matches, OpDesc, PolicyDecision, PolicyOp, RuleMatch,
+    GUARD_UNDECIDABLE, POLICY_DENIED,
+};
+pub use host::{fold_input_hash, policy_scope, HostContext, HOST_CONTEXT_ROOT};
+pub use quota::{rule_max_calls, CallQuotas, MAX_CALLS_KEY, QUOTA_EXHAUSTED};
+pub use trace::{
+    trace_guard, trace_policy_cond, CondResult, CondTrace, GuardTrace, RuleOutcome, RuleTrace,
+};
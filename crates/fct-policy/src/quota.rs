@@ -0,0 +1,128 @@
+//! Call-count quotas: the `x_fct_max_calls` host extension on `allow` rules.
+//!
+//! An `allow` rule for `tool_call` may cap how often it grants a call within one
+//! run or session:
+//!
+//! ```facet
+//! @policy
+//!   allow: [{ id: "refunds", op: "tool_call", name: "PaymentAPI.refund", x_fct_max_calls: 3 }]
+//! ```
+//!
+//! Counters are keyed by `policy_rule_id` and only charged for calls the rule
+//! allows; once a rule's limit is reached further calls it would allow are
+//! denied with [`QUOTA_EXHAUSTED`].
+
+use crate::effective::{policy_rule_id, EffectivePolicy};
+use fct_ast::{ScalarValue, ValueNode};
+use std::collections::BTreeMap;
+
+/// PolicyRule key of the call-count limit (host extension, §2.2).
+pub const MAX_CALLS_KEY: &str = "x_fct_max_calls";
+/// Error code of a call denied because its rule's quota is used up.
+pub const QUOTA_EXHAUSTED: &str = "X.fct.QUOTA_EXHAUSTED";
+
+impl EffectivePolicy {
+    /// `x_fct_max_calls` of the `allow` rule with `policy_rule_id` `id`, if any.
+    pub fn max_calls(&self, id: &str) -> Option<u64> {
+        self.rules("allow")
+            .iter()
+            .find(|rule| policy_rule_id(rule) == Some(id))
+            .and_then(rule_max_calls)
+    }
+}
+
+/// `x_fct_max_calls` of one rule, when present and a non-negative integer.
+pub fn rule_max_calls(rule: &ValueNode) -> Option<u64> {
+    let ValueNode::Map(map) = rule else {
+        return None;
+    };
+    match map.get(MAX_CALLS_KEY) {
+        Some(ValueNode::Scalar(ScalarValue::Int(limit))) => u64::try_from(*limit).ok(),
+        _ => None,
+    }
+}
+
+/// Per-rule call counters for one run or session.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallQuotas {
+    used: BTreeMap<String, u64>,
+}
+
+impl CallQuotas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Charge one call allowed by `policy_rule_id`. Returns `false` without
+    /// charging when the rule's quota is already used up; calls allowed by rules
+    /// without a quota (or by no rule) always pass.
+    pub fn charge(
+        &mut self,
+        policy: Option<&EffectivePolicy>,
+        policy_rule_id: Option<&str>,
+    ) -> bool {
+        let (Some(policy), Some(rule_id)) = (policy, policy_rule_id) else {
+            return true;
+        };
+        let Some(limit) = policy.max_calls(rule_id) else {
+            return true;
+        };
+        let used = self.used.entry(rule_id.to_string()).or_insert(0);
+        if *used >= limit {
+            return false;
+        }
+        *used += 1;
+        true
+    }
+
+    /// Calls charged so far against `policy_rule_id`.
+    pub fn used(&self, policy_rule_id: &str) -> u64 {
+        self.used.get(policy_rule_id).copied().unwrap_or(0)
+    }
+
+    /// Remaining calls for `policy_rule_id`, or `None` when it has no quota.
+    pub fn remaining(&self, policy: &EffectivePolicy, policy_rule_id: &str) -> Option<u64> {
+        policy
+            .max_calls(policy_rule_id)
+            .map(|limit| limit.saturating_sub(self.used(policy_rule_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fct_parser::parse_document;
+
+    fn policy(source: &str) -> EffectivePolicy {
+        EffectivePolicy::from_document(&parse_document(source).expect("parse")).expect("policy")
+    }
+
+    #[test]
+    fn charges_until_the_rule_limit_then_refuses() {
+        let policy = policy(
+            "@policy\n  allow: [{ id: \"refunds\", op: \"tool_call\", name: \"Pay.refund\", x_fct_max_calls: 2 }]\n",
+        );
+        let mut quotas = CallQuotas::new();
+
+        assert_eq!(policy.max_calls("refunds"), Some(2));
+        assert!(quotas.charge(Some(&policy), Some("refunds")));
+        assert!(quotas.charge(Some(&policy), Some("refunds")));
+        assert!(!quotas.charge(Some(&policy), Some("refunds")));
+        assert_eq!(quotas.used("refunds"), 2);
+        assert_eq!(quotas.remaining(&policy, "refunds"), Some(0));
+    }
+
+    #[test]
+    fn rules_without_quota_are_not_counted() {
+        let policy =
+            policy("@policy\n  allow: [{ id: \"reads\", op: \"tool_call\", name: \"Pay.*\" }]\n");
+        let mut quotas = CallQuotas::new();
+        for _ in 0..5 {
+            assert!(quotas.charge(Some(&policy), Some("reads")));
+        }
+        assert!(quotas.charge(Some(&policy), None));
+        assert!(quotas.charge(None, Some("reads")));
+        assert_eq!(quotas, CallQuotas::new());
+        assert_eq!(quotas.remaining(&policy, "reads"), None);
+    }
+}
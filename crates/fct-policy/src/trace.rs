@@ -0,0 +1,452 @@
+//! Guard evaluation with a rule-by-rule explanation.
+//!
+//! [`trace_guard`] reaches the same decision as [`evaluate_guard`] and also
+//! records, for every rule it looked at, why the rule matched or not and how
+//! its `when`/`unless` conditions evaluated.
+//!
+//! [`evaluate_guard`]: crate::evaluate_guard
+
+use crate::cond::eval_policy_cond;
+use crate::effective::{policy_rule_id, EffectivePolicy};
+use crate::guard::{
+    evaluate_guard, matcher_matches, rule_matches, OpDesc, PolicyDecision, RuleMatch,
+};
+use fct_ast::{ScalarValue, ValueNode};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Value of one condition node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CondResult {
+    True,
+    False,
+    /// Evaluation failed; the rule is undecidable.
+    Error,
+    /// Not evaluated because an earlier `all`/`any` item decided the result.
+    Skipped,
+}
+
+impl From<bool> for CondResult {
+    fn from(value: bool) -> Self {
+        if value {
+            CondResult::True
+        } else {
+            CondResult::False
+        }
+    }
+}
+
+/// Evaluation trace of a `PolicyCond` node and its operands.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CondTrace {
+    /// `true`/`false`, `$var.path`, `not`, `all`, `any`, or `<malformed>`.
+    pub expr: String,
+    pub result: CondResult,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub operands: Vec<CondTrace>,
+}
+
+/// How a traced rule related to the operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleOutcome {
+    Matched,
+    NoMatch,
+    Undecidable,
+}
+
+/// Trace of one `allow`/`deny` rule whose `op` equals the operation's.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleTrace {
+    /// `deny` or `allow`.
+    pub list: &'static str,
+    /// Position in the effective rule list.
+    pub index: usize,
+    pub policy_rule_id: Option<String>,
+    pub outcome: RuleOutcome,
+    /// Which filter decided a `no_match` or `undecidable` outcome.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub when: Option<CondTrace>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unless: Option<CondTrace>,
+}
+
+/// A guard decision together with the rules that led to it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GuardTrace {
+    #[serde(flatten)]
+    pub decision: PolicyDecision,
+    /// `rule`, `defaults`, or `fallback` (no policy or no `defaults.<op>`).
+    pub decided_by: &'static str,
+    /// Rules for the operation's `op` in evaluation order, up to the deciding one.
+    pub rules: Vec<RuleTrace>,
+}
+
+/// Guard `op` like [`evaluate_guard`] and explain the decision.
+pub fn trace_guard(
+    policy: Option<&EffectivePolicy>,
+    op: &OpDesc,
+    computed_vars: Option<&HashMap<String, ValueNode>>,
+) -> GuardTrace {
+    let decision = evaluate_guard(policy, op, computed_vars);
+    let Some(policy) = policy else {
+        return GuardTrace {
+            decision,
+            decided_by: "fallback",
+            rules: Vec::new(),
+        };
+    };
+
+    let mut rules = Vec::new();
+    for list in ["deny", "allow"] {
+        for (index, rule) in policy.rules(list).iter().enumerate() {
+            let Some(trace) = trace_rule(list, index, rule, op, computed_vars) else {
+                continue;
+            };
+            let decided = trace.outcome != RuleOutcome::NoMatch;
+            rules.push(trace);
+            if decided {
+                return GuardTrace {
+                    decision,
+                    decided_by: "rule",
+                    rules,
+                };
+            }
+        }
+    }
+
+    // A non-map `defaults` decides every operation (as undecidable).
+    let has_default = match policy.get("defaults") {
+        Some(ValueNode::Map(defaults)) => defaults.get(op.op.as_str()).is_some(),
+        Some(_) => true,
+        None => false,
+    };
+    GuardTrace {
+        decision,
+        decided_by: if has_default { "defaults" } else { "fallback" },
+        rules,
+    }
+}
+
+/// Trace one rule, or `None` when it is not a rule for `op.op`.
+fn trace_rule(
+    list: &'static str,
+    index: usize,
+    rule: &ValueNode,
+    op: &OpDesc,
+    computed_vars: Option<&HashMap<String, ValueNode>>,
+) -> Option<RuleTrace> {
+    let ValueNode::Map(map) = rule else {
+        return None;
+    };
+    match map.get("op") {
+        Some(ValueNode::String(rule_op)) if rule_op == op.op.as_str() => {}
+        _ => return None,
+    }
+
+    let outcome = match rule_matches(rule, op, computed_vars) {
+        RuleMatch::Matched(_) => RuleOutcome::Matched,
+        RuleMatch::Undecidable(_) => RuleOutcome::Undecidable,
+        RuleMatch::NoMatch => RuleOutcome::NoMatch,
+    };
+    let mut trace = RuleTrace {
+        list,
+        index,
+        policy_rule_id: policy_rule_id(rule).map(str::to_string),
+        outcome,
+        reason: None,
+        when: None,
+        unless: None,
+    };
+
+    match map.get("name") {
+        Some(ValueNode::String(pattern)) if !matcher_matches(pattern, &op.name) => {
+            trace.reason = Some(format!("name '{}' does not match '{}'", pattern, op.name));
+            return Some(trace);
+        }
+        Some(ValueNode::String(_)) | None => {}
+        Some(_) => {
+            trace.reason = Some("name matcher is not a string".to_string());
+            return Some(trace);
+        }
+    }
+
+    match map.get("effect") {
+        Some(ValueNode::String(pattern)) => match op.effect_class.as_deref() {
+            Some(effect) if matcher_matches(pattern, effect) => {}
+            Some(effect) => {
+                trace.reason = Some(format!("effect '{}' does not match '{}'", pattern, effect));
+                return Some(trace);
+            }
+            None => {
+                trace.reason = Some(format!(
+                    "effect '{}' requires an effect class; the operation has none",
+                    pattern
+                ));
+                return Some(trace);
+            }
+        },
+        Some(_) => {
+            trace.reason = Some("effect matcher is not a string".to_string());
+            return Some(trace);
+        }
+        None => {}
+    }
+
+    if let Some(cond) = map.get("when") {
+        let cond_trace = trace_policy_cond(cond, computed_vars);
+        let result = cond_trace.result;
+        trace.when = Some(cond_trace);
+        match result {
+            CondResult::True => {}
+            CondResult::False => {
+                trace.reason = Some("when is false".to_string());
+                return Some(trace);
+            }
+            _ => {
+                trace.reason = Some("when could not be evaluated".to_string());
+                return Some(trace);
+            }
+        }
+    }
+
+    if let Some(cond) = map.get("unless") {
+        let cond_trace = trace_policy_cond(cond, computed_vars);
+        trace.reason = match cond_trace.result {
+            CondResult::False => None,
+            CondResult::True => Some("unless is true".to_string()),
+            _ => Some("unless could not be evaluated".to_string()),
+        };
+        trace.unless = Some(cond_trace);
+    }
+
+    Some(trace)
+}
+
+/// Evaluate a `PolicyCond` like [`eval_policy_cond`], recording every operand.
+pub fn trace_policy_cond(
+    cond: &ValueNode,
+    computed_vars: Option<&HashMap<String, ValueNode>>,
+) -> CondTrace {
+    match cond {
+        ValueNode::Scalar(ScalarValue::Bool(v)) => leaf(v.to_string(), Ok(*v)),
+        ValueNode::Variable(var_ref) => leaf(
+            format!("${}", var_ref),
+            eval_policy_cond(cond, computed_vars).map_err(|e| e.to_string()),
+        ),
+        ValueNode::Map(map) if map.len() == 1 => {
+            let (op, arg) = map.iter().next().expect("map has one entry");
+            match (op.as_str(), arg) {
+                ("not", _) => {
+                    let operand = trace_policy_cond(arg, computed_vars);
+                    let result = match operand.result {
+                        CondResult::True => CondResult::False,
+                        CondResult::False => CondResult::True,
+                        other => other,
+                    };
+                    node("not", result, vec![operand])
+                }
+                ("all" | "any", ValueNode::List(items)) if !items.is_empty() => {
+                    // `all` stops at the first false item, `any` at the first true one.
+                    let stop_at = if op == "all" {
+                        CondResult::False
+                    } else {
+                        CondResult::True
+                    };
+                    let mut result = if op == "all" {
+                        CondResult::True
+                    } else {
+                        CondResult::False
+                    };
+                    let mut operands = Vec::with_capacity(items.len());
+                    for item in items {
+                        if result == stop_at || result == CondResult::Error {
+                            operands.push(skipped(item));
+                            continue;
+                        }
+                        let operand = trace_policy_cond(item, computed_vars);
+                        if operand.result == stop_at || operand.result == CondResult::Error {
+                            result = operand.result;
+                        }
+                        operands.push(operand);
+                    }
+                    node(op, result, operands)
+                }
+                _ => malformed(cond, computed_vars),
+            }
+        }
+        _ => malformed(cond, computed_vars),
+    }
+}
+
+fn leaf(expr: String, value: Result<bool, String>) -> CondTrace {
+    match value {
+        Ok(v) => CondTrace {
+            expr,
+            result: v.into(),
+            error: None,
+            operands: Vec::new(),
+        },
+        Err(error) => CondTrace {
+            expr,
+            result: CondResult::Error,
+            error: Some(error),
+            operands: Vec::new(),
+        },
+    }
+}
+
+fn node(expr: &str, result: CondResult, operands: Vec<CondTrace>) -> CondTrace {
+    CondTrace {
+        expr: expr.to_string(),
+        result,
+        error: None,
+        operands,
+    }
+}
+
+fn skipped(cond: &ValueNode) -> CondTrace {
+    CondTrace {
+        expr: cond_label(cond),
+        result: CondResult::Skipped,
+        error: None,
+        operands: Vec::new(),
+    }
+}
+
+fn malformed(cond: &ValueNode, computed_vars: Option<&HashMap<String, ValueNode>>) -> CondTrace {
+    let error = eval_policy_cond(cond, computed_vars)
+        .err()
+        .map(|e| e.to_string());
+    CondTrace {
+        expr: "<malformed>".to_string(),
+        result: CondResult::Error,
+        error,
+        operands: Vec::new(),
+    }
+}
+
+fn cond_label(cond: &ValueNode) -> String {
+    match cond {
+        ValueNode::Scalar(ScalarValue::Bool(v)) => v.to_string(),
+        ValueNode::Variable(var_ref) => format!("${}", var_ref),
+        ValueNode::Map(map) if map.len() == 1 => map.keys().next().cloned().unwrap_or_default(),
+        _ => "<malformed>".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::guard::{PolicyOp, GUARD_UNDECIDABLE};
+    use fct_parser::parse_document;
+
+    fn policy(source: &str) -> EffectivePolicy {
+        EffectivePolicy::from_document(&parse_document(source).expect("parse")).expect("policy")
+    }
+
+    fn vars(entries: &[(&str, ValueNode)]) -> HashMap<String, ValueNode> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    fn bool_node(v: bool) -> ValueNode {
+        ValueNode::Scalar(ScalarValue::Bool(v))
+    }
+
+    #[test]
+    fn traces_rules_up_to_the_deciding_one() {
+        let policy = policy(
+            "@policy\n  deny: [{ id: \"no-pay\", op: \"tool_call\", name: \"Api.*\", effect: \"payment\" }, { id: \"quiet\", op: \"tool_call\", name: \"Api.*\", unless: $trusted }]\n  allow: [{ id: \"api\", op: \"tool_call\", name: \"Api.*\" }, { id: \"later\", op: \"tool_call\" }]\n",
+        );
+        let op = OpDesc::new(PolicyOp::ToolCall, "Api.get", Some("read"));
+        let vars = vars(&[("trusted", bool_node(true))]);
+
+        let trace = trace_guard(Some(&policy), &op, Some(&vars));
+        assert_eq!(trace.decision, policy.evaluate(&op, Some(&vars)));
+        assert_eq!(trace.decided_by, "rule");
+        let summary: Vec<_> = trace
+            .rules
+            .iter()
+            .map(|r| (r.list, r.policy_rule_id.as_deref(), r.outcome))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("deny", Some("no-pay"), RuleOutcome::NoMatch),
+                ("deny", Some("quiet"), RuleOutcome::NoMatch),
+                ("allow", Some("api"), RuleOutcome::Matched),
+            ]
+        );
+        assert_eq!(
+            trace.rules[0].reason.as_deref(),
+            Some("effect 'payment' does not match 'read'")
+        );
+        assert_eq!(trace.rules[1].reason.as_deref(), Some("unless is true"));
+        assert_eq!(
+            trace.rules[1].unless.as_ref().map(|c| c.result),
+            Some(CondResult::True)
+        );
+    }
+
+    #[test]
+    fn cond_trace_marks_short_circuited_and_failing_operands() {
+        let cond = ValueNode::Map(fct_ast::OrderedMap::from([(
+            "any".to_string(),
+            ValueNode::List(vec![
+                ValueNode::Variable("off".to_string()),
+                ValueNode::Variable("missing".to_string()),
+                bool_node(true),
+            ]),
+        )]));
+        let vars = vars(&[("off", bool_node(false))]);
+
+        let trace = trace_policy_cond(&cond, Some(&vars));
+        assert_eq!(trace.result, CondResult::Error);
+        assert!(eval_policy_cond(&cond, Some(&vars)).is_err());
+        let results: Vec<_> = trace.operands.iter().map(|o| o.result).collect();
+        assert_eq!(
+            results,
+            vec![CondResult::False, CondResult::Error, CondResult::Skipped]
+        );
+        assert_eq!(
+            trace.operands[1].error.as_deref(),
+            Some("unknown variable $missing")
+        );
+    }
+
+    #[test]
+    fn trace_agrees_with_evaluate_on_defaults_and_undecidable_rules() {
+        let policy = policy(
+            "@policy\n  defaults: { lens_call: \"allow\" }\n  allow: [{ id: \"gated\", op: \"tool_call\", when: $flag }]\n",
+        );
+        let lens = OpDesc::new(PolicyOp::LensCall, "trim", None);
+        let trace = trace_guard(Some(&policy), &lens, None);
+        assert!(trace.decision.allowed);
+        assert_eq!(trace.decided_by, "defaults");
+        assert!(trace.rules.is_empty());
+
+        let tool = OpDesc::new(PolicyOp::ToolCall, "Api.get", None);
+        let trace = trace_guard(Some(&policy), &tool, None);
+        assert_eq!(
+            trace.decision.error_code.as_deref(),
+            Some(GUARD_UNDECIDABLE)
+        );
+        assert_eq!(trace.rules[0].outcome, RuleOutcome::Undecidable);
+        assert_eq!(
+            trace.rules[0].reason.as_deref(),
+            Some("when could not be evaluated")
+        );
+
+        let trace = trace_guard(None, &tool, None);
+        assert_eq!(trace.decided_by, "fallback");
+        assert!(!trace.decision.allowed);
+    }
+}
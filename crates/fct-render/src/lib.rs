@@ -4,10 +4,14 @@
 //! suitable for LLM providers.
 
 use fct_ast::{
-    BodyNode, FacetDocument, FacetNode, OrderedMap, ScalarValue, ValueNode, FACET_VERSION,
-    POLICY_VERSION,
+    BodyNode, FacetDocument, FacetNode, OrderedMap, ScalarValue, ValueNode, CORE_PROFILE,
+    FACET_VERSION, HYPERVISOR_PROFILE, POLICY_VERSION,
+};
+use fct_engine::{count_facet_units, AllocationResult};
+use fct_policy::{
+    evaluate_guard, fold_input_hash, policy_scope, EffectivePolicy, HostContext, OpDesc,
+    PolicyDecision, PolicyOp,
 };
-use fct_engine::AllocationResult;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::{HashMap, HashSet};
@@ -30,6 +34,9 @@ pub enum RenderError {
 
     #[error("F455: Guard undecidable for operation: {name}")]
     GuardUndecidable { name: String },
+
+    #[error("Policy serialization error: {0}")]
+    PolicyError(#[from] fct_policy::PolicyError),
 }
 
 #[derive(Debug, Clone, Default)]
@@ -42,6 +49,8 @@ pub struct RenderContext {
     pub budget_units: Option<usize>,
     pub target_provider_id: Option<String>,
     pub computed_vars: Option<HashMap<String, ValueNode>>,
+    /// Read-only host context for `message_emit`/`tool_expose` guards.
+    pub host_context: Option<HostContext>,
 }
 
 #[derive(Debug, Clone)]
@@ -105,7 +114,9 @@ impl Renderer {
             None => fallback_document_hash(document)?,
         };
         let mode = context.mode.unwrap_or_else(|| "exec".to_string());
-        let profile = context.profile.unwrap_or_else(|| "hypervisor".to_string());
+        let profile = context
+            .profile
+            .unwrap_or_else(|| HYPERVISOR_PROFILE.to_string());
         let host_profile_id = context
             .host_profile_id
             .unwrap_or_else(|| "local.default.v1".to_string());
@@ -113,7 +124,9 @@ impl Renderer {
         let target_provider_id = context
             .target_provider_id
             .unwrap_or_else(|| "unknown-provider".to_string());
-        let computed_vars = context.computed_vars;
+        let host_context = context.host_context;
+        let computed_vars = policy_scope(context.computed_vars.as_ref(), host_context.as_ref())
+            .map(|scope| scope.into_owned());
 
         let policy_hash = if let Some(hash) = context.policy_hash {
             Some(hash)
@@ -132,6 +145,8 @@ impl Renderer {
                 policy_hash,
                 budget_units,
                 target_provider_id,
+                reserved_tool_units: allocation.reserved.tools,
+                reserved_output_units: allocation.reserved.output,
             },
             tools: Vec::new(),
             messages: Vec::new(),
@@ -140,186 +155,67 @@ impl Renderer {
         let mut system_messages: Vec<CanonicalMessage> = Vec::new();
         let mut user_messages: Vec<CanonicalMessage> = Vec::new();
         let mut assistant_messages: Vec<CanonicalMessage> = Vec::new();
-        let effective_policy = collect_effective_policy(document);
+        let effective_policy = EffectivePolicy::from_document(document);
         let mut message_guard_decisions: Vec<GuardDecision> = Vec::new();
 
+        // Core has no runtime guard (§2.1.1): messages are emitted unguarded.
+        let guarded = profile_has_guards(&payload.metadata.profile);
+
         // Process allocated sections in canonical order
         for section_result in &allocation.sections {
             let section = &section_result.section;
+            if section_result.final_size == 0 {
+                continue;
+            }
 
-            match section_role_for_section(section) {
-                Some("system") => {
-                    if section_result.final_size > 0 {
-                        let guard = evaluate_message_emit(
-                            effective_policy.as_ref(),
-                            &section.id,
-                            &mode,
-                            computed_vars.as_ref(),
-                        )?;
-                        let input_hash =
-                            message_emit_input_hash(&section.id, "system", &host_profile_id)?;
-                        message_guard_decisions.push(GuardDecision {
-                            seq: 0,
-                            op: "message_emit".to_string(),
-                            name: section.id.clone(),
-                            effect_class: None,
-                            mode: mode.clone(),
-                            decision: if guard.allowed {
-                                "allowed".to_string()
-                            } else {
-                                "denied".to_string()
-                            },
-                            policy_rule_id: guard.policy_rule_id,
-                            input_hash,
-                            error_code: guard.error_code.clone(),
-                        });
-                        if guard.error_code.as_deref() == Some("F455") {
-                            return Err(RenderError::GuardUndecidable {
-                                name: section.id.clone(),
-                            });
-                        }
-                        if !guard.allowed {
-                            continue;
-                        }
-
-                        let content = render_value_node(&section.content)?;
-                        system_messages.push(CanonicalMessage {
-                            role: "system".to_string(),
-                            content,
-                        });
-                    }
-                }
-                Some("tools") => {
-                    // Handle tool definitions from @interface blocks
-                    if section_result.final_size > 0 {
-                        // This would be populated from @interface blocks in the document
-                        // For now, we'll implement basic structure
-                    }
-                }
-                Some("user") => {
-                    if section_result.final_size > 0 {
-                        let guard = evaluate_message_emit(
-                            effective_policy.as_ref(),
-                            &section.id,
-                            &mode,
-                            computed_vars.as_ref(),
-                        )?;
-                        let input_hash =
-                            message_emit_input_hash(&section.id, "user", &host_profile_id)?;
-                        message_guard_decisions.push(GuardDecision {
-                            seq: 0,
-                            op: "message_emit".to_string(),
-                            name: section.id.clone(),
-                            effect_class: None,
-                            mode: mode.clone(),
-                            decision: if guard.allowed {
-                                "allowed".to_string()
-                            } else {
-                                "denied".to_string()
-                            },
-                            policy_rule_id: guard.policy_rule_id,
-                            input_hash,
-                            error_code: guard.error_code.clone(),
-                        });
-                        if guard.error_code.as_deref() == Some("F455") {
-                            return Err(RenderError::GuardUndecidable {
-                                name: section.id.clone(),
-                            });
-                        }
-                        if !guard.allowed {
-                            continue;
-                        }
+            let role = match section_role_for_section(section) {
+                Some("system") => "system",
+                Some("assistant") => "assistant",
+                // Tool definitions are extracted from @interface blocks below.
+                Some("tools") => continue,
+                // Unknown sections are treated as user-role content.
+                _ => "user",
+            };
 
-                        let content = render_value_node(&section.content)?;
-                        user_messages.push(CanonicalMessage {
-                            role: "user".to_string(),
-                            content,
-                        });
-                    }
+            if guarded {
+                let guard = evaluate_message_emit(
+                    effective_policy.as_ref(),
+                    &section.id,
+                    computed_vars.as_ref(),
+                );
+                let input_hash = fold_input_hash(
+                    message_emit_input_hash(&section.id, role, &host_profile_id)?,
+                    host_context.as_ref(),
+                )?;
+                message_guard_decisions.push(GuardDecision {
+                    seq: 0,
+                    op: "message_emit".to_string(),
+                    name: section.id.clone(),
+                    effect_class: None,
+                    mode: mode.clone(),
+                    decision: guard.decision().to_string(),
+                    policy_rule_id: guard.policy_rule_id.clone(),
+                    input_hash,
+                    error_code: guard.error_code.clone(),
+                });
+                if guard.is_undecidable() {
+                    return Err(RenderError::GuardUndecidable {
+                        name: section.id.clone(),
+                    });
                 }
-                Some("assistant") => {
-                    if section_result.final_size > 0 {
-                        let guard = evaluate_message_emit(
-                            effective_policy.as_ref(),
-                            &section.id,
-                            &mode,
-                            computed_vars.as_ref(),
-                        )?;
-                        let input_hash =
-                            message_emit_input_hash(&section.id, "assistant", &host_profile_id)?;
-                        message_guard_decisions.push(GuardDecision {
-                            seq: 0,
-                            op: "message_emit".to_string(),
-                            name: section.id.clone(),
-                            effect_class: None,
-                            mode: mode.clone(),
-                            decision: if guard.allowed {
-                                "allowed".to_string()
-                            } else {
-                                "denied".to_string()
-                            },
-                            policy_rule_id: guard.policy_rule_id,
-                            input_hash,
-                            error_code: guard.error_code.clone(),
-                        });
-                        if guard.error_code.as_deref() == Some("F455") {
-                            return Err(RenderError::GuardUndecidable {
-                                name: section.id.clone(),
-                            });
-                        }
-                        if !guard.allowed {
-                            continue;
-                        }
-
-                        let content = render_value_node(&section.content)?;
-                        assistant_messages.push(CanonicalMessage {
-                            role: "assistant".to_string(),
-                            content,
-                        });
-                    }
+                if !guard.allowed {
+                    continue;
                 }
-                _ => {
-                    // Unknown sections are treated as user-role content.
-                    if section_result.final_size > 0 {
-                        let guard = evaluate_message_emit(
-                            effective_policy.as_ref(),
-                            &section.id,
-                            &mode,
-                            computed_vars.as_ref(),
-                        )?;
-                        let input_hash =
-                            message_emit_input_hash(&section.id, "user", &host_profile_id)?;
-                        message_guard_decisions.push(GuardDecision {
-                            seq: 0,
-                            op: "message_emit".to_string(),
-                            name: section.id.clone(),
-                            effect_class: None,
-                            mode: mode.clone(),
-                            decision: if guard.allowed {
-                                "allowed".to_string()
-                            } else {
-                                "denied".to_string()
-                            },
-                            policy_rule_id: guard.policy_rule_id,
-                            input_hash,
-                            error_code: guard.error_code.clone(),
-                        });
-                        if guard.error_code.as_deref() == Some("F455") {
-                            return Err(RenderError::GuardUndecidable {
-                                name: section.id.clone(),
-                            });
-                        }
-                        if !guard.allowed {
-                            continue;
-                        }
+            }
 
-                        let content = render_value_node(&section.content)?;
-                        user_messages.push(CanonicalMessage {
-                            role: "user".to_string(),
-                            content,
-                        });
-                    }
-                }
+            let message = CanonicalMessage {
+                role: role.to_string(),
+                content: render_value_node(&section.content)?,
+            };
+            match role {
+                "system" => system_messages.push(message),
+                "assistant" => assistant_messages.push(message),
+                _ => user_messages.push(message),
             }
         }
 
@@ -329,8 +225,13 @@ impl Renderer {
         payload.messages.extend(assistant_messages);
 
         // Extract tool definitions from @interface blocks
-        let (tools, mut tool_guard_decisions) =
-            extract_tools_with_guard(document, &mode, &host_profile_id, computed_vars.as_ref())?;
+        let (tools, mut tool_guard_decisions) = extract_tools_with_guard(
+            document,
+            &mode,
+            &host_profile_id,
+            computed_vars.as_ref(),
+            host_context.as_ref(),
+        )?;
         payload.tools = tools;
         let mut guard_decisions = Vec::new();
         guard_decisions.append(&mut message_guard_decisions);
@@ -394,6 +295,26 @@ pub struct Metadata {
 
     /// Target provider identifier
     pub target_provider_id: String,
+
+    /// FACET Units of `tools` counted as fixed load by the layout (host extension)
+    #[serde(
+        rename = "x_fct_reserved_tool_units",
+        default,
+        skip_serializing_if = "is_zero"
+    )]
+    pub reserved_tool_units: usize,
+
+    /// FACET Units of the budget kept free for the model's reply (host extension)
+    #[serde(
+        rename = "x_fct_reserved_output_units",
+        default,
+        skip_serializing_if = "is_zero"
+    )]
+    pub reserved_output_units: usize,
+}
+
+fn is_zero(units: &usize) -> bool {
+    *units == 0
 }
 
 fn fallback_document_hash(document: &FacetDocument) -> Result<String, RenderError> {
@@ -403,19 +324,9 @@ fn fallback_document_hash(document: &FacetDocument) -> Result<String, RenderErro
 }
 
 fn compute_policy_hash(document: &FacetDocument) -> Result<Option<String>, RenderError> {
-    let effective_policy = collect_effective_policy(document);
-    let Some(policy_map) = effective_policy else {
-        return Ok(None);
-    };
-
-    let policy_json = ordered_map_to_json(&policy_map)?;
-    let envelope = serde_json::json!({
-        "policy_version": POLICY_VERSION,
-        "policy": policy_json,
-    });
-    let canonical = canonicalize_json(&envelope)?;
-    let hash = Sha256::digest(canonical.as_bytes());
-    Ok(Some(format!("sha256:{:x}", hash)))
+    Ok(EffectivePolicy::from_document(document)
+        .map(|policy| policy.policy_hash())
+        .transpose()?)
 }
 
 /// Compute v2.1.3 `policy_hash` for a resolved document.
@@ -427,100 +338,9 @@ pub fn policy_hash_for_document(document: &FacetDocument) -> Result<Option<Strin
 pub fn effective_policy_json_for_document(
     document: &FacetDocument,
 ) -> Result<Option<serde_json::Value>, RenderError> {
-    let Some(policy_map) = collect_effective_policy(document) else {
-        return Ok(None);
-    };
-    Ok(Some(ordered_map_to_json(&policy_map)?))
-}
-
-fn collect_effective_policy(document: &FacetDocument) -> Option<OrderedMap<String, ValueNode>> {
-    let mut effective: OrderedMap<String, ValueNode> = OrderedMap::new();
-    let mut seen_policy = false;
-
-    for node in &document.blocks {
-        if let FacetNode::Policy(policy_block) = node {
-            seen_policy = true;
-            for body in &policy_block.body {
-                if let BodyNode::KeyValue(kv) = body {
-                    if let Some(existing) = effective.get_mut(&kv.key) {
-                        let merged =
-                            merge_policy_value(&kv.key, existing.clone(), kv.value.clone());
-                        *existing = merged;
-                    } else {
-                        effective.insert(kv.key.clone(), kv.value.clone());
-                    }
-                }
-            }
-        }
-    }
-
-    seen_policy.then_some(effective)
-}
-
-fn merge_policy_value(key: &str, old: ValueNode, new: ValueNode) -> ValueNode {
-    match (old, new) {
-        (ValueNode::Map(old_map), ValueNode::Map(new_map)) => {
-            ValueNode::Map(merge_policy_maps(old_map, new_map))
-        }
-        (ValueNode::List(old_list), ValueNode::List(new_list))
-            if key == "allow" || key == "deny" =>
-        {
-            ValueNode::List(merge_policy_lists(old_list, new_list))
-        }
-        (_, replacement) => replacement,
-    }
-}
-
-fn merge_policy_maps(
-    mut old_map: OrderedMap<String, ValueNode>,
-    new_map: OrderedMap<String, ValueNode>,
-) -> OrderedMap<String, ValueNode> {
-    for (key, new_value) in new_map {
-        if let Some(old_value) = old_map.get_mut(&key) {
-            *old_value = merge_policy_value(&key, old_value.clone(), new_value);
-        } else {
-            old_map.insert(key, new_value);
-        }
-    }
-    old_map
-}
-
-fn merge_policy_lists(old_items: Vec<ValueNode>, new_items: Vec<ValueNode>) -> Vec<ValueNode> {
-    let mut merged = Vec::new();
-    let mut id_index: OrderedMap<String, usize> = OrderedMap::new();
-
-    for item in old_items {
-        if let Some(id) = policy_rule_id(&item) {
-            id_index.insert(id.to_string(), merged.len());
-        }
-        merged.push(item);
-    }
-
-    for item in new_items {
-        if let Some(id) = policy_rule_id(&item) {
-            if let Some(pos) = id_index.get(id).copied() {
-                let old_item = merged[pos].clone();
-                merged[pos] = merge_policy_value("", old_item, item);
-            } else {
-                id_index.insert(id.to_string(), merged.len());
-                merged.push(item);
-            }
-        } else {
-            merged.push(item);
-        }
-    }
-
-    merged
-}
-
-fn policy_rule_id(item: &ValueNode) -> Option<&str> {
-    match item {
-        ValueNode::Map(map) => match map.get("id") {
-            Some(ValueNode::String(id)) => Some(id.as_str()),
-            _ => None,
-        },
-        _ => None,
-    }
+    Ok(EffectivePolicy::from_document(document)
+        .map(|policy| policy.to_json())
+        .transpose()?)
 }
 
 fn ordered_map_to_json(
@@ -637,6 +457,14 @@ pub struct ParametersSchema {
 
     /// Required parameters
     pub required: Vec<String>,
+
+    /// `@types` aliases referenced (transitively) by the parameters
+    #[serde(
+        rename = "$defs",
+        default,
+        skip_serializing_if = "serde_json::Map::is_empty"
+    )]
+    pub defs: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Example interaction
@@ -780,10 +608,40 @@ fn section_role_for_section(section: &fct_engine::Section) -> Option<&str> {
     section_role(&section.id)
 }
 
+/// FACET Units of the canonical `tools` array the render would emit, or 0
+/// when no tool is exposed. Hosts count it as layout fixed load.
+pub fn tools_facet_units(
+    document: &FacetDocument,
+    context: &RenderContext,
+) -> Result<usize, RenderError> {
+    let mode = context.mode.as_deref().unwrap_or("exec");
+    let host_profile_id = context
+        .host_profile_id
+        .as_deref()
+        .unwrap_or("local.default.v1");
+    let computed_vars = policy_scope(
+        context.computed_vars.as_ref(),
+        context.host_context.as_ref(),
+    )
+    .map(|scope| scope.into_owned());
+    let (tools, _) = extract_tools_with_guard(
+        document,
+        mode,
+        host_profile_id,
+        computed_vars.as_ref(),
+        context.host_context.as_ref(),
+    )?;
+    if tools.is_empty() {
+        return Ok(0);
+    }
+    let tools_json = canonicalize_json(&serde_json::to_value(&tools)?)?;
+    Ok(count_facet_units(&tools_json))
+}
+
 /// Extract tool definitions from @interface blocks in the document
 #[allow(dead_code)]
 fn extract_tools(document: &FacetDocument) -> Result<Vec<ToolDefinition>, RenderError> {
-    let (tools, _) = extract_tools_with_guard(document, "exec", "local.default.v1", None)?;
+    let (tools, _) = extract_tools_with_guard(document, "exec", "local.default.v1", None, None)?;
     Ok(tools)
 }
 
@@ -792,13 +650,15 @@ fn extract_tools_with_guard(
     mode: &str,
     host_profile_id: &str,
     computed_vars: Option<&HashMap<String, ValueNode>>,
+    host_context: Option<&HostContext>,
 ) -> Result<(Vec<ToolDefinition>, Vec<GuardDecision>), RenderError> {
     let mut tools = Vec::new();
     let mut guard_decisions = Vec::new();
     let mut seq = 1usize;
     let mut referenced_interfaces = HashSet::new();
     let mut has_tool_refs = false;
-    let effective_policy = collect_effective_policy(document);
+    let effective_policy = EffectivePolicy::from_document(document);
+    let type_aliases = collect_type_aliases(document);
 
     for node in &document.blocks {
         if let FacetNode::System(system_block) = node {
@@ -848,33 +708,29 @@ fn extract_tools_with_guard(
                     effective_policy.as_ref(),
                     &canonical_name,
                     function.effect.as_deref(),
-                    mode,
                     computed_vars,
+                );
+                let input_hash = fold_input_hash(
+                    tool_expose_input_hash(&interface_block.name, host_profile_id)?,
+                    host_context,
                 )?;
-                let input_hash = tool_expose_input_hash(&interface_block.name, host_profile_id)?;
                 guard_decisions.push(GuardDecision {
                     seq,
                     op: "tool_expose".to_string(),
                     name: canonical_name.clone(),
                     effect_class: function.effect.clone(),
                     mode: mode.to_string(),
-                    decision: if guard.allowed {
-                        "allowed".to_string()
-                    } else {
-                        "denied".to_string()
-                    },
-                    policy_rule_id: guard.policy_rule_id,
+                    decision: guard.decision().to_string(),
+                    policy_rule_id: guard.policy_rule_id.clone(),
                     input_hash,
                     error_code: guard.error_code.clone(),
                 });
                 seq += 1;
 
-                if let Some(code) = guard.error_code.as_deref() {
-                    if code == "F455" {
-                        return Err(RenderError::GuardUndecidable {
-                            name: canonical_name,
-                        });
-                    }
+                if guard.is_undecidable() {
+                    return Err(RenderError::GuardUndecidable {
+                        name: canonical_name,
+                    });
                 }
 
                 if !guard.allowed {
@@ -886,7 +742,7 @@ fn extract_tools_with_guard(
 
                 for param in &function.params {
                     // Convert FACET parameter to JSON schema property
-                    let param_schema = type_node_to_json_schema(&param.type_node)?;
+                    let param_schema = type_node_to_json_schema(&param.type_node, &type_aliases)?;
                     properties.insert(param.name.clone(), param_schema);
 
                     // For now, assume all parameters are required
@@ -903,6 +759,10 @@ fn extract_tools_with_guard(
                             schema_type: "object".to_string(),
                             properties,
                             required,
+                            defs: alias_defs_json_schema(
+                                function.params.iter().map(|p| &p.type_node),
+                                &type_aliases,
+                            )?,
                         },
                     },
                 };
@@ -914,9 +774,48 @@ fn extract_tools_with_guard(
     Ok((tools, guard_decisions))
 }
 
-/// Convert FACET TypeNode to JSON schema
+/// Collect `@types` aliases in declaration order; later declarations override.
+fn collect_type_aliases(document: &FacetDocument) -> OrderedMap<String, fct_ast::TypeNode> {
+    let mut aliases = OrderedMap::new();
+    for node in &document.blocks {
+        if let FacetNode::Types(types) = node {
+            for alias in &types.aliases {
+                aliases.insert(alias.name.clone(), alias.type_node.clone());
+            }
+        }
+    }
+    aliases
+}
+
+/// Build the `$defs` map for every alias reachable from `roots`, in alias declaration order.
+fn alias_defs_json_schema<'a>(
+    roots: impl IntoIterator<Item = &'a fct_ast::TypeNode>,
+    aliases: &OrderedMap<String, fct_ast::TypeNode>,
+) -> Result<serde_json::Map<String, serde_json::Value>, RenderError> {
+    let mut reachable: HashSet<&str> = HashSet::new();
+    let mut pending: Vec<&str> = roots.into_iter().flat_map(|ty| ty.alias_refs()).collect();
+    while let Some(name) = pending.pop() {
+        let Some(alias_type) = aliases.get(name) else {
+            continue;
+        };
+        if reachable.insert(name) {
+            pending.extend(alias_type.alias_refs());
+        }
+    }
+
+    let mut defs = serde_json::Map::new();
+    for (name, alias_type) in aliases {
+        if reachable.contains(name.as_str()) {
+            defs.insert(name.clone(), type_node_to_json_schema(alias_type, aliases)?);
+        }
+    }
+    Ok(defs)
+}
+
+/// Convert FACET TypeNode to JSON schema; alias references become `$ref`s into `$defs`.
 fn type_node_to_json_schema(
     type_node: &fct_ast::TypeNode,
+    aliases: &OrderedMap<String, fct_ast::TypeNode>,
 ) -> Result<serde_json::Value, RenderError> {
     match type_node {
         fct_ast::TypeNode::Primitive(primitive) => {
@@ -927,6 +826,9 @@ fn type_node_to_json_schema(
                 "bool" | "Bool" => Ok(serde_json::json!({"type": "boolean"})),
                 "null" | "Null" => Ok(serde_json::json!({"type": "null"})),
                 "any" | "Any" => Ok(serde_json::json!({})),
+                alias if aliases.contains_key(alias) => {
+                    Ok(serde_json::json!({"$ref": format!("#/$defs/{alias}")}))
+                }
                 _ => Ok(serde_json::json!({"type": "string"})), // fallback for legacy unknown primitives
             }
         }
@@ -934,7 +836,10 @@ fn type_node_to_json_schema(
             let mut properties = serde_json::Map::new();
             let mut required = Vec::new();
             for (field_name, field_type) in fields {
-                properties.insert(field_name.clone(), type_node_to_json_schema(field_type)?);
+                properties.insert(
+                    field_name.clone(),
+                    type_node_to_json_schema(field_type, aliases)?,
+                );
                 required.push(field_name.clone());
             }
             Ok(serde_json::json!({
@@ -946,16 +851,16 @@ fn type_node_to_json_schema(
         }
         fct_ast::TypeNode::List(item_type) => Ok(serde_json::json!({
             "type": "array",
-            "items": type_node_to_json_schema(item_type)?
+            "items": type_node_to_json_schema(item_type, aliases)?
         })),
         fct_ast::TypeNode::Map(value_type) => Ok(serde_json::json!({
             "type": "object",
-            "additionalProperties": type_node_to_json_schema(value_type)?
+            "additionalProperties": type_node_to_json_schema(value_type, aliases)?
         })),
         fct_ast::TypeNode::Union(types) => {
             let mut one_of = Vec::new();
             for ty in types {
-                one_of.push(type_node_to_json_schema(ty)?);
+                one_of.push(type_node_to_json_schema(ty, aliases)?);
             }
             Ok(serde_json::json!({
                 "oneOf": one_of
@@ -975,429 +880,249 @@ fn type_node_to_json_schema(
     }
 }
 
-#[derive(Debug, Clone)]
-struct ToolExposeDecision {
-    allowed: bool,
-    policy_rule_id: Option<String>,
-    error_code: Option<String>,
-}
-
-fn evaluate_tool_expose(
-    policy: Option<&OrderedMap<String, ValueNode>>,
-    tool_name: &str,
-    effect_class: Option<&str>,
-    _mode: &str,
-    computed_vars: Option<&HashMap<String, ValueNode>>,
-) -> Result<ToolExposeDecision, RenderError> {
-    let Some(policy_map) = policy else {
-        return Ok(ToolExposeDecision {
-            allowed: true,
-            policy_rule_id: None,
-            error_code: None,
-        });
-    };
+/// Build a JSON Schema (Appendix D mapping) describing the runtime inputs a
+/// document expects, i.e. every `@vars` entry backed by `@input(...)`.
+///
+/// Inputs with a `default` are optional. `@var_types` constraints are included
+/// when the variable's whole value is the `@input` directive, matching the
+/// runtime check performed by the engine. Struct schemas stay open to extra
+/// properties, as the runtime check ignores fields it does not declare.
+pub fn input_schema_for_document(
+    document: &FacetDocument,
+) -> Result<serde_json::Value, RenderError> {
+    let aliases = collect_type_aliases(document);
 
-    if let Some(deny_rules) = policy_map.get("deny").and_then(as_rule_list) {
-        for rule in deny_rules {
-            match rule_matches_tool_expose(rule, tool_name, effect_class, computed_vars)? {
-                RuleMatch::Matched(rule_id) => {
-                    return Ok(ToolExposeDecision {
-                        allowed: false,
-                        policy_rule_id: rule_id,
-                        error_code: Some("F454".to_string()),
-                    })
-                }
-                RuleMatch::Undecidable(rule_id) => {
-                    return Ok(ToolExposeDecision {
-                        allowed: false,
-                        policy_rule_id: rule_id,
-                        error_code: Some("F455".to_string()),
-                    })
+    let mut vars: OrderedMap<String, &ValueNode> = OrderedMap::new();
+    let mut var_types: HashMap<&str, &ValueNode> = HashMap::new();
+    for node in &document.blocks {
+        match node {
+            FacetNode::Vars(block) => {
+                for body in &block.body {
+                    if let BodyNode::KeyValue(kv) = body {
+                        vars.insert(kv.key.clone(), &kv.value);
+                    }
                 }
-                RuleMatch::NoMatch => {}
             }
-        }
-    }
-
-    if let Some(allow_rules) = policy_map.get("allow").and_then(as_rule_list) {
-        for rule in allow_rules {
-            match rule_matches_tool_expose(rule, tool_name, effect_class, computed_vars)? {
-                RuleMatch::Matched(rule_id) => {
-                    return Ok(ToolExposeDecision {
-                        allowed: true,
-                        policy_rule_id: rule_id,
-                        error_code: None,
-                    })
-                }
-                RuleMatch::Undecidable(rule_id) => {
-                    return Ok(ToolExposeDecision {
-                        allowed: false,
-                        policy_rule_id: rule_id,
-                        error_code: Some("F455".to_string()),
-                    })
+            FacetNode::VarTypes(block) => {
+                for body in &block.body {
+                    if let BodyNode::KeyValue(kv) = body {
+                        var_types.insert(kv.key.as_str(), &kv.value);
+                    }
                 }
-                RuleMatch::NoMatch => {}
             }
+            _ => {}
         }
     }
 
-    default_guard_decision(policy_map, "tool_expose", true)
-}
-
-fn evaluate_message_emit(
-    policy: Option<&OrderedMap<String, ValueNode>>,
-    message_id: &str,
-    _mode: &str,
-    computed_vars: Option<&HashMap<String, ValueNode>>,
-) -> Result<ToolExposeDecision, RenderError> {
-    let Some(policy_map) = policy else {
-        return Ok(ToolExposeDecision {
-            allowed: true,
-            policy_rule_id: None,
-            error_code: None,
-        });
-    };
+    let mut properties = serde_json::Map::new();
+    let mut required = Vec::new();
+    let mut roots = Vec::new();
+    for (name, value) in &vars {
+        let (directive, whole_value) = match value {
+            ValueNode::Directive(d) if d.name == "input" => (d, true),
+            ValueNode::Pipeline(p) => match p.initial.as_ref() {
+                ValueNode::Directive(d) if d.name == "input" => (d, false),
+                _ => continue,
+            },
+            _ => continue,
+        };
 
-    if let Some(deny_rules) = policy_map.get("deny").and_then(as_rule_list) {
-        for rule in deny_rules {
-            match rule_matches_message_emit(rule, message_id, computed_vars)? {
-                RuleMatch::Matched(rule_id) => {
-                    return Ok(ToolExposeDecision {
-                        allowed: false,
-                        policy_rule_id: rule_id,
-                        error_code: Some("F454".to_string()),
-                    })
+        let type_str = match directive.args.get("type") {
+            Some(ValueNode::String(s)) => s.as_str(),
+            _ => {
+                return Err(RenderError::MissingRequiredField(format!(
+                    "@input(type=...) for '{name}'"
+                )))
+            }
+        };
+        let input_type = parse_type_for_schema(name, type_str)?;
+        let mut schema = type_node_to_json_schema(&input_type, &aliases)?;
+        roots.push(input_type);
+
+        if let Some(declaration) = var_types.get(name.as_str()).filter(|_| whole_value) {
+            let (declared_type, constraints) = match declaration {
+                ValueNode::String(s) => (s.as_str(), None),
+                ValueNode::Map(map) => match map.get("type") {
+                    Some(ValueNode::String(s)) => (s.as_str(), Some(map)),
+                    _ => (type_str, Some(map)),
+                },
+                _ => (type_str, None),
+            };
+            if normalize_type_text(declared_type) == normalize_type_text(type_str) {
+                if let Some(constraints) = constraints {
+                    // Runtime still checks the `@input` type itself, where every
+                    // field is required, so presence constraints cannot relax it.
+                    apply_schema_constraints(&mut schema, constraints, false)?;
                 }
-                RuleMatch::Undecidable(rule_id) => {
-                    return Ok(ToolExposeDecision {
-                        allowed: false,
-                        policy_rule_id: rule_id,
-                        error_code: Some("F455".to_string()),
-                    })
+            } else {
+                let var_type = parse_type_for_schema(name, declared_type)?;
+                let mut var_schema = type_node_to_json_schema(&var_type, &aliases)?;
+                if let Some(constraints) = constraints {
+                    if has_presence_constraints(constraints) {
+                        // Field presence rewrites the struct, so the alias cannot stay a `$ref`.
+                        var_schema = type_node_to_json_schema(
+                            resolve_alias_node(&var_type, &aliases),
+                            &aliases,
+                        )?;
+                    }
+                    apply_schema_constraints(&mut var_schema, constraints, true)?;
                 }
-                RuleMatch::NoMatch => {}
+                roots.push(var_type);
+                schema = serde_json::json!({ "allOf": [schema, var_schema] });
             }
         }
-    }
 
-    if let Some(allow_rules) = policy_map.get("allow").and_then(as_rule_list) {
-        for rule in allow_rules {
-            match rule_matches_message_emit(rule, message_id, computed_vars)? {
-                RuleMatch::Matched(rule_id) => {
-                    return Ok(ToolExposeDecision {
-                        allowed: true,
-                        policy_rule_id: rule_id,
-                        error_code: None,
-                    })
+        match directive.args.get("default") {
+            Some(default) => {
+                if let serde_json::Value::Object(obj) = &mut schema {
+                    obj.insert("default".to_string(), value_node_to_json(default)?);
                 }
-                RuleMatch::Undecidable(rule_id) => {
-                    return Ok(ToolExposeDecision {
-                        allowed: false,
-                        policy_rule_id: rule_id,
-                        error_code: Some("F455".to_string()),
-                    })
-                }
-                RuleMatch::NoMatch => {}
             }
+            None => required.push(name.clone()),
         }
+        properties.insert(name.clone(), schema);
     }
 
-    default_guard_decision(policy_map, "message_emit", true)
-}
-
-fn as_rule_list(value: &ValueNode) -> Option<&Vec<ValueNode>> {
-    match value {
-        ValueNode::List(items) => Some(items),
-        _ => None,
+    let mut root = serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    });
+    let defs = alias_defs_json_schema(&roots, &aliases)?;
+    if !defs.is_empty() {
+        root["$defs"] = serde_json::Value::Object(defs);
     }
+    open_struct_schemas(&mut root);
+    Ok(root)
 }
 
-fn default_guard_decision(
-    policy_map: &OrderedMap<String, ValueNode>,
-    op: &str,
-    fallback_allow: bool,
-) -> Result<ToolExposeDecision, RenderError> {
-    if let Some(defaults_node) = policy_map.get("defaults") {
-        let defaults_map = match defaults_node {
-            ValueNode::Map(map) => map,
-            _ => {
-                return Ok(ToolExposeDecision {
-                    allowed: false,
-                    policy_rule_id: None,
-                    error_code: Some("F455".to_string()),
-                })
+/// Drop `"additionalProperties": false` from every struct schema in `schema`.
+/// Map schemas keep their `additionalProperties` value schema.
+fn open_struct_schemas(schema: &mut serde_json::Value) {
+    match schema {
+        serde_json::Value::Object(obj) => {
+            if obj.get("additionalProperties") == Some(&serde_json::Value::Bool(false)) {
+                obj.remove("additionalProperties");
             }
-        };
-
-        if let Some(op_default) = defaults_map.get(op) {
-            return match op_default {
-                ValueNode::String(s) if s == "allow" => Ok(ToolExposeDecision {
-                    allowed: true,
-                    policy_rule_id: None,
-                    error_code: None,
-                }),
-                ValueNode::String(s) if s == "deny" => Ok(ToolExposeDecision {
-                    allowed: false,
-                    policy_rule_id: None,
-                    error_code: Some("F454".to_string()),
-                }),
-                ValueNode::Scalar(ScalarValue::Bool(true)) => Ok(ToolExposeDecision {
-                    allowed: true,
-                    policy_rule_id: None,
-                    error_code: None,
-                }),
-                ValueNode::Scalar(ScalarValue::Bool(false)) => Ok(ToolExposeDecision {
-                    allowed: false,
-                    policy_rule_id: None,
-                    error_code: Some("F454".to_string()),
-                }),
-                _ => Ok(ToolExposeDecision {
-                    allowed: false,
-                    policy_rule_id: None,
-                    error_code: Some("F455".to_string()),
-                }),
-            };
+            obj.values_mut().for_each(open_struct_schemas);
         }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(open_struct_schemas),
+        _ => {}
     }
-
-    Ok(ToolExposeDecision {
-        allowed: fallback_allow,
-        policy_rule_id: None,
-        error_code: if fallback_allow {
-            None
-        } else {
-            Some("F454".to_string())
-        },
-    })
 }
 
-enum RuleMatch {
-    Matched(Option<String>),
-    Undecidable(Option<String>),
-    NoMatch,
+fn parse_type_for_schema(name: &str, type_str: &str) -> Result<fct_ast::TypeNode, RenderError> {
+    fct_parser::parse_type_expression(type_str)
+        .map_err(|e| RenderError::ConversionError(format!("{e} (input '{name}')")))
 }
 
-fn rule_matches_tool_expose(
-    rule: &ValueNode,
-    tool_name: &str,
-    tool_effect_class: Option<&str>,
-    computed_vars: Option<&HashMap<String, ValueNode>>,
-) -> Result<RuleMatch, RenderError> {
-    let ValueNode::Map(map) = rule else {
-        return Ok(RuleMatch::NoMatch);
-    };
-
-    let Some(ValueNode::String(op)) = map.get("op") else {
-        return Ok(RuleMatch::NoMatch);
-    };
-    if op != "tool_expose" {
-        return Ok(RuleMatch::NoMatch);
-    }
-    let rule_id = match map.get("id") {
-        Some(ValueNode::String(id)) => Some(id.clone()),
-        _ => None,
-    };
-
-    let name_match = match map.get("name") {
-        Some(ValueNode::String(pattern)) => matcher_matches(pattern, tool_name),
-        Some(_) => return Ok(RuleMatch::Undecidable(rule_id)),
-        None => true,
-    };
-    if !name_match {
-        return Ok(RuleMatch::NoMatch);
-    }
-
-    if let Some(effect_matcher) = map.get("effect") {
-        match effect_matcher {
-            ValueNode::String(pattern) => {
-                let Some(effect_class) = tool_effect_class else {
-                    return Ok(RuleMatch::NoMatch);
-                };
-                if !matcher_matches(pattern, effect_class) {
-                    return Ok(RuleMatch::NoMatch);
-                }
-            }
-            _ => return Ok(RuleMatch::Undecidable(rule_id)),
-        }
-    }
-
-    let when_eval = match map.get("when") {
-        None => true,
-        Some(cond) => match eval_policy_cond(cond, computed_vars) {
-            Ok(v) => v,
-            Err(()) => return Ok(RuleMatch::Undecidable(rule_id)),
-        },
-    };
-
-    if !when_eval {
-        return Ok(RuleMatch::NoMatch);
-    }
-
-    let unless_eval = match map.get("unless") {
-        None => false,
-        Some(cond) => match eval_policy_cond(cond, computed_vars) {
-            Ok(v) => v,
-            Err(()) => return Ok(RuleMatch::Undecidable(rule_id)),
-        },
-    };
-
-    if unless_eval {
-        return Ok(RuleMatch::NoMatch);
-    }
-
-    Ok(RuleMatch::Matched(rule_id))
+fn normalize_type_text(type_str: &str) -> String {
+    type_str.chars().filter(|c| !c.is_whitespace()).collect()
 }
 
-fn rule_matches_message_emit(
-    rule: &ValueNode,
-    message_id: &str,
-    computed_vars: Option<&HashMap<String, ValueNode>>,
-) -> Result<RuleMatch, RenderError> {
-    let ValueNode::Map(map) = rule else {
-        return Ok(RuleMatch::NoMatch);
-    };
-
-    let Some(ValueNode::String(op)) = map.get("op") else {
-        return Ok(RuleMatch::NoMatch);
-    };
-    if op != "message_emit" {
-        return Ok(RuleMatch::NoMatch);
-    }
-    let rule_id = match map.get("id") {
-        Some(ValueNode::String(id)) => Some(id.clone()),
-        _ => None,
-    };
-
-    let name_match = match map.get("name") {
-        Some(ValueNode::String(pattern)) => matcher_matches(pattern, message_id),
-        Some(_) => return Ok(RuleMatch::Undecidable(rule_id)),
-        None => true,
-    };
-    if !name_match {
-        return Ok(RuleMatch::NoMatch);
-    }
-
-    // message_emit OpDesc currently has effect_class = null; if rule requires effect,
-    // the conjunctive match fails (must not match).
-    if let Some(effect_matcher) = map.get("effect") {
-        match effect_matcher {
-            ValueNode::String(_pattern) => return Ok(RuleMatch::NoMatch),
-            _ => return Ok(RuleMatch::Undecidable(rule_id)),
+/// Follow top-level alias references to the aliased type node.
+fn resolve_alias_node<'a>(
+    type_node: &'a fct_ast::TypeNode,
+    aliases: &'a OrderedMap<String, fct_ast::TypeNode>,
+) -> &'a fct_ast::TypeNode {
+    let mut current = type_node;
+    let mut seen = HashSet::new();
+    while let fct_ast::TypeNode::Primitive(name) = current {
+        match aliases.get(name) {
+            Some(target) if seen.insert(name.as_str()) => current = target,
+            _ => break,
         }
     }
+    current
+}
 
-    let when_eval = match map.get("when") {
-        None => true,
-        Some(cond) => match eval_policy_cond(cond, computed_vars) {
-            Ok(v) => v,
-            Err(()) => return Ok(RuleMatch::Undecidable(rule_id)),
-        },
-    };
-
-    if !when_eval {
-        return Ok(RuleMatch::NoMatch);
-    }
+fn has_presence_constraints(constraints: &OrderedMap<String, ValueNode>) -> bool {
+    constraints.contains_key("required") || constraints.contains_key("optional")
+}
 
-    let unless_eval = match map.get("unless") {
-        None => false,
-        Some(cond) => match eval_policy_cond(cond, computed_vars) {
-            Ok(v) => v,
-            Err(()) => return Ok(RuleMatch::Undecidable(rule_id)),
-        },
+/// Map `@var_types` constraint keys onto JSON Schema validation keywords.
+fn apply_schema_constraints(
+    schema: &mut serde_json::Value,
+    constraints: &OrderedMap<String, ValueNode>,
+    apply_presence: bool,
+) -> Result<(), RenderError> {
+    let serde_json::Value::Object(obj) = schema else {
+        return Ok(());
     };
-
-    if unless_eval {
-        return Ok(RuleMatch::NoMatch);
+    for (key, value) in constraints {
+        let keyword = match key.as_str() {
+            "min" => "minimum",
+            "max" => "maximum",
+            "pattern" => "pattern",
+            "enum" => "enum",
+            "min_length" => "minLength",
+            "max_length" => "maxLength",
+            "format" => "format",
+            "min_items" => "minItems",
+            "max_items" => "maxItems",
+            "unique_items" => "uniqueItems",
+            _ => continue,
+        };
+        obj.insert(keyword.to_string(), value_node_to_json(value)?);
     }
 
-    Ok(RuleMatch::Matched(rule_id))
-}
-
-fn eval_policy_cond(
-    cond: &ValueNode,
-    computed_vars: Option<&HashMap<String, ValueNode>>,
-) -> Result<bool, ()> {
-    match cond {
-        ValueNode::Scalar(ScalarValue::Bool(v)) => Ok(*v),
-        ValueNode::Variable(var_ref) => {
-            let vars = computed_vars.ok_or(())?;
-            let value = resolve_policy_var(var_ref, vars)?;
-            match value {
-                ValueNode::Scalar(ScalarValue::Bool(v)) => Ok(*v),
-                _ => Err(()),
-            }
-        }
-        ValueNode::Map(map) => {
-            if map.len() != 1 {
-                return Err(());
+    if apply_presence {
+        let field_names = |key: &str| -> Vec<String> {
+            match constraints.get(key) {
+                Some(ValueNode::List(items)) => items
+                    .iter()
+                    .filter_map(|item| match item {
+                        ValueNode::String(name) => Some(name.clone()),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => Vec::new(),
             }
-
-            let (op, arg) = map.iter().next().ok_or(())?;
-            match op.as_str() {
-                "not" => Ok(!eval_policy_cond(arg, computed_vars)?),
-                "all" => {
-                    let items = match arg {
-                        ValueNode::List(items) if !items.is_empty() => items,
-                        _ => return Err(()),
-                    };
-                    // Short-circuit: stop at first false.
-                    for item in items {
-                        if !eval_policy_cond(item, computed_vars)? {
-                            return Ok(false);
-                        }
-                    }
-                    Ok(true)
-                }
-                "any" => {
-                    let items = match arg {
-                        ValueNode::List(items) if !items.is_empty() => items,
-                        _ => return Err(()),
-                    };
-                    // Short-circuit: stop at first true.
-                    for item in items {
-                        if eval_policy_cond(item, computed_vars)? {
-                            return Ok(true);
-                        }
-                    }
-                    Ok(false)
-                }
-                _ => Err(()),
+        };
+        // Mirrors the validator: an explicit `required` list is exhaustive,
+        // and `optional` then removes names from whatever remains.
+        let optional = field_names("optional");
+        if let Some(serde_json::Value::Array(required)) = obj.get_mut("required") {
+            if constraints.contains_key("required") {
+                *required = field_names("required")
+                    .into_iter()
+                    .map(serde_json::Value::String)
+                    .collect();
             }
+            required.retain(|field| !optional.iter().any(|name| field == name.as_str()));
         }
-        _ => Err(()),
     }
+    Ok(())
 }
 
-fn resolve_policy_var<'a>(
-    var_ref: &str,
-    vars: &'a HashMap<String, ValueNode>,
-) -> Result<&'a ValueNode, ()> {
-    let mut parts = var_ref.split('.');
-    let base = parts.next().ok_or(())?;
-    let mut current = vars.get(base).ok_or(())?;
-
-    for seg in parts {
-        if seg.chars().all(|c| c.is_ascii_digit()) {
-            return Err(());
-        }
-        current = match current {
-            ValueNode::Map(map) => map.get(seg).ok_or(())?,
-            _ => return Err(()),
-        };
-    }
+fn evaluate_tool_expose(
+    policy: Option<&EffectivePolicy>,
+    tool_name: &str,
+    effect_class: Option<&str>,
+    computed_vars: Option<&HashMap<String, ValueNode>>,
+) -> PolicyDecision {
+    let op = OpDesc::new(PolicyOp::ToolExpose, tool_name, effect_class);
+    evaluate_guard(policy, &op, computed_vars)
+}
 
-    Ok(current)
+/// Whether `profile` guards operations at render time; Core has no runtime guard.
+fn profile_has_guards(profile: &str) -> bool {
+    profile != CORE_PROFILE
 }
 
-fn matcher_matches(pattern: &str, value: &str) -> bool {
-    if let Some(prefix) = pattern.strip_suffix(".*") {
-        value.starts_with(prefix)
-    } else {
-        pattern == value
-    }
+/// message_emit OpDesc carries no effect class, so rules with an `effect` matcher never match.
+fn evaluate_message_emit(
+    policy: Option<&EffectivePolicy>,
+    message_id: &str,
+    computed_vars: Option<&HashMap<String, ValueNode>>,
+) -> PolicyDecision {
+    let op = OpDesc::new(PolicyOp::MessageEmit, message_id, None);
+    evaluate_guard(policy, &op, computed_vars)
 }
 
-fn tool_expose_input_hash(
+/// `input_hash` of a `tool_expose` guard decision for an interface.
+pub fn tool_expose_input_hash(
     interface_name: &str,
     host_profile_id: &str,
 ) -> Result<String, RenderError> {
@@ -1411,7 +1136,8 @@ fn tool_expose_input_hash(
     Ok(format!("sha256:{:x}", hash))
 }
 
-fn message_emit_input_hash(
+/// `input_hash` of a `message_emit` guard decision for a message section.
+pub fn message_emit_input_hash(
     message_id: &str,
     role: &str,
     host_profile_id: &str,
@@ -1445,7 +1171,7 @@ mod tests {
         BodyNode, FacetBlock, FacetNode, FunctionSignature, InterfaceNode, KeyValueNode,
         OrderedMap, Parameter, ScalarValue, Span, TypeNode, ValueNode,
     };
-    use fct_engine::{AllocatedSection, AllocationResult, Section};
+    use fct_engine::{AllocatedSection, AllocationResult, LayoutReservation, Section};
 
     #[test]
     fn test_simple_render() {
@@ -1467,8 +1193,10 @@ mod tests {
             sections: vec![AllocatedSection {
                 final_size: 50,
                 was_compressed: false,
+                strategy_applied: false,
                 was_truncated: false,
                 was_dropped: false,
+                dropped_items: Vec::new(),
                 section: Section::new(
                     "system".to_string(),
                     ValueNode::String("You are a helpful assistant".to_string()),
@@ -1478,6 +1206,7 @@ mod tests {
             total_size: 50,
             budget: 100,
             overflow: 0,
+            reserved: LayoutReservation::default(),
         };
 
         let result = renderer.render(&document, &allocation).unwrap();
@@ -1516,8 +1245,10 @@ mod tests {
                 AllocatedSection {
                     final_size: 10,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section: Section::new(
                         "system#1".to_string(),
                         ValueNode::String("sys".to_string()),
@@ -1527,8 +1258,10 @@ mod tests {
                 AllocatedSection {
                     final_size: 10,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section: Section::new(
                         "user#1".to_string(),
                         ValueNode::String("usr".to_string()),
@@ -1538,8 +1271,10 @@ mod tests {
                 AllocatedSection {
                     final_size: 10,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section: Section::new(
                         "assistant#1".to_string(),
                         ValueNode::String("asst".to_string()),
@@ -1550,6 +1285,7 @@ mod tests {
             total_size: 30,
             budget: 100,
             overflow: 0,
+            reserved: LayoutReservation::default(),
         };
 
         let result = renderer.render(&document, &allocation).unwrap();
@@ -1577,8 +1313,10 @@ mod tests {
                 AllocatedSection {
                     final_size: 10,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section: Section::new(
                         "sys.long".to_string(),
                         ValueNode::String("sys".to_string()),
@@ -1589,8 +1327,10 @@ mod tests {
                 AllocatedSection {
                     final_size: 10,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section: Section::new(
                         "user.critical".to_string(),
                         ValueNode::String("usr".to_string()),
@@ -1601,8 +1341,10 @@ mod tests {
                 AllocatedSection {
                     final_size: 10,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section: Section::new(
                         "assistant.flex".to_string(),
                         ValueNode::String("asst".to_string()),
@@ -1614,6 +1356,7 @@ mod tests {
             total_size: 30,
             budget: 100,
             overflow: 0,
+            reserved: LayoutReservation::default(),
         };
 
         let result = renderer.render(&document, &allocation).unwrap();
@@ -1705,13 +1448,16 @@ mod tests {
             sections: vec![AllocatedSection {
                 final_size: 100,
                 was_compressed: false,
+                strategy_applied: false,
                 was_truncated: false,
                 was_dropped: false,
+                dropped_items: Vec::new(),
                 section: Section::new("user#1".to_string(), list_content, 100),
             }],
             total_size: 100,
             budget: 200,
             overflow: 0,
+            reserved: LayoutReservation::default(),
         };
 
         let result = renderer.render(&document, &allocation).unwrap();
@@ -1774,7 +1520,8 @@ mod tests {
         ];
 
         for (ty, expected) in cases {
-            let schema = type_node_to_json_schema(&ty).expect("primitive mapping should succeed");
+            let schema = type_node_to_json_schema(&ty, &OrderedMap::new())
+                .expect("primitive mapping should succeed");
             assert_eq!(schema, expected);
         }
     }
@@ -1804,7 +1551,8 @@ mod tests {
             ("vec".to_string(), TypeNode::Embedding { size: 3 }),
         ]));
 
-        let schema = type_node_to_json_schema(&struct_type).expect("struct mapping should succeed");
+        let schema = type_node_to_json_schema(&struct_type, &OrderedMap::new())
+            .expect("struct mapping should succeed");
         let expected = serde_json::json!({
             "type": "object",
             "properties": {
@@ -1826,6 +1574,73 @@ mod tests {
         assert_eq!(schema, expected);
     }
 
+    #[test]
+    fn test_input_schema_maps_inputs_defaults_and_constraints() {
+        let document = fct_parser::parse_document(
+            r#"@types
+  Tag: string
+
+@var_types
+  email: { type: "string", format: "email", max_length: 32 }
+  tags: { type: "list<Tag>", min_items: 1, unique_items: true }
+
+@vars
+  email: @input(type="string")
+  tags: @input(type="list<Tag>")
+  limit: @input(type="int", default=10)
+  topic: @input(type="string") |> trim()
+  greeting: "hello"
+"#,
+        )
+        .expect("document should parse");
+
+        let schema = input_schema_for_document(&document).expect("schema should build");
+        let expected = serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {
+                "email": { "type": "string", "format": "email", "maxLength": 32 },
+                "tags": {
+                    "type": "array",
+                    "items": { "$ref": "#/$defs/Tag" },
+                    "minItems": 1,
+                    "uniqueItems": true
+                },
+                "limit": { "type": "integer", "default": 10 },
+                "topic": { "type": "string" }
+            },
+            "required": ["email", "tags", "topic"],
+            "$defs": { "Tag": { "type": "string" } }
+        });
+
+        assert_eq!(schema, expected);
+    }
+
+    #[test]
+    fn test_input_schema_combines_distinct_var_types_with_allof() {
+        let document = fct_parser::parse_document(
+            r#"@types
+  Signup: struct { email: string, nick: string }
+
+@var_types
+  signup: { type: "Signup", optional: ["nick"] }
+
+@vars
+  signup: @input(type="struct { email: string, nick: string }")
+"#,
+        )
+        .expect("document should parse");
+
+        let schema = input_schema_for_document(&document).expect("schema should build");
+        let all_of = schema["properties"]["signup"]["allOf"]
+            .as_array()
+            .expect("distinct declarations should combine with allOf");
+        assert_eq!(all_of[0]["required"], serde_json::json!(["email", "nick"]));
+        assert_eq!(all_of[1]["type"], "object");
+        assert_eq!(all_of[1]["required"], serde_json::json!(["email"]));
+        assert_eq!(schema["$defs"]["Signup"]["type"], "object");
+    }
+
     #[test]
     fn test_render_messages_preserve_within_role_order() {
         let renderer = Renderer::new();
@@ -1844,8 +1659,10 @@ mod tests {
                 AllocatedSection {
                     final_size: 10,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section: Section::new(
                         "user#1".to_string(),
                         ValueNode::String("u1".to_string()),
@@ -1855,8 +1672,10 @@ mod tests {
                 AllocatedSection {
                     final_size: 10,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section: Section::new(
                         "system#1".to_string(),
                         ValueNode::String("s1".to_string()),
@@ -1866,8 +1685,10 @@ mod tests {
                 AllocatedSection {
                     final_size: 10,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section: Section::new(
                         "user#2".to_string(),
                         ValueNode::String("u2".to_string()),
@@ -1877,8 +1698,10 @@ mod tests {
                 AllocatedSection {
                     final_size: 10,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section: Section::new(
                         "assistant#1".to_string(),
                         ValueNode::String("a1".to_string()),
@@ -1888,8 +1711,10 @@ mod tests {
                 AllocatedSection {
                     final_size: 10,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section: Section::new(
                         "system#2".to_string(),
                         ValueNode::String("s2".to_string()),
@@ -1900,6 +1725,7 @@ mod tests {
             total_size: 50,
             budget: 100,
             overflow: 0,
+            reserved: LayoutReservation::default(),
         };
 
         let result = renderer.render(&document, &allocation).unwrap();
@@ -1935,8 +1761,10 @@ mod tests {
                 AllocatedSection {
                     final_size: 0,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: true,
+                    dropped_items: Vec::new(),
                     section: Section::new(
                         "user#1".to_string(),
                         ValueNode::String("u1".to_string()),
@@ -1946,8 +1774,10 @@ mod tests {
                 AllocatedSection {
                     final_size: 12,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section: Section::new(
                         "system#1".to_string(),
                         ValueNode::String("s1".to_string()),
@@ -1957,8 +1787,10 @@ mod tests {
                 AllocatedSection {
                     final_size: 0,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: true,
+                    dropped_items: Vec::new(),
                     section: Section::new(
                         "assistant#1".to_string(),
                         ValueNode::String("a1".to_string()),
@@ -1968,8 +1800,10 @@ mod tests {
                 AllocatedSection {
                     final_size: 8,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section: Section::new(
                         "user#2".to_string(),
                         ValueNode::String("u2".to_string()),
@@ -1979,8 +1813,10 @@ mod tests {
                 AllocatedSection {
                     final_size: 7,
                     was_compressed: false,
+                    strategy_applied: false,
                     was_truncated: false,
                     was_dropped: false,
+                    dropped_items: Vec::new(),
                     section: Section::new(
                         "assistant#2".to_string(),
                         ValueNode::String("a2".to_string()),
@@ -1991,6 +1827,7 @@ mod tests {
             total_size: 27,
             budget: 27,
             overflow: 0,
+            reserved: LayoutReservation::default(),
         };
 
         let result = renderer.render(&document, &allocation).unwrap();
@@ -2016,6 +1853,8 @@ mod tests {
                 policy_hash: None,
                 budget_units: 150,
                 target_provider_id: "test-provider".to_string(),
+                reserved_tool_units: 0,
+                reserved_output_units: 0,
             },
             tools: vec![],
             messages: vec![],
@@ -2042,6 +1881,8 @@ mod tests {
                 policy_hash: None,
                 budget_units: 256,
                 target_provider_id: "test-provider".to_string(),
+                reserved_tool_units: 0,
+                reserved_output_units: 0,
             },
             tools: vec![],
             messages: vec![],
@@ -2253,7 +2094,7 @@ mod tests {
         };
 
         let (tools, decisions) =
-            extract_tools_with_guard(&document, "exec", "local.default.v1", None).unwrap();
+            extract_tools_with_guard(&document, "exec", "local.default.v1", None, None).unwrap();
         assert!(tools.is_empty());
         assert_eq!(decisions.len(), 1);
         assert_eq!(decisions[0].decision, "denied");
@@ -2371,8 +2212,8 @@ mod tests {
             },
         };
 
-        let err =
-            extract_tools_with_guard(&document, "exec", "local.default.v1", None).unwrap_err();
+        let err = extract_tools_with_guard(&document, "exec", "local.default.v1", None, None)
+            .unwrap_err();
         assert!(matches!(err, RenderError::GuardUndecidable { .. }));
         assert!(err.to_string().contains("F455"));
     }
@@ -2481,7 +2322,7 @@ mod tests {
         };
 
         let (tools, decisions) =
-            extract_tools_with_guard(&document, "exec", "local.default.v1", None).unwrap();
+            extract_tools_with_guard(&document, "exec", "local.default.v1", None, None).unwrap();
         assert_eq!(tools.len(), 1);
         assert_eq!(decisions.len(), 1);
         assert_eq!(decisions[0].decision, "allowed");
@@ -2588,7 +2429,7 @@ mod tests {
         };
 
         let (tools, decisions) =
-            extract_tools_with_guard(&document, "exec", "local.default.v1", None).unwrap();
+            extract_tools_with_guard(&document, "exec", "local.default.v1", None, None).unwrap();
         assert!(tools.is_empty());
         assert_eq!(decisions.len(), 1);
         assert_eq!(decisions[0].decision, "denied");
@@ -2705,7 +2546,7 @@ mod tests {
         };
 
         let (tools, decisions) =
-            extract_tools_with_guard(&document, "exec", "local.default.v1", None).unwrap();
+            extract_tools_with_guard(&document, "exec", "local.default.v1", None, None).unwrap();
         assert_eq!(tools.len(), 1);
         assert_eq!(decisions.len(), 1);
         assert_eq!(decisions[0].decision, "allowed");
@@ -2759,13 +2600,16 @@ mod tests {
             sections: vec![AllocatedSection {
                 final_size: 5,
                 was_compressed: false,
+                strategy_applied: false,
                 was_truncated: false,
                 was_dropped: false,
+                dropped_items: Vec::new(),
                 section,
             }],
             total_size: 5,
             budget: 100,
             overflow: 0,
+            reserved: LayoutReservation::default(),
         };
 
         let output = Renderer::new()
@@ -2845,13 +2689,16 @@ mod tests {
             sections: vec![AllocatedSection {
                 final_size: 5,
                 was_compressed: false,
+                strategy_applied: false,
                 was_truncated: false,
                 was_dropped: false,
+                dropped_items: Vec::new(),
                 section,
             }],
             total_size: 5,
             budget: 100,
             overflow: 0,
+            reserved: LayoutReservation::default(),
         };
 
         let err = Renderer::new()
@@ -2910,13 +2757,16 @@ mod tests {
             sections: vec![AllocatedSection {
                 final_size: 5,
                 was_compressed: false,
+                strategy_applied: false,
                 was_truncated: false,
                 was_dropped: false,
+                dropped_items: Vec::new(),
                 section,
             }],
             total_size: 5,
             budget: 100,
             overflow: 0,
+            reserved: LayoutReservation::default(),
         };
 
         let output = Renderer::new()
@@ -3021,7 +2871,7 @@ mod tests {
         };
 
         let (tools, decisions) =
-            extract_tools_with_guard(&document, "exec", "local.default.v1", None).unwrap();
+            extract_tools_with_guard(&document, "exec", "local.default.v1", None, None).unwrap();
         assert!(tools.is_empty());
         assert_eq!(decisions.len(), 1);
         assert_eq!(decisions[0].decision, "denied");
@@ -3073,13 +2923,16 @@ mod tests {
             sections: vec![AllocatedSection {
                 final_size: 5,
                 was_compressed: false,
+                strategy_applied: false,
                 was_truncated: false,
                 was_dropped: false,
+                dropped_items: Vec::new(),
                 section,
             }],
             total_size: 5,
             budget: 100,
             overflow: 0,
+            reserved: LayoutReservation::default(),
         };
 
         let output = Renderer::new()
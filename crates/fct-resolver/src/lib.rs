@@ -68,7 +68,7 @@
 //! - **X.resolver.SENSITIVE_LOCATION**: Access to sensitive location denied
 //! - **X.resolver.SUSPICIOUS_ENCODING**: Suspicious path encoding detected
 
-use fct_ast::{FacetBlock, FacetDocument, FacetNode, ImportNode};
+use fct_ast::{FacetBlock, FacetDocument, FacetNode, ImportNode, Span};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
@@ -538,6 +538,96 @@ pub struct Resolver {
 pub struct Phase1Output {
     pub resolved_source_form: String,
     pub resolved_ast: FacetDocument,
+    /// Origin of every line of `resolved_source_form`.
+    pub source_map: SourceMap,
+}
+
+/// A run of Resolved Source Form text copied unchanged from one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSegment {
+    /// Byte range in the Resolved Source Form.
+    pub start: usize,
+    pub end: usize,
+    /// Line of `start` in the Resolved Source Form (1-based).
+    pub line: usize,
+    /// Imported file the text came from; `None` for the entry source.
+    pub file: Option<PathBuf>,
+    /// Byte offset and line of `start` in that file's normalized source.
+    pub origin_start: usize,
+    pub origin_line: usize,
+}
+
+/// Maps Resolved Source Form positions back to the file they were expanded from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    segments: Vec<SourceSegment>,
+}
+
+impl SourceMap {
+    pub fn segments(&self) -> &[SourceSegment] {
+        &self.segments
+    }
+
+    /// The file a Resolved Source Form `span` came from (`None` for the entry
+    /// source) and the same span in that file, or `None` when it crosses files.
+    pub fn locate(&self, span: &Span) -> Option<(Option<&Path>, Span)> {
+        let segment = self
+            .segments
+            .iter()
+            .find(|segment| segment.start <= span.start && span.start < segment.end)?;
+        if span.end > segment.end {
+            return None;
+        }
+        let origin_start = segment.origin_start + (span.start - segment.start);
+        Some((
+            segment.file.as_deref(),
+            Span {
+                start: origin_start,
+                end: origin_start + (span.end - span.start),
+                line: segment.origin_line + (span.line - segment.line),
+                column: span.column,
+            },
+        ))
+    }
+
+    /// Record one copied line, extending the previous segment when it continues it.
+    fn push_line(
+        &mut self,
+        start: usize,
+        end: usize,
+        file: Option<&Path>,
+        origin_start: usize,
+        origin_line: usize,
+    ) {
+        if let Some(last) = self.segments.last_mut() {
+            if last.end == start
+                && last.file.as_deref() == file
+                && last.origin_start + (last.end - last.start) == origin_start
+            {
+                last.end = end;
+                return;
+            }
+        }
+        self.segments.push(SourceSegment {
+            start,
+            end,
+            line: 0,
+            file: file.map(Path::to_path_buf),
+            origin_start,
+            origin_line,
+        });
+    }
+
+    /// Fill in each segment's line once `resolved` is complete.
+    fn number_lines(&mut self, resolved: &str) {
+        let mut counted = 0;
+        let mut line = 1;
+        for segment in &mut self.segments {
+            line += resolved[counted..segment.start].matches('\n').count();
+            counted = segment.start;
+            segment.line = line;
+        }
+    }
 }
 
 impl Resolver {
@@ -609,17 +699,24 @@ impl Resolver {
     /// expanded in-place, preserving non-import lines from source.
     pub fn resolve_source_form(&mut self, source: &str) -> ResolverResult<String> {
         self.context.import_stack.clear();
-        self.expand_source_form(source, None)
+        let mut out = String::new();
+        self.expand_source_form(source, None, &mut out, &mut SourceMap::default())?;
+        Ok(out)
     }
 
     /// Resolve imports and return both the Resolved Source Form and Resolved AST.
     pub fn resolve_phase1(&mut self, source: &str) -> ResolverResult<Phase1Output> {
         let parsed = fct_parser::parse_document(source).map_err(ResolverError::ParseError)?;
-        let resolved_source_form = self.resolve_source_form(source)?;
+        self.context.import_stack.clear();
+        let mut resolved_source_form = String::new();
+        let mut source_map = SourceMap::default();
+        self.expand_source_form(source, None, &mut resolved_source_form, &mut source_map)?;
+        source_map.number_lines(&resolved_source_form);
         let resolved_ast = self.resolve(parsed)?;
         Ok(Phase1Output {
             resolved_source_form,
             resolved_ast,
+            source_map,
         })
     }
 
@@ -627,11 +724,16 @@ impl Resolver {
         &mut self,
         source: &str,
         importer_file: Option<&Path>,
-    ) -> ResolverResult<String> {
+        out: &mut String,
+        source_map: &mut SourceMap,
+    ) -> ResolverResult<()> {
         let normalized = fct_parser::normalize_source(source);
-        let mut out = String::new();
+        let file_start = out.len();
+        let mut origin_start = 0;
 
-        for line_chunk in normalized.split_inclusive('\n') {
+        for (index, line_chunk) in normalized.split_inclusive('\n').enumerate() {
+            let chunk_start = origin_start;
+            origin_start += line_chunk.len();
             let has_newline = line_chunk.ends_with('\n');
             let line = if has_newline {
                 &line_chunk[..line_chunk.len() - 1]
@@ -652,25 +754,22 @@ impl Resolver {
                 self.context.import_stack.push(path.clone());
                 let expanded = (|| {
                     let content = self.read_file_with_timeout(&path)?;
-                    self.expand_source_form(&content, Some(path.as_path()))
+                    self.expand_source_form(&content, Some(path.as_path()), out, source_map)
                 })();
                 self.context.import_stack.pop();
+                expanded?;
 
-                let imported_source = expanded?;
-                out.push_str(&imported_source);
-
-                if has_newline && !out.ends_with('\n') {
+                if has_newline && (out.len() == file_start || !out.ends_with('\n')) {
                     out.push('\n');
                 }
             } else {
-                out.push_str(line);
-                if has_newline {
-                    out.push('\n');
-                }
+                let start = out.len();
+                out.push_str(line_chunk);
+                source_map.push_line(start, out.len(), importer_file, chunk_start, index + 1);
             }
         }
 
-        Ok(out)
+        Ok(())
     }
 
     fn extract_top_level_import_path(line: &str) -> Option<&str> {
@@ -794,6 +893,7 @@ impl Resolver {
             FacetNode::Context(_) => Some("context"),
             FacetNode::Vars(_) => Some("vars"),
             FacetNode::VarTypes(_) => Some("var_types"),
+            FacetNode::Types(_) => Some("types"),
             FacetNode::Policy(_) => Some("policy"),
             _ => None,
         }
@@ -816,11 +916,24 @@ impl Resolver {
             (FacetNode::Policy(existing_block), FacetNode::Policy(new_block)) => {
                 self.merge_facet_blocks(existing_block, &new_block, true);
             }
+            (FacetNode::Types(existing_types), FacetNode::Types(new_types)) => {
+                Self::merge_type_aliases(existing_types, new_types);
+            }
             // Mismatched singletons should not happen in normal flow.
             (_, _) => {}
         }
     }
 
+    /// Aliases merge by name: a later declaration replaces the earlier one in place.
+    fn merge_type_aliases(existing: &mut fct_ast::TypesNode, new: fct_ast::TypesNode) {
+        for alias in new.aliases {
+            match existing.aliases.iter_mut().find(|a| a.name == alias.name) {
+                Some(slot) => *slot = alias,
+                None => existing.aliases.push(alias),
+            }
+        }
+    }
+
     fn merge_facet_blocks(&self, existing: &mut FacetBlock, new: &FacetBlock, policy_mode: bool) {
         use fct_ast::{BodyNode, KeyValueNode};
 
@@ -1702,6 +1815,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_types_aliases_merge_across_imports() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        std::fs::write(
+            root.join("types.facet"),
+            "@types\n  Id: string\n  Point: struct { x: int, y: int }\n",
+        )
+        .unwrap();
+
+        let source = "@import \"types.facet\"\n\n@types\n  Tags: list<string>\n  Id: int\n";
+        let doc = fct_parser::parse_document(source).unwrap();
+
+        let mut resolver = Resolver::new(ResolverConfig {
+            base_dir: root.to_path_buf(),
+            allowed_roots: vec![root.canonicalize().unwrap()],
+        });
+        let resolved = resolver.resolve(doc).unwrap();
+
+        let types_blocks: Vec<&fct_ast::TypesNode> = resolved
+            .blocks
+            .iter()
+            .filter_map(|b| match b {
+                FacetNode::Types(t) => Some(t),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(types_blocks.len(), 1);
+
+        let aliases: Vec<(&str, &fct_ast::TypeNode)> = types_blocks[0]
+            .aliases
+            .iter()
+            .map(|a| (a.name.as_str(), &a.type_node))
+            .collect();
+        assert_eq!(aliases.len(), 3);
+        assert_eq!(aliases[0].0, "Id");
+        assert_eq!(
+            aliases[0].1,
+            &fct_ast::TypeNode::Primitive("int".to_string())
+        );
+        assert_eq!(aliases[1].0, "Point");
+        assert_eq!(aliases[2].0, "Tags");
+    }
+
     #[test]
     fn test_resolve_source_form_expands_imports_in_place() {
         use tempfile::TempDir;
@@ -1795,6 +1955,56 @@ mod tests {
         assert_eq!(keys, vec!["imported".to_string(), "root".to_string()]);
     }
 
+    #[test]
+    fn test_resolve_phase1_maps_spans_back_to_their_file() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(root.join("lib.facet"), "@vars\n  imported: \"I\"\n").unwrap();
+
+        let source = "@vars\n  first: \"F\"\n@import \"lib.facet\"\n@vars\n  root: \"R\"\n";
+        let mut resolver = Resolver::new(ResolverConfig {
+            base_dir: root.to_path_buf(),
+            allowed_roots: vec![root.canonicalize().unwrap()],
+        });
+        let phase1 = resolver.resolve_phase1(source).unwrap();
+        let expanded = &phase1.resolved_source_form;
+        let span_of = |text: &str, line: usize| {
+            let start = expanded.find(text).unwrap();
+            Span {
+                start,
+                end: start + text.len(),
+                line,
+                column: 3,
+            }
+        };
+
+        let (file, span) = phase1
+            .source_map
+            .locate(&span_of("imported: \"I\"", 4))
+            .unwrap();
+        assert_eq!(file.and_then(Path::file_name), Some("lib.facet".as_ref()));
+        assert_eq!((span.start, span.line), (8, 2));
+
+        let (file, span) = phase1
+            .source_map
+            .locate(&span_of("root: \"R\"", 6))
+            .unwrap();
+        assert!(file.is_none());
+        assert_eq!(span.line, 5);
+        assert_eq!(&source[span.start..span.end], "root: \"R\"");
+
+        // A span across the import boundary has no single origin.
+        let across = Span {
+            start: 0,
+            end: expanded.len(),
+            line: 1,
+            column: 1,
+        };
+        assert!(phase1.source_map.locate(&across).is_none());
+    }
+
     #[test]
     fn test_file_read_timeout() {
         use tempfile::NamedTempFile;
@@ -0,0 +1,59 @@
+// ============================================================================
+// CANONICAL TEXT
+// ============================================================================
+
+//! Canonical text of computed values, shared by the formatting and template
+//! lenses: map keys in canonical JSON order (by UTF-16 code units) and
+//! non-string values as canonical JSON (RFC 8785).
+
+use fct_ast::{OrderedMap, ScalarValue, ValueNode};
+
+/// Canonical JSON (RFC 8785) key order: by UTF-16 code units.
+pub(crate) fn canonical_key_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    a.encode_utf16().cmp(b.encode_utf16())
+}
+
+/// Map entries in canonical key order.
+pub(crate) fn canonical_entries(map: &OrderedMap<String, ValueNode>) -> Vec<(&String, &ValueNode)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by(|(a, _), (b, _)| canonical_key_cmp(a, b));
+    entries
+}
+
+/// Canonical JSON text of a value.
+pub(crate) fn canonical_json(value: &ValueNode) -> String {
+    let json = to_json(value);
+    serde_json_canonicalizer::to_string(&json).unwrap_or_else(|_| json.to_string())
+}
+
+/// Text of a value inside prompt text: strings as-is, `null` as nothing, and
+/// anything else as canonical JSON.
+pub(crate) fn value_text(value: &ValueNode) -> String {
+    match value {
+        ValueNode::String(s) => s.clone(),
+        ValueNode::Scalar(ScalarValue::Null) => String::new(),
+        other => canonical_json(other),
+    }
+}
+
+/// JSON form of a computed value, with map keys sorted.
+pub(crate) fn to_json(value: &ValueNode) -> serde_json::Value {
+    match value {
+        ValueNode::Scalar(ScalarValue::Null) => serde_json::Value::Null,
+        ValueNode::Scalar(ScalarValue::Bool(b)) => serde_json::Value::Bool(*b),
+        ValueNode::Scalar(ScalarValue::Int(i)) => serde_json::Value::from(*i),
+        ValueNode::Scalar(ScalarValue::Float(f)) => serde_json::Number::from_f64(*f)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        ValueNode::String(s) => serde_json::Value::String(s.clone()),
+        ValueNode::List(items) => serde_json::Value::Array(items.iter().map(to_json).collect()),
+        ValueNode::Map(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), to_json(value)))
+                .collect(),
+        ),
+        // Unevaluated nodes never reach a lens; keep their source form visible.
+        ValueNode::Variable(name) => serde_json::Value::String(format!("${}", name)),
+        other => serde_json::Value::String(format!("{:?}", other)),
+    }
+}
@@ -0,0 +1,482 @@
+// ============================================================================
+// LAYOUT COMPRESSION LENSES
+// ============================================================================
+
+//! Level-0 lenses designed for a section `strategy` in the Token Box Model
+//! (§9.8). Each takes a `target` size in FACET Units and leaves input that
+//! already fits untouched, which makes it idempotent: its output either fits
+//! the target or cannot be reduced further by the same lens. They are total
+//! on strings (and lists for `drop_lowest`), falling back from sentence or
+//! word boundaries to character boundaries instead of failing. Truncating
+//! lenses return NFC+LF text, in which FACET Units are bytes.
+
+use crate::units::{count_facet_units, count_facet_units_in_value, normalize_for_facet_units};
+use crate::{Lens, LensContext, LensError, LensResult, LensSignature, TrustLevel};
+use fct_ast::{ScalarValue, ValueNode};
+use std::collections::HashMap;
+
+/// Elision marker of `keep_head_tail` unless `marker` is given.
+pub const DEFAULT_ELISION_MARKER: &str = "\n[...]\n";
+
+/// truncate_sentences(target) - Keep the whole sentences that fit `target` units
+pub struct TruncateSentencesLens;
+
+impl Lens for TruncateSentencesLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let text = expect_string("truncate_sentences", input)?;
+        let target = target_units("truncate_sentences", &args, &kwargs)?;
+        let text = normalize_for_facet_units(&text);
+        if text.len() <= target {
+            return Ok(ValueNode::String(text));
+        }
+        Ok(ValueNode::String(truncate_at_boundary(&text, target)))
+    }
+
+    fn signature(&self) -> LensSignature {
+        strategy_signature("truncate_sentences", "string")
+    }
+}
+
+/// keep_head_tail(target, marker) - Keep the start and end, eliding the middle
+pub struct KeepHeadTailLens;
+
+impl Lens for KeepHeadTailLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let text = expect_string("keep_head_tail", input)?;
+        let target = target_units("keep_head_tail", &args, &kwargs)?;
+        let marker = match kwargs.get("marker") {
+            None => DEFAULT_ELISION_MARKER,
+            Some(ValueNode::String(marker)) => marker.as_str(),
+            Some(other) => {
+                return Err(LensError::ArgumentError {
+                    message: format!("keep_head_tail marker must be a string, got {:?}", other),
+                })
+            }
+        };
+        let text = normalize_for_facet_units(&text);
+        if text.len() <= target {
+            return Ok(ValueNode::String(text));
+        }
+
+        let marker_units = count_facet_units(marker);
+        if marker_units >= target {
+            return Ok(ValueNode::String(char_prefix(&text, target).to_string()));
+        }
+        // The head gets the odd unit; each side is trimmed at whitespace
+        // where one is near, so words are not cut in half.
+        let room = target - marker_units;
+        let head = trim_to_word_end(char_prefix(&text, room - room / 2));
+        let tail = trim_to_word_start(char_suffix(&text, room / 2));
+        Ok(ValueNode::String(format!("{}{}{}", head, marker, tail)))
+    }
+
+    fn signature(&self) -> LensSignature {
+        strategy_signature("keep_head_tail", "string")
+    }
+}
+
+/// collapse_whitespace(target) - Squeeze blank runs, trailing spaces and blank lines
+pub struct CollapseWhitespaceLens;
+
+impl Lens for CollapseWhitespaceLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let text = expect_string("collapse_whitespace", input)?;
+        if fits_optional_target("collapse_whitespace", &text, &args, &kwargs)? {
+            return Ok(ValueNode::String(text));
+        }
+        Ok(ValueNode::String(collapse_whitespace(&text)))
+    }
+
+    fn signature(&self) -> LensSignature {
+        strategy_signature("collapse_whitespace", "string")
+    }
+}
+
+/// strip_markdown(target) - Remove markdown markup, keeping the text
+pub struct StripMarkdownLens;
+
+impl Lens for StripMarkdownLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let text = expect_string("strip_markdown", input)?;
+        if fits_optional_target("strip_markdown", &text, &args, &kwargs)? {
+            return Ok(ValueNode::String(text));
+        }
+        // Every pass that changes the text shortens it, so this reaches the
+        // fixpoint that makes the lens idempotent.
+        let mut current = text;
+        loop {
+            let next = collapse_whitespace(&strip_markdown_once(&current));
+            if next == current {
+                return Ok(ValueNode::String(current));
+            }
+            current = next;
+        }
+    }
+
+    fn signature(&self) -> LensSignature {
+        strategy_signature("strip_markdown", "string")
+    }
+}
+
+/// drop_lowest(target, field) - Drop the lowest-scored list items until the rest fit
+pub struct DropLowestLens;
+
+impl Lens for DropLowestLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let items = match input {
+            ValueNode::List(items) => items,
+            other => {
+                return Err(LensError::TypeMismatch {
+                    expected: "list".to_string(),
+                    got: format!("{:?}", other),
+                })
+            }
+        };
+        let target = target_units("drop_lowest", &args, &kwargs)?;
+        let field = match kwargs.get("field") {
+            Some(ValueNode::String(field)) => field,
+            Some(other) => {
+                return Err(LensError::ArgumentError {
+                    message: format!("drop_lowest field must be a string, got {:?}", other),
+                })
+            }
+            None => {
+                return Err(LensError::ArgumentError {
+                    message: "drop_lowest() requires a score field".to_string(),
+                })
+            }
+        };
+
+        let sizes: Vec<usize> = items.iter().map(count_facet_units_in_value).collect();
+        let mut total: usize = sizes.iter().sum();
+        if total <= target {
+            return Ok(ValueNode::List(items));
+        }
+
+        let scores: Vec<Option<f64>> = items.iter().map(|item| item_score(item, field)).collect();
+        let mut keep = vec![true; items.len()];
+        for index in lowest_score_order(&scores) {
+            if total <= target {
+                break;
+            }
+            keep[index] = false;
+            total -= sizes[index];
+        }
+
+        Ok(ValueNode::List(
+            items
+                .into_iter()
+                .zip(keep)
+                .filter_map(|(item, keep)| keep.then_some(item))
+                .collect(),
+        ))
+    }
+
+    fn signature(&self) -> LensSignature {
+        strategy_signature("drop_lowest", "list")
+    }
+}
+
+fn strategy_signature(name: &str, input_type: &str) -> LensSignature {
+    LensSignature {
+        name: name.to_string(),
+        input_type: input_type.to_string(),
+        output_type: input_type.to_string(),
+        trust_level: TrustLevel::Pure,
+        deterministic: true,
+    }
+}
+
+fn expect_string(lens: &str, input: ValueNode) -> LensResult<String> {
+    match input {
+        ValueNode::String(text) => Ok(text),
+        other => Err(LensError::TypeMismatch {
+            expected: format!("string for {}()", lens),
+            got: format!("{:?}", other),
+        }),
+    }
+}
+
+/// The `target` keyword argument, or the first positional one.
+fn optional_target(
+    lens: &str,
+    args: &[ValueNode],
+    kwargs: &HashMap<String, ValueNode>,
+) -> LensResult<Option<usize>> {
+    match kwargs.get("target").or_else(|| args.first()) {
+        None => Ok(None),
+        Some(ValueNode::Scalar(ScalarValue::Int(units))) if *units >= 0 => {
+            Ok(Some(*units as usize))
+        }
+        Some(other) => Err(LensError::ArgumentError {
+            message: format!(
+                "{} target must be a non-negative number of FACET Units, got {:?}",
+                lens, other
+            ),
+        }),
+    }
+}
+
+fn target_units(
+    lens: &str,
+    args: &[ValueNode],
+    kwargs: &HashMap<String, ValueNode>,
+) -> LensResult<usize> {
+    optional_target(lens, args, kwargs)?.ok_or_else(|| LensError::ArgumentError {
+        message: format!("{}() requires a target in FACET Units", lens),
+    })
+}
+
+/// Whether `text` already fits an optional target; without one it never does.
+fn fits_optional_target(
+    lens: &str,
+    text: &str,
+    args: &[ValueNode],
+    kwargs: &HashMap<String, ValueNode>,
+) -> LensResult<bool> {
+    Ok(
+        optional_target(lens, args, kwargs)?
+            .is_some_and(|target| count_facet_units(text) <= target),
+    )
+}
+
+/// Numeric `field` of a map item, the score `drop_lowest` and layout item
+/// packing rank by. Missing, non-numeric and NaN scores are `None`.
+pub fn item_score(item: &ValueNode, field: &str) -> Option<f64> {
+    let ValueNode::Map(map) = item else {
+        return None;
+    };
+    match map.get(field) {
+        Some(ValueNode::Scalar(ScalarValue::Int(i))) => Some(*i as f64),
+        Some(ValueNode::Scalar(ScalarValue::Float(f))) if !f.is_nan() => Some(*f),
+        _ => None,
+    }
+}
+
+/// Indices of `scores` in drop order: lowest score first, items without a
+/// score counting as lowest, and among equal scores the later item first.
+pub fn lowest_score_order(scores: &[Option<f64>]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (sa, sb) = (
+            scores[a].unwrap_or(f64::NEG_INFINITY),
+            scores[b].unwrap_or(f64::NEG_INFINITY),
+        );
+        sa.partial_cmp(&sb)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.cmp(&a))
+    });
+    order
+}
+
+// ----------------------------------------------------------------------------
+// Boundaries
+// ----------------------------------------------------------------------------
+
+/// Longest prefix of `text`, within `target` units, that ends at a sentence
+/// end, else at a word end, else at a character boundary.
+fn truncate_at_boundary(text: &str, target: usize) -> String {
+    let prefix = char_prefix(text, target);
+    let sentence_end = sentence_ends(text)
+        .take_while(|&end| end <= prefix.len())
+        .last();
+    let cut = match sentence_end {
+        Some(end) => &text[..end],
+        None => trim_to_word_end(prefix),
+    };
+    cut.trim_end().to_string()
+}
+
+/// Byte offsets just past each sentence: `.`, `!` or `?`, plus any closing
+/// quotes or brackets, followed by whitespace or the end of the text; and
+/// every line break.
+fn sentence_ends(text: &str) -> impl Iterator<Item = usize> + '_ {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut ends = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (offset, c) = chars[i];
+        if c == '\n' {
+            ends.push(offset);
+        } else if matches!(c, '.' | '!' | '?') {
+            let mut j = i + 1;
+            while j < chars.len() && matches!(chars[j].1, '"' | '\'' | ')' | ']' | '’' | '”') {
+                j += 1;
+            }
+            if j == chars.len() || chars[j].1.is_whitespace() {
+                ends.push(chars.get(j).map_or(text.len(), |&(offset, _)| offset));
+                i = j;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    ends.into_iter().filter(|&end| end > 0)
+}
+
+/// Longest prefix within `target` bytes that ends at a character boundary.
+/// On NFC+LF text, bytes are FACET Units.
+fn char_prefix(text: &str, target: usize) -> &str {
+    let mut end = target.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Longest suffix within `target` bytes that starts at a character boundary.
+fn char_suffix(text: &str, target: usize) -> &str {
+    let mut start = text.len().saturating_sub(target);
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    &text[start..]
+}
+
+/// Drop a trailing partial word, unless the prefix is a single word.
+fn trim_to_word_end(prefix: &str) -> &str {
+    match prefix.rfind(char::is_whitespace) {
+        Some(space) if space > 0 => prefix[..space].trim_end(),
+        _ => prefix,
+    }
+}
+
+/// Drop a leading partial word, unless the suffix is a single word.
+fn trim_to_word_start(suffix: &str) -> &str {
+    match suffix.find(char::is_whitespace) {
+        Some(space) if space + 1 < suffix.len() => suffix[space..].trim_start(),
+        _ => suffix,
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Collapsing
+// ----------------------------------------------------------------------------
+
+/// Trim each line's end, squeeze runs of spaces and tabs after the
+/// indentation to one space, drop leading and trailing blank lines, and keep
+/// at most one blank line in a row.
+fn collapse_whitespace(text: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let content = line.trim_start_matches([' ', '\t']);
+        let indent = &line[..line.len() - content.len()];
+        let mut collapsed = String::with_capacity(line.len());
+        collapsed.push_str(indent);
+        let mut in_space = false;
+        for c in content.trim_end().chars() {
+            if c == ' ' || c == '\t' {
+                in_space = true;
+                continue;
+            }
+            if in_space {
+                collapsed.push(' ');
+                in_space = false;
+            }
+            collapsed.push(c);
+        }
+        if content.trim_end().is_empty() {
+            collapsed.clear();
+        }
+        if collapsed.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(collapsed);
+    }
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// One pass of markup removal: heading, quote and fence markers, horizontal
+/// rules, link and image syntax, bold markers and code backticks.
+fn strip_markdown_once(text: &str) -> String {
+    let lines: Vec<String> = text
+        .lines()
+        .filter_map(|line| {
+            let trimmed = line.trim();
+            let is_rule = trimmed.len() >= 3
+                && ['-', '*', '_']
+                    .iter()
+                    .any(|&mark| trimmed.chars().all(|c| c == mark || c == ' '));
+            if is_rule || trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                return None;
+            }
+            let mut content = line;
+            if let Some(rest) = content.trim_start().strip_prefix('>') {
+                content = rest.strip_prefix(' ').unwrap_or(rest);
+            }
+            let hashes = content.len() - content.trim_start_matches('#').len();
+            if (1..=6).contains(&hashes) {
+                if let Some(rest) = content[hashes..].strip_prefix(' ') {
+                    content = rest;
+                }
+            }
+            Some(strip_inline_markdown(content))
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// `![alt](url)` and `[text](url)` become their text; `**`, `__` and
+/// backticks are removed.
+fn strip_inline_markdown(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(open) = rest.find('[') {
+        let after = &rest[open + 1..];
+        let link = after
+            .find(']')
+            .filter(|&close| after[close + 1..].starts_with('('))
+            .and_then(|close| {
+                after[close + 2..]
+                    .find(')')
+                    .map(|url_end| (close, close + 2 + url_end + 1))
+            });
+        match link {
+            Some((close, end)) => {
+                let image = rest[..open].ends_with('!');
+                out.push_str(&rest[..if image { open - 1 } else { open }]);
+                out.push_str(&after[..close]);
+                rest = &after[end..];
+            }
+            None => {
+                out.push_str(&rest[..=open]);
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out.replace("**", "").replace("__", "").replace('`', "")
+}
@@ -0,0 +1,393 @@
+// ============================================================================
+// FORMATTING LENSES
+// ============================================================================
+
+//! Level-0 lenses that turn structured data into prompt text: markdown
+//! tables, bullet and numbered lists, XML-tagged sections and YAML.
+//!
+//! Map keys are emitted in canonical JSON order (by UTF-16 code units), and
+//! nested non-string values render as canonical JSON, so the same data always
+//! produces the same text. None of the lenses end their output with a newline.
+
+use super::canonical::{canonical_entries, canonical_json, canonical_key_cmp, value_text};
+use crate::{Lens, LensContext, LensError, LensResult, LensSignature, TrustLevel};
+use fct_ast::ValueNode;
+use std::collections::HashMap;
+
+/// to_markdown_table(columns=[...]) - Render a list of maps as a markdown table
+pub struct ToMarkdownTableLens;
+
+impl Lens for ToMarkdownTableLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let rows = expect_list(input)?;
+        let rows = rows
+            .iter()
+            .map(|row| match row {
+                ValueNode::Map(map) => Ok(map),
+                other => Err(LensError::TypeMismatch {
+                    expected: "list<map>".to_string(),
+                    got: format!("{:?}", other),
+                }),
+            })
+            .collect::<LensResult<Vec<_>>>()?;
+
+        // Without `columns`, every key of every row, in canonical order.
+        let columns: Vec<String> = match named_arg("columns", &args, &kwargs) {
+            Some(ValueNode::List(columns)) => columns
+                .iter()
+                .map(|column| match column {
+                    ValueNode::String(name) => Ok(name.clone()),
+                    other => Err(LensError::ArgumentError {
+                        message: format!(
+                            "to_markdown_table columns must be strings, got {:?}",
+                            other
+                        ),
+                    }),
+                })
+                .collect::<LensResult<_>>()?,
+            Some(other) => {
+                return Err(LensError::ArgumentError {
+                    message: format!("to_markdown_table columns must be a list, got {:?}", other),
+                })
+            }
+            None => {
+                let mut columns: Vec<String> = Vec::new();
+                for row in &rows {
+                    for key in row.keys() {
+                        if !columns.contains(key) {
+                            columns.push(key.clone());
+                        }
+                    }
+                }
+                columns.sort_by(|a, b| canonical_key_cmp(a, b));
+                columns
+            }
+        };
+        if columns.is_empty() {
+            return Ok(ValueNode::String(String::new()));
+        }
+
+        let line = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+        let mut lines = vec![
+            line(columns.iter().map(|c| table_cell(c)).collect()),
+            line(vec!["---".to_string(); columns.len()]),
+        ];
+        for row in rows {
+            lines.push(line(
+                columns
+                    .iter()
+                    .map(|column| row.get(column).map(value_text).unwrap_or_default())
+                    .map(|text| table_cell(&text))
+                    .collect(),
+            ));
+        }
+        Ok(ValueNode::String(lines.join("\n")))
+    }
+
+    fn signature(&self) -> LensSignature {
+        text_signature("to_markdown_table", "list")
+    }
+}
+
+/// to_bullets(field) - Render a list as `- item` lines
+pub struct ToBulletsLens;
+
+impl Lens for ToBulletsLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let items = item_texts("to_bullets", input, &args, &kwargs)?;
+        Ok(ValueNode::String(list_lines(items, |_| "- ".to_string())))
+    }
+
+    fn signature(&self) -> LensSignature {
+        text_signature("to_bullets", "list")
+    }
+}
+
+/// to_numbered(field) - Render a list as `1. item` lines
+pub struct ToNumberedLens;
+
+impl Lens for ToNumberedLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let items = item_texts("to_numbered", input, &args, &kwargs)?;
+        Ok(ValueNode::String(list_lines(items, |i| {
+            format!("{}. ", i + 1)
+        })))
+    }
+
+    fn signature(&self) -> LensSignature {
+        text_signature("to_numbered", "list")
+    }
+}
+
+/// xml_wrap(tag) - Wrap the input in `<tag>` ... `</tag>` lines
+pub struct XmlWrapLens;
+
+impl Lens for XmlWrapLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let tag = match named_arg("tag", &args, &kwargs) {
+            Some(ValueNode::String(tag)) if is_xml_name(tag) => tag,
+            Some(other) => {
+                return Err(LensError::ArgumentError {
+                    message: format!("xml_wrap tag must be an XML name, got {:?}", other),
+                })
+            }
+            None => {
+                return Err(LensError::ArgumentError {
+                    message: "xml_wrap() requires a tag".to_string(),
+                })
+            }
+        };
+        // The content is not XML-escaped: tags delimit prompt sections, they
+        // do not make the text an XML document. Only a closing tag for this
+        // section is neutralized, so the content cannot end it early.
+        Ok(ValueNode::String(format!(
+            "<{tag}>\n{}\n</{tag}>",
+            neutralize_closing_tag(&value_text(&input), tag)
+        )))
+    }
+
+    fn signature(&self) -> LensSignature {
+        text_signature("xml_wrap", "any")
+    }
+}
+
+/// to_yaml() - Render a value as block-style YAML
+pub struct ToYamlLens;
+
+impl Lens for ToYamlLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        _args: Vec<ValueNode>,
+        _kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        Ok(ValueNode::String(yaml_lines(&input).join("\n")))
+    }
+
+    fn signature(&self) -> LensSignature {
+        text_signature("to_yaml", "any")
+    }
+}
+
+fn text_signature(name: &str, input_type: &str) -> LensSignature {
+    LensSignature {
+        name: name.to_string(),
+        input_type: input_type.to_string(),
+        output_type: "string".to_string(),
+        trust_level: TrustLevel::Pure,
+        deterministic: true,
+    }
+}
+
+/// A keyword argument, or the first positional argument in its place.
+fn named_arg<'a>(
+    name: &str,
+    args: &'a [ValueNode],
+    kwargs: &'a HashMap<String, ValueNode>,
+) -> Option<&'a ValueNode> {
+    kwargs.get(name).or_else(|| args.first())
+}
+
+fn expect_list(input: ValueNode) -> LensResult<Vec<ValueNode>> {
+    match input {
+        ValueNode::List(items) => Ok(items),
+        other => Err(LensError::TypeMismatch {
+            expected: "list".to_string(),
+            got: format!("{:?}", other),
+        }),
+    }
+}
+
+/// Text of each list item, or of its `field` when one is given.
+fn item_texts(
+    lens: &str,
+    input: ValueNode,
+    args: &[ValueNode],
+    kwargs: &HashMap<String, ValueNode>,
+) -> LensResult<Vec<String>> {
+    let items = expect_list(input)?;
+    let field = match named_arg("field", args, kwargs) {
+        None => None,
+        Some(ValueNode::String(field)) => Some(field.as_str()),
+        Some(other) => {
+            return Err(LensError::ArgumentError {
+                message: format!("{} field must be a string, got {:?}", lens, other),
+            })
+        }
+    };
+
+    items
+        .iter()
+        .map(|item| match (field, item) {
+            (None, item) => Ok(value_text(item)),
+            (Some(field), ValueNode::Map(map)) => {
+                Ok(map.get(field).map(value_text).unwrap_or_default())
+            }
+            (Some(_), other) => Err(LensError::TypeMismatch {
+                expected: "list<map>".to_string(),
+                got: format!("{:?}", other),
+            }),
+        })
+        .collect()
+}
+
+/// One `marker + item` entry per item; continuation lines of multi-line
+/// items are indented to align with the first.
+fn list_lines(items: Vec<String>, marker: impl Fn(usize) -> String) -> String {
+    let mut out = Vec::with_capacity(items.len());
+    for (i, item) in items.iter().enumerate() {
+        let marker = marker(i);
+        let indent = " ".repeat(marker.len());
+        let mut lines = item.split('\n');
+        let first = lines.next().unwrap_or_default();
+        let mut entry = format!("{}{}", marker, first);
+        for line in lines {
+            entry.push('\n');
+            if !line.is_empty() {
+                entry.push_str(&indent);
+                entry.push_str(line);
+            }
+        }
+        out.push(entry);
+    }
+    out.join("\n")
+}
+
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+/// `text` with every `</tag` (ASCII case-insensitive) written as `&lt;/tag`.
+fn neutralize_closing_tag(text: &str, tag: &str) -> String {
+    let closing = format!("</{}", tag).to_ascii_lowercase();
+    // ASCII lowercasing keeps byte offsets, so matches index `text` directly.
+    let lowered = text.to_ascii_lowercase();
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    while let Some(offset) = lowered[pos..].find(&closing) {
+        let start = pos + offset;
+        out.push_str(&text[pos..start]);
+        out.push_str("&lt;/");
+        pos = start + 2;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+fn is_xml_name(tag: &str) -> bool {
+    let mut chars = tag.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+// ----------------------------------------------------------------------------
+// YAML
+// ----------------------------------------------------------------------------
+
+fn yaml_lines(value: &ValueNode) -> Vec<String> {
+    match value {
+        ValueNode::Map(map) if !map.is_empty() => {
+            let mut lines = Vec::new();
+            for (key, value) in canonical_entries(map) {
+                let key = yaml_scalar_string(key);
+                if is_yaml_block(value) {
+                    lines.push(format!("{}:", key));
+                    lines.extend(
+                        yaml_lines(value)
+                            .into_iter()
+                            .map(|line| format!("  {}", line)),
+                    );
+                } else {
+                    lines.push(format!("{}: {}", key, yaml_inline(value)));
+                }
+            }
+            lines
+        }
+        ValueNode::List(items) if !items.is_empty() => {
+            let mut lines = Vec::new();
+            for item in items {
+                // Block items continue on the `- ` line: `- key: value`.
+                for (i, line) in yaml_lines(item).into_iter().enumerate() {
+                    let prefix = if i == 0 { "- " } else { "  " };
+                    lines.push(format!("{}{}", prefix, line));
+                }
+            }
+            lines
+        }
+        other => vec![yaml_inline(other)],
+    }
+}
+
+fn is_yaml_block(value: &ValueNode) -> bool {
+    match value {
+        ValueNode::Map(map) => !map.is_empty(),
+        ValueNode::List(items) => !items.is_empty(),
+        _ => false,
+    }
+}
+
+fn yaml_inline(value: &ValueNode) -> String {
+    match value {
+        ValueNode::String(s) => yaml_scalar_string(s),
+        ValueNode::Map(_) => "{}".to_string(),
+        ValueNode::List(_) => "[]".to_string(),
+        other => canonical_json(other),
+    }
+}
+
+/// A plain scalar when YAML would read it back as the same string, otherwise
+/// a double-quoted (JSON-compatible) one.
+fn yaml_scalar_string(s: &str) -> String {
+    if is_plain_yaml_string(s) {
+        s.to_string()
+    } else {
+        serde_json::Value::String(s.to_string()).to_string()
+    }
+}
+
+fn is_plain_yaml_string(s: &str) -> bool {
+    let Some(first) = s.chars().next() else {
+        return false;
+    };
+    let reserved = matches!(
+        s.to_ascii_lowercase().as_str(),
+        "null" | "~" | "true" | "false" | "yes" | "no" | "on" | "off" | "y" | "n"
+    );
+    !reserved
+        && s.parse::<f64>().is_err()
+        && !first.is_ascii_digit()
+        && !"-+.?:,[]{}#&*!|>'\"%@`".contains(first)
+        && !first.is_whitespace()
+        && !s.ends_with(char::is_whitespace)
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && !s.ends_with(':')
+        && !s.chars().any(char::is_control)
+}
@@ -7,6 +7,9 @@ use fct_ast::{ScalarValue, ValueNode};
 use std::collections::HashMap;
 
 /// map(operation) - Transform list elements
+///
+/// `map(field="f")`, or `map("f")` for any name but the `to_string` operation,
+/// picks field `f` of every map item.
 pub struct MapLens;
 
 impl Lens for MapLens {
@@ -27,7 +30,11 @@ impl Lens for MapLens {
             }
         };
 
-        if let Some(field_value) = kwargs.get("field") {
+        let field = kwargs.get("field").or(match args.first() {
+            Some(ValueNode::String(op)) if op != "to_string" => args.first(),
+            _ => None,
+        });
+        if let Some(field_value) = field {
             let field_name = match field_value {
                 ValueNode::String(name) => name.as_str(),
                 _ => {
@@ -73,18 +80,9 @@ impl Lens for MapLens {
                     // In full implementation, this would support more complex operations
                     mapped_items.push(item.clone());
                 }
-                ValueNode::String(op) => {
-                    // String-based operations
-                    match op.as_str() {
-                        "to_string" => {
-                            mapped_items.push(ValueNode::String(format!("{:?}", item)));
-                        }
-                        _ => {
-                            return Err(LensError::ArgumentError {
-                                message: format!("Unknown map operation: {}", op),
-                            });
-                        }
-                    }
+                ValueNode::String(_) => {
+                    // `to_string`; any other string is a field, handled above
+                    mapped_items.push(ValueNode::String(format!("{:?}", item)));
                 }
                 _ => {
                     return Err(LensError::ArgumentError {
@@ -650,4 +648,36 @@ mod tests {
             ValueNode::List(vec![ValueNode::Scalar(ScalarValue::Null)])
         );
     }
+
+    #[test]
+    fn map_takes_the_field_positionally() {
+        let lens = MapLens;
+        let mut row = OrderedMap::new();
+        row.insert("title".to_string(), ValueNode::String("A".to_string()));
+        let input = ValueNode::List(vec![ValueNode::Map(row)]);
+
+        let out = lens
+            .execute(
+                input.clone(),
+                vec![ValueNode::String("title".to_string())],
+                HashMap::new(),
+                &LensContext::new(),
+            )
+            .expect("positional field map should succeed");
+        assert_eq!(
+            out,
+            ValueNode::List(vec![ValueNode::String("A".to_string())])
+        );
+
+        // `to_string` stays an operation rather than a field name.
+        let out = lens
+            .execute(
+                input,
+                vec![ValueNode::String("to_string".to_string())],
+                HashMap::new(),
+                &LensContext::new(),
+            )
+            .expect("to_string map should succeed");
+        assert!(matches!(out, ValueNode::List(items) if matches!(items[0], ValueNode::String(_))));
+    }
 }
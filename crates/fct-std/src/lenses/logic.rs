@@ -0,0 +1,377 @@
+// ============================================================================
+// LOGIC LENSES
+// ============================================================================
+
+//! Level-0 comparison, boolean and conditional lenses. They compute the bools
+//! that `when` gates and `@policy` conditions read, e.g.
+//! `has_docs: $docs |> length() |> gt(0)`.
+//!
+//! Booleans are strict: `not`, `and`, `or` and `if_else` take `bool` values
+//! only, with no truthiness of strings, numbers or lists.
+
+use crate::{Lens, LensContext, LensError, LensResult, LensSignature, TrustLevel};
+use fct_ast::{ScalarValue, ValueNode};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// eq(value) - Whether the input equals `value`
+pub struct EqLens;
+
+impl Lens for EqLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        _kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let other = required_arg("eq", &args, 0)?;
+        Ok(bool_value(values_equal(&input, other)))
+    }
+
+    fn signature(&self) -> LensSignature {
+        predicate_signature("eq", "any")
+    }
+}
+
+/// ne(value) - Whether the input differs from `value`
+pub struct NeLens;
+
+impl Lens for NeLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        _kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let other = required_arg("ne", &args, 0)?;
+        Ok(bool_value(!values_equal(&input, other)))
+    }
+
+    fn signature(&self) -> LensSignature {
+        predicate_signature("ne", "any")
+    }
+}
+
+/// gt(value) - Whether the input is greater than `value`
+pub struct GtLens;
+
+impl Lens for GtLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        _kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let ordering = compare_values(&input, required_arg("gt", &args, 0)?)?;
+        Ok(bool_value(ordering == Ordering::Greater))
+    }
+
+    fn signature(&self) -> LensSignature {
+        predicate_signature("gt", "number|string")
+    }
+}
+
+/// gte(value) - Whether the input is greater than or equal to `value`
+pub struct GteLens;
+
+impl Lens for GteLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        _kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let ordering = compare_values(&input, required_arg("gte", &args, 0)?)?;
+        Ok(bool_value(ordering != Ordering::Less))
+    }
+
+    fn signature(&self) -> LensSignature {
+        predicate_signature("gte", "number|string")
+    }
+}
+
+/// lt(value) - Whether the input is less than `value`
+pub struct LtLens;
+
+impl Lens for LtLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        _kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let ordering = compare_values(&input, required_arg("lt", &args, 0)?)?;
+        Ok(bool_value(ordering == Ordering::Less))
+    }
+
+    fn signature(&self) -> LensSignature {
+        predicate_signature("lt", "number|string")
+    }
+}
+
+/// lte(value) - Whether the input is less than or equal to `value`
+pub struct LteLens;
+
+impl Lens for LteLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        _kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let ordering = compare_values(&input, required_arg("lte", &args, 0)?)?;
+        Ok(bool_value(ordering != Ordering::Greater))
+    }
+
+    fn signature(&self) -> LensSignature {
+        predicate_signature("lte", "number|string")
+    }
+}
+
+/// contains(value) - Substring of a string, element of a list, or key of a map
+pub struct ContainsLens;
+
+impl Lens for ContainsLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        _kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let needle = required_arg("contains", &args, 0)?;
+        let found = match (&input, needle) {
+            (ValueNode::String(haystack), ValueNode::String(needle)) => haystack.contains(needle),
+            (ValueNode::List(items), needle) => items.iter().any(|item| values_equal(item, needle)),
+            (ValueNode::Map(map), ValueNode::String(key)) => map.contains_key(key),
+            (ValueNode::String(_) | ValueNode::Map(_), other) => {
+                return Err(LensError::ArgumentError {
+                    message: format!(
+                        "contains() on a string or map needs a string, got {:?}",
+                        other
+                    ),
+                })
+            }
+            (other, _) => {
+                return Err(LensError::TypeMismatch {
+                    expected: "string, list or map".to_string(),
+                    got: format!("{:?}", other),
+                })
+            }
+        };
+        Ok(bool_value(found))
+    }
+
+    fn signature(&self) -> LensSignature {
+        predicate_signature("contains", "string|list|map")
+    }
+}
+
+/// is_empty() - Whether a string, list or map has no items; `null` is empty
+pub struct IsEmptyLens;
+
+impl Lens for IsEmptyLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        _args: Vec<ValueNode>,
+        _kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let empty = match &input {
+            ValueNode::String(s) => s.is_empty(),
+            ValueNode::List(items) => items.is_empty(),
+            ValueNode::Map(map) => map.is_empty(),
+            ValueNode::Scalar(ScalarValue::Null) => true,
+            other => {
+                return Err(LensError::TypeMismatch {
+                    expected: "string, list, map or null".to_string(),
+                    got: format!("{:?}", other),
+                })
+            }
+        };
+        Ok(bool_value(empty))
+    }
+
+    fn signature(&self) -> LensSignature {
+        predicate_signature("is_empty", "string|list|map|null")
+    }
+}
+
+/// not() - Boolean negation
+pub struct NotLens;
+
+impl Lens for NotLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        _args: Vec<ValueNode>,
+        _kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        Ok(bool_value(!expect_bool("not", &input)?))
+    }
+
+    fn signature(&self) -> LensSignature {
+        predicate_signature("not", "bool")
+    }
+}
+
+/// and(other, ...) - Whether the input and every argument are true
+pub struct AndLens;
+
+impl Lens for AndLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        _kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        // Every operand is checked, so a non-bool is an error even after a false.
+        let mut result = expect_bool("and", &input)?;
+        required_arg("and", &args, 0)?;
+        for arg in &args {
+            result &= expect_bool("and", arg)?;
+        }
+        Ok(bool_value(result))
+    }
+
+    fn signature(&self) -> LensSignature {
+        predicate_signature("and", "bool")
+    }
+}
+
+/// or(other, ...) - Whether the input or any argument is true
+pub struct OrLens;
+
+impl Lens for OrLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        _kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let mut result = expect_bool("or", &input)?;
+        required_arg("or", &args, 0)?;
+        for arg in &args {
+            result |= expect_bool("or", arg)?;
+        }
+        Ok(bool_value(result))
+    }
+
+    fn signature(&self) -> LensSignature {
+        predicate_signature("or", "bool")
+    }
+}
+
+/// if_else(then, else) - `then` when the input is true, otherwise `else`
+pub struct IfElseLens;
+
+impl Lens for IfElseLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        _kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        let condition = expect_bool("if_else", &input)?;
+        let then_value = required_arg("if_else", &args, 0)?;
+        let else_value = required_arg("if_else", &args, 1)?;
+        Ok(if condition { then_value } else { else_value }.clone())
+    }
+
+    fn signature(&self) -> LensSignature {
+        LensSignature {
+            name: "if_else".to_string(),
+            input_type: "bool".to_string(),
+            output_type: "any".to_string(),
+            trust_level: TrustLevel::Pure,
+            deterministic: true,
+        }
+    }
+}
+
+fn predicate_signature(name: &str, input_type: &str) -> LensSignature {
+    LensSignature {
+        name: name.to_string(),
+        input_type: input_type.to_string(),
+        output_type: "bool".to_string(),
+        trust_level: TrustLevel::Pure,
+        deterministic: true,
+    }
+}
+
+fn bool_value(value: bool) -> ValueNode {
+    ValueNode::Scalar(ScalarValue::Bool(value))
+}
+
+fn required_arg<'a>(lens: &str, args: &'a [ValueNode], index: usize) -> LensResult<&'a ValueNode> {
+    args.get(index).ok_or_else(|| LensError::ArgumentError {
+        message: format!("{}() requires argument {}", lens, index + 1),
+    })
+}
+
+fn expect_bool(lens: &str, value: &ValueNode) -> LensResult<bool> {
+    match value {
+        ValueNode::Scalar(ScalarValue::Bool(b)) => Ok(*b),
+        other => Err(LensError::TypeMismatch {
+            expected: format!("bool for {}()", lens),
+            got: format!("{:?}", other),
+        }),
+    }
+}
+
+/// Equality under canonical JSON rules: numbers compare by value (`1 == 1.0`)
+/// and map key order does not matter.
+fn values_equal(left: &ValueNode, right: &ValueNode) -> bool {
+    match (left, right) {
+        (ValueNode::Scalar(ScalarValue::Int(a)), ValueNode::Scalar(ScalarValue::Float(b)))
+        | (ValueNode::Scalar(ScalarValue::Float(b)), ValueNode::Scalar(ScalarValue::Int(a))) => {
+            *a as f64 == *b
+        }
+        (ValueNode::List(a), ValueNode::List(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(x, y)| values_equal(x, y))
+        }
+        (ValueNode::Map(a), ValueNode::Map(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, x)| b.get(key).is_some_and(|y| values_equal(x, y)))
+        }
+        _ => left == right,
+    }
+}
+
+/// Order two numbers, or two strings by code point.
+fn compare_values(left: &ValueNode, right: &ValueNode) -> LensResult<Ordering> {
+    let ordering = match (left, right) {
+        (ValueNode::Scalar(ScalarValue::Int(a)), ValueNode::Scalar(ScalarValue::Int(b))) => {
+            Some(a.cmp(b))
+        }
+        (ValueNode::String(a), ValueNode::String(b)) => Some(a.cmp(b)),
+        _ => match (as_number(left), as_number(right)) {
+            (Some(a), Some(b)) => a.partial_cmp(&b),
+            _ => None,
+        },
+    };
+    ordering.ok_or_else(|| LensError::TypeMismatch {
+        expected: "two numbers or two strings".to_string(),
+        got: format!("{:?} and {:?}", left, right),
+    })
+}
+
+fn as_number(value: &ValueNode) -> Option<f64> {
+    match value {
+        ValueNode::Scalar(ScalarValue::Int(i)) => Some(*i as f64),
+        ValueNode::Scalar(ScalarValue::Float(f)) => Some(*f),
+        _ => None,
+    }
+}
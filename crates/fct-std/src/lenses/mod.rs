@@ -2,10 +2,15 @@
 // LENSES MODULE - Categorical Organization
 // ============================================================================
 
+mod canonical;
+pub mod compress;
+pub mod format;
 pub mod level1;
 pub mod list;
+pub mod logic;
 pub mod map;
 pub mod string;
+pub mod template;
 pub mod utility;
 
 // Re-export all lens types for convenient access
@@ -0,0 +1,533 @@
+// ============================================================================
+// TEMPLATE LENS
+// ============================================================================
+
+//! Mustache-style `template` lens.
+//!
+//! Unlike Mustache, `{{name}}` is not HTML-escaped unless `escape="html"` is
+//! given: templates render prompt text, not markup.
+//!
+//! Supported tags: `{{name}}`, `{{{name}}}` / `{{& name}}` (never escaped),
+//! `{{#name}}...{{/name}}` sections, `{{^name}}...{{/name}}` inverted
+//! sections, `{{! comment }}` and `{{> partial}}`. Names are dotted paths
+//! (`{{doc.title}}`) resolved against the section stack, and `{{.}}` is the
+//! current item. Custom delimiters (`{{=<% %>=}}`) are not supported.
+//!
+//! The lens is total: a well-formed template renders for every input, with
+//! missing names rendering as nothing. Only malformed templates (unclosed or
+//! mismatched tags) and partials nested deeper than [`MAX_PARTIAL_DEPTH`]
+//! are errors.
+
+use super::canonical::value_text;
+use crate::{Lens, LensContext, LensError, LensResult, LensSignature, TrustLevel};
+use fct_ast::{OrderedMap, ScalarValue, ValueNode};
+use std::collections::HashMap;
+
+/// Deepest `{{> partial}}` nesting rendered before giving up on a cycle.
+pub const MAX_PARTIAL_DEPTH: usize = 16;
+
+/// Output bytes covered by one unit of gas, beyond the base unit.
+const GAS_BYTES_PER_UNIT: usize = 64;
+
+/// template(partials={...}, escape="none|html|json", **data) - Render a
+/// Mustache template against the keyword arguments
+pub struct TemplateLens;
+
+impl Lens for TemplateLens {
+    fn execute(
+        &self,
+        input: ValueNode,
+        _args: Vec<ValueNode>,
+        kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+    ) -> LensResult<ValueNode> {
+        render(&input, &kwargs, usize::MAX).map(ValueNode::String)
+    }
+
+    fn signature(&self) -> LensSignature {
+        LensSignature {
+            name: "template".to_string(),
+            input_type: "string".to_string(),
+            output_type: "string".to_string(),
+            trust_level: TrustLevel::Pure,
+            deterministic: true,
+        }
+    }
+
+    fn version(&self) -> &'static str {
+        "2"
+    }
+
+    /// One unit plus one per started 64 bytes of output, metered while
+    /// rendering: the render stops as soon as its output outgrows the gas
+    /// left, so the template is never rendered just to price it.
+    fn execute_metered(
+        &self,
+        input: ValueNode,
+        _args: Vec<ValueNode>,
+        kwargs: HashMap<String, ValueNode>,
+        _ctx: &LensContext,
+        gas_available: usize,
+    ) -> LensResult<(ValueNode, usize)> {
+        if gas_available == 0 {
+            return Err(LensError::GasExhausted { needed: 1 });
+        }
+        let max_output = (gas_available - 1).saturating_mul(GAS_BYTES_PER_UNIT);
+        let output = render(&input, &kwargs, max_output)?;
+        let gas = output_gas(output.len());
+        Ok((ValueNode::String(output), gas))
+    }
+}
+
+/// How `{{name}}` output is escaped; `{{{name}}}` is never escaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    None,
+    Html,
+    Json,
+}
+
+fn output_gas(output_len: usize) -> usize {
+    1 + output_len.div_ceil(GAS_BYTES_PER_UNIT)
+}
+
+/// Render `input` against `kwargs`, failing with [`LensError::GasExhausted`]
+/// once the output exceeds `max_output` bytes.
+fn render(
+    input: &ValueNode,
+    kwargs: &HashMap<String, ValueNode>,
+    max_output: usize,
+) -> LensResult<String> {
+    let template = match input {
+        ValueNode::String(s) => s,
+        other => {
+            return Err(LensError::TypeMismatch {
+                expected: "string".to_string(),
+                got: format!("{:?}", other),
+            })
+        }
+    };
+
+    let escape = match kwargs.get("escape") {
+        None => Escape::None,
+        Some(ValueNode::String(mode)) => match mode.as_str() {
+            "none" => Escape::None,
+            "html" => Escape::Html,
+            "json" => Escape::Json,
+            other => {
+                return Err(LensError::ArgumentError {
+                    message: format!(
+                        "template escape must be none, html or json, got '{}'",
+                        other
+                    ),
+                })
+            }
+        },
+        Some(other) => {
+            return Err(LensError::ArgumentError {
+                message: format!("template escape must be a string, got {:?}", other),
+            })
+        }
+    };
+
+    let partials = match kwargs.get("partials") {
+        None => OrderedMap::new(),
+        Some(ValueNode::Map(map)) => map.clone(),
+        Some(other) => {
+            return Err(LensError::ArgumentError {
+                message: format!("template partials must be a map, got {:?}", other),
+            })
+        }
+    };
+
+    // Every other keyword argument is template data.
+    let mut names: Vec<&String> = kwargs
+        .keys()
+        .filter(|name| !matches!(name.as_str(), "escape" | "partials"))
+        .collect();
+    names.sort();
+    let data = ValueNode::Map(
+        names
+            .into_iter()
+            .map(|name| (name.clone(), kwargs[name].clone()))
+            .collect(),
+    );
+
+    let renderer = Renderer {
+        escape,
+        partials,
+        max_output,
+    };
+    let nodes = parse(template)?;
+    let mut output = String::new();
+    renderer.render_nodes(&nodes, &mut vec![&data], 0, &mut output)?;
+    Ok(output)
+}
+
+// ----------------------------------------------------------------------------
+// Parsing
+// ----------------------------------------------------------------------------
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Variable {
+        name: String,
+        escaped: bool,
+    },
+    Section {
+        name: String,
+        inverted: bool,
+        children: Vec<Node>,
+    },
+    /// A partial and the indentation of its standalone line.
+    Partial {
+        name: String,
+        indent: String,
+    },
+}
+
+#[derive(Debug)]
+enum Token {
+    Text(String),
+    Variable { name: String, escaped: bool },
+    Open { name: String, inverted: bool },
+    Close(String),
+    Partial { name: String, indent: String },
+}
+
+fn template_error(message: String) -> LensError {
+    LensError::ArgumentError {
+        message: format!("Invalid template: {}", message),
+    }
+}
+
+fn parse(template: &str) -> LensResult<Vec<Node>> {
+    let mut stack: Vec<(String, bool, Vec<Node>)> = Vec::new();
+    let mut nodes = Vec::new();
+
+    for token in tokenize(template)? {
+        let node = match token {
+            Token::Text(text) => Node::Text(text),
+            Token::Variable { name, escaped } => Node::Variable { name, escaped },
+            Token::Partial { name, indent } => Node::Partial { name, indent },
+            Token::Open { name, inverted } => {
+                stack.push((name, inverted, std::mem::take(&mut nodes)));
+                continue;
+            }
+            Token::Close(name) => {
+                let Some((open, inverted, parent)) = stack.pop() else {
+                    return Err(template_error(format!(
+                        "{{{{/{}}}}} closes no section",
+                        name
+                    )));
+                };
+                if open != name {
+                    return Err(template_error(format!(
+                        "{{{{/{}}}}} closes section '{}'",
+                        name, open
+                    )));
+                }
+                let children = std::mem::replace(&mut nodes, parent);
+                Node::Section {
+                    name,
+                    inverted,
+                    children,
+                }
+            }
+        };
+        nodes.push(node);
+    }
+
+    match stack.pop() {
+        Some((open, _, _)) => Err(template_error(format!("section '{}' is not closed", open))),
+        None => Ok(nodes),
+    }
+}
+
+/// Split a template into text and tags. Section, comment and partial tags on
+/// a line of their own ("standalone") take the whole line with them, as in
+/// the Mustache spec, so block templates do not leave blank lines behind.
+fn tokenize(template: &str) -> LensResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while let Some(offset) = template[pos..].find("{{") {
+        let open = pos + offset;
+        let triple = template[open..].starts_with("{{{");
+        let (inner_start, close_delim) = if triple {
+            (open + 3, "}}}")
+        } else {
+            (open + 2, "}}")
+        };
+        let inner_end = template[inner_start..]
+            .find(close_delim)
+            .map(|end| inner_start + end)
+            .ok_or_else(|| template_error(format!("tag at byte {} is not closed", open)))?;
+        let tag_end = inner_end + close_delim.len();
+        let inner = template[inner_start..inner_end].trim();
+
+        let (sigil, name) = if triple {
+            ('&', inner)
+        } else {
+            match inner.chars().next() {
+                Some(c @ ('#' | '^' | '/' | '!' | '>' | '&' | '=')) => (c, inner[1..].trim()),
+                _ => (' ', inner),
+            }
+        };
+        if sigil == '=' {
+            return Err(template_error(
+                "custom delimiters are not supported".to_string(),
+            ));
+        }
+        if name.is_empty() && sigil != '!' {
+            return Err(template_error(format!("empty tag at byte {}", open)));
+        }
+
+        let mut text_end = open;
+        let mut next = tag_end;
+        let mut indent = String::new();
+        if matches!(sigil, '#' | '^' | '/' | '!' | '>') {
+            if let Some((line_start, line_end)) = standalone_line(template, pos, open, tag_end) {
+                indent = template[line_start..open].to_string();
+                text_end = line_start;
+                next = line_end;
+            }
+        }
+        if text_end > pos {
+            tokens.push(Token::Text(template[pos..text_end].to_string()));
+        }
+
+        let name = name.to_string();
+        match sigil {
+            '#' => tokens.push(Token::Open {
+                name,
+                inverted: false,
+            }),
+            '^' => tokens.push(Token::Open {
+                name,
+                inverted: true,
+            }),
+            '/' => tokens.push(Token::Close(name)),
+            '!' => {}
+            '>' => tokens.push(Token::Partial { name, indent }),
+            '&' => tokens.push(Token::Variable {
+                name,
+                escaped: false,
+            }),
+            _ => tokens.push(Token::Variable {
+                name,
+                escaped: true,
+            }),
+        }
+        pos = next;
+    }
+
+    if pos < template.len() {
+        tokens.push(Token::Text(template[pos..].to_string()));
+    }
+    Ok(tokens)
+}
+
+/// `(line_start, next_line_start)` when the tag at `open..tag_end` is the only
+/// non-blank content of its line and no earlier tag (ending at `pos`) shares it.
+fn standalone_line(
+    template: &str,
+    pos: usize,
+    open: usize,
+    tag_end: usize,
+) -> Option<(usize, usize)> {
+    let line_start = template[..open].rfind('\n').map_or(0, |i| i + 1);
+    let is_blank = |s: &str| s.chars().all(|c| c == ' ' || c == '\t' || c == '\r');
+    if line_start < pos || !is_blank(&template[line_start..open]) {
+        return None;
+    }
+    let line_end = template[tag_end..]
+        .find('\n')
+        .map_or(template.len(), |i| tag_end + i + 1);
+    is_blank(template[tag_end..line_end].trim_end_matches('\n')).then_some((line_start, line_end))
+}
+
+// ----------------------------------------------------------------------------
+// Rendering
+// ----------------------------------------------------------------------------
+
+struct Renderer {
+    escape: Escape,
+    partials: OrderedMap<String, ValueNode>,
+    max_output: usize,
+}
+
+impl Renderer {
+    fn render_nodes<'a>(
+        &self,
+        nodes: &'a [Node],
+        stack: &mut Vec<&'a ValueNode>,
+        depth: usize,
+        output: &mut String,
+    ) -> LensResult<()> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => {
+                    output.push_str(text);
+                    self.check_output(output)?;
+                }
+                Node::Variable { name, escaped } => {
+                    if let Some(value) = lookup(stack, name) {
+                        let text = value_text(value);
+                        if *escaped {
+                            escape_into(&text, self.escape, output);
+                        } else {
+                            output.push_str(&text);
+                        }
+                        self.check_output(output)?;
+                    }
+                }
+                Node::Section {
+                    name,
+                    inverted,
+                    children,
+                } => {
+                    let value = lookup(stack, name);
+                    let truthy = value.is_some_and(is_truthy);
+                    if *inverted {
+                        if !truthy {
+                            self.render_nodes(children, stack, depth, output)?;
+                        }
+                        continue;
+                    }
+                    let Some(value) = value.filter(|_| truthy) else {
+                        continue;
+                    };
+                    // Lists repeat the section once per item; anything else
+                    // renders it once with the value on top of the stack.
+                    let items: Vec<&ValueNode> = match value {
+                        ValueNode::List(items) => items.iter().collect(),
+                        other => vec![other],
+                    };
+                    for item in items {
+                        stack.push(item);
+                        let result = self.render_nodes(children, stack, depth, output);
+                        stack.pop();
+                        result?;
+                    }
+                }
+                Node::Partial { name, indent } => {
+                    self.render_partial(name, indent, stack, depth, output)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_output(&self, output: &str) -> LensResult<()> {
+        if output.len() > self.max_output {
+            return Err(LensError::GasExhausted {
+                needed: output_gas(output.len()),
+            });
+        }
+        Ok(())
+    }
+
+    fn render_partial(
+        &self,
+        name: &str,
+        indent: &str,
+        stack: &mut Vec<&ValueNode>,
+        depth: usize,
+        output: &mut String,
+    ) -> LensResult<()> {
+        let source = match self.partials.get(name) {
+            None => return Ok(()),
+            Some(ValueNode::String(source)) => source,
+            Some(other) => {
+                return Err(LensError::ArgumentError {
+                    message: format!("partial '{}' must be a string, got {:?}", name, other),
+                })
+            }
+        };
+        if depth >= MAX_PARTIAL_DEPTH {
+            return Err(template_error(format!(
+                "partials nest deeper than {} at '{}'",
+                MAX_PARTIAL_DEPTH, name
+            )));
+        }
+
+        let indented;
+        let source = if indent.is_empty() {
+            source.as_str()
+        } else {
+            indented = indent_lines(source, indent);
+            indented.as_str()
+        };
+        let nodes = parse(source)?;
+        let mut frames: Vec<&ValueNode> = stack.to_vec();
+        self.render_nodes(&nodes, &mut frames, depth + 1, output)
+    }
+}
+
+/// Resolve `name` against the section stack: `.` is the top frame; otherwise
+/// the first segment is looked up from the top frame down, and the remaining
+/// segments inside the value found.
+fn lookup<'a>(stack: &[&'a ValueNode], name: &str) -> Option<&'a ValueNode> {
+    if name == "." {
+        return stack.last().copied();
+    }
+    let mut segments = name.split('.');
+    let first = segments.next()?;
+    let mut value = stack.iter().rev().find_map(|frame| match frame {
+        ValueNode::Map(map) => map.get(first),
+        _ => None,
+    })?;
+    for segment in segments {
+        value = match value {
+            ValueNode::Map(map) => map.get(segment)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+/// `false`, `null`, `""` and `[]` skip a section; everything else renders it.
+fn is_truthy(value: &ValueNode) -> bool {
+    match value {
+        ValueNode::Scalar(ScalarValue::Bool(b)) => *b,
+        ValueNode::Scalar(ScalarValue::Null) => false,
+        ValueNode::String(s) => !s.is_empty(),
+        ValueNode::List(items) => !items.is_empty(),
+        _ => true,
+    }
+}
+
+fn escape_into(text: &str, escape: Escape, output: &mut String) {
+    match escape {
+        Escape::None => output.push_str(text),
+        Escape::Html => {
+            for c in text.chars() {
+                match c {
+                    '&' => output.push_str("&amp;"),
+                    '<' => output.push_str("&lt;"),
+                    '>' => output.push_str("&gt;"),
+                    '"' => output.push_str("&quot;"),
+                    '\'' => output.push_str("&#39;"),
+                    c => output.push(c),
+                }
+            }
+        }
+        Escape::Json => {
+            let quoted = serde_json::Value::String(text.to_string()).to_string();
+            output.push_str(&quoted[1..quoted.len() - 1]);
+        }
+    }
+}
+
+fn indent_lines(source: &str, indent: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    for (i, line) in source.split_inclusive('\n').enumerate() {
+        if i == 0 || !line.is_empty() {
+            out.push_str(indent);
+        }
+        out.push_str(line);
+    }
+    out
+}
@@ -309,55 +309,3 @@ impl Lens for HashLens {
         }
     }
 }
-
-/// template(**kwargs) - Simple template rendering with variable substitution
-/// Supports {{variable}} syntax
-pub struct TemplateLens;
-
-impl Lens for TemplateLens {
-    fn execute(
-        &self,
-        input: ValueNode,
-        _args: Vec<ValueNode>,
-        kwargs: HashMap<String, ValueNode>,
-        _ctx: &LensContext,
-    ) -> LensResult<ValueNode> {
-        let template_str = match input {
-            ValueNode::String(s) => s,
-            other => {
-                return Err(LensError::TypeMismatch {
-                    expected: "string".to_string(),
-                    got: format!("{:?}", other),
-                })
-            }
-        };
-
-        let mut result = template_str.clone();
-
-        // Replace {{variable}} patterns with kwargs values
-        for (key, value) in kwargs.iter() {
-            let placeholder = format!("{{{{{}}}}}", key);
-            let replacement = match value {
-                ValueNode::String(s) => s.clone(),
-                ValueNode::Scalar(ScalarValue::Int(n)) => n.to_string(),
-                ValueNode::Scalar(ScalarValue::Float(f)) => f.to_string(),
-                ValueNode::Scalar(ScalarValue::Bool(b)) => b.to_string(),
-                ValueNode::Scalar(ScalarValue::Null) => "null".to_string(),
-                _ => format!("{:?}", value), // Fallback for complex types
-            };
-            result = result.replace(&placeholder, &replacement);
-        }
-
-        Ok(ValueNode::String(result))
-    }
-
-    fn signature(&self) -> LensSignature {
-        LensSignature {
-            name: "template".to_string(),
-            input_type: "string".to_string(),
-            output_type: "string".to_string(),
-            trust_level: TrustLevel::Pure,
-            deterministic: true,
-        }
-    }
-}
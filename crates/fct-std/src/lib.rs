@@ -18,6 +18,9 @@ pub enum LensError {
 
     #[error("Execution error: {message}")]
     ExecutionError { message: String },
+
+    #[error("Gas exhausted: needs at least {needed} units")]
+    GasExhausted { needed: usize },
 }
 
 pub type LensResult<T> = Result<T, LensError>;
@@ -128,6 +131,26 @@ pub trait Lens: Send + Sync {
         1
     }
 
+    /// Execute within `gas_available` units, returning the output and the gas
+    /// used. The default charges [`Lens::gas_cost`] before executing; lenses
+    /// whose cost depends on their output override this to meter as they go
+    /// and fail with [`LensError::GasExhausted`] once past the budget.
+    fn execute_metered(
+        &self,
+        input: ValueNode,
+        args: Vec<ValueNode>,
+        kwargs: HashMap<String, ValueNode>,
+        ctx: &LensContext,
+        gas_available: usize,
+    ) -> LensResult<(ValueNode, usize)> {
+        let gas = self.gas_cost(&input, &args, &kwargs);
+        if gas > gas_available {
+            return Err(LensError::GasExhausted { needed: gas });
+        }
+        self.execute(input, args, kwargs, ctx)
+            .map(|output| (output, gas))
+    }
+
     /// Optional effect class for policy/guard classification.
     /// Level-1/2 lenses are expected to override this.
     fn effect_class(&self) -> Option<&'static str> {
@@ -143,19 +166,27 @@ mod lenses;
 
 // Re-export all lens types
 pub use lenses::{
+    compress::{
+        item_score, lowest_score_order, CollapseWhitespaceLens, DropLowestLens, KeepHeadTailLens,
+        StripMarkdownLens, TruncateSentencesLens,
+    },
+    format::{ToBulletsLens, ToMarkdownTableLens, ToNumberedLens, ToYamlLens, XmlWrapLens},
     level1::{EmbeddingLens, LlmCallLens, RagSearchLens},
     list::{
         EnsureListLens, FilterLens, FirstLens, JoinLens, LastLens, LengthLens, MapLens, NthLens,
         SliceLens, SortByLens, UniqueLens,
     },
+    logic::{
+        AndLens, ContainsLens, EqLens, GtLens, GteLens, IfElseLens, IsEmptyLens, LtLens, LteLens,
+        NeLens, NotLens, OrLens,
+    },
     map::{KeysLens, ValuesLens},
     string::{
         CapitalizeLens, IndentLens, LowercaseLens, ReplaceLens, ReverseLens, SplitLens,
         SubstringLens, TrimLens, UppercaseLens,
     },
-    utility::{
-        DefaultLens, HashLens, JsonLens, JsonParseLens, TemplateLens, UrlDecodeLens, UrlEncodeLens,
-    },
+    template::TemplateLens,
+    utility::{DefaultLens, HashLens, JsonLens, JsonParseLens, UrlDecodeLens, UrlEncodeLens},
 };
 
 // ============================================================================
@@ -197,6 +228,34 @@ impl LensRegistry {
         registry.register(Box::new(UniqueLens));
         registry.register(Box::new(JoinLens));
 
+        // Register logic lenses
+        registry.register(Box::new(EqLens));
+        registry.register(Box::new(NeLens));
+        registry.register(Box::new(GtLens));
+        registry.register(Box::new(GteLens));
+        registry.register(Box::new(LtLens));
+        registry.register(Box::new(LteLens));
+        registry.register(Box::new(ContainsLens));
+        registry.register(Box::new(IsEmptyLens));
+        registry.register(Box::new(NotLens));
+        registry.register(Box::new(AndLens));
+        registry.register(Box::new(OrLens));
+        registry.register(Box::new(IfElseLens));
+
+        // Register layout compression lenses
+        registry.register(Box::new(TruncateSentencesLens));
+        registry.register(Box::new(KeepHeadTailLens));
+        registry.register(Box::new(CollapseWhitespaceLens));
+        registry.register(Box::new(StripMarkdownLens));
+        registry.register(Box::new(DropLowestLens));
+
+        // Register formatting lenses
+        registry.register(Box::new(ToMarkdownTableLens));
+        registry.register(Box::new(ToBulletsLens));
+        registry.register(Box::new(ToNumberedLens));
+        registry.register(Box::new(XmlWrapLens));
+        registry.register(Box::new(ToYamlLens));
+
         // Register map lenses
         registry.register(Box::new(KeysLens));
         registry.register(Box::new(ValuesLens));
@@ -674,6 +733,31 @@ mod tests {
             "length",
             "unique",
             "join",
+            // Logic lenses
+            "eq",
+            "ne",
+            "gt",
+            "gte",
+            "lt",
+            "lte",
+            "contains",
+            "is_empty",
+            "not",
+            "and",
+            "or",
+            "if_else",
+            // Layout compression lenses
+            "truncate_sentences",
+            "keep_head_tail",
+            "collapse_whitespace",
+            "strip_markdown",
+            "drop_lowest",
+            // Formatting lenses
+            "to_markdown_table",
+            "to_bullets",
+            "to_numbered",
+            "xml_wrap",
+            "to_yaml",
             // Map lenses
             "keys",
             "values",
@@ -699,7 +783,7 @@ mod tests {
             );
         }
 
-        assert_eq!(lenses.len(), 32); // 9 string + 11 list + 2 map + 7 utility + 3 level1
+        assert_eq!(lenses.len(), 54); // 9 string + 11 list + 12 logic + 5 compression + 5 formatting + 2 map + 7 utility + 3 level1
     }
 
     #[test]
@@ -1173,6 +1257,166 @@ mod tests {
         assert_eq!(result3, input3);
     }
 
+    #[test]
+    fn test_template_sections_and_paths() {
+        let ctx = LensContext::new();
+        let render = |template: &str, kwargs: Vec<(&str, ValueNode)>| {
+            let kwargs = kwargs
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect();
+            TemplateLens.execute(
+                ValueNode::String(template.to_string()),
+                vec![],
+                kwargs,
+                &ctx,
+            )
+        };
+        let text = |s: &str| ValueNode::String(s.to_string());
+        let doc = |title: &str| {
+            ValueNode::Map(fct_ast::OrderedMap::from([(
+                "title".to_string(),
+                text(title),
+            )]))
+        };
+        let docs = ValueNode::List(vec![doc("A"), doc("B")]);
+
+        // Sections repeat per list item and drop standalone tag lines.
+        assert_eq!(
+            render(
+                "{{#docs}}\n- {{title}}\n{{/docs}}\n",
+                vec![("docs", docs.clone())]
+            )
+            .unwrap(),
+            text("- A\n- B\n")
+        );
+        // Inverted sections render for empty lists, false and missing names.
+        let none = "{{^docs}}none{{/docs}}";
+        assert_eq!(
+            render(none, vec![("docs", ValueNode::List(vec![]))]).unwrap(),
+            text("none")
+        );
+        assert_eq!(render(none, vec![]).unwrap(), text("none"));
+        assert_eq!(
+            render(none, vec![("docs", docs.clone())]).unwrap(),
+            text("")
+        );
+
+        // Dotted paths, `{{.}}`, outer-frame lookup and JSON for collections.
+        assert_eq!(
+            render("{{doc.title}} {{doc.missing}}|", vec![("doc", doc("T"))]).unwrap(),
+            text("T |")
+        );
+        assert_eq!(
+            render(
+                "{{#tags}}{{.}}/{{sep}} {{/tags}}",
+                vec![
+                    ("tags", ValueNode::List(vec![text("x"), text("y")])),
+                    ("sep", text(";")),
+                ]
+            )
+            .unwrap(),
+            text("x/; y/; ")
+        );
+        assert_eq!(
+            render("{{doc}}", vec![("doc", doc("T"))]).unwrap(),
+            text(r#"{"title":"T"}"#)
+        );
+
+        // Malformed templates are the only failures.
+        assert!(render("{{#docs}}open", vec![]).is_err());
+        assert!(render("{{#a}}{{/b}}", vec![]).is_err());
+        assert!(render("{{name", vec![]).is_err());
+        assert!(render("{{=<% %>=}}", vec![]).is_err());
+    }
+
+    #[test]
+    fn test_template_partials_escaping_and_gas() {
+        let ctx = LensContext::new();
+        let text = |s: &str| ValueNode::String(s.to_string());
+        let partials = |entries: Vec<(&str, &str)>| {
+            ValueNode::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), text(v)))
+                    .collect(),
+            )
+        };
+
+        let kwargs = HashMap::from([
+            (
+                "partials".to_string(),
+                partials(vec![("item", "* {{.}}\n")]),
+            ),
+            (
+                "items".to_string(),
+                ValueNode::List(vec![text("a"), text("b")]),
+            ),
+        ]);
+        let result = TemplateLens
+            .execute(
+                text("{{#items}}\n  {{> item}}\n{{/items}}"),
+                vec![],
+                kwargs,
+                &ctx,
+            )
+            .unwrap();
+        assert_eq!(result, text("  * a\n  * b\n"));
+
+        // A partial that includes itself stops at the depth limit.
+        let kwargs = HashMap::from([(
+            "partials".to_string(),
+            partials(vec![("loop", "{{> loop}}")]),
+        )]);
+        assert!(TemplateLens
+            .execute(text("{{> loop}}"), vec![], kwargs, &ctx)
+            .is_err());
+
+        // `escape` applies to `{{name}}` only; triple mustaches stay raw.
+        let kwargs = |escape: &str| {
+            HashMap::from([
+                ("escape".to_string(), text(escape)),
+                ("v".to_string(), text("<a href=\"x\">")),
+            ])
+        };
+        let template = text("{{v}} {{{v}}}");
+        assert_eq!(
+            TemplateLens
+                .execute(template.clone(), vec![], kwargs("html"), &ctx)
+                .unwrap(),
+            text("&lt;a href=&quot;x&quot;&gt; <a href=\"x\">")
+        );
+        assert_eq!(
+            TemplateLens
+                .execute(template.clone(), vec![], kwargs("json"), &ctx)
+                .unwrap(),
+            text("<a href=\\\"x\\\"> <a href=\"x\">")
+        );
+        assert!(TemplateLens
+            .execute(template, vec![], kwargs("xml"), &ctx)
+            .is_err());
+
+        // Gas grows with the rendered output, not the template, and a render
+        // that outgrows the gas left stops there.
+        let small = HashMap::from([("v".to_string(), text("x"))]);
+        let large = HashMap::from([("v".to_string(), text(&"x".repeat(640)))]);
+        let template = text("{{v}}");
+        let metered = |kwargs: &HashMap<String, ValueNode>, gas: usize| {
+            TemplateLens.execute_metered(template.clone(), vec![], kwargs.clone(), &ctx, gas)
+        };
+        assert_eq!(metered(&small, 100).unwrap().1, 2);
+        assert_eq!(metered(&large, 100).unwrap().1, 11);
+        assert_eq!(metered(&large, 11).unwrap().1, 11);
+        assert!(matches!(
+            metered(&large, 10),
+            Err(LensError::GasExhausted { needed: 11 })
+        ));
+        assert!(matches!(
+            metered(&small, 0),
+            Err(LensError::GasExhausted { needed: 1 })
+        ));
+    }
+
     #[test]
     fn test_llm_call_lens() {
         let lens = LlmCallLens;
@@ -1339,6 +1583,485 @@ mod tests {
         assert!(!lens.signature().deterministic);
     }
 
+    #[test]
+    fn test_comparison_lenses() {
+        let ctx = LensContext::new();
+        let run = |lens: &dyn Lens, input: ValueNode, args: Vec<ValueNode>| {
+            lens.execute(input, args, HashMap::new(), &ctx)
+        };
+        let int = |v| ValueNode::Scalar(ScalarValue::Int(v));
+        let yes = ValueNode::Scalar(ScalarValue::Bool(true));
+        let no = ValueNode::Scalar(ScalarValue::Bool(false));
+
+        assert_eq!(run(&GtLens, int(3), vec![int(0)]).unwrap(), yes);
+        assert_eq!(run(&LteLens, int(3), vec![int(0)]).unwrap(), no);
+        assert_eq!(
+            run(
+                &LtLens,
+                int(1),
+                vec![ValueNode::Scalar(ScalarValue::Float(1.5))]
+            )
+            .unwrap(),
+            yes
+        );
+        assert_eq!(
+            run(
+                &GteLens,
+                ValueNode::String("b".into()),
+                vec![ValueNode::String("a".into())]
+            )
+            .unwrap(),
+            yes
+        );
+        assert!(run(&GtLens, ValueNode::String("1".into()), vec![int(0)]).is_err());
+        assert!(run(&GtLens, int(1), vec![]).is_err());
+
+        // Canonical JSON equality: 1 == 1.0, and map key order is ignored.
+        assert_eq!(
+            run(
+                &EqLens,
+                int(1),
+                vec![ValueNode::Scalar(ScalarValue::Float(1.0))]
+            )
+            .unwrap(),
+            yes
+        );
+        let ab = fct_ast::OrderedMap::from([("a".to_string(), int(1)), ("b".to_string(), int(2))]);
+        let ba = fct_ast::OrderedMap::from([("b".to_string(), int(2)), ("a".to_string(), int(1))]);
+        assert_eq!(
+            run(
+                &EqLens,
+                ValueNode::Map(ab.clone()),
+                vec![ValueNode::Map(ba)]
+            )
+            .unwrap(),
+            yes
+        );
+        assert_eq!(
+            run(
+                &NeLens,
+                ValueNode::Map(ab),
+                vec![ValueNode::String("a".into())]
+            )
+            .unwrap(),
+            yes
+        );
+    }
+
+    #[test]
+    fn test_contains_and_is_empty_lenses() {
+        let ctx = LensContext::new();
+        let run = |lens: &dyn Lens, input: ValueNode, args: Vec<ValueNode>| {
+            lens.execute(input, args, HashMap::new(), &ctx)
+        };
+        let yes = ValueNode::Scalar(ScalarValue::Bool(true));
+        let no = ValueNode::Scalar(ScalarValue::Bool(false));
+        let list = ValueNode::List(vec![ValueNode::Scalar(ScalarValue::Int(2))]);
+
+        assert_eq!(
+            run(
+                &ContainsLens,
+                ValueNode::String("facet".into()),
+                vec![ValueNode::String("ce".into())]
+            )
+            .unwrap(),
+            yes
+        );
+        assert_eq!(
+            run(
+                &ContainsLens,
+                list.clone(),
+                vec![ValueNode::Scalar(ScalarValue::Float(2.0))]
+            )
+            .unwrap(),
+            yes
+        );
+        assert!(run(
+            &ContainsLens,
+            ValueNode::Scalar(ScalarValue::Int(2)),
+            vec![list.clone()]
+        )
+        .is_err());
+
+        assert_eq!(run(&IsEmptyLens, list, vec![]).unwrap(), no);
+        assert_eq!(
+            run(&IsEmptyLens, ValueNode::String(String::new()), vec![]).unwrap(),
+            yes
+        );
+        assert_eq!(
+            run(&IsEmptyLens, ValueNode::Scalar(ScalarValue::Null), vec![]).unwrap(),
+            yes
+        );
+        assert!(run(&IsEmptyLens, ValueNode::Scalar(ScalarValue::Int(0)), vec![]).is_err());
+    }
+
+    #[test]
+    fn test_boolean_and_if_else_lenses() {
+        let ctx = LensContext::new();
+        let run = |lens: &dyn Lens, input: ValueNode, args: Vec<ValueNode>| {
+            lens.execute(input, args, HashMap::new(), &ctx)
+        };
+        let yes = ValueNode::Scalar(ScalarValue::Bool(true));
+        let no = ValueNode::Scalar(ScalarValue::Bool(false));
+
+        assert_eq!(run(&NotLens, yes.clone(), vec![]).unwrap(), no);
+        assert_eq!(
+            run(&AndLens, yes.clone(), vec![yes.clone(), no.clone()]).unwrap(),
+            no
+        );
+        assert_eq!(
+            run(&OrLens, no.clone(), vec![no.clone(), yes.clone()]).unwrap(),
+            yes
+        );
+        // No truthiness: every operand must be a bool.
+        assert!(run(&NotLens, ValueNode::String("yes".into()), vec![]).is_err());
+        assert!(run(
+            &AndLens,
+            no.clone(),
+            vec![ValueNode::Scalar(ScalarValue::Int(1))]
+        )
+        .is_err());
+        assert!(run(&OrLens, yes.clone(), vec![]).is_err());
+
+        let branches = vec![
+            ValueNode::String("on".into()),
+            ValueNode::String("off".into()),
+        ];
+        assert_eq!(
+            run(&IfElseLens, no, branches.clone()).unwrap(),
+            ValueNode::String("off".into())
+        );
+        assert!(run(&IfElseLens, yes, branches[..1].to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_markdown_table_lens() {
+        let ctx = LensContext::new();
+        let text = |s: &str| ValueNode::String(s.to_string());
+        let row = |entries: Vec<(&str, ValueNode)>| {
+            ValueNode::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), v))
+                    .collect(),
+            )
+        };
+        let rows = ValueNode::List(vec![
+            row(vec![
+                ("title", text("A|B")),
+                ("score", ValueNode::Scalar(ScalarValue::Int(2))),
+            ]),
+            row(vec![
+                ("title", text("C\nD")),
+                ("tags", ValueNode::List(vec![text("x")])),
+            ]),
+        ]);
+
+        // Without `columns`, every key in canonical order.
+        let all = ToMarkdownTableLens
+            .execute(rows.clone(), vec![], HashMap::new(), &ctx)
+            .unwrap();
+        assert_eq!(
+            all,
+            text(
+                "| score | tags | title |\n| --- | --- | --- |\n| 2 |  | A\\|B |\n|  | [\"x\"] | C<br>D |"
+            )
+        );
+
+        let kwargs = HashMap::from([("columns".to_string(), ValueNode::List(vec![text("title")]))]);
+        let picked = ToMarkdownTableLens
+            .execute(rows, vec![], kwargs, &ctx)
+            .unwrap();
+        assert_eq!(picked, text("| title |\n| --- |\n| A\\|B |\n| C<br>D |"));
+
+        assert!(ToMarkdownTableLens
+            .execute(
+                ValueNode::List(vec![text("x")]),
+                vec![],
+                HashMap::new(),
+                &ctx
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_bullet_and_numbered_lenses() {
+        let ctx = LensContext::new();
+        let text = |s: &str| ValueNode::String(s.to_string());
+        let items = ValueNode::List(vec![
+            text("one"),
+            text("two\nlines"),
+            ValueNode::Scalar(ScalarValue::Int(3)),
+        ]);
+
+        assert_eq!(
+            ToBulletsLens
+                .execute(items.clone(), vec![], HashMap::new(), &ctx)
+                .unwrap(),
+            text("- one\n- two\n  lines\n- 3")
+        );
+        assert_eq!(
+            ToNumberedLens
+                .execute(items, vec![], HashMap::new(), &ctx)
+                .unwrap(),
+            text("1. one\n2. two\n   lines\n3. 3")
+        );
+
+        let docs = ValueNode::List(vec![ValueNode::Map(fct_ast::OrderedMap::from([(
+            "title".to_string(),
+            text("Intro"),
+        )]))]);
+        let field = HashMap::from([("field".to_string(), text("title"))]);
+        assert_eq!(
+            ToBulletsLens
+                .execute(docs, vec![], field.clone(), &ctx)
+                .unwrap(),
+            text("- Intro")
+        );
+        assert!(ToBulletsLens
+            .execute(ValueNode::List(vec![text("x")]), vec![], field, &ctx)
+            .is_err());
+    }
+
+    #[test]
+    fn test_xml_wrap_and_yaml_lenses() {
+        let ctx = LensContext::new();
+        let text = |s: &str| ValueNode::String(s.to_string());
+
+        assert_eq!(
+            XmlWrapLens
+                .execute(text("a < b"), vec![text("context")], HashMap::new(), &ctx)
+                .unwrap(),
+            text("<context>\na < b\n</context>")
+        );
+        // Content cannot close the section early, in any case.
+        assert_eq!(
+            XmlWrapLens
+                .execute(
+                    text("a</doc>b</DOC >"),
+                    vec![text("doc")],
+                    HashMap::new(),
+                    &ctx
+                )
+                .unwrap(),
+            text("<doc>\na&lt;/doc>b&lt;/DOC >\n</doc>")
+        );
+        assert!(XmlWrapLens
+            .execute(text("x"), vec![text("1bad")], HashMap::new(), &ctx)
+            .is_err());
+        assert!(XmlWrapLens
+            .execute(text("x"), vec![], HashMap::new(), &ctx)
+            .is_err());
+
+        let value = ValueNode::Map(fct_ast::OrderedMap::from([
+            ("name".to_string(), text("demo")),
+            (
+                "items".to_string(),
+                ValueNode::List(vec![
+                    ValueNode::Map(fct_ast::OrderedMap::from([
+                        ("id".to_string(), ValueNode::Scalar(ScalarValue::Int(1))),
+                        ("note".to_string(), text("yes")),
+                    ])),
+                    text("a: b"),
+                ]),
+            ),
+            ("empty".to_string(), ValueNode::List(vec![])),
+            (
+                "flag".to_string(),
+                ValueNode::Scalar(ScalarValue::Bool(true)),
+            ),
+            ("version".to_string(), text("1.0")),
+        ]));
+        assert_eq!(
+            ToYamlLens
+                .execute(value, vec![], HashMap::new(), &ctx)
+                .unwrap(),
+            text(
+                "empty: []\nflag: true\nitems:\n  - id: 1\n    note: \"yes\"\n  - \"a: b\"\nname: demo\nversion: \"1.0\""
+            )
+        );
+    }
+
+    #[test]
+    fn test_truncating_strategy_lenses_fit_target_and_are_idempotent() {
+        let ctx = LensContext::new();
+        let text = |s: &str| ValueNode::String(s.to_string());
+        let target = |units: i64| {
+            HashMap::from([(
+                "target".to_string(),
+                ValueNode::Scalar(ScalarValue::Int(units)),
+            )])
+        };
+        let run = |lens: &dyn Lens, input: &str, units: i64| {
+            lens.execute(text(input), vec![], target(units), &ctx)
+                .unwrap()
+        };
+        let source = "First point. Second point! Third (aside). Fourth";
+
+        // Whole sentences, then words, then characters.
+        assert_eq!(
+            run(&TruncateSentencesLens, source, 30),
+            text("First point. Second point!")
+        );
+        assert_eq!(run(&TruncateSentencesLens, source, 8), text("First"));
+        assert_eq!(run(&TruncateSentencesLens, "Überlänge", 6), text("Überl"));
+        assert_eq!(run(&TruncateSentencesLens, source, 0), text(""));
+        assert_eq!(run(&TruncateSentencesLens, source, 500), text(source));
+
+        let long = "alpha beta gamma delta epsilon zeta eta theta";
+        let kept = run(&KeepHeadTailLens, long, 30);
+        assert_eq!(kept, text("alpha beta\n[...]\neta theta"));
+        let marked = KeepHeadTailLens
+            .execute(
+                text(long),
+                vec![ValueNode::Scalar(ScalarValue::Int(20))],
+                HashMap::from([("marker".to_string(), text(" … "))]),
+                &ctx,
+            )
+            .unwrap();
+        assert_eq!(marked, text("alpha … theta"));
+        assert_eq!(run(&KeepHeadTailLens, long, 4), text("alph"));
+
+        for lens in [&TruncateSentencesLens as &dyn Lens, &KeepHeadTailLens] {
+            for units in [0, 7, 25, 40] {
+                let once = lens
+                    .execute(text(long), vec![], target(units), &ctx)
+                    .unwrap();
+                let ValueNode::String(once_text) = &once else {
+                    panic!("expected string");
+                };
+                assert!(count_facet_units(once_text) <= units as usize);
+                let twice = lens
+                    .execute(once.clone(), vec![], target(units), &ctx)
+                    .unwrap();
+                assert_eq!(once, twice);
+            }
+        }
+
+        assert!(TruncateSentencesLens
+            .execute(text(source), vec![], HashMap::new(), &ctx)
+            .is_err());
+        assert!(KeepHeadTailLens
+            .execute(text(source), vec![], target(-1), &ctx)
+            .is_err());
+    }
+
+    #[test]
+    fn test_collapsing_strategy_lenses() {
+        let ctx = LensContext::new();
+        let text = |s: &str| ValueNode::String(s.to_string());
+        let source = "\n# Title\n\nSome   **bold**\ttext.   \n\n\n> quoted [link](https://x.y) and ![img](a.png)\n---\n```\n  let x = `1`;\n```\n";
+
+        let collapsed = CollapseWhitespaceLens
+            .execute(text(source), vec![], HashMap::new(), &ctx)
+            .unwrap();
+        assert_eq!(
+            collapsed,
+            text("# Title\n\nSome **bold** text.\n\n> quoted [link](https://x.y) and ![img](a.png)\n---\n```\n  let x = `1`;\n```")
+        );
+
+        let stripped = StripMarkdownLens
+            .execute(text(source), vec![], HashMap::new(), &ctx)
+            .unwrap();
+        assert_eq!(
+            stripped,
+            text("Title\n\nSome bold text.\n\nquoted link and img\n  let x = 1;")
+        );
+
+        for (lens, output) in [
+            (&CollapseWhitespaceLens as &dyn Lens, collapsed),
+            (&StripMarkdownLens, stripped),
+        ] {
+            let again = lens
+                .execute(output.clone(), vec![], HashMap::new(), &ctx)
+                .unwrap();
+            assert_eq!(again, output);
+        }
+
+        // Input that fits an explicit target is left alone.
+        let fits = HashMap::from([(
+            "target".to_string(),
+            ValueNode::Scalar(ScalarValue::Int(1000)),
+        )]);
+        assert_eq!(
+            StripMarkdownLens
+                .execute(text(source), vec![], fits, &ctx)
+                .unwrap(),
+            text(source)
+        );
+    }
+
+    #[test]
+    fn test_drop_lowest_lens() {
+        let ctx = LensContext::new();
+        let doc = |id: &str, score: Option<i64>| {
+            let mut map = fct_ast::OrderedMap::new();
+            map.insert("id".to_string(), ValueNode::String(id.to_string()));
+            if let Some(score) = score {
+                map.insert(
+                    "score".to_string(),
+                    ValueNode::Scalar(ScalarValue::Int(score)),
+                );
+            }
+            ValueNode::Map(map)
+        };
+        // Each item is 2 ("id") + 1 + 5 ("score") + 1 = 9 units, or 3 without a score.
+        let docs = ValueNode::List(vec![
+            doc("a", Some(3)),
+            doc("b", Some(1)),
+            doc("c", None),
+            doc("d", Some(1)),
+            doc("e", Some(2)),
+        ]);
+        let kwargs = |units: i64| {
+            HashMap::from([
+                ("field".to_string(), ValueNode::String("score".to_string())),
+                (
+                    "target".to_string(),
+                    ValueNode::Scalar(ScalarValue::Int(units)),
+                ),
+            ])
+        };
+
+        let kept = DropLowestLens
+            .execute(docs.clone(), vec![], kwargs(27), &ctx)
+            .unwrap();
+        assert_eq!(
+            kept,
+            ValueNode::List(vec![
+                doc("a", Some(3)),
+                doc("b", Some(1)),
+                doc("e", Some(2))
+            ])
+        );
+        assert_eq!(
+            DropLowestLens
+                .execute(kept.clone(), vec![], kwargs(27), &ctx)
+                .unwrap(),
+            kept
+        );
+        assert_eq!(
+            DropLowestLens
+                .execute(docs.clone(), vec![], kwargs(0), &ctx)
+                .unwrap(),
+            ValueNode::List(vec![])
+        );
+        assert_eq!(
+            DropLowestLens
+                .execute(docs.clone(), vec![], kwargs(1000), &ctx)
+                .unwrap(),
+            docs
+        );
+        assert!(DropLowestLens
+            .execute(
+                docs,
+                vec![ValueNode::Scalar(ScalarValue::Int(10))],
+                HashMap::new(),
+                &ctx
+            )
+            .is_err());
+    }
+
     #[test]
     fn test_registry_metadata_has_version_and_determinism_class() {
         let registry = LensRegistry::new();
@@ -1363,6 +2086,13 @@ mod tests {
     }
 }
 
+// ============================================================================
+// FACET UNITS MODULE
+// ============================================================================
+
+pub mod units;
+pub use units::{count_facet_units, count_facet_units_in_value, normalize_for_facet_units};
+
 // ============================================================================
 // LENS ADAPTER MODULE
 // ============================================================================
@@ -0,0 +1,44 @@
+// ============================================================================
+// FACET UNITS
+// ============================================================================
+
+//! FACET Units, the measure of the Token Box Model: the UTF-8 byte length of
+//! a string after NFC and LF normalization. Layout strategy lenses measure
+//! their output with the same rule the layout engine uses.
+
+use fct_ast::{ScalarValue, ValueNode};
+use unicode_normalization::UnicodeNormalization;
+
+/// Normalize string to NFC and LF line endings for FACET Units.
+pub fn normalize_for_facet_units(input: &str) -> String {
+    let nfc: String = input.nfc().collect();
+    nfc.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// FACET Units of a string: byte_length(UTF-8(NFC+LF normalized s)).
+pub fn count_facet_units(text: &str) -> usize {
+    normalize_for_facet_units(text).len()
+}
+
+/// FACET Units of a value. Non-string values count their deterministic
+/// textual form; lists and maps count their items, keys and values.
+pub fn count_facet_units_in_value(value: &ValueNode) -> usize {
+    match value {
+        ValueNode::String(s) => count_facet_units(s),
+        ValueNode::Scalar(scalar) => match scalar {
+            ScalarValue::Int(i) => count_facet_units(&i.to_string()),
+            ScalarValue::Float(f) => count_facet_units(&f.to_string()),
+            ScalarValue::Bool(true) => count_facet_units("true"),
+            ScalarValue::Bool(false) => count_facet_units("false"),
+            ScalarValue::Null => count_facet_units("null"),
+        },
+        ValueNode::Variable(var) => count_facet_units(var),
+        ValueNode::List(items) => items.iter().map(count_facet_units_in_value).sum(),
+        ValueNode::Map(map) => map
+            .iter()
+            .map(|(key, val)| count_facet_units(key) + count_facet_units_in_value(val))
+            .sum(),
+        ValueNode::Pipeline(_) => count_facet_units("<pipeline>"),
+        ValueNode::Directive(_) => count_facet_units("<directive>"),
+    }
+}
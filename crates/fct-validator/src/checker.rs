@@ -2,17 +2,20 @@
 //!
 //! This module contains the main TypeChecker implementation for FACET validation.
 
-use crate::constraints::TypeConstraints;
+use crate::constraints::{StringFormat, TypeConstraints};
 use crate::errors::{ValidationError, ValidationResult};
+use crate::host_context::{HostContextSchema, HOST_CONTEXT_ROOT};
 use crate::types::FacetType;
+use crate::values::{describe_violations, multimodal_value_matches, render_value};
 use crate::VarTypeDecl;
 use fct_ast::types::FacetType as AstFacetType;
 use fct_ast::{
-    BodyNode, FacetBlock, FacetDocument, FacetNode, KeyValueNode, LensSignatureProvider,
-    LensSignatureRegistry, MapKeyKind, OrderedMap, ScalarValue, TypeNode, ValueNode,
+    BodyNode, FacetBlock, FacetDocument, FacetNode, KeyValueNode, LensCallNode,
+    LensSignatureProvider, LensSignatureRegistry, MapKeyKind, OrderedMap, ScalarValue, TypeNode,
+    ValueNode,
 };
 use fct_resolver::{Resolver, ResolverConfig, ResolverError};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ValidationProfile {
@@ -20,6 +23,16 @@ pub enum ValidationProfile {
     Hypervisor,
 }
 
+impl ValidationProfile {
+    /// Label recorded as the payload's `metadata.profile`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Core => fct_ast::CORE_PROFILE,
+            Self::Hypervisor => fct_ast::HYPERVISOR_PROFILE,
+        }
+    }
+}
+
 /// Main validator engine for FACET documents.
 ///
 /// The TypeChecker performs comprehensive validation of FACET documents including:
@@ -31,7 +44,7 @@ pub enum ValidationProfile {
 ///
 /// The validation process follows these steps:
 /// 1. **Import Validation**: Resolve all @import statements and detect circular dependencies
-/// 2. **Type Declaration**: Load and register all variable type declarations
+/// 2. **Type Declaration**: Load `@types` aliases, then register all variable type declarations
 /// 3. **Variable Validation**: Validate variable assignments against their declared types
 /// 4. **Resolution Checking**: Ensure all variable references can be resolved
 /// 5. **Interface Validation**: Validate component and function interfaces
@@ -40,12 +53,21 @@ pub struct TypeChecker<S: LensSignatureProvider = LensSignatureRegistry> {
     /// Storage for variable type declarations with their constraints
     var_types: HashMap<String, VarTypeDecl>,
 
+    /// Resolved `@types` aliases, usable from `@var_types` and `@input` type strings
+    type_aliases: HashMap<String, FacetType>,
+
+    /// Declared `@types` alias bodies, used for interface JSON Schema mappability checks
+    type_alias_nodes: HashMap<String, TypeNode>,
+
     /// Runtime variable types inferred from assignments and usage
     variables: HashMap<String, FacetType>,
 
     /// Provider for lens signature validation (decoupled from fct-std)
     _lens_provider: S,
 
+    /// Host-declared `$x.<host>` context available to policy conditions
+    host_context: Option<HostContextSchema>,
+
     /// Active validation profile
     profile: ValidationProfile,
 }
@@ -55,9 +77,12 @@ impl TypeChecker {
     pub fn new() -> Self {
         Self {
             var_types: HashMap::new(),
+            type_aliases: HashMap::new(),
+            type_alias_nodes: HashMap::new(),
             variables: HashMap::new(),
             _lens_provider: LensSignatureRegistry::with_standard_lenses(),
             profile: ValidationProfile::Hypervisor,
+            host_context: None,
         }
     }
 
@@ -65,9 +90,12 @@ impl TypeChecker {
     pub fn with_provider<S: LensSignatureProvider>(provider: S) -> TypeChecker<S> {
         TypeChecker {
             var_types: HashMap::new(),
+            type_aliases: HashMap::new(),
+            type_alias_nodes: HashMap::new(),
             variables: HashMap::new(),
             _lens_provider: provider,
             profile: ValidationProfile::Hypervisor,
+            host_context: None,
         }
     }
 }
@@ -83,9 +111,12 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
     pub fn new_with_provider(provider: S) -> Self {
         Self {
             var_types: HashMap::new(),
+            type_aliases: HashMap::new(),
+            type_alias_nodes: HashMap::new(),
             variables: HashMap::new(),
             _lens_provider: provider,
             profile: ValidationProfile::Hypervisor,
+            host_context: None,
         }
     }
 
@@ -94,6 +125,41 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
         self
     }
 
+    /// Accept `$x.<host>...` policy condition paths declared by a host profile.
+    pub fn with_host_context(mut self, schema: HostContextSchema) -> Self {
+        self.host_context = Some(schema);
+        self
+    }
+
+    /// Type of every variable after [`TypeChecker::validate`]: the `@var_types`
+    /// declaration with its constraints, or else the inferred type.
+    pub fn var_types(&self) -> BTreeMap<String, VarTypeDecl> {
+        let mut table: BTreeMap<String, VarTypeDecl> = self
+            .variables
+            .iter()
+            .map(|(name, var_type)| {
+                (
+                    name.clone(),
+                    VarTypeDecl {
+                        var_type: var_type.clone(),
+                        constraints: None,
+                    },
+                )
+            })
+            .collect();
+        for (name, decl) in &self.var_types {
+            table.insert(name.clone(), decl.clone());
+        }
+        table
+    }
+
+    /// Type of a standalone value expression against the variables of the last
+    /// [`TypeChecker::validate`], checking each lens step as `@vars` pipelines
+    /// are checked (F451).
+    pub fn infer_expression_type(&self, value: &ValueNode) -> ValidationResult<FacetType> {
+        self.infer_pipeline_checked_type(value, "expression")
+    }
+
     /// Main validation entry point for FACET documents.
     ///
     /// This method performs comprehensive validation of a FACET document including
@@ -112,7 +178,8 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
         // Step 1: Validate imports (critical - stops on failure)
         self.validate_imports(doc)?;
 
-        // Step 2: Load type declarations
+        // Step 2: Load type aliases and type declarations
+        self.load_type_aliases(doc)?;
         self.load_var_types(doc)?;
 
         // Step 3: Validate variables
@@ -167,6 +234,18 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
                         }
                     }
                 }
+                // Without an R-DAG, messages can reference @vars but not compute.
+                FacetNode::System(facet) | FacetNode::User(facet) | FacetNode::Assistant(facet) => {
+                    for item in &facet.body {
+                        if let BodyNode::KeyValue(kv) = item {
+                            if Self::contains_compute(&kv.value) {
+                                return Err(ValidationError::ProfileViolation {
+                                    construct: format!("@{}.{}", facet.name, kv.key),
+                                });
+                            }
+                        }
+                    }
+                }
                 _ => {}
             }
         }
@@ -174,6 +253,15 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
         Ok(())
     }
 
+    fn contains_compute(value: &ValueNode) -> bool {
+        match value {
+            ValueNode::Pipeline(_) | ValueNode::Directive(_) => true,
+            ValueNode::List(items) => items.iter().any(Self::contains_compute),
+            ValueNode::Map(map) => map.values().any(Self::contains_compute),
+            ValueNode::Scalar(_) | ValueNode::String(_) | ValueNode::Variable(_) => false,
+        }
+    }
+
     fn is_core_literal_value(value: &ValueNode) -> bool {
         match value {
             ValueNode::Scalar(_) | ValueNode::String(_) => true,
@@ -183,6 +271,66 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
         }
     }
 
+    /// Extract and resolve `@types` alias declarations.
+    ///
+    /// Later declarations of the same name override earlier ones. Aliases may
+    /// reference each other in any order; cyclic references are rejected (F452).
+    pub fn load_type_aliases(&mut self, doc: &FacetDocument) -> ValidationResult<()> {
+        let mut declared: OrderedMap<String, TypeNode> = OrderedMap::new();
+        for block in &doc.blocks {
+            if let FacetNode::Types(types) = block {
+                for alias in &types.aliases {
+                    if fct_ast::PRIMITIVE_TYPE_NAMES.contains(&alias.name.as_str())
+                        || RESERVED_TYPE_KEYWORDS.contains(&alias.name.as_str())
+                    {
+                        return Err(Self::policy_err(
+                            "Type alias shadows a built-in type",
+                            &alias.name,
+                        ));
+                    }
+                    declared.insert(alias.name.clone(), alias.type_node.clone());
+                }
+            }
+        }
+
+        let mut resolving = Vec::new();
+        for name in declared.keys() {
+            self.resolve_type_alias(name, &declared, &mut resolving)?;
+        }
+        self.type_alias_nodes = declared.into_iter().collect();
+        Ok(())
+    }
+
+    fn resolve_type_alias(
+        &mut self,
+        name: &str,
+        declared: &OrderedMap<String, TypeNode>,
+        resolving: &mut Vec<String>,
+    ) -> ValidationResult<()> {
+        if self.type_aliases.contains_key(name) {
+            return Ok(());
+        }
+        if let Some(pos) = resolving.iter().position(|n| n == name) {
+            let mut chain = resolving[pos..].to_vec();
+            chain.push(name.to_string());
+            return Err(Self::policy_err("Cyclic type alias", &chain.join(" -> ")));
+        }
+
+        let Some(type_node) = declared.get(name) else {
+            return Err(Self::policy_err("Unknown type alias", name));
+        };
+
+        resolving.push(name.to_string());
+        for dep in type_node.alias_refs() {
+            self.resolve_type_alias(dep, declared, resolving)?;
+        }
+        resolving.pop();
+
+        let resolved = facet_type_from_node(type_node, &self.type_aliases)?;
+        self.type_aliases.insert(name.to_string(), resolved);
+        Ok(())
+    }
+
     /// Extract and parse @var_types block
     pub fn load_var_types(&mut self, doc: &FacetDocument) -> ValidationResult<()> {
         for block in &doc.blocks {
@@ -203,7 +351,15 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
         Ok(())
     }
 
-    fn parse_type_declaration(&self, value: &ValueNode) -> ValidationResult<VarTypeDecl> {
+    /// Resolved `@types` aliases loaded by [`Self::load_type_aliases`].
+    pub(crate) fn type_aliases(&self) -> &HashMap<String, FacetType> {
+        &self.type_aliases
+    }
+
+    pub(crate) fn parse_type_declaration(
+        &self,
+        value: &ValueNode,
+    ) -> ValidationResult<VarTypeDecl> {
         match value {
             ValueNode::String(type_str) => {
                 let var_type = self.parse_type_string(type_str)?;
@@ -220,7 +376,7 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
                             message: "Missing 'type' field in type declaration".to_string(),
                         })?;
 
-                let var_type = match type_node {
+                let mut var_type = match type_node {
                     ValueNode::String(type_str) => self.parse_type_string(type_str)?,
                     _ => {
                         return Err(ValidationError::TypeInferenceFailed {
@@ -290,6 +446,73 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
                     constraints.enum_values = Some(enum_values.clone());
                 }
 
+                constraints.min_length = Self::count_constraint(map, "min_length")?;
+                constraints.max_length = Self::count_constraint(map, "max_length")?;
+                constraints.min_items = Self::count_constraint(map, "min_items")?;
+                constraints.max_items = Self::count_constraint(map, "max_items")?;
+
+                match map.get("unique_items") {
+                    Some(ValueNode::Scalar(ScalarValue::Bool(unique))) => {
+                        constraints.unique_items = *unique;
+                    }
+                    Some(other) => {
+                        return Err(Self::policy_err(
+                            "unique_items must be a bool",
+                            &format!("{:?}", other),
+                        ));
+                    }
+                    None => {}
+                }
+
+                match map.get("format") {
+                    Some(ValueNode::String(name)) => {
+                        constraints.format = Some(StringFormat::parse(name).ok_or_else(|| {
+                            Self::policy_err("format must be one of uri|email|uuid|date", name)
+                        })?);
+                    }
+                    Some(other) => {
+                        return Err(Self::policy_err(
+                            "format must be a string",
+                            &format!("{:?}", other),
+                        ));
+                    }
+                    None => {}
+                }
+
+                let admits_string = Self::type_admits(&var_type, &|ty| {
+                    matches!(
+                        ty,
+                        FacetType::Primitive(crate::types::PrimitiveType::String)
+                    )
+                });
+                let admits_list =
+                    Self::type_admits(&var_type, &|ty| matches!(ty, FacetType::List(_)));
+                for (key, set, applicable) in [
+                    (
+                        "min_length",
+                        constraints.min_length.is_some(),
+                        admits_string,
+                    ),
+                    (
+                        "max_length",
+                        constraints.max_length.is_some(),
+                        admits_string,
+                    ),
+                    ("format", constraints.format.is_some(), admits_string),
+                    ("min_items", constraints.min_items.is_some(), admits_list),
+                    ("max_items", constraints.max_items.is_some(), admits_list),
+                    ("unique_items", constraints.unique_items, admits_list),
+                ] {
+                    if set && !applicable {
+                        return Err(Self::policy_err(
+                            &format!("constraint '{key}' is not applicable to declared type"),
+                            &var_type.to_string(),
+                        ));
+                    }
+                }
+
+                Self::apply_field_presence(&mut var_type, map)?;
+
                 Ok(VarTypeDecl {
                     var_type,
                     constraints: Some(constraints),
@@ -301,8 +524,99 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
         }
     }
 
+    /// Read a non-negative integer constraint such as `min_length`.
+    fn count_constraint(
+        map: &OrderedMap<String, ValueNode>,
+        key: &str,
+    ) -> ValidationResult<Option<usize>> {
+        match map.get(key) {
+            None => Ok(None),
+            Some(ValueNode::Scalar(ScalarValue::Int(n))) if *n >= 0 => Ok(Some(*n as usize)),
+            Some(other) => Err(Self::policy_err(
+                &format!("{key} must be a non-negative int"),
+                &format!("{:?}", other),
+            )),
+        }
+    }
+
+    /// Whether `ty`, or any member of a union `ty`, satisfies `pred`.
+    fn type_admits(ty: &FacetType, pred: &dyn Fn(&FacetType) -> bool) -> bool {
+        match ty {
+            FacetType::Union(members) => members.iter().any(|m| Self::type_admits(m, pred)),
+            other => pred(other),
+        }
+    }
+
+    /// Apply `required: [...]` / `optional: [...]` field lists to a struct declaration.
+    ///
+    /// `required` lists the only mandatory fields; `optional` relaxes the named ones.
+    fn apply_field_presence(
+        var_type: &mut FacetType,
+        map: &OrderedMap<String, ValueNode>,
+    ) -> ValidationResult<()> {
+        let field_list = |key: &str| -> ValidationResult<Option<Vec<String>>> {
+            match map.get(key) {
+                None => Ok(None),
+                Some(ValueNode::List(items)) => items
+                    .iter()
+                    .map(|item| match item {
+                        ValueNode::String(name) => Ok(name.clone()),
+                        other => Err(Self::policy_err(
+                            &format!("{key} must be a list of field names"),
+                            &format!("{:?}", other),
+                        )),
+                    })
+                    .collect::<ValidationResult<Vec<_>>>()
+                    .map(Some),
+                Some(other) => Err(Self::policy_err(
+                    &format!("{key} must be a list of field names"),
+                    &format!("{:?}", other),
+                )),
+            }
+        };
+        let required = field_list("required")?;
+        let optional = field_list("optional")?;
+        if required.is_none() && optional.is_none() {
+            return Ok(());
+        }
+
+        let FacetType::Struct(fields) = var_type else {
+            return Err(Self::policy_err(
+                "required/optional constraints apply only to struct types",
+                &var_type.to_string(),
+            ));
+        };
+        for name in required.iter().chain(optional.iter()).flatten() {
+            if !fields.iter().any(|field| &field.name == name) {
+                return Err(Self::policy_err("Unknown struct field in constraint", name));
+            }
+        }
+        if let Some(required) = &required {
+            for field in fields.iter_mut() {
+                field.required = required.contains(&field.name);
+            }
+        }
+        if let Some(optional) = &optional {
+            if let Some(name) = optional
+                .iter()
+                .find(|name| required.as_ref().is_some_and(|r| r.contains(name)))
+            {
+                return Err(Self::policy_err(
+                    "Struct field cannot be both required and optional",
+                    name,
+                ));
+            }
+            for field in fields.iter_mut() {
+                if optional.contains(&field.name) {
+                    field.required = false;
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn parse_type_string(&self, type_str: &str) -> ValidationResult<FacetType> {
-        parse_type_expr(type_str)
+        parse_type_expr(type_str, &self.type_aliases)
     }
 
     /// Validate all imports in the document
@@ -381,7 +695,7 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
         self.validate_input_usage_for_var(&kv.value, &kv.key)?;
 
         // Check if variable has type declaration
-        if let Some(var_decl) = self.var_types.get(&kv.key) {
+        let var_type = if let Some(var_decl) = self.var_types.get(&kv.key) {
             // Validate value against declared type
             self.validate_value_against_type(&kv.value, &var_decl.var_type)?;
 
@@ -389,11 +703,13 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
             if let Some(ref constraints) = var_decl.constraints {
                 self.validate_value_constraints(&kv.value, constraints)?;
             }
-        }
+            var_decl.var_type.clone()
+        } else {
+            self.infer_type(&kv.value)?
+        };
 
-        // Infer and store variable type
-        let inferred_type = self.infer_type(&kv.value)?;
-        self.variables.insert(kv.key.clone(), inferred_type);
+        // Store the declared type, or the inferred one
+        self.variables.insert(kv.key.clone(), var_type);
 
         Ok(())
     }
@@ -409,9 +725,18 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
                 ScalarValue::Bool(_) => Ok(FacetType::Primitive(crate::types::PrimitiveType::Bool)),
                 ScalarValue::Null => Ok(FacetType::Primitive(crate::types::PrimitiveType::Null)),
             },
-            ValueNode::List(_) => Ok(FacetType::List(Box::new(FacetType::Primitive(
-                crate::types::PrimitiveType::Any,
-            )))),
+            ValueNode::List(items) => {
+                let item_types = items
+                    .iter()
+                    .map(|item| match item {
+                        ValueNode::Map(map) => {
+                            Self::literal_struct_type(map, |value| self.infer_type(value))
+                        }
+                        _ => self.infer_type(item),
+                    })
+                    .collect::<ValidationResult<Vec<_>>>()?;
+                Ok(Self::literal_list_type(item_types))
+            }
             ValueNode::Map(_) => Ok(FacetType::Map(Box::new(FacetType::Primitive(
                 crate::types::PrimitiveType::Any,
             )))),
@@ -450,16 +775,22 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
         value: &ValueNode,
         constraints: &TypeConstraints,
     ) -> ValidationResult<()> {
-        match value {
-            ValueNode::Scalar(scalar) => match scalar {
-                ScalarValue::Int(i) => constraints.validate_int(*i),
-                ScalarValue::Float(f) => constraints.validate_float(*f),
-                ScalarValue::Bool(b) => constraints.validate_bool(*b),
-                ScalarValue::Null => constraints.validate_null(),
-            },
-            ValueNode::String(s) => constraints.validate_string(s),
-            _ => Ok(()), // Non-scalar values can't be constrained with current constraint types
+        // Only literals are checked here; computed values are checked at runtime.
+        if !matches!(
+            value,
+            ValueNode::Scalar(_) | ValueNode::String(_) | ValueNode::List(_)
+        ) {
+            return Ok(());
         }
+
+        let violations = constraints.check_value(value, "$");
+        if violations.is_empty() {
+            return Ok(());
+        }
+        Err(ValidationError::ConstraintViolation {
+            constraint: describe_violations(&violations),
+            value: render_value(value),
+        })
     }
 
     /// Check that all variable references can be resolved
@@ -658,12 +989,16 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
             }
             ValueNode::Directive(_) => Ok(FacetType::Primitive(crate::types::PrimitiveType::Any)),
             ValueNode::List(items) => {
-                for item in items {
-                    self.infer_pipeline_checked_type(item, location)?;
-                }
-                Ok(FacetType::List(Box::new(FacetType::Primitive(
-                    crate::types::PrimitiveType::Any,
-                ))))
+                let item_types = items
+                    .iter()
+                    .map(|item| match item {
+                        ValueNode::Map(map) => Self::literal_struct_type(map, |value| {
+                            self.infer_pipeline_checked_type(value, location)
+                        }),
+                        _ => self.infer_pipeline_checked_type(item, location),
+                    })
+                    .collect::<ValidationResult<Vec<_>>>()?;
+                Ok(Self::literal_list_type(item_types))
             }
             ValueNode::Map(map) => {
                 for nested in map.values() {
@@ -700,7 +1035,8 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
                         self.infer_pipeline_checked_type(arg, location)?;
                     }
 
-                    current = Self::ast_type_to_validator_type(&signature.output_type);
+                    let output = Self::ast_type_to_validator_type(&signature.output_type);
+                    current = Self::map_field_output(lens, &current).unwrap_or(output);
                 }
 
                 Ok(current)
@@ -708,6 +1044,98 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
         }
     }
 
+    /// A map item of a list literal, typed as a struct with one required field per key.
+    fn literal_struct_type(
+        map: &OrderedMap<String, ValueNode>,
+        mut infer: impl FnMut(&ValueNode) -> ValidationResult<FacetType>,
+    ) -> ValidationResult<FacetType> {
+        let fields = map
+            .iter()
+            .map(|(name, value)| {
+                Ok(crate::types::StructField {
+                    name: name.clone(),
+                    field_type: infer(value)?,
+                    required: true,
+                })
+            })
+            .collect::<ValidationResult<Vec<_>>>()?;
+        Ok(FacetType::Struct(fields))
+    }
+
+    /// The type of a list literal from its item types: `list<T>` when every item
+    /// is a `T`, and for struct items one struct whose fields missing from some
+    /// items are optional. Anything else is `list<any>`.
+    fn literal_list_type(item_types: Vec<FacetType>) -> FacetType {
+        use crate::types::PrimitiveType;
+
+        let any = FacetType::Primitive(PrimitiveType::Any);
+        let Some(first) = item_types.first() else {
+            return FacetType::List(Box::new(any));
+        };
+        if item_types.iter().all(|item| item == first) {
+            return FacetType::List(Box::new(first.clone()));
+        }
+
+        let mut fields: Vec<crate::types::StructField> = Vec::new();
+        for item in &item_types {
+            let FacetType::Struct(item_fields) = item else {
+                return FacetType::List(Box::new(any));
+            };
+            for item_field in item_fields {
+                match fields
+                    .iter_mut()
+                    .find(|field| field.name == item_field.name)
+                {
+                    Some(field) if field.field_type != item_field.field_type => {
+                        field.field_type = any.clone();
+                    }
+                    Some(_) => {}
+                    None => fields.push(item_field.clone()),
+                }
+            }
+        }
+        for field in &mut fields {
+            field.required = item_types.iter().all(|item| {
+                matches!(item, FacetType::Struct(item_fields)
+                    if item_fields.iter().any(|item_field| item_field.name == field.name))
+            });
+        }
+        FacetType::List(Box::new(FacetType::Struct(fields)))
+    }
+
+    /// `map(field="f")` (or `map("f")`) over `list<struct { f: T }>` yields
+    /// `list<T>`; the lens substitutes `null` where the field is absent, so an
+    /// optional `f` adds `null`.
+    fn map_field_output(lens: &LensCallNode, input: &FacetType) -> Option<FacetType> {
+        use crate::types::PrimitiveType;
+
+        if lens.name != "map" {
+            return None;
+        }
+        let field_name = match (lens.kwargs.get("field"), lens.args.first()) {
+            (Some(ValueNode::String(name)), _) => name,
+            // `to_string` is a map operation, not a field.
+            (None, Some(ValueNode::String(name))) if name != "to_string" => name,
+            _ => return None,
+        };
+        let FacetType::List(item) = input else {
+            return None;
+        };
+        let FacetType::Struct(fields) = item.as_ref() else {
+            return None;
+        };
+        let field = fields.iter().find(|field| &field.name == field_name)?;
+        let element = if field.required {
+            field.field_type.clone()
+        } else {
+            FacetType::Union(vec![
+                field.field_type.clone(),
+                FacetType::Primitive(PrimitiveType::Null),
+            ])
+        };
+        Some(FacetType::List(Box::new(element)))
+    }
+
     fn is_pipeline_assignable(actual: &FacetType, expected: &FacetType) -> bool {
         if actual.is_assignable_to(expected) {
             return true;
@@ -762,7 +1190,7 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
                         });
                     }
 
-                    Self::validate_interface_type_mappable(
+                    self.validate_interface_type_mappable(
                         &func.return_type,
                         &format!("{}.{} return type", interface.name, func.name),
                     )?;
@@ -775,7 +1203,7 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
                                 &format!("{}.{}.{}", interface.name, func.name, param.name),
                             ));
                         }
-                        Self::validate_interface_type_mappable(
+                        self.validate_interface_type_mappable(
                             &param.type_node,
                             &format!("{}.{}.{} parameter", interface.name, func.name, param.name),
                         )?;
@@ -915,6 +1343,15 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
                     }
                 }
                 "defaults" => self.validate_context_defaults(&kv.value)?,
+                CONTEXT_OUTPUT_RESERVE_KEY => match &kv.value {
+                    ValueNode::Scalar(ScalarValue::Int(v)) if *v >= 0 => {}
+                    _ => {
+                        return Err(Self::policy_err(
+                            "@context.x_fct_output_reserve must be integer >= 0",
+                            CONTEXT_OUTPUT_RESERVE_KEY,
+                        ))
+                    }
+                },
                 _ => return Err(Self::policy_err("Unknown @context key", &kv.key)),
             }
         }
@@ -980,13 +1417,30 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
         let mut content_seen = false;
         let allowed = if allow_tools {
             [
-                "content", "id", "priority", "min", "grow", "shrink", "strategy", "when", "tools",
+                "content",
+                "id",
+                "priority",
+                "min",
+                "grow",
+                "shrink",
+                "strategy",
+                MESSAGE_PACK_KEY,
+                "when",
+                "tools",
             ]
             .into_iter()
             .collect::<HashSet<_>>()
         } else {
             [
-                "content", "id", "priority", "min", "grow", "shrink", "strategy", "when",
+                "content",
+                "id",
+                "priority",
+                "min",
+                "grow",
+                "shrink",
+                "strategy",
+                MESSAGE_PACK_KEY,
+                "when",
             ]
             .into_iter()
             .collect::<HashSet<_>>()
@@ -1046,6 +1500,21 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
                         ));
                     }
                 }
+                MESSAGE_PACK_KEY => {
+                    let valid = match &kv.value {
+                        ValueNode::String(order) => order == "position",
+                        ValueNode::Map(map) => {
+                            map.len() == 1 && matches!(map.get("score"), Some(ValueNode::String(_)))
+                        }
+                        _ => false,
+                    };
+                    if !valid {
+                        return Err(Self::policy_err(
+                            "Message x_fct_pack must be \"position\" or {score: \"<field>\"}",
+                            MESSAGE_PACK_KEY,
+                        ));
+                    }
+                }
                 "when" => self.validate_when_atom(&kv.value)?,
                 "tools" => {
                     if !allow_tools {
@@ -1451,6 +1920,16 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
     }
 
     fn validate_policy(&self, doc: &FacetDocument) -> ValidationResult<()> {
+        if self.host_context.is_some()
+            && (self.variables.contains_key(HOST_CONTEXT_ROOT)
+                || self.var_types.contains_key(HOST_CONTEXT_ROOT))
+        {
+            return Err(ValidationError::ConstraintViolation {
+                constraint: "variable name is reserved for the host context".to_string(),
+                value: HOST_CONTEXT_ROOT.to_string(),
+            });
+        }
+
         let known_tool_functions = self.collect_interface_function_index(doc);
         let known_lenses: HashSet<String> = self._lens_provider.lens_names().into_iter().collect();
         let known_message_ids = self.collect_message_ids(doc);
@@ -1562,13 +2041,20 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
         };
 
         for rule in rules {
-            self.validate_policy_rule(rule, known_tool_functions, known_lenses, known_message_ids)?;
+            self.validate_policy_rule(
+                list_name,
+                rule,
+                known_tool_functions,
+                known_lenses,
+                known_message_ids,
+            )?;
         }
         Ok(())
     }
 
     fn validate_policy_rule(
         &self,
+        list_name: &str,
         rule: &ValueNode,
         known_tool_functions: &HashMap<String, HashSet<String>>,
         known_lenses: &HashSet<String>,
@@ -1581,7 +2067,7 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
 
         for key in map.keys() {
             match key.as_str() {
-                "id" | "op" | "name" | "effect" | "when" | "unless" => {}
+                "id" | "op" | "name" | "effect" | "when" | "unless" | POLICY_MAX_CALLS_KEY => {}
                 _ => return Err(Self::policy_err("Unknown key inside PolicyRule", key)),
             }
         }
@@ -1649,6 +2135,27 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
             self.validate_policy_cond(unless_val)?;
         }
 
+        if let Some(max_calls) = map.get(POLICY_MAX_CALLS_KEY) {
+            if list_name != "allow" || op != "tool_call" {
+                return Err(Self::policy_err(
+                    "x_fct_max_calls is only allowed on allow rules for tool_call",
+                    POLICY_MAX_CALLS_KEY,
+                ));
+            }
+            if !map.contains_key("id") {
+                return Err(Self::policy_err(
+                    "PolicyRule with x_fct_max_calls requires an id",
+                    POLICY_MAX_CALLS_KEY,
+                ));
+            }
+            if !matches!(max_calls, ValueNode::Scalar(ScalarValue::Int(n)) if *n >= 0) {
+                return Err(Self::policy_err(
+                    "x_fct_max_calls must be a non-negative int",
+                    POLICY_MAX_CALLS_KEY,
+                ));
+            }
+        }
+
         Ok(())
     }
 
@@ -1846,11 +2353,20 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
             ValueNode::Scalar(ScalarValue::Bool(_)) => Ok(()),
             ValueNode::Variable(var_ref) => {
                 let base = var_ref.split('.').next().unwrap_or(var_ref);
-                let declared_type = self
-                    .variables
-                    .get(base)
-                    .cloned()
-                    .or_else(|| self.var_types.get(base).map(|decl| decl.var_type.clone()));
+                let declared_type = match &self.host_context {
+                    Some(schema) if base == HOST_CONTEXT_ROOT => {
+                        Some(schema.resolve(var_ref).cloned().ok_or_else(|| {
+                            ValidationError::VariableNotFound {
+                                var: var_ref.to_string(),
+                            }
+                        })?)
+                    }
+                    _ => self
+                        .variables
+                        .get(base)
+                        .cloned()
+                        .or_else(|| self.var_types.get(base).map(|decl| decl.var_type.clone())),
+                };
 
                 if declared_type.is_none() {
                     return Err(ValidationError::VariableNotFound {
@@ -1930,10 +2446,18 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
         }
     }
 
-    fn validate_interface_type_mappable(ty: &TypeNode, location: &str) -> ValidationResult<()> {
+    fn validate_interface_type_mappable(
+        &self,
+        ty: &TypeNode,
+        location: &str,
+    ) -> ValidationResult<()> {
         match ty {
             TypeNode::Primitive(name) => match name.as_str() {
                 "string" | "int" | "float" | "bool" | "null" | "any" => Ok(()),
+                // Aliases are acyclic once loaded, so recursion terminates.
+                _ if self.type_alias_nodes.contains_key(name) => {
+                    self.validate_interface_type_mappable(&self.type_alias_nodes[name], location)
+                }
                 _ => Err(Self::policy_err(
                     "Interface type is not mappable to JSON Schema (Appendix D)",
                     location,
@@ -1941,12 +2465,12 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
             },
             TypeNode::Struct(fields) => {
                 for field_ty in fields.values() {
-                    Self::validate_interface_type_mappable(field_ty, location)?;
+                    self.validate_interface_type_mappable(field_ty, location)?;
                 }
                 Ok(())
             }
             TypeNode::List(item_ty) | TypeNode::Map(item_ty) => {
-                Self::validate_interface_type_mappable(item_ty, location)
+                self.validate_interface_type_mappable(item_ty, location)
             }
             TypeNode::Union(types) => {
                 if types.is_empty() {
@@ -1956,7 +2480,7 @@ impl<S: LensSignatureProvider> TypeChecker<S> {
                     ));
                 }
                 for member in types {
-                    Self::validate_interface_type_mappable(member, location)?;
+                    self.validate_interface_type_mappable(member, location)?;
                 }
                 Ok(())
             }
@@ -2073,18 +2597,7 @@ fn value_matches_expected_type<S: LensSignatureProvider>(
                 }
                 Ok(true)
             }
-            FacetType::Multimodal(crate::types::MultimodalType::Embedding(embed_ty)) => {
-                if items.len() != embed_ty.size {
-                    return Ok(false);
-                }
-                Ok(items.iter().all(|item| {
-                    matches!(
-                        item,
-                        ValueNode::Scalar(ScalarValue::Int(_))
-                            | ValueNode::Scalar(ScalarValue::Float(_))
-                    )
-                }))
-            }
+            FacetType::Multimodal(media) => Ok(multimodal_value_matches(value, media)),
             _ => Ok(false),
         },
         ValueNode::Map(map) => match expected_type {
@@ -2108,59 +2621,7 @@ fn value_matches_expected_type<S: LensSignatureProvider>(
                 }
                 Ok(true)
             }
-            FacetType::Multimodal(crate::types::MultimodalType::Image(image_ty)) => {
-                if !matches!(map.get("kind"), Some(ValueNode::String(kind)) if kind == "image") {
-                    return Ok(false);
-                }
-                if let Some(required_format) = &image_ty.format {
-                    if !matches!(map.get("format"), Some(ValueNode::String(v)) if v == required_format)
-                    {
-                        return Ok(false);
-                    }
-                }
-                if let Some(max_dim) = image_ty.max_dim {
-                    let Some(ValueNode::Map(shape)) = map.get("shape") else {
-                        return Ok(false);
-                    };
-                    let width_ok = matches!(
-                        shape.get("width"),
-                        Some(ValueNode::Scalar(ScalarValue::Int(width))) if *width >= 0 && (*width as u32) <= max_dim
-                    );
-                    let height_ok = matches!(
-                        shape.get("height"),
-                        Some(ValueNode::Scalar(ScalarValue::Int(height))) if *height >= 0 && (*height as u32) <= max_dim
-                    );
-                    if !(width_ok && height_ok) {
-                        return Ok(false);
-                    }
-                }
-                Ok(true)
-            }
-            FacetType::Multimodal(crate::types::MultimodalType::Audio(audio_ty)) => {
-                if !matches!(map.get("kind"), Some(ValueNode::String(kind)) if kind == "audio") {
-                    return Ok(false);
-                }
-                if let Some(required_format) = &audio_ty.format {
-                    if !matches!(map.get("format"), Some(ValueNode::String(v)) if v == required_format)
-                    {
-                        return Ok(false);
-                    }
-                }
-                if let Some(max_duration) = audio_ty.max_duration {
-                    let Some(ValueNode::Map(shape)) = map.get("shape") else {
-                        return Ok(false);
-                    };
-                    let duration = match shape.get("duration") {
-                        Some(ValueNode::Scalar(ScalarValue::Int(v))) => *v as f64,
-                        Some(ValueNode::Scalar(ScalarValue::Float(v))) => *v,
-                        _ => return Ok(false),
-                    };
-                    if duration < 0.0 || duration > max_duration {
-                        return Ok(false);
-                    }
-                }
-                Ok(true)
-            }
+            FacetType::Multimodal(media) => Ok(multimodal_value_matches(value, media)),
             _ => Ok(false),
         },
         ValueNode::Variable(var_ref) => {
@@ -2177,7 +2638,7 @@ fn value_matches_expected_type<S: LensSignatureProvider>(
         ValueNode::Directive(directive) => {
             if directive.name == "input" {
                 if let Some(ValueNode::String(type_str)) = directive.args.get("type") {
-                    let actual_type = parse_type_expr(type_str)?;
+                    let actual_type = checker.parse_type_string(type_str)?;
                     return Ok(actual_type.is_assignable_to(expected_type));
                 }
             }
@@ -2245,8 +2706,20 @@ fn struct_field_type(struct_fields: &[crate::types::StructField], name: &str) ->
         .map(|field| field.field_type.clone())
 }
 
-fn parse_type_expr(type_str: &str) -> ValidationResult<FacetType> {
-    let mut parser = TypeExprParser::new(type_str);
+/// Call-quota host extension on `allow` rules (`fct_policy::MAX_CALLS_KEY`).
+const POLICY_MAX_CALLS_KEY: &str = "x_fct_max_calls";
+const CONTEXT_OUTPUT_RESERVE_KEY: &str = "x_fct_output_reserve";
+/// Item-packing host extension on message blocks (`fct_engine::PACK_KEY`).
+const MESSAGE_PACK_KEY: &str = "x_fct_pack";
+
+/// Keywords that introduce composite type forms and cannot be used as alias names.
+const RESERVED_TYPE_KEYWORDS: &[&str] = &["struct", "list", "map", "embedding", "image", "audio"];
+
+pub(crate) fn parse_type_expr(
+    type_str: &str,
+    aliases: &HashMap<String, FacetType>,
+) -> ValidationResult<FacetType> {
+    let mut parser = TypeExprParser::new(type_str, aliases);
     let parsed = parser.parse_type_expr()?;
     parser.skip_ws();
     if parser.is_eof() {
@@ -2256,14 +2729,121 @@ fn parse_type_expr(type_str: &str) -> ValidationResult<FacetType> {
     }
 }
 
+/// Lower an already-parsed [`TypeNode`] (e.g. an `@types` entry) using the same
+/// leaf rules as [`parse_type_expr`], so both paths agree on primitives, aliases
+/// and multimodal constraints.
+pub(crate) fn facet_type_from_node(
+    ty: &TypeNode,
+    aliases: &HashMap<String, FacetType>,
+) -> ValidationResult<FacetType> {
+    let leaf_err = |message: &str, value: &str| ValidationError::ConstraintViolation {
+        constraint: message.to_string(),
+        value: value.to_string(),
+    };
+    match ty {
+        TypeNode::Primitive(name) => {
+            primitive_or_alias(name, aliases).ok_or_else(|| leaf_err("Unknown type alias", name))
+        }
+        TypeNode::Struct(fields) => {
+            let mut out = Vec::with_capacity(fields.len());
+            for (field_name, field_ty) in fields {
+                out.push(crate::types::StructField {
+                    name: field_name.clone(),
+                    field_type: facet_type_from_node(field_ty, aliases)?,
+                    required: true,
+                });
+            }
+            Ok(FacetType::Struct(out))
+        }
+        TypeNode::List(item) => Ok(FacetType::List(Box::new(facet_type_from_node(
+            item, aliases,
+        )?))),
+        TypeNode::Map(value) => Ok(FacetType::Map(Box::new(facet_type_from_node(
+            value, aliases,
+        )?))),
+        TypeNode::Union(members) => Ok(FacetType::Union(
+            members
+                .iter()
+                .map(|member| facet_type_from_node(member, aliases))
+                .collect::<ValidationResult<Vec<_>>>()?,
+        )),
+        TypeNode::Embedding { size } => {
+            embedding_type(*size).map_err(|message| leaf_err(message, "embedding"))
+        }
+        TypeNode::Image { max_dim, format } => image_type(*max_dim, format.clone())
+            .map_err(|message| leaf_err(message, format.as_deref().unwrap_or("image"))),
+        TypeNode::Audio {
+            max_duration,
+            format,
+        } => audio_type(*max_duration, format.clone())
+            .map_err(|message| leaf_err(message, format.as_deref().unwrap_or("audio"))),
+    }
+}
+
+fn primitive_or_alias(name: &str, aliases: &HashMap<String, FacetType>) -> Option<FacetType> {
+    use crate::types::PrimitiveType;
+    let primitive = match name {
+        "string" => PrimitiveType::String,
+        "int" => PrimitiveType::Int,
+        "float" => PrimitiveType::Float,
+        "bool" => PrimitiveType::Bool,
+        "null" => PrimitiveType::Null,
+        "any" => PrimitiveType::Any,
+        _ => return aliases.get(name).cloned(),
+    };
+    Some(FacetType::Primitive(primitive))
+}
+
+fn embedding_type(size: usize) -> Result<FacetType, &'static str> {
+    if size == 0 {
+        return Err("embedding size must be positive");
+    }
+    Ok(FacetType::Multimodal(
+        crate::types::MultimodalType::Embedding(crate::types::EmbeddingType { size }),
+    ))
+}
+
+fn image_type(max_dim: Option<u32>, format: Option<String>) -> Result<FacetType, &'static str> {
+    if let Some(ref fmt) = format {
+        if !matches!(fmt.as_str(), "png" | "jpeg" | "webp") {
+            return Err("image format must be one of png|jpeg|webp");
+        }
+    }
+    Ok(FacetType::Multimodal(crate::types::MultimodalType::Image(
+        crate::types::ImageType { max_dim, format },
+    )))
+}
+
+fn audio_type(
+    max_duration: Option<f64>,
+    format: Option<String>,
+) -> Result<FacetType, &'static str> {
+    if let Some(ref fmt) = format {
+        if !matches!(fmt.as_str(), "mp3" | "wav" | "ogg") {
+            return Err("audio format must be one of mp3|wav|ogg");
+        }
+    }
+    Ok(FacetType::Multimodal(crate::types::MultimodalType::Audio(
+        crate::types::AudioType {
+            max_duration,
+            format,
+        },
+    )))
+}
+
 struct TypeExprParser<'a> {
     input: &'a str,
     offset: usize,
+    aliases: &'a HashMap<String, FacetType>,
 }
 
 impl<'a> TypeExprParser<'a> {
-    fn new(input: &'a str) -> Self {
-        Self { input, offset: 0 }
+    fn new(input: &'a str, aliases: &'a HashMap<String, FacetType>) -> Self {
+        Self {
+            input,
+            offset: 0,
+            aliases,
+        }
     }
 
     fn parse_type_expr(&mut self) -> ValidationResult<FacetType> {
@@ -2312,15 +2892,7 @@ impl<'a> TypeExprParser<'a> {
         }
 
         let ident = self.parse_identifier()?;
-        match ident.as_str() {
-            "string" => Ok(FacetType::Primitive(crate::types::PrimitiveType::String)),
-            "int" => Ok(FacetType::Primitive(crate::types::PrimitiveType::Int)),
-            "float" => Ok(FacetType::Primitive(crate::types::PrimitiveType::Float)),
-            "bool" => Ok(FacetType::Primitive(crate::types::PrimitiveType::Bool)),
-            "null" => Ok(FacetType::Primitive(crate::types::PrimitiveType::Null)),
-            "any" => Ok(FacetType::Primitive(crate::types::PrimitiveType::Any)),
-            _ => Err(self.err("Unknown type identifier")),
-        }
+        primitive_or_alias(&ident, self.aliases).ok_or_else(|| self.err("Unknown type identifier"))
     }
 
     fn parse_list_type(&mut self) -> ValidationResult<FacetType> {
@@ -2406,43 +2978,21 @@ impl<'a> TypeExprParser<'a> {
         self.expect_char('=')?;
         self.skip_ws();
         let size = self.parse_usize()?;
-        if size == 0 {
-            return Err(self.err("embedding size must be positive"));
-        }
         self.skip_ws();
         self.expect_char('>')?;
-        Ok(FacetType::Multimodal(
-            crate::types::MultimodalType::Embedding(crate::types::EmbeddingType { size }),
-        ))
+        embedding_type(size).map_err(|message| self.err(message))
     }
 
     fn parse_image_type(&mut self) -> ValidationResult<FacetType> {
         self.expect_keyword("image")?;
         let (format, max_dim, _) = self.parse_media_constraints(true)?;
-        if let Some(ref fmt) = format {
-            if !matches!(fmt.as_str(), "png" | "jpeg" | "webp") {
-                return Err(self.err("image format must be one of png|jpeg|webp"));
-            }
-        }
-        Ok(FacetType::Multimodal(crate::types::MultimodalType::Image(
-            crate::types::ImageType { max_dim, format },
-        )))
+        image_type(max_dim, format).map_err(|message| self.err(message))
     }
 
     fn parse_audio_type(&mut self) -> ValidationResult<FacetType> {
         self.expect_keyword("audio")?;
         let (format, _, max_duration) = self.parse_media_constraints(false)?;
-        if let Some(ref fmt) = format {
-            if !matches!(fmt.as_str(), "mp3" | "wav" | "ogg") {
-                return Err(self.err("audio format must be one of mp3|wav|ogg"));
-            }
-        }
-        Ok(FacetType::Multimodal(crate::types::MultimodalType::Audio(
-            crate::types::AudioType {
-                max_duration,
-                format,
-            },
-        )))
+        audio_type(max_duration, format).map_err(|message| self.err(message))
     }
 
     fn parse_media_constraints(
@@ -2761,6 +3311,33 @@ mod tests {
             .expect("compatible lens chain should validate");
     }
 
+    #[test]
+    fn infer_expression_type_uses_validated_variables() {
+        let doc = vars_doc(vec![("csv", ValueNode::String("a,b".to_string()))]);
+        let mut checker = TypeChecker::new();
+        checker.validate(&doc).unwrap();
+
+        let split = ValueNode::Pipeline(PipelineNode {
+            initial: Box::new(ValueNode::Variable("csv".to_string())),
+            lenses: vec![lens("split", vec![ValueNode::String(",".to_string())])],
+            span: span(),
+        });
+        assert!(matches!(
+            checker.infer_expression_type(&split).unwrap(),
+            crate::types::FacetType::List(_)
+        ));
+
+        let broken = ValueNode::Pipeline(PipelineNode {
+            initial: Box::new(split),
+            lenses: vec![lens("trim", vec![])],
+            span: span(),
+        });
+        assert!(matches!(
+            checker.infer_expression_type(&broken),
+            Err(ValidationError::TypeMismatch { .. })
+        ));
+    }
+
     #[test]
     fn pipeline_uses_variable_type_for_step_assignability() {
         let doc = vars_doc(vec![
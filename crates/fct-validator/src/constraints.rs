@@ -5,13 +5,114 @@
 
 use crate::errors::ValidationError;
 use crate::types::PrimitiveType;
+use crate::values::{render_value, ValueViolation};
 use fct_ast::{ScalarValue, ValueNode};
 use regex::Regex;
 
+/// Well-known string formats accepted by the `format` constraint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StringFormat {
+    /// Absolute URI with an RFC 3986 scheme (`https://...`, `urn:...`)
+    Uri,
+    /// `local@domain.tld` mailbox
+    Email,
+    /// Hyphenated 8-4-4-4-12 hex UUID
+    Uuid,
+    /// RFC 3339 full-date (`YYYY-MM-DD`)
+    Date,
+}
+
+impl StringFormat {
+    /// Parse a `format` constraint value.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "uri" => Some(Self::Uri),
+            "email" => Some(Self::Email),
+            "uuid" => Some(Self::Uuid),
+            "date" => Some(Self::Date),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Uri => "uri",
+            Self::Email => "email",
+            Self::Uuid => "uuid",
+            Self::Date => "date",
+        }
+    }
+
+    /// Check whether `value` is in this format.
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            Self::Uri => {
+                let Some((scheme, rest)) = value.split_once(':') else {
+                    return false;
+                };
+                let mut scheme_chars = scheme.chars();
+                matches!(scheme_chars.next(), Some(c) if c.is_ascii_alphabetic())
+                    && scheme_chars
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+                    && !rest.is_empty()
+                    && !value.chars().any(char::is_whitespace)
+            }
+            Self::Email => {
+                let Some((local, domain)) = value.rsplit_once('@') else {
+                    return false;
+                };
+                !local.is_empty()
+                    && !domain.starts_with('.')
+                    && !domain.ends_with('.')
+                    && domain.contains('.')
+                    && !value.chars().any(char::is_whitespace)
+                    && !local.contains('@')
+            }
+            Self::Uuid => {
+                let groups: Vec<&str> = value.split('-').collect();
+                groups.len() == 5
+                    && groups
+                        .iter()
+                        .zip([8, 4, 4, 4, 12])
+                        .all(|(g, len)| g.len() == len && g.chars().all(|c| c.is_ascii_hexdigit()))
+            }
+            Self::Date => {
+                let bytes = value.as_bytes();
+                if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+                    return false;
+                }
+                let (Ok(year), Ok(month), Ok(day)) = (
+                    value[0..4].parse::<u32>(),
+                    value[5..7].parse::<u32>(),
+                    value[8..10].parse::<u32>(),
+                ) else {
+                    return false;
+                };
+                if !value[0..4].chars().all(|c| c.is_ascii_digit())
+                    || !value[5..7].chars().all(|c| c.is_ascii_digit())
+                    || !value[8..10].chars().all(|c| c.is_ascii_digit())
+                {
+                    return false;
+                }
+                let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+                let days_in_month = match month {
+                    1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+                    4 | 6 | 9 | 11 => 30,
+                    2 if leap => 29,
+                    2 => 28,
+                    _ => return false,
+                };
+                (1..=days_in_month).contains(&day)
+            }
+        }
+    }
+}
+
 /// Type constraints for FACET types.
 ///
 /// Constraints allow fine-tuned validation of values against specific
-/// requirements like ranges, patterns, and enumerated values.
+/// requirements like ranges, patterns, lengths, formats and enumerated values.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TypeConstraints {
     /// Minimum value for numeric types (int, float)
@@ -25,6 +126,30 @@ pub struct TypeConstraints {
 
     /// List of allowed values for enum-like validation
     pub enum_values: Option<Vec<ValueNode>>,
+
+    /// Minimum string length, counted in Unicode scalar values
+    #[serde(default)]
+    pub min_length: Option<usize>,
+
+    /// Maximum string length, counted in Unicode scalar values
+    #[serde(default)]
+    pub max_length: Option<usize>,
+
+    /// Well-known string format (uri, email, uuid, date)
+    #[serde(default)]
+    pub format: Option<StringFormat>,
+
+    /// Minimum number of list items
+    #[serde(default)]
+    pub min_items: Option<usize>,
+
+    /// Maximum number of list items
+    #[serde(default)]
+    pub max_items: Option<usize>,
+
+    /// Require list items to be pairwise distinct
+    #[serde(default)]
+    pub unique_items: bool,
 }
 
 impl TypeConstraints {
@@ -35,107 +160,149 @@ impl TypeConstraints {
             max: None,
             pattern: None,
             enum_values: None,
+            min_length: None,
+            max_length: None,
+            format: None,
+            min_items: None,
+            max_items: None,
+            unique_items: false,
         }
     }
 
     /// Validate an integer value against constraints
     pub fn validate_int(&self, value: i64) -> Result<(), ValidationError> {
-        self.validate_enum_value(&ValueNode::Scalar(ScalarValue::Int(value)))?;
-
-        if let Some(min) = self.min {
-            if (value as f64) < min {
-                return Err(ValidationError::ConstraintViolation {
-                    constraint: format!("min >= {}", min),
-                    value: value.to_string(),
-                });
-            }
-        }
-
-        if let Some(max) = self.max {
-            if (value as f64) > max {
-                return Err(ValidationError::ConstraintViolation {
-                    constraint: format!("max <= {}", max),
-                    value: value.to_string(),
-                });
-            }
-        }
-
-        Ok(())
+        self.first_violation(&ValueNode::Scalar(ScalarValue::Int(value)))
     }
 
     /// Validate a float value against constraints
     pub fn validate_float(&self, value: f64) -> Result<(), ValidationError> {
-        self.validate_enum_value(&ValueNode::Scalar(ScalarValue::Float(value)))?;
-
-        if let Some(min) = self.min {
-            if value < min {
-                return Err(ValidationError::ConstraintViolation {
-                    constraint: format!("min >= {}", min),
-                    value: value.to_string(),
-                });
-            }
-        }
-
-        if let Some(max) = self.max {
-            if value > max {
-                return Err(ValidationError::ConstraintViolation {
-                    constraint: format!("max <= {}", max),
-                    value: value.to_string(),
-                });
-            }
-        }
-
-        Ok(())
+        self.first_violation(&ValueNode::Scalar(ScalarValue::Float(value)))
     }
 
     /// Validate a string value against constraints
     pub fn validate_string(&self, value: &str) -> Result<(), ValidationError> {
-        self.validate_enum_value(&ValueNode::String(value.to_string()))?;
-
-        // Check pattern
-        if let Some(ref pattern_str) = self.pattern {
-            match Regex::new(pattern_str) {
-                Ok(regex) => {
-                    if !regex.is_match(value) {
-                        return Err(ValidationError::ConstraintViolation {
-                            constraint: format!("pattern '{}'", pattern_str),
-                            value: value.to_string(),
-                        });
-                    }
-                }
-                Err(_) => {
-                    // Invalid regex pattern - treat as constraint violation
-                    return Err(ValidationError::ConstraintViolation {
-                        constraint: format!("valid regex pattern (invalid: '{}')", pattern_str),
-                        value: value.to_string(),
-                    });
-                }
-            }
-        }
-
-        Ok(())
+        self.first_violation(&ValueNode::String(value.to_string()))
     }
 
     /// Validate a bool value against enum constraints.
     pub fn validate_bool(&self, value: bool) -> Result<(), ValidationError> {
-        self.validate_enum_value(&ValueNode::Scalar(ScalarValue::Bool(value)))
+        self.first_violation(&ValueNode::Scalar(ScalarValue::Bool(value)))
     }
 
     /// Validate null against enum constraints.
     pub fn validate_null(&self) -> Result<(), ValidationError> {
-        self.validate_enum_value(&ValueNode::Scalar(ScalarValue::Null))
+        self.first_violation(&ValueNode::Scalar(ScalarValue::Null))
+    }
+
+    /// Validate a list value against item-count and uniqueness constraints.
+    pub fn validate_list(&self, items: &[ValueNode]) -> Result<(), ValidationError> {
+        self.first_violation(&ValueNode::List(items.to_vec()))
+    }
+
+    fn first_violation(&self, value: &ValueNode) -> Result<(), ValidationError> {
+        match self.check_value(value, "$").into_iter().next() {
+            Some(violation) => Err(ValidationError::ConstraintViolation {
+                constraint: violation.message,
+                value: render_value(value),
+            }),
+            None => Ok(()),
+        }
     }
 
-    fn validate_enum_value(&self, value: &ValueNode) -> Result<(), ValidationError> {
+    /// Collect every constraint violated by `value`, reported at `path`.
+    ///
+    /// Constraints that do not apply to the value's kind are ignored; kind
+    /// mismatches are reported by the type check, not here.
+    pub fn check_value(&self, value: &ValueNode, path: &str) -> Vec<ValueViolation> {
+        let mut violations = Vec::new();
+        let mut violate = |message: String| {
+            violations.push(ValueViolation {
+                path: path.to_string(),
+                message,
+            })
+        };
+
         if let Some(ref enum_vals) = self.enum_values {
             if !enum_vals.iter().any(|v| v == value) {
-                return Err(ValidationError::ConstraintViolation {
-                    constraint: format!("one of {:?}", enum_vals),
-                    value: format!("{:?}", value),
-                });
+                violate(format!("one of {:?}", enum_vals));
             }
         }
-        Ok(())
+
+        let number = match value {
+            ValueNode::Scalar(ScalarValue::Int(i)) => Some(*i as f64),
+            ValueNode::Scalar(ScalarValue::Float(f)) => Some(*f),
+            _ => None,
+        };
+        if let Some(number) = number {
+            if let Some(min) = self.min {
+                if number < min {
+                    violate(format!("min >= {}", min));
+                }
+            }
+            if let Some(max) = self.max {
+                if number > max {
+                    violate(format!("max <= {}", max));
+                }
+            }
+        }
+
+        match value {
+            ValueNode::String(text) => {
+                if let Some(ref pattern_str) = self.pattern {
+                    match Regex::new(pattern_str) {
+                        Ok(regex) => {
+                            if !regex.is_match(text) {
+                                violate(format!("pattern '{}'", pattern_str));
+                            }
+                        }
+                        // Invalid regex pattern - treat as constraint violation
+                        Err(_) => {
+                            violate(format!("valid regex pattern (invalid: '{}')", pattern_str))
+                        }
+                    }
+                }
+                let length = text.chars().count();
+                if let Some(min_length) = self.min_length {
+                    if length < min_length {
+                        violate(format!("min_length >= {}", min_length));
+                    }
+                }
+                if let Some(max_length) = self.max_length {
+                    if length > max_length {
+                        violate(format!("max_length <= {}", max_length));
+                    }
+                }
+                if let Some(format) = self.format {
+                    if !format.matches(text) {
+                        violate(format!("format '{}'", format.as_str()));
+                    }
+                }
+            }
+            ValueNode::List(items) => {
+                if let Some(min_items) = self.min_items {
+                    if items.len() < min_items {
+                        violate(format!("min_items >= {}", min_items));
+                    }
+                }
+                if let Some(max_items) = self.max_items {
+                    if items.len() > max_items {
+                        violate(format!("max_items <= {}", max_items));
+                    }
+                }
+                if self.unique_items {
+                    let duplicate = items
+                        .iter()
+                        .enumerate()
+                        .any(|(idx, item)| items[..idx].contains(item));
+                    if duplicate {
+                        violate("unique_items".to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        violations
     }
 
     /// Check if constraints are applicable to a specific primitive type
@@ -144,7 +311,13 @@ impl TypeConstraints {
             PrimitiveType::Int | PrimitiveType::Float | PrimitiveType::Number => {
                 self.min.is_some() || self.max.is_some() || self.enum_values.is_some()
             }
-            PrimitiveType::String => self.pattern.is_some() || self.enum_values.is_some(),
+            PrimitiveType::String => {
+                self.pattern.is_some()
+                    || self.enum_values.is_some()
+                    || self.min_length.is_some()
+                    || self.max_length.is_some()
+                    || self.format.is_some()
+            }
             PrimitiveType::Bool | PrimitiveType::Boolean | PrimitiveType::Null => {
                 self.enum_values.is_some()
             }
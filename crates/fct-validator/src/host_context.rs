@@ -0,0 +1,205 @@
+//! # Host Context Schema
+//!
+//! Types a host profile declares for its read-only policy context. Policy
+//! conditions reach the context as `$x.<host>.<path>`; the schema lets Phase 2
+//! check those references and lets hosts check the context they supply.
+
+use crate::checker::parse_type_expr;
+use crate::errors::{ValidationError, ValidationResult};
+use crate::types::FacetType;
+use crate::values::{check_value, ValueViolation};
+use fct_ast::ValueNode;
+use std::collections::{BTreeMap, HashMap};
+
+/// Root variable of the host context; the same as `fct_policy::HOST_CONTEXT_ROOT`.
+pub const HOST_CONTEXT_ROOT: &str = "x";
+
+/// Declared type of each host namespace under `$x`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HostContextSchema {
+    namespaces: BTreeMap<String, FacetType>,
+}
+
+impl HostContextSchema {
+    /// Parse `(host, type expression)` declarations, e.g.
+    /// `("acme", "struct { principal: struct { admin: bool } }")`.
+    pub fn parse<'a>(
+        declarations: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> ValidationResult<Self> {
+        let aliases = HashMap::new();
+        let mut namespaces = BTreeMap::new();
+        for (host, type_expr) in declarations {
+            if !is_identifier(host) {
+                return Err(ValidationError::ConstraintViolation {
+                    constraint: "host context namespace must be an identifier".to_string(),
+                    value: host.to_string(),
+                });
+            }
+            namespaces.insert(host.to_string(), parse_type_expr(type_expr, &aliases)?);
+        }
+        Ok(Self { namespaces })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.namespaces.is_empty()
+    }
+
+    /// Declared type of each host namespace, by host name.
+    pub fn namespaces(&self) -> &BTreeMap<String, FacetType> {
+        &self.namespaces
+    }
+
+    /// Declared type of a `x.<host>.<path>` reference, or `None` when the
+    /// reference is outside the declared context.
+    pub fn resolve(&self, var_ref: &str) -> Option<&FacetType> {
+        let mut segments = var_ref.split('.');
+        if segments.next() != Some(HOST_CONTEXT_ROOT) {
+            return None;
+        }
+        let mut current = self.namespaces.get(segments.next()?)?;
+        for segment in segments {
+            current = match current {
+                FacetType::Struct(fields) => {
+                    &fields
+                        .iter()
+                        .find(|field| field.name == segment)?
+                        .field_type
+                }
+                FacetType::Map(value_type) => value_type,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    /// Check a supplied context (`{ "<host>": value }`) against the schema:
+    /// every declared namespace must be present, well typed, and nothing else
+    /// may be supplied.
+    pub fn check_context(&self, context: &ValueNode) -> Result<(), Vec<ValueViolation>> {
+        let ValueNode::Map(supplied) = context else {
+            return Err(vec![ValueViolation {
+                path: "$".to_string(),
+                message: "expected a map of host namespaces".to_string(),
+            }]);
+        };
+
+        let mut violations = Vec::new();
+        for (host, ty) in &self.namespaces {
+            let path = format!("$.{}", host);
+            match supplied.get(host) {
+                Some(value) => {
+                    violations.extend(check_value(value, ty, None).into_iter().map(|v| {
+                        ValueViolation {
+                            path: v.path.replacen('$', &path, 1),
+                            message: v.message,
+                        }
+                    }))
+                }
+                None => violations.push(ValueViolation {
+                    path,
+                    message: "missing host namespace".to_string(),
+                }),
+            }
+        }
+        for host in supplied.keys() {
+            if !self.namespaces.contains_key(host) {
+                violations.push(ValueViolation {
+                    path: format!("$.{}", host),
+                    message: "host namespace is not declared by the host profile".to_string(),
+                });
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PrimitiveType;
+    use fct_ast::{OrderedMap, ScalarValue};
+
+    fn schema() -> HostContextSchema {
+        HostContextSchema::parse([(
+            "acme",
+            "struct { principal: struct { admin: bool, role: string }, flags: map<string, bool> }",
+        )])
+        .expect("schema")
+    }
+
+    fn map(entries: Vec<(&str, ValueNode)>) -> ValueNode {
+        ValueNode::Map(
+            entries
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect::<OrderedMap<_, _>>(),
+        )
+    }
+
+    #[test]
+    fn resolves_struct_and_map_paths() {
+        let schema = schema();
+        assert_eq!(
+            schema.resolve("x.acme.principal.admin"),
+            Some(&FacetType::Primitive(PrimitiveType::Bool))
+        );
+        assert_eq!(
+            schema.resolve("x.acme.flags.beta"),
+            Some(&FacetType::Primitive(PrimitiveType::Bool))
+        );
+        assert_eq!(schema.resolve("x.acme.principal.missing"), None);
+        assert_eq!(schema.resolve("x.other.principal"), None);
+        assert_eq!(schema.resolve("acme.principal.admin"), None);
+    }
+
+    #[test]
+    fn checks_supplied_context_against_declared_types() {
+        let schema = schema();
+        let principal = |admin: ValueNode| {
+            map(vec![(
+                "acme",
+                map(vec![
+                    (
+                        "principal",
+                        map(vec![
+                            ("admin", admin),
+                            ("role", ValueNode::String("ops".to_string())),
+                        ]),
+                    ),
+                    ("flags", map(vec![])),
+                ]),
+            )])
+        };
+        assert!(schema
+            .check_context(&principal(ValueNode::Scalar(ScalarValue::Bool(true))))
+            .is_ok());
+
+        let violations = schema
+            .check_context(&principal(ValueNode::String("yes".to_string())))
+            .unwrap_err();
+        assert_eq!(violations[0].path, "$.acme.principal.admin");
+
+        let violations = schema
+            .check_context(&map(vec![("other", map(vec![]))]))
+            .unwrap_err();
+        let paths: Vec<_> = violations.iter().map(|v| v.path.as_str()).collect();
+        assert_eq!(paths, vec!["$.acme", "$.other"]);
+    }
+
+    #[test]
+    fn rejects_invalid_namespaces_and_types() {
+        assert!(HostContextSchema::parse([("not-a-host", "bool")]).is_err());
+        assert!(HostContextSchema::parse([("acme", "struct {")]).is_err());
+    }
+}
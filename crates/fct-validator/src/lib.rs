@@ -65,15 +65,23 @@ use fct_ast::FacetDocument;
 pub mod checker;
 pub mod constraints;
 pub mod errors;
+pub mod host_context;
+pub mod lint;
 pub mod types;
+pub mod values;
 
 // Re-export public API
 pub use checker::{TypeChecker, ValidationProfile};
-pub use constraints::TypeConstraints;
+pub use constraints::{StringFormat, TypeConstraints};
 pub use errors::{ValidationError, ValidationResult};
+pub use host_context::{HostContextSchema, HOST_CONTEXT_ROOT};
+pub use lint::{
+    LintConfig, LintDiagnostic, LintEdit, LintSeverity, LintSuggestion, Linter, LINT_RULES,
+};
 pub use types::{
     AudioType, EmbeddingType, FacetType, ImageType, MultimodalType, PrimitiveType, StructField,
 };
+pub use values::{check_value, InputTypeTable, ValueViolation};
 
 // Variable type declarations
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
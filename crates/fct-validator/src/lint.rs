@@ -0,0 +1,804 @@
+//! # Lint Engine
+//!
+//! Static checks for contracts that validate but still carry likely mistakes:
+//! dead variables, unexposed interfaces, unreachable messages, and policy rules
+//! that can never take effect.
+//!
+//! Every rule can be configured individually through [`LintConfig`]. Diagnostics
+//! use the host-extension namespace `X.lint.<rule>` (§2.2). The only autofix
+//! removes an unused variable whose value is a literal, and it is offered only
+//! for edits inside the entry file.
+
+use fct_ast::{BodyNode, FacetBlock, FacetDocument, FacetNode, ScalarValue, Span, ValueNode};
+use fct_resolver::SourceMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+
+/// Severity assigned to a lint rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl LintSeverity {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "off" => Some(Self::Off),
+            "info" => Some(Self::Info),
+            "warning" | "warn" => Some(Self::Warning),
+            "error" => Some(Self::Error),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// Static description of a lint rule.
+#[derive(Debug, Clone, Copy)]
+pub struct LintRule {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub default_severity: LintSeverity,
+}
+
+pub const UNUSED_VAR: &str = "unused_var";
+pub const UNREFERENCED_INTERFACE: &str = "unreferenced_interface";
+pub const DEAD_MESSAGE: &str = "dead_message";
+pub const DUPLICATE_POLICY_RULE_ID: &str = "duplicate_policy_rule_id";
+pub const SHADOWED_ALLOW_RULE: &str = "shadowed_allow_rule";
+
+/// All lint rules, in reporting order.
+pub const LINT_RULES: &[LintRule] = &[
+    LintRule {
+        name: UNUSED_VAR,
+        description: "@vars entry is never referenced",
+        default_severity: LintSeverity::Warning,
+    },
+    LintRule {
+        name: UNREFERENCED_INTERFACE,
+        description: "@interface is not listed in any @system.tools",
+        default_severity: LintSeverity::Warning,
+    },
+    LintRule {
+        name: DEAD_MESSAGE,
+        description: "message block has a literal `when: false`",
+        default_severity: LintSeverity::Warning,
+    },
+    LintRule {
+        name: DUPLICATE_POLICY_RULE_ID,
+        description: "policy rule id is declared more than once",
+        default_severity: LintSeverity::Warning,
+    },
+    LintRule {
+        name: SHADOWED_ALLOW_RULE,
+        description: "allow rule can never match because an unconditional deny covers it",
+        default_severity: LintSeverity::Warning,
+    },
+];
+
+/// Per-rule severity configuration; rules not mentioned use their default.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LintConfig {
+    #[serde(default)]
+    pub rules: BTreeMap<String, LintSeverity>,
+}
+
+impl LintConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the severity of `rule`; unknown rule names are rejected.
+    pub fn with_severity(mut self, rule: &str, severity: LintSeverity) -> Result<Self, String> {
+        self.set_severity(rule, severity)?;
+        Ok(self)
+    }
+
+    pub fn set_severity(&mut self, rule: &str, severity: LintSeverity) -> Result<(), String> {
+        if !LINT_RULES.iter().any(|r| r.name == rule) {
+            return Err(format!("Unknown lint rule: {}", rule));
+        }
+        self.rules.insert(rule.to_string(), severity);
+        Ok(())
+    }
+
+    /// Reject configurations naming rules this linter does not know.
+    pub fn check_rules(&self) -> Result<(), String> {
+        match self
+            .rules
+            .keys()
+            .find(|name| !LINT_RULES.iter().any(|r| r.name == name.as_str()))
+        {
+            Some(name) => Err(format!("Unknown lint rule: {}", name)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn severity(&self, rule: &str) -> LintSeverity {
+        self.rules.get(rule).copied().unwrap_or_else(|| {
+            LINT_RULES
+                .iter()
+                .find(|r| r.name == rule)
+                .map(|r| r.default_severity)
+                .unwrap_or(LintSeverity::Off)
+        })
+    }
+}
+
+/// Suggested fix for a diagnostic. `replacement` replaces the diagnostic span;
+/// `additional_edits` must be applied together with it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LintSuggestion {
+    pub message: String,
+    pub replacement: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub additional_edits: Vec<LintEdit>,
+}
+
+/// Replacement of a span other than the diagnostic's own.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LintEdit {
+    pub span: Span,
+    pub replacement: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LintDiagnostic {
+    pub code: String,
+    pub rule: &'static str,
+    pub severity: LintSeverity,
+    pub message: String,
+    /// Span in `file`, or in the linted source when no source map is known.
+    pub span: Span,
+    /// Imported file the span is in; `None` for the entry file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<LintSuggestion>,
+}
+
+/// Runs the configured lint rules over a resolved document.
+#[derive(Debug, Clone, Default)]
+pub struct Linter {
+    config: LintConfig,
+    source: Option<String>,
+    source_map: Option<SourceMap>,
+}
+
+impl Linter {
+    pub fn new(config: LintConfig) -> Self {
+        Self {
+            config,
+            source: None,
+            source_map: None,
+        }
+    }
+
+    /// Source text the linted document was parsed from, used to point
+    /// diagnostics at individual policy rules instead of their whole list.
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Map of the Resolved Source Form both linted documents were parsed from,
+    /// used to report each span in the file it was imported from.
+    pub fn with_source_map(mut self, source_map: SourceMap) -> Self {
+        self.source_map = Some(source_map);
+        self
+    }
+
+    /// Lint a resolved document.
+    ///
+    /// Import merging collapses policy rules that share an id, so duplicates across
+    /// imports are only visible through [`Linter::lint_with_sources`].
+    pub fn lint(&self, resolved: &FacetDocument) -> Vec<LintDiagnostic> {
+        self.lint_with_sources(resolved, resolved)
+    }
+
+    /// Lint a resolved document, reading policy rule ids from `expanded`: the
+    /// document parsed from the Resolved Source Form, before singleton merging.
+    pub fn lint_with_sources(
+        &self,
+        resolved: &FacetDocument,
+        expanded: &FacetDocument,
+    ) -> Vec<LintDiagnostic> {
+        let mut diagnostics = Vec::new();
+        self.check_unused_vars(resolved, &mut diagnostics);
+        self.check_unreferenced_interfaces(resolved, &mut diagnostics);
+        self.check_dead_messages(resolved, &mut diagnostics);
+        self.check_duplicate_policy_rule_ids(expanded, &mut diagnostics);
+        self.check_shadowed_allow_rules(resolved, expanded, &mut diagnostics);
+        diagnostics
+    }
+
+    fn report(
+        &self,
+        diagnostics: &mut Vec<LintDiagnostic>,
+        rule: &'static str,
+        message: String,
+        span: &Span,
+        suggestion: Option<LintSuggestion>,
+    ) {
+        let severity = self.config.severity(rule);
+        if severity == LintSeverity::Off {
+            return;
+        }
+        let (file, span, suggestion) = match &self.source_map {
+            Some(source_map) => match source_map.locate(span) {
+                Some((file, mapped)) => {
+                    // Autofix only edits the entry file.
+                    let suggestion = suggestion
+                        .filter(|_| file.is_none())
+                        .and_then(|suggestion| entry_file_suggestion(source_map, suggestion));
+                    (file.map(PathBuf::from), mapped, suggestion)
+                }
+                None => (None, span.clone(), None),
+            },
+            None => (None, span.clone(), suggestion),
+        };
+        diagnostics.push(LintDiagnostic {
+            code: format!("X.lint.{}", rule),
+            rule,
+            severity,
+            message,
+            span,
+            file,
+            suggestion,
+        });
+    }
+
+    fn check_unused_vars(&self, doc: &FacetDocument, diagnostics: &mut Vec<LintDiagnostic>) {
+        let mut referenced = HashSet::new();
+        for node in &doc.blocks {
+            match node {
+                FacetNode::System(block) => collect_block_refs(block, &mut referenced, true),
+                FacetNode::Meta(block)
+                | FacetNode::User(block)
+                | FacetNode::Assistant(block)
+                | FacetNode::Vars(block)
+                | FacetNode::Context(block)
+                | FacetNode::Policy(block) => collect_block_refs(block, &mut referenced, false),
+                FacetNode::Test(test) => {
+                    for value in test.vars.values().chain(test.input.values()) {
+                        collect_value_refs(value, &mut referenced);
+                    }
+                    for mock in &test.mocks {
+                        collect_value_refs(&mock.return_value, &mut referenced);
+                    }
+                    for body in &test.body {
+                        collect_value_refs(body_value(body), &mut referenced);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let var_type_entries: HashMap<&str, &Span> = doc
+            .blocks
+            .iter()
+            .filter_map(|node| match node {
+                FacetNode::VarTypes(block) => Some(block),
+                _ => None,
+            })
+            .flat_map(|block| &block.body)
+            .filter_map(|body| match body {
+                BodyNode::KeyValue(kv) => Some((kv.key.as_str(), &kv.span)),
+                _ => None,
+            })
+            .collect();
+
+        for node in &doc.blocks {
+            let FacetNode::Vars(block) = node else {
+                continue;
+            };
+            for body in &block.body {
+                let BodyNode::KeyValue(kv) = body else {
+                    continue;
+                };
+                if referenced.contains(kv.key.as_str()) {
+                    continue;
+                }
+                let message = format!(
+                    "Variable '{}' is declared in @vars but never referenced",
+                    kv.key
+                );
+                // Removing an @input, lens call or reference could change the inputs
+                // a host must supply or what runs, so only literals get an autofix.
+                if !is_literal(&kv.value) {
+                    self.report(diagnostics, UNUSED_VAR, message, &kv.span, None);
+                    continue;
+                }
+                // Removing only the @vars entry would leave its declaration orphaned.
+                let (fix_message, additional_edits) = match var_type_entries.get(kv.key.as_str()) {
+                    Some(span) => (
+                        format!("Remove '{}' from @vars and @var_types", kv.key),
+                        vec![LintEdit {
+                            span: (*span).clone(),
+                            replacement: String::new(),
+                        }],
+                    ),
+                    None => (format!("Remove '{}' from @vars", kv.key), Vec::new()),
+                };
+                self.report(
+                    diagnostics,
+                    UNUSED_VAR,
+                    message,
+                    &kv.span,
+                    Some(LintSuggestion {
+                        message: fix_message,
+                        replacement: String::new(),
+                        additional_edits,
+                    }),
+                );
+            }
+        }
+    }
+
+    fn check_unreferenced_interfaces(
+        &self,
+        doc: &FacetDocument,
+        diagnostics: &mut Vec<LintDiagnostic>,
+    ) {
+        let mut has_tool_refs = false;
+        let mut referenced = HashSet::new();
+        for node in &doc.blocks {
+            let FacetNode::System(block) = node else {
+                continue;
+            };
+            for body in &block.body {
+                if let BodyNode::KeyValue(kv) = body {
+                    if kv.key == "tools" {
+                        has_tool_refs = true;
+                        if let ValueNode::List(items) = &kv.value {
+                            referenced.extend(items.iter().filter_map(|item| match item {
+                                ValueNode::Variable(name) => Some(name.as_str()),
+                                _ => None,
+                            }));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Without any @system.tools list every interface is exposed implicitly.
+        if !has_tool_refs {
+            return;
+        }
+
+        for node in &doc.blocks {
+            if let FacetNode::Interface(interface) = node {
+                if !referenced.contains(interface.name.as_str()) {
+                    self.report(
+                        diagnostics,
+                        UNREFERENCED_INTERFACE,
+                        format!(
+                            "Interface '{}' is not referenced by any @system.tools list",
+                            interface.name
+                        ),
+                        &interface.span,
+                        None,
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_dead_messages(&self, doc: &FacetDocument, diagnostics: &mut Vec<LintDiagnostic>) {
+        for node in &doc.blocks {
+            let (facet, block) = match node {
+                FacetNode::System(block) => ("@system", block),
+                FacetNode::User(block) => ("@user", block),
+                FacetNode::Assistant(block) => ("@assistant", block),
+                _ => continue,
+            };
+            let body_when = block.body.iter().filter_map(|body| match body {
+                BodyNode::KeyValue(kv) if kv.key == "when" => Some(&kv.value),
+                _ => None,
+            });
+            let never_emitted = block
+                .attributes
+                .get("when")
+                .into_iter()
+                .chain(body_when)
+                .any(|value| matches!(value, ValueNode::Scalar(ScalarValue::Bool(false))));
+            if never_emitted {
+                self.report(
+                    diagnostics,
+                    DEAD_MESSAGE,
+                    format!("{} block has `when: false` and is never emitted", facet),
+                    &block.span,
+                    Some(LintSuggestion {
+                        message: format!("Remove the {} block", facet),
+                        replacement: String::new(),
+                        additional_edits: Vec::new(),
+                    }),
+                );
+            }
+        }
+    }
+
+    fn check_duplicate_policy_rule_ids(
+        &self,
+        doc: &FacetDocument,
+        diagnostics: &mut Vec<LintDiagnostic>,
+    ) {
+        let mut seen: HashMap<String, String> = HashMap::new();
+        for node in &doc.blocks {
+            let FacetNode::Policy(block) = node else {
+                continue;
+            };
+            for body in &block.body {
+                let BodyNode::KeyValue(kv) = body else {
+                    continue;
+                };
+                if kv.key != "allow" && kv.key != "deny" {
+                    continue;
+                }
+                let ValueNode::List(rules) = &kv.value else {
+                    continue;
+                };
+                for rule in rules {
+                    let Some(id) = rule_field(rule, "id") else {
+                        continue;
+                    };
+                    match seen.get(id) {
+                        Some(first_list) => self.report(
+                            diagnostics,
+                            DUPLICATE_POLICY_RULE_ID,
+                            format!(
+                                "Policy rule id '{}' in @policy.{} was already declared in @policy.{}",
+                                id, kv.key, first_list
+                            ),
+                            &kv.span,
+                            None,
+                        ),
+                        None => {
+                            seen.insert(id.to_string(), kv.key.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_shadowed_allow_rules(
+        &self,
+        doc: &FacetDocument,
+        expanded: &FacetDocument,
+        diagnostics: &mut Vec<LintDiagnostic>,
+    ) {
+        for node in &doc.blocks {
+            let FacetNode::Policy(block) = node else {
+                continue;
+            };
+            let Some((_, deny_rules)) = policy_list(block, "deny") else {
+                continue;
+            };
+            let Some((allow_span, allow_rules)) = policy_list(block, "allow") else {
+                continue;
+            };
+
+            for allow in allow_rules {
+                // Deny rules are evaluated before allow rules for every op (§16.6.2).
+                let Some(deny) = deny_rules
+                    .iter()
+                    .find(|deny| deny_shadows_allow(deny, allow))
+                else {
+                    continue;
+                };
+                let describe = |rule: &ValueNode| match rule_field(rule, "id") {
+                    Some(id) => format!("'{}'", id),
+                    None => format!("for '{}'", rule_field(rule, "name").unwrap_or("*")),
+                };
+                self.report(
+                    diagnostics,
+                    SHADOWED_ALLOW_RULE,
+                    format!(
+                        "Allow rule {} can never match: deny rule {} always matches first",
+                        describe(allow),
+                        describe(deny)
+                    ),
+                    self.allow_rule_span(expanded, allow)
+                        .as_ref()
+                        .unwrap_or(allow_span),
+                    None,
+                );
+            }
+        }
+    }
+
+    /// Span of `rule` inside an `@policy.allow` list of `expanded`, when the source is known.
+    fn allow_rule_span(&self, expanded: &FacetDocument, rule: &ValueNode) -> Option<Span> {
+        let source = self.source.as_deref()?;
+        expanded.blocks.iter().find_map(|node| {
+            let FacetNode::Policy(block) = node else {
+                return None;
+            };
+            block.body.iter().find_map(|body| match body {
+                BodyNode::KeyValue(kv) if kv.key == "allow" => {
+                    let ValueNode::List(rules) = &kv.value else {
+                        return None;
+                    };
+                    let index = rules.iter().position(|candidate| candidate == rule)?;
+                    fct_parser::list_item_spans(source, &kv.span)?
+                        .into_iter()
+                        .nth(index)
+                }
+                _ => None,
+            })
+        })
+    }
+}
+
+/// `suggestion` with its additional edits moved to entry-file spans, or `None`
+/// when one of them lies in an imported file.
+fn entry_file_suggestion(
+    source_map: &SourceMap,
+    mut suggestion: LintSuggestion,
+) -> Option<LintSuggestion> {
+    for edit in &mut suggestion.additional_edits {
+        let (None, span) = source_map.locate(&edit.span)? else {
+            return None;
+        };
+        edit.span = span;
+    }
+    Some(suggestion)
+}
+
+fn is_literal(value: &ValueNode) -> bool {
+    match value {
+        ValueNode::String(_) | ValueNode::Scalar(_) => true,
+        ValueNode::List(items) => items.iter().all(is_literal),
+        ValueNode::Map(map) => map.values().all(is_literal),
+        ValueNode::Variable(_) | ValueNode::Pipeline(_) | ValueNode::Directive(_) => false,
+    }
+}
+
+fn body_value(body: &BodyNode) -> &ValueNode {
+    match body {
+        BodyNode::KeyValue(kv) => &kv.value,
+        BodyNode::ListItem(item) => &item.value,
+    }
+}
+
+fn collect_block_refs<'a>(block: &'a FacetBlock, refs: &mut HashSet<&'a str>, skip_tools: bool) {
+    for value in block.attributes.values() {
+        collect_value_refs(value, refs);
+    }
+    for body in &block.body {
+        // `@system.tools` entries name interfaces, not variables.
+        if skip_tools && matches!(body, BodyNode::KeyValue(kv) if kv.key == "tools") {
+            continue;
+        }
+        collect_value_refs(body_value(body), refs);
+    }
+}
+
+fn collect_value_refs<'a>(value: &'a ValueNode, refs: &mut HashSet<&'a str>) {
+    match value {
+        ValueNode::Variable(var_ref) => {
+            refs.insert(var_ref.split('.').next().unwrap_or(var_ref));
+        }
+        ValueNode::Pipeline(pipeline) => {
+            collect_value_refs(&pipeline.initial, refs);
+            for lens in &pipeline.lenses {
+                for arg in lens.args.iter().chain(lens.kwargs.values()) {
+                    collect_value_refs(arg, refs);
+                }
+            }
+        }
+        ValueNode::Directive(directive) => {
+            for arg in directive.args.values() {
+                collect_value_refs(arg, refs);
+            }
+        }
+        ValueNode::List(items) => {
+            for item in items {
+                collect_value_refs(item, refs);
+            }
+        }
+        ValueNode::Map(map) => {
+            for item in map.values() {
+                collect_value_refs(item, refs);
+            }
+        }
+        ValueNode::Scalar(_) | ValueNode::String(_) => {}
+    }
+}
+
+fn policy_list<'a>(block: &'a FacetBlock, key: &str) -> Option<(&'a Span, &'a Vec<ValueNode>)> {
+    block.body.iter().find_map(|body| match body {
+        BodyNode::KeyValue(kv) if kv.key == key => match &kv.value {
+            ValueNode::List(rules) => Some((&kv.span, rules)),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+fn rule_field<'a>(rule: &'a ValueNode, field: &str) -> Option<&'a str> {
+    match rule {
+        ValueNode::Map(map) => match map.get(field) {
+            Some(ValueNode::String(value)) => Some(value.as_str()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn deny_shadows_allow(deny: &ValueNode, allow: &ValueNode) -> bool {
+    let (ValueNode::Map(deny_map), ValueNode::Map(_)) = (deny, allow) else {
+        return false;
+    };
+    // Only a deny that always fires (no condition, or a literal one) shadows anything.
+    let unconditional = matches!(
+        deny_map.get("when"),
+        None | Some(ValueNode::Scalar(ScalarValue::Bool(true)))
+    ) && matches!(
+        deny_map.get("unless"),
+        None | Some(ValueNode::Scalar(ScalarValue::Bool(false)))
+    );
+
+    unconditional
+        && rule_field(deny, "op").is_some()
+        && rule_field(deny, "op") == rule_field(allow, "op")
+        && matcher_covers(rule_field(deny, "name"), rule_field(allow, "name"))
+        && matcher_covers(rule_field(deny, "effect"), rule_field(allow, "effect"))
+}
+
+/// Whether every value matched by `inner` is also matched by `outer`.
+/// `None` matches everything; `prefix.*` matches by prefix; otherwise exact.
+fn matcher_covers(outer: Option<&str>, inner: Option<&str>) -> bool {
+    let Some(outer) = outer else {
+        return true;
+    };
+    match (outer.strip_suffix(".*"), inner) {
+        (Some(""), _) => true,
+        (_, None) => false,
+        (Some(prefix), Some(inner)) => inner
+            .strip_suffix(".*")
+            .unwrap_or(inner)
+            .starts_with(prefix),
+        (None, Some(inner)) => outer == inner,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(source: &str) -> Vec<LintDiagnostic> {
+        let doc = fct_parser::parse_document(source).expect("source should parse");
+        Linter::default().lint(&doc)
+    }
+
+    fn rules(diagnostics: &[LintDiagnostic]) -> Vec<&'static str> {
+        diagnostics.iter().map(|d| d.rule).collect()
+    }
+
+    #[test]
+    fn reports_unused_vars_with_removal_suggestion() {
+        let diagnostics = lint(
+            "@vars\n  name: \"Ada\"\n  greeting: $name\n  spare: 1\n@user\n  content: $greeting\n",
+        );
+        assert_eq!(rules(&diagnostics), vec![UNUSED_VAR]);
+        assert_eq!(diagnostics[0].code, "X.lint.unused_var");
+        assert!(diagnostics[0].message.contains("'spare'"));
+        assert_eq!(
+            diagnostics[0]
+                .suggestion
+                .as_ref()
+                .map(|s| s.replacement.as_str()),
+            Some("")
+        );
+    }
+
+    #[test]
+    fn unused_var_suggestion_also_removes_its_var_types_entry() {
+        let diagnostics =
+            lint("@var_types\n  spare: \"int\"\n@vars\n  spare: 1\n  name: \"Ada\"\n@user\n  content: $name\n");
+        assert_eq!(rules(&diagnostics), vec![UNUSED_VAR]);
+        let suggestion = diagnostics[0].suggestion.as_ref().expect("autofix offered");
+        assert_eq!(
+            suggestion.message,
+            "Remove 'spare' from @vars and @var_types"
+        );
+        assert_eq!(suggestion.additional_edits.len(), 1);
+        assert_eq!(suggestion.additional_edits[0].span.line, 2);
+        assert_eq!(suggestion.additional_edits[0].replacement, "");
+    }
+
+    #[test]
+    fn unused_var_autofix_is_limited_to_literals() {
+        let diagnostics = lint(
+            "@vars\n  query: @input(type=\"string\")\n  shout: \"hi\" |> uppercase()\n  tags: [\"a\", { b: 1 }]\n@user\n  content: \"hi\"\n",
+        );
+        assert_eq!(rules(&diagnostics), vec![UNUSED_VAR; 3]);
+        let fixable: Vec<bool> = diagnostics.iter().map(|d| d.suggestion.is_some()).collect();
+        assert_eq!(fixable, vec![false, false, true]);
+    }
+
+    #[test]
+    fn reports_unreferenced_interfaces_only_when_tools_are_listed() {
+        let source = "@interface Weather\n  fn get(city: string) -> string\n@interface Mail\n  fn send(to: string) -> string\n@system\n  content: \"hi\"\n  tools: [$Weather]\n";
+        let diagnostics = lint(source);
+        assert_eq!(rules(&diagnostics), vec![UNREFERENCED_INTERFACE]);
+        assert!(diagnostics[0].message.contains("'Mail'"));
+
+        let implicit = lint("@interface Mail\n  fn send(to: string) -> string\n");
+        assert!(implicit.is_empty());
+    }
+
+    #[test]
+    fn reports_messages_with_literal_false_when() {
+        let diagnostics = lint("@user(when=false)\n  content: \"a\"\n@user\n  content: \"b\"\n  when: false\n@user\n  content: \"c\"\n  when: true\n");
+        assert_eq!(rules(&diagnostics), vec![DEAD_MESSAGE, DEAD_MESSAGE]);
+    }
+
+    #[test]
+    fn reports_duplicate_rule_ids_across_policy_blocks() {
+        let source = "@policy\n  allow: [{ id: \"r1\", op: \"lens_call\", name: \"trim\" }]\n@policy\n  deny: [{ id: \"r1\", op: \"lens_call\", name: \"trim\" }]\n";
+        let doc = fct_parser::parse_document(source).expect("source should parse");
+        let diagnostics = Linter::default().lint(&doc);
+        assert_eq!(rules(&diagnostics), vec![DUPLICATE_POLICY_RULE_ID]);
+        assert!(diagnostics[0].message.contains("'r1'"));
+    }
+
+    #[test]
+    fn reports_allow_rules_shadowed_by_unconditional_deny() {
+        let source = "@policy\n  deny: [{ id: \"no-net\", op: \"tool_call\", name: \"Net.*\" }, { id: \"maybe\", op: \"tool_call\", name: \"Mail.send\", when: $flag }]\n  allow: [{ id: \"fetch\", op: \"tool_call\", name: \"Net.fetch\" }, { id: \"mail\", op: \"tool_call\", name: \"Mail.send\" }, { id: \"any\", op: \"tool_call\", name: \"Other.*\" }]\n@vars\n  flag: true\n";
+        let diagnostics = lint(source);
+        assert_eq!(rules(&diagnostics), vec![SHADOWED_ALLOW_RULE]);
+        assert!(diagnostics[0].message.contains("'fetch'"));
+        assert!(diagnostics[0].message.contains("'no-net'"));
+    }
+
+    #[test]
+    fn shadowed_allow_rule_points_at_the_rule_when_source_is_known() {
+        let source = "@policy\n  deny: [{ op: \"tool_call\", name: \"Net.*\" }]\n  allow: [\n    { id: \"mail\", op: \"tool_call\", name: \"Mail.send\" },\n    { id: \"fetch\", op: \"tool_call\", name: \"Net.fetch\" }\n  ]\n";
+        let doc = fct_parser::parse_document(source).expect("source should parse");
+
+        let diagnostics = Linter::default().with_source(source).lint(&doc);
+        assert_eq!(rules(&diagnostics), vec![SHADOWED_ALLOW_RULE]);
+        assert_eq!(
+            (diagnostics[0].span.line, diagnostics[0].span.column),
+            (5, 5)
+        );
+
+        let without_source = Linter::default().lint(&doc);
+        assert_eq!(without_source[0].span.line, 3);
+    }
+
+    #[test]
+    fn config_overrides_and_disables_rules() {
+        let config = LintConfig::new()
+            .with_severity(UNUSED_VAR, LintSeverity::Error)
+            .expect("known rule")
+            .with_severity(DEAD_MESSAGE, LintSeverity::Off)
+            .expect("known rule");
+        let doc =
+            fct_parser::parse_document("@vars\n  spare: 1\n@user(when=false)\n  content: \"a\"\n")
+                .expect("source should parse");
+        let diagnostics = Linter::new(config).lint(&doc);
+        assert_eq!(rules(&diagnostics), vec![UNUSED_VAR]);
+        assert_eq!(diagnostics[0].severity, LintSeverity::Error);
+
+        assert!(LintConfig::new()
+            .with_severity("no_such_rule", LintSeverity::Error)
+            .is_err());
+    }
+}
@@ -0,0 +1,432 @@
+//! # Value Checks
+//!
+//! Path-aware validation of concrete values against FTS types and
+//! `@var_types` constraints. Phase 2 uses it for literal constraint checks and
+//! the R-DAG engine uses [`InputTypeTable`] for runtime `@input` values.
+
+use crate::checker::parse_type_expr;
+use crate::constraints::TypeConstraints;
+use crate::types::{AudioType, EmbeddingType, FacetType, ImageType, MultimodalType, PrimitiveType};
+use crate::{TypeChecker, VarTypeDecl};
+use fct_ast::{BodyNode, FacetDocument, FacetNode, OrderedMap, ScalarValue, ValueNode};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A single failed check, located by a JSON path (`$`, `$.user.tags[2]`).
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ValueViolation {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValueViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Join violations into one diagnostic line.
+pub fn describe_violations(violations: &[ValueViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Check a concrete value against a type and optional top-level constraints,
+/// returning every violation found.
+///
+/// Runtime values come from JSON, so `int` values are accepted where `float`
+/// is declared.
+pub fn check_value(
+    value: &ValueNode,
+    ty: &FacetType,
+    constraints: Option<&TypeConstraints>,
+) -> Vec<ValueViolation> {
+    let mut violations = Vec::new();
+    check_type(value, ty, "$", &mut violations);
+    if let Some(constraints) = constraints {
+        violations.extend(constraints.check_value(value, "$"));
+    }
+    violations
+}
+
+fn check_type(value: &ValueNode, ty: &FacetType, path: &str, out: &mut Vec<ValueViolation>) {
+    let mismatch = |out: &mut Vec<ValueViolation>| {
+        out.push(ValueViolation {
+            path: path.to_string(),
+            message: format!("expected {}, got {}", ty, value_kind(value)),
+        })
+    };
+
+    match ty {
+        FacetType::Any => {}
+        FacetType::Primitive(primitive) => {
+            let ok = matches!(
+                (primitive, value),
+                (PrimitiveType::Any, _)
+                    | (PrimitiveType::String, ValueNode::String(_))
+                    | (PrimitiveType::Int, ValueNode::Scalar(ScalarValue::Int(_)))
+                    | (
+                        PrimitiveType::Float | PrimitiveType::Number,
+                        ValueNode::Scalar(ScalarValue::Int(_) | ScalarValue::Float(_)),
+                    )
+                    | (
+                        PrimitiveType::Bool | PrimitiveType::Boolean,
+                        ValueNode::Scalar(ScalarValue::Bool(_)),
+                    )
+                    | (PrimitiveType::Null, ValueNode::Scalar(ScalarValue::Null))
+            );
+            if !ok {
+                mismatch(out);
+            }
+        }
+        FacetType::List(item_ty) => match value {
+            ValueNode::List(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    check_type(item, item_ty, &format!("{path}[{idx}]"), out);
+                }
+            }
+            _ => mismatch(out),
+        },
+        FacetType::Map(value_ty) => match value {
+            ValueNode::Map(map) => {
+                for (key, entry) in map {
+                    check_type(entry, value_ty, &child_path(path, key), out);
+                }
+            }
+            _ => mismatch(out),
+        },
+        FacetType::Struct(fields) => match value {
+            ValueNode::Map(map) => {
+                for field in fields {
+                    match map.get(&field.name) {
+                        Some(field_value) => check_type(
+                            field_value,
+                            &field.field_type,
+                            &child_path(path, &field.name),
+                            out,
+                        ),
+                        None if field.required => out.push(ValueViolation {
+                            path: child_path(path, &field.name),
+                            message: "required field is missing".to_string(),
+                        }),
+                        None => {}
+                    }
+                }
+            }
+            _ => mismatch(out),
+        },
+        FacetType::Union(members) => {
+            let matched = members.iter().any(|member| {
+                let mut member_violations = Vec::new();
+                check_type(value, member, path, &mut member_violations);
+                member_violations.is_empty()
+            });
+            if !matched {
+                mismatch(out);
+            }
+        }
+        FacetType::Multimodal(media) => {
+            if !multimodal_value_matches(value, media) {
+                mismatch(out);
+            }
+        }
+        FacetType::Image { max_dim, format } => {
+            let media = MultimodalType::Image(ImageType {
+                max_dim: *max_dim,
+                format: format.clone(),
+            });
+            if !multimodal_value_matches(value, &media) {
+                mismatch(out);
+            }
+        }
+        FacetType::Audio {
+            max_duration,
+            format,
+        } => {
+            let media = MultimodalType::Audio(AudioType {
+                max_duration: *max_duration,
+                format: format.clone(),
+            });
+            if !multimodal_value_matches(value, &media) {
+                mismatch(out);
+            }
+        }
+        FacetType::Embedding { size } => {
+            let media = MultimodalType::Embedding(EmbeddingType { size: *size });
+            if !multimodal_value_matches(value, &media) {
+                mismatch(out);
+            }
+        }
+        // Function/never types have no value representation.
+        _ => mismatch(out),
+    }
+}
+
+/// Structural check for multimodal values (`embedding` lists and
+/// `{ kind, format, shape }` image/audio asset maps).
+pub(crate) fn multimodal_value_matches(value: &ValueNode, media: &MultimodalType) -> bool {
+    match (media, value) {
+        (MultimodalType::Embedding(embed_ty), ValueNode::List(items)) => {
+            items.len() == embed_ty.size
+                && items.iter().all(|item| {
+                    matches!(
+                        item,
+                        ValueNode::Scalar(ScalarValue::Int(_))
+                            | ValueNode::Scalar(ScalarValue::Float(_))
+                    )
+                })
+        }
+        (MultimodalType::Image(image_ty), ValueNode::Map(map)) => {
+            if !asset_header_matches(map, "image", image_ty.format.as_deref()) {
+                return false;
+            }
+            let Some(max_dim) = image_ty.max_dim else {
+                return true;
+            };
+            let Some(ValueNode::Map(shape)) = map.get("shape") else {
+                return false;
+            };
+            let dim_ok = |key: &str| {
+                matches!(
+                    shape.get(key),
+                    Some(ValueNode::Scalar(ScalarValue::Int(v))) if *v >= 0 && (*v as u32) <= max_dim
+                )
+            };
+            dim_ok("width") && dim_ok("height")
+        }
+        (MultimodalType::Audio(audio_ty), ValueNode::Map(map)) => {
+            if !asset_header_matches(map, "audio", audio_ty.format.as_deref()) {
+                return false;
+            }
+            let Some(max_duration) = audio_ty.max_duration else {
+                return true;
+            };
+            let Some(ValueNode::Map(shape)) = map.get("shape") else {
+                return false;
+            };
+            let duration = match shape.get("duration") {
+                Some(ValueNode::Scalar(ScalarValue::Int(v))) => *v as f64,
+                Some(ValueNode::Scalar(ScalarValue::Float(v))) => *v,
+                _ => return false,
+            };
+            (0.0..=max_duration).contains(&duration)
+        }
+        _ => false,
+    }
+}
+
+fn asset_header_matches(
+    map: &OrderedMap<String, ValueNode>,
+    kind: &str,
+    required_format: Option<&str>,
+) -> bool {
+    if !matches!(map.get("kind"), Some(ValueNode::String(k)) if k == kind) {
+        return false;
+    }
+    match required_format {
+        Some(format) => matches!(map.get("format"), Some(ValueNode::String(v)) if v == format),
+        None => true,
+    }
+}
+
+fn child_path(parent: &str, key: &str) -> String {
+    let is_identifier = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_identifier {
+        format!("{parent}.{key}")
+    } else {
+        format!("{parent}[{key:?}]")
+    }
+}
+
+fn value_kind(value: &ValueNode) -> &'static str {
+    match value {
+        ValueNode::String(_) => "string",
+        ValueNode::Scalar(ScalarValue::Int(_)) => "int",
+        ValueNode::Scalar(ScalarValue::Float(_)) => "float",
+        ValueNode::Scalar(ScalarValue::Bool(_)) => "bool",
+        ValueNode::Scalar(ScalarValue::Null) => "null",
+        ValueNode::List(_) => "list",
+        ValueNode::Map(_) => "map",
+        ValueNode::Variable(_) => "variable reference",
+        ValueNode::Pipeline(_) => "pipeline",
+        ValueNode::Directive(_) => "directive",
+    }
+}
+
+/// Short human-readable rendering of a value for diagnostics.
+pub(crate) fn render_value(value: &ValueNode) -> String {
+    match value {
+        ValueNode::String(s) => s.clone(),
+        ValueNode::Scalar(ScalarValue::Int(i)) => i.to_string(),
+        ValueNode::Scalar(ScalarValue::Float(f)) => f.to_string(),
+        ValueNode::Scalar(ScalarValue::Bool(b)) => b.to_string(),
+        ValueNode::Scalar(ScalarValue::Null) => "null".to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+/// Type information needed to validate runtime `@input` values.
+///
+/// Holds the document's resolved `@types` aliases and, for every `@vars`
+/// entry whose whole value is an `@input(...)` directive, its `@var_types`
+/// declaration. Building the table never fails: documents are expected to
+/// pass Phase 2 first, and unresolvable declarations are simply skipped.
+#[derive(Debug, Clone, Default)]
+pub struct InputTypeTable {
+    aliases: HashMap<String, FacetType>,
+    declarations: HashMap<String, VarTypeDecl>,
+}
+
+impl InputTypeTable {
+    pub fn from_document(doc: &FacetDocument) -> Self {
+        let mut checker = TypeChecker::new();
+        // A failed alias load keeps whatever resolved before the failure.
+        let _ = checker.load_type_aliases(doc);
+
+        let mut var_types = HashMap::new();
+        for block in &doc.blocks {
+            if let FacetNode::VarTypes(var_types_block) = block {
+                for body in &var_types_block.body {
+                    if let BodyNode::KeyValue(kv) = body {
+                        var_types.insert(kv.key.clone(), &kv.value);
+                    }
+                }
+            }
+        }
+
+        let mut declarations = HashMap::new();
+        for block in &doc.blocks {
+            let FacetNode::Vars(vars_block) = block else {
+                continue;
+            };
+            for body in &vars_block.body {
+                let BodyNode::KeyValue(kv) = body else {
+                    continue;
+                };
+                if !matches!(&kv.value, ValueNode::Directive(d) if d.name == "input") {
+                    continue;
+                }
+                if let Some(decl) = var_types
+                    .get(&kv.key)
+                    .and_then(|value| checker.parse_type_declaration(value).ok())
+                {
+                    declarations.insert(kv.key.clone(), decl);
+                }
+            }
+        }
+
+        Self {
+            aliases: checker.type_aliases().clone(),
+            declarations,
+        }
+    }
+
+    /// Validate a runtime input value for `var_name` against the directive's
+    /// declared type and, when present, the variable's `@var_types` entry.
+    pub fn check_input(
+        &self,
+        var_name: &str,
+        declared_type: &str,
+        value: &ValueNode,
+    ) -> Result<(), Vec<ValueViolation>> {
+        let input_type = parse_type_expr(declared_type, &self.aliases).map_err(|e| {
+            vec![ValueViolation {
+                path: "$".to_string(),
+                message: e.to_string(),
+            }]
+        })?;
+
+        let mut violations = check_value(value, &input_type, None);
+        if let Some(decl) = self.declarations.get(var_name) {
+            for violation in check_value(value, &decl.var_type, decl.constraints.as_ref()) {
+                if !violations.contains(&violation) {
+                    violations.push(violation);
+                }
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::StructField;
+
+    fn string() -> FacetType {
+        FacetType::Primitive(PrimitiveType::String)
+    }
+
+    #[test]
+    fn check_value_reports_every_violation_with_json_path() {
+        let ty = FacetType::Struct(vec![
+            StructField {
+                name: "name".to_string(),
+                field_type: string(),
+                required: true,
+            },
+            StructField {
+                name: "tags".to_string(),
+                field_type: FacetType::List(Box::new(string())),
+                required: true,
+            },
+            StructField {
+                name: "nick".to_string(),
+                field_type: string(),
+                required: false,
+            },
+        ]);
+        let value = ValueNode::Map(OrderedMap::from([(
+            "tags".to_string(),
+            ValueNode::List(vec![
+                ValueNode::String("a".to_string()),
+                ValueNode::Scalar(ScalarValue::Int(1)),
+            ]),
+        )]));
+
+        let violations = check_value(&value, &ty, None);
+        let paths: Vec<&str> = violations.iter().map(|v| v.path.as_str()).collect();
+        assert_eq!(paths, vec!["$.name", "$.tags[1]"]);
+        assert_eq!(violations[1].message, "expected string, got int");
+    }
+
+    #[test]
+    fn check_value_accepts_int_for_float_and_quotes_odd_keys() {
+        let float = FacetType::Primitive(PrimitiveType::Float);
+        assert!(check_value(&ValueNode::Scalar(ScalarValue::Int(3)), &float, None).is_empty());
+
+        let ty = FacetType::Map(Box::new(float));
+        let value = ValueNode::Map(OrderedMap::from([(
+            "a b".to_string(),
+            ValueNode::String("x".to_string()),
+        )]));
+        let violations = check_value(&value, &ty, None);
+        assert_eq!(violations[0].path, "$[\"a b\"]");
+    }
+
+    #[test]
+    fn check_value_collects_constraint_violations() {
+        let mut constraints = TypeConstraints::new();
+        constraints.min_length = Some(5);
+        constraints.format = Some(crate::constraints::StringFormat::Email);
+        let violations = check_value(
+            &ValueNode::String("abc".to_string()),
+            &string(),
+            Some(&constraints),
+        );
+        let messages: Vec<&str> = violations.iter().map(|v| v.message.as_str()).collect();
+        assert_eq!(messages, vec!["min_length >= 5", "format 'email'"]);
+    }
+}
@@ -3,6 +3,8 @@
 //! This module implements the build command for the FACET compiler.
 //! The build command parses, resolves, validates, and compiles FACET documents.
 
+use crate::commands::host_profile::load_host_setup;
+use crate::commands::mode_profile::resolve_profile;
 use anyhow::{Context, Result};
 use console::style;
 use fct_parser::parse_document;
@@ -19,6 +21,9 @@ const INFO: console::Emoji = console::Emoji("ℹ️ ", "[INFO] ");
 /// Build command handler
 pub fn execute_build(
     input: PathBuf,
+    profile: String,
+    host_profile: Option<PathBuf>,
+    host_context: Option<PathBuf>,
     verbose: bool,
     _no_progress: bool,
     rate_limiter: &crate::commands::DefaultRateLimiter,
@@ -32,7 +37,13 @@ pub fn execute_build(
         std::process::exit(1);
     }
 
-    info!("Building FACET document: {:?}", input);
+    let (validation_profile, profile_label) = resolve_profile(&profile)?;
+    let host = load_host_setup(host_profile.as_deref(), host_context.as_deref())?;
+
+    info!(
+        "Building FACET document: {:?} (profile: {})",
+        input, profile_label
+    );
     println!("{} Building {:?}", GEAR, input);
 
     if verbose {
@@ -56,7 +67,10 @@ pub fn execute_build(
         .resolve(parsed)
         .map_err(|e| anyhow::anyhow!("Resolution error: {}", e))?;
 
-    let mut checker = TypeChecker::new();
+    let mut checker = TypeChecker::new().with_profile(validation_profile);
+    if let Some(host) = &host {
+        checker = checker.with_host_context(host.schema.clone());
+    }
     checker
         .validate(&resolved)
         .map_err(|e| anyhow::anyhow!("Validation error: {}", e))?;
@@ -95,7 +109,16 @@ mod tests {
         .expect("write input");
 
         let limiter = RateLimiter::direct(Quota::per_second(nonzero!(10u32)));
-        execute_build(input_path, false, true, &limiter).expect("build should succeed");
+        execute_build(
+            input_path,
+            "hypervisor".to_string(),
+            None,
+            None,
+            false,
+            true,
+            &limiter,
+        )
+        .expect("build should succeed");
 
         let _ = fs::remove_dir_all(test_dir);
     }
@@ -113,7 +136,16 @@ mod tests {
         fs::write(&input_path, "@system\n content: \"bad indent\"\n").expect("write input");
 
         let limiter = RateLimiter::direct(Quota::per_second(nonzero!(10u32)));
-        let err = execute_build(input_path, false, true, &limiter).unwrap_err();
+        let err = execute_build(
+            input_path,
+            "hypervisor".to_string(),
+            None,
+            None,
+            false,
+            true,
+            &limiter,
+        )
+        .unwrap_err();
         assert!(err.to_string().contains("Parse error"));
 
         let _ = fs::remove_dir_all(test_dir);
@@ -142,8 +174,16 @@ mod tests {
         .expect("write input");
 
         let limiter = RateLimiter::direct(Quota::per_second(nonzero!(10u32)));
-        let err = execute_build(input_path, false, true, &limiter)
-            .expect_err("build must reject import outside allowed root");
+        let err = execute_build(
+            input_path,
+            "hypervisor".to_string(),
+            None,
+            None,
+            false,
+            true,
+            &limiter,
+        )
+        .expect_err("build must reject import outside allowed root");
         let text = err.to_string();
         assert!(
             text.contains("Resolution error"),
@@ -154,4 +194,96 @@ mod tests {
         let _ = fs::remove_dir_all(root_dir);
         let _ = fs::remove_dir_all(outside_dir);
     }
+
+    #[test]
+    fn execute_build_core_profile_rejects_interface_with_f801() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let test_dir = std::env::temp_dir().join(format!("facet-build-core-{}", nonce));
+        fs::create_dir_all(&test_dir).expect("create temp dir");
+
+        let input_path = test_dir.join("input.facet");
+        fs::write(
+            &input_path,
+            "@interface WeatherAPI\n  fn get(city: string) -> string (effect=\"read\")\n\n@user\n  content: \"hi\"\n",
+        )
+        .expect("write input");
+
+        let limiter = RateLimiter::direct(Quota::per_second(nonzero!(10u32)));
+        execute_build(
+            input_path.clone(),
+            "hypervisor".to_string(),
+            None,
+            None,
+            false,
+            true,
+            &limiter,
+        )
+        .expect("hypervisor build accepts @interface");
+        let err = execute_build(
+            input_path,
+            "core".to_string(),
+            None,
+            None,
+            false,
+            true,
+            &limiter,
+        )
+        .expect_err("core build must reject @interface");
+        let text = err.to_string();
+        assert!(text.contains("F801"), "expected F801, got: {text}");
+
+        let _ = fs::remove_dir_all(test_dir);
+    }
+
+    #[test]
+    fn execute_build_accepts_host_context_conditions_declared_by_the_host_profile() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let test_dir = std::env::temp_dir().join(format!("facet-build-host-{}", nonce));
+        fs::create_dir_all(&test_dir).expect("create temp dir");
+
+        let input_path = test_dir.join("input.facet");
+        fs::write(
+            &input_path,
+            "@interface Payments\n  fn charge(amount: int) -> string (effect=\"payment\")\n\n@user\n  content: \"hi\"\n\n@policy\n  allow: [{ op: \"tool_call\", name: \"Payments.*\", when: $x.acme.admin }]\n",
+        )
+        .expect("write input");
+        let profile_path = test_dir.join("host.json");
+        fs::write(
+            &profile_path,
+            r#"{ "id": "acme.prod.v1", "context": { "acme": "struct { admin: bool }" } }"#,
+        )
+        .expect("write host profile");
+
+        let limiter = RateLimiter::direct(Quota::per_second(nonzero!(10u32)));
+        let err = execute_build(
+            input_path.clone(),
+            "hypervisor".to_string(),
+            None,
+            None,
+            false,
+            true,
+            &limiter,
+        )
+        .expect_err("$x is unknown without a host profile");
+        assert!(err.to_string().contains("F401"), "{err}");
+
+        execute_build(
+            input_path,
+            "hypervisor".to_string(),
+            Some(profile_path),
+            None,
+            false,
+            true,
+            &limiter,
+        )
+        .expect("the host profile declares $x.acme");
+
+        let _ = fs::remove_dir_all(test_dir);
+    }
 }
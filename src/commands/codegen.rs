@@ -5,6 +5,7 @@
 
 use anyhow::{Context, Result};
 use console::style;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use tracing::info;
@@ -103,6 +104,14 @@ pub fn execute_codegen(
 /// Extract interface definitions from parsed document
 fn extract_interfaces(document: &fct_ast::FacetDocument) -> Vec<InterfaceInfo> {
     let mut interfaces = Vec::new();
+    let mut aliases = HashMap::new();
+    for block in &document.blocks {
+        if let fct_ast::FacetNode::Types(types) = block {
+            for alias in &types.aliases {
+                aliases.insert(alias.name.as_str(), &alias.type_node);
+            }
+        }
+    }
 
     for block in &document.blocks {
         if let fct_ast::FacetNode::Interface(interface_block) = block {
@@ -116,10 +125,18 @@ fn extract_interfaces(document: &fct_ast::FacetDocument) -> Vec<InterfaceInfo> {
                         .iter()
                         .map(|param| ParameterInfo {
                             name: param.name.clone(),
-                            param_type: Some(type_node_to_string(&param.type_node)),
+                            param_type: Some(type_node_to_string(
+                                &param.type_node,
+                                &aliases,
+                                &mut Vec::new(),
+                            )),
                         })
                         .collect(),
-                    return_type: Some(type_node_to_string(&func.return_type)),
+                    return_type: Some(type_node_to_string(
+                        &func.return_type,
+                        &aliases,
+                        &mut Vec::new(),
+                    )),
                     description: None, // TODO: Extract description from comments if needed
                 })
                 .collect();
@@ -135,18 +152,36 @@ fn extract_interfaces(document: &fct_ast::FacetDocument) -> Vec<InterfaceInfo> {
     interfaces
 }
 
-/// Convert TypeNode to string representation
-fn type_node_to_string(type_node: &fct_ast::TypeNode) -> String {
+/// Convert TypeNode to string representation, expanding `@types` aliases.
+///
+/// `expanding` tracks the alias chain so a cyclic alias degrades to `any`
+/// instead of recursing forever (codegen does not run the validator).
+fn type_node_to_string<'a>(
+    type_node: &'a fct_ast::TypeNode,
+    aliases: &HashMap<&'a str, &'a fct_ast::TypeNode>,
+    expanding: &mut Vec<&'a str>,
+) -> String {
     match type_node {
         fct_ast::TypeNode::Primitive(name) => match name.as_str() {
             "string" => "string".to_string(),
             "number" => "number".to_string(),
             "boolean" => "boolean".to_string(),
             "object" => "object".to_string(),
+            alias if aliases.contains_key(alias) => {
+                if expanding.contains(&alias) {
+                    return "any".to_string();
+                }
+                expanding.push(alias);
+                let expanded = type_node_to_string(aliases[alias], aliases, expanding);
+                expanding.pop();
+                expanded
+            }
             _ => name.clone(),
         },
         fct_ast::TypeNode::Struct(_) => "object".to_string(),
-        fct_ast::TypeNode::List(inner_type) => format!("{}[]", type_node_to_string(inner_type)),
+        fct_ast::TypeNode::List(inner_type) => {
+            format!("{}[]", type_node_to_string(inner_type, aliases, expanding))
+        }
         fct_ast::TypeNode::Map(_) => "object".to_string(),
         fct_ast::TypeNode::Union(_) => "any".to_string(),
         fct_ast::TypeNode::Image { .. } => "string".to_string(),
@@ -0,0 +1,152 @@
+//! # Compile Command
+//!
+//! This module implements the compile command for the FACET compiler.
+//! The compile command runs Phases 1-2 once and writes a precompiled contract
+//! bundle (`.fctb`) that `run` can execute without re-parsing or re-validating.
+
+use crate::commands::host_profile::load_host_setup;
+use crate::commands::mode_profile::resolve_profile;
+use anyhow::{Context, Result};
+use console::style;
+use fct_compiler::Compiler;
+use std::fs;
+use std::path::PathBuf;
+use tracing::info;
+
+const GEAR: console::Emoji = console::Emoji("⚙️ ", "[COMPILE] ");
+const INFO: console::Emoji = console::Emoji("ℹ️ ", "[INFO] ");
+
+/// Compile command handler
+pub fn execute_compile(
+    input: PathBuf,
+    output: PathBuf,
+    profile: String,
+    host_profile: Option<PathBuf>,
+    rate_limiter: &crate::commands::DefaultRateLimiter,
+) -> Result<()> {
+    // Check rate limit
+    if rate_limiter.check().is_err() {
+        eprintln!(
+            "{}",
+            style("Rate limit exceeded. Please wait before running another command.").red()
+        );
+        std::process::exit(1);
+    }
+
+    let (validation_profile, profile_label) = resolve_profile(&profile)?;
+    info!(
+        "Compiling FACET document: {:?} (profile: {})",
+        input, profile_label
+    );
+    println!("{} Compiling {:?}", GEAR, input);
+
+    let host = load_host_setup(host_profile.as_deref(), None)?;
+    let mut builder = Compiler::builder().profile(validation_profile);
+    if let Some(host) = &host {
+        builder = host.configure(builder);
+    }
+    let bundle = builder.build().load_file(&input)?.bundle()?;
+
+    fs::write(&output, bundle.to_bytes()?)
+        .with_context(|| format!("Failed to write contract bundle: {:?}", output))?;
+
+    println!(
+        "{} {} for host profile {}",
+        INFO, bundle.document_hash, bundle.host_profile_id
+    );
+    println!(
+        "{}",
+        style(format!("✓ Wrote {:?} ({})", output, bundle.integrity)).green()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::run::execute_run;
+    use governor::{Quota, RateLimiter};
+    use nonzero_ext::nonzero;
+
+    fn fixture_dir(name: &str) -> PathBuf {
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("facet-compile-{name}-{nonce}"));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn compiled_bundle_runs_and_rejects_other_host_profiles() {
+        let dir = fixture_dir("bundle");
+        let input = dir.join("contract.facet");
+        fs::write(
+            &input,
+            "@vars\n  name: @input(type=\"string\")\n\n@user\n  content: $name\n",
+        )
+        .unwrap();
+        let runtime_input = dir.join("runtime.json");
+        fs::write(&runtime_input, r#"{"name":"Ada"}"#).unwrap();
+        let bundle = dir.join("contract.fctb");
+
+        let limiter = RateLimiter::direct(Quota::per_second(nonzero!(10u32)));
+        execute_compile(
+            input,
+            bundle.clone(),
+            "hypervisor".to_string(),
+            None,
+            &limiter,
+        )
+        .expect("compile should succeed");
+        assert!(bundle.exists());
+
+        execute_run(
+            bundle.clone(),
+            Some(runtime_input),
+            1024,
+            2048,
+            0,
+            "json".to_string(),
+            false,
+            true,
+            None,
+            None,
+            "hypervisor".to_string(),
+            false,
+            false,
+            None,
+            true,
+            &limiter,
+        )
+        .expect("bundle run should succeed");
+        assert!(dir.join("execution.json").exists());
+
+        let host_profile = dir.join("host.json");
+        fs::write(&host_profile, r#"{"id":"acme.prod.v1"}"#).unwrap();
+        let err = execute_run(
+            bundle,
+            None,
+            1024,
+            2048,
+            0,
+            "json".to_string(),
+            false,
+            true,
+            Some(host_profile),
+            None,
+            "hypervisor".to_string(),
+            false,
+            false,
+            None,
+            true,
+            &limiter,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("host profile"), "{err}");
+
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,911 @@
+//! # Guard Serve Command
+//!
+//! This module implements `guard-serve`, a policy sidecar for hosts that execute
+//! tool and lens calls themselves. It compiles a contract once, then answers
+//! `tool_call`/`lens_call` guard requests over line-delimited JSON-RPC 2.0 on
+//! stdio or a Unix socket.
+//!
+//! Every decision is recorded as a `GuardDecision` event in a per-session hash
+//! chain (Appendix F.4) seeded with the events of the compile itself, and
+//! `session.close` returns the session's Execution Artifact.
+//!
+//! Methods:
+//!
+//! - `session.open` → `{session_id, document_hash, policy_hash, mode}`
+//! - `guard.tool_call` `{session_id, name, args?, effect_class?}` → `GuardDecision`
+//! - `guard.lens_call` `{session_id, name, input?, args?, kwargs?, effect_class?, version?}`
+//!   → `GuardDecision`
+//! - `session.close` `{session_id}` → Execution Artifact
+//!
+//! Each session also keeps its own `x_fct_max_calls` counters: once an allow
+//! rule's quota is used up, calls it would allow are denied with
+//! `X.fct.QUOTA_EXHAUSTED`.
+//!
+//! Denials are ordinary results; JSON-RPC errors are reserved for malformed
+//! requests and unknown sessions.
+//!
+//! At most `--max-sessions` sessions stay open at once, and a session idle for
+//! longer than `--session-idle-secs` expires and becomes unknown.
+
+use crate::commands::host_profile::load_host_setup;
+use crate::commands::mode_profile::resolve_execution_mode;
+use anyhow::{Context, Result};
+use console::style;
+use fct_ast::{FacetDocument, FacetNode, OrderedMap, ValueNode};
+use fct_compiler::{
+    build_execution_artifact_with_host_context, json_to_value_node, load_runtime_inputs,
+    normalize_guard_decisions, Compilation, Compiler,
+};
+use fct_engine::{
+    lens_call_input_hash, ExecutionGuardDecision, ExecutionMode, ToolExecutor, ToolInvocation,
+};
+use fct_policy::{
+    evaluate_guard, fold_input_hash, policy_scope, CallQuotas, EffectivePolicy, HostContext,
+    OpDesc, PolicyOp, QUOTA_EXHAUSTED,
+};
+use fct_render::{CanonicalPayload, GuardDecision};
+use fct_std::{LensRegistry, TrustLevel};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Error code for operations that perform runtime I/O in pure mode.
+const PURE_MODE_DISALLOWED: &str = "F801";
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const UNKNOWN_SESSION: i64 = -32001;
+const SESSION_LIMIT: i64 = -32002;
+
+/// Open sessions kept before `session.open` is refused (`--max-sessions`).
+pub const DEFAULT_MAX_SESSIONS: usize = 1024;
+/// Idle time after which a session expires (`--session-idle-secs`).
+pub const DEFAULT_SESSION_IDLE: Duration = Duration::from_secs(15 * 60);
+
+/// Guard serve command handler
+#[allow(clippy::too_many_arguments)]
+pub fn execute_guard_serve(
+    input: PathBuf,
+    runtime_input: Option<PathBuf>,
+    socket: Option<PathBuf>,
+    budget: usize,
+    context_budget: usize,
+    pure: bool,
+    exec: bool,
+    host_profile: Option<PathBuf>,
+    host_context: Option<PathBuf>,
+    max_sessions: usize,
+    session_idle: Duration,
+    rate_limiter: &crate::commands::DefaultRateLimiter,
+) -> Result<()> {
+    // Check rate limit
+    if rate_limiter.check().is_err() {
+        warn!("Rate limit exceeded for guard-serve command");
+        eprintln!(
+            "{}",
+            style("Rate limit exceeded. Please wait before running another command.").red()
+        );
+        std::process::exit(1);
+    }
+
+    // No stdout logging here: in stdio mode stdout carries the protocol.
+    let (execution_mode, _) = resolve_execution_mode(pure, exec)?;
+    let host = load_host_setup(host_profile.as_deref(), host_context.as_deref())?;
+    let runtime_inputs = match runtime_input {
+        Some(path) => load_runtime_inputs(&path)?,
+        None => HashMap::new(),
+    };
+
+    let mut builder = Compiler::builder()
+        .mode(execution_mode)
+        .budget(budget)
+        .context_budget(context_budget);
+    if let Some(host) = &host {
+        builder = host.configure(builder);
+    }
+    // A `.fctb` bundle from `compile` skips Phases 1-2, as in `run`.
+    let mut session = builder.build().load_contract_file(&input)?;
+    let compilation = session.run(runtime_inputs)?;
+    let mut service = GuardService::new(session.document(), compilation, execution_mode)
+        .with_session_limits(max_sessions, session_idle);
+    if let Some(context) = host.as_ref().and_then(|host| host.context()) {
+        service = service.with_host_context(context.clone());
+    }
+    let service = Mutex::new(service);
+
+    match socket {
+        Some(path) => serve_socket(service, &path),
+        None => {
+            let stdin = std::io::stdin();
+            let stdout = std::io::stdout();
+            serve(&service, stdin.lock(), stdout.lock())
+        }
+    }
+}
+
+/// Answer requests read line by line from `reader` until end of input.
+pub fn serve<R: BufRead, W: Write>(
+    service: &Mutex<GuardService>,
+    reader: R,
+    mut writer: W,
+) -> Result<()> {
+    for line in reader.lines() {
+        let line = line.context("Failed to read guard request")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = service
+            .lock()
+            .map_err(|_| anyhow::anyhow!("guard service state is poisoned"))?
+            .handle_line(&line);
+        if let Some(response) = response {
+            writeln!(writer, "{}", response)?;
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn serve_socket(service: Mutex<GuardService>, path: &Path) -> Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::UnixListener;
+    use std::sync::Arc;
+
+    if let Ok(meta) = std::fs::symlink_metadata(path) {
+        if !meta.file_type().is_socket() {
+            return Err(anyhow::anyhow!(
+                "Refusing to replace non-socket file: {:?}",
+                path
+            ));
+        }
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove stale socket: {:?}", path))?;
+    }
+
+    let listener =
+        UnixListener::bind(path).with_context(|| format!("Failed to bind socket: {:?}", path))?;
+    eprintln!("guard-serve listening on {}", path.display());
+
+    let service = Arc::new(service);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                warn!("guard-serve connection failed: {}", err);
+                continue;
+            }
+        };
+        let service = Arc::clone(&service);
+        std::thread::spawn(move || {
+            let reader = match stream.try_clone() {
+                Ok(reader) => std::io::BufReader::new(reader),
+                Err(err) => {
+                    warn!("guard-serve connection failed: {}", err);
+                    return;
+                }
+            };
+            if let Err(err) = serve(&service, reader, stream) {
+                warn!("guard-serve connection closed: {}", err);
+            }
+        });
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn serve_socket(_service: Mutex<GuardService>, path: &Path) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "Unix sockets are not supported on this platform: {:?}",
+        path
+    ))
+}
+
+/// A JSON-RPC error response body.
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+}
+
+/// One guard session: its decision chain and call quota counters.
+struct Session {
+    /// Events with their final `seq`, so recording a decision is a push.
+    events: Vec<GuardDecision>,
+    quotas: CallQuotas,
+    last_used: Instant,
+}
+
+/// Compiled contract plus the open guard sessions.
+pub struct GuardService {
+    payload: CanonicalPayload,
+    /// Compile-time events (`tool_expose`, `message_emit`, engine lens calls) that
+    /// open every session's chain.
+    base_decisions: Vec<GuardDecision>,
+    policy: Option<EffectivePolicy>,
+    computed_vars: HashMap<String, ValueNode>,
+    /// Host-supplied `$x` context visible to every session's guards.
+    host_context: Option<HostContext>,
+    /// Declared effect class of every `Iface.fn` in the contract.
+    tool_effects: HashMap<String, Option<String>>,
+    lens_registry: LensRegistry,
+    execution_mode: ExecutionMode,
+    sessions: BTreeMap<String, Session>,
+    next_session: u64,
+    max_sessions: usize,
+    session_idle: Duration,
+}
+
+impl GuardService {
+    pub fn new(
+        document: &FacetDocument,
+        compilation: Compilation,
+        execution_mode: ExecutionMode,
+    ) -> Self {
+        let mut tool_effects = HashMap::new();
+        for node in &document.blocks {
+            if let FacetNode::Interface(interface) = node {
+                for function in &interface.functions {
+                    tool_effects.insert(
+                        format!("{}.{}", interface.name, function.name),
+                        function.effect.clone(),
+                    );
+                }
+            }
+        }
+
+        Self {
+            payload: compilation.payload,
+            base_decisions: normalize_guard_decisions(&compilation.guard_decisions),
+            policy: compilation.effective_policy,
+            computed_vars: compilation.computed_vars,
+            host_context: None,
+            tool_effects,
+            lens_registry: LensRegistry::new(),
+            execution_mode,
+            sessions: BTreeMap::new(),
+            next_session: 1,
+            max_sessions: DEFAULT_MAX_SESSIONS,
+            session_idle: DEFAULT_SESSION_IDLE,
+        }
+    }
+
+    /// Bound the number of open sessions and how long one may stay idle.
+    pub fn with_session_limits(mut self, max_sessions: usize, idle: Duration) -> Self {
+        self.max_sessions = max_sessions;
+        self.session_idle = idle;
+        self
+    }
+
+    /// Guard every session under `host_context`.
+    pub fn with_host_context(mut self, host_context: HostContext) -> Self {
+        self.host_context = Some(host_context);
+        self
+    }
+
+    /// Handle one request line; `None` for notifications.
+    pub fn handle_line(&mut self, line: &str) -> Option<String> {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(err) => {
+                return Some(error_response(
+                    Value::Null,
+                    RpcError::new(PARSE_ERROR, format!("Parse error: {}", err)),
+                ))
+            }
+        };
+
+        let id = request.get("id").cloned();
+        let method = match (
+            request.get("jsonrpc").and_then(Value::as_str),
+            request.get("method").and_then(Value::as_str),
+        ) {
+            (Some("2.0"), Some(method)) => method,
+            _ => {
+                return Some(error_response(
+                    id.unwrap_or(Value::Null),
+                    RpcError::new(INVALID_REQUEST, "Invalid Request"),
+                ))
+            }
+        };
+        let params = request.get("params").cloned().unwrap_or_else(|| json!({}));
+
+        let outcome = self.dispatch(method, &params);
+        let id = id?;
+        Some(match outcome {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }).to_string(),
+            Err(err) => error_response(id, err),
+        })
+    }
+
+    fn dispatch(&mut self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "session.open" => self.open_session(),
+            "guard.tool_call" => {
+                let session_id = session_param(params)?;
+                self.ensure_session(&session_id)?;
+                let decision = self.guard_tool_call(params)?;
+                let decision = self.charge_quota(&session_id, decision);
+                self.record(&session_id, decision)
+            }
+            "guard.lens_call" => {
+                let session_id = session_param(params)?;
+                self.ensure_session(&session_id)?;
+                let decision = self.guard_lens_call(params)?;
+                self.record(&session_id, decision)
+            }
+            "session.close" => {
+                let session_id = session_param(params)?;
+                self.ensure_session(&session_id)?;
+                let events = self
+                    .sessions
+                    .remove(&session_id)
+                    .map(|session| session.events)
+                    .unwrap_or_default();
+                build_execution_artifact_with_host_context(
+                    &self.payload,
+                    &events,
+                    self.host_context.as_ref(),
+                )
+                .and_then(|artifact| artifact.to_json())
+                .map_err(|err| RpcError::new(INVALID_REQUEST, err.to_string()))
+            }
+            other => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Method not found: {}", other),
+            )),
+        }
+    }
+
+    fn open_session(&mut self) -> Result<Value, RpcError> {
+        let idle = self.session_idle;
+        self.sessions
+            .retain(|_, session| session.last_used.elapsed() <= idle);
+        if self.sessions.len() >= self.max_sessions {
+            return Err(RpcError::new(
+                SESSION_LIMIT,
+                format!("Too many open sessions (limit {})", self.max_sessions),
+            ));
+        }
+
+        let session_id = format!("s{}", self.next_session);
+        self.next_session += 1;
+        self.sessions.insert(
+            session_id.clone(),
+            Session {
+                events: self.base_decisions.clone(),
+                quotas: CallQuotas::new(),
+                last_used: Instant::now(),
+            },
+        );
+
+        Ok(json!({
+            "session_id": session_id,
+            "document_hash": self.payload.metadata.document_hash,
+            "policy_hash": self.payload.metadata.policy_hash,
+            "mode": self.payload.metadata.mode,
+        }))
+    }
+
+    /// Fail unless `session_id` is open, expiring it first if it sat idle too long.
+    fn ensure_session(&mut self, session_id: &str) -> Result<(), RpcError> {
+        let idle = self.session_idle;
+        match self.sessions.get_mut(session_id) {
+            Some(session) if session.last_used.elapsed() <= idle => {
+                session.last_used = Instant::now();
+                Ok(())
+            }
+            expired => {
+                if expired.is_some() {
+                    self.sessions.remove(session_id);
+                }
+                Err(RpcError::new(
+                    UNKNOWN_SESSION,
+                    format!("Unknown session: {}", session_id),
+                ))
+            }
+        }
+    }
+
+    /// Charge an allowed tool call against the session's quota of its rule.
+    fn charge_quota(&mut self, session_id: &str, mut decision: GuardDecision) -> GuardDecision {
+        let Some(session) = self.sessions.get_mut(session_id) else {
+            return decision;
+        };
+        if decision.decision == "allowed"
+            && !session
+                .quotas
+                .charge(self.policy.as_ref(), decision.policy_rule_id.as_deref())
+        {
+            decision.decision = "denied".to_string();
+            decision.error_code = Some(QUOTA_EXHAUSTED.to_string());
+        }
+        decision
+    }
+
+    /// Append `decision` to the session chain and return it with its session `seq`.
+    fn record(&mut self, session_id: &str, mut decision: GuardDecision) -> Result<Value, RpcError> {
+        let events = &mut self
+            .sessions
+            .get_mut(session_id)
+            .ok_or_else(|| RpcError::new(UNKNOWN_SESSION, "Unknown session"))?
+            .events;
+        decision.seq = events.len() + 1;
+        let recorded = serde_json::to_value(&decision)
+            .map_err(|err| RpcError::invalid_params(err.to_string()))?;
+        events.push(decision);
+        Ok(recorded)
+    }
+
+    fn guard_tool_call(&self, params: &Value) -> Result<GuardDecision, RpcError> {
+        let name = string_param(params, "name")?
+            .ok_or_else(|| RpcError::invalid_params("missing 'name'"))?;
+        let arguments = match params.get("args") {
+            None | Some(Value::Null) => HashMap::new(),
+            Some(Value::Object(map)) => map
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), to_value_node(v)?)))
+                .collect::<Result<_, RpcError>>()?,
+            Some(_) => return Err(RpcError::invalid_params("'args' must be an object")),
+        };
+        // The contract's declared effect wins over the host's claim.
+        let effect_class = match self.tool_effects.get(&name) {
+            Some(declared) => declared.clone(),
+            None => string_param(params, "effect_class")?,
+        };
+
+        let invocation = ToolInvocation {
+            tool_name: name,
+            arguments,
+            invocation_id: None,
+        };
+        let mut executor = ToolExecutor::new();
+        if let Some(host_context) = &self.host_context {
+            executor = executor.with_host_context(host_context.clone());
+        }
+        let decision = executor
+            .evaluate_tool_call_guard(
+                &invocation,
+                self.policy.as_ref(),
+                Some(&self.computed_vars),
+                self.mode(),
+                &self.payload.metadata.host_profile_id,
+                effect_class.as_deref(),
+            )
+            .map_err(|err| RpcError::invalid_params(err.to_string()))?;
+
+        let mut decision = into_guard_decision(decision);
+        if self.execution_mode == ExecutionMode::Pure {
+            deny_in_pure_mode(&mut decision);
+        }
+        Ok(decision)
+    }
+
+    fn guard_lens_call(&self, params: &Value) -> Result<GuardDecision, RpcError> {
+        let name = string_param(params, "name")?
+            .ok_or_else(|| RpcError::invalid_params("missing 'name'"))?;
+        let input = match params.get("input") {
+            Some(value) => to_value_node(value)?,
+            None => to_value_node(&Value::Null)?,
+        };
+        let args = match params.get("args") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(items)) => items
+                .iter()
+                .map(to_value_node)
+                .collect::<Result<_, RpcError>>()?,
+            Some(_) => return Err(RpcError::invalid_params("'args' must be an array")),
+        };
+        let mut kwargs = OrderedMap::new();
+        match params.get("kwargs") {
+            None | Some(Value::Null) => {}
+            Some(Value::Object(map)) => {
+                for (key, value) in map {
+                    kwargs.insert(key.clone(), to_value_node(value)?);
+                }
+            }
+            Some(_) => return Err(RpcError::invalid_params("'kwargs' must be an object")),
+        }
+
+        // Registered lenses report their own version, effect and trust level.
+        let lens = self.lens_registry.get(&name);
+        let version = match lens {
+            Some(lens) => lens.version().to_string(),
+            None => string_param(params, "version")?.unwrap_or_else(|| "1".to_string()),
+        };
+        let effect_class = match lens {
+            Some(lens) => lens.effect_class().map(str::to_string),
+            None => string_param(params, "effect_class")?,
+        };
+
+        let op = OpDesc::new(PolicyOp::LensCall, name.clone(), effect_class.as_deref());
+        let scope = policy_scope(Some(&self.computed_vars), self.host_context.as_ref());
+        let guard = evaluate_guard(self.policy.as_ref(), &op, scope.as_deref());
+        let input_hash = lens_call_input_hash(
+            &name,
+            &version,
+            &input,
+            &args,
+            &kwargs,
+            &self.payload.metadata.host_profile_id,
+        )
+        .map_err(|err| RpcError::invalid_params(err.to_string()))?;
+        let input_hash = fold_input_hash(input_hash, self.host_context.as_ref())
+            .map_err(|err| RpcError::invalid_params(err.to_string()))?;
+
+        let mut decision = GuardDecision {
+            seq: 0,
+            op: PolicyOp::LensCall.as_str().to_string(),
+            name,
+            effect_class,
+            mode: self.mode().to_string(),
+            decision: guard.decision().to_string(),
+            policy_rule_id: guard.policy_rule_id,
+            input_hash,
+            error_code: guard.error_code,
+        };
+        let pure_lens = lens.is_some_and(|lens| lens.signature().trust_level == TrustLevel::Pure);
+        if self.execution_mode == ExecutionMode::Pure && !pure_lens {
+            deny_in_pure_mode(&mut decision);
+        }
+        Ok(decision)
+    }
+
+    fn mode(&self) -> &str {
+        &self.payload.metadata.mode
+    }
+}
+
+fn into_guard_decision(decision: ExecutionGuardDecision) -> GuardDecision {
+    GuardDecision {
+        seq: decision.seq,
+        op: decision.op,
+        name: decision.name,
+        effect_class: decision.effect_class,
+        mode: decision.mode,
+        decision: decision.decision,
+        policy_rule_id: decision.policy_rule_id,
+        input_hash: decision.input_hash,
+        error_code: decision.error_code,
+    }
+}
+
+/// Runtime I/O is prohibited in pure mode regardless of policy.
+fn deny_in_pure_mode(decision: &mut GuardDecision) {
+    decision.decision = "denied".to_string();
+    decision.policy_rule_id = None;
+    decision.error_code = Some(PURE_MODE_DISALLOWED.to_string());
+}
+
+fn session_param(params: &Value) -> Result<String, RpcError> {
+    string_param(params, "session_id")?
+        .ok_or_else(|| RpcError::invalid_params("missing 'session_id'"))
+}
+
+fn string_param(params: &Value, key: &str) -> Result<Option<String>, RpcError> {
+    match params.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.clone())),
+        Some(_) => Err(RpcError::invalid_params(format!(
+            "'{}' must be a string",
+            key
+        ))),
+    }
+}
+
+fn to_value_node(value: &Value) -> Result<ValueNode, RpcError> {
+    json_to_value_node(value).map_err(|err| RpcError::invalid_params(err.to_string()))
+}
+
+fn error_response(id: Value, err: RpcError) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": err.code, "message": err.message },
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::host_profile::HostSetup;
+    use fct_compiler::build_execution_artifact;
+    use fct_validator::HostContextSchema;
+    use std::io::Cursor;
+
+    const CONTRACT: &str = r#"@interface Payments
+  fn charge(amount: int) -> string (effect="payment")
+  fn quote(amount: int) -> string (effect="read")
+
+@system
+  tools: [$Payments]
+  content: "billing agent"
+
+@policy
+  allow: [{ id: "quotes", op: "tool_call", name: "Payments.quote" }]
+  deny: [{ id: "no-pay", op: "tool_call", name: "Payments.*", effect: "payment" }]
+"#;
+
+    fn service(mode: ExecutionMode) -> GuardService {
+        compile(CONTRACT, mode, None)
+    }
+
+    fn compile(source: &str, mode: ExecutionMode, host: Option<&HostSetup>) -> GuardService {
+        let mut builder = Compiler::builder().mode(mode);
+        if let Some(host) = host {
+            builder = host.configure(builder);
+        }
+        let mut session = builder
+            .build()
+            .load_source(source, &std::env::temp_dir())
+            .unwrap();
+        let compilation = session.run(HashMap::new()).unwrap();
+        let service = GuardService::new(session.document(), compilation, mode);
+        match host.and_then(HostSetup::context) {
+            Some(context) => service.with_host_context(context.clone()),
+            None => service,
+        }
+    }
+
+    fn call(service: &mut GuardService, id: u64, method: &str, params: Value) -> Value {
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        let response = service
+            .handle_line(&request.to_string())
+            .expect("request with id gets a response");
+        serde_json::from_str(&response).unwrap()
+    }
+
+    #[test]
+    fn session_records_decisions_and_closes_with_artifact() {
+        let mut service = service(ExecutionMode::Exec);
+        let base = service.base_decisions.clone();
+
+        let opened = call(&mut service, 1, "session.open", json!({}));
+        assert_eq!(opened["result"]["session_id"], "s1");
+        assert_eq!(opened["result"]["mode"], "exec");
+
+        let quote = call(
+            &mut service,
+            2,
+            "guard.tool_call",
+            json!({ "session_id": "s1", "name": "Payments.quote", "args": { "amount": 3 } }),
+        );
+        assert_eq!(quote["result"]["decision"], "allowed");
+        assert_eq!(quote["result"]["policy_rule_id"], "quotes");
+        assert_eq!(quote["result"]["effect_class"], "read");
+        assert_eq!(quote["result"]["seq"], base.len() + 1);
+
+        // The declared effect wins over the host's claim.
+        let charge = call(
+            &mut service,
+            3,
+            "guard.tool_call",
+            json!({ "session_id": "s1", "name": "Payments.charge", "effect_class": "read" }),
+        );
+        assert_eq!(charge["result"]["decision"], "denied");
+        assert_eq!(charge["result"]["policy_rule_id"], "no-pay");
+        assert_eq!(charge["result"]["error_code"], "F454");
+
+        let lens = call(
+            &mut service,
+            4,
+            "guard.lens_call",
+            json!({ "session_id": "s1", "name": "trim", "input": " x " }),
+        );
+        assert_eq!(lens["result"]["op"], "lens_call");
+        assert_eq!(lens["result"]["decision"], "denied");
+
+        let closed = call(
+            &mut service,
+            5,
+            "session.close",
+            json!({ "session_id": "s1" }),
+        );
+        let artifact = &closed["result"];
+        let events = artifact["provenance"]["events"].as_array().unwrap();
+        assert_eq!(events.len(), base.len() + 3);
+        assert_eq!(events[base.len() + 1], charge["result"]);
+
+        let mut expected_events = base;
+        for response in [&quote, &charge, &lens] {
+            expected_events.push(serde_json::from_value(response["result"].clone()).unwrap());
+        }
+        let expected = build_execution_artifact(&service.payload, &expected_events)
+            .unwrap()
+            .to_json()
+            .unwrap();
+        assert_eq!(artifact, &expected);
+
+        let reused = call(
+            &mut service,
+            6,
+            "guard.tool_call",
+            json!({ "session_id": "s1", "name": "Payments.quote" }),
+        );
+        assert_eq!(reused["error"]["code"], UNKNOWN_SESSION);
+    }
+
+    #[test]
+    fn host_context_decides_rules_and_is_recorded_in_the_artifact() {
+        let contract = r#"@interface Payments
+  fn charge(amount: int) -> string (effect="payment")
+
+@policy
+  allow: [{ id: "admins", op: "tool_call", name: "Payments.*", when: $x.acme.principal.admin }]
+"#;
+        let host = |admin: bool| HostSetup {
+            profile_id: "acme.prod.v1".to_string(),
+            schema: HostContextSchema::parse([(
+                "acme",
+                "struct { principal: struct { admin: bool } }",
+            )])
+            .unwrap(),
+            context: Some(
+                HostContext::new(
+                    json_to_value_node(&json!({ "acme": { "principal": { "admin": admin } } }))
+                        .unwrap(),
+                )
+                .unwrap(),
+            ),
+        };
+
+        let mut decisions = Vec::new();
+        for admin in [true, false] {
+            let host = host(admin);
+            let mut service = compile(contract, ExecutionMode::Exec, Some(&host));
+            assert_eq!(service.payload.metadata.host_profile_id, "acme.prod.v1");
+            call(&mut service, 1, "session.open", json!({}));
+            let charge = call(
+                &mut service,
+                2,
+                "guard.tool_call",
+                json!({ "session_id": "s1", "name": "Payments.charge" }),
+            );
+            let closed = call(
+                &mut service,
+                3,
+                "session.close",
+                json!({ "session_id": "s1" }),
+            );
+            assert_eq!(
+                closed["result"]["metadata"]["host_context"]["hash"],
+                host.context().unwrap().hash()
+            );
+            decisions.push(charge["result"].clone());
+        }
+
+        assert_eq!(decisions[0]["decision"], "allowed");
+        assert_eq!(decisions[0]["policy_rule_id"], "admins");
+        assert_eq!(decisions[1]["decision"], "denied");
+        assert_ne!(decisions[0]["input_hash"], decisions[1]["input_hash"]);
+    }
+
+    #[test]
+    fn call_quotas_are_counted_per_session() {
+        let contract = r#"@interface Payments
+  fn refund(amount: int) -> string (effect="payment")
+
+@policy
+  allow: [{ id: "refunds", op: "tool_call", name: "Payments.refund", x_fct_max_calls: 1 }]
+"#;
+        let mut service = compile(contract, ExecutionMode::Exec, None);
+        call(&mut service, 1, "session.open", json!({}));
+        call(&mut service, 2, "session.open", json!({}));
+        let refund = |service: &mut GuardService, id, session: &str| {
+            call(
+                service,
+                id,
+                "guard.tool_call",
+                json!({ "session_id": session, "name": "Payments.refund" }),
+            )["result"]
+                .clone()
+        };
+
+        assert_eq!(refund(&mut service, 3, "s1")["decision"], "allowed");
+        let exhausted = refund(&mut service, 4, "s1");
+        assert_eq!(exhausted["decision"], "denied");
+        assert_eq!(exhausted["error_code"], QUOTA_EXHAUSTED);
+        assert_eq!(exhausted["policy_rule_id"], "refunds");
+        assert_eq!(refund(&mut service, 5, "s2")["decision"], "allowed");
+    }
+
+    #[test]
+    fn pure_mode_denies_runtime_io() {
+        let mut service = service(ExecutionMode::Pure);
+        call(&mut service, 1, "session.open", json!({}));
+        let quote = call(
+            &mut service,
+            2,
+            "guard.tool_call",
+            json!({ "session_id": "s1", "name": "Payments.quote" }),
+        );
+        assert_eq!(quote["result"]["decision"], "denied");
+        assert_eq!(quote["result"]["error_code"], "F801");
+        assert_eq!(quote["result"]["policy_rule_id"], Value::Null);
+    }
+
+    #[test]
+    fn sessions_are_capped_and_expire_when_idle() {
+        let mut service = service(ExecutionMode::Exec).with_session_limits(1, Duration::MAX);
+        call(&mut service, 1, "session.open", json!({}));
+        let refused = call(&mut service, 2, "session.open", json!({}));
+        assert_eq!(refused["error"]["code"], SESSION_LIMIT);
+
+        call(
+            &mut service,
+            3,
+            "session.close",
+            json!({ "session_id": "s1" }),
+        );
+        let reopened = call(&mut service, 4, "session.open", json!({}));
+        assert_eq!(reopened["result"]["session_id"], "s2");
+
+        let mut service = service.with_session_limits(1, Duration::ZERO);
+        std::thread::sleep(Duration::from_millis(1));
+        let expired = call(
+            &mut service,
+            5,
+            "guard.tool_call",
+            json!({ "session_id": "s2", "name": "Payments.quote" }),
+        );
+        assert_eq!(expired["error"]["code"], UNKNOWN_SESSION);
+        assert!(service.sessions.is_empty());
+    }
+
+    #[test]
+    fn serve_reports_protocol_errors_and_skips_notifications() {
+        let service = Mutex::new(service(ExecutionMode::Exec));
+        let requests = [
+            "not json",
+            r#"{"jsonrpc":"2.0","id":1,"method":"guard.nope"}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"guard.tool_call","params":{"session_id":"s9","name":"A.b"}}"#,
+            r#"{"jsonrpc":"2.0","id":3,"method":"guard.tool_call","params":{"name":"A.b"}}"#,
+            r#"{"id":4,"method":"session.open"}"#,
+            r#"{"jsonrpc":"2.0","method":"session.open"}"#,
+        ]
+        .join("\n");
+        let mut out = Vec::new();
+        serve(&service, Cursor::new(requests), &mut out).unwrap();
+
+        let codes: Vec<i64> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let response: Value = serde_json::from_str(line).unwrap();
+                response["error"]["code"].as_i64().unwrap()
+            })
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                PARSE_ERROR,
+                METHOD_NOT_FOUND,
+                UNKNOWN_SESSION,
+                INVALID_PARAMS,
+                INVALID_REQUEST
+            ]
+        );
+        // The notification still opened a session.
+        assert_eq!(service.lock().unwrap().sessions.len(), 1);
+    }
+}
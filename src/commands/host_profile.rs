@@ -0,0 +1,174 @@
+//! # Host Profiles
+//!
+//! A host profile names the host (`host_profile_id`) and declares the types of
+//! the read-only context it supplies to policy conditions as `$x.<host>...`:
+//!
+//! ```json
+//! { "id": "acme.prod.v1",
+//!   "context": { "acme": "struct { principal: struct { role: string, admin: bool } }" } }
+//! ```
+//!
+//! The context itself is a separate JSON file (`{ "acme": { "principal": ... } }`)
+//! checked against those declarations before any phase runs.
+
+use anyhow::{anyhow, Context, Result};
+use fct_compiler::{json_to_value_node, CompilerBuilder};
+use fct_policy::HostContext;
+use fct_validator::HostContextSchema;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HostProfileFile {
+    id: String,
+    #[serde(default)]
+    context: BTreeMap<String, String>,
+}
+
+/// A loaded host profile and, optionally, the context supplied under it.
+#[derive(Debug, Clone)]
+pub struct HostSetup {
+    pub profile_id: String,
+    pub schema: HostContextSchema,
+    pub context: Option<HostContext>,
+}
+
+impl HostSetup {
+    pub fn context(&self) -> Option<&HostContext> {
+        self.context.as_ref()
+    }
+
+    /// Apply the host id, context types and context to a compiler.
+    pub fn configure(&self, builder: CompilerBuilder) -> CompilerBuilder {
+        let builder = builder
+            .host_profile_id(&self.profile_id)
+            .host_context_schema(self.schema.clone());
+        match &self.context {
+            Some(context) => builder.host_context(context.clone()),
+            None => builder,
+        }
+    }
+}
+
+/// Load `--host-profile` / `--host-context`. Returns `None` when neither is given.
+pub fn load_host_setup(
+    profile: Option<&Path>,
+    context: Option<&Path>,
+) -> Result<Option<HostSetup>> {
+    let Some(profile_path) = profile else {
+        if context.is_some() {
+            return Err(anyhow!(
+                "--host-context requires --host-profile to declare its types"
+            ));
+        }
+        return Ok(None);
+    };
+
+    let raw = fs::read_to_string(profile_path)
+        .with_context(|| format!("Failed to read host profile: {:?}", profile_path))?;
+    let file: HostProfileFile = serde_json::from_str(&raw)
+        .with_context(|| format!("Invalid host profile: {:?}", profile_path))?;
+    if file.id.trim().is_empty() {
+        return Err(anyhow!(
+            "Host profile id must be non-empty: {:?}",
+            profile_path
+        ));
+    }
+    let schema = HostContextSchema::parse(
+        file.context
+            .iter()
+            .map(|(host, type_expr)| (host.as_str(), type_expr.as_str())),
+    )
+    .map_err(|e| anyhow!("Invalid host profile context declaration: {}", e))?;
+
+    let context = match context {
+        Some(context_path) => Some(load_host_context(context_path, &schema)?),
+        None => None,
+    };
+
+    Ok(Some(HostSetup {
+        profile_id: file.id,
+        schema,
+        context,
+    }))
+}
+
+fn load_host_context(path: &Path, schema: &HostContextSchema) -> Result<HostContext> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read host context: {:?}", path))?;
+    let json: serde_json::Value = serde_json::from_str(&raw)
+        .with_context(|| format!("Host context file is not valid JSON: {:?}", path))?;
+    let value = json_to_value_node(&json)?;
+
+    if let Err(violations) = schema.check_context(&value) {
+        let details: Vec<String> = violations
+            .iter()
+            .map(|v| format!("{}: {}", v.path, v.message))
+            .collect();
+        return Err(anyhow!(
+            "Host context does not match the host profile: {}",
+            details.join("; ")
+        ));
+    }
+    HostContext::new(value).map_err(|e| anyhow!("Invalid host context: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_dir(name: &str) -> PathBuf {
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("facet-host-profile-{name}-{nonce}"));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_profile(dir: &Path) -> PathBuf {
+        let path = dir.join("host.json");
+        fs::write(
+            &path,
+            r#"{"id":"acme.prod.v1","context":{"acme":"struct { principal: struct { admin: bool } }"}}"#,
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn loads_profile_and_checked_context() {
+        let dir = fixture_dir("ok");
+        let profile = write_profile(&dir);
+        let context = dir.join("context.json");
+        fs::write(&context, r#"{"acme":{"principal":{"admin":true}}}"#).unwrap();
+
+        let setup = load_host_setup(Some(&profile), Some(&context))
+            .unwrap()
+            .expect("host setup");
+        assert_eq!(setup.profile_id, "acme.prod.v1");
+        assert!(setup.schema.resolve("x.acme.principal.admin").is_some());
+        assert!(setup.context().unwrap().hash().starts_with("sha256:"));
+
+        assert!(load_host_setup(None, None).unwrap().is_none());
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn rejects_mistyped_context_and_context_without_profile() {
+        let dir = fixture_dir("bad");
+        let profile = write_profile(&dir);
+        let context = dir.join("context.json");
+        fs::write(&context, r#"{"acme":{"principal":{"admin":"yes"}}}"#).unwrap();
+
+        let err = load_host_setup(Some(&profile), Some(&context)).unwrap_err();
+        assert!(err.to_string().contains("$.acme.principal.admin"), "{err}");
+        assert!(load_host_setup(None, Some(&context)).is_err());
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -3,21 +3,20 @@
 //! This module implements the inspect command for the FACET compiler.
 //! The inspect command emits structured views (AST, DAG, layout, policy).
 
+use crate::commands::host_profile::load_host_setup;
+use crate::commands::mode_profile::resolve_profile;
 use anyhow::{Context, Result};
 use console::style;
-use fct_ast::{BodyNode, FacetDocument, FacetNode, OrderedMap, ScalarValue, ValueNode};
+use fct_compiler::{effective_layout_budget, load_runtime_inputs, Compiler};
 use fct_engine::{
-    count_facet_units_in_value, derive_message_section_id, AllocationResult, ExecutionContext,
-    ExecutionMode, RDagEngine, Section, TokenBoxModel,
+    AllocationResult, EngineError, ExecutionMode, LayoutReservation, RDagEngine, Section,
+    TokenBoxModel,
 };
 use fct_render::{effective_policy_json_for_document, policy_hash_for_document};
-use fct_resolver::{Resolver, ResolverConfig};
 use fct_std::LensRegistry;
-use fct_validator::TypeChecker;
+use fct_validator::ValidationProfile;
 use serde::Serialize;
-use sha2::{Digest, Sha256};
-use std::cmp::Reverse;
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use tracing::warn;
@@ -32,6 +31,10 @@ struct DagNodeView {
 struct DagView {
     nodes: Vec<DagNodeView>,
     topological_order: Vec<String>,
+    /// `eager` (every `@vars` entry, the default) or `demand`.
+    evaluation: &'static str,
+    /// Nodes demand-driven evaluation did not compute, in declaration order.
+    skipped: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -49,6 +52,16 @@ struct LayoutSectionView {
     dropped: bool,
     compressed: bool,
     truncated: bool,
+    /// Whole list items removed by `x_fct_pack`, in drop order.
+    dropped_items: Vec<DroppedItemView>,
+}
+
+#[derive(Debug, Serialize)]
+struct DroppedItemView {
+    index: usize,
+    size: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score: Option<f64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -56,13 +69,67 @@ struct LayoutView {
     budget: usize,
     total_size: usize,
     overflow: usize,
+    /// Fixed load taken from the budget before any section is packed.
+    reserved: ReservedView,
     sections: Vec<LayoutSectionView>,
 }
 
+#[derive(Debug, Serialize)]
+struct ReservedView {
+    tools: usize,
+    output: usize,
+}
+
+/// Largest number of budgets one `--budget-sweep` may allocate.
+const MAX_SWEEP_POINTS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BudgetRange {
+    start: usize,
+    end: usize,
+    step: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct SweepPointView {
+    budget: usize,
+    /// `false` below the critical floor, where layout fails with F901.
+    feasible: bool,
+    total_size: Option<usize>,
+    dropped: Vec<String>,
+}
+
+/// The highest swept budget at which a section is compressed, truncated,
+/// dropped or has list items dropped by `x_fct_pack`; `None` when it never is.
+#[derive(Debug, Serialize)]
+struct SweepSectionView {
+    id: String,
+    role: String,
+    base_size: usize,
+    critical: bool,
+    compressed_at: Option<usize>,
+    truncated_at: Option<usize>,
+    dropped_at: Option<usize>,
+    items_dropped_at: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct BudgetSweepView {
+    start: usize,
+    end: usize,
+    step: usize,
+    /// Fixed load of the critical sections, tools and output reservation;
+    /// smaller budgets fail with F901.
+    critical_floor: usize,
+    sections: Vec<SweepSectionView>,
+    points: Vec<SweepPointView>,
+}
+
 /// Inspect command handler
 #[allow(clippy::too_many_arguments)]
 pub fn execute_inspect(
     input: PathBuf,
+    runtime_input: Option<PathBuf>,
     ast_output: Option<PathBuf>,
     dag_output: Option<PathBuf>,
     layout_output: Option<PathBuf>,
@@ -70,6 +137,13 @@ pub fn execute_inspect(
     budget: usize,
     pure: bool,
     exec: bool,
+    profile: String,
+    demand_driven: bool,
+    output_reserve: usize,
+    budget_sweep: Option<String>,
+    sweep_output: Option<PathBuf>,
+    host_profile: Option<PathBuf>,
+    host_context: Option<PathBuf>,
     rate_limiter: &crate::commands::DefaultRateLimiter,
 ) -> Result<()> {
     // Check rate limit
@@ -91,67 +165,95 @@ pub fn execute_inspect(
         ExecutionMode::Exec
     };
     let mode_label = if pure { "pure" } else { "exec" };
+    let (validation_profile, profile_label) = resolve_profile(&profile)?;
+    let core = validation_profile == ValidationProfile::Core;
+    if core
+        && (dag_output.is_some()
+            || layout_output.is_some()
+            || budget_sweep.is_some()
+            || runtime_input.is_some())
+    {
+        return Err(anyhow::anyhow!(
+            "--dag, --layout, --budget-sweep and --runtime-input are not available under --profile core (no Phase 3/4)"
+        ));
+    }
+    let sweep_range = budget_sweep
+        .as_deref()
+        .map(parse_budget_range)
+        .transpose()?;
+    let runtime_inputs = match runtime_input {
+        Some(path) => load_runtime_inputs(&path)?,
+        None => HashMap::new(),
+    };
+    let host = load_host_setup(host_profile.as_deref(), host_context.as_deref())?;
+
+    let mut builder = Compiler::builder()
+        .mode(mode)
+        .profile(validation_profile)
+        .budget(budget)
+        .output_reserve(output_reserve)
+        .demand_driven(demand_driven);
+    if let Some(host) = &host {
+        builder = host.configure(builder);
+    }
+    let session = builder.build().load_file(&input)?;
+    let document = session.document();
 
-    let source = fs::read_to_string(&input)
-        .with_context(|| format!("Failed to read input file: {:?}", input))?;
-
-    let base_dir = input
-        .parent()
-        .map(Path::to_path_buf)
-        .unwrap_or(std::env::current_dir()?);
-    let mut resolver = Resolver::new(ResolverConfig {
-        allowed_roots: vec![base_dir.clone()],
-        base_dir,
-    });
-    let phase1 = resolver
-        .resolve_phase1(&source)
-        .map_err(|e| anyhow::anyhow!("Resolution error: {}", e))?;
-    let document_hash = format!(
-        "sha256:{:x}",
-        Sha256::digest(phase1.resolved_source_form.as_bytes())
-    );
-    let resolved = phase1.resolved_ast;
-
-    let mut checker = TypeChecker::new();
-    checker
-        .validate(&resolved)
-        .map_err(|e| anyhow::anyhow!("Validation error: {}", e))?;
-
-    let mut engine = RDagEngine::new();
-    engine.build(&resolved)?;
-    engine.validate()?;
-
-    let mut exec_ctx = ExecutionContext::new_with_mode(10_000, mode);
-    engine.execute(&mut exec_ctx)?;
-
-    let dag_view = build_dag_view(&resolved)?;
-    let sections = doc_to_sections(&resolved, &exec_ctx.variables)?;
-    let allocation = TokenBoxModel::new(budget).allocate(sections, &LensRegistry::new())?;
-    let layout_view = build_layout_view(budget, &allocation);
+    // Core stops after Phase 2: there is no R-DAG or layout to show.
+    let (dag_view, layout_view, sweep_view) = if core {
+        (serde_json::Value::Null, serde_json::Value::Null, None)
+    } else {
+        let mut execution = session.execute(runtime_inputs)?;
+        let dag_view = build_dag_view(&execution.engine, demand_driven)?;
+        let layout = session.lay_out(&mut execution, None)?;
+        let sweep_view = sweep_range
+            .map(|range| {
+                build_budget_sweep_view(
+                    range,
+                    &layout.sections,
+                    layout.reservation,
+                    &execution.context.lens_registry,
+                    mode,
+                )
+            })
+            .transpose()?;
+        (
+            serde_json::to_value(dag_view)?,
+            serde_json::to_value(build_layout_view(&layout.allocation))?,
+            sweep_view,
+        )
+    };
 
-    let ast_view = serde_json::to_value(&resolved)?;
+    let ast_view = serde_json::to_value(document)?;
     let policy_view = serde_json::json!({
-        "policy_hash": policy_hash_for_document(&resolved)?,
-        "effective_policy": effective_policy_json_for_document(&resolved)?,
+        "policy_hash": policy_hash_for_document(document)?,
+        "effective_policy": effective_policy_json_for_document(document)?,
     });
 
     let combined = serde_json::json!({
         "metadata": {
-            "document_hash": document_hash,
+            "document_hash": session.document_hash(),
             "mode": mode_label,
-            "budget": budget,
+            "profile": profile_label,
+            "budget": effective_layout_budget(document, budget),
         },
         "ast": ast_view,
         "dag": dag_view,
         "layout": layout_view,
         "policy": policy_view,
+        "budget_sweep": serde_json::to_value(&sweep_view)?,
     });
 
+    if let Some(sweep) = &sweep_view {
+        print_budget_sweep(sweep);
+    }
+
     let writes_requested = ast_output.is_some()
         || dag_output.is_some()
         || layout_output.is_some()
-        || policy_output.is_some();
-    if !writes_requested {
+        || policy_output.is_some()
+        || sweep_output.is_some();
+    if !writes_requested && sweep_view.is_none() {
         println!("{}", serde_json::to_string_pretty(&combined)?);
         return Ok(());
     }
@@ -172,344 +274,32 @@ pub fn execute_inspect(
         write_json_file(&path, &combined["policy"])?;
         println!("wrote policy view: {}", path.display());
     }
+    if let Some(path) = sweep_output {
+        write_json_file(&path, &combined["budget_sweep"])?;
+        println!("wrote budget sweep view: {}", path.display());
+    }
 
     Ok(())
 }
 
-fn build_dag_view(doc: &FacetDocument) -> Result<DagView> {
-    let vars = collect_merged_vars(doc);
-
-    let mut nodes = Vec::with_capacity(vars.len());
-    let mut deps_by_var: OrderedMap<String, Vec<String>> = OrderedMap::new();
-
-    for (name, value) in &vars {
-        let deps = dedupe_preserve_order(extract_dependencies(value));
-        nodes.push(DagNodeView {
-            name: name.clone(),
-            depends_on: deps.clone(),
-        });
-        deps_by_var.insert(name.clone(), deps);
-    }
-
-    let topo = deterministic_topological_order(&deps_by_var)?;
+fn build_dag_view(engine: &RDagEngine, demand_driven: bool) -> Result<DagView> {
+    let nodes = engine
+        .nodes()
+        .into_iter()
+        .map(|node| DagNodeView {
+            name: node.name.clone(),
+            depends_on: node.dependencies.clone(),
+        })
+        .collect();
     Ok(DagView {
         nodes,
-        topological_order: topo,
+        topological_order: engine.topological_order()?,
+        evaluation: if demand_driven { "demand" } else { "eager" },
+        skipped: engine.skipped_nodes(),
     })
 }
 
-fn collect_merged_vars(doc: &FacetDocument) -> OrderedMap<String, ValueNode> {
-    let mut vars = OrderedMap::new();
-    for node in &doc.blocks {
-        if let FacetNode::Vars(vars_block) = node {
-            for body in &vars_block.body {
-                if let BodyNode::KeyValue(kv) = body {
-                    vars.insert(kv.key.clone(), kv.value.clone());
-                }
-            }
-        }
-    }
-    vars
-}
-
-fn deterministic_topological_order(
-    deps_by_var: &OrderedMap<String, Vec<String>>,
-) -> Result<Vec<String>> {
-    let mut in_degree: HashMap<String, usize> = HashMap::new();
-    let mut adj: HashMap<String, Vec<String>> = HashMap::new();
-    let mut order_index: HashMap<String, usize> = HashMap::new();
-
-    for (idx, name) in deps_by_var.keys().enumerate() {
-        in_degree.insert(name.clone(), 0);
-        adj.insert(name.clone(), Vec::new());
-        order_index.insert(name.clone(), idx);
-    }
-
-    for (name, deps) in deps_by_var {
-        for dep in deps {
-            if !deps_by_var.contains_key(dep) {
-                return Err(anyhow::anyhow!("F401: Variable not found: {}", dep));
-            }
-            adj.entry(dep.clone()).or_default().push(name.clone());
-            *in_degree.entry(name.clone()).or_insert(0) += 1;
-        }
-    }
-
-    let mut queue: BinaryHeap<Reverse<(usize, String)>> = BinaryHeap::new();
-    for name in deps_by_var.keys() {
-        if in_degree.get(name).copied().unwrap_or(0) == 0 {
-            let idx = *order_index.get(name).expect("order index");
-            queue.push(Reverse((idx, name.clone())));
-        }
-    }
-
-    let mut sorted = Vec::with_capacity(deps_by_var.len());
-    while let Some(Reverse((_, name))) = queue.pop() {
-        sorted.push(name.clone());
-        if let Some(neighbors) = adj.get(&name) {
-            for neighbor in neighbors {
-                if let Some(deg) = in_degree.get_mut(neighbor) {
-                    *deg -= 1;
-                    if *deg == 0 {
-                        let idx = *order_index.get(neighbor).expect("order index");
-                        queue.push(Reverse((idx, neighbor.clone())));
-                    }
-                }
-            }
-        }
-    }
-
-    if sorted.len() != deps_by_var.len() {
-        return Err(anyhow::anyhow!("F505: Cyclic dependency detected in @vars"));
-    }
-
-    Ok(sorted)
-}
-
-fn extract_dependencies(value: &ValueNode) -> Vec<String> {
-    let mut deps = Vec::new();
-    match value {
-        ValueNode::Variable(var_ref) => deps.push(base_var_name(var_ref).to_string()),
-        ValueNode::Pipeline(p) => {
-            deps.extend(extract_dependencies(&p.initial));
-            for lens in &p.lenses {
-                for arg in &lens.args {
-                    deps.extend(extract_dependencies(arg));
-                }
-                for arg in lens.kwargs.values() {
-                    deps.extend(extract_dependencies(arg));
-                }
-            }
-        }
-        ValueNode::List(items) => {
-            for item in items {
-                deps.extend(extract_dependencies(item));
-            }
-        }
-        ValueNode::Map(map) => {
-            for val in map.values() {
-                deps.extend(extract_dependencies(val));
-            }
-        }
-        _ => {}
-    }
-    deps
-}
-
-fn dedupe_preserve_order(items: Vec<String>) -> Vec<String> {
-    let mut seen = HashSet::new();
-    let mut out = Vec::new();
-    for item in items {
-        if seen.insert(item.clone()) {
-            out.push(item);
-        }
-    }
-    out
-}
-
-fn base_var_name(var_ref: &str) -> &str {
-    var_ref.split('.').next().unwrap_or(var_ref)
-}
-
-fn doc_to_sections(
-    doc: &FacetDocument,
-    computed_vars: &HashMap<String, ValueNode>,
-) -> Result<Vec<Section>> {
-    let mut sections = Vec::new();
-    let mut system_count = 0usize;
-    let mut user_count = 0usize;
-    let mut assistant_count = 0usize;
-
-    for node in &doc.blocks {
-        let (role, ordinal, block) = match node {
-            FacetNode::System(b) => {
-                system_count += 1;
-                ("system", system_count, b)
-            }
-            FacetNode::User(b) => {
-                user_count += 1;
-                ("user", user_count, b)
-            }
-            FacetNode::Assistant(b) => {
-                assistant_count += 1;
-                ("assistant", assistant_count, b)
-            }
-            _ => continue,
-        };
-
-        if !should_emit_message_block(block, computed_vars)? {
-            continue;
-        }
-
-        let mut id = derive_message_section_id(role, ordinal);
-        let mut priority = 500;
-        let mut min = 0usize;
-        let mut grow = 0.0f64;
-        let mut shrink = 0.0f64;
-        let mut strategy = None;
-        let mut content = None;
-
-        for body in &block.body {
-            let BodyNode::KeyValue(kv) = body else {
-                continue;
-            };
-            match kv.key.as_str() {
-                "content" => content = Some(resolve_message_value(&kv.value, computed_vars)?),
-                "id" => {
-                    if let ValueNode::String(v) = &kv.value {
-                        id = v.clone();
-                    }
-                }
-                "priority" => {
-                    if let Some(v) = as_i32(&kv.value) {
-                        priority = v;
-                    }
-                }
-                "min" => {
-                    if let Some(v) = as_usize(&kv.value) {
-                        min = v;
-                    }
-                }
-                "grow" => {
-                    if let Some(v) = as_f64(&kv.value) {
-                        grow = v;
-                    }
-                }
-                "shrink" => {
-                    if let Some(v) = as_f64(&kv.value) {
-                        shrink = v;
-                    }
-                }
-                "strategy" => {
-                    if let ValueNode::Pipeline(p) = &kv.value {
-                        strategy = Some(p.clone());
-                    }
-                }
-                _ => {}
-            }
-        }
-
-        let content = content.unwrap_or_else(|| ValueNode::String(String::new()));
-        let base_size = count_facet_units_in_value(&content);
-        let mut section = Section::new(id, content, base_size)
-            .with_role(role)
-            .with_priority(priority)
-            .with_limits(min, grow, shrink);
-        if let Some(strategy_pipeline) = strategy {
-            section = section.with_strategy(strategy_pipeline);
-        }
-        sections.push(section);
-    }
-
-    Ok(sections)
-}
-
-fn as_i32(v: &ValueNode) -> Option<i32> {
-    match v {
-        ValueNode::Scalar(ScalarValue::Int(i)) => i32::try_from(*i).ok(),
-        _ => None,
-    }
-}
-
-fn as_usize(v: &ValueNode) -> Option<usize> {
-    match v {
-        ValueNode::Scalar(ScalarValue::Int(i)) if *i >= 0 => usize::try_from(*i).ok(),
-        _ => None,
-    }
-}
-
-fn as_f64(v: &ValueNode) -> Option<f64> {
-    match v {
-        ValueNode::Scalar(ScalarValue::Int(i)) => Some(*i as f64),
-        ValueNode::Scalar(ScalarValue::Float(f)) => Some(*f),
-        _ => None,
-    }
-}
-
-fn should_emit_message_block(
-    block: &fct_ast::FacetBlock,
-    computed_vars: &HashMap<String, ValueNode>,
-) -> Result<bool> {
-    let Some(when_value) = block.attributes.get("when") else {
-        return Ok(true);
-    };
-
-    match when_value {
-        ValueNode::Scalar(ScalarValue::Bool(v)) => Ok(*v),
-        ValueNode::Variable(var_ref) => match resolve_variable_ref(var_ref, computed_vars)? {
-            ValueNode::Scalar(ScalarValue::Bool(v)) => Ok(v),
-            _ => Err(anyhow::anyhow!(
-                "F451: Type mismatch: 'when' must evaluate to bool"
-            )),
-        },
-        _ => Err(anyhow::anyhow!(
-            "F451: Type mismatch: 'when' must be bool or variable reference"
-        )),
-    }
-}
-
-fn resolve_message_value(
-    value: &ValueNode,
-    computed_vars: &HashMap<String, ValueNode>,
-) -> Result<ValueNode> {
-    match value {
-        ValueNode::Variable(var_ref) => resolve_variable_ref(var_ref, computed_vars),
-        ValueNode::List(items) => {
-            let mut out = Vec::with_capacity(items.len());
-            for item in items {
-                out.push(resolve_message_value(item, computed_vars)?);
-            }
-            Ok(ValueNode::List(out))
-        }
-        ValueNode::Map(map) => {
-            let mut out = OrderedMap::new();
-            for (k, v) in map {
-                out.insert(k.clone(), resolve_message_value(v, computed_vars)?);
-            }
-            Ok(ValueNode::Map(out))
-        }
-        ValueNode::Pipeline(_) => Err(anyhow::anyhow!(
-            "Unresolved pipeline in message content; expected computed value"
-        )),
-        ValueNode::Directive(_) => Err(anyhow::anyhow!(
-            "Unresolved directive in message content; expected computed value"
-        )),
-        _ => Ok(value.clone()),
-    }
-}
-
-fn resolve_variable_ref(
-    var_ref: &str,
-    computed_vars: &HashMap<String, ValueNode>,
-) -> Result<ValueNode> {
-    let mut parts = var_ref.split('.');
-    let base = parts.next().unwrap_or(var_ref);
-    let mut current = computed_vars
-        .get(base)
-        .cloned()
-        .ok_or_else(|| anyhow::anyhow!("F401: Variable not found: {}", base))?;
-
-    for segment in parts {
-        if segment.chars().all(|c| c.is_ascii_digit()) {
-            return Err(anyhow::anyhow!(
-                "F452: Numeric indexing is not standardized in v2.1.3: {}",
-                var_ref
-            ));
-        }
-
-        current = match current {
-            ValueNode::Map(map) => map
-                .get(segment)
-                .cloned()
-                .ok_or_else(|| anyhow::anyhow!("F405: Invalid variable path: {}", var_ref))?,
-            _ => return Err(anyhow::anyhow!("F405: Invalid variable path: {}", var_ref)),
-        };
-    }
-
-    Ok(current)
-}
-
-fn build_layout_view(budget: usize, allocation: &AllocationResult) -> LayoutView {
+fn build_layout_view(allocation: &AllocationResult) -> LayoutView {
     let sections = allocation
         .sections
         .iter()
@@ -530,19 +320,214 @@ fn build_layout_view(budget: usize, allocation: &AllocationResult) -> LayoutView
             shrink: item.section.shrink,
             critical: item.section.is_critical,
             dropped: item.was_dropped,
-            compressed: item.was_compressed,
+            compressed: item.strategy_applied,
             truncated: item.was_truncated,
+            dropped_items: item
+                .dropped_items
+                .iter()
+                .map(|dropped| DroppedItemView {
+                    index: dropped.index,
+                    size: dropped.size,
+                    score: dropped.score,
+                })
+                .collect(),
         })
         .collect();
 
     LayoutView {
-        budget,
+        budget: allocation.budget,
         total_size: allocation.total_size,
         overflow: allocation.overflow,
+        reserved: ReservedView {
+            tools: allocation.reserved.tools,
+            output: allocation.reserved.output,
+        },
         sections,
     }
 }
 
+fn parse_budget_range(spec: &str) -> Result<BudgetRange> {
+    let parts: Vec<&str> = spec.split(':').collect();
+    let [start, end, step] = parts.as_slice() else {
+        return Err(anyhow::anyhow!(
+            "--budget-sweep must be START:END:STEP, got '{}'",
+            spec
+        ));
+    };
+    let parse = |name: &str, value: &str| {
+        value.trim().parse::<usize>().with_context(|| {
+            format!(
+                "--budget-sweep {} must be a non-negative integer, got '{}'",
+                name, value
+            )
+        })
+    };
+    let range = BudgetRange {
+        start: parse("START", start)?,
+        end: parse("END", end)?,
+        step: parse("STEP", step)?,
+    };
+    if range.step == 0 || range.start > range.end {
+        return Err(anyhow::anyhow!(
+            "--budget-sweep needs STEP > 0 and START <= END, got '{}'",
+            spec
+        ));
+    }
+    if (range.end - range.start) / range.step >= MAX_SWEEP_POINTS {
+        return Err(anyhow::anyhow!(
+            "--budget-sweep '{}' covers more than {} budgets; use a larger STEP",
+            spec,
+            MAX_SWEEP_POINTS
+        ));
+    }
+    Ok(range)
+}
+
+fn build_budget_sweep_view(
+    range: BudgetRange,
+    sections: &[Section],
+    reservation: LayoutReservation,
+    lens_registry: &LensRegistry,
+    mode: ExecutionMode,
+) -> Result<BudgetSweepView> {
+    let critical_floor = TokenBoxModel::new(range.start)
+        .with_reservation(reservation)
+        .fixed_load(sections);
+    let mut section_views: Vec<SweepSectionView> = sections
+        .iter()
+        .map(|section| SweepSectionView {
+            id: section.id.clone(),
+            role: section
+                .role
+                .as_deref()
+                .unwrap_or_else(|| section_role(&section.id))
+                .to_string(),
+            base_size: section.base_size,
+            critical: section.is_critical,
+            compressed_at: None,
+            truncated_at: None,
+            dropped_at: None,
+            items_dropped_at: None,
+        })
+        .collect();
+
+    let mut points = Vec::new();
+    for budget in (range.start..=range.end).step_by(range.step) {
+        let model = TokenBoxModel::new(budget).with_reservation(reservation);
+        let allocation = match model.allocate_with_mode(sections.to_vec(), lens_registry, mode) {
+            Ok(allocation) => allocation,
+            Err(EngineError::BudgetExceeded { .. }) => {
+                points.push(SweepPointView {
+                    budget,
+                    feasible: false,
+                    total_size: None,
+                    dropped: Vec::new(),
+                });
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        // Budgets ascend, so the last one seen for each step is the highest.
+        for item in &allocation.sections {
+            let view = &mut section_views[item.section.source_index];
+            let steps = [
+                (&mut view.compressed_at, item.strategy_applied),
+                (&mut view.truncated_at, item.was_truncated),
+                (&mut view.dropped_at, item.was_dropped),
+                (&mut view.items_dropped_at, !item.dropped_items.is_empty()),
+            ];
+            for (threshold, happened) in steps {
+                if happened {
+                    *threshold = Some(budget);
+                }
+            }
+        }
+        points.push(SweepPointView {
+            budget,
+            feasible: true,
+            total_size: Some(allocation.total_size),
+            dropped: allocation
+                .sections
+                .iter()
+                .filter(|item| item.was_dropped)
+                .map(|item| item.section.id.clone())
+                .collect(),
+        });
+    }
+
+    Ok(BudgetSweepView {
+        start: range.start,
+        end: range.end,
+        step: range.step,
+        critical_floor,
+        sections: section_views,
+        points,
+    })
+}
+
+fn print_budget_sweep(sweep: &BudgetSweepView) {
+    println!(
+        "budget sweep {}..{} step {} (critical floor {}, F901 below)",
+        sweep.start, sweep.end, sweep.step, sweep.critical_floor
+    );
+    let threshold = |budget: Option<usize>| match budget {
+        Some(budget) => format!("<= {}", budget),
+        None => "-".to_string(),
+    };
+    let rows: Vec<[String; 6]> = sweep
+        .sections
+        .iter()
+        .map(|section| {
+            if section.critical {
+                let critical = "critical".to_string();
+                return [
+                    section.id.clone(),
+                    section.base_size.to_string(),
+                    critical,
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                ];
+            }
+            [
+                section.id.clone(),
+                section.base_size.to_string(),
+                threshold(section.compressed_at),
+                threshold(section.items_dropped_at),
+                threshold(section.truncated_at),
+                threshold(section.dropped_at),
+            ]
+        })
+        .collect();
+    let header = [
+        "SECTION",
+        "BASE",
+        "COMPRESSED",
+        "ITEMS DROPPED",
+        "TRUNCATED",
+        "DROPPED",
+    ]
+    .map(String::from);
+    let widths: Vec<usize> = (0..header.len())
+        .map(|col| {
+            std::iter::once(&header)
+                .chain(&rows)
+                .map(|row| row[col].len())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    for row in std::iter::once(&header).chain(&rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+}
+
 fn section_role(id: &str) -> &str {
     if id.starts_with("system") {
         "system"
@@ -584,7 +569,9 @@ mod tests {
   c: $a
 "#;
         let doc = fct_parser::parse_document(source).expect("parse");
-        let view = build_dag_view(&doc).expect("dag view");
+        let mut engine = RDagEngine::new();
+        engine.build(&doc).expect("build");
+        let view = build_dag_view(&engine, false).expect("dag view");
 
         let node_names: Vec<&str> = view.nodes.iter().map(|n| n.name.as_str()).collect();
         assert_eq!(node_names, vec!["a", "b", "c"]);
@@ -625,6 +612,7 @@ mod tests {
         let limiter = RateLimiter::direct(Quota::per_second(nonzero!(10u32)));
         execute_inspect(
             input_path,
+            None,
             Some(ast_path.clone()),
             Some(dag_path.clone()),
             Some(layout_path.clone()),
@@ -632,6 +620,13 @@ mod tests {
             512,
             false,
             true,
+            "hypervisor".to_string(),
+            false,
+            0,
+            None,
+            None,
+            None,
+            None,
             &limiter,
         )
         .expect("inspect should succeed");
@@ -669,4 +664,420 @@ mod tests {
 
         let _ = fs::remove_dir_all(&test_dir);
     }
+
+    #[test]
+    fn execute_inspect_layout_reports_packed_items() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let test_dir = std::env::temp_dir().join(format!("facet-inspect-pack-{}", nonce));
+        fs::create_dir_all(&test_dir).expect("create temp dir");
+
+        let input_path = test_dir.join("input.facet");
+        let layout_path = test_dir.join("layout.json");
+        let source = r#"
+@vars
+  docs: [{ type: "text", text: "first retrieved chunk", relevance: 0.9 }, { type: "text", text: "other retrieved chunk", relevance: 0.2 }, { type: "text", text: "third retrieved chunk", relevance: 0.5 }]
+
+@system
+  content: "System"
+
+@user
+  content: $docs
+  shrink: 1
+  x_fct_pack: { score: "relevance" }
+"#;
+        fs::write(&input_path, source).expect("write source");
+
+        let limiter = RateLimiter::direct(Quota::per_second(nonzero!(10u32)));
+        execute_inspect(
+            input_path,
+            None,
+            None,
+            None,
+            Some(layout_path.clone()),
+            None,
+            110,
+            false,
+            false,
+            "hypervisor".to_string(),
+            false,
+            0,
+            None,
+            None,
+            None,
+            None,
+            &limiter,
+        )
+        .expect("inspect should succeed");
+
+        let layout_json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&layout_path).expect("read layout"))
+                .expect("layout json");
+        let user = &layout_json["sections"][1];
+        assert_eq!(user["id"], "user#1");
+        assert_eq!(user["truncated"], false);
+        assert_eq!(
+            user["dropped_items"],
+            serde_json::json!([{ "index": 1, "size": 45, "score": 0.2 }])
+        );
+        assert_eq!(user["final_size"], 90);
+        assert_eq!(
+            layout_json["reserved"],
+            serde_json::json!({ "tools": 0, "output": 0 })
+        );
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn execute_inspect_lays_out_at_context_budget_with_runtime_inputs() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let test_dir = std::env::temp_dir().join(format!("facet-inspect-context-{}", nonce));
+        fs::create_dir_all(&test_dir).expect("create temp dir");
+
+        let input_path = test_dir.join("input.facet");
+        let inputs_path = test_dir.join("inputs.json");
+        let layout_path = test_dir.join("layout.json");
+        let source = r#"
+@context
+  budget: 20
+
+@vars
+  question: @input(type="string")
+
+@system
+  content: "System"
+
+@user
+  content: $question
+  shrink: 1
+"#;
+        fs::write(&input_path, source).expect("write source");
+        fs::write(
+            &inputs_path,
+            serde_json::json!({ "question": "x".repeat(40) }).to_string(),
+        )
+        .expect("write inputs");
+
+        let limiter = RateLimiter::direct(Quota::per_second(nonzero!(10u32)));
+        execute_inspect(
+            input_path,
+            Some(inputs_path),
+            None,
+            None,
+            Some(layout_path.clone()),
+            None,
+            4096,
+            false,
+            false,
+            "hypervisor".to_string(),
+            false,
+            0,
+            None,
+            None,
+            None,
+            None,
+            &limiter,
+        )
+        .expect("inspect should succeed");
+
+        let layout_json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&layout_path).expect("read layout"))
+                .expect("layout json");
+        // `@context.budget` wins over --budget, as in `run`.
+        assert_eq!(layout_json["budget"], 20);
+        let user = &layout_json["sections"][1];
+        assert_eq!(user["base_size"], 40);
+        assert_eq!(user["final_size"], 14);
+        assert_eq!(user["truncated"], true);
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn execute_inspect_measures_tools_exposed_by_the_host_context() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let test_dir = std::env::temp_dir().join(format!("facet-inspect-host-{}", nonce));
+        fs::create_dir_all(&test_dir).expect("create temp dir");
+
+        let input_path = test_dir.join("input.facet");
+        let layout_path = test_dir.join("layout.json");
+        let profile_path = test_dir.join("host.json");
+        let source = r#"
+@interface Payments
+  fn charge(amount: int) -> string (effect="payment")
+
+@system
+  tools: [$Payments]
+  content: "System"
+
+@policy
+  deny: [{ op: "tool_expose", name: "Payments.*", unless: $x.acme.admin }]
+"#;
+        fs::write(&input_path, source).expect("write source");
+        fs::write(
+            &profile_path,
+            r#"{ "id": "acme.prod.v1", "context": { "acme": "struct { admin: bool }" } }"#,
+        )
+        .expect("write host profile");
+
+        let limiter = RateLimiter::direct(Quota::per_second(nonzero!(10u32)));
+        let inspect = |host_profile: Option<PathBuf>, admin: Option<bool>| {
+            let host_context = admin.map(|admin| {
+                let path = test_dir.join(format!("context-{admin}.json"));
+                fs::write(
+                    &path,
+                    serde_json::json!({ "acme": { "admin": admin } }).to_string(),
+                )
+                .expect("write host context");
+                path
+            });
+            execute_inspect(
+                input_path.clone(),
+                None,
+                None,
+                None,
+                Some(layout_path.clone()),
+                None,
+                4096,
+                false,
+                false,
+                "hypervisor".to_string(),
+                false,
+                0,
+                None,
+                None,
+                host_profile,
+                host_context,
+                &limiter,
+            )
+            .map(|()| {
+                let layout: serde_json::Value =
+                    serde_json::from_str(&fs::read_to_string(&layout_path).expect("read layout"))
+                        .expect("layout json");
+                layout["reserved"]["tools"].as_u64().expect("tool units")
+            })
+        };
+
+        let err = inspect(None, None).expect_err("$x is unknown without a host profile");
+        assert!(err.to_string().contains("F401"), "{err}");
+        let admin_tools = inspect(Some(profile_path.clone()), Some(true)).expect("admin");
+        assert!(admin_tools > 0);
+        assert_eq!(inspect(Some(profile_path), Some(false)).expect("user"), 0);
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn execute_inspect_budget_sweep_reports_section_thresholds() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let test_dir = std::env::temp_dir().join(format!("facet-inspect-sweep-{}", nonce));
+        fs::create_dir_all(&test_dir).expect("create temp dir");
+
+        let input_path = test_dir.join("input.facet");
+        let sweep_path = test_dir.join("sweep.json");
+        let source = format!(
+            "@system\n  content: \"System\"\n\n@user\n  content: \"{}\"\n  min: 10\n  shrink: 1\n",
+            "x".repeat(40)
+        );
+        fs::write(&input_path, source).expect("write source");
+
+        let limiter = RateLimiter::direct(Quota::per_second(nonzero!(10u32)));
+        execute_inspect(
+            input_path,
+            None,
+            None,
+            None,
+            None,
+            None,
+            512,
+            false,
+            false,
+            "hypervisor".to_string(),
+            false,
+            4,
+            Some("0:60:10".to_string()),
+            Some(sweep_path.clone()),
+            None,
+            None,
+            &limiter,
+        )
+        .expect("inspect should succeed");
+
+        let sweep: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&sweep_path).expect("read sweep"))
+                .expect("sweep json");
+        // The 6-unit system section plus the 4-unit output reserve.
+        assert_eq!(sweep["critical_floor"], 10);
+        assert_eq!(sweep["points"][0]["budget"], 0);
+        assert_eq!(sweep["points"][0]["feasible"], false);
+        assert_eq!(sweep["points"][1]["dropped"], serde_json::json!(["user#1"]));
+        assert_eq!(sweep["points"].as_array().map(Vec::len), Some(7));
+
+        let system = &sweep["sections"][0];
+        assert_eq!(system["critical"], true);
+        assert!(system["dropped_at"].is_null());
+        let user = &sweep["sections"][1];
+        assert_eq!(user["id"], "user#1");
+        assert_eq!(user["truncated_at"], 40);
+        assert_eq!(user["dropped_at"], 10);
+        assert!(user["items_dropped_at"].is_null());
+        // Truncation shrinks the section too, but it has no strategy to apply.
+        assert!(user["compressed_at"].is_null());
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn parse_budget_range_rejects_malformed_sweeps() {
+        assert_eq!(
+            parse_budget_range("1000:64000:1000").expect("valid range"),
+            BudgetRange {
+                start: 1000,
+                end: 64000,
+                step: 1000
+            }
+        );
+        for spec in ["1000:64000", "a:10:1", "10:0:1", "0:10:0", "0:100000:1"] {
+            assert!(
+                parse_budget_range(spec).is_err(),
+                "{spec} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn execute_inspect_core_profile_skips_dag_and_layout() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let test_dir = std::env::temp_dir().join(format!("facet-inspect-core-{}", nonce));
+        fs::create_dir_all(&test_dir).expect("create temp dir");
+
+        let input_path = test_dir.join("input.facet");
+        let policy_path = test_dir.join("policy.json");
+        fs::write(
+            &input_path,
+            "@vars\n  name: \"World\"\n\n@user\n  content: $name\n",
+        )
+        .expect("write source");
+
+        let limiter = RateLimiter::direct(Quota::per_second(nonzero!(10u32)));
+        execute_inspect(
+            input_path.clone(),
+            None,
+            None,
+            None,
+            None,
+            Some(policy_path.clone()),
+            512,
+            false,
+            false,
+            "core".to_string(),
+            false,
+            0,
+            None,
+            None,
+            None,
+            None,
+            &limiter,
+        )
+        .expect("core inspect should succeed");
+        let policy_json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&policy_path).expect("read policy"))
+                .expect("policy json");
+        assert!(policy_json.get("policy_hash").is_some());
+
+        let err = execute_inspect(
+            input_path,
+            None,
+            None,
+            Some(test_dir.join("dag.json")),
+            None,
+            None,
+            512,
+            false,
+            false,
+            "core".to_string(),
+            false,
+            0,
+            None,
+            None,
+            None,
+            None,
+            &limiter,
+        )
+        .expect_err("core has no dag view");
+        assert!(err.to_string().contains("--profile core"));
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn execute_inspect_dag_reports_demand_driven_skips() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let test_dir = std::env::temp_dir().join(format!("facet-inspect-demand-{}", nonce));
+        fs::create_dir_all(&test_dir).expect("create temp dir");
+
+        let input_path = test_dir.join("input.facet");
+        let dag_path = test_dir.join("dag.json");
+        let source = r#"
+@vars
+  name: "World"
+  library_note: "unused" |> trim()
+  unused_input: @input(type="string")
+
+@user
+  content: $name
+"#;
+        fs::write(&input_path, source).expect("write source");
+
+        let limiter = RateLimiter::direct(Quota::per_second(nonzero!(10u32)));
+        execute_inspect(
+            input_path,
+            None,
+            None,
+            Some(dag_path.clone()),
+            None,
+            None,
+            512,
+            false,
+            true,
+            "hypervisor".to_string(),
+            true,
+            0,
+            None,
+            None,
+            None,
+            None,
+            &limiter,
+        )
+        .expect("demand-driven inspect skips the missing input");
+
+        let dag_json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&dag_path).expect("read dag"))
+                .expect("dag json");
+        assert_eq!(dag_json["evaluation"], "demand");
+        assert_eq!(
+            dag_json["skipped"],
+            serde_json::json!(["library_note", "unused_input"])
+        );
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
 }
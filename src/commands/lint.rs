@@ -0,0 +1,252 @@
+//! # Lint Command
+//!
+//! This module implements the lint command for the FACET compiler.
+//! The lint command reports likely mistakes in contracts that already validate.
+
+use crate::commands::host_profile::load_host_setup;
+use anyhow::{Context, Result};
+use console::style;
+use fct_ast::FacetDocument;
+use fct_parser::parse_document;
+use fct_resolver::{Resolver, ResolverConfig};
+use fct_validator::{
+    HostContextSchema, LintConfig, LintDiagnostic, LintSeverity, Linter, TypeChecker,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Lint command handler
+pub fn execute_lint(
+    input: PathBuf,
+    config: Option<PathBuf>,
+    rules: Vec<String>,
+    format: String,
+    host_profile: Option<PathBuf>,
+    host_context: Option<PathBuf>,
+    rate_limiter: &crate::commands::DefaultRateLimiter,
+) -> Result<()> {
+    // Check rate limit
+    if rate_limiter.check().is_err() {
+        warn!("Rate limit exceeded for lint command");
+        eprintln!(
+            "{}",
+            style("Rate limit exceeded. Please wait before running another command.").red()
+        );
+        std::process::exit(1);
+    }
+
+    let lint_config = load_lint_config(config.as_deref(), &rules)?;
+    let host = load_host_setup(host_profile.as_deref(), host_context.as_deref())?;
+    let diagnostics = lint_file(&input, lint_config, host.as_ref().map(|host| &host.schema))?;
+
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&diagnostics)?),
+        "text" => print_text_report(&input, &diagnostics),
+        other => {
+            return Err(anyhow::anyhow!(
+                "Unsupported lint output format '{}': use text or json",
+                other
+            ))
+        }
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == LintSeverity::Error)
+        .count();
+    if errors > 0 {
+        return Err(anyhow::anyhow!("Lint failed with {} error(s)", errors));
+    }
+    Ok(())
+}
+
+/// Build the rule configuration from an optional JSON file plus `rule=severity` overrides.
+fn load_lint_config(path: Option<&Path>, overrides: &[String]) -> Result<LintConfig> {
+    let mut config = match path {
+        Some(path) => {
+            let raw = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read lint config: {:?}", path))?;
+            let config: LintConfig = serde_json::from_str(&raw)
+                .with_context(|| format!("Invalid lint config: {:?}", path))?;
+            config.check_rules().map_err(|e| anyhow::anyhow!(e))?;
+            config
+        }
+        None => LintConfig::new(),
+    };
+
+    for item in overrides {
+        let (rule, severity) = item
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("Expected --rule <name>=<severity>, got '{}'", item))?;
+        let severity = LintSeverity::parse(severity.trim()).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown lint severity '{}': use off, info, warning or error",
+                severity
+            )
+        })?;
+        config
+            .set_severity(rule.trim(), severity)
+            .map_err(|e| anyhow::anyhow!(e))?;
+    }
+    Ok(config)
+}
+
+/// Parse, resolve, and validate `input`, then run the linter over it.
+fn lint_file(
+    input: &Path,
+    config: LintConfig,
+    host_context: Option<&HostContextSchema>,
+) -> Result<Vec<LintDiagnostic>> {
+    let source = fs::read_to_string(input)
+        .with_context(|| format!("Failed to read input file: {:?}", input))?;
+
+    let base_dir = input
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or(std::env::current_dir()?);
+    let mut resolver = Resolver::new(ResolverConfig {
+        allowed_roots: vec![base_dir.clone()],
+        base_dir,
+    });
+    let phase1 = resolver
+        .resolve_phase1(&source)
+        .map_err(|e| anyhow::anyhow!("Resolution error: {}", e))?;
+
+    let mut checker = TypeChecker::new();
+    if let Some(schema) = host_context {
+        checker = checker.with_host_context(schema.clone());
+    }
+    checker
+        .validate(&phase1.resolved_ast)
+        .map_err(|e| anyhow::anyhow!("Validation error: {}", e))?;
+
+    // The expanded (unmerged) form keeps policy rules that imports would collapse by id.
+    // Both documents are parsed from it, so every span maps back through the source map.
+    let expanded = parse_document(&phase1.resolved_source_form)
+        .map_err(|e| anyhow::anyhow!("Parse error: {}", e))?;
+    let resolved = FacetDocument {
+        blocks: resolver.merge_blocks(expanded.blocks.clone()),
+        span: expanded.span.clone(),
+    };
+
+    Ok(Linter::new(config)
+        .with_source(phase1.resolved_source_form)
+        .with_source_map(phase1.source_map)
+        .lint_with_sources(&resolved, &expanded))
+}
+
+fn print_text_report(input: &Path, diagnostics: &[LintDiagnostic]) {
+    for diagnostic in diagnostics {
+        let label = match diagnostic.severity {
+            LintSeverity::Error => style("error").red().bold(),
+            LintSeverity::Warning => style("warning").yellow().bold(),
+            _ => style(diagnostic.severity.as_str()).cyan().bold(),
+        };
+        println!(
+            "{}[{}]: {} ({}:{})",
+            label,
+            diagnostic.code,
+            diagnostic.message,
+            diagnostic.file.as_deref().unwrap_or(input).display(),
+            diagnostic.span.line
+        );
+        if let Some(suggestion) = &diagnostic.suggestion {
+            println!("  {} {}", style("fix:").green(), suggestion.message);
+        }
+    }
+
+    if diagnostics.is_empty() {
+        println!("{}", style("✓ No lint findings").green());
+    } else {
+        println!("{} finding(s)", diagnostics.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_dir(label: &str) -> PathBuf {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("facet-lint-{}-{}", label, nonce));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn lint_reports_duplicate_rule_ids_across_imports() {
+        let test_dir = temp_dir("imports");
+        fs::write(
+            test_dir.join("base.facet"),
+            "@policy\n  allow: [{ id: \"lens\", op: \"lens_call\", name: \"trim\" }]\n",
+        )
+        .expect("write import");
+        let input_path = test_dir.join("input.facet");
+        fs::write(
+            &input_path,
+            "@import \"base.facet\"\n@policy\n  allow: [{ id: \"lens\", op: \"lens_call\", name: \"trim\" }]\n@user\n  content: \"hi\"\n",
+        )
+        .expect("write input");
+
+        let diagnostics = lint_file(&input_path, LintConfig::new(), None).expect("lint should run");
+        let codes: Vec<&str> = diagnostics.iter().map(|d| d.code.as_str()).collect();
+        assert_eq!(codes, vec!["X.lint.duplicate_policy_rule_id"]);
+
+        let _ = fs::remove_dir_all(test_dir);
+    }
+
+    #[test]
+    fn lint_reports_imported_spans_in_their_file_without_autofix() {
+        let test_dir = temp_dir("origins");
+        fs::write(test_dir.join("base.facet"), "@vars\n  shared: \"x\"\n").expect("write import");
+        let input_path = test_dir.join("input.facet");
+        let source = "@import \"base.facet\"\n@vars\n  local: 1\n@user\n  content: \"hi\"\n";
+        fs::write(&input_path, source).expect("write input");
+
+        let diagnostics = lint_file(&input_path, LintConfig::new(), None).expect("lint should run");
+        assert_eq!(diagnostics.len(), 2);
+
+        let shared = &diagnostics[0];
+        assert!(shared.message.contains("'shared'"));
+        let file = shared.file.as_deref().expect("imported file");
+        assert_eq!(file.file_name(), Some("base.facet".as_ref()));
+        assert_eq!(shared.span.line, 2);
+        assert!(shared.suggestion.is_none());
+
+        let local = &diagnostics[1];
+        assert!(local.message.contains("'local'"));
+        assert!(local.file.is_none());
+        assert_eq!(local.span.line, 3);
+        assert_eq!(&source[local.span.start..local.span.end], "local: 1");
+        assert!(local.suggestion.is_some());
+
+        let _ = fs::remove_dir_all(test_dir);
+    }
+
+    #[test]
+    fn lint_config_file_and_overrides_combine() {
+        let test_dir = temp_dir("config");
+        let config_path = test_dir.join("lint.json");
+        fs::write(
+            &config_path,
+            r#"{ "rules": { "unused_var": "error", "dead_message": "off" } }"#,
+        )
+        .expect("write config");
+
+        let config = load_lint_config(Some(&config_path), &["dead_message=info".to_string()])
+            .expect("config should load");
+        assert_eq!(config.severity("unused_var"), LintSeverity::Error);
+        assert_eq!(config.severity("dead_message"), LintSeverity::Info);
+
+        fs::write(&config_path, r#"{ "rules": { "bogus": "error" } }"#).expect("write config");
+        assert!(load_lint_config(Some(&config_path), &[]).is_err());
+        assert!(load_lint_config(None, &["unused_var=loud".to_string()]).is_err());
+
+        let _ = fs::remove_dir_all(test_dir);
+    }
+}
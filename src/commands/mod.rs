@@ -9,16 +9,20 @@ use std::path::PathBuf;
 
 pub type DefaultRateLimiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
 
-pub mod artifact;
 pub mod build;
-pub mod canonical;
 pub mod codegen;
-pub mod guard;
+pub mod compile;
+pub mod guard_serve;
+pub mod host_profile;
 pub mod inspect;
+pub mod lint;
 pub mod mode_profile;
 pub mod policy;
+pub mod repl;
 pub mod run;
+pub mod schema;
 pub mod test;
+pub mod trace_output;
 
 /// Main CLI structure using clap for argument parsing
 #[derive(Parser)]
@@ -50,6 +54,37 @@ pub enum Commands {
         /// Input FACET file path
         #[arg(short, long)]
         input: PathBuf,
+
+        /// Conformance profile: core or hypervisor
+        #[arg(long, default_value = "hypervisor")]
+        profile: String,
+
+        /// Host profile JSON declaring the host id and its $x context types
+        #[arg(long)]
+        host_profile: Option<PathBuf>,
+
+        /// Host context JSON visible to policy conditions as $x.<host>...
+        #[arg(long, requires = "host_profile")]
+        host_context: Option<PathBuf>,
+    },
+
+    /// Precompile a FACET document into a contract bundle (.fctb) for `run`
+    Compile {
+        /// Input FACET file path
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output bundle path
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Conformance profile: core or hypervisor
+        #[arg(long, default_value = "hypervisor")]
+        profile: String,
+
+        /// Host profile JSON the bundle is bound to (id and $x context types)
+        #[arg(long)]
+        host_profile: Option<PathBuf>,
     },
 
     /// Inspect the parsed AST structure
@@ -58,6 +93,10 @@ pub enum Commands {
         #[arg(short, long)]
         input: PathBuf,
 
+        /// Runtime input values JSON file for @input(...) variables
+        #[arg(long)]
+        runtime_input: Option<PathBuf>,
+
         /// Write resolved AST view to JSON file
         #[arg(long)]
         ast: Option<PathBuf>,
@@ -85,11 +124,39 @@ pub enum Commands {
         /// Inspect in exec mode (default)
         #[arg(long)]
         exec: bool,
+
+        /// Conformance profile: core or hypervisor
+        #[arg(long, default_value = "hypervisor")]
+        profile: String,
+
+        /// Evaluate only the @vars that messages and @policy need
+        #[arg(long)]
+        demand_driven: bool,
+
+        /// Layout units kept free for the model's reply (@context.x_fct_output_reserve overrides)
+        #[arg(long, default_value_t = 0)]
+        output_reserve: usize,
+
+        /// Rerun layout over a budget range START:END:STEP and print when each section is compressed, truncated or dropped
+        #[arg(long, value_name = "START:END:STEP")]
+        budget_sweep: Option<String>,
+
+        /// Write budget sweep view to JSON file
+        #[arg(long, requires = "budget_sweep")]
+        sweep: Option<PathBuf>,
+
+        /// Host profile JSON declaring the host id and its $x context types
+        #[arg(long)]
+        host_profile: Option<PathBuf>,
+
+        /// Host context JSON visible to policy conditions as $x.<host>...
+        #[arg(long, requires = "host_profile")]
+        host_context: Option<PathBuf>,
     },
 
     /// Run full pipeline: parse, resolve, validate, compute, and render
     Run {
-        /// Input FACET file path
+        /// Input FACET file path, or a .fctb bundle from `compile`
         #[arg(short, long)]
         input: PathBuf,
 
@@ -105,6 +172,10 @@ pub enum Commands {
         #[arg(short = 'c', long, default_value_t = 10000)]
         context_budget: usize,
 
+        /// Layout units kept free for the model's reply (@context.x_fct_output_reserve overrides)
+        #[arg(long, default_value_t = 0)]
+        output_reserve: usize,
+
         /// Output format: json or pretty
         #[arg(short, long, default_value = "json")]
         format: String,
@@ -116,6 +187,65 @@ pub enum Commands {
         /// Run in exec mode (default)
         #[arg(long)]
         exec: bool,
+
+        /// Host profile JSON declaring the host id and its $x context types
+        #[arg(long)]
+        host_profile: Option<PathBuf>,
+
+        /// Host context JSON visible to policy conditions as $x.<host>...
+        #[arg(long, requires = "host_profile")]
+        host_context: Option<PathBuf>,
+
+        /// Conformance profile: core or hypervisor
+        #[arg(long, default_value = "hypervisor")]
+        profile: String,
+
+        /// Evaluate only the @vars that messages and @policy need
+        #[arg(long)]
+        demand_driven: bool,
+
+        /// Evaluate independent @vars concurrently (same results as sequential)
+        #[arg(long)]
+        parallel: bool,
+
+        /// Write a Chrome-trace timeline (phases, @vars, lens calls, layout) to this file
+        #[arg(long)]
+        trace: Option<PathBuf>,
+    },
+
+    /// Evaluate value expressions interactively against a contract's computed @vars
+    Repl {
+        /// Input FACET file path
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Runtime input values JSON file for @input(...) variables
+        #[arg(long)]
+        runtime_input: Option<PathBuf>,
+
+        /// Default token budget for :layout (default: 4096)
+        #[arg(short, long, default_value_t = 4096)]
+        budget: usize,
+
+        /// Execution context budget for R-DAG (default: 10000)
+        #[arg(short = 'c', long, default_value_t = 10000)]
+        context_budget: usize,
+
+        /// Evaluate in pure mode
+        #[arg(long, conflicts_with = "exec")]
+        pure: bool,
+
+        /// Evaluate in exec mode (default)
+        #[arg(long)]
+        exec: bool,
+
+        /// Host profile JSON declaring the host id and its $x context types
+        #[arg(long)]
+        host_profile: Option<PathBuf>,
+
+        /// Host context JSON visible to policy conditions as $x.<host>...
+        #[arg(long, requires = "host_profile")]
+        host_context: Option<PathBuf>,
     },
 
     /// Run @test blocks
@@ -147,6 +277,18 @@ pub enum Commands {
         /// Run tests in exec mode (default)
         #[arg(long)]
         exec: bool,
+
+        /// Write a Chrome-trace timeline of every test to this file
+        #[arg(long)]
+        trace: Option<PathBuf>,
+
+        /// Host profile JSON declaring the host id and its $x context types
+        #[arg(long)]
+        host_profile: Option<PathBuf>,
+
+        /// Host context JSON visible to policy conditions as $x.<host>...
+        #[arg(long, requires = "host_profile")]
+        host_context: Option<PathBuf>,
     },
 
     /// Generate SDK from FACET interfaces
@@ -167,4 +309,179 @@ pub enum Commands {
         #[arg(long)]
         name: Option<String>,
     },
+
+    /// Report likely mistakes in a valid FACET document
+    Lint {
+        /// Input FACET file path
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// JSON lint config: {"rules": {"<rule>": "off|info|warning|error"}}
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Override one rule's severity, e.g. --rule unused_var=error (repeatable)
+        #[arg(long = "rule", value_name = "RULE=SEVERITY")]
+        rules: Vec<String>,
+
+        /// Output format: text or json
+        #[arg(short, long, default_value = "text")]
+        format: String,
+
+        /// Host profile JSON declaring the host id and its $x context types
+        #[arg(long)]
+        host_profile: Option<PathBuf>,
+
+        /// Host context JSON visible to policy conditions as $x.<host>...
+        #[arg(long, requires = "host_profile")]
+        host_context: Option<PathBuf>,
+    },
+
+    /// Serve guard decisions for host tool/lens calls over JSON-RPC
+    GuardServe {
+        /// Input FACET file path, or a .fctb bundle from `compile`
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Runtime input values JSON file for @input(...) variables
+        #[arg(long)]
+        runtime_input: Option<PathBuf>,
+
+        /// Listen on a Unix socket instead of stdio
+        #[arg(long)]
+        socket: Option<PathBuf>,
+
+        /// Token budget for context window (default: 4096)
+        #[arg(short, long, default_value_t = 4096)]
+        budget: usize,
+
+        /// Execution context budget for R-DAG (default: 10000)
+        #[arg(short = 'c', long, default_value_t = 10000)]
+        context_budget: usize,
+
+        /// Guard in pure mode
+        #[arg(long, conflicts_with = "exec")]
+        pure: bool,
+
+        /// Guard in exec mode (default)
+        #[arg(long)]
+        exec: bool,
+
+        /// Host profile JSON declaring the host id and its $x context types
+        #[arg(long)]
+        host_profile: Option<PathBuf>,
+
+        /// Host context JSON visible to policy conditions as $x.<host>...
+        #[arg(long, requires = "host_profile")]
+        host_context: Option<PathBuf>,
+
+        /// Maximum number of open sessions (default: 1024)
+        #[arg(long, default_value_t = guard_serve::DEFAULT_MAX_SESSIONS)]
+        max_sessions: usize,
+
+        /// Seconds a session may stay idle before it expires (default: 900)
+        #[arg(long, default_value_t = guard_serve::DEFAULT_SESSION_IDLE.as_secs())]
+        session_idle_secs: u64,
+    },
+
+    /// Analyze the effective @policy of a FACET document
+    Policy {
+        #[command(subcommand)]
+        command: PolicyCommands,
+    },
+
+    /// Emit JSON Schema views of a FACET document
+    Schema {
+        #[command(subcommand)]
+        command: SchemaCommands,
+    },
+}
+
+/// Available `policy` subcommands
+#[derive(clap::Subcommand)]
+pub enum PolicyCommands {
+    /// Static decision table for every guarded operation the document can produce
+    Explain {
+        /// Input FACET file path
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output format: text or json
+        #[arg(short, long, default_value = "text")]
+        format: String,
+
+        /// Host profile JSON declaring the host id and its $x context types
+        #[arg(long)]
+        host_profile: Option<PathBuf>,
+
+        /// Host context JSON visible to policy conditions as $x.<host>...
+        #[arg(long, requires = "host_profile")]
+        host_context: Option<PathBuf>,
+    },
+
+    /// Traced guard decision for one operation, as the host would see it at runtime
+    Eval {
+        /// Input FACET file path
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Operation: tool_expose, tool_call, lens_call or message_emit
+        #[arg(long)]
+        op: String,
+
+        /// Operation name: Interface.fn, lens name, or message id
+        #[arg(long)]
+        name: String,
+
+        /// Effect class (default: declared by the contract or lens)
+        #[arg(long)]
+        effect_class: Option<String>,
+
+        /// JSON object of variable values for when/unless conditions
+        #[arg(long)]
+        vars: Option<PathBuf>,
+
+        /// JSON call arguments for the input_hash (tool args, or {input, args, kwargs} for lenses)
+        #[arg(long)]
+        args: Option<PathBuf>,
+
+        /// Host profile id used in the input_hash
+        #[arg(long, default_value = "local.default.v1")]
+        host_profile_id: String,
+
+        /// Host profile JSON declaring the host id and its $x context types
+        #[arg(long, conflicts_with = "host_profile_id")]
+        host_profile: Option<PathBuf>,
+
+        /// Host context JSON visible to policy conditions as $x.<host>...
+        #[arg(long, requires = "host_profile")]
+        host_context: Option<PathBuf>,
+
+        /// Output format: text or json
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+}
+
+/// Available `schema` subcommands
+#[derive(clap::Subcommand)]
+pub enum SchemaCommands {
+    /// JSON Schema for the runtime inputs declared with @input(...)
+    Inputs {
+        /// Input FACET file path
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Write the schema to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Host profile JSON declaring the host id and its $x context types
+        #[arg(long)]
+        host_profile: Option<PathBuf>,
+
+        /// Host context JSON visible to policy conditions as $x.<host>...
+        #[arg(long, requires = "host_profile")]
+        host_context: Option<PathBuf>,
+    },
 }
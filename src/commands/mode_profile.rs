@@ -1,5 +1,6 @@
 use anyhow::{anyhow, Result};
 use fct_engine::ExecutionMode;
+use fct_validator::ValidationProfile;
 
 pub fn resolve_execution_mode(pure: bool, exec: bool) -> Result<(ExecutionMode, &'static str)> {
     if pure && exec {
@@ -13,10 +14,23 @@ pub fn resolve_execution_mode(pure: bool, exec: bool) -> Result<(ExecutionMode,
     }
 }
 
+/// Resolve `--profile` (§2.1.1): `core` runs Phases 1, 2 and 5 only.
+pub fn resolve_profile(profile: &str) -> Result<(ValidationProfile, &'static str)> {
+    match profile {
+        "core" => Ok((ValidationProfile::Core, "core")),
+        "hypervisor" => Ok((ValidationProfile::Hypervisor, "hypervisor")),
+        other => Err(anyhow!(
+            "Unknown profile '{}': expected core or hypervisor",
+            other
+        )),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::resolve_execution_mode;
+    use super::{resolve_execution_mode, resolve_profile};
     use fct_engine::ExecutionMode;
+    use fct_validator::ValidationProfile;
 
     #[test]
     fn resolve_execution_mode_rejects_conflicting_flags() {
@@ -36,4 +50,17 @@ mod tests {
         assert_eq!(mode, ExecutionMode::Exec);
         assert_eq!(label, "exec");
     }
+
+    #[test]
+    fn resolve_profile_accepts_core_and_hypervisor_only() {
+        assert_eq!(
+            resolve_profile("core").expect("core").0,
+            ValidationProfile::Core
+        );
+        assert_eq!(
+            resolve_profile("hypervisor").expect("hypervisor").0,
+            ValidationProfile::Hypervisor
+        );
+        assert!(resolve_profile("Core").is_err());
+    }
 }
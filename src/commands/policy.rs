@@ -1,21 +1,1049 @@
-use fct_render::CanonicalPayload;
-
-pub fn hash_chain_seed_input(payload: &CanonicalPayload) -> serde_json::Value {
-    serde_json::json!({
-        "facet_version": payload.metadata.facet_version,
-        "host_profile_id": payload.metadata.host_profile_id,
-        "document_hash": payload.metadata.document_hash,
-        "policy_hash": payload.metadata.policy_hash,
-        "policy_version": payload.metadata.policy_version,
-        "profile": payload.metadata.profile,
-        "mode": payload.metadata.mode,
+//! # Policy Command
+//!
+//! Policy helpers shared by CLI commands, plus `policy explain`: a static
+//! decision table for every OpDesc a contract can produce (§16.6.2), and
+//! `policy eval`: the traced decision for one OpDesc and given variable values.
+
+use crate::commands::host_profile::{load_host_setup, HostSetup};
+use anyhow::{Context, Result};
+use console::style;
+use fct_ast::{BodyNode, FacetDocument, FacetNode, OrderedMap, ScalarValue, ValueNode};
+use fct_compiler::json_to_value_node;
+use fct_engine::{derive_message_section_id, lens_call_input_hash, tool_call_input_hash};
+use fct_parser::parse_document;
+use fct_policy::{
+    fold_input_hash, matcher_matches, policy_rule_id, policy_scope, resolve_policy_var,
+    trace_guard, CondResult, CondTrace, EffectivePolicy, GuardTrace, HostContext, OpDesc, PolicyOp,
+    RuleOutcome, HOST_CONTEXT_ROOT,
+};
+use fct_render::{message_emit_input_hash, policy_hash_for_document, tool_expose_input_hash};
+use fct_resolver::{Resolver, ResolverConfig};
+use fct_std::{LensRegistry, TrustLevel};
+use fct_validator::{FacetType, HostContextSchema, PrimitiveType, TypeChecker};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Static decision table for one OpDesc.
+#[derive(Debug, Serialize)]
+pub struct OperationExplanation {
+    pub op: &'static str,
+    pub name: String,
+    pub effect_class: Option<String>,
+    /// Execution modes in which the guard runs for this OpDesc.
+    pub modes: Vec<&'static str>,
+    /// Possible decisions, in guard evaluation order.
+    pub outcomes: Vec<DecisionOutcome>,
+    /// Variables whose runtime value selects between `outcomes`.
+    pub depends_on: Vec<String>,
+    /// Whether some runtime state makes the guard raise F455.
+    pub undecidable: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DecisionOutcome {
+    pub decision: &'static str,
+    pub error_code: Option<&'static str>,
+    pub policy_rule_id: Option<String>,
+    /// `rule`, `defaults`, or `fallback` (no policy or no op default).
+    pub decided_by: &'static str,
+    /// Conditions under which this outcome is reached; empty means always.
+    pub conditions: Vec<String>,
+}
+
+/// Policy explain command handler
+pub fn execute_policy_explain(
+    input: PathBuf,
+    format: String,
+    host_profile: Option<PathBuf>,
+    host_context: Option<PathBuf>,
+    rate_limiter: &crate::commands::DefaultRateLimiter,
+) -> Result<()> {
+    // Check rate limit
+    if rate_limiter.check().is_err() {
+        warn!("Rate limit exceeded for policy explain command");
+        eprintln!(
+            "{}",
+            style("Rate limit exceeded. Please wait before running another command.").red()
+        );
+        std::process::exit(1);
+    }
+
+    let host = load_host_setup(host_profile.as_deref(), host_context.as_deref())?;
+    let document = load_validated_document(&input, host.as_ref().map(|host| &host.schema))?;
+    let operations = explain_policy(&document, host.as_ref());
+
+    match format.as_str() {
+        "json" => {
+            let view = serde_json::json!({
+                "policy_hash": policy_hash_for_document(&document)?,
+                "operations": operations,
+            });
+            println!("{}", serde_json::to_string_pretty(&view)?);
+        }
+        "text" => print_explanation(&operations),
+        other => {
+            return Err(anyhow::anyhow!(
+                "Unsupported policy explain format '{}': use text or json",
+                other
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Policy eval command handler
+#[allow(clippy::too_many_arguments)]
+pub fn execute_policy_eval(
+    input: PathBuf,
+    op: String,
+    name: String,
+    effect_class: Option<String>,
+    vars: Option<PathBuf>,
+    args: Option<PathBuf>,
+    host_profile_id: String,
+    host_profile: Option<PathBuf>,
+    host_context: Option<PathBuf>,
+    format: String,
+    rate_limiter: &crate::commands::DefaultRateLimiter,
+) -> Result<()> {
+    // Check rate limit
+    if rate_limiter.check().is_err() {
+        warn!("Rate limit exceeded for policy eval command");
+        eprintln!(
+            "{}",
+            style("Rate limit exceeded. Please wait before running another command.").red()
+        );
+        std::process::exit(1);
+    }
+
+    let op = PolicyOp::parse(&op).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Unknown op '{}': use tool_expose, tool_call, lens_call or message_emit",
+            op
+        )
+    })?;
+    let vars = vars
+        .map(|path| -> Result<HashMap<String, ValueNode>> {
+            let serde_json::Value::Object(map) = read_json_file(&path)? else {
+                return Err(anyhow::anyhow!("--vars must contain a JSON object"));
+            };
+            map.iter()
+                .map(|(key, value)| Ok((key.clone(), json_to_value_node(value)?)))
+                .collect()
+        })
+        .transpose()?;
+    let args = match args {
+        Some(path) => read_json_file(&path)?,
+        None => serde_json::Value::Null,
+    };
+
+    let host = load_host_setup(host_profile.as_deref(), host_context.as_deref())?;
+    let document = load_validated_document(&input, host.as_ref().map(|host| &host.schema))?;
+    let (host_profile_id, host_context) = match host {
+        Some(host) => (host.profile_id, host.context),
+        None => (host_profile_id, None),
+    };
+    let evaluation = eval_policy(
+        &document,
+        &EvalRequest {
+            op,
+            name,
+            effect_class,
+            vars,
+            args,
+            host_profile_id,
+            host_context,
+        },
+    )?;
+
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&evaluation)?),
+        "text" => print_evaluation(&evaluation),
+        other => {
+            return Err(anyhow::anyhow!(
+                "Unsupported policy eval format '{}': use text or json",
+                other
+            ))
+        }
+    }
+    Ok(())
+}
+
+fn read_json_file(path: &Path) -> Result<serde_json::Value> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read JSON file: {:?}", path))?;
+    serde_json::from_str(&content).with_context(|| format!("Invalid JSON in {:?}", path))
+}
+
+fn load_validated_document(
+    input: &Path,
+    host_context: Option<&HostContextSchema>,
+) -> Result<FacetDocument> {
+    let source = fs::read_to_string(input)
+        .with_context(|| format!("Failed to read input file: {:?}", input))?;
+
+    let parsed = parse_document(&source).map_err(|e| anyhow::anyhow!("Parse error: {}", e))?;
+
+    let base_dir = input
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or(std::env::current_dir()?);
+    let mut resolver = Resolver::new(ResolverConfig {
+        allowed_roots: vec![base_dir.clone()],
+        base_dir,
+    });
+    let resolved = resolver
+        .resolve(parsed)
+        .map_err(|e| anyhow::anyhow!("Resolution error: {}", e))?;
+
+    let mut checker = TypeChecker::new();
+    if let Some(schema) = host_context {
+        checker = checker.with_host_context(schema.clone());
+    }
+    checker
+        .validate(&resolved)
+        .map_err(|e| anyhow::anyhow!("Validation error: {}", e))?;
+    Ok(resolved)
+}
+
+/// Enumerate every `tool_expose`, `tool_call`, `lens_call` and `message_emit`
+/// OpDesc of a resolved document and derive its possible guard decisions.
+/// `$x.<host>...` conditions are read from `host`'s context when it has one,
+/// and otherwise depend on `$x` at runtime.
+pub fn explain_policy(
+    document: &FacetDocument,
+    host: Option<&HostSetup>,
+) -> Vec<OperationExplanation> {
+    let policy = EffectivePolicy::from_document(document);
+    let analyzer = CondAnalyzer::new(document, host);
+    let mut operations = Vec::new();
+
+    for (name, effect) in exposed_tool_functions(document) {
+        for op in [PolicyOp::ToolExpose, PolicyOp::ToolCall] {
+            operations.push(explain_op(
+                &analyzer,
+                policy.as_ref(),
+                op,
+                name.clone(),
+                effect.clone(),
+                vec!["pure", "exec"],
+            ));
+        }
+    }
+
+    let registry = LensRegistry::new();
+    for lens_name in pipeline_lens_names(document) {
+        let Some(lens) = registry.get(&lens_name) else {
+            continue;
+        };
+        // Mirrors `RDagEngine::should_guard_lens_call`.
+        let modes = match lens.signature().trust_level {
+            TrustLevel::Pure => continue,
+            TrustLevel::Bounded => vec!["exec"],
+            TrustLevel::Volatile => vec!["pure", "exec"],
+        };
+        let effect = lens.effect_class().map(str::to_string);
+        operations.push(explain_op(
+            &analyzer,
+            policy.as_ref(),
+            PolicyOp::LensCall,
+            lens_name,
+            effect,
+            modes,
+        ));
+    }
+
+    for (_, message_id) in message_ids(document) {
+        operations.push(explain_op(
+            &analyzer,
+            policy.as_ref(),
+            PolicyOp::MessageEmit,
+            message_id,
+            None,
+            vec!["pure", "exec"],
+        ));
+    }
+
+    operations
+}
+
+/// One operation to evaluate with `policy eval`.
+#[derive(Debug, Clone)]
+pub struct EvalRequest {
+    pub op: PolicyOp,
+    /// `Interface.fn`, lens name, or message id.
+    pub name: String,
+    /// Overrides the effect class declared by the contract or lens.
+    pub effect_class: Option<String>,
+    /// Variable values for `when`/`unless`; `None` makes variable conditions F455.
+    pub vars: Option<HashMap<String, ValueNode>>,
+    /// Call arguments for the `input_hash`: an object of tool arguments for
+    /// `tool_call`, `{input, args, kwargs}` for `lens_call`; ignored otherwise.
+    pub args: serde_json::Value,
+    pub host_profile_id: String,
+    /// Host context visible as `$x.<host>...` and folded into the `input_hash`.
+    pub host_context: Option<HostContext>,
+}
+
+/// Traced guard decision for one operation.
+#[derive(Debug, Serialize)]
+pub struct PolicyEvaluation {
+    pub op: &'static str,
+    pub name: String,
+    pub effect_class: Option<String>,
+    /// `allowed` or `denied`.
+    pub decision: &'static str,
+    #[serde(flatten)]
+    pub trace: GuardTrace,
+    pub input_hash: String,
+}
+
+/// Evaluate the guard for one OpDesc of a resolved document, as the host would at runtime.
+pub fn eval_policy(document: &FacetDocument, request: &EvalRequest) -> Result<PolicyEvaluation> {
+    let registry = LensRegistry::new();
+    let effect_class = match (&request.effect_class, request.op) {
+        (Some(effect), _) => Some(effect.clone()),
+        (None, PolicyOp::ToolExpose | PolicyOp::ToolCall) => {
+            declared_tool_effect(document, &request.name)
+        }
+        (None, PolicyOp::LensCall) => registry
+            .get(&request.name)
+            .and_then(|lens| lens.effect_class())
+            .map(str::to_string),
+        // message_emit OpDescs carry no effect class.
+        (None, PolicyOp::MessageEmit) => None,
+    };
+
+    let input_hash = match request.op {
+        PolicyOp::ToolExpose => {
+            let interface = request
+                .name
+                .split_once('.')
+                .map_or(request.name.as_str(), |(iface, _)| iface);
+            tool_expose_input_hash(interface, &request.host_profile_id)?
+        }
+        PolicyOp::ToolCall => {
+            let args = match &request.args {
+                serde_json::Value::Null => HashMap::new(),
+                serde_json::Value::Object(map) => map
+                    .iter()
+                    .map(|(key, value)| Ok((key.clone(), json_to_value_node(value)?)))
+                    .collect::<Result<_>>()?,
+                _ => return Err(anyhow::anyhow!("tool_call args must be a JSON object")),
+            };
+            tool_call_input_hash(&request.name, &args, &request.host_profile_id)?
+        }
+        PolicyOp::LensCall => {
+            let field = |key: &str| request.args.get(key).unwrap_or(&serde_json::Value::Null);
+            let input = json_to_value_node(field("input"))?;
+            let args = match field("args") {
+                serde_json::Value::Null => Vec::new(),
+                serde_json::Value::Array(items) => items
+                    .iter()
+                    .map(json_to_value_node)
+                    .collect::<Result<_, _>>()?,
+                _ => return Err(anyhow::anyhow!("lens_call args.args must be a JSON array")),
+            };
+            let mut kwargs = OrderedMap::new();
+            match field("kwargs") {
+                serde_json::Value::Null => {}
+                serde_json::Value::Object(map) => {
+                    for (key, value) in map {
+                        kwargs.insert(key.clone(), json_to_value_node(value)?);
+                    }
+                }
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "lens_call args.kwargs must be a JSON object"
+                    ))
+                }
+            }
+            let version = registry
+                .get(&request.name)
+                .map_or("1", |lens| lens.version());
+            lens_call_input_hash(
+                &request.name,
+                version,
+                &input,
+                &args,
+                &kwargs,
+                &request.host_profile_id,
+            )?
+        }
+        PolicyOp::MessageEmit => {
+            let role = message_ids(document)
+                .into_iter()
+                .find_map(|(role, id)| (id == request.name).then_some(role))
+                .ok_or_else(|| anyhow::anyhow!("Unknown message id '{}'", request.name))?;
+            message_emit_input_hash(&request.name, role, &request.host_profile_id)?
+        }
+    };
+
+    let input_hash = fold_input_hash(input_hash, request.host_context.as_ref())?;
+
+    let policy = EffectivePolicy::from_document(document);
+    let op = OpDesc::new(request.op, request.name.clone(), effect_class.as_deref());
+    let scope = policy_scope(request.vars.as_ref(), request.host_context.as_ref());
+    let trace = trace_guard(policy.as_ref(), &op, scope.as_deref());
+    Ok(PolicyEvaluation {
+        op: request.op.as_str(),
+        name: request.name.clone(),
+        effect_class,
+        decision: trace.decision.decision(),
+        trace,
+        input_hash,
+    })
+}
+
+/// Effect class declared for `Interface.fn` by any `@interface` block.
+fn declared_tool_effect(document: &FacetDocument, name: &str) -> Option<String> {
+    let (interface_name, fn_name) = name.split_once('.')?;
+    document.blocks.iter().find_map(|node| match node {
+        FacetNode::Interface(interface) if interface.name == interface_name => interface
+            .functions
+            .iter()
+            .find(|function| function.name == fn_name)
+            .and_then(|function| function.effect.clone()),
+        _ => None,
     })
 }
 
+/// Interface functions exposed to the model, as `Interface.fn` with their effect class.
+fn exposed_tool_functions(document: &FacetDocument) -> Vec<(String, Option<String>)> {
+    let mut has_tool_refs = false;
+    let mut referenced = HashSet::new();
+    for node in &document.blocks {
+        let FacetNode::System(block) = node else {
+            continue;
+        };
+        for body in &block.body {
+            if let BodyNode::KeyValue(kv) = body {
+                if kv.key == "tools" {
+                    has_tool_refs = true;
+                    if let ValueNode::List(items) = &kv.value {
+                        for item in items {
+                            if let ValueNode::Variable(name) = item {
+                                referenced.insert(name.as_str());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut functions = Vec::new();
+    for node in &document.blocks {
+        let FacetNode::Interface(interface) = node else {
+            continue;
+        };
+        if has_tool_refs && !referenced.contains(interface.name.as_str()) {
+            continue;
+        }
+        for function in &interface.functions {
+            functions.push((
+                format!("{}.{}", interface.name, function.name),
+                function.effect.clone(),
+            ));
+        }
+    }
+    functions
+}
+
+/// Lens names used in any pipeline, in first-use order.
+fn pipeline_lens_names(document: &FacetDocument) -> Vec<String> {
+    fn visit(value: &ValueNode, seen: &mut Vec<String>) {
+        match value {
+            ValueNode::Pipeline(pipeline) => {
+                visit(&pipeline.initial, seen);
+                for lens in &pipeline.lenses {
+                    if !seen.contains(&lens.name) {
+                        seen.push(lens.name.clone());
+                    }
+                    for arg in lens.args.iter().chain(lens.kwargs.values()) {
+                        visit(arg, seen);
+                    }
+                }
+            }
+            ValueNode::List(items) => items.iter().for_each(|item| visit(item, seen)),
+            ValueNode::Map(map) => map.values().for_each(|item| visit(item, seen)),
+            ValueNode::Directive(directive) => {
+                directive.args.values().for_each(|item| visit(item, seen))
+            }
+            _ => {}
+        }
+    }
+
+    let mut names = Vec::new();
+    for node in &document.blocks {
+        let block = match node {
+            FacetNode::Vars(block)
+            | FacetNode::Context(block)
+            | FacetNode::System(block)
+            | FacetNode::User(block)
+            | FacetNode::Assistant(block) => block,
+            _ => continue,
+        };
+        for body in &block.body {
+            match body {
+                BodyNode::KeyValue(kv) => visit(&kv.value, &mut names),
+                BodyNode::ListItem(item) => visit(&item.value, &mut names),
+            }
+        }
+    }
+    names
+}
+
+/// Message roles and section ids as rendered: explicit `id`, else `role#ordinal`.
+fn message_ids(document: &FacetDocument) -> Vec<(&'static str, String)> {
+    let mut counts = [0usize; 3];
+    let mut ids = Vec::new();
+    for node in &document.blocks {
+        let (role, slot, block) = match node {
+            FacetNode::System(block) => ("system", 0, block),
+            FacetNode::User(block) => ("user", 1, block),
+            FacetNode::Assistant(block) => ("assistant", 2, block),
+            _ => continue,
+        };
+        counts[slot] += 1;
+        let explicit = block.body.iter().find_map(|body| match body {
+            BodyNode::KeyValue(kv) if kv.key == "id" => match &kv.value {
+                ValueNode::String(id) => Some(id.clone()),
+                _ => None,
+            },
+            _ => None,
+        });
+        ids.push((
+            role,
+            explicit.unwrap_or_else(|| derive_message_section_id(role, counts[slot])),
+        ));
+    }
+    ids
+}
+
+/// Static value of a PolicyCond or rule match.
+#[derive(Debug, Clone, PartialEq)]
+enum Tri {
+    True,
+    False,
+    /// Decided at runtime by `vars`; `may_fail` when some values make it F455.
+    Dynamic {
+        vars: BTreeSet<String>,
+        may_fail: bool,
+    },
+    Undecidable,
+}
+
+impl Tri {
+    fn dynamic(vars: BTreeSet<String>, may_fail: bool) -> Self {
+        Tri::Dynamic { vars, may_fail }
+    }
+
+    /// Join two runtime-dependent branches into one dynamic value.
+    fn merge(self, other: Tri) -> Tri {
+        let (mut vars, mut may_fail) = match self {
+            Tri::Dynamic { vars, may_fail } => (vars, may_fail),
+            Tri::Undecidable => (BTreeSet::new(), true),
+            _ => (BTreeSet::new(), false),
+        };
+        match other {
+            Tri::Dynamic {
+                vars: more,
+                may_fail: fails,
+            } => {
+                vars.extend(more);
+                may_fail |= fails;
+            }
+            Tri::Undecidable => may_fail = true,
+            _ => {}
+        }
+        Tri::dynamic(vars, may_fail)
+    }
+}
+
+/// Evaluates PolicyCond atoms against what is statically known about `@vars`
+/// and the host context.
+struct CondAnalyzer<'a> {
+    vars: OrderedMap<String, &'a ValueNode>,
+    host: Option<&'a HostSetup>,
+}
+
+impl<'a> CondAnalyzer<'a> {
+    fn new(document: &'a FacetDocument, host: Option<&'a HostSetup>) -> Self {
+        let mut vars = OrderedMap::new();
+        for node in &document.blocks {
+            if let FacetNode::Vars(block) = node {
+                for body in &block.body {
+                    if let BodyNode::KeyValue(kv) = body {
+                        vars.insert(kv.key.clone(), &kv.value);
+                    }
+                }
+            }
+        }
+        Self { vars, host }
+    }
+
+    fn var_atom(&self, var_ref: &str) -> Tri {
+        let mut segments = var_ref.split('.');
+        let base = segments.next().unwrap_or(var_ref);
+        let Some(mut value) = self.vars.get(base).copied() else {
+            if base == HOST_CONTEXT_ROOT {
+                return self.host_atom(var_ref);
+            }
+            return Tri::Undecidable;
+        };
+        for segment in segments {
+            value = match value {
+                ValueNode::Map(map) => match map.get(segment) {
+                    Some(next) => next,
+                    None => return Tri::Undecidable,
+                },
+                ValueNode::Scalar(_) | ValueNode::String(_) | ValueNode::List(_) => {
+                    return Tri::Undecidable
+                }
+                _ => return Tri::dynamic(BTreeSet::from([base.to_string()]), true),
+            };
+        }
+        match value {
+            ValueNode::Scalar(ScalarValue::Bool(true)) => Tri::True,
+            ValueNode::Scalar(ScalarValue::Bool(false)) => Tri::False,
+            ValueNode::Scalar(_)
+            | ValueNode::String(_)
+            | ValueNode::List(_)
+            | ValueNode::Map(_) => Tri::Undecidable,
+            ValueNode::Directive(d) if d.name == "input" => {
+                let typed_bool =
+                    matches!(d.args.get("type"), Some(ValueNode::String(t)) if t.trim() == "bool");
+                Tri::dynamic(BTreeSet::from([base.to_string()]), !typed_bool)
+            }
+            _ => Tri::dynamic(BTreeSet::from([base.to_string()]), true),
+        }
+    }
+
+    /// A `$x.<host>...` path: its value in the supplied host context, or else
+    /// decided by `$x` at runtime, and F455-free only when declared `bool`.
+    fn host_atom(&self, var_ref: &str) -> Tri {
+        let host = self.host;
+        if let Some(context) = host.and_then(HostSetup::context) {
+            let scope = HashMap::from([(HOST_CONTEXT_ROOT.to_string(), context.value().clone())]);
+            return match resolve_policy_var(var_ref, &scope) {
+                Ok(ValueNode::Scalar(ScalarValue::Bool(true))) => Tri::True,
+                Ok(ValueNode::Scalar(ScalarValue::Bool(false))) => Tri::False,
+                _ => Tri::Undecidable,
+            };
+        }
+        let declared_bool = host
+            .and_then(|host| host.schema.resolve(var_ref))
+            .is_some_and(|ty| *ty == FacetType::Primitive(PrimitiveType::Bool));
+        Tri::dynamic(
+            BTreeSet::from([HOST_CONTEXT_ROOT.to_string()]),
+            !declared_bool,
+        )
+    }
+
+    /// Three-valued mirror of the runtime `eval_policy_cond`, including short-circuiting.
+    fn cond(&self, cond: &ValueNode) -> Tri {
+        match cond {
+            ValueNode::Scalar(ScalarValue::Bool(true)) => Tri::True,
+            ValueNode::Scalar(ScalarValue::Bool(false)) => Tri::False,
+            ValueNode::Variable(var_ref) => self.var_atom(var_ref),
+            ValueNode::Map(map) if map.len() == 1 => {
+                let Some((op, arg)) = map.iter().next() else {
+                    return Tri::Undecidable;
+                };
+                let items = match (op.as_str(), arg) {
+                    ("not", inner) => {
+                        return match self.cond(inner) {
+                            Tri::True => Tri::False,
+                            Tri::False => Tri::True,
+                            other => other,
+                        }
+                    }
+                    ("all" | "any", ValueNode::List(items)) if !items.is_empty() => items,
+                    _ => return Tri::Undecidable,
+                };
+                // `all` stops at the first false, `any` at the first true.
+                let stop = if op == "all" { Tri::False } else { Tri::True };
+                let mut pending: Option<Tri> = None;
+                for item in items {
+                    let value = self.cond(item);
+                    match (&value, pending.take()) {
+                        (v, None) if *v == stop => return stop,
+                        (Tri::Undecidable, None) => return Tri::Undecidable,
+                        (Tri::Dynamic { .. }, None) => pending = Some(value),
+                        (_, None) => {}
+                        // A later definite stop value settles the result unless an
+                        // earlier runtime-dependent item can fail first.
+                        (v, Some(prev)) if *v == stop => {
+                            return match prev {
+                                Tri::Dynamic { may_fail: true, .. } => prev,
+                                _ => stop,
+                            }
+                        }
+                        (_, Some(prev)) => pending = Some(prev.merge(value)),
+                    }
+                }
+                pending.unwrap_or(if op == "all" { Tri::True } else { Tri::False })
+            }
+            _ => Tri::Undecidable,
+        }
+    }
+
+    /// Static match of one PolicyRule against an OpDesc, mirroring the runtime matchers.
+    fn rule(&self, rule: &ValueNode, op: PolicyOp, name: &str, effect: Option<&str>) -> Tri {
+        let ValueNode::Map(map) = rule else {
+            return Tri::False;
+        };
+        if !matches!(map.get("op"), Some(ValueNode::String(rule_op)) if rule_op == op.as_str()) {
+            return Tri::False;
+        }
+        match map.get("name") {
+            Some(ValueNode::String(pattern)) if !matcher_matches(pattern, name) => {
+                return Tri::False
+            }
+            Some(ValueNode::String(_)) | None => {}
+            Some(_) => return Tri::Undecidable,
+        }
+        match map.get("effect") {
+            None => {}
+            Some(ValueNode::String(pattern)) => match effect {
+                Some(effect) if matcher_matches(pattern, effect) => {}
+                _ => return Tri::False,
+            },
+            Some(_) => return Tri::Undecidable,
+        }
+
+        let when = map.get("when").map_or(Tri::True, |cond| self.cond(cond));
+        if matches!(when, Tri::False | Tri::Undecidable) {
+            return when;
+        }
+        let unless = map.get("unless").map_or(Tri::False, |cond| self.cond(cond));
+        match (when, unless) {
+            (_, Tri::True) => Tri::False,
+            (Tri::True, Tri::False) => Tri::True,
+            (Tri::True, Tri::Undecidable) => Tri::Undecidable,
+            (when, unless) => when.merge(unless),
+        }
+    }
+}
+
+fn explain_op(
+    analyzer: &CondAnalyzer<'_>,
+    policy: Option<&EffectivePolicy>,
+    op: PolicyOp,
+    name: String,
+    effect_class: Option<String>,
+    modes: Vec<&'static str>,
+) -> OperationExplanation {
+    let mut outcomes = Vec::new();
+    let mut depends_on = BTreeSet::new();
+
+    match policy {
+        None => {
+            // tool_expose and message_emit are open without a policy; calls fail closed.
+            outcomes.push(fixed_outcome(
+                op.fallback_allows(),
+                None,
+                "fallback",
+                Vec::new(),
+            ));
+        }
+        Some(policy) => {
+            let mut assumptions = Vec::new();
+            let mut decided = false;
+            'lists: for (list, allowed) in [("deny", false), ("allow", true)] {
+                for (index, rule) in policy.rules(list).iter().enumerate() {
+                    let rule_id = policy_rule_id(rule).map(str::to_string);
+                    let label = match &rule_id {
+                        Some(id) => format!("{} rule '{}'", list, id),
+                        None => format!("{} rule #{}", list, index),
+                    };
+                    match analyzer.rule(rule, op, &name, effect_class.as_deref()) {
+                        Tri::False => {}
+                        Tri::True => {
+                            outcomes.push(fixed_outcome(
+                                allowed,
+                                rule_id,
+                                "rule",
+                                assumptions.clone(),
+                            ));
+                            decided = true;
+                            break 'lists;
+                        }
+                        Tri::Undecidable => {
+                            outcomes.push(undecidable_outcome(rule_id, assumptions.clone()));
+                            decided = true;
+                            break 'lists;
+                        }
+                        Tri::Dynamic { vars, may_fail } => {
+                            let vars_label = vars
+                                .iter()
+                                .map(|v| format!("${}", v))
+                                .collect::<Vec<_>>()
+                                .join(", ");
+                            let mut matched = assumptions.clone();
+                            matched.push(format!("{} matches ({})", label, vars_label));
+                            outcomes.push(fixed_outcome(allowed, rule_id.clone(), "rule", matched));
+                            if may_fail {
+                                let mut failed = assumptions.clone();
+                                failed.push(format!(
+                                    "{} condition is not boolean ({})",
+                                    label, vars_label
+                                ));
+                                outcomes.push(undecidable_outcome(rule_id, failed));
+                            }
+                            assumptions.push(format!("{} does not match", label));
+                            depends_on.extend(vars);
+                        }
+                    }
+                }
+            }
+            if !decided {
+                outcomes.push(default_outcome(policy, op, assumptions));
+            }
+        }
+    }
+
+    let undecidable = outcomes.iter().any(|o| o.error_code == Some("F455"));
+    OperationExplanation {
+        op: op.as_str(),
+        name,
+        effect_class,
+        modes,
+        outcomes,
+        depends_on: depends_on.into_iter().collect(),
+        undecidable,
+    }
+}
+
+fn fixed_outcome(
+    allowed: bool,
+    policy_rule_id: Option<String>,
+    decided_by: &'static str,
+    conditions: Vec<String>,
+) -> DecisionOutcome {
+    DecisionOutcome {
+        decision: if allowed { "allowed" } else { "denied" },
+        error_code: if allowed { None } else { Some("F454") },
+        policy_rule_id,
+        decided_by,
+        conditions,
+    }
+}
+
+fn undecidable_outcome(policy_rule_id: Option<String>, conditions: Vec<String>) -> DecisionOutcome {
+    DecisionOutcome {
+        decision: "denied",
+        error_code: Some("F455"),
+        policy_rule_id,
+        decided_by: "rule",
+        conditions,
+    }
+}
+
+/// Mirrors `EffectivePolicy::evaluate`: `defaults.<op>` first, then the per-op fallback.
+fn default_outcome(
+    policy: &EffectivePolicy,
+    op: PolicyOp,
+    conditions: Vec<String>,
+) -> DecisionOutcome {
+    let op_default = match policy.get("defaults") {
+        None => None,
+        Some(ValueNode::Map(defaults)) => defaults.get(op.as_str()),
+        Some(_) => {
+            return DecisionOutcome {
+                decided_by: "defaults",
+                ..undecidable_outcome(None, conditions)
+            }
+        }
+    };
+    match op_default {
+        None => fixed_outcome(op.fallback_allows(), None, "fallback", conditions),
+        Some(ValueNode::String(s)) if s == "allow" => {
+            fixed_outcome(true, None, "defaults", conditions)
+        }
+        Some(ValueNode::Scalar(ScalarValue::Bool(true))) => {
+            fixed_outcome(true, None, "defaults", conditions)
+        }
+        Some(ValueNode::String(s)) if s == "deny" => {
+            fixed_outcome(false, None, "defaults", conditions)
+        }
+        Some(ValueNode::Scalar(ScalarValue::Bool(false))) => {
+            fixed_outcome(false, None, "defaults", conditions)
+        }
+        Some(_) => DecisionOutcome {
+            decided_by: "defaults",
+            ..undecidable_outcome(None, conditions)
+        },
+    }
+}
+
+fn print_explanation(operations: &[OperationExplanation]) {
+    if operations.is_empty() {
+        println!("No guarded operations in this document.");
+        return;
+    }
+    for operation in operations {
+        let effect = operation
+            .effect_class
+            .as_deref()
+            .map(|e| format!(" [effect: {}]", e))
+            .unwrap_or_default();
+        println!(
+            "{} {}{} (modes: {})",
+            style(operation.op).bold(),
+            operation.name,
+            effect,
+            operation.modes.join(", ")
+        );
+        for outcome in &operation.outcomes {
+            let decision = match outcome.error_code {
+                Some("F455") => style("undecidable (F455)".to_string()).red(),
+                Some(code) => style(format!("denied ({})", code)).yellow(),
+                None => style("allowed".to_string()).green(),
+            };
+            let source = match &outcome.policy_rule_id {
+                Some(id) => format!("rule '{}'", id),
+                None => outcome.decided_by.to_string(),
+            };
+            let conditions = if outcome.conditions.is_empty() {
+                "always".to_string()
+            } else {
+                format!("if {}", outcome.conditions.join(" and "))
+            };
+            println!("  -> {} by {} {}", decision, source, conditions);
+        }
+    }
+}
+
+fn print_evaluation(evaluation: &PolicyEvaluation) {
+    let effect = evaluation
+        .effect_class
+        .as_deref()
+        .map(|e| format!(" [effect: {}]", e))
+        .unwrap_or_default();
+    println!(
+        "{} {}{}",
+        style(evaluation.op).bold(),
+        evaluation.name,
+        effect
+    );
+
+    let trace = &evaluation.trace;
+    let decision = match trace.decision.error_code.as_deref() {
+        Some(code) if trace.decision.is_undecidable() => {
+            style(format!("undecidable ({})", code)).red()
+        }
+        Some(code) => style(format!("denied ({})", code)).yellow(),
+        None => style("allowed".to_string()).green(),
+    };
+    let source = match &trace.decision.policy_rule_id {
+        Some(id) => format!("rule '{}'", id),
+        None => trace.decided_by.to_string(),
+    };
+    println!("decision: {} by {}", decision, source);
+    println!("input_hash: {}", evaluation.input_hash);
+
+    for rule in &trace.rules {
+        let id = rule
+            .policy_rule_id
+            .as_deref()
+            .map(|id| format!(" '{}'", id))
+            .unwrap_or_default();
+        let outcome = match rule.outcome {
+            RuleOutcome::Matched => "matched",
+            RuleOutcome::NoMatch => "no match",
+            RuleOutcome::Undecidable => "undecidable",
+        };
+        let reason = rule
+            .reason
+            .as_deref()
+            .map(|r| format!(": {}", r))
+            .unwrap_or_default();
+        println!(
+            "  {}[{}]{} {}{}",
+            rule.list, rule.index, id, outcome, reason
+        );
+        for (clause, cond) in [("when", &rule.when), ("unless", &rule.unless)] {
+            if let Some(cond) = cond {
+                print_cond(&format!("{}: ", clause), cond, 4);
+            }
+        }
+    }
+}
+
+fn print_cond(label: &str, cond: &CondTrace, indent: usize) {
+    let result = match cond.result {
+        CondResult::True => "true",
+        CondResult::False => "false",
+        CondResult::Error => "error",
+        CondResult::Skipped => "skipped",
+    };
+    let error = cond
+        .error
+        .as_deref()
+        .map(|e| format!(" ({})", e))
+        .unwrap_or_default();
+    println!(
+        "{:indent$}{}{} = {}{}",
+        "",
+        label,
+        cond.expr,
+        result,
+        error,
+        indent = indent
+    );
+    for operand in &cond.operands {
+        print_cond("", operand, indent + 2);
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::hash_chain_seed_input;
+    use super::{eval_policy, explain_policy, EvalRequest, HostSetup, OperationExplanation};
+    use fct_ast::{ScalarValue, ValueNode};
+    use fct_compiler::{hash_chain_seed_input, json_to_value_node};
+    use fct_engine::{ToolExecutor, ToolInvocation};
+    use fct_parser::parse_document;
+    use fct_policy::{CondResult, EffectivePolicy, HostContext, PolicyOp, RuleOutcome};
     use fct_render::{CanonicalPayload, Metadata};
+    use fct_validator::HostContextSchema;
+    use std::collections::HashMap;
+
+    const PAYMENTS: &str = r#"@interface Payments
+  fn charge(amount: int) -> string (effect="payment")
+@vars
+  trusted: @input(type="bool", default=false)
+@policy
+  deny: [{ id: "no-pay", op: "tool_call", name: "Payments.*", effect: "payment", unless: $trusted }]
+@user
+  content: "hi"
+"#;
+
+    fn eval_request(op: PolicyOp, name: &str, args: serde_json::Value) -> EvalRequest {
+        EvalRequest {
+            op,
+            name: name.to_string(),
+            effect_class: None,
+            vars: None,
+            args,
+            host_profile_id: "local.default.v1".to_string(),
+            host_context: None,
+        }
+    }
+
+    fn explain(source: &str) -> Vec<OperationExplanation> {
+        explain_policy(&parse_document(source).expect("source should parse"), None)
+    }
+
+    fn find<'a>(ops: &'a [OperationExplanation], op: &str, name: &str) -> &'a OperationExplanation {
+        ops.iter()
+            .find(|o| o.op == op && o.name == name)
+            .unwrap_or_else(|| panic!("missing {} {}", op, name))
+    }
 
     #[test]
     fn hash_chain_seed_contains_required_policy_fields() {
@@ -30,6 +1058,8 @@ mod tests {
                 policy_hash: Some("sha256:def".to_string()),
                 budget_units: 1,
                 target_provider_id: "generic-llm".to_string(),
+                reserved_tool_units: 0,
+                reserved_output_units: 0,
             },
             tools: Vec::new(),
             messages: Vec::new(),
@@ -41,4 +1071,250 @@ mod tests {
         assert_eq!(seed.get("profile").unwrap(), "hypervisor");
         assert_eq!(seed.get("mode").unwrap(), "exec");
     }
+
+    #[test]
+    fn explain_without_policy_uses_per_op_fallbacks() {
+        let ops = explain(
+            "@interface Weather\n  fn get(city: string) -> string (effect=\"read\")\n@vars\n  answer: \"q\" |> llm_call()\n@user\n  content: $answer\n",
+        );
+        let expose = find(&ops, "tool_expose", "Weather.get");
+        assert_eq!(expose.outcomes[0].decision, "allowed");
+        assert_eq!(expose.outcomes[0].decided_by, "fallback");
+
+        let call = find(&ops, "tool_call", "Weather.get");
+        assert_eq!(call.outcomes[0].error_code, Some("F454"));
+
+        let lens = find(&ops, "lens_call", "llm_call");
+        assert_eq!(lens.modes, vec!["exec"]);
+        assert_eq!(lens.effect_class.as_deref(), Some("external"));
+        assert_eq!(lens.outcomes[0].decision, "denied");
+
+        assert_eq!(find(&ops, "message_emit", "user#1").outcomes.len(), 1);
+        assert!(!ops
+            .iter()
+            .any(|o| o.op == "lens_call" && o.name != "llm_call"));
+    }
+
+    #[test]
+    fn explain_lists_conditional_outcomes_and_dependencies() {
+        let ops = explain(
+            r#"@interface Weather
+  fn set(city: string) -> string (effect="write")
+@vars
+  strict: @input(type="bool", default=false)
+@policy
+  deny: [{ id: "no-write", op: "tool_call", name: "Weather.*", effect: "write", when: $strict }]
+  allow: [{ id: "tools", op: "tool_call", name: "Weather.*" }]
+"#,
+        );
+        let call = find(&ops, "tool_call", "Weather.set");
+        assert_eq!(call.depends_on, vec!["strict".to_string()]);
+        assert!(!call.undecidable);
+        assert_eq!(call.outcomes.len(), 2);
+        assert_eq!(call.outcomes[0].policy_rule_id.as_deref(), Some("no-write"));
+        assert_eq!(call.outcomes[0].decision, "denied");
+        assert_eq!(call.outcomes[1].policy_rule_id.as_deref(), Some("tools"));
+        assert_eq!(
+            call.outcomes[1].conditions,
+            vec!["deny rule 'no-write' does not match".to_string()]
+        );
+    }
+
+    #[test]
+    fn explain_flags_undecidable_conditions() {
+        let ops = explain(
+            r#"@vars
+  mode: @input(type="string")
+  flag: "yes"
+@policy
+  deny: [{ id: "by-mode", op: "message_emit", when: $mode }]
+  allow: [{ id: "static", op: "message_emit", name: "user#1", when: { all: [$flag] } }]
+@user
+  content: "hi"
+"#,
+        );
+        let emit = find(&ops, "message_emit", "user#1");
+        assert!(emit.undecidable);
+        let codes: Vec<_> = emit.outcomes.iter().map(|o| o.error_code).collect();
+        // by-mode may match, may fail on a non-bool input, and otherwise the
+        // allow rule always fails on the string literal.
+        assert_eq!(codes, vec![Some("F454"), Some("F455"), Some("F455")]);
+        assert_eq!(emit.outcomes[2].policy_rule_id.as_deref(), Some("static"));
+    }
+
+    #[test]
+    fn explain_decides_host_context_conditions_from_the_host_profile() {
+        let document = parse_document(
+            r#"@interface Payments
+  fn charge(amount: int) -> string (effect="payment")
+@policy
+  allow: [{ id: "admins", op: "tool_call", name: "Payments.*", when: $x.acme.admin }]
+"#,
+        )
+        .unwrap();
+        let mut host = HostSetup {
+            profile_id: "acme.v1".to_string(),
+            schema: HostContextSchema::parse([("acme", "struct { admin: bool }")]).unwrap(),
+            context: None,
+        };
+
+        let ops = explain_policy(&document, Some(&host));
+        let call = find(&ops, "tool_call", "Payments.charge");
+        assert_eq!(call.depends_on, vec!["x".to_string()]);
+        assert!(!call.undecidable);
+        assert_eq!(call.outcomes[0].policy_rule_id.as_deref(), Some("admins"));
+        assert_eq!(call.outcomes[0].decision, "allowed");
+
+        // Without the declared types the same condition may not be a bool.
+        let ops = explain_policy(&document, None);
+        assert!(find(&ops, "tool_call", "Payments.charge").undecidable);
+
+        let context = serde_json::json!({ "acme": { "admin": false } });
+        host.context = Some(HostContext::new(json_to_value_node(&context).unwrap()).unwrap());
+        let ops = explain_policy(&document, Some(&host));
+        let call = find(&ops, "tool_call", "Payments.charge");
+        assert!(call.depends_on.is_empty());
+        assert_eq!(call.outcomes.len(), 1);
+        assert_eq!(call.outcomes[0].decision, "denied");
+    }
+
+    #[test]
+    fn eval_reproduces_the_runtime_tool_call_decision() {
+        let document = parse_document(PAYMENTS).unwrap();
+        let vars = HashMap::from([(
+            "trusted".to_string(),
+            ValueNode::Scalar(ScalarValue::Bool(false)),
+        )]);
+        let mut request = eval_request(
+            PolicyOp::ToolCall,
+            "Payments.charge",
+            serde_json::json!({ "amount": 5 }),
+        );
+        request.vars = Some(vars.clone());
+
+        let evaluation = eval_policy(&document, &request).unwrap();
+        assert_eq!(evaluation.effect_class.as_deref(), Some("payment"));
+        assert_eq!(evaluation.decision, "denied");
+        assert_eq!(evaluation.trace.decided_by, "rule");
+        let rule = &evaluation.trace.rules[0];
+        assert_eq!(rule.outcome, RuleOutcome::Matched);
+        assert_eq!(
+            rule.unless.as_ref().map(|c| (c.expr.as_str(), c.result)),
+            Some(("$trusted", CondResult::False))
+        );
+
+        let runtime = ToolExecutor::new()
+            .evaluate_tool_call_guard(
+                &ToolInvocation {
+                    tool_name: "Payments.charge".to_string(),
+                    arguments: HashMap::from([(
+                        "amount".to_string(),
+                        ValueNode::Scalar(ScalarValue::Int(5)),
+                    )]),
+                    invocation_id: None,
+                },
+                EffectivePolicy::from_document(&document).as_ref(),
+                Some(&vars),
+                "exec",
+                "local.default.v1",
+                Some("payment"),
+            )
+            .unwrap();
+        assert_eq!(evaluation.input_hash, runtime.input_hash);
+        assert_eq!(
+            evaluation.trace.decision.policy_rule_id,
+            runtime.policy_rule_id
+        );
+        assert_eq!(evaluation.trace.decision.error_code, runtime.error_code);
+    }
+
+    #[test]
+    fn eval_without_vars_is_undecidable_and_effect_can_be_overridden() {
+        let document = parse_document(PAYMENTS).unwrap();
+        let request = eval_request(
+            PolicyOp::ToolCall,
+            "Payments.charge",
+            serde_json::Value::Null,
+        );
+        let evaluation = eval_policy(&document, &request).unwrap();
+        assert!(evaluation.trace.decision.is_undecidable());
+
+        let mut request = request;
+        request.effect_class = Some("read".to_string());
+        let evaluation = eval_policy(&document, &request).unwrap();
+        assert_eq!(evaluation.trace.decided_by, "fallback");
+        assert_eq!(
+            evaluation.trace.rules[0].reason.as_deref(),
+            Some("effect 'payment' does not match 'read'")
+        );
+    }
+
+    #[test]
+    fn eval_message_emit_requires_a_known_message_id() {
+        let document = parse_document(PAYMENTS).unwrap();
+        let known = eval_request(PolicyOp::MessageEmit, "user#1", serde_json::Value::Null);
+        assert_eq!(eval_policy(&document, &known).unwrap().decision, "allowed");
+
+        let unknown = eval_request(PolicyOp::MessageEmit, "user#2", serde_json::Value::Null);
+        assert!(eval_policy(&document, &unknown)
+            .unwrap_err()
+            .to_string()
+            .contains("Unknown message id 'user#2'"));
+    }
+
+    #[test]
+    fn eval_sees_the_host_context_and_folds_it_into_the_input_hash() {
+        let document = parse_document(
+            r#"@interface Payments
+  fn charge(amount: int) -> string (effect="payment")
+@policy
+  allow: [{ id: "admins", op: "tool_call", name: "Payments.*", when: $x.acme.principal.admin }]
+"#,
+        )
+        .unwrap();
+        let host_context = |admin: bool| {
+            let context = serde_json::json!({ "acme": { "principal": { "admin": admin } } });
+            HostContext::new(json_to_value_node(&context).unwrap()).unwrap()
+        };
+
+        let mut request = eval_request(
+            PolicyOp::ToolCall,
+            "Payments.charge",
+            serde_json::Value::Null,
+        );
+        let anonymous = eval_policy(&document, &request).unwrap();
+        assert!(anonymous.trace.decision.is_undecidable());
+
+        request.host_context = Some(host_context(true));
+        let admin = eval_policy(&document, &request).unwrap();
+        assert_eq!(admin.decision, "allowed");
+        assert_eq!(
+            admin.trace.decision.policy_rule_id.as_deref(),
+            Some("admins")
+        );
+        assert_ne!(admin.input_hash, anonymous.input_hash);
+
+        let runtime = ToolExecutor::new()
+            .with_host_context(host_context(true))
+            .evaluate_tool_call_guard(
+                &ToolInvocation {
+                    tool_name: "Payments.charge".to_string(),
+                    arguments: HashMap::new(),
+                    invocation_id: None,
+                },
+                EffectivePolicy::from_document(&document).as_ref(),
+                None,
+                "exec",
+                "local.default.v1",
+                Some("payment"),
+            )
+            .unwrap();
+        assert_eq!(admin.input_hash, runtime.input_hash);
+        assert_eq!(runtime.decision, "allowed");
+
+        request.host_context = Some(host_context(false));
+        let user = eval_policy(&document, &request).unwrap();
+        assert_eq!(user.decision, "denied");
+        assert_ne!(user.input_hash, admin.input_hash);
+    }
 }
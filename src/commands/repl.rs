@@ -0,0 +1,460 @@
+//! # REPL Command
+//!
+//! This module implements `repl`, an interactive loop over one contract. It
+//! computes the document's `@vars` once (Phases 1-3), then evaluates value
+//! expressions typed at the prompt against them with the same compiler
+//! session, validator and R-DAG engine `run` uses.
+//!
+//! Input lines:
+//!
+//! - `<expression>` → value, inferred type and gas, e.g. `$docs |> map(field="title") |> join(", ")`
+//! - `:vars` → every `@vars` entry with its type and value
+//! - `:type <expression>` → inferred type, without evaluating
+//! - `:gas` → gas used so far against the context budget
+//! - `:layout [budget]` → Token Box Model allocation of the messages
+//! - `:help`, `:quit`
+//!
+//! Errors are printed and the loop continues; expressions never change `@vars`.
+
+use crate::commands::host_profile::load_host_setup;
+use crate::commands::mode_profile::resolve_execution_mode;
+use anyhow::{Context, Result};
+use console::style;
+use fct_ast::{BodyNode, FacetDocument, FacetNode, OrderedMap, ValueNode};
+use fct_compiler::{load_runtime_inputs, Compiler, Execution, Session};
+use fct_engine::{value_node_to_json, AllocationResult, GasContext};
+use fct_parser::parse_value_expression;
+use fct_validator::{FacetType, PrimitiveType, TypeChecker};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+const PROMPT: &str = "fct> ";
+
+const HELP: &str = "\
+<expression>       evaluate, e.g. $docs |> map(field=\"title\") |> join(\", \")
+:vars              list @vars with their types and values
+:type <expression> infer the type without evaluating
+:gas               gas used so far
+:layout [budget]   lay out the messages at budget (default --budget)
+:help              show this help
+:quit              leave the repl";
+
+/// REPL command handler
+#[allow(clippy::too_many_arguments)]
+pub fn execute_repl(
+    input: PathBuf,
+    runtime_input: Option<PathBuf>,
+    budget: usize,
+    context_budget: usize,
+    pure: bool,
+    exec: bool,
+    host_profile: Option<PathBuf>,
+    host_context: Option<PathBuf>,
+    rate_limiter: &crate::commands::DefaultRateLimiter,
+) -> Result<()> {
+    // Check rate limit
+    if rate_limiter.check().is_err() {
+        warn!("Rate limit exceeded for repl command");
+        eprintln!(
+            "{}",
+            style("Rate limit exceeded. Please wait before running another command.").red()
+        );
+        std::process::exit(1);
+    }
+
+    let (execution_mode, _) = resolve_execution_mode(pure, exec)?;
+    let host = load_host_setup(host_profile.as_deref(), host_context.as_deref())?;
+    let runtime_inputs = match runtime_input {
+        Some(path) => load_runtime_inputs(&path)?,
+        None => HashMap::new(),
+    };
+    let mut builder = Compiler::builder()
+        .mode(execution_mode)
+        .budget(budget)
+        .context_budget(context_budget);
+    if let Some(host) = &host {
+        builder = host.configure(builder);
+    }
+
+    let source = fs::read_to_string(&input)
+        .with_context(|| format!("Failed to read input file: {:?}", input))?;
+    let base_dir = input
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or(std::env::current_dir()?);
+    let mut repl = Repl::load(&builder.build(), &source, &base_dir, runtime_inputs)?;
+
+    println!(
+        "{} {} ({} vars, gas {}). Type :help for commands.",
+        style("Loaded").green(),
+        input.display(),
+        repl.var_names().len(),
+        repl.gas().consumed
+    );
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    serve(&mut repl, stdin.lock(), stdout.lock())
+}
+
+/// Prompt, read and answer lines from `reader` until `:quit` or end of input.
+pub fn serve<R: BufRead, W: Write>(repl: &mut Repl, reader: R, mut writer: W) -> Result<()> {
+    let mut lines = reader.lines();
+    loop {
+        write!(writer, "{}", PROMPT)?;
+        writer.flush()?;
+        let Some(line) = lines.next() else {
+            writeln!(writer)?;
+            return Ok(());
+        };
+        let line = line.context("Failed to read repl input")?;
+        match repl.handle_line(&line) {
+            Some(output) if output.is_empty() => {}
+            Some(output) => writeln!(writer, "{}", output)?,
+            None => return Ok(()),
+        }
+    }
+}
+
+/// A contract whose `@vars` are computed, ready to evaluate expressions.
+pub struct Repl {
+    session: Session,
+    execution: Execution,
+}
+
+impl Repl {
+    /// Phases 1-3 for `source`: resolve, type check and compute `@vars`.
+    pub fn load(
+        compiler: &Compiler,
+        source: &str,
+        base_dir: &Path,
+        runtime_inputs: HashMap<String, ValueNode>,
+    ) -> Result<Self> {
+        let session = compiler.load_source(source, base_dir)?;
+        let execution = session.execute(runtime_inputs)?;
+        Ok(Self { session, execution })
+    }
+
+    /// Answer one input line. `None` ends the session.
+    pub fn handle_line(&mut self, line: &str) -> Option<String> {
+        let line = line.trim();
+        let (command, rest) = match line.split_once(char::is_whitespace) {
+            Some((command, rest)) => (command, rest.trim()),
+            None => (line, ""),
+        };
+        let output = match command {
+            "" => Ok(String::new()),
+            ":quit" | ":q" | ":exit" => return None,
+            ":help" => Ok(HELP.to_string()),
+            ":vars" => Ok(self.describe_vars()),
+            ":gas" => Ok(format!(
+                "gas: {} / {}",
+                self.gas().consumed,
+                self.gas().limit
+            )),
+            ":type" => self.expression_type(rest).map(|ty| ty.to_string()),
+            ":layout" => self.layout(rest),
+            _ if command.starts_with(':') => Err(anyhow::anyhow!(
+                "Unknown command '{}'; type :help for commands",
+                command
+            )),
+            _ => self.evaluate(line),
+        };
+        Some(output.unwrap_or_else(|e| format!("error: {}", e)))
+    }
+
+    fn gas(&self) -> &GasContext {
+        &self.execution.context.gas
+    }
+
+    fn checker(&self) -> &TypeChecker {
+        // Only sessions restored from a bundle lack one; the repl loads source.
+        self.session
+            .type_checker()
+            .expect("source sessions keep their type checker")
+    }
+
+    fn expression_type(&self, source: &str) -> Result<FacetType> {
+        let expression = parse_value_expression(source).map_err(|e| anyhow::anyhow!(e))?;
+        Ok(self.checker().infer_expression_type(&expression)?)
+    }
+
+    /// Type check, then evaluate against the computed `@vars`.
+    fn evaluate(&mut self, source: &str) -> Result<String> {
+        let expression = parse_value_expression(source).map_err(|e| anyhow::anyhow!(e))?;
+        let ty = self.checker().infer_expression_type(&expression)?;
+        let gas_before = self.gas().consumed;
+        let Execution { engine, context } = &mut self.execution;
+        let value = engine.evaluate_expression(&expression, context)?;
+        Ok(format!(
+            "{}\n  : {} (gas {})",
+            display_value(&value),
+            ty,
+            self.gas().consumed - gas_before
+        ))
+    }
+
+    /// Names of the document's `@vars`, in declaration order.
+    fn var_names(&self) -> Vec<String> {
+        document_vars(self.session.document()).into_keys().collect()
+    }
+
+    fn describe_vars(&self) -> String {
+        let declared = self.session.var_types();
+        document_vars(self.session.document())
+            .into_iter()
+            .map(|(name, source)| {
+                // Declared and literal types are known; computed ones come from
+                // their lens chain.
+                let ty = match declared.get(&name) {
+                    Some(decl) if !is_any(&decl.var_type) => Ok(decl.var_type.clone()),
+                    _ => self.checker().infer_expression_type(&source),
+                };
+                let ty = ty.map_or_else(|e| format!("<{}>", e), |ty| ty.to_string());
+                let value = self
+                    .execution
+                    .context
+                    .get_variable(&name)
+                    .map_or_else(|| "<not computed>".to_string(), display_value);
+                format!("${}: {} = {}", name, ty, value)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Lay out at `budget`, or at the effective layout budget when empty.
+    fn layout(&mut self, budget: &str) -> Result<String> {
+        let budget = if budget.is_empty() {
+            None
+        } else {
+            Some(
+                budget
+                    .parse()
+                    .with_context(|| format!("Invalid budget '{}'", budget))?,
+            )
+        };
+        let layout = self.session.lay_out(&mut self.execution, budget)?;
+        Ok(describe_layout(&layout.allocation))
+    }
+}
+
+/// `@vars` entries by name in declaration order; later blocks override.
+fn document_vars(document: &FacetDocument) -> OrderedMap<String, ValueNode> {
+    let mut vars = OrderedMap::new();
+    for block in &document.blocks {
+        if let FacetNode::Vars(vars_block) = block {
+            for body in &vars_block.body {
+                if let BodyNode::KeyValue(kv) = body {
+                    vars.insert(kv.key.clone(), kv.value.clone());
+                }
+            }
+        }
+    }
+    vars
+}
+
+fn is_any(ty: &FacetType) -> bool {
+    matches!(
+        ty,
+        FacetType::Any | FacetType::Primitive(PrimitiveType::Any)
+    )
+}
+
+fn display_value(value: &ValueNode) -> String {
+    value_node_to_json(value)
+        .map(|json| json.to_string())
+        .unwrap_or_else(|_| format!("{:?}", value))
+}
+
+fn describe_layout(allocation: &AllocationResult) -> String {
+    let mut summary = format!(
+        "budget {}, total {}, overflow {}",
+        allocation.budget, allocation.total_size, allocation.overflow
+    );
+    if allocation.reserved.total() > 0 {
+        summary.push_str(&format!(
+            ", reserved {} (tools {}, output {})",
+            allocation.reserved.total(),
+            allocation.reserved.tools,
+            allocation.reserved.output
+        ));
+    }
+    let mut lines = vec![summary];
+    for item in &allocation.sections {
+        let mut flags = Vec::new();
+        if item.section.is_critical {
+            flags.push("critical");
+        }
+        if item.was_dropped {
+            flags.push("dropped");
+        }
+        if item.strategy_applied {
+            flags.push("compressed");
+        }
+        if item.was_truncated {
+            flags.push("truncated");
+        }
+        let flags = if flags.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", flags.join(", "))
+        };
+        lines.push(format!(
+            "  {}: {} -> {} (priority {}){}",
+            item.section.id, item.section.base_size, item.final_size, item.section.priority, flags
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT: &str = r#"@vars
+  docs: @input(type="list<struct { title: string }>")
+  titles: $docs |> map(field="title")
+  greeting: "hello"
+
+@user
+  content: $greeting
+"#;
+
+    fn repl() -> Repl {
+        repl_with_budget(4096)
+    }
+
+    fn repl_with_budget(budget: usize) -> Repl {
+        let docs = fct_compiler::json_to_value_node(&serde_json::json!([
+            { "title": "Intro" },
+            { "title": "Usage" }
+        ]))
+        .unwrap();
+        Repl::load(
+            &Compiler::builder().budget(budget).build(),
+            CONTRACT,
+            &std::env::temp_dir(),
+            HashMap::from([("docs".to_string(), docs)]),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn evaluates_expressions_against_computed_vars() {
+        let mut repl = repl();
+        let gas_after_load = repl.gas().consumed;
+
+        let output = repl.handle_line(r#"$titles |> join(", ")"#).unwrap();
+        assert!(
+            output.starts_with("\"Intro, Usage\"\n  : string (gas "),
+            "{}",
+            output
+        );
+        assert!(repl.gas().consumed > gas_after_load);
+        assert_eq!(
+            repl.handle_line(":gas").unwrap(),
+            format!("gas: {} / 10000", repl.gas().consumed)
+        );
+        assert_eq!(
+            repl.handle_line(":type $docs").unwrap(),
+            "list[{title: string}]"
+        );
+
+        // map(field) over a struct list keeps the field type, so join accepts it.
+        let joined = repl
+            .handle_line(r#"$docs |> map(field="title") |> join(", ")"#)
+            .unwrap();
+        assert!(
+            joined.starts_with("\"Intro, Usage\"\n  : string (gas "),
+            "{}",
+            joined
+        );
+
+        // Lens steps are checked before anything runs.
+        let gas = repl.gas().consumed;
+        let mismatch = repl.handle_line(r#"$greeting |> join(", ")"#).unwrap();
+        assert!(mismatch.starts_with("error: F451"), "{}", mismatch);
+        assert_eq!(repl.gas().consumed, gas);
+        assert!(repl.handle_line("$missing").unwrap().starts_with("error: "));
+
+        assert!(repl.handle_line(":quit").is_none());
+    }
+
+    #[test]
+    fn types_literal_and_declared_list_vars() {
+        let source = r#"@var_types
+  picks: "list<struct { title: string }>"
+
+@vars
+  docs: [{ title: "A" }, { title: "B", score: 2 }]
+  picks: $docs
+
+@user
+  content: "hi"
+"#;
+        let mut repl = Repl::load(
+            &Compiler::builder().build(),
+            source,
+            &std::env::temp_dir(),
+            HashMap::new(),
+        )
+        .unwrap();
+
+        for (line, expected) in [
+            (r#"$docs |> map(field="title") |> join(", ")"#, "\"A, B\""),
+            (r#"$docs |> map("title") |> join(", ")"#, "\"A, B\""),
+            (r#"$picks |> map("title") |> join(" & ")"#, "\"A & B\""),
+            (r#"["a", "b"] |> join(", ")"#, "\"a, b\""),
+        ] {
+            let output = repl.handle_line(line).unwrap();
+            assert!(
+                output.starts_with(&format!("{}\n  : string (gas ", expected)),
+                "{}: {}",
+                line,
+                output
+            );
+        }
+        // `score` is missing from one item, so it may be null.
+        let scores = repl.handle_line(r#":type $docs |> map("score")"#).unwrap();
+        assert!(scores.contains("null"), "{}", scores);
+    }
+
+    #[test]
+    fn lists_vars_and_layout() {
+        let mut repl = repl_with_budget(512);
+
+        let vars = repl.handle_line(":vars").unwrap();
+        let lines: Vec<&str> = vars.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("$docs: list["));
+        assert_eq!(lines[1], r#"$titles: list[string] = ["Intro","Usage"]"#);
+        assert_eq!(lines[2], r#"$greeting: string = "hello""#);
+
+        let layout = repl.handle_line(":layout").unwrap();
+        assert!(layout.starts_with("budget 512, total "), "{}", layout);
+        assert!(layout.contains("user"), "{}", layout);
+        assert!(repl
+            .handle_line(":layout lots")
+            .unwrap()
+            .starts_with("error: Invalid budget"));
+    }
+
+    #[test]
+    fn serve_prompts_until_end_of_input() {
+        let mut repl = repl();
+        let mut output = Vec::new();
+        serve(
+            &mut repl,
+            "\"a\" |> uppercase()\n:nope\n".as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("fct> \"A\"\n  : string (gas "));
+        assert!(output.contains("fct> error: Unknown command ':nope'"));
+        assert!(output.ends_with("fct> \n"));
+    }
+}
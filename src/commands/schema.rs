@@ -0,0 +1,144 @@
+//! # Schema Command
+//!
+//! This module implements the schema command for the FACET compiler.
+//! `schema inputs` emits the JSON Schema for a document's `@input(...)` variables.
+
+use crate::commands::host_profile::load_host_setup;
+use anyhow::{Context, Result};
+use console::style;
+use fct_parser::parse_document;
+use fct_render::input_schema_for_document;
+use fct_resolver::{Resolver, ResolverConfig};
+use fct_validator::{HostContextSchema, TypeChecker};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Schema inputs command handler
+pub fn execute_schema_inputs(
+    input: PathBuf,
+    output: Option<PathBuf>,
+    host_profile: Option<PathBuf>,
+    host_context: Option<PathBuf>,
+    rate_limiter: &crate::commands::DefaultRateLimiter,
+) -> Result<()> {
+    // Check rate limit
+    if rate_limiter.check().is_err() {
+        warn!("Rate limit exceeded for schema command");
+        eprintln!(
+            "{}",
+            style("Rate limit exceeded. Please wait before running another command.").red()
+        );
+        std::process::exit(1);
+    }
+
+    let host = load_host_setup(host_profile.as_deref(), host_context.as_deref())?;
+    let schema = build_input_schema(&input, host.as_ref().map(|host| &host.schema))?;
+    let rendered = serde_json::to_string_pretty(&schema)?;
+
+    match output {
+        Some(path) => {
+            fs::write(&path, format!("{rendered}\n"))
+                .with_context(|| format!("Failed to write schema file: {:?}", path))?;
+            println!("wrote input schema: {}", path.display());
+        }
+        None => println!("{rendered}"),
+    }
+
+    Ok(())
+}
+
+/// Parse, resolve, and validate `input`, then derive its runtime input schema.
+fn build_input_schema(
+    input: &Path,
+    host_context: Option<&HostContextSchema>,
+) -> Result<serde_json::Value> {
+    let source = fs::read_to_string(input)
+        .with_context(|| format!("Failed to read input file: {:?}", input))?;
+
+    let parsed = parse_document(&source).map_err(|e| anyhow::anyhow!("Parse error: {}", e))?;
+
+    let base_dir = input
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or(std::env::current_dir()?);
+    let mut resolver = Resolver::new(ResolverConfig {
+        allowed_roots: vec![base_dir.clone()],
+        base_dir,
+    });
+    let resolved = resolver
+        .resolve(parsed)
+        .map_err(|e| anyhow::anyhow!("Resolution error: {}", e))?;
+
+    let mut checker = TypeChecker::new();
+    if let Some(schema) = host_context {
+        checker = checker.with_host_context(schema.clone());
+    }
+    checker
+        .validate(&resolved)
+        .map_err(|e| anyhow::anyhow!("Validation error: {}", e))?;
+
+    input_schema_for_document(&resolved).map_err(|e| anyhow::anyhow!("Schema error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use governor::{Quota, RateLimiter};
+    use nonzero_ext::nonzero;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_dir(label: &str) -> PathBuf {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("facet-schema-{}-{}", label, nonce));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn schema_inputs_writes_schema_for_input_vars() {
+        let test_dir = temp_dir("ok");
+        let input_path = test_dir.join("input.facet");
+        fs::write(
+            &input_path,
+            "@var_types\n  age: { type: \"int\", min: 0, max: 130 }\n\n@vars\n  age: @input(type=\"int\")\n  name: @input(type=\"string\", default=\"anon\")\n",
+        )
+        .expect("write input");
+        let output_path = test_dir.join("inputs.schema.json");
+
+        let limiter = RateLimiter::direct(Quota::per_second(nonzero!(10u32)));
+        execute_schema_inputs(input_path, Some(output_path.clone()), None, None, &limiter)
+            .expect("schema inputs should succeed");
+
+        let schema: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&output_path).expect("read schema"))
+                .expect("schema should be JSON");
+        assert_eq!(
+            schema["properties"]["age"],
+            serde_json::json!({ "type": "integer", "minimum": 0, "maximum": 130 })
+        );
+        assert_eq!(schema["properties"]["name"]["default"], "anon");
+        assert_eq!(schema["required"], serde_json::json!(["age"]));
+
+        let _ = fs::remove_dir_all(test_dir);
+    }
+
+    #[test]
+    fn schema_inputs_reports_validation_errors() {
+        let test_dir = temp_dir("err");
+        let input_path = test_dir.join("input.facet");
+        fs::write(
+            &input_path,
+            "@var_types\n  age: { type: \"int\", min: 0 }\n\n@vars\n  age: -1\n",
+        )
+        .expect("write input");
+
+        let err = build_input_schema(&input_path, None).unwrap_err();
+        assert!(err.to_string().contains("Validation error"));
+
+        let _ = fs::remove_dir_all(test_dir);
+    }
+}
@@ -12,7 +12,9 @@ use std::time::Instant;
 use tracing::{debug, error, info};
 
 // Import FACET crates
-use fct_engine::{ReportFormat, TestReporter};
+use crate::commands::host_profile::load_host_setup;
+use crate::commands::trace_output::write_trace;
+use fct_engine::{ExecutionTrace, ReportFormat, TestReporter};
 use fct_parser::parse_document;
 use fct_resolver::{Resolver, ResolverConfig};
 use fct_validator::TypeChecker;
@@ -31,6 +33,9 @@ pub fn execute_test(
     gas_limit: usize,
     pure: bool,
     exec: bool,
+    trace: Option<std::path::PathBuf>,
+    host_profile: Option<std::path::PathBuf>,
+    host_context: Option<std::path::PathBuf>,
     rate_limiter: &crate::commands::DefaultRateLimiter,
 ) -> Result<()> {
     // Check rate limit
@@ -43,6 +48,9 @@ pub fn execute_test(
     }
 
     let start_time = Instant::now();
+    let mut timeline = trace
+        .as_ref()
+        .map(|_| ExecutionTrace::with_origin(start_time));
     info!("Running tests for file: {:?}", input);
 
     if pure && exec {
@@ -55,6 +63,8 @@ pub fn execute_test(
         fct_engine::ExecutionMode::Exec
     };
 
+    let host = load_host_setup(host_profile.as_deref(), host_context.as_deref())?;
+
     // Validate input file exists
     if !input.exists() {
         return Err(anyhow::anyhow!("Input file does not exist: {:?}", input));
@@ -64,13 +74,16 @@ pub fn execute_test(
     let content =
         fs::read_to_string(&input).with_context(|| format!("Failed to read file: {:?}", input))?;
 
+    let started = Instant::now();
     let parsed = parse_document(&content)
         .map_err(|e| anyhow::anyhow!("Failed to parse FACET document {:?}: {}", input, e))?;
+    record_phase(&mut timeline, "parse", started);
 
     let base_dir = input
         .parent()
         .map(std::path::Path::to_path_buf)
         .unwrap_or(std::env::current_dir()?);
+    let started = Instant::now();
     let mut resolver = Resolver::new(ResolverConfig {
         allowed_roots: vec![base_dir.clone()],
         base_dir,
@@ -78,11 +91,17 @@ pub fn execute_test(
     let document = resolver
         .resolve(parsed)
         .map_err(|e| anyhow::anyhow!("Resolution error: {}", e))?;
+    record_phase(&mut timeline, "resolve", started);
 
+    let started = Instant::now();
     let mut checker = TypeChecker::new();
+    if let Some(host) = &host {
+        checker = checker.with_host_context(host.schema.clone());
+    }
     checker
         .validate(&document)
         .map_err(|e| anyhow::anyhow!("Validation error: {}", e))?;
+    record_phase(&mut timeline, "type_check", started);
 
     // Extract test blocks from the document
     let test_blocks: Vec<_> = document
@@ -139,7 +158,10 @@ pub fn execute_test(
     println!();
 
     // Create test runner with resource limits and execution mode
-    let test_runner = fct_engine::TestRunner::new_with_mode(gas_limit, budget, mode);
+    let mut test_runner = fct_engine::TestRunner::new_with_mode(gas_limit, budget, mode);
+    if let Some(host) = host {
+        test_runner = test_runner.with_host(host.profile_id, host.context);
+    }
 
     // Run all tests
     let mut test_results = Vec::new();
@@ -150,7 +172,11 @@ pub fn execute_test(
         let test_start = Instant::now();
         debug!("Running test: {}", test_block.name);
 
-        match test_runner.run_test(&document, test_block) {
+        let result = match timeline.as_mut() {
+            Some(timeline) => test_runner.run_test_traced(&document, test_block, timeline),
+            None => test_runner.run_test(&document, test_block),
+        };
+        match result {
             Ok(result) => {
                 let test_duration = test_start.elapsed();
 
@@ -226,6 +252,10 @@ pub fn execute_test(
         }
     }
 
+    if let (Some(path), Some(timeline)) = (&trace, &timeline) {
+        write_trace(path, timeline)?;
+    }
+
     // Generate output in the requested format
     let total_duration = start_time.elapsed();
 
@@ -249,6 +279,12 @@ pub fn execute_test(
     Ok(())
 }
 
+fn record_phase(timeline: &mut Option<ExecutionTrace>, name: &str, started: Instant) {
+    if let Some(timeline) = timeline {
+        timeline.record(name, "phase", started, serde_json::json!({}));
+    }
+}
+
 /// Generate JSON output using TestReporter
 fn generate_json_output(
     test_results: &[fct_engine::TestResult],
@@ -373,6 +409,9 @@ mod tests {
             2048,
             true,
             true,
+            None,
+            None,
+            None,
             &limiter,
         )
         .unwrap_err();
@@ -401,6 +440,9 @@ mod tests {
             2048,
             false,
             true,
+            None,
+            None,
+            None,
             &limiter,
         )
         .expect("should return ok for document without @test blocks");
@@ -435,6 +477,9 @@ mod tests {
             2048,
             false,
             true,
+            None,
+            None,
+            None,
             &limiter,
         )
         .expect_err("invalid document must fail validation before test discovery");
@@ -477,10 +522,84 @@ mod tests {
             2048,
             false,
             true,
+            None,
+            None,
+            None,
             &limiter,
         )
         .expect("execute_test should pass guarded mock flow in exec mode");
 
         let _ = fs::remove_dir_all(test_dir);
     }
+
+    #[test]
+    fn execute_test_writes_chrome_trace() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let test_dir = std::env::temp_dir().join(format!("facet-test-trace-{}", nonce));
+        fs::create_dir_all(&test_dir).expect("create temp dir");
+        let input_path = test_dir.join("input.facet");
+        fs::write(
+            &input_path,
+            r#"
+@vars
+  name: "Ada" |> trim()
+
+@user
+  content: $name
+
+@test(name="traced")
+  assert:
+    - "output contains blocks"
+"#,
+        )
+        .expect("write input");
+        let trace_path = test_dir.join("trace.json");
+
+        let limiter = RateLimiter::direct(Quota::per_second(nonzero!(10u32)));
+        execute_test(
+            input_path,
+            None,
+            "summary".to_string(),
+            1024,
+            2048,
+            false,
+            true,
+            Some(trace_path.clone()),
+            None,
+            None,
+            &limiter,
+        )
+        .expect("traced test run should pass");
+
+        let trace: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&trace_path).expect("read trace"))
+                .expect("trace is JSON");
+        let spans: Vec<(&str, &str)> = trace["traceEvents"]
+            .as_array()
+            .expect("traceEvents")
+            .iter()
+            .filter(|event| event["ph"] == "X")
+            .map(|event| {
+                (
+                    event["cat"].as_str().unwrap(),
+                    event["name"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        for expected in [
+            ("phase", "parse"),
+            ("phase", "type_check"),
+            ("test", "traced"),
+            ("rdag", "name"),
+            ("lens", "trim"),
+            ("phase", "layout"),
+        ] {
+            assert!(spans.contains(&expected), "{expected:?} in {spans:?}");
+        }
+
+        let _ = fs::remove_dir_all(test_dir);
+    }
 }
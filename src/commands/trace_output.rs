@@ -0,0 +1,12 @@
+//! `--trace` output shared by `run` and `test`.
+
+use anyhow::{Context, Result};
+use fct_engine::ExecutionTrace;
+use std::fs;
+use std::path::Path;
+
+/// Write `trace` as a Chrome trace (load it in Perfetto or `chrome://tracing`).
+pub fn write_trace(path: &Path, trace: &ExecutionTrace) -> Result<()> {
+    let json = serde_json::to_string(&trace.to_chrome_json())?;
+    fs::write(path, json).with_context(|| format!("Failed to write trace: {:?}", path))
+}
@@ -6,7 +6,7 @@
 mod commands;
 
 use clap::Parser;
-use commands::{Cli, Commands, DefaultRateLimiter};
+use commands::{Cli, Commands, DefaultRateLimiter, PolicyCommands, SchemaCommands};
 use governor::{Quota, RateLimiter};
 use nonzero_ext::nonzero;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -23,11 +23,31 @@ fn main() -> anyhow::Result<()> {
 
     // Execute command
     match cli.command {
-        Commands::Build { input } => {
-            commands::build::execute_build(input, cli.verbose, cli.no_progress, &rate_limiter)
+        Commands::Build {
+            input,
+            profile,
+            host_profile,
+            host_context,
+        } => commands::build::execute_build(
+            input,
+            profile,
+            host_profile,
+            host_context,
+            cli.verbose,
+            cli.no_progress,
+            &rate_limiter,
+        ),
+        Commands::Compile {
+            input,
+            output,
+            profile,
+            host_profile,
+        } => {
+            commands::compile::execute_compile(input, output, profile, host_profile, &rate_limiter)
         }
         Commands::Inspect {
             input,
+            runtime_input,
             ast,
             dag,
             layout,
@@ -35,8 +55,16 @@ fn main() -> anyhow::Result<()> {
             budget,
             pure,
             exec,
+            profile,
+            demand_driven,
+            output_reserve,
+            budget_sweep,
+            sweep,
+            host_profile,
+            host_context,
         } => commands::inspect::execute_inspect(
             input,
+            runtime_input,
             ast,
             dag,
             layout,
@@ -44,6 +72,13 @@ fn main() -> anyhow::Result<()> {
             budget,
             pure,
             exec,
+            profile,
+            demand_driven,
+            output_reserve,
+            budget_sweep,
+            sweep,
+            host_profile,
+            host_context,
             &rate_limiter,
         ),
         Commands::Run {
@@ -51,20 +86,54 @@ fn main() -> anyhow::Result<()> {
             runtime_input,
             budget,
             context_budget,
+            output_reserve,
             format,
             pure,
             exec,
+            host_profile,
+            host_context,
+            profile,
+            demand_driven,
+            parallel,
+            trace,
         } => commands::run::execute_run(
             input,
             runtime_input,
             budget,
             context_budget,
+            output_reserve,
             format,
             pure,
             exec,
+            host_profile,
+            host_context,
+            profile,
+            demand_driven,
+            parallel,
+            trace,
             cli.no_progress,
             &rate_limiter,
         ),
+        Commands::Repl {
+            input,
+            runtime_input,
+            budget,
+            context_budget,
+            pure,
+            exec,
+            host_profile,
+            host_context,
+        } => commands::repl::execute_repl(
+            input,
+            runtime_input,
+            budget,
+            context_budget,
+            pure,
+            exec,
+            host_profile,
+            host_context,
+            &rate_limiter,
+        ),
         Commands::Test {
             input,
             filter,
@@ -73,6 +142,9 @@ fn main() -> anyhow::Result<()> {
             gas_limit,
             pure,
             exec,
+            trace,
+            host_profile,
+            host_context,
         } => commands::test::execute_test(
             input,
             filter,
@@ -81,6 +153,9 @@ fn main() -> anyhow::Result<()> {
             gas_limit,
             pure,
             exec,
+            trace,
+            host_profile,
+            host_context,
             &rate_limiter,
         ),
         Commands::Codegen {
@@ -89,6 +164,105 @@ fn main() -> anyhow::Result<()> {
             language,
             name,
         } => commands::codegen::execute_codegen(input, output, language, name, &rate_limiter),
+        Commands::Lint {
+            input,
+            config,
+            rules,
+            format,
+            host_profile,
+            host_context,
+        } => commands::lint::execute_lint(
+            input,
+            config,
+            rules,
+            format,
+            host_profile,
+            host_context,
+            &rate_limiter,
+        ),
+        Commands::GuardServe {
+            input,
+            runtime_input,
+            socket,
+            budget,
+            context_budget,
+            pure,
+            exec,
+            host_profile,
+            host_context,
+            max_sessions,
+            session_idle_secs,
+        } => commands::guard_serve::execute_guard_serve(
+            input,
+            runtime_input,
+            socket,
+            budget,
+            context_budget,
+            pure,
+            exec,
+            host_profile,
+            host_context,
+            max_sessions,
+            std::time::Duration::from_secs(session_idle_secs),
+            &rate_limiter,
+        ),
+        Commands::Policy {
+            command:
+                PolicyCommands::Explain {
+                    input,
+                    format,
+                    host_profile,
+                    host_context,
+                },
+        } => commands::policy::execute_policy_explain(
+            input,
+            format,
+            host_profile,
+            host_context,
+            &rate_limiter,
+        ),
+        Commands::Policy {
+            command:
+                PolicyCommands::Eval {
+                    input,
+                    op,
+                    name,
+                    effect_class,
+                    vars,
+                    args,
+                    host_profile_id,
+                    host_profile,
+                    host_context,
+                    format,
+                },
+        } => commands::policy::execute_policy_eval(
+            input,
+            op,
+            name,
+            effect_class,
+            vars,
+            args,
+            host_profile_id,
+            host_profile,
+            host_context,
+            format,
+            &rate_limiter,
+        ),
+        Commands::Schema {
+            command:
+                SchemaCommands::Inputs {
+                    input,
+                    output,
+                    host_profile,
+                    host_context,
+                },
+        } => commands::schema::execute_schema_inputs(
+            input,
+            output,
+            host_profile,
+            host_context,
+            &rate_limiter,
+        ),
     }
 }
 
@@ -166,6 +166,84 @@ fn matrix_f453_runtime_input_validation() {
     );
 }
 
+#[test]
+fn matrix_f453_runtime_input_reports_every_violation_with_path() {
+    let source = r#"
+@types
+  Signup: struct { email: string, tags: list<string> }
+
+@var_types
+  tags: { type: "list<string>", min_items: 1, max_items: 2, unique_items: true }
+  email: { type: "string", format: "email", max_length: 8 }
+
+@vars
+  user: @input(type="Signup")
+  tags: @input(type="list<string>")
+  email: @input(type="string")
+"#;
+
+    let user = ValueNode::Map(OrderedMap::from([(
+        "tags".to_string(),
+        ValueNode::List(vec![ValueNode::Scalar(fct_ast::ScalarValue::Int(1))]),
+    )]));
+    let err = execute_source_with_input(source, 1000, ExecutionMode::Exec, "user", user)
+        .expect_err("expected F453");
+    assert!(err.contains("F453"), "expected F453, got: {err}");
+    assert!(err.contains("$.email: required field is missing"), "{err}");
+    assert!(err.contains("$.tags[0]: expected string, got int"), "{err}");
+
+    let doc = validate_source(source).expect("document should validate");
+    let mut engine = RDagEngine::new();
+    engine.build(&doc).unwrap();
+    let mut ctx = ExecutionContext::new(1000);
+    ctx.set_input(
+        "user".to_string(),
+        ValueNode::Map(OrderedMap::from([
+            ("email".to_string(), ValueNode::String("a@b.io".to_string())),
+            ("tags".to_string(), ValueNode::List(vec![])),
+        ])),
+    );
+    ctx.set_input(
+        "tags".to_string(),
+        ValueNode::List(vec![
+            ValueNode::String("x".to_string()),
+            ValueNode::String("x".to_string()),
+            ValueNode::String("y".to_string()),
+        ]),
+    );
+    ctx.set_input(
+        "email".to_string(),
+        ValueNode::String("someone@example".to_string()),
+    );
+    let err = engine.execute(&mut ctx).unwrap_err().to_string();
+    assert!(err.contains("F453"), "expected F453, got: {err}");
+    assert!(err.contains("$: max_items <= 2; $: unique_items"), "{err}");
+}
+
+#[test]
+fn matrix_f453_runtime_input_format_and_length() {
+    let source = r#"
+@var_types
+  email: { type: "string", format: "email", max_length: 8 }
+
+@vars
+  email: @input(type="string")
+"#;
+    let err = execute_source_with_input(
+        source,
+        1000,
+        ExecutionMode::Exec,
+        "email",
+        ValueNode::String("someone@example".to_string()),
+    )
+    .expect_err("expected F453");
+    assert!(err.contains("F453"), "expected F453, got: {err}");
+    assert!(
+        err.contains("$: max_length <= 8; $: format 'email'"),
+        "{err}"
+    );
+}
+
 #[test]
 fn matrix_f454_policy_deny() {
     let doc = llm_call_vars_doc();
@@ -336,3 +414,87 @@ fn matrix_f902_gas_exhausted() {
     let source = "@vars\n  a: \"1\"\n  b: \"2\"\n";
     assert_error_code(execute_source(source, 1, ExecutionMode::Exec), "F902");
 }
+
+#[test]
+fn matrix_f902_template_output_exhausts_gas() {
+    // 1000 bytes of output cost 17 units on top of the variables themselves.
+    let source = format!(
+        "@vars\n  body: \"{}\"\n  prompt: \"{{{{b}}}}\" |> template(b=$body)\n",
+        "x".repeat(1000)
+    );
+    assert!(execute_source(&source, 100, ExecutionMode::Exec).is_ok());
+    assert_error_code(execute_source(&source, 10, ExecutionMode::Exec), "F902");
+}
+
+#[test]
+fn matrix_input_schema_structs_accept_extra_fields_like_runtime() {
+    let source = r#"
+@types
+  User: struct { name: string, address: struct { city: string } }
+
+@vars
+  user: @input(type="User")
+"#;
+    let doc = parse_document(source).expect("document should parse");
+    let schema = fct_render::input_schema_for_document(&doc).expect("schema should build");
+    let user = &schema["$defs"]["User"];
+    assert_eq!(user["required"], serde_json::json!(["name", "address"]));
+    assert!(user.get("additionalProperties").is_none());
+    assert!(user["properties"]["address"]
+        .get("additionalProperties")
+        .is_none());
+
+    let address = ValueNode::Map(OrderedMap::from([
+        ("city".to_string(), ValueNode::String("London".to_string())),
+        ("zip".to_string(), ValueNode::String("N1".to_string())),
+    ]));
+    let input = ValueNode::Map(OrderedMap::from([
+        ("name".to_string(), ValueNode::String("Ada".to_string())),
+        ("address".to_string(), address),
+        (
+            "email".to_string(),
+            ValueNode::String("ada@example.com".to_string()),
+        ),
+    ]));
+    let result = execute_source_with_input(source, 1000, ExecutionMode::Exec, "user", input);
+    assert!(result.is_ok(), "extra field should be accepted: {result:?}");
+}
+
+#[test]
+fn matrix_f453_input_schema_required_matches_runtime_presence() {
+    let source = r#"
+@var_types
+  user: { type: "struct { name: string, email: string, age: int }", required: ["name"] }
+
+@vars
+  user: @input(type="struct { name: string }")
+"#;
+    let doc = parse_document(source).expect("document should parse");
+    let schema = fct_render::input_schema_for_document(&doc).expect("schema should build");
+    let declared = &schema["properties"]["user"]["allOf"][1];
+    assert_eq!(declared["required"], serde_json::json!(["name"]));
+
+    let fields = [
+        ("name", ValueNode::String("Ada".to_string())),
+        ("email", ValueNode::String("ada@example.com".to_string())),
+        ("age", ValueNode::Scalar(fct_ast::ScalarValue::Int(36))),
+    ];
+    for (present, value) in fields {
+        let input = ValueNode::Map(OrderedMap::from([(present.to_string(), value)]));
+        let result = execute_source_with_input(source, 1000, ExecutionMode::Exec, "user", input);
+        let schema_accepts = declared["required"]
+            .as_array()
+            .expect("required list")
+            .iter()
+            .all(|name| name == present);
+        if schema_accepts {
+            assert!(result.is_ok(), "only '{present}' given: {result:?}");
+        } else {
+            let err = result.expect_err("schema-required field is missing");
+            assert!(err.contains("F453"), "expected F453, got: {err}");
+            assert!(err.contains("$.name: required field is missing"), "{err}");
+            assert!(!err.contains("$.email"), "{err}");
+            assert!(!err.contains("$.age"), "{err}");
+        }
+    }
+}
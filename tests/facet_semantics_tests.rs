@@ -130,6 +130,24 @@ fn input_allows_pipeline_with_input_as_base() {
     assert!(validate(source).is_ok());
 }
 
+#[test]
+fn map_field_keeps_struct_field_type_for_later_lens_steps() {
+    let source = r#"
+@vars
+  docs: @input(type="list<struct { title: string, views: int }>")
+  titles: $docs |> map(field="title") |> join(", ")
+"#;
+    assert!(validate(source).is_ok());
+
+    let source = r#"
+@vars
+  docs: @input(type="list<struct { title: string, views: int }>")
+  views: $docs |> map(field="views") |> join(", ")
+"#;
+    let err = validate(source).unwrap_err();
+    assert!(err.contains("F451"), "expected F451, got: {err}");
+}
+
 #[test]
 fn input_allows_all_primitive_types() {
     let source = r#"
@@ -592,3 +610,93 @@ fn multimodal_audio_asset_rejects_invalid_format() {
     let err = validate(source).unwrap_err();
     assert!(err.contains("F452"), "expected F452, got: {err}");
 }
+
+#[test]
+fn logic_lenses_compute_when_gates() {
+    let source = r#"
+@vars
+  docs: ["a", "b"]
+  has_docs: $docs |> length() |> gt(0)
+  quiet: $has_docs |> not() |> or($has_docs)
+
+@user(when=$has_docs)
+  content: "docs"
+"#;
+
+    assert!(validate(source).is_ok(), "{:?}", validate(source));
+}
+
+#[test]
+fn logic_lens_inputs_are_type_checked() {
+    let source = r#"
+@vars
+  flag: "yes" |> not()
+"#;
+
+    let err = validate(source).unwrap_err();
+    assert!(err.contains("F451"), "expected F451, got: {err}");
+}
+
+#[test]
+fn formatting_lenses_build_prompt_text_from_lists() {
+    let source = r#"
+@vars
+  docs: [{title: "Intro", pages: 3}]
+  table: $docs |> to_markdown_table(columns=["title", "pages"])
+  bullets: $docs |> to_bullets(field="title") |> xml_wrap(tag="sources")
+  config: $docs |> to_yaml()
+
+@user
+  content: $bullets
+"#;
+
+    assert!(validate(source).is_ok(), "{:?}", validate(source));
+}
+
+#[test]
+fn formatting_lens_inputs_are_type_checked() {
+    let source = r#"
+@vars
+  bullets: "not a list" |> to_bullets()
+"#;
+
+    let err = validate(source).unwrap_err();
+    assert!(err.contains("F451"), "expected F451, got: {err}");
+}
+
+#[test]
+fn message_pack_accepts_position_or_score_field() {
+    for pack in [r#""position""#, r#"{ score: "relevance" }"#] {
+        let source = format!("@user\n  content: \"Docs\"\n  x_fct_pack: {pack}\n");
+        assert!(validate(&source).is_ok(), "pack {pack} should validate");
+    }
+
+    for pack in [
+        r#""score""#,
+        r#"{ score: 1 }"#,
+        r#"{ score: "a", by: "b" }"#,
+    ] {
+        let source = format!("@user\n  content: \"Docs\"\n  x_fct_pack: {pack}\n");
+        let err = validate(&source).unwrap_err();
+        assert!(err.contains("F452"), "expected F452 for {pack}, got: {err}");
+    }
+
+    // Packing is a host extension, not a §12.3 message field.
+    let err = validate("@user\n  content: \"Docs\"\n  pack: \"position\"\n").unwrap_err();
+    assert!(err.contains("Unknown message field"), "got: {err}");
+}
+
+#[test]
+fn context_output_reserve_must_be_non_negative_int() {
+    let ok = "@context\n  budget: 4096\n  x_fct_output_reserve: 512\n";
+    assert!(validate(ok).is_ok());
+
+    for value in ["-1", "\"512\""] {
+        let source = format!("@context\n  budget: 4096\n  x_fct_output_reserve: {value}\n");
+        let err = validate(&source).unwrap_err();
+        assert!(
+            err.contains("F452"),
+            "expected F452 for {value}, got: {err}"
+        );
+    }
+}
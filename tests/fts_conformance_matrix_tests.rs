@@ -80,6 +80,71 @@ fn fts_positive_conformance_matrix() {
 
 @vars
   img: { kind: "image", format: "jpeg", shape: { width: 900, height: 700 } }
+"#,
+        ),
+        (
+            "type_alias_in_var_types",
+            r#"
+@types
+  Users: list<User>
+  User: struct { name: string, age: int }
+
+@var_types
+  team: "Users"
+  lead: { type: "User | null" }
+
+@vars
+  team: [{ name: "Ada", age: 37 }]
+  lead: null
+"#,
+        ),
+        (
+            "string_length_and_format",
+            r#"
+@var_types
+  id: { type: "string", format: "uuid" }
+  site: { type: "string | null", format: "uri", min_length: 8 }
+  day: { type: "string", format: "date", max_length: 10 }
+
+@vars
+  id: "123e4567-e89b-12d3-a456-426614174000"
+  site: "https://example.com"
+  day: "2024-02-29"
+"#,
+        ),
+        (
+            "list_item_constraints",
+            r#"
+@var_types
+  tags: { type: "list<string>", min_items: 1, max_items: 3, unique_items: true }
+
+@vars
+  tags: ["a", "b"]
+"#,
+        ),
+        (
+            "struct_optional_fields",
+            r#"
+@var_types
+  user: { type: "struct { name: string, email: string, age: int }", required: ["name"] }
+  contact: { type: "struct { phone: string, email: string }", optional: ["phone"] }
+
+@vars
+  user: { name: "Ada" }
+  contact: { email: "ada@example.com" }
+"#,
+        ),
+        (
+            "type_alias_in_input_directive",
+            r#"
+@types
+  Query: struct { text: string }
+
+@var_types
+  q: "Query"
+
+@vars
+  q: @input(type="Query")
 "#,
         ),
     ];
@@ -193,6 +258,113 @@ fn fts_negative_conformance_matrix() {
             r#"
 @vars
   img: @input(type="image(format=gif)")
+"#,
+            "F452",
+        ),
+        (
+            "min_length_violation",
+            r#"
+@var_types
+  name: { type: "string", min_length: 3 }
+
+@vars
+  name: "Al"
+"#,
+            "F452",
+        ),
+        (
+            "format_violation",
+            r#"
+@var_types
+  day: { type: "string", format: "date" }
+
+@vars
+  day: "2023-02-29"
+"#,
+            "F452",
+        ),
+        (
+            "unique_items_violation",
+            r#"
+@var_types
+  tags: { type: "list<string>", unique_items: true }
+
+@vars
+  tags: ["a", "a"]
+"#,
+            "F452",
+        ),
+        (
+            "unknown_format",
+            r#"
+@var_types
+  x: { type: "string", format: "ipv4" }
+"#,
+            "F452",
+        ),
+        (
+            "constraint_not_applicable",
+            r#"
+@var_types
+  n: { type: "int", min_length: 1 }
+"#,
+            "F452",
+        ),
+        (
+            "optional_unknown_field",
+            r#"
+@var_types
+  user: { type: "struct { name: string }", optional: ["age"] }
+"#,
+            "F452",
+        ),
+        (
+            "required_field_still_enforced",
+            r#"
+@var_types
+  user: { type: "struct { name: string, age: int }", optional: ["age"] }
+
+@vars
+  user: { age: 3 }
+"#,
+            "F451",
+        ),
+        (
+            "type_alias_mismatch",
+            r#"
+@types
+  User: struct { name: string, age: int }
+
+@var_types
+  user: "User"
+
+@vars
+  user: { name: "Ada" }
+"#,
+            "F451",
+        ),
+        (
+            "type_alias_cycle",
+            r#"
+@types
+  A: list<B>
+  B: struct { next: A }
+"#,
+            "F452",
+        ),
+        (
+            "type_alias_unknown_reference",
+            r#"
+@types
+  A: list<Missing>
+"#,
+            "F452",
+        ),
+        (
+            "type_alias_shadows_builtin",
+            r#"
+@types
+  string: int
 "#,
             "F452",
         ),
@@ -124,6 +124,7 @@ fn full_pipeline_with_context(
         budget_units: None,
         target_provider_id: None,
         computed_vars: None,
+        host_context: None,
     };
     let payload = renderer
         .render_with_context(&doc, &allocation, render_context)
@@ -183,6 +183,7 @@ fn renderer_emits_only_system_referenced_tools() {
                 budget_units: Some(1000),
                 target_provider_id: Some("test-provider".to_string()),
                 computed_vars: None,
+                host_context: None,
             },
         )
         .expect("render should succeed");
@@ -194,3 +195,95 @@ fn renderer_emits_only_system_referenced_tools() {
         .collect();
     assert_eq!(names, vec!["charge".to_string()]);
 }
+
+#[test]
+fn validator_accepts_type_alias_interface_params() {
+    let source = r#"
+@types
+  Address: struct { street: string, city: string }
+  Route: list<Address>
+
+@interface GeoAPI
+  fn plan(route: Route, origin: Address | null) -> Address (effect="read")
+"#;
+    let result = validate(source);
+    assert!(
+        result.is_ok(),
+        "expected aliases to resolve, got {:?}",
+        result
+    );
+}
+
+#[test]
+fn validator_rejects_unmappable_type_alias_in_interface() {
+    let source = r#"
+@types
+  Photo: image(format=jpeg)
+
+@interface VisionAPI
+  fn describe(photo: Photo) -> string (effect="read")
+"#;
+    let err = validate(source).unwrap_err();
+    assert!(err.contains("F452"), "expected F452, got: {}", err);
+}
+
+#[test]
+fn renderer_emits_type_aliases_as_defs() {
+    let source = r#"
+@types
+  Address: struct { street: string, city: string }
+  Route: list<Address>
+  Unused: int
+
+@interface GeoAPI
+  fn plan(route: Route) -> string (effect="read")
+
+@system
+  tools: [$GeoAPI]
+  content: "x"
+"#;
+
+    let doc = validate(source).expect("document should validate");
+    let allocation = TokenBoxModel::new(1000)
+        .allocate(Vec::new(), &LensRegistry::new())
+        .expect("allocation should succeed");
+    let payload = Renderer::new()
+        .render_with_context(
+            &doc,
+            &allocation,
+            RenderContext {
+                document_hash: Some(compute_document_hash(source)),
+                policy_hash: None,
+                profile: Some("hypervisor".to_string()),
+                mode: Some("exec".to_string()),
+                host_profile_id: Some("local.default.v1".to_string()),
+                budget_units: Some(1000),
+                target_provider_id: Some("test-provider".to_string()),
+                computed_vars: None,
+                host_context: None,
+            },
+        )
+        .expect("render should succeed");
+
+    let parameters = serde_json::to_value(&payload.tools[0].function.parameters).unwrap();
+    assert_eq!(
+        parameters,
+        serde_json::json!({
+            "type": "object",
+            "properties": { "route": { "$ref": "#/$defs/Route" } },
+            "required": ["route"],
+            "$defs": {
+                "Address": {
+                    "type": "object",
+                    "properties": {
+                        "street": { "type": "string" },
+                        "city": { "type": "string" }
+                    },
+                    "required": ["street", "city"],
+                    "additionalProperties": false
+                },
+                "Route": { "type": "array", "items": { "$ref": "#/$defs/Address" } }
+            }
+        })
+    );
+}
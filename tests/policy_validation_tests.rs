@@ -1,5 +1,5 @@
 use fct_parser::parse_document;
-use fct_validator::TypeChecker;
+use fct_validator::{HostContextSchema, TypeChecker};
 
 fn validate(source: &str) -> Result<(), String> {
     let doc = parse_document(source)?;
@@ -254,3 +254,91 @@ fn policy_rejects_unknown_message_emit_id() {
     let err = validate(source).unwrap_err();
     assert!(err.contains("F452"), "expected F452, got: {}", err);
 }
+
+fn validate_with_host_context(source: &str) -> Result<(), String> {
+    let doc = parse_document(source)?;
+    let schema = HostContextSchema::parse([(
+        "acme",
+        "struct { principal: struct { admin: bool, role: string } }",
+    )])
+    .map_err(|e| e.to_string())?;
+    let mut checker = TypeChecker::new().with_host_context(schema);
+    checker.validate(&doc).map_err(|e| e.to_string())
+}
+
+#[test]
+fn policy_accepts_declared_host_context_condition() {
+    let source = r#"
+@policy
+  allow: [{ op: "lens_call", name: "llm_call", when: $x.acme.principal.admin }]
+"#;
+    assert!(validate_with_host_context(source).is_ok());
+
+    // Without a host profile `$x` is an ordinary (undeclared) variable.
+    let err = validate(source).unwrap_err();
+    assert!(err.contains("F401"), "expected F401, got: {}", err);
+}
+
+#[test]
+fn policy_rejects_undeclared_or_non_bool_host_context_paths() {
+    let undeclared = r#"
+@policy
+  allow: [{ op: "lens_call", name: "llm_call", when: $x.acme.principal.owner }]
+"#;
+    let err = validate_with_host_context(undeclared).unwrap_err();
+    assert!(err.contains("F401"), "expected F401, got: {}", err);
+
+    let non_bool = r#"
+@policy
+  allow: [{ op: "lens_call", name: "llm_call", when: $x.acme.principal.role }]
+"#;
+    let err = validate_with_host_context(non_bool).unwrap_err();
+    assert!(err.contains("F451"), "expected F451, got: {}", err);
+}
+
+#[test]
+fn host_context_root_is_reserved_when_a_host_profile_is_active() {
+    let source = r#"
+@vars
+  x: true
+
+@policy
+  allow: [{ op: "lens_call", name: "llm_call", when: $x }]
+"#;
+    assert!(validate(source).is_ok());
+    let err = validate_with_host_context(source).unwrap_err();
+    assert!(err.contains("F452"), "expected F452, got: {}", err);
+}
+
+#[test]
+fn policy_accepts_max_calls_quota_on_tool_call_allow_rules() {
+    let source = format!(
+        "{}{}",
+        payment_interface(),
+        r#"
+@policy
+  allow: [{ id: "charges", op: "tool_call", name: "PaymentAPI.charge", x_fct_max_calls: 3 }]
+"#
+    );
+    assert!(validate(&source).is_ok());
+}
+
+#[test]
+fn policy_rejects_misplaced_or_invalid_max_calls() {
+    for rule in [
+        r#"deny: [{ id: "q", op: "tool_call", name: "PaymentAPI.charge", x_fct_max_calls: 3 }]"#,
+        r#"allow: [{ id: "q", op: "tool_expose", name: "PaymentAPI.charge", x_fct_max_calls: 3 }]"#,
+        r#"allow: [{ op: "tool_call", name: "PaymentAPI.charge", x_fct_max_calls: 3 }]"#,
+        r#"allow: [{ id: "q", op: "tool_call", name: "PaymentAPI.charge", x_fct_max_calls: -1 }]"#,
+        r#"allow: [{ id: "q", op: "tool_call", name: "PaymentAPI.charge", x_fct_max_calls: "3" }]"#,
+    ] {
+        let source = format!("{}\n@policy\n  {}\n", payment_interface(), rule);
+        let err = validate(&source).unwrap_err();
+        assert!(
+            err.contains("F452"),
+            "expected F452 for {}, got: {}",
+            rule,
+            err
+        );
+    }
+}
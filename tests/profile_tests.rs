@@ -91,3 +91,23 @@ fn core_profile_rejects_pipeline_in_vars() {
     assert!(error.contains("F801"));
     assert!(error.contains("@vars.greeting"));
 }
+
+#[test]
+fn core_profile_rejects_pipeline_in_message_block() {
+    let source = r#"
+@vars
+  name: "Ada"
+
+@user
+  content: $name |> trim()
+"#;
+
+    let result = validate_with_profile(source, ValidationProfile::Core);
+    assert!(result.is_err());
+    let error = result.unwrap_err();
+    assert!(error.contains("F801"));
+    assert!(error.contains("@user.content"));
+
+    let plain = "@vars\n  name: \"Ada\"\n\n@user\n  content: $name\n";
+    assert!(validate_with_profile(plain, ValidationProfile::Core).is_ok());
+}
@@ -63,6 +63,7 @@ fn render_once(source: &str) -> CanonicalPayload {
                 budget_units: Some(4096),
                 target_provider_id: Some("generic-llm".to_string()),
                 computed_vars: Some(ctx.variables.clone()),
+                host_context: None,
             },
         )
         .expect("render")
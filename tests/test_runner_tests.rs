@@ -1424,3 +1424,75 @@ fn test_runner_assertions_can_read_execution_paths() {
     let result = runner.run_test(&doc, &test_block).unwrap();
     assert!(result.passed, "expected execution path assertions to pass");
 }
+
+#[test]
+fn test_runner_mock_tool_call_sees_the_host_context() {
+    let test_block = TestBlock {
+        name: "host-driven-policy".to_string(),
+        vars: OrderedMap::new(),
+        input: OrderedMap::new(),
+        mocks: vec![MockDefinition {
+            target: "WeatherAPI.get_current".to_string(),
+            return_value: ValueNode::String("ok".to_string()),
+            span: span(),
+        }],
+        assertions: vec![Assertion {
+            kind: AssertionKind::Equals {
+                target: "execution.metadata.host_profile_id".to_string(),
+                expected: ValueNode::String("acme.prod.v1".to_string()),
+            },
+            span: span(),
+        }],
+        body: Vec::new(),
+        span: span(),
+    };
+
+    let rule = ValueNode::Map(OrderedMap::from([
+        ("op".to_string(), ValueNode::String("tool_call".to_string())),
+        (
+            "name".to_string(),
+            ValueNode::String("WeatherAPI.get_current".to_string()),
+        ),
+        (
+            "when".to_string(),
+            ValueNode::Variable("x.acme.admin".to_string()),
+        ),
+    ]));
+    let policy_block = FacetNode::Policy(FacetBlock {
+        name: "policy".to_string(),
+        attributes: OrderedMap::new(),
+        body: vec![BodyNode::KeyValue(KeyValueNode {
+            key: "allow".to_string(),
+            key_kind: Default::default(),
+            value: ValueNode::List(vec![rule]),
+            span: span(),
+        })],
+        span: span(),
+    });
+    let doc = fct_ast::FacetDocument {
+        blocks: vec![weather_interface_read(), policy_block],
+        span: span(),
+    };
+    let host_context = fct_policy::HostContext::new(ValueNode::Map(OrderedMap::from([(
+        "acme".to_string(),
+        ValueNode::Map(OrderedMap::from([(
+            "admin".to_string(),
+            ValueNode::Scalar(ScalarValue::Bool(true)),
+        )])),
+    )])))
+    .unwrap();
+
+    let anonymous = TestRunner::new(1000, 4096)
+        .run_test(&doc, &test_block)
+        .unwrap();
+    assert!(
+        !anonymous.passed,
+        "no host context: the condition is undecidable"
+    );
+
+    let admin = TestRunner::new(1000, 4096)
+        .with_host("acme.prod.v1", Some(host_context))
+        .run_test(&doc, &test_block)
+        .unwrap();
+    assert!(admin.passed, "{:?}", admin.error);
+}